@@ -10,6 +10,7 @@ use libc::{c_int, c_void};
 use native;
 use servo;
 use servo_util::opts;
+use servo_util::time::TextOutput;
 use std::mem;
 use types::{cef_app_t, cef_main_args_t, cef_settings_t};
 
@@ -52,6 +53,7 @@ pub extern "C" fn cef_run_message_loop() {
         tile_size: 512,
         device_pixels_per_px: None,
         time_profiler_period: None,
+        time_profiler_output: TextOutput,
         memory_profiler_period: None,
         layout_threads: 1,
         //layout_threads: cmp::max(rt::default_sched_threads() * 3 / 4, 1),
@@ -60,6 +62,9 @@ pub extern "C" fn cef_run_message_loop() {
         headless: false,
         hard_fail: false,
         bubble_widths_separately: false,
+        debug: opts::DebugOptions::new(),
+        custom_events: vec!(),
+        clicks: vec!(),
     };
     native::start(0, 0 as **u8, proc() {
        servo::run(opts);
@@ -16,7 +16,6 @@
 
 use color::Color;
 use render_context::RenderContext;
-use text::glyph::CharIndex;
 use text::TextRun;
 
 use std::collections::Deque;
@@ -26,7 +25,7 @@ use geom::{Point2D, Rect, SideOffsets2D, Size2D};
 use libc::uintptr_t;
 use servo_net::image::base::Image;
 use servo_util::geometry::Au;
-use servo_util::range::Range;
+use servo_util::range::{CharIndex, Range};
 use std::fmt;
 use std::mem;
 use std::slice::Items;
@@ -13,8 +13,9 @@ use std::str;
 use std::rc::Rc;
 use std::cell::RefCell;
 use servo_util::cache::{Cache, HashCache};
-use servo_util::range::Range;
-use style::computed_values::{text_decoration, font_weight, font_style};
+use servo_util::range::{CharIndex, Range};
+use servo_util::smallvec::SmallVec4;
+use style::computed_values::{text_decoration, font_stretch, font_weight, font_style};
 use sync::Arc;
 
 use color::Color;
@@ -23,10 +24,27 @@ use servo_util::geometry::Au;
 use platform::font_context::FontContextHandle;
 use platform::font::{FontHandle, FontTable};
 use render_context::RenderContext;
-use text::glyph::{CharIndex, GlyphStore, GlyphId};
+use text::glyph::{GlyphStore, GlyphId};
 use text::shaping::ShaperMethods;
 use text::{Shaper, TextRun};
 
+/// Why a font could not be loaded. Each variant carries the identifier or path
+/// that was being loaded.
+#[deriving(Clone, PartialEq, Show)]
+pub enum FontError {
+    /// No installed font matches the identifier.
+    NotFound(String),
+    /// The font data exists but the platform could not load it, or could not
+    /// set it up for the requested style. Carries the platform's error code,
+    /// if it gave one.
+    UnsupportedFormat(String, Option<int>),
+    /// The font file could not be read.
+    IoError(String),
+    /// The platform's font library could not be set up. Carries the
+    /// library's error code, if it gave one.
+    LibraryError(Option<int>),
+}
+
 // FontHandle encapsulates access to the platform's font API,
 // e.g. quartz, FreeType. It provides access to metrics and tables
 // needed by the text shaper as well as access to the underlying font
@@ -88,6 +106,19 @@ pub struct FontMetrics {
     pub max_advance:      Au
 }
 
+/// Measurements of a face beyond those in `FontMetrics`: for `ex` units,
+/// aligning to capitals, and drawing text decorations. Offsets are measured
+/// up from the baseline, to the top of the line for underlines and strikeouts.
+#[deriving(Clone, PartialEq, Show)]
+pub struct FontMetricsExt {
+    pub x_height: Au,
+    pub cap_height: Au,
+    pub underline_offset: Au,
+    pub underline_size: Au,
+    pub strikeout_offset: Au,
+    pub strikeout_size: Au,
+}
+
 // TODO(Issue #179): eventually this will be split into the specified
 // and used font styles.  specified contains uninterpreted CSS font
 // property values, while 'used' is attached to gfx::Font to descript
@@ -99,13 +130,48 @@ pub struct FontStyle {
     pub pt_size: f64,
     pub weight: font_weight::T,
     pub style: font_style::T,
+    pub stretch: font_stretch::T,
     pub families: Vec<String>,
-    // TODO(Issue #198): font-stretch, text-decoration, font-variant, size-adjust
+    /// OpenType features to turn on or off when shaping, as (tag, value)
+    /// pairs. Tags are packed like `true_type_tag('s', 'm', 'c', 'p')`, and
+    /// a value of 0 turns a feature off.
+    pub features: SmallVec4<(u32, u32)>,
+    // TODO(Issue #198): text-decoration, font-variant, size-adjust
 }
 
 pub type SpecifiedFontStyle = FontStyle;
 pub type UsedFontStyle = FontStyle;
 
+/// A style of `pt_size` at the normal weight, slant and stretch, with no
+/// families or features, for tests to start from.
+#[cfg(test)]
+pub fn test_style(pt_size: f64) -> FontStyle {
+    FontStyle {
+        pt_size: pt_size,
+        weight: font_weight::Weight400,
+        style: font_style::normal,
+        stretch: font_stretch::normal,
+        families: vec!(),
+        features: SmallVec4::new(),
+    }
+}
+
+/// The width a `font-stretch` keyword asks for, as a percentage of the
+/// normal width.
+pub fn stretch_percentage(stretch: font_stretch::T) -> f64 {
+    match stretch {
+        font_stretch::ultra_condensed => 50.0,
+        font_stretch::extra_condensed => 62.5,
+        font_stretch::condensed => 75.0,
+        font_stretch::semi_condensed => 87.5,
+        font_stretch::normal => 100.0,
+        font_stretch::semi_expanded => 112.5,
+        font_stretch::expanded => 125.0,
+        font_stretch::extra_expanded => 150.0,
+        font_stretch::ultra_expanded => 200.0,
+    }
+}
+
 // FontDescriptor serializes a specific font and used font style
 // options, such as point size.
 
@@ -410,6 +476,16 @@ impl Font {
         })
     }
 
+    /// The number of bytes this font's caches have allocated on the heap. What the platform holds
+    /// for the face itself isn't counted.
+    pub fn heap_size(&self) -> uint {
+        let mut size = 0;
+        for (text, glyphs) in self.shape_cache.iter() {
+            size += text.capacity() + mem::size_of::<GlyphStore>() + glyphs.heap_size();
+        }
+        size + self.glyph_advance_cache.len() * mem::size_of::<(GlyphId, FractionalPixel)>()
+    }
+
     pub fn get_descriptor(&self) -> FontDescriptor {
         FontDescriptor::new(self.style.clone(), SelectorPlatformIdentifier(self.handle.face_identifier()))
     }
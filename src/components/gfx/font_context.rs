@@ -2,18 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use font::{Font, FontDescriptor, FontGroup, FontHandleMethods, SelectorPlatformIdentifier};
+use font::{Font, FontDescriptor, FontError, FontGroup, FontHandleMethods};
+use font::SelectorPlatformIdentifier;
 use font::{SpecifiedFontStyle, UsedFontStyle};
 use font_list::FontList;
 use platform::font::FontHandle;
 use platform::font_context::FontContextHandle;
+pub use platform::font_context::FontContextHandleFactory;
 
 use azure::azure_hl::BackendType;
 use std::collections::hashmap::HashMap;
+use std::mem;
 use servo_util::cache::{Cache, LRUCache};
 use servo_util::time::TimeProfilerChan;
 
 use std::rc::Rc;
+use sync::Arc;
 use std::cell::RefCell;
 
 /// Information needed to create a font context.
@@ -27,10 +31,21 @@ pub struct FontContextInfo {
 
     /// A channel up to the time profiler.
     pub time_profiler_chan: TimeProfilerChan,
+
+    /// Creates the platform font context handle on the task that uses it.
+    pub handle_factory: FontContextHandleFactory,
+
+    /// Whether to print cache misses (`--debug=gfx.fonts`).
+    pub print_cache_misses: bool,
 }
 
 pub trait FontContextHandleMethods {
-    fn create_font_from_identifier(&self, String, UsedFontStyle) -> Result<FontHandle, ()>;
+    fn create_font_from_identifier(&self, String, UsedFontStyle) -> Result<FontHandle, FontError>;
+
+    /// Creates a font from raw font data, e.g. a downloaded web font. `face_index` selects a
+    /// face within a collection.
+    fn create_font_from_buffer(&self, bytes: Arc<Vec<u8>>, face_index: uint,
+                               style: &UsedFontStyle) -> Result<FontHandle, FontError>;
 }
 
 pub struct FontContext {
@@ -41,11 +56,15 @@ pub struct FontContext {
     pub backend: BackendType,
     pub generic_fonts: HashMap<String,String>,
     pub time_profiler_chan: TimeProfilerChan,
+    print_cache_misses: bool,
 }
 
 impl FontContext {
     pub fn new(info: FontContextInfo) -> FontContext {
-        let handle = FontContextHandle::new();
+        let handle = match info.handle_factory.create_handle() {
+            Ok(handle) => handle,
+            Err(error) => fail!("Unable to create a font context: {}", error),
+        };
         let font_list = if info.needs_font_list {
             Some(FontList::new(&handle, info.time_profiler_chan.clone()))
         } else {
@@ -68,9 +87,17 @@ impl FontContext {
             backend: info.backend,
             generic_fonts: generic_fonts,
             time_profiler_chan: info.time_profiler_chan.clone(),
+            print_cache_misses: info.print_cache_misses,
         }
     }
 
+    /// The number of bytes the fonts in the instance cache have allocated on the heap.
+    pub fn heap_size(&self) -> uint {
+        self.instance_cache.iter().fold(0, |size, &(_, ref font)| {
+            size + mem::size_of::<Font>() + font.borrow().heap_size()
+        })
+    }
+
     pub fn get_resolved_font_for_style(&mut self, style: &SpecifiedFontStyle)
                                        -> Rc<RefCell<FontGroup>> {
         match self.group_cache.find(style) {
@@ -80,6 +107,9 @@ impl FontContext {
             },
             None => {
                 debug!("font group cache miss");
+                if self.print_cache_misses {
+                    println!("font group cache miss: {:?}", style);
+                }
                 let fg = self.create_font_group(style);
                 self.group_cache.insert(style.clone(), fg.clone());
                 fg
@@ -96,6 +126,9 @@ impl FontContext {
             },
             None => {
                 debug!("font cache miss");
+                if self.print_cache_misses {
+                    println!("font cache miss: {:?}", desc);
+                }
                 let result = self.create_font_instance(desc);
                 match result.clone() {
                     Ok(ref font) => {
@@ -209,15 +242,21 @@ impl FontContext {
             &SelectorPlatformIdentifier(ref identifier) => {
                 let result_handle = self.handle.create_font_from_identifier((*identifier).clone(),
                                                                             desc.style.clone());
-                result_handle.and_then(|handle| {
-                    Ok(
-                        Rc::new(
-                            RefCell::new(
-                                Font::new_from_adopted_handle(self,
-                                                              handle,
-                                                              &desc.style,
-                                                              self.backend))))
-                })
+                match result_handle {
+                    Ok(handle) => {
+                        Ok(
+                            Rc::new(
+                                RefCell::new(
+                                    Font::new_from_adopted_handle(self,
+                                                                  handle,
+                                                                  &desc.style,
+                                                                  self.backend))))
+                    }
+                    Err(error) => {
+                        warn!("(create font instance) failed to load font: {}", error);
+                        Err(())
+                    }
+                }
             }
         };
     }
@@ -47,7 +47,7 @@ impl FontList {
         // changed.  Does OSX have a notification for this event?
         //
         // Should font families with entries be invalidated/refreshed too?
-        profile(time::GfxRegenAvailableFontsCategory, self.time_profiler_chan.clone(), || {
+        profile(time::GfxRegenAvailableFontsCategory, None, self.time_profiler_chan.clone(), || {
             self.family_map = self.handle.get_available_families();
         });
     }
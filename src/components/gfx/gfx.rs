@@ -16,6 +16,7 @@ extern crate log;
 extern crate debug;
 extern crate azure;
 extern crate collections;
+extern crate flate;
 extern crate geom;
 extern crate layers;
 extern crate libc;
@@ -64,6 +65,7 @@ pub mod render_task;
 pub mod font;
 pub mod font_context;
 pub mod font_list;
+pub mod webfont;
 
 // Misc.
 mod buffer_map;
@@ -6,6 +6,7 @@ extern crate freetype;
 
 use font::{FontHandleMethods, FontMetrics, FontTableMethods};
 use font::{FontTableTag, FractionalPixel, SpecifiedFontStyle, UsedFontStyle};
+use font::{FontError, IoError, UnsupportedFormat};
 use servo_util::geometry::Au;
 use servo_util::geometry;
 use platform::font_context::FontContextHandle;
@@ -264,28 +265,34 @@ impl<'a> FontHandle {
     }
 
     pub fn new_from_file(fctx: &FontContextHandle, file: &str,
-                         style: &SpecifiedFontStyle) -> Result<FontHandle, ()> {
+                         style: &SpecifiedFontStyle) -> Result<FontHandle, FontError> {
         unsafe {
             let ft_ctx: FT_Library = fctx.ctx.ctx;
-            if ft_ctx.is_null() { return Err(()); }
+            if ft_ctx.is_null() { return Err(UnsupportedFormat(file.to_str(), None)); }
 
             let mut face: FT_Face = ptr::null();
             let face_index = 0 as FT_Long;
-            file.to_c_str().with_ref(|file_str| {
+            let result = file.to_c_str().with_ref(|file_str| {
                 FT_New_Face(ft_ctx, file_str,
-                            face_index, &mut face);
+                            face_index, &mut face)
             });
-            if face.is_null() {
-                return Err(());
+            if !result.succeeded() || face.is_null() {
+                // FT_Err_Cannot_Open_Resource
+                return Err(if result == 0x01 {
+                    IoError(file.to_str())
+                } else {
+                    UnsupportedFormat(file.to_str(), Some(result as int))
+                });
             }
+            let handle = FontHandle {
+                source: FontSourceFile(file.to_str()),
+                face: face,
+                handle: fctx.clone()
+            };
             if FontHandle::set_char_size(face, style.pt_size).is_ok() {
-                Ok(FontHandle {
-                    source: FontSourceFile(file.to_str()),
-                    face: face,
-                    handle: fctx.clone()
-                })
+                Ok(handle)
             } else {
-                Err(())
+                Err(UnsupportedFormat(file.to_str(), None))
             }
         }
     }
@@ -2,16 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use font::UsedFontStyle;
+use font::{FontError, FontHandleMethods, NotFound, UnsupportedFormat, UsedFontStyle};
 use platform::font::FontHandle;
 use font_context::FontContextHandleMethods;
 use platform::font_list::path_from_identifier;
+use webfont::decode_font_data;
 
 use freetype::freetype::{FTErrorMethods, FT_Library};
 use freetype::freetype::{FT_Done_FreeType, FT_Init_FreeType};
 
 use std::ptr;
 use std::rc::Rc;
+use sync::Arc;
 
 #[deriving(Clone)]
 pub struct FreeTypeLibraryHandle {
@@ -43,13 +45,43 @@ impl FontContextHandle {
     }
 }
 
+/// Creates a `FontContextHandle` on whichever task needs one.
+#[deriving(Clone)]
+pub struct FontContextHandleFactory;
+
+impl FontContextHandleFactory {
+    pub fn new() -> FontContextHandleFactory {
+        FontContextHandleFactory
+    }
+
+    pub fn create_handle(&self) -> Result<FontContextHandle, FontError> {
+        Ok(FontContextHandle::new())
+    }
+}
+
 impl FontContextHandleMethods for FontContextHandle {
     fn create_font_from_identifier(&self, name: String, style: UsedFontStyle)
-                                -> Result<FontHandle, ()> {
+                                -> Result<FontHandle, FontError> {
         debug!("Creating font handle for {:s}", name);
-        path_from_identifier(name, &style).and_then(|file_name| {
-            debug!("Opening font face {:s}", file_name);
-            FontHandle::new_from_file(self, file_name.as_slice(), &style)
+        match path_from_identifier(name.clone(), &style) {
+            Ok(file_name) => {
+                debug!("Opening font face {:s}", file_name);
+                FontHandle::new_from_file(self, file_name.as_slice(), &style)
+            }
+            Err(()) => Err(NotFound(name)),
+        }
+    }
+
+    fn create_font_from_buffer(&self, bytes: Arc<Vec<u8>>, face_index: uint,
+                               style: &UsedFontStyle) -> Result<FontHandle, FontError> {
+        // TODO: select faces other than the first within a font collection.
+        let description = format!("font buffer ({:u} bytes, face {:u})", bytes.len(), face_index);
+        if face_index != 0 {
+            return Err(UnsupportedFormat(description, None));
+        }
+        let bytes = try!(decode_font_data(bytes, description.as_slice()));
+        FontHandleMethods::new_from_buffer(self, (*bytes).clone(), style).map_err(|()| {
+            UnsupportedFormat(description.clone(), None)
         })
     }
 }
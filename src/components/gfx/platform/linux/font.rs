@@ -4,29 +4,306 @@
 
 extern crate freetype;
 
-use font::{FontHandleMethods, FontMetrics, FontTableMethods};
-use font::{FontTableTag, FractionalPixel, SpecifiedFontStyle};
+use font::{FontHandleMethods, FontMetrics, FontMetricsExt, FontTableMethods};
+use font::{FontError, FontTableTag, FractionalPixel, SpecifiedFontStyle, stretch_percentage};
+use font::{IoError, UnsupportedFormat};
+use servo_util::cache::{Cache, LRUCache};
+use servo_util::smallvec::{SmallVec, SmallVec4};
 use servo_util::geometry::Au;
 use servo_util::geometry;
-use platform::font_context::FontContextHandle;
+use platform::font_context::{FontContextHandle, FreeTypeLibraryHandle};
 use text::glyph::GlyphId;
-use text::util::{float_to_fixed, fixed_to_float};
-use style::computed_values::font_weight;
+use text::util::{float_to_fixed, fixed_to_float, true_type_tag};
+use style::computed_values::{font_style, font_weight};
 
 use freetype::freetype::{FT_Get_Char_Index, FT_Get_Postscript_Name};
 use freetype::freetype::{FT_Load_Glyph, FT_Set_Char_Size};
 use freetype::freetype::{FT_New_Face, FT_Get_Sfnt_Table};
 use freetype::freetype::{FT_New_Memory_Face, FT_Done_Face};
-use freetype::freetype::{FTErrorMethods, FT_F26Dot6, FT_Face, FT_FaceRec};
+use freetype::freetype::{FTErrorMethods, FT_Error, FT_F26Dot6, FT_Face, FT_FaceRec};
 use freetype::freetype::{FT_GlyphSlot, FT_Library, FT_Long, FT_ULong};
 use freetype::freetype::{FT_STYLE_FLAG_ITALIC, FT_STYLE_FLAG_BOLD};
-use freetype::freetype::{FT_SizeRec, FT_UInt, FT_Size_Metrics};
+use freetype::freetype::{FT_SizeRec, FT_UInt, FT_Size_Metrics, FT_Outline, FT_Pos};
 use freetype::freetype::{ft_sfnt_os2};
 use freetype::tt_os2::TT_OS2;
 
+use libc;
+use libc::{c_char, c_int, c_long, c_void};
+use std::cell::{Cell, RefCell};
+use std::io::{File, IoResult};
+use std::io::fs;
 use std::mem;
+use std::os::{MapFd, MapReadable, MemoryMap};
 use std::ptr;
+use std::raw;
+use std::rc::{Rc, Weak};
 use std::str;
+use sync::Arc;
+
+/// The error FreeType reports when it cannot open a font file at all.
+static FT_ERR_CANNOT_OPEN_RESOURCE: FT_Error = 0x01;
+
+/// The error FreeType reports when it opened a font file but could not read
+/// it.
+static FT_ERR_CANNOT_OPEN_STREAM: FT_Error = 0x51;
+
+// FreeType's font variation API, which the freetype bindings don't cover yet.
+#[allow(non_camel_case_types)]
+type FT_Fixed = c_long;
+
+#[allow(non_camel_case_types)]
+struct FT_Var_Axis {
+    name: *c_char,
+    minimum: FT_Fixed,
+    def: FT_Fixed,
+    maximum: FT_Fixed,
+    tag: FT_ULong,
+    strid: FT_UInt,
+}
+
+#[allow(non_camel_case_types)]
+struct FT_Var_Named_Style {
+    coords: *FT_Fixed,
+    strid: FT_UInt,
+    psid: FT_UInt,
+}
+
+#[allow(non_camel_case_types)]
+struct FT_MM_Var {
+    num_axis: FT_UInt,
+    num_designs: FT_UInt,
+    num_namedstyles: FT_UInt,
+    axis: *FT_Var_Axis,
+    namedstyle: *FT_Var_Named_Style,
+}
+
+extern {
+    fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *FT_MM_Var) -> FT_Error;
+    fn FT_Set_Var_Design_Coordinates(face: FT_Face, num_coords: FT_UInt, coords: *FT_Fixed)
+                                     -> FT_Error;
+}
+
+static FT_FACE_FLAG_MULTIPLE_MASTERS: FT_Long = 1 << 8;
+
+// Bitmap strike selection and raw table access, also missing from the bindings.
+extern {
+    fn FT_Select_Size(face: FT_Face, strike_index: c_int) -> FT_Error;
+    fn FT_Load_Sfnt_Table(face: FT_Face, tag: FT_ULong, offset: FT_Long, buffer: *mut u8,
+                          length: *mut FT_ULong) -> FT_Error;
+}
+
+// Size objects, so that one face can be used at several sizes. The bindings
+// leave FT_FaceRec's `size` untyped, so this is too.
+#[allow(non_camel_case_types)]
+type FT_Size = *c_void;
+
+extern {
+    fn FT_New_Size(face: FT_Face, size: *mut FT_Size) -> FT_Error;
+    fn FT_Activate_Size(size: FT_Size) -> FT_Error;
+    fn FT_Done_Size(size: FT_Size) -> FT_Error;
+}
+
+/// The number of sizes each face keeps set up for reuse.
+static FACE_SIZE_CACHE_SIZE: uint = 8;
+
+static FT_FACE_FLAG_SCALABLE: FT_Long = 1 << 0;
+static FT_FACE_FLAG_FIXED_SIZES: FT_Long = 1 << 1;
+static FT_FACE_FLAG_COLOR: FT_Long = 1 << 14;
+
+/// Makes FreeType hand back color bitmaps as BGRA instead of converting them
+/// to 8-bit alpha.
+static FT_LOAD_COLOR: i32 = 1 << 20;
+
+/// Loads glyphs unhinted, for measuring them as designed.
+static FT_LOAD_NO_HINTING: i32 = 1 << 1;
+
+// Outline transformations for synthetic bold and oblique.
+#[allow(non_camel_case_types)]
+struct FT_Matrix {
+    xx: FT_Fixed,
+    xy: FT_Fixed,
+    yx: FT_Fixed,
+    yy: FT_Fixed,
+}
+
+extern {
+    fn FT_Outline_Embolden(outline: *mut FT_Outline, strength: FT_Pos) -> FT_Error;
+    fn FT_Outline_Transform(outline: *FT_Outline, matrix: *FT_Matrix);
+}
+
+/// The glyph slot format of scalable outlines, 'outl'.
+static FT_GLYPH_FORMAT_OUTLINE: u32 = 0x6F75746C;
+
+/// The horizontal shear of synthetic oblique glyphs, about 12 degrees. This is
+/// the slant FreeType's own FT_GlyphSlot_Oblique uses.
+static SYNTHETIC_OBLIQUE_SHEAR: FT_Fixed = 0x0366A;
+
+/// Synthetic bold thickens strokes by this fraction of the em, as FreeType's
+/// own FT_GlyphSlot_Embolden does.
+static SYNTHETIC_BOLD_EM_FRACTION: f64 = 1.0 / 24.0;
+
+/// Chooses the strike of a bitmap font to draw at `pixel_size`: the smallest
+/// one at least that big, so glyphs are only ever scaled down, or failing that
+/// the largest.
+fn best_strike(strike_sizes: &[f64], pixel_size: f64) -> Option<uint> {
+    let mut best: Option<uint> = None;
+    for (i, &size) in strike_sizes.iter().enumerate() {
+        best = match best {
+            None => Some(i),
+            Some(current) => {
+                let current_size = strike_sizes[current];
+                let better = if current_size < pixel_size {
+                    size > current_size
+                } else {
+                    size >= pixel_size && size < current_size
+                };
+                if better { Some(i) } else { Some(current) }
+            }
+        };
+    }
+    best
+}
+
+/// Chooses the strike of a monochrome or grayscale bitmap font to draw at
+/// `pixel_size`. These can't be scaled at all, so the closest one is best.
+fn nearest_strike(strike_sizes: &[f64], pixel_size: f64) -> Option<uint> {
+    let mut best: Option<uint> = None;
+    for (i, &size) in strike_sizes.iter().enumerate() {
+        best = match best {
+            Some(current) if (strike_sizes[current] - pixel_size).abs() <=
+                             (size - pixel_size).abs() => Some(current),
+            _ => Some(i),
+        };
+    }
+    best
+}
+
+/// A strike within this fraction of the requested size is laid out at its
+/// own size. Further off, its metrics are scaled to the requested size, so
+/// that text takes up the space the page asked for.
+static STRIKE_SIZE_TOLERANCE: f64 = 0.2;
+
+/// How much to scale the metrics of a strike of `strike_size` pixels that was
+/// chosen for `pixel_size`.
+fn strike_metrics_scale(strike_size: f64, pixel_size: f64) -> f64 {
+    if (strike_size - pixel_size).abs() <= pixel_size * STRIKE_SIZE_TOLERANCE {
+        1.0
+    } else {
+        pixel_size / strike_size
+    }
+}
+
+/// Converts a length from a font table to pixels at `ppem` pixels per em.
+/// Zero means the table doesn't say, and bitmap fonts may have no font units
+/// at all.
+fn font_units_to_pixels(units: i16, units_per_em: f64, ppem: f64) -> Option<f64> {
+    if units_per_em == 0.0 || units == 0 {
+        None
+    } else {
+        Some(units as f64 * ppem / units_per_em)
+    }
+}
+
+/// The OpenType tag of the weight axis, 'wght'.
+static WEIGHT_AXIS_TAG: FT_ULong = 0x77676874;
+
+/// The OpenType tag of the width axis, 'wdth'.
+static WIDTH_AXIS_TAG: FT_ULong = 0x77647468;
+
+fn fixed_16_16_to_float(f: FT_Fixed) -> f64 {
+    f as f64 / 65536.0
+}
+
+fn float_to_fixed_16_16(f: f64) -> FT_Fixed {
+    (f * 65536.0).round() as FT_Fixed
+}
+
+/// One axis of a variable font, in design units.
+struct VariationAxis {
+    tag: FT_ULong,
+    minimum: f64,
+    default: f64,
+    maximum: f64,
+}
+
+/// Chooses design coordinates that give `weight` and `width`, a percentage
+/// of the normal width. A named instance with exactly that weight and width
+/// wins, since the designer tuned the other axes for it. Otherwise every axis
+/// keeps its default, apart from the weight and width axes, which are clamped
+/// to the range the font supports.
+fn coordinates_for_style(axes: &[VariationAxis], named_instances: &[Vec<f64>], weight: f64,
+                         width: f64) -> Vec<f64> {
+    let weight_axis = axes.iter().position(|axis| axis.tag == WEIGHT_AXIS_TAG);
+    let width_axis = axes.iter().position(|axis| axis.tag == WIDTH_AXIS_TAG);
+    if weight_axis.is_some() || width_axis.is_some() {
+        for coords in named_instances.iter() {
+            if coords.len() != axes.len() {
+                continue;
+            }
+            let matches = |axis: Option<uint>, value: f64| {
+                axis.map_or(true, |index| *coords.get(index) == value)
+            };
+            if matches(weight_axis, weight) && matches(width_axis, width) {
+                return coords.clone();
+            }
+        }
+    }
+
+    axes.iter().enumerate().map(|(i, axis)| {
+        if Some(i) == weight_axis {
+            weight.max(axis.minimum).min(axis.maximum)
+        } else if Some(i) == width_axis {
+            width.max(axis.minimum).min(axis.maximum)
+        } else {
+            axis.default
+        }
+    }).collect()
+}
+
+/// The numeric value of a CSS font-weight.
+fn css_weight(weight: font_weight::T) -> f64 {
+    match weight {
+        font_weight::Weight100 => 100.0,
+        font_weight::Weight200 => 200.0,
+        font_weight::Weight300 => 300.0,
+        font_weight::Weight400 => 400.0,
+        font_weight::Weight500 => 500.0,
+        font_weight::Weight600 => 600.0,
+        font_weight::Weight700 => 700.0,
+        font_weight::Weight800 => 800.0,
+        font_weight::Weight900 => 900.0,
+    }
+}
+
+/// The weight a face was designed with, from its OS/2 table.
+fn face_weight(face: FT_Face) -> font_weight::T {
+    let default_weight = font_weight::Weight400;
+    if unsafe { (*face).style_flags & FT_STYLE_FLAG_BOLD == 0 } {
+        default_weight
+    } else {
+        unsafe {
+            let os2 = FT_Get_Sfnt_Table(face, ft_sfnt_os2) as *TT_OS2;
+            let valid = os2.is_not_null() && (*os2).version != 0xffff;
+            if valid {
+                let weight =(*os2).usWeightClass;
+                match weight {
+                    1 | 100..199 => font_weight::Weight100,
+                    2 | 200..299 => font_weight::Weight200,
+                    3 | 300..399 => font_weight::Weight300,
+                    4 | 400..499 => font_weight::Weight400,
+                    5 | 500..599 => font_weight::Weight500,
+                    6 | 600..699 => font_weight::Weight600,
+                    7 | 700..799 => font_weight::Weight700,
+                    8 | 800..899 => font_weight::Weight800,
+                    9 | 900..999 => font_weight::Weight900,
+                    _ => default_weight
+                }
+            } else {
+                default_weight
+            }
+        }
+    }
+}
 
 fn float_to_fixed_ft(f: f64) -> i32 {
     float_to_fixed(6, f)
@@ -47,23 +324,91 @@ impl FontTableMethods for FontTable {
 }
 
 pub enum FontSource {
-    FontSourceMem(Vec<u8>),
-    FontSourceFile(String)
+    FontSourceMem(Arc<Vec<u8>>),
+    FontSourceFile(String),
+    FontSourceFileData(Rc<FontFileData>),
 }
 
-pub struct FontHandle {
-    // The font binary. This must stay valid for the lifetime of the font,
-    // if the font is created using FT_Memory_Face.
-    pub source: FontSource,
+/// The contents of a font file. Mapping the file in means only the tables
+/// FreeType actually reads take up memory, which matters for large CJK fonts.
+pub enum FontFileData {
+    FontFileMapped(MemoryMap, uint),
+    /// The fallback for files that can't be mapped.
+    FontFileRead(Vec<u8>),
+}
+
+impl FontFileData {
+    pub fn open(path: &Path) -> IoResult<FontFileData> {
+        let size = try!(fs::stat(path)).size as uint;
+        match FontFileData::map(path, size) {
+            Some(map) => Ok(FontFileMapped(map, size)),
+            None => {
+                debug!("mapping {} failed, reading it instead", path.display());
+                Ok(FontFileRead(try!(File::open(path).read_to_end())))
+            }
+        }
+    }
+
+    fn map(path: &Path, size: uint) -> Option<MemoryMap> {
+        if size == 0 {
+            return None;
+        }
+        unsafe {
+            let fd = path.with_c_str(|path| libc::open(path, libc::O_RDONLY, 0));
+            if fd < 0 {
+                return None;
+            }
+            let map = MemoryMap::new(size, &[MapReadable, MapFd(fd)]).ok();
+            // The mapping stays valid without the descriptor.
+            libc::close(fd);
+            map
+        }
+    }
+
+    pub fn as_slice<'a>(&'a self) -> &'a [u8] {
+        match *self {
+            FontFileMapped(ref map, size) => unsafe {
+                mem::transmute(raw::Slice { data: map.data() as *u8, len: size })
+            },
+            FontFileRead(ref bytes) => bytes.as_slice(),
+        }
+    }
+}
+
+/// A FreeType face and the data backing it. Every `FontHandle` created for the
+/// same face shares one of these, and the face is released along with the last.
+pub struct FontFace {
     pub face: FT_Face,
-    pub handle: FontContextHandle
+    // The font binary. This must stay valid for the lifetime of the face,
+    // if the face is created using FT_Memory_Face. Fields are dropped after
+    // `drop` has run, so a mapped file is only unmapped after FT_Done_Face.
+    pub source: FontSource,
+    // The library must outlive every face created from it.
+    library: Rc<FreeTypeLibraryHandle>,
+    /// Whether the glyphs are color bitmaps, as in emoji fonts.
+    pub color_bitmaps: bool,
+    /// Whether glyphs are emboldened because the style asked for a bold face
+    /// and this one isn't.
+    pub synthetic_bold: bool,
+    /// Whether glyphs are slanted because the style asked for an italic or
+    /// oblique face and this one is upright.
+    pub synthetic_oblique: bool,
+    /// The OpenType features text in this face is shaped with.
+    pub features: SmallVec4<(u32, u32)>,
+    /// The sizes the face has recently been used at, keyed by size in points.
+    sizes: RefCell<LRUCache<f64, Rc<FaceSize>>>,
+    /// How many times a size has been set up for this face. Lets tests
+    /// observe cache hits.
+    pub sizes_set_up: Cell<uint>,
 }
 
 #[unsafe_destructor]
-impl Drop for FontHandle {
+impl Drop for FontFace {
     fn drop(&mut self) {
         assert!(self.face.is_not_null());
+        assert!(self.library.ctx.is_not_null());
         unsafe {
+            let _guard = self.library.lock();
             if !FT_Done_Face(self.face).succeeded() {
                 fail!("FT_Done_Face failed");
             }
@@ -71,49 +416,251 @@ impl Drop for FontHandle {
     }
 }
 
+/// A face set up for one size, in an FT_Size of its own so that switching
+/// between sizes doesn't redo the setup, and what was measured at that size.
+pub struct FaceSize {
+    size: FT_Size,
+    // FT_Done_Face releases every size of the face, so this one only needs
+    // releasing while the face is alive.
+    face: Weak<FontFace>,
+    /// The size in pixels of the selected strike, for faces drawn from
+    /// bitmap strikes rather than scaled outlines.
+    pub strike_size: Option<f64>,
+    /// What the strike's metrics are multiplied by, when it is far from the
+    /// requested size.
+    pub metrics_scale: f64,
+    /// The x-height, cap height and decoration metrics at this size.
+    pub metrics_ext: FontMetricsExt,
+}
+
+#[unsafe_destructor]
+impl Drop for FaceSize {
+    fn drop(&mut self) {
+        if self.face.upgrade().is_some() {
+            unsafe {
+                FT_Done_Size(self.size);
+            }
+        }
+    }
+}
+
+impl FontFace {
+    fn new(fctx: &FontContextHandle, face: FT_Face, source: FontSource) -> FontFace {
+        FontFace {
+            face: face,
+            source: source,
+            library: fctx.ctx.clone(),
+            color_bitmaps: FontFace::has_color_bitmaps(face),
+            synthetic_bold: false,
+            synthetic_oblique: false,
+            features: SmallVec4::new(),
+            sizes: RefCell::new(LRUCache::new(FACE_SIZE_CACHE_SIZE)),
+            sizes_set_up: Cell::new(0),
+        }
+    }
+
+    /// What to call the face in errors.
+    fn description(&self) -> String {
+        match self.source {
+            FontSourceFile(ref path) => path.clone(),
+            FontSourceMem(_) | FontSourceFileData(_) => unsafe {
+                // Fonts don't have to name their family.
+                let family_name = (*self.face).family_name;
+                if family_name.is_null() {
+                    "font data without a family name".to_string()
+                } else {
+                    str::raw::from_c_str(family_name)
+                }
+            },
+        }
+    }
+
+    /// The face set up for `pt_size`, reusing a size it was set up for
+    /// recently.
+    fn size(shared_face: &Rc<FontFace>, pt_size: f64) -> Result<Rc<FaceSize>, FT_Error> {
+        match shared_face.sizes.borrow_mut().find(&pt_size) {
+            Some(face_size) => return Ok(face_size),
+            None => {}
+        }
+
+        let face = shared_face.face;
+        unsafe {
+            let mut size: FT_Size = ptr::null();
+            let result = FT_New_Size(face, &mut size);
+            if !result.succeeded() {
+                return Err(result);
+            }
+            // Set up the new size without disturbing whoever uses the face's
+            // active one.
+            let previous = (*face).size;
+            FT_Activate_Size(size);
+            shared_face.sizes_set_up.set(shared_face.sizes_set_up.get() + 1);
+            let sized = if shared_face.color_bitmaps || FontFace::is_bitmap_only(face) {
+                FontHandle::select_strike(face, pt_size, shared_face.color_bitmaps)
+            } else {
+                FontHandle::set_char_size(face, pt_size).map(|()| None)
+            };
+            let result = sized.map(|strike_size| {
+                let metrics_scale = match strike_size {
+                    Some(strike_size) => strike_metrics_scale(strike_size, pt_size),
+                    None => 1.0,
+                };
+                Rc::new(FaceSize {
+                    size: size,
+                    face: shared_face.downgrade(),
+                    strike_size: strike_size,
+                    metrics_scale: metrics_scale,
+                    metrics_ext: FontFace::measure_metrics_ext(face, metrics_scale),
+                })
+            });
+            FT_Activate_Size(previous);
+
+            match result {
+                Ok(ref face_size) => {
+                    shared_face.sizes.borrow_mut().insert(pt_size, face_size.clone());
+                }
+                Err(_) => {
+                    FT_Done_Size(size);
+                }
+            }
+            result
+        }
+    }
+
+    /// Measures `face` at its current size. Values come from the OS/2 and
+    /// post tables where the font has them. Otherwise the x-height and cap
+    /// height are the heights of 'x' and 'H', and text decorations get
+    /// proportions that suit most fonts.
+    fn measure_metrics_ext(face: FT_Face, metrics_scale: f64) -> FontMetricsExt {
+        unsafe {
+            let size: &FT_SizeRec = mem::transmute(&(*(*face).size));
+            let ppem = size.metrics.x_ppem as f64;
+            let units_per_em = (*face).units_per_EM as f64;
+            let to_pixels = |units: i16| font_units_to_pixels(units, units_per_em, ppem);
+
+            let os2 = FT_Get_Sfnt_Table(face, ft_sfnt_os2) as *TT_OS2;
+            let os2 = if os2.is_not_null() && (*os2).version != 0xffff {
+                Some(&*os2)
+            } else {
+                None
+            };
+
+            let (table_x_height, table_cap_height) = match os2 {
+                // These were only added in version 2.
+                Some(os2) if os2.version >= 2 => {
+                    (to_pixels(os2.sxHeight), to_pixels(os2.sCapHeight))
+                }
+                _ => (None, None),
+            };
+            let x_height = table_x_height.or_else(|| FontFace::glyph_top(face, 'x'))
+                                         .unwrap_or(ppem / 2.0);
+            let ascender = fixed_to_float_ft(size.metrics.ascender as i32);
+            let cap_height = table_cap_height.or_else(|| FontFace::glyph_top(face, 'H'))
+                                             .unwrap_or(ascender);
+
+            let (underline_offset, underline_size) = match to_pixels((*face).underline_thickness) {
+                Some(thickness) => {
+                    (to_pixels((*face).underline_position).unwrap_or(0.0), thickness)
+                }
+                None => {
+                    let descender = fixed_to_float_ft(size.metrics.descender as i32);
+                    (descender / 2.0, ppem / 14.0)
+                }
+            };
+
+            let table_strikeout = match os2 {
+                Some(os2) => (to_pixels(os2.yStrikeoutPosition), to_pixels(os2.yStrikeoutSize)),
+                None => (None, None),
+            };
+            let (strikeout_offset, strikeout_size) = match table_strikeout {
+                (Some(offset), Some(size)) => (offset, size),
+                // Strike through the middle of the lowercase letters.
+                _ => ((x_height + underline_size) / 2.0, underline_size),
+            };
+
+            let to_au = |pixels: f64| geometry::from_frac_px(pixels * metrics_scale);
+            FontMetricsExt {
+                x_height: to_au(x_height),
+                cap_height: to_au(cap_height),
+                underline_offset: to_au(underline_offset),
+                underline_size: to_au(underline_size),
+                strikeout_offset: to_au(strikeout_offset),
+                strikeout_size: to_au(strikeout_size),
+            }
+        }
+    }
+
+    /// How far the glyph for `codepoint` reaches above the baseline, in
+    /// pixels at the face's current size.
+    fn glyph_top(face: FT_Face, codepoint: char) -> Option<f64> {
+        unsafe {
+            let glyph = FT_Get_Char_Index(face, codepoint as FT_ULong);
+            if glyph == 0 || !FT_Load_Glyph(face, glyph, FT_LOAD_NO_HINTING).succeeded() {
+                return None;
+            }
+            let slot: FT_GlyphSlot = mem::transmute((*face).glyph);
+            Some(fixed_to_float_ft((*slot).metrics.horiBearingY as i32))
+        }
+    }
+
+    /// Whether `face` only has bitmap strikes, like PCF and BDF fonts, and
+    /// has to be set up with FT_Select_Size instead of FT_Set_Char_Size.
+    fn is_bitmap_only(face: FT_Face) -> bool {
+        unsafe { (*face).face_flags & FT_FACE_FLAG_SCALABLE == 0 }
+    }
+
+    /// Whether `face` draws its glyphs from color bitmap strikes, stored in
+    /// CBDT/CBLC or sbix tables, rather than from outlines.
+    fn has_color_bitmaps(face: FT_Face) -> bool {
+        unsafe {
+            let flags = (*face).face_flags;
+            if flags & FT_FACE_FLAG_COLOR != 0 && flags & FT_FACE_FLAG_FIXED_SIZES != 0 {
+                return true;
+            }
+            // Older FreeType releases never set FT_FACE_FLAG_COLOR.
+            let tables = [true_type_tag('C', 'B', 'D', 'T'), true_type_tag('s', 'b', 'i', 'x')];
+            tables.iter().any(|&tag| {
+                let mut length: FT_ULong = 0;
+                let result = FT_Load_Sfnt_Table(face, tag as FT_ULong, 0, ptr::mut_null(),
+                                                &mut length);
+                result.succeeded() && length > 0
+            })
+        }
+    }
+}
+
+#[deriving(Clone)]
+pub struct FontHandle {
+    pub face: FT_Face,
+    pub shared_face: Rc<FontFace>,
+    pub handle: FontContextHandle,
+    /// The FT_LOAD_* flags glyphs are loaded with, from the context's
+    /// rasterization options.
+    pub load_flags: i32,
+    /// Whether glyphs load as BGRA bitmaps rather than 8-bit alpha coverage.
+    /// These already carry their colors, so they must not be tinted with the
+    /// text color when composited.
+    pub color_bitmaps: bool,
+    /// Whether `load_glyph` emboldens glyphs and widens their advances.
+    pub synthetic_bold: bool,
+    /// Whether `load_glyph` slants glyphs.
+    pub synthetic_oblique: bool,
+    /// The size in pixels of the strike glyphs come from, for bitmap faces.
+    /// Metrics are those of the strike, not of the requested size.
+    pub strike_size: Option<f64>,
+    /// Scales the strike's metrics to the requested size.
+    pub metrics_scale: f64,
+    /// The size this handle uses the face at. Handles opened without a
+    /// style use whatever size the face has.
+    pub face_size: Option<Rc<FaceSize>>,
+}
+
 impl FontHandleMethods for FontHandle {
     fn new_from_buffer(fctx: &FontContextHandle,
                        buf: Vec<u8>,
                        style: &SpecifiedFontStyle)
                         -> Result<FontHandle, ()> {
-        let ft_ctx: FT_Library = fctx.ctx.ctx;
-        if ft_ctx.is_null() { return Err(()); }
-
-        let face_result = create_face_from_buffer(ft_ctx, buf.as_ptr(), buf.len(), style.pt_size);
-
-        // TODO: this could be more simply written as result::chain
-        // and moving buf into the struct ctor, but cant' move out of
-        // captured binding.
-        return match face_result {
-            Ok(face) => {
-              let handle = FontHandle {
-                  face: face,
-                  source: FontSourceMem(buf),
-                  handle: fctx.clone()
-              };
-              Ok(handle)
-            }
-            Err(()) => Err(())
-        };
-
-         fn create_face_from_buffer(lib: FT_Library, cbuf: *u8, cbuflen: uint, pt_size: f64)
-                                    -> Result<FT_Face, ()> {
-             unsafe {
-                 let mut face: FT_Face = ptr::null();
-                 let face_index = 0 as FT_Long;
-                 let result = FT_New_Memory_Face(lib, cbuf, cbuflen as FT_Long,
-                                                 face_index, &mut face);
-
-                 if !result.succeeded() || face.is_null() {
-                     return Err(());
-                 }
-                 if FontHandle::set_char_size(face, pt_size).is_ok() {
-                     Ok(face)
-                 } else {
-                     Err(())
-                 }
-             }
-         }
+        FontHandle::new_from_shared_buffer(fctx, Arc::new(buf), 0, style).map_err(|_| ())
     }
 
     // an identifier usable by FontContextHandle to recreate this FontHandle.
@@ -132,32 +679,7 @@ impl FontHandleMethods for FontHandle {
         unsafe { (*self.face).style_flags & FT_STYLE_FLAG_ITALIC != 0 }
     }
     fn boldness(&self) -> font_weight::T {
-        let default_weight = font_weight::Weight400;
-        if unsafe { (*self.face).style_flags & FT_STYLE_FLAG_BOLD == 0 } {
-            default_weight
-        } else {
-            unsafe {
-                let os2 = FT_Get_Sfnt_Table(self.face, ft_sfnt_os2) as *TT_OS2;
-                let valid = os2.is_not_null() && (*os2).version != 0xffff;
-                if valid {
-                    let weight =(*os2).usWeightClass;
-                    match weight {
-                        1 | 100..199 => font_weight::Weight100,
-                        2 | 200..299 => font_weight::Weight200,
-                        3 | 300..399 => font_weight::Weight300,
-                        4 | 400..499 => font_weight::Weight400,
-                        5 | 500..599 => font_weight::Weight500,
-                        6 | 600..699 => font_weight::Weight600,
-                        7 | 700..799 => font_weight::Weight700,
-                        8 | 800..899 => font_weight::Weight800,
-                        9 | 900..999 => font_weight::Weight900,
-                        _ => default_weight
-                    }
-                } else {
-                    default_weight
-                }
-            }
-        }
+        face_weight(self.face)
     }
 
     fn glyph_index(&self,
@@ -178,7 +700,7 @@ impl FontHandleMethods for FontHandle {
                            glyph: GlyphId) -> Option<FractionalPixel> {
         assert!(self.face.is_not_null());
         unsafe {
-            let res =  FT_Load_Glyph(self.face, glyph as FT_UInt, 0);
+            let res = self.load_glyph(glyph);
             if res.succeeded() {
                 let void_glyph = (*self.face).glyph;
                 let slot: FT_GlyphSlot = mem::transmute(void_glyph);
@@ -187,7 +709,8 @@ impl FontHandleMethods for FontHandle {
                 let advance = (*slot).metrics.horiAdvance;
                 debug!("h_advance for {} is {}", glyph, advance);
                 let advance = advance as i32;
-                return Some(fixed_to_float_ft(advance) as FractionalPixel);
+                let advance = fixed_to_float_ft(advance) * self.metrics_scale;
+                return Some(advance as FractionalPixel);
             } else {
                 debug!("Unable to load glyph {}. reason: {}", glyph, res);
                 return None;
@@ -196,15 +719,40 @@ impl FontHandleMethods for FontHandle {
     }
 
     fn get_metrics(&self) -> FontMetrics {
+        self.with_size(|| self.metrics_at_active_size())
+    }
+
+    fn get_table_for_tag(&self, _: FontTableTag) -> Option<FontTable> {
+        None
+    }
+}
+
+impl<'a> FontHandle {
+    fn metrics_at_active_size(&self) -> FontMetrics {
         /* TODO(Issue #76): complete me */
         let face = self.get_face_rec();
 
-        let underline_size = self.font_units_to_au(face.underline_thickness as f64);
-        let underline_offset = self.font_units_to_au(face.underline_position as f64);
-        let em_size = self.font_units_to_au(face.units_per_EM as f64);
-        let ascent = self.font_units_to_au(face.ascender as f64);
-        let descent = self.font_units_to_au(face.descender as f64);
-        let max_advance = self.font_units_to_au(face.max_advance_width as f64);
+        let metrics_ext = self.metrics_ext();
+        let (em_size, ascent, descent, max_advance, height) = match self.strike_size {
+            // Bitmap fonts may have no font units at all, just the strike's
+            // metrics in pixels.
+            Some(strike_size) => {
+                let size: &FT_SizeRec = unsafe { mem::transmute(&(*face.size)) };
+                let metrics = &size.metrics;
+                (self.strike_pixels_to_au(strike_size),
+                 self.strike_pixels_to_au(fixed_to_float_ft(metrics.ascender as i32)),
+                 self.strike_pixels_to_au(fixed_to_float_ft(metrics.descender as i32)),
+                 self.strike_pixels_to_au(fixed_to_float_ft(metrics.max_advance as i32)),
+                 self.strike_pixels_to_au(fixed_to_float_ft(metrics.height as i32)))
+            }
+            None => {
+                (self.font_units_to_au(face.units_per_EM as f64),
+                 self.font_units_to_au(face.ascender as f64),
+                 self.font_units_to_au(face.descender as f64),
+                 self.font_units_to_au(face.max_advance_width as f64),
+                 self.font_units_to_au(face.height as f64))
+            }
+        };
 
         // 'leading' is supposed to be the vertical distance between two baselines,
         // reflected by the height attibute in freetype.  On OS X (w/ CTFont),
@@ -212,29 +760,15 @@ impl FontHandleMethods for FontHandle {
         // the top of the next line's ascent or: (line_height - ascent - descent),
         // see http://stackoverflow.com/a/5635981 for CTFont implementation.
         // Convert using a formular similar to what CTFont returns for consistency.
-        let height = self.font_units_to_au(face.height as f64);
         let leading = height - (ascent + descent);
 
-        let mut strikeout_size = geometry::from_pt(0.0);
-        let mut strikeout_offset = geometry::from_pt(0.0);
-        let mut x_height = geometry::from_pt(0.0);
-        unsafe {
-            let os2 = FT_Get_Sfnt_Table(face, ft_sfnt_os2) as *TT_OS2;
-            let valid = os2.is_not_null() && (*os2).version != 0xffff;
-            if valid {
-               strikeout_size = self.font_units_to_au((*os2).yStrikeoutSize as f64);
-               strikeout_offset = self.font_units_to_au((*os2).yStrikeoutPosition as f64);
-               x_height = self.font_units_to_au((*os2).sxHeight as f64);
-            }
-        }
-
         let metrics = FontMetrics {
-            underline_size:   underline_size,
-            underline_offset: underline_offset,
-            strikeout_size:   strikeout_size,
-            strikeout_offset: strikeout_offset,
+            underline_size:   metrics_ext.underline_size,
+            underline_offset: metrics_ext.underline_offset,
+            strikeout_size:   metrics_ext.strikeout_size,
+            strikeout_offset: metrics_ext.strikeout_offset,
             leading:          leading,
-            x_height:         x_height,
+            x_height:         metrics_ext.x_height,
             em_size:          em_size,
             ascent:           ascent,
             descent:          -descent, // linux font's seem to use the opposite sign from mac
@@ -245,13 +779,65 @@ impl FontHandleMethods for FontHandle {
         return metrics;
     }
 
-    fn get_table_for_tag(&self, _: FontTableTag) -> Option<FontTable> {
-        None
+    /// Runs `f` with this handle's size active, then makes whatever was
+    /// active before active again. Others using the face, like the scaled
+    /// font we draw with, size the face for themselves and don't expect us to
+    /// change it.
+    fn with_size<T>(&self, f: || -> T) -> T {
+        let size = match self.face_size {
+            Some(ref face_size) => face_size.size,
+            None => return f(),
+        };
+        unsafe {
+            let previous = (*self.face).size;
+            if previous == size {
+                return f();
+            }
+            FT_Activate_Size(size);
+            let result = f();
+            FT_Activate_Size(previous);
+            result
+        }
     }
-}
 
-impl<'a> FontHandle {
-    fn set_char_size(face: FT_Face, pt_size: f64) -> Result<(), ()>{
+    /// Creates another handle to an already loaded face, at `pt_size`. The
+    /// face is only set up for the size if it hasn't been recently.
+    pub fn from_shared_face(fctx: &FontContextHandle, shared_face: Rc<FontFace>, pt_size: f64)
+                            -> Result<FontHandle, FontError> {
+        match FontFace::size(&shared_face, pt_size) {
+            Ok(face_size) => Ok(FontHandle::with_face_size(fctx, shared_face, Some(face_size))),
+            Err(result) => Err(UnsupportedFormat(shared_face.description(), Some(result as int))),
+        }
+    }
+
+    fn with_face_size(fctx: &FontContextHandle, shared_face: Rc<FontFace>,
+                      face_size: Option<Rc<FaceSize>>) -> FontHandle {
+        let color_bitmaps = shared_face.color_bitmaps;
+        let (synthetic_bold, synthetic_oblique) = (shared_face.synthetic_bold,
+                                                   shared_face.synthetic_oblique);
+        let (strike_size, metrics_scale) = match face_size {
+            Some(ref face_size) => (face_size.strike_size, face_size.metrics_scale),
+            None => (None, 1.0),
+        };
+        let mut load_flags = fctx.rasterization_options().load_flags();
+        if color_bitmaps {
+            load_flags |= FT_LOAD_COLOR;
+        }
+        FontHandle {
+            face: shared_face.face,
+            shared_face: shared_face,
+            handle: fctx.clone(),
+            load_flags: load_flags,
+            color_bitmaps: color_bitmaps,
+            synthetic_bold: synthetic_bold,
+            synthetic_oblique: synthetic_oblique,
+            strike_size: strike_size,
+            metrics_scale: metrics_scale,
+            face_size: face_size,
+        }
+    }
+
+    fn set_char_size(face: FT_Face, pt_size: f64) -> Result<(), FT_Error>{
         let char_width = float_to_fixed_ft(pt_size) as FT_F26Dot6;
         let char_height = float_to_fixed_ft(pt_size) as FT_F26Dot6;
         let h_dpi = 72;
@@ -259,58 +845,252 @@ impl<'a> FontHandle {
 
         unsafe {
             let result = FT_Set_Char_Size(face, char_width, char_height, h_dpi, v_dpi);
-            if result.succeeded() { Ok(()) } else { Err(()) }
+            if result.succeeded() { Ok(()) } else { Err(result) }
         }
     }
 
-    pub fn new_from_file(fctx: &FontContextHandle, file: &str,
-                         style: &SpecifiedFontStyle) -> Result<FontHandle, ()> {
+    /// Bitmap fonts can't be scaled by FreeType, so pick the strike closest to
+    /// `pt_size` instead, and return its size in pixels. Color bitmaps are
+    /// scaled down when drawn, so for those the closest strike at least as
+    /// big wins. Like `set_char_size`, this assumes 72 dpi, making points and
+    /// pixels the same.
+    fn select_strike(face: FT_Face, pt_size: f64, color_bitmaps: bool)
+                     -> Result<Option<f64>, FT_Error> {
+        unsafe {
+            let strike_sizes: Vec<f64> = range(0, (*face).num_fixed_sizes as int).map(|i| {
+                fixed_to_float_ft((*(*face).available_sizes.offset(i)).y_ppem as i32)
+            }).collect();
+            let strike = if color_bitmaps {
+                best_strike(strike_sizes.as_slice(), pt_size)
+            } else {
+                nearest_strike(strike_sizes.as_slice(), pt_size)
+            };
+            match strike {
+                Some(strike) => {
+                    let strike_size = *strike_sizes.get(strike);
+                    debug!("selecting the {}px strike for {}px", strike_size, pt_size);
+                    let result = FT_Select_Size(face, strike as c_int);
+                    if result.succeeded() { Ok(Some(strike_size)) } else { Err(result) }
+                }
+                None => FontHandle::set_char_size(face, pt_size).map(|()| None),
+            }
+        }
+    }
+
+    /// Selects the instance of a variable font that matches `style`. Faces
+    /// that aren't variable are left alone, and so is a face whose variations
+    /// FreeType can't read; its default instance is still usable. Returns
+    /// whether the face has a weight axis that was set from `style`.
+    fn apply_variations(face: FT_Face, style: &SpecifiedFontStyle) -> bool {
+        unsafe {
+            if (*face).face_flags & FT_FACE_FLAG_MULTIPLE_MASTERS == 0 {
+                return false;
+            }
+
+            let mut master: *FT_MM_Var = ptr::null();
+            if !FT_Get_MM_Var(face, &mut master).succeeded() || master.is_null() {
+                debug!("couldn't read the variation axes of a variable font");
+                return false;
+            }
+
+            let num_axes = (*master).num_axis as uint;
+            let axes: Vec<VariationAxis> = range(0, num_axes).map(|i| {
+                let axis = &*(*master).axis.offset(i as int);
+                VariationAxis {
+                    tag: axis.tag,
+                    minimum: fixed_16_16_to_float(axis.minimum),
+                    default: fixed_16_16_to_float(axis.def),
+                    maximum: fixed_16_16_to_float(axis.maximum),
+                }
+            }).collect();
+            let named_instances: Vec<Vec<f64>> =
+                range(0, (*master).num_namedstyles as int).map(|i| {
+                    let instance = &*(*master).namedstyle.offset(i);
+                    range(0, num_axes as int).map(|j| {
+                        fixed_16_16_to_float(*instance.coords.offset(j))
+                    }).collect()
+                }).collect();
+
+            // FreeType allocated this through the library's FT_Memory.
+            let memory = (*face).memory;
+            ((*memory).free)(memory, master as *c_void);
+
+            let coords = coordinates_for_style(axes.as_slice(), named_instances.as_slice(),
+                                               css_weight(style.weight),
+                                               stretch_percentage(style.stretch));
+            let fixed_coords: Vec<FT_Fixed> =
+                coords.iter().map(|&coord| float_to_fixed_16_16(coord)).collect();
+            let result = FT_Set_Var_Design_Coordinates(face, fixed_coords.len() as FT_UInt,
+                                                       fixed_coords.as_ptr());
+            if !result.succeeded() {
+                debug!("setting variation coordinates {} failed: {}", coords, result);
+                return false;
+            }
+            axes.iter().any(|axis| axis.tag == WEIGHT_AXIS_TAG)
+        }
+    }
+
+    /// Loads `glyph` into the face's glyph slot, applying any synthetic bold
+    /// or oblique. Emboldening widens the glyph, so its advance grows to
+    /// match.
+    pub fn load_glyph(&self, glyph: GlyphId) -> FT_Error {
+        self.with_size(|| self.load_glyph_at_active_size(glyph))
+    }
+
+    fn load_glyph_at_active_size(&self, glyph: GlyphId) -> FT_Error {
+        unsafe {
+            let result = FT_Load_Glyph(self.face, glyph as FT_UInt, self.load_flags);
+            if !result.succeeded() || !(self.synthetic_bold || self.synthetic_oblique) {
+                return result;
+            }
+
+            let slot: FT_GlyphSlot = mem::transmute((*self.face).glyph);
+            // Only outlines can be reshaped; bitmap glyphs are left alone.
+            if (*slot).format as u32 != FT_GLYPH_FORMAT_OUTLINE {
+                return result;
+            }
+
+            if self.synthetic_bold {
+                let size: &FT_SizeRec = mem::transmute(&(*(*self.face).size));
+                // In 26.6 fixed point, like the outline.
+                let strength = (size.metrics.y_ppem as f64 * 64.0 *
+                                SYNTHETIC_BOLD_EM_FRACTION) as FT_Pos;
+                let result = FT_Outline_Embolden(&mut (*slot).outline, strength);
+                if !result.succeeded() {
+                    return result;
+                }
+                (*slot).metrics.width += strength;
+                (*slot).metrics.height += strength;
+                (*slot).metrics.horiBearingY += strength;
+                (*slot).metrics.horiAdvance += strength;
+                (*slot).advance.x += strength;
+            }
+
+            if self.synthetic_oblique {
+                let shear = FT_Matrix {
+                    xx: 0x10000,
+                    xy: SYNTHETIC_OBLIQUE_SHEAR,
+                    yx: 0,
+                    yy: 0x10000,
+                };
+                FT_Outline_Transform(&(*slot).outline, &shear);
+            }
+            result
+        }
+    }
+
+    fn error_for(source: &str, result: FT_Error) -> FontError {
+        if result == FT_ERR_CANNOT_OPEN_RESOURCE || result == FT_ERR_CANNOT_OPEN_STREAM {
+            IoError(source.to_str())
+        } else {
+            UnsupportedFormat(source.to_str(), Some(result as int))
+        }
+    }
+
+    /// Takes ownership of a freshly created `face` and applies `style` to it.
+    /// The face is released again if that fails.
+    fn from_face(fctx: &FontContextHandle, face: FT_Face, source: FontSource,
+                 style: &SpecifiedFontStyle) -> Result<FontHandle, FontError> {
+        let mut shared_face = FontFace::new(fctx, face, source);
+        let weight_applied = FontHandle::apply_variations(face, style);
+        // Never synthesize on top of a face that already is bold or slanted.
+        shared_face.synthetic_bold = !weight_applied &&
+            css_weight(style.weight) >= 600.0 && css_weight(face_weight(face)) < 600.0;
+        shared_face.synthetic_oblique = style.style != font_style::normal &&
+            unsafe { (*face).style_flags & FT_STYLE_FLAG_ITALIC == 0 };
+        shared_face.features = style.features.clone();
+        FontHandle::from_shared_face(fctx, Rc::new(shared_face), style.pt_size)
+    }
+
+    /// Opens face `face_index` of `file`. The index is only non-zero for font
+    /// collections (.ttc files).
+    pub fn new_from_file(fctx: &FontContextHandle, file: &str, face_index: uint,
+                         style: &SpecifiedFontStyle) -> Result<FontHandle, FontError> {
         unsafe {
             let ft_ctx: FT_Library = fctx.ctx.ctx;
-            if ft_ctx.is_null() { return Err(()); }
+            if ft_ctx.is_null() { return Err(UnsupportedFormat(file.to_str(), None)); }
 
             let mut face: FT_Face = ptr::null();
-            let face_index = 0 as FT_Long;
-            file.to_c_str().with_ref(|file_str| {
-                FT_New_Face(ft_ctx, file_str,
-                            face_index, &mut face);
-            });
-            if face.is_null() {
-                return Err(());
-            }
-            if FontHandle::set_char_size(face, style.pt_size).is_ok() {
-                Ok(FontHandle {
-                    source: FontSourceFile(file.to_str()),
-                    face: face,
-                    handle: fctx.clone()
+            let result = {
+                let _guard = fctx.ctx.lock();
+                file.to_c_str().with_ref(|file_str| {
+                    FT_New_Face(ft_ctx, file_str,
+                                face_index as FT_Long, &mut face)
                 })
-            } else {
-                Err(())
+            };
+            if !result.succeeded() || face.is_null() {
+                return Err(FontHandle::error_for(file, result));
             }
+            FontHandle::from_face(fctx, face, FontSourceFile(file.to_str()), style)
         }
     }
 
-    pub fn new_from_file_unstyled(fctx: &FontContextHandle, file: String)
+    /// Creates a face from font data in memory, such as a downloaded web font.
+    /// FreeType reads from `bytes` for as long as the face is alive, so the
+    /// handle keeps a reference to them.
+    pub fn new_from_shared_buffer(fctx: &FontContextHandle, bytes: Arc<Vec<u8>>,
+                                  face_index: uint, style: &SpecifiedFontStyle)
+                                  -> Result<FontHandle, FontError> {
+        let description = format!("font buffer ({:u} bytes, face {:u})", bytes.len(), face_index);
+        let (data, len) = (bytes.as_ptr(), bytes.len());
+        FontHandle::new_from_memory(fctx, data, len, FontSourceMem(bytes), face_index,
+                                    description, style)
+    }
+
+    /// Creates a face from the contents of a font file, which the handle keeps
+    /// alive. `path` only names the file in errors.
+    pub fn new_from_file_data(fctx: &FontContextHandle, file_data: Rc<FontFileData>,
+                              face_index: uint, path: &Path, style: &SpecifiedFontStyle)
+                              -> Result<FontHandle, FontError> {
+        let (data, len) = {
+            let bytes = file_data.as_slice();
+            (bytes.as_ptr(), bytes.len())
+        };
+        FontHandle::new_from_memory(fctx, data, len, FontSourceFileData(file_data), face_index,
+                                    path.display().to_str(), style)
+    }
+
+    /// Creates a face from the `len` bytes at `data`, which `source` owns.
+    fn new_from_memory(fctx: &FontContextHandle, data: *u8, len: uint, source: FontSource,
+                       face_index: uint, description: String, style: &SpecifiedFontStyle)
+                       -> Result<FontHandle, FontError> {
+        unsafe {
+            let ft_ctx: FT_Library = fctx.ctx.ctx;
+            if ft_ctx.is_null() { return Err(UnsupportedFormat(description, None)); }
+
+            let mut face: FT_Face = ptr::null();
+            let result = {
+                let _guard = fctx.ctx.lock();
+                FT_New_Memory_Face(ft_ctx, data, len as FT_Long,
+                                   face_index as FT_Long, &mut face)
+            };
+            if !result.succeeded() || face.is_null() {
+                return Err(FontHandle::error_for(description.as_slice(), result));
+            }
+            FontHandle::from_face(fctx, face, source, style)
+        }
+    }
+
+    pub fn new_from_file_unstyled(fctx: &FontContextHandle, file: String, face_index: uint)
                                -> Result<FontHandle, ()> {
         unsafe {
             let ft_ctx: FT_Library = fctx.ctx.ctx;
             if ft_ctx.is_null() { return Err(()); }
 
             let mut face: FT_Face = ptr::null();
-            let face_index = 0 as FT_Long;
-            file.to_c_str().with_ref(|file_str| {
-                FT_New_Face(ft_ctx, file_str,
-                            face_index, &mut face);
-            });
+            {
+                let _guard = fctx.ctx.lock();
+                file.to_c_str().with_ref(|file_str| {
+                    FT_New_Face(ft_ctx, file_str,
+                                face_index as FT_Long, &mut face);
+                });
+            }
             if face.is_null() {
                 return Err(());
             }
 
-            Ok(FontHandle {
-                source: FontSourceFile(file),
-                face: face,
-                handle: fctx.clone()
-            })
+            let shared_face = Rc::new(FontFace::new(fctx, face, FontSourceFile(file)));
+            Ok(FontHandle::with_face_size(fctx, shared_face, None))
         }
     }
 
@@ -329,12 +1109,663 @@ impl<'a> FontHandle {
         let metrics: &FT_Size_Metrics = &(*size).metrics;
 
         let em_size = face.units_per_EM as f64;
+        // Bitmap fonts in formats without outlines have no font units.
+        if em_size == 0.0 {
+            return geometry::from_frac_px(0.0);
+        }
         let x_scale = (metrics.x_ppem as f64) / em_size as f64;
 
         // If this isn't true then we're scaling one of the axes wrong
         assert!(metrics.x_ppem == metrics.y_ppem);
 
-        return geometry::from_frac_px(value * x_scale);
+        return geometry::from_frac_px(value * x_scale * self.metrics_scale);
+    }
+
+    /// The OpenType features, as (tag, value) pairs, that the style this
+    /// handle was created for asked text to be shaped with.
+    pub fn features(&'a self) -> &'a [(u32, u32)] {
+        self.shared_face.features.as_slice()
+    }
+
+    /// The x-height, cap height and text decoration metrics of the face at
+    /// this handle's size.
+    pub fn metrics_ext(&self) -> FontMetricsExt {
+        match self.face_size {
+            Some(ref face_size) => face_size.metrics_ext.clone(),
+            // Faces opened without a style are measured at whatever size they
+            // have now.
+            None => FontFace::measure_metrics_ext(self.face, self.metrics_scale),
+        }
+    }
+
+    fn strike_pixels_to_au(&self, pixels: f64) -> Au {
+        geometry::from_frac_px(pixels * self.metrics_scale)
     }
 }
 
+
+#[cfg(test)]
+pub mod test {
+    use super::{FACE_SIZE_CACHE_SIZE, FT_GLYPH_FORMAT_OUTLINE, FontHandle, VariationAxis};
+    use super::WEIGHT_AXIS_TAG;
+    use super::{WIDTH_AXIS_TAG, best_strike, coordinates_for_style, nearest_strike};
+    use font::{FontHandleMethods, FontStyle, IoError, UnsupportedFormat, test_style};
+    use font_context::FontContextHandleMethods;
+    use platform::font_context::{FontContextHandle, LAST_RESORT_FONT};
+    use platform::font_list::path_from_identifier;
+    use servo_util::geometry;
+    use freetype::freetype::{FTErrorMethods, FT_GlyphSlot, FT_RENDER_MODE_NORMAL};
+    use freetype::freetype::FT_Render_Glyph;
+    use text::glyph::GlyphId;
+    use style::computed_values::{font_style, font_weight};
+    use webfont::{read_u16, read_u32, write_u16, write_u32};
+    use std::io::{File, TempDir};
+    use std::iter::range_step;
+    use std::mem;
+    use sync::Arc;
+
+    /// A font with color bitmaps of U+1F600 at 16 and 32 pixels per em.
+    static TEST_COLOR_FONT: &'static [u8] = include_bin!("fonts/TestColorEmoji.ttf");
+
+    /// A font with no outlines, just monochrome bitmaps of 'A' at 10 and 16
+    /// pixels per em.
+    static TEST_BITMAP_FONT: &'static [u8] = include_bin!("fonts/TestBitmap.otb");
+
+    /// A variable font with a weight axis from 100 to 900. Its 'A' is 500
+    /// units wide at the default weight of 400, and 200 units wider at 900.
+    static TEST_VARIABLE_FONT: &'static [u8] = include_bin!("fonts/TestVariable.ttf");
+
+    /// FT_PIXEL_MODE_MONO, for bitmaps with one bit per pixel.
+    static FT_PIXEL_MODE_MONO: int = 1;
+
+    /// FT_PIXEL_MODE_BGRA, for bitmaps with premultiplied color.
+    static FT_PIXEL_MODE_BGRA: int = 7;
+
+    fn test_font_bytes() -> Arc<Vec<u8>> {
+        Arc::new(LAST_RESORT_FONT.iter().map(|&x| x).collect())
+    }
+
+    /// Builds a TrueType collection holding `fonts`, in order. Each font is
+    /// copied whole, with its table offsets rebased onto its new position.
+    fn make_collection(fonts: &[Vec<u8>]) -> Vec<u8> {
+        let mut collection = Vec::from_slice("ttcf".as_bytes());
+        collection.grow(8 + 4 * fonts.len(), &0u8);
+        write_u32(&mut collection, 4, 0x00010000);
+        write_u32(&mut collection, 8, fonts.len() as u32);
+
+        for (i, font) in fonts.iter().enumerate() {
+            while collection.len() % 4 != 0 {
+                collection.push(0);
+            }
+            let base = collection.len();
+            write_u32(&mut collection, 12 + 4 * i, base as u32);
+            collection.push_all(font.as_slice());
+
+            let num_tables = read_u16(font.as_slice(), 4) as uint;
+            for table in range(0, num_tables) {
+                let record = base + 12 + 16 * table + 8;
+                let offset = read_u32(collection.as_slice(), record);
+                write_u32(&mut collection, record, base as u32 + offset);
+            }
+        }
+        collection
+    }
+
+    /// A collection of the bundled font and DejaVu Serif.
+    fn test_collection() -> Vec<u8> {
+        let serif_style = test_style(12.0);
+        let serif_match = path_from_identifier("DejaVu Serif".to_string(),
+                                               &serif_style).unwrap();
+        let serif = File::open(&serif_match.path).read_to_end().unwrap();
+        make_collection(&[(*test_font_bytes()).clone(), serif])
+    }
+
+    #[test]
+    fn test_create_font_from_buffer() {
+        let fctx = FontContextHandle::new();
+        let handle = fctx.create_font_from_buffer(test_font_bytes(), 0, &test_style(12.0)).unwrap();
+        assert_eq!(handle.family_name(), "DejaVu Sans Mono".to_string());
+        assert!(handle.glyph_index('A').is_some());
+    }
+
+    #[test]
+    fn test_buffer_outlives_caller() {
+        let fctx = FontContextHandle::new();
+        let handle = {
+            let bytes = test_font_bytes();
+            FontHandle::new_from_shared_buffer(&fctx, bytes, 0, &test_style(12.0)).unwrap()
+        };
+        assert!(handle.glyph_index('A').is_some());
+    }
+
+    #[test]
+    fn test_create_font_from_bad_buffer() {
+        let fctx = FontContextHandle::new();
+        let bytes = Arc::new(vec!(0u8, 1, 2, 3));
+        assert!(fctx.create_font_from_buffer(bytes, 0, &test_style(12.0)).is_err());
+    }
+
+    #[test]
+    fn test_create_font_from_woff_buffer() {
+        let fctx = FontContextHandle::new();
+        let woff = include_bin!("fonts/TestColorEmoji.woff");
+        let bytes = Arc::new(Vec::from_slice(woff));
+        let handle = fctx.create_font_from_buffer(bytes, 0, &test_style(12.0)).unwrap();
+        assert_eq!(handle.family_name(), "Test Color Emoji".to_string());
+        assert!(handle.glyph_index('\U0001F600').is_some());
+
+        let truncated = Arc::new(Vec::from_slice(woff.slice_to(woff.len() / 2)));
+        assert!(fctx.create_font_from_buffer(truncated, 0, &test_style(12.0)).is_err());
+    }
+
+    #[test]
+    fn test_create_font_from_buffer_bad_face_index() {
+        let fctx = FontContextHandle::new();
+        assert!(fctx.create_font_from_buffer(test_font_bytes(), 7, &test_style(12.0)).is_err());
+    }
+
+    #[test]
+    fn test_new_from_file_not_a_font() {
+        let dir = TempDir::new("gfx-font-test").unwrap();
+        let path = dir.path().join("not-a-font.ttf");
+        File::create(&path).write("This is a text file, not a font.".as_bytes()).unwrap();
+        let path = path.as_str().unwrap();
+
+        let fctx = FontContextHandle::new();
+        match FontHandle::new_from_file(&fctx, path, 0, &test_style(12.0)) {
+            Err(UnsupportedFormat(ref file, Some(_))) => assert_eq!(file.as_slice(), path),
+            Err(error) => fail!("unexpected error {}", error),
+            Ok(_) => fail!("loaded a text file as a font"),
+        }
+    }
+
+    #[test]
+    fn test_new_from_file_missing() {
+        let fctx = FontContextHandle::new();
+        let path = "/nonexistent/servo/no-such-font.ttf";
+        match FontHandle::new_from_file(&fctx, path, 0, &test_style(12.0)) {
+            Err(IoError(ref file)) => assert_eq!(file.as_slice(), path),
+            Err(error) => fail!("unexpected error {}", error),
+            Ok(_) => fail!("loaded a font that does not exist"),
+        }
+    }
+
+    #[test]
+    fn test_stream_errors_are_io_errors() {
+        let error_for = |result| FontHandle::error_for("font.ttf", result);
+        assert_eq!(error_for(0x01), IoError("font.ttf".to_string()));
+        assert_eq!(error_for(0x51), IoError("font.ttf".to_string()));
+        assert_eq!(error_for(0x52), UnsupportedFormat("font.ttf".to_string(), Some(0x52)));
+    }
+
+    #[test]
+    fn test_collection_from_buffer() {
+        let fctx = FontContextHandle::new();
+        let bytes = Arc::new(test_collection());
+        let first = fctx.create_font_from_buffer(bytes.clone(), 0, &test_style(12.0)).unwrap();
+        let second = fctx.create_font_from_buffer(bytes.clone(), 1, &test_style(12.0)).unwrap();
+        assert_eq!(first.family_name(), "DejaVu Sans Mono".to_string());
+        assert_eq!(second.family_name(), "DejaVu Serif".to_string());
+        assert!(fctx.create_font_from_buffer(bytes, 2, &test_style(12.0)).is_err());
+    }
+
+    #[test]
+    fn test_collection_from_file() {
+        let dir = TempDir::new("gfx-font-test").unwrap();
+        let path = dir.path().join("collection.ttc");
+        File::create(&path).write(test_collection().as_slice()).unwrap();
+        let path = path.as_str().unwrap();
+
+        let fctx = FontContextHandle::new();
+        let first = FontHandle::new_from_file(&fctx, path, 0, &test_style(12.0)).unwrap();
+        let second = FontHandle::new_from_file(&fctx, path, 1, &test_style(12.0)).unwrap();
+        assert_eq!(first.family_name(), "DejaVu Sans Mono".to_string());
+        assert_eq!(second.family_name(), "DejaVu Serif".to_string());
+    }
+
+    /// Renames a font's family by rewriting the UTF-16 strings in its name
+    /// table. `new` must be as long as `old`.
+    pub fn rename_family(font: &[u8], old: &str, new: &str) -> Vec<u8> {
+        assert_eq!(old.len(), new.len());
+        let utf16 = |name: &str| -> Vec<u8> {
+            name.as_bytes().iter().flat_map(|&byte| vec!(0u8, byte).move_iter()).collect()
+        };
+        let (old, new) = (utf16(old), utf16(new));
+        let mut renamed = Vec::from_slice(font);
+        for start in range(0, font.len() - old.len() + 1) {
+            if font.slice(start, start + old.len()) == old.as_slice() {
+                for (i, &byte) in new.iter().enumerate() {
+                    *renamed.get_mut(start + i) = byte;
+                }
+            }
+        }
+        renamed
+    }
+
+    #[test]
+    fn test_collection_match_has_index() {
+        // Give the second face a family that nothing else provides.
+        let second = rename_family(TEST_COLOR_FONT, "Test Color Emoji", "Test Collection1");
+        let collection = make_collection(&[(*test_font_bytes()).clone(), second]);
+        let dir = TempDir::new("gfx-font-test").unwrap();
+        let path = dir.path().join("collection.ttc");
+        File::create(&path).write(collection.as_slice()).unwrap();
+
+        let fctx = FontContextHandle::new();
+        fctx.add_font_directory(dir.path()).unwrap();
+        let font_match = path_from_identifier("Test Collection1".to_string(),
+                                              &test_style(12.0)).unwrap();
+        assert!(font_match.exact);
+        assert_eq!(font_match.index, 1);
+        assert!(font_match.path == path);
+    }
+
+    #[test]
+    fn test_truncated_font_falls_back() {
+        let family = "Test Broken Font";
+        let font = rename_family(TEST_COLOR_FONT, "Test Color Emoji", family);
+        let dir = TempDir::new("gfx-font-test").unwrap();
+        let path = dir.path().join("broken.ttf");
+        File::create(&path).write(font.as_slice()).unwrap();
+        let fctx = FontContextHandle::new();
+        fctx.add_font_directory(dir.path()).unwrap();
+
+        // Damage the file behind fontconfig's back, as a bad download would.
+        File::create(&path).write(font.slice_to(600)).unwrap();
+        let mut style = test_style(12.0);
+        style.families = vec!(family.to_string(), "monospace".to_string());
+        let handle = fctx.create_font_from_identifier(family.to_string(), style).unwrap();
+        assert_eq!(handle.family_name(), fctx.resolve_generic_family("monospace"));
+        assert!(fctx.is_blacklisted(&path, 0));
+        match fctx.create_font_from_family(family.to_string(), &test_style(12.0)) {
+            Err(UnsupportedFormat(_, None)) => {}
+            _ => fail!("a blacklisted font was opened again"),
+        }
+    }
+
+    fn test_axes() -> Vec<VariationAxis> {
+        vec!(
+            VariationAxis { tag: WIDTH_AXIS_TAG, minimum: 75.0, default: 100.0, maximum: 100.0 },
+            VariationAxis { tag: WEIGHT_AXIS_TAG, minimum: 300.0, default: 400.0, maximum: 800.0 }
+        )
+    }
+
+    #[test]
+    fn test_variation_weight_is_applied() {
+        let coords = coordinates_for_style(test_axes().as_slice(), &[], 700.0, 100.0);
+        assert_eq!(coords, vec!(100.0, 700.0));
+    }
+
+    #[test]
+    fn test_variation_weight_is_clamped() {
+        let axes = test_axes();
+        assert_eq!(coordinates_for_style(axes.as_slice(), &[], 100.0, 100.0), vec!(100.0, 300.0));
+        assert_eq!(coordinates_for_style(axes.as_slice(), &[], 900.0, 100.0), vec!(100.0, 800.0));
+    }
+
+    #[test]
+    fn test_variation_width_is_applied() {
+        let axes = test_axes();
+        assert_eq!(coordinates_for_style(axes.as_slice(), &[], 400.0, 87.5), vec!(87.5, 400.0));
+        // The font is no wider than normal, nor narrower than condensed.
+        assert_eq!(coordinates_for_style(axes.as_slice(), &[], 400.0, 125.0), vec!(100.0, 400.0));
+        assert_eq!(coordinates_for_style(axes.as_slice(), &[], 400.0, 50.0), vec!(75.0, 400.0));
+    }
+
+    #[test]
+    fn test_variation_prefers_named_instance() {
+        let instances = [vec!(100.0, 400.0), vec!(87.5, 700.0)];
+        assert_eq!(coordinates_for_style(test_axes().as_slice(), &instances, 700.0, 87.5),
+                   vec!(87.5, 700.0));
+        assert_eq!(coordinates_for_style(test_axes().as_slice(), &instances, 600.0, 87.5),
+                   vec!(87.5, 600.0));
+        // An instance only matches if it has the requested width too.
+        assert_eq!(coordinates_for_style(test_axes().as_slice(), &instances, 700.0, 100.0),
+                   vec!(100.0, 700.0));
+    }
+
+    #[test]
+    fn test_variation_without_weight_axis() {
+        let axes = [VariationAxis { tag: WIDTH_AXIS_TAG,
+                                    minimum: 75.0, default: 100.0, maximum: 100.0 }];
+        assert_eq!(coordinates_for_style(&axes, &[], 700.0, 100.0), vec!(100.0));
+        assert_eq!(coordinates_for_style(&axes, &[], 700.0, 75.0), vec!(75.0));
+    }
+
+    #[test]
+    fn test_variable_font_weight_changes_metrics() {
+        let fctx = FontContextHandle::new();
+        let open = |weight| {
+            let bytes = Arc::new(Vec::from_slice(TEST_VARIABLE_FONT));
+            let style = FontStyle { weight: weight, ..test_style(48.0) };
+            fctx.create_font_from_buffer(bytes, 0, &style).unwrap()
+        };
+        let regular = open(font_weight::Weight400);
+        let bold = open(font_weight::Weight700);
+        // The weight comes from the font's own axis, not from emboldening.
+        assert!(!regular.synthetic_bold);
+        assert!(!bold.synthetic_bold);
+
+        let glyph = regular.glyph_index('A').unwrap();
+        // Half an em at 48 pixels per em.
+        assert_eq!(regular.glyph_h_advance(glyph).unwrap(), 24.0);
+        assert!(bold.glyph_h_advance(glyph).unwrap() > regular.glyph_h_advance(glyph).unwrap());
+        let (_, regular_width, _, _) = load_bitmap(&regular, glyph);
+        let (_, bold_width, _, _) = load_bitmap(&bold, glyph);
+        assert!(bold_width > regular_width);
+    }
+
+    #[test]
+    fn test_best_strike() {
+        let strikes = [16.0, 32.0, 109.0];
+        assert_eq!(best_strike(&strikes, 12.0), Some(0));
+        assert_eq!(best_strike(&strikes, 16.0), Some(0));
+        assert_eq!(best_strike(&strikes, 20.0), Some(1));
+        assert_eq!(best_strike(&strikes, 200.0), Some(2));
+        assert_eq!(best_strike(&[], 16.0), None);
+    }
+
+    /// Loads and renders `glyph` the way it is rasterized, and describes the
+    /// bitmap: its pixel mode, width, height and whether any pixel is visible.
+    fn load_bitmap(handle: &FontHandle, glyph: GlyphId) -> (int, uint, uint, bool) {
+        unsafe {
+            assert!(handle.load_glyph(glyph).succeeded());
+            let slot: FT_GlyphSlot = mem::transmute((*handle.face).glyph);
+            if (*slot).format as u32 == FT_GLYPH_FORMAT_OUTLINE {
+                assert!(FT_Render_Glyph(slot, FT_RENDER_MODE_NORMAL).succeeded());
+            }
+            let bitmap = &(*slot).bitmap;
+            let (width, rows) = (bitmap.width as uint, bitmap.rows as uint);
+            // Coverage is the only byte of gray pixels, and alpha the last of
+            // BGRA ones. Monochrome bitmaps pack eight pixels into a byte.
+            let pixel_mode = bitmap.pixel_mode as int;
+            let (row_bytes, stride, alpha) = if pixel_mode == FT_PIXEL_MODE_BGRA {
+                (width * 4, 4, 3)
+            } else if pixel_mode == FT_PIXEL_MODE_MONO {
+                ((width + 7) / 8, 1, 0)
+            } else {
+                (width, 1, 0)
+            };
+            let mut visible = false;
+            for row in range(0, rows) {
+                let line = bitmap.buffer.offset((row as int) * (bitmap.pitch as int));
+                for x in range_step(alpha, row_bytes, stride) {
+                    visible = visible || *line.offset(x as int) != 0;
+                }
+            }
+            (bitmap.pixel_mode as int, width, rows, visible)
+        }
+    }
+
+    #[test]
+    fn test_color_emoji_font() {
+        let fctx = FontContextHandle::new();
+        let emoji = "Noto Color Emoji".to_string();
+        let installed = match path_from_identifier(emoji, &test_style(12.0)) {
+            Ok(ref font_match) if font_match.exact => Some(font_match.clone()),
+            _ => None,
+        };
+        for &size in [16.0, 32.0].iter() {
+            let style = test_style(size);
+            let handle = match installed {
+                Some(ref font_match) => {
+                    FontHandle::new_from_file(&fctx, font_match.path.as_str().unwrap(),
+                                              font_match.index, &style).unwrap()
+                }
+                None => {
+                    let bytes = Arc::new(TEST_COLOR_FONT.iter().map(|&x| x).collect());
+                    fctx.create_font_from_buffer(bytes, 0, &style).unwrap()
+                }
+            };
+            assert!(handle.color_bitmaps);
+
+            let glyph = handle.glyph_index('\U0001F600').unwrap();
+            let (pixel_mode, width, rows, visible) = load_bitmap(&handle, glyph);
+            assert_eq!(pixel_mode, FT_PIXEL_MODE_BGRA);
+            assert!(width > 0 && rows > 0);
+            assert!(visible);
+            if installed.is_none() {
+                // The strikes of the test font match the sizes exactly.
+                assert_eq!((width, rows), (size as uint, size as uint));
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_strike() {
+        let strikes = [10.0, 16.0, 24.0];
+        assert_eq!(nearest_strike(&strikes, 11.0), Some(0));
+        assert_eq!(nearest_strike(&strikes, 14.0), Some(1));
+        assert_eq!(nearest_strike(&strikes, 16.0), Some(1));
+        assert_eq!(nearest_strike(&strikes, 100.0), Some(2));
+        assert_eq!(nearest_strike(&[], 16.0), None);
+    }
+
+    fn bitmap_font(fctx: &FontContextHandle, size: f64) -> FontHandle {
+        let style = test_style(size);
+        let bytes = Arc::new(TEST_BITMAP_FONT.iter().map(|&x| x).collect());
+        fctx.create_font_from_buffer(bytes, 0, &style).unwrap()
+    }
+
+    #[test]
+    fn test_bitmap_font_selects_strike() {
+        let fctx = FontContextHandle::new();
+        for &(size, strike, glyph_size) in [(11.0, 10.0, (5, 7)), (16.0, 16.0, (8, 12))].iter() {
+            let handle = bitmap_font(&fctx, size);
+            assert_eq!(handle.strike_size, Some(strike));
+            assert!(!handle.color_bitmaps);
+
+            let glyph = handle.glyph_index('A').unwrap();
+            let (pixel_mode, width, rows, visible) = load_bitmap(&handle, glyph);
+            assert_eq!(pixel_mode, FT_PIXEL_MODE_MONO);
+            assert_eq!((width, rows), glyph_size);
+            assert!(visible);
+
+            // Layout gets the strike's size, not the one asked for.
+            let metrics = handle.get_metrics();
+            assert_eq!(metrics.em_size, geometry::from_frac_px(strike));
+            assert!(metrics.ascent > geometry::from_frac_px(0.0));
+        }
+    }
+
+    #[test]
+    fn test_bitmap_font_far_from_strikes_is_scaled() {
+        let fctx = FontContextHandle::new();
+        let handle = bitmap_font(&fctx, 40.0);
+        assert_eq!(handle.strike_size, Some(16.0));
+        assert_eq!(handle.get_metrics().em_size, geometry::from_frac_px(40.0));
+        let strike = bitmap_font(&fctx, 16.0);
+        let glyph = handle.glyph_index('A').unwrap();
+        assert_eq!(handle.glyph_h_advance(glyph).unwrap(),
+                   strike.glyph_h_advance(glyph).unwrap() * 2.5);
+    }
+
+    #[test]
+    fn test_outline_font_is_not_color() {
+        let fctx = FontContextHandle::new();
+        let handle = fctx.create_font_from_buffer(test_font_bytes(), 0, &test_style(12.0)).unwrap();
+        assert!(!handle.color_bitmaps);
+    }
+
+    /// The bundled font only has a regular face.
+    fn bundled_font(style: &FontStyle) -> FontHandle {
+        let fctx = FontContextHandle::new();
+        fctx.create_font_from_buffer(test_font_bytes(), 0, style).unwrap()
+    }
+
+    #[test]
+    fn test_synthetic_bold() {
+        // At this size, emboldening changes the rendered width.
+        let regular = bundled_font(&test_style(48.0));
+        let bold = bundled_font(&FontStyle { weight: font_weight::Weight700, ..test_style(48.0) });
+        assert!(!regular.synthetic_bold);
+        assert!(bold.synthetic_bold);
+        assert!(!bold.synthetic_oblique);
+
+        let glyph = regular.glyph_index('H').unwrap();
+        assert!(bold.glyph_h_advance(glyph).unwrap() > regular.glyph_h_advance(glyph).unwrap());
+        let (_, regular_width, _, _) = load_bitmap(&regular, glyph);
+        let (_, bold_width, _, _) = load_bitmap(&bold, glyph);
+        assert!(bold_width > regular_width);
+    }
+
+    #[test]
+    fn test_synthetic_oblique() {
+        let regular = bundled_font(&test_style(48.0));
+        let oblique = bundled_font(&FontStyle { style: font_style::italic, ..test_style(48.0) });
+        assert!(oblique.synthetic_oblique);
+        assert!(!oblique.synthetic_bold);
+
+        // Slanting leaves the advance alone but leans the glyph past it.
+        let glyph = regular.glyph_index('H').unwrap();
+        assert_eq!(oblique.glyph_h_advance(glyph), regular.glyph_h_advance(glyph));
+        let (_, regular_width, _, _) = load_bitmap(&regular, glyph);
+        let (_, oblique_width, _, _) = load_bitmap(&oblique, glyph);
+        assert!(oblique_width > regular_width);
+    }
+
+    #[test]
+    fn test_real_faces_are_not_synthesized() {
+        let fctx = FontContextHandle::new();
+        let style = FontStyle {
+            weight: font_weight::Weight700,
+            style: font_style::italic,
+            ..test_style(48.0)
+        };
+        let font_match = path_from_identifier("DejaVu Sans".to_string(), &style).unwrap();
+        let handle = FontHandle::new_from_file(&fctx, font_match.path.as_str().unwrap(),
+                                               font_match.index, &style).unwrap();
+        assert!(handle.is_italic());
+        assert!(!handle.synthetic_bold);
+        assert!(!handle.synthetic_oblique);
+    }
+
+    /// The bundled font with its version 1 OS/2 table replaced by a version 2
+    /// one giving the x-height and cap height, in font units.
+    fn font_with_os2_heights(x_height: u16, cap_height: u16) -> Vec<u8> {
+        let mut font = (*test_font_bytes()).clone();
+        let num_tables = read_u16(font.as_slice(), 4) as uint;
+        let record = range(0, num_tables).map(|table| 12 + 16 * table).find(|&record| {
+            font.slice(record, record + 4) == "OS/2".as_bytes()
+        }).unwrap();
+        let offset = read_u32(font.as_slice(), record + 8) as uint;
+        let length = read_u32(font.as_slice(), record + 12) as uint;
+
+        while font.len() % 4 != 0 {
+            font.push(0);
+        }
+        let new_offset = font.len();
+        let table = Vec::from_slice(font.slice(offset, offset + length));
+        font.push_all(table.as_slice());
+        font.grow(96 - length, &0u8);
+        write_u16(&mut font, new_offset, 2);
+        write_u16(&mut font, new_offset + 86, x_height);
+        write_u16(&mut font, new_offset + 88, cap_height);
+        write_u32(&mut font, record + 8, new_offset as u32);
+        write_u32(&mut font, record + 12, 96);
+        font
+    }
+
+    fn assert_px_near(value: geometry::Au, expected: f64) {
+        let px = geometry::to_frac_px(value);
+        assert!((px - expected).abs() < 0.1, "{} is not close to {}", px, expected);
+    }
+
+    #[test]
+    fn test_metrics_ext_from_os2_table() {
+        let fctx = FontContextHandle::new();
+        let bytes = Arc::new(font_with_os2_heights(1024, 1536));
+        for &size in [16.0, 32.0].iter() {
+            let style = test_style(size);
+            let handle = fctx.create_font_from_buffer(bytes.clone(), 0, &style).unwrap();
+            let metrics = handle.metrics_ext();
+            assert_px_near(metrics.x_height, size / 2.0);
+            assert_px_near(metrics.cap_height, size * 0.75);
+            assert_eq!(handle.get_metrics().x_height, metrics.x_height);
+        }
+    }
+
+    #[test]
+    fn test_metrics_ext_measures_glyphs() {
+        // The bundled font's OS/2 table predates the x-height and cap height,
+        // so they come from 'x' (1120 units tall) and 'H' (1493 units).
+        for &size in [16.0, 32.0].iter() {
+            let style = test_style(size);
+            let metrics = bundled_font(&style).metrics_ext();
+            assert_px_near(metrics.x_height, 1120.0 * size / 2048.0);
+            assert_px_near(metrics.cap_height, 1493.0 * size / 2048.0);
+            assert_px_near(metrics.strikeout_offset, 530.0 * size / 2048.0);
+            assert_px_near(metrics.strikeout_size, 102.0 * size / 2048.0);
+            assert_px_near(metrics.underline_offset, -85.0 * size / 2048.0);
+            assert_px_near(metrics.underline_size, 90.0 * size / 2048.0);
+        }
+    }
+
+    #[test]
+    fn test_metrics_ext_without_outlines() {
+        // The bitmap font has no font units; everything falls back to the
+        // strike's size.
+        let fctx = FontContextHandle::new();
+        let metrics = bitmap_font(&fctx, 16.0).metrics_ext();
+        assert!(metrics.x_height > geometry::Au(0));
+        assert!(metrics.cap_height > geometry::Au(0));
+        assert!(metrics.underline_size > geometry::Au(0));
+        assert!(metrics.strikeout_size > geometry::Au(0));
+    }
+
+    #[test]
+    fn test_alternating_sizes_reuse_face_sizes() {
+        let fctx = FontContextHandle::new();
+        let body = bundled_font(&test_style(14.0));
+        let heading = FontHandle::from_shared_face(&fctx, body.shared_face.clone(),
+                                                   16.0).unwrap();
+        assert_eq!(body.face, heading.face);
+
+        let glyph = body.glyph_index('g').unwrap();
+        for i in range(0u, 10000) {
+            let handle = if i % 2 == 0 { &body } else { &heading };
+            assert!(handle.glyph_h_advance(glyph).is_some());
+        }
+        assert_eq!(body.shared_face.sizes_set_up.get(), 2);
+    }
+
+    #[test]
+    fn test_shared_face_metrics_match_per_size() {
+        let fctx = FontContextHandle::new();
+        let first = bundled_font(&test_style(14.0));
+        let glyph = first.glyph_index('W').unwrap();
+        for &size in [16.0, 14.0, 32.0, 16.0].iter() {
+            let shared = FontHandle::from_shared_face(&fctx, first.shared_face.clone(),
+                                                      size).unwrap();
+            let alone = bundled_font(&test_style(size));
+            let (shared_metrics, metrics) = (shared.get_metrics(), alone.get_metrics());
+            assert_eq!(shared_metrics.em_size, metrics.em_size);
+            assert_eq!(shared_metrics.ascent, metrics.ascent);
+            assert_eq!(shared_metrics.descent, metrics.descent);
+            assert_eq!(shared_metrics.x_height, metrics.x_height);
+            assert_eq!(shared.glyph_h_advance(glyph), alone.glyph_h_advance(glyph));
+        }
+        assert_eq!(first.shared_face.sizes_set_up.get(), 3);
+        // The first handle's size is still its own.
+        assert_eq!(first.get_metrics().em_size, geometry::from_frac_px(14.0));
+    }
+
+    #[test]
+    fn test_face_sizes_are_evicted() {
+        let fctx = FontContextHandle::new();
+        let first = bundled_font(&test_style(10.0));
+        let glyph = first.glyph_index('A').unwrap();
+        let advance = first.glyph_h_advance(glyph);
+        for i in range(1, FACE_SIZE_CACHE_SIZE + 1) {
+            FontHandle::from_shared_face(&fctx, first.shared_face.clone(),
+                                         10.0 + i as f64).unwrap();
+        }
+        let sizes_set_up = FACE_SIZE_CACHE_SIZE + 1;
+        assert_eq!(first.shared_face.sizes_set_up.get(), sizes_set_up);
+
+        // The first size is set up again, but our handle still has its own.
+        FontHandle::from_shared_face(&fctx, first.shared_face.clone(), 10.0).unwrap();
+        assert_eq!(first.shared_face.sizes_set_up.get(), sizes_set_up + 1);
+        assert_eq!(first.glyph_h_advance(glyph), advance);
+    }
+}
@@ -2,98 +2,1538 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use font::UsedFontStyle;
-use platform::font::FontHandle;
+use font::{FontError, FontHandleMethods, IoError, LibraryError, NotFound, UnsupportedFormat};
+use font::{FontStyle, UsedFontStyle};
+use platform::font::{FontFace, FontFileData, FontHandle};
 use font_context::FontContextHandleMethods;
-use platform::font_list::path_from_identifier;
+use platform::font_list::{FontMatch, add_font_directory, family_for_generic};
+use platform::font_list::{get_available_families, get_variations_for_family, path_for_codepoint};
+use platform::font_list::{path_from_identifier, rescan_fonts};
+use text::util::is_emoji;
+use webfont::decode_font_data;
 
 use freetype::freetype::FTErrorMethods;
+use freetype::freetype::FT_Error;
 use freetype::freetype::FT_Add_Default_Modules;
-use freetype::freetype::FT_Done_FreeType;
+use freetype::freetype::FT_Done_Library;
 use freetype::freetype::FT_Library;
 use freetype::freetype::FT_Memory;
 use freetype::freetype::FT_New_Library;
 use freetype::freetype::struct_FT_MemoryRec_;
 
+use servo_util::cache::{Cache, LRUCache};
+use servo_util::smallvec::{SmallVec, SmallVec4};
+use style::computed_values::{font_stretch, font_style, font_weight};
+
+use std::ascii::StrAsciiExt;
+use std::cell::RefCell;
+use std::collections::hashmap::{HashMap, HashSet};
+use std::comm::{Disconnected, Empty};
+use std::kinds::marker;
 use std::ptr;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::rt::mutex::{LockGuard, StaticNativeMutex, NATIVE_MUTEX_INIT};
+use std::sync::atomics::{AtomicUint, INIT_ATOMIC_UINT, SeqCst};
+use std::task::TaskBuilder;
+use sync::Arc;
 
 use libc;
-use libc::{c_void, c_long, size_t, malloc};
+use libc::{c_int, c_void, c_long, size_t, malloc};
 use std::mem;
 
-extern fn ft_alloc(_mem: FT_Memory, size: c_long) -> *c_void {
+/// Accounting for the memory FreeType allocates through one library's
+/// `FT_Memory`. The allocation callbacks find it through the `user` pointer.
+struct FreeTypeMemoryStats {
+    bytes_allocated: AtomicUint,
+}
+
+/// Every block handed to FreeType is preceded by a header that records its
+/// size, since `ft_free` isn't told how big the block is. Sixteen bytes keeps
+/// the block itself as aligned as malloc's result.
+static FT_BLOCK_HEADER_SIZE: uint = 16;
+
+unsafe fn memory_stats<'a>(mem: FT_Memory) -> &'a FreeTypeMemoryStats {
+    &*((*mem).user as *FreeTypeMemoryStats)
+}
+
+unsafe fn block_header(block: *c_void) -> *mut c_void {
+    (block as *u8).offset(-(FT_BLOCK_HEADER_SIZE as int)) as *mut c_void
+}
+
+unsafe fn block_from_header(header: *mut c_void, size: uint) -> *c_void {
+    *(header as *mut uint) = size;
+    (header as *u8).offset(FT_BLOCK_HEADER_SIZE as int) as *c_void
+}
+
+extern fn ft_alloc(mem: FT_Memory, size: c_long) -> *c_void {
     unsafe {
-        let ptr = libc::malloc(size as size_t);
-        ptr as *c_void
+        let size = size as uint;
+        let header = libc::malloc((FT_BLOCK_HEADER_SIZE + size) as size_t);
+        if header.is_null() {
+            return ptr::null();
+        }
+        memory_stats(mem).bytes_allocated.fetch_add(size, SeqCst);
+        block_from_header(header, size)
     }
 }
 
-extern fn ft_free(_mem: FT_Memory, block: *c_void) {
+extern fn ft_free(mem: FT_Memory, block: *c_void) {
     unsafe {
-        libc::free(block as *mut c_void);
+        if block.is_null() {
+            return;
+        }
+        let header = block_header(block);
+        memory_stats(mem).bytes_allocated.fetch_sub(*(header as *uint), SeqCst);
+        libc::free(header);
     }
 }
 
-extern fn ft_realloc(_mem: FT_Memory, _cur_size: c_long, new_size: c_long, block: *c_void) -> *c_void {
+extern fn ft_realloc(mem: FT_Memory, _cur_size: c_long, new_size: c_long, block: *c_void) -> *c_void {
     unsafe {
-        let ptr = libc::realloc(block as *mut c_void, new_size as size_t);
-        ptr as *c_void
+        if block.is_null() {
+            return ft_alloc(mem, new_size);
+        }
+        let new_size = new_size as uint;
+        let header = block_header(block);
+        let old_size = *(header as *uint);
+        let new_header = libc::realloc(header, (FT_BLOCK_HEADER_SIZE + new_size) as size_t);
+        if new_header.is_null() {
+            // The old block is untouched, and FreeType reports the failure.
+            return ptr::null();
+        }
+        let bytes_allocated = &memory_stats(mem).bytes_allocated;
+        if new_size > old_size {
+            bytes_allocated.fetch_add(new_size - old_size, SeqCst);
+        } else {
+            bytes_allocated.fetch_sub(old_size - new_size, SeqCst);
+        }
+        block_from_header(new_header, new_size)
     }
 }
 
-#[deriving(Clone)]
+/// Guards the process-wide FreeType library below, and serializes creating
+/// and destroying faces, which touch the library's state. FreeType allows
+/// different faces to be used from different threads at the same time.
+static FREETYPE_LOCK: StaticNativeMutex = NATIVE_MUTEX_INIT;
+
+// The FreeType library shared by every FontContextHandle in the process, the
+// LCD filter it was set up with, and how many FreeTypeLibraryHandles refer to
+// it. Only touched with FREETYPE_LOCK held.
+static mut SHARED_LIBRARY: FT_Library = 0 as FT_Library;
+static mut SHARED_MEMORY: FT_Memory = 0 as FT_Memory;
+static mut SHARED_LCD_FILTER: LcdFilter = LcdFilterNone;
+static mut SHARED_LIBRARY_HANDLES: uint = 0;
+
+/// One reference to the process-wide FreeType library. The library is created
+/// along with the first of these and torn down with the last.
 pub struct FreeTypeLibraryHandle {
     pub ctx: FT_Library,
+    mem: FT_Memory,
+    /// The LCD filter of the library, which is the same for every handle.
+    pub lcd_filter: LcdFilter,
+}
+
+impl FreeTypeLibraryHandle {
+    /// Takes a reference to the shared library, creating it if need be. Only
+    /// the handle that creates the library gets to choose its LCD filter:
+    /// it's library-wide state that the rasterizer reads without taking
+    /// our lock, so it is set once and never changed afterwards.
+    fn acquire(lcd_filter: LcdFilter) -> Result<FreeTypeLibraryHandle, FontError> {
+        unsafe {
+            let _guard = FREETYPE_LOCK.lock();
+            if SHARED_LIBRARY_HANDLES == 0 {
+                let (ctx, mem) = try!(FreeTypeLibraryHandle::create_library(libc::malloc));
+                SHARED_LIBRARY = ctx;
+                SHARED_MEMORY = mem;
+                SHARED_LCD_FILTER = FreeTypeLibraryHandle::set_lcd_filter(ctx, lcd_filter);
+            } else if lcd_filter != SHARED_LCD_FILTER {
+                debug!("FreeType already uses LCD filter {}, not {}",
+                       SHARED_LCD_FILTER, lcd_filter);
+            }
+            SHARED_LIBRARY_HANDLES += 1;
+            Ok(FreeTypeLibraryHandle {
+                ctx: SHARED_LIBRARY,
+                mem: SHARED_MEMORY,
+                lcd_filter: SHARED_LCD_FILTER.clone(),
+            })
+        }
+    }
+
+    /// Creates a library whose FT_Memory record is allocated with `malloc`.
+    /// Nothing is left behind if this fails.
+    unsafe fn create_library(malloc: unsafe extern "C" fn(size_t) -> *mut c_void)
+                             -> Result<(FT_Library, FT_Memory), FontError> {
+        let ptr = malloc(mem::size_of::<struct_FT_MemoryRec_>() as size_t);
+        if ptr.is_null() {
+            debug!("Unable to allocate FreeType memory record");
+            return Err(LibraryError(None));
+        }
+        let stats = box FreeTypeMemoryStats { bytes_allocated: AtomicUint::new(0) };
+        let allocator: &mut struct_FT_MemoryRec_ = mem::transmute(ptr);
+        mem::overwrite(allocator, struct_FT_MemoryRec_ {
+            user: mem::transmute(stats),
+            alloc: ft_alloc,
+            free: ft_free,
+            realloc: ft_realloc,
+        });
+
+        let ctx: FT_Library = ptr::null();
+
+        let result = FT_New_Library(ptr as FT_Memory, &ctx);
+        if !result.succeeded() || ctx.is_null() {
+            debug!("Unable to initialize FreeType library: {}", result);
+            let stats: Box<FreeTypeMemoryStats> = mem::transmute((*(ptr as FT_Memory)).user);
+            drop(stats);
+            libc::free(ptr);
+            return Err(LibraryError(Some(result as int)));
+        }
+
+        // This can't fail; a module that doesn't load is simply missing.
+        FT_Add_Default_Modules(ctx);
+        Ok((ctx, ptr as FT_Memory))
+    }
+
+    /// Takes the lock that must be held while creating or destroying faces.
+    pub fn lock(&self) -> LockGuard<'static> {
+        unsafe { FREETYPE_LOCK.lock() }
+    }
+
+    /// Sets the LCD filter of a newly created library, and returns the filter
+    /// it ends up with: none, if FreeType was built without subpixel rendering.
+    /// Must be called with FREETYPE_LOCK held.
+    unsafe fn set_lcd_filter(ctx: FT_Library, lcd_filter: LcdFilter) -> LcdFilter {
+        let result = FT_Library_SetLcdFilter(ctx, lcd_filter.to_ft_lcd_filter());
+        if !result.succeeded() {
+            debug!("FreeType can't use LCD filter {}: {}", lcd_filter, result);
+            return LcdFilterNone;
+        }
+        lcd_filter
+    }
+
+    /// `options`, with the LCD filter replaced by the one the library uses.
+    fn effective_options(&self, options: RasterizationOptions) -> RasterizationOptions {
+        RasterizationOptions {
+            lcd_filter: self.lcd_filter.clone(),
+            .. options
+        }
+    }
+
+    /// How many bytes FreeType currently has allocated, for the library and
+    /// every face created from it.
+    pub fn bytes_allocated(&self) -> uint {
+        unsafe { memory_stats(self.mem).bytes_allocated.load(SeqCst) }
+    }
+}
+
+// FreeType's LCD filter API, which the freetype bindings don't cover yet.
+extern {
+    fn FT_Library_SetLcdFilter(library: FT_Library, filter: c_int) -> FT_Error;
+}
+
+static FT_LOAD_NO_HINTING: i32 = 1 << 1;
+static FT_LOAD_FORCE_AUTOHINT: i32 = 1 << 5;
+static FT_LOAD_TARGET_NORMAL: i32 = 0 << 16;
+static FT_LOAD_TARGET_LIGHT: i32 = 1 << 16;
+static FT_LOAD_TARGET_LCD: i32 = 3 << 16;
+
+/// How strongly glyph outlines are fitted to the pixel grid.
+#[deriving(Clone, PartialEq, Show)]
+pub enum Hinting {
+    HintingNone,
+    HintingSlight,
+    HintingFull,
+}
+
+/// The filter FreeType applies when rendering for subpixel antialiasing.
+#[deriving(Clone, PartialEq, Show)]
+pub enum LcdFilter {
+    LcdFilterNone,
+    LcdFilterDefault,
+    LcdFilterLight,
+}
+
+/// How glyphs from a font context are loaded and rasterized.
+#[deriving(Clone, PartialEq, Show)]
+pub struct RasterizationOptions {
+    pub hinting: Hinting,
+    /// Process-wide: only honoured for the context that sets up the FreeType
+    /// library. Other contexts get the library's filter instead.
+    pub lcd_filter: LcdFilter,
+    /// Use FreeType's autohinter even for fonts that have their own hints.
+    pub autohint: bool,
+}
+
+impl RasterizationOptions {
+    /// FreeType's defaults, which is what we've always used.
+    pub fn default() -> RasterizationOptions {
+        RasterizationOptions {
+            hinting: HintingFull,
+            lcd_filter: LcdFilterNone,
+            autohint: false,
+        }
+    }
+
+    /// The `FT_LOAD_*` flags that glyphs should be loaded with.
+    pub fn load_flags(&self) -> i32 {
+        let mut flags = match self.hinting {
+            HintingNone => FT_LOAD_NO_HINTING,
+            HintingSlight => FT_LOAD_TARGET_LIGHT,
+            HintingFull if self.lcd_filter != LcdFilterNone => FT_LOAD_TARGET_LCD,
+            HintingFull => FT_LOAD_TARGET_NORMAL,
+        };
+        if self.autohint {
+            flags |= FT_LOAD_FORCE_AUTOHINT;
+        }
+        flags
+    }
+
+}
+
+impl LcdFilter {
+    fn to_ft_lcd_filter(&self) -> c_int {
+        match *self {
+            LcdFilterNone => 0,
+            LcdFilterDefault => 1,
+            LcdFilterLight => 2,
+        }
+    }
+}
+
+/// The font used when nothing installed can be loaded, so that text still
+/// renders on systems without any fonts. To keep it small, it only covers
+/// ASCII and Latin-1.
+pub static LAST_RESORT_FONT: &'static [u8] = include_bin!("fonts/DejaVuSansMono-Latin1.ttf");
+
+/// The identifier the last resort font is cached under. No real family is
+/// called this.
+static LAST_RESORT_IDENTIFIER: &'static str = "<built-in last resort font>";
+
+/// How many times any font context has been refreshed.
+static mut FONT_GENERATION: AtomicUint = INIT_ATOMIC_UINT;
+
+/// The number of faces `FontContextHandle` keeps open for reuse.
+static FONT_HANDLE_CACHE_SIZE: uint = 64;
+
+/// The CSS generic font families.
+static GENERIC_FAMILIES: &'static [&'static str] = &[
+    "serif", "sans-serif", "monospace", "cursive", "fantasy"
+];
+
+/// The generic family tried last if nothing else in a style can be loaded.
+static DEFAULT_GENERIC_FAMILY: &'static str = "sans-serif";
+
+/// The families that handles from a `FontContextHandleFactory` look up in
+/// the background, since nearly every page uses one of them.
+static PREWARM_FAMILIES: &'static [&'static str] = &["serif", "sans-serif", "monospace"];
+
+/// The span of codepoints that share a cached fallback family. Scripts are
+/// laid out in blocks, so neighbouring codepoints usually share a font.
+static UNICODE_BLOCK_SIZE: u32 = 128;
+
+fn unicode_block(codepoint: char) -> u32 {
+    codepoint as u32 / UNICODE_BLOCK_SIZE
+}
+
+/// Why FreeType refused to open a face, if the error means the font file
+/// itself is bad and not worth trying again.
+fn broken_font_file_reason(error: &FontError) -> Option<&'static str> {
+    match *error {
+        UnsupportedFormat(_, Some(code)) => match code {
+            // FT_Err_Unknown_File_Format
+            0x02 => Some("not a font format FreeType supports"),
+            // FT_Err_Invalid_File_Format, _Version, _Table and _Offset, the
+            // stream errors raised by reading past the end of the file, and
+            // the TrueType and CFF table errors. FT_Err_Cannot_Open_Stream
+            // is an I/O error, not a broken file.
+            0x03 | 0x04 | 0x08 | 0x09 | 0x52..0x58 | 0x80..0xBF => Some("invalid font data"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The parts of a `UsedFontStyle` that affect which face gets loaded and how
+/// it is set up. Faces are shared between sizes, which they keep set up
+/// themselves.
+#[deriving(Clone, PartialEq)]
+struct FontHandleKey {
+    identifier: String,
+    weight: font_weight::T,
+    style: font_style::T,
+    stretch: font_stretch::T,
+    features: Vec<(u32, u32)>,
+}
+
+impl FontHandleKey {
+    fn new(identifier: &String, style: &UsedFontStyle) -> FontHandleKey {
+        FontHandleKey {
+            identifier: identifier.clone(),
+            weight: style.weight,
+            style: style.style,
+            stretch: style.stretch,
+            features: Vec::from_slice(style.features.as_slice()),
+        }
+    }
 }
 
+/// Asks fontconfig for the face of each of `families` in `style`, leaving
+/// out families that aren't installed.
+fn match_families(families: Vec<String>, style: &UsedFontStyle)
+                  -> Vec<(FontHandleKey, FontMatch)> {
+    families.move_iter().filter_map(|family| {
+        match path_from_identifier(family.clone(), style) {
+            Ok(font_match) if font_match.exact => {
+                Some((FontHandleKey::new(&family, style), font_match))
+            }
+            _ => None,
+        }
+    }).collect()
+}
+
+struct FontHandleCache {
+    faces: LRUCache<FontHandleKey, Rc<FontFace>>,
+    /// Every installed family, once someone has asked for them.
+    families: Option<Vec<String>>,
+    /// The face identifiers of each family that has been asked about.
+    variations: HashMap<String, Vec<String>>,
+    /// A copy of `LAST_RESORT_FONT`, made the first time it is needed.
+    last_resort_font: Option<Arc<Vec<u8>>>,
+    /// Font templates: the contents of each font file in use, keyed by path
+    /// and face index. Every face opened from a file reads from one mapping,
+    /// which is released along with the last of them.
+    templates: HashMap<(Path, uint), Weak<FontFileData>>,
+    /// The family that last covered a codepoint in each Unicode block, keyed
+    /// by `unicode_block`.
+    codepoint_families: HashMap<u32, String>,
+    /// Faces of font files that FreeType found to be corrupt or in a format
+    /// it doesn't know, keyed by path and face index. They are skipped until
+    /// the next `refresh`.
+    blacklist: HashSet<(Path, uint)>,
+    /// Faces that fontconfig picked on another task, to be opened without
+    /// asking it again.
+    prewarmed: Vec<(FontHandleKey, FontMatch)>,
+    /// Where that task sends its matches, until they have been merged into
+    /// `prewarmed`.
+    prewarm_port: Option<Receiver<Vec<(FontHandleKey, FontMatch)>>>,
+    /// How many times we have had to ask fontconfig for a path. Lets tests
+    /// observe cache hits.
+    path_lookups: uint,
+}
+
+/// A task's way into FreeType and fontconfig. Its caches aren't thread-safe,
+/// so a handle and its clones must stay on the task that created them; send a
+/// `FontContextHandleFactory` to other tasks instead.
 #[deriving(Clone)]
 pub struct FontContextHandle {
     pub ctx: Rc<FreeTypeLibraryHandle>,
+    cache: Rc<RefCell<FontHandleCache>>,
+    /// The installed family fontconfig picks for each generic family.
+    generic_families: Rc<RefCell<HashMap<String, String>>>,
+    options: Rc<RefCell<RasterizationOptions>>,
+    nosend: marker::NoSend,
+}
+
+/// Creates `FontContextHandle`s with the same settings on any task. Every
+/// handle uses the process-wide FreeType library, which serializes the
+/// operations that need it.
+#[deriving(Clone)]
+pub struct FontContextHandleFactory {
+    options: RasterizationOptions,
+}
+
+impl FontContextHandleFactory {
+    pub fn new() -> FontContextHandleFactory {
+        FontContextHandleFactory::with_options(RasterizationOptions::default())
+    }
+
+    pub fn with_options(options: RasterizationOptions) -> FontContextHandleFactory {
+        FontContextHandleFactory {
+            options: options,
+        }
+    }
+
+    /// Creates a handle for the calling task. The faces of the common generic
+    /// families are looked up in the background, so that layout doesn't wait
+    /// on fontconfig for the first text it sees.
+    pub fn create_handle(&self) -> Result<FontContextHandle, FontError> {
+        let handle = try!(FontContextHandle::try_new_with_options(self.options.clone()));
+        handle.prewarm_in_background(PREWARM_FAMILIES, &FontContextHandle::default_style());
+        Ok(handle)
+    }
 }
 
 impl Drop for FreeTypeLibraryHandle {
     fn drop(&mut self) {
         assert!(self.ctx.is_not_null());
-        unsafe { FT_Done_FreeType(self.ctx) };
+        unsafe {
+            let _guard = FREETYPE_LOCK.lock();
+            SHARED_LIBRARY_HANDLES -= 1;
+            if SHARED_LIBRARY_HANDLES > 0 {
+                return;
+            }
+
+            // Unlike FT_Done_FreeType, this leaves the FT_Memory to us.
+            FT_Done_Library(self.ctx);
+            let stats: Box<FreeTypeMemoryStats> = mem::transmute((*self.mem).user);
+            drop(stats);
+            libc::free(self.mem as *mut c_void);
+            SHARED_LIBRARY = ptr::null();
+            SHARED_MEMORY = ptr::null();
+        }
     }
 }
 
 impl FontContextHandle {
     pub fn new() -> FontContextHandle {
+        match FontContextHandle::try_new() {
+            Ok(handle) => handle,
+            Err(error) => fail!("Unable to initialize FreeType library: {}", error),
+        }
+    }
+
+    /// Like `new`, but reports a FreeType library that can't be set up
+    /// instead of failing the task.
+    pub fn try_new() -> Result<FontContextHandle, FontError> {
+        FontContextHandle::try_new_with_options(RasterizationOptions::default())
+    }
+
+    pub fn new_with_options(options: RasterizationOptions) -> FontContextHandle {
+        match FontContextHandle::try_new_with_options(options) {
+            Ok(handle) => handle,
+            Err(error) => fail!("Unable to initialize FreeType library: {}", error),
+        }
+    }
+
+    /// Creates a handle that can also use the fonts in `directories`, e.g.
+    /// ones shipped alongside an embedding application. See
+    /// `add_font_directory`.
+    pub fn new_with_font_dirs(directories: &[Path]) -> Result<FontContextHandle, FontError> {
+        let handle = try!(FontContextHandle::try_new());
+        for directory in directories.iter() {
+            try!(handle.add_font_directory(directory));
+        }
+        Ok(handle)
+    }
+
+    pub fn try_new_with_options(options: RasterizationOptions)
+                                -> Result<FontContextHandle, FontError> {
+        let library = try!(FreeTypeLibraryHandle::acquire(options.lcd_filter.clone()));
+        let options = library.effective_options(options);
+
+        Ok(FontContextHandle {
+            ctx: Rc::new(library),
+            cache: Rc::new(RefCell::new(FontHandleCache {
+                faces: LRUCache::new(FONT_HANDLE_CACHE_SIZE),
+                families: None,
+                variations: HashMap::new(),
+                last_resort_font: None,
+                templates: HashMap::new(),
+                codepoint_families: HashMap::new(),
+                blacklist: HashSet::new(),
+                prewarmed: vec!(),
+                prewarm_port: None,
+                path_lookups: 0,
+            })),
+            generic_families: Rc::new(RefCell::new(FontContextHandle::generic_families())),
+            options: Rc::new(RefCell::new(options)),
+            nosend: marker::NoSend,
+        })
+    }
+
+    /// A factory for handles like this one on other tasks.
+    pub fn factory(&self) -> FontContextHandleFactory {
+        FontContextHandleFactory::with_options(self.rasterization_options())
+    }
+
+    /// Asks fontconfig for the installed family behind each generic family.
+    fn generic_families() -> HashMap<String, String> {
+        let mut generic_families = HashMap::new();
+        for generic in GENERIC_FAMILIES.iter() {
+            match family_for_generic(*generic) {
+                Some(family) => {
+                    debug!("generic family {:s} is {:s}", *generic, family);
+                    generic_families.insert(generic.to_string(), family);
+                }
+                None => debug!("no font for generic family {:s}", *generic),
+            }
+        }
+        generic_families
+    }
+
+    pub fn rasterization_options(&self) -> RasterizationOptions {
+        self.options.borrow().clone()
+    }
+
+    /// Changes how glyphs are loaded by font handles created from now on.
+    /// The LCD filter can't be changed: it belongs to the FreeType library,
+    /// which every context in the process shares, and stays whatever the
+    /// first context asked for.
+    pub fn set_rasterization_options(&self, options: RasterizationOptions) {
+        *self.options.borrow_mut() = self.ctx.effective_options(options);
+    }
+
+    /// Forgets every cached face, e.g. because the set of installed fonts changed.
+    /// Handles that are still in use keep their faces alive.
+    pub fn invalidate(&self) {
+        let mut cache = self.cache.borrow_mut();
+        cache.faces.evict_all();
+        cache.families = None;
+        cache.variations.clear();
+        cache.codepoint_families.clear();
+        cache.templates.clear();
+        // Matches still on their way may be for the old set of fonts.
+        cache.prewarmed.clear();
+        cache.prewarm_port = None;
+    }
+
+    /// Picks up fonts installed or removed since fontconfig was last asked,
+    /// including those in directories given to `add_font_directory`, and
+    /// forgets everything cached about the old set. Handles created before
+    /// stay valid. Bumps the font generation, so that caches elsewhere know to
+    /// resolve their fonts again.
+    pub fn refresh(&self) {
+        if !rescan_fonts() {
+            warn!("Unable to rescan installed fonts");
+        }
+        *self.generic_families.borrow_mut() = FontContextHandle::generic_families();
+        self.invalidate();
+        // Broken files may have been replaced.
+        self.cache.borrow_mut().blacklist.clear();
         unsafe {
+            FONT_GENERATION.fetch_add(1, SeqCst);
+        }
+    }
 
-            let ptr = libc::malloc(mem::size_of::<struct_FT_MemoryRec_>() as size_t);
-            let allocator: &mut struct_FT_MemoryRec_ = mem::transmute(ptr);
-            mem::overwrite(allocator, struct_FT_MemoryRec_ {
-                user: ptr::null(),
-                alloc: ft_alloc,
-                free: ft_free,
-                realloc: ft_realloc,
-            });
+    /// Makes the fonts in `directory` available to every font context in this
+    /// process, without installing them, and refreshes this context. A family
+    /// found there hides installed fonts of the same family.
+    pub fn add_font_directory(&self, directory: &Path) -> Result<(), FontError> {
+        // fontconfig happily adds directories that don't exist.
+        if !directory.is_dir() {
+            return Err(IoError(directory.display().to_str()));
+        }
+        let directory = directory.as_str().unwrap_or("").to_string();
+        if !add_font_directory(directory.as_slice()) {
+            return Err(IoError(directory));
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Counts the times the set of available fonts has changed. Anything
+    /// derived from a font context is stale once this moves on.
+    pub fn generation(&self) -> uint {
+        unsafe { FONT_GENERATION.load(SeqCst) }
+    }
+
+    /// The names of every installed font family, without duplicates.
+    pub fn available_families(&self) -> Vec<String> {
+        let mut cache = self.cache.borrow_mut();
+        if cache.families.is_none() {
+            let mut families = vec!();
+            get_available_families(|family| families.push(family));
+            cache.families = Some(families);
+        }
+        cache.families.get_ref().clone()
+    }
+
+    /// The identifiers of the faces in `family`, suitable for
+    /// `create_font_from_identifier`.
+    pub fn variations_for_family(&self, family: &str) -> Vec<String> {
+        let mut cache = self.cache.borrow_mut();
+        cache.variations.find_or_insert_with(family.to_string(), |_| {
+            let mut variations = vec!();
+            get_variations_for_family(family, |identifier| variations.push(identifier));
+            variations
+        }).clone()
+    }
+
+    /// Maps a CSS generic family to an installed family; other names are
+    /// returned unchanged.
+    pub fn resolve_generic_family(&self, family: &str) -> String {
+        for (generic, installed) in self.generic_families.borrow().iter() {
+            if generic.as_slice().eq_ignore_ascii_case(family) {
+                return installed.clone();
+            }
+        }
+        family.to_string()
+    }
+
+    /// The families to try, in order, when `name` cannot be loaded: the
+    /// families listed after it in `style`, then the default generic family.
+    pub fn fallback_families(&self, name: &str, style: &UsedFontStyle) -> Vec<String> {
+        let start = match style.families.iter().position(|family| family.as_slice() == name) {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        let mut families: Vec<String> = vec!();
+        let rest = style.families.slice_from(start).iter().map(|family| family.as_slice());
+        for family in rest.chain(Some(DEFAULT_GENERIC_FAMILY).move_iter()) {
+            let resolved = self.resolve_generic_family(family);
+            if resolved.as_slice() != name && !families.contains(&resolved) {
+                families.push(resolved);
+            }
+        }
+        families
+    }
+
+    /// Loads `name`, or failing that the first of `fallbacks` that can be
+    /// loaded, or failing that the built-in last resort font. Fonts that are
+    /// installed but can't be opened are passed over like missing ones.
+    fn create_font_with_fallbacks(&self, name: String, style: &UsedFontStyle,
+                                  fallbacks: Vec<String>) -> Result<FontHandle, FontError> {
+        match self.create_font_from_family(name.clone(), style) {
+            Ok(handle) => return Ok(handle),
+            Err(error) => debug!("unable to load {:s}: {}", name, error),
+        }
+
+        for family in fallbacks.move_iter() {
+            debug!("falling back from {:s} to {:s}", name, family);
+            match self.create_font_from_family(family, style) {
+                Ok(handle) => return Ok(handle),
+                Err(_) => {}
+            }
+        }
+
+        debug!("no installed font for {:s}, using the last resort font", name);
+        self.last_resort_font(style)
+    }
+
+    /// Loads the font built into Servo, which needs nothing to be installed.
+    pub fn last_resort_font(&self, style: &UsedFontStyle) -> Result<FontHandle, FontError> {
+        let key = FontHandleKey::new(&LAST_RESORT_IDENTIFIER.to_string(), style);
+        let bytes = {
+            let mut cache = self.cache.borrow_mut();
+            match cache.faces.find(&key) {
+                Some(face) => return FontHandle::from_shared_face(self, face, style.pt_size),
+                None => {}
+            }
+            if cache.last_resort_font.is_none() {
+                let bytes = LAST_RESORT_FONT.iter().map(|&byte| byte).collect();
+                cache.last_resort_font = Some(Arc::new(bytes));
+            }
+            cache.last_resort_font.get_ref().clone()
+        };
+
+        let result = FontHandle::new_from_shared_buffer(self, bytes, 0, style);
+        match result {
+            Ok(ref handle) => {
+                self.cache.borrow_mut().faces.insert(key, handle.shared_face.clone());
+            }
+            Err(_) => {}
+        }
+        result
+    }
 
-            let ctx: FT_Library = ptr::null();
+    /// The style of text that no style sheet has touched.
+    fn default_style() -> UsedFontStyle {
+        FontStyle {
+            pt_size: 16.0,
+            weight: font_weight::Weight400,
+            style: font_style::normal,
+            stretch: font_stretch::normal,
+            families: vec!(),
+            features: SmallVec4::new(),
+        }
+    }
 
-            let result = FT_New_Library(ptr as FT_Memory, &ctx);
-            if !result.succeeded() { fail!("Unable to initialize FreeType library"); }
+    /// Loads `families` in `style` into the caches, so that asking for them
+    /// later costs nothing. Generic families are loaded as the installed
+    /// families they map to, and families that can't be loaded are skipped.
+    pub fn prewarm(&self, families: &[&str], style: &UsedFontStyle) {
+        for family in families.iter() {
+            let name = self.resolve_generic_family(*family);
+            match self.create_font_from_family(name.clone(), style) {
+                Ok(_) => debug!("prewarmed {:s}", name),
+                Err(error) => debug!("unable to prewarm {:s}: {}", name, error),
+            }
+        }
+    }
 
-            FT_Add_Default_Modules(ctx);
+    /// Does the slow part of `prewarm`, asking fontconfig for the faces, on
+    /// another task. The matches are picked up by the first request for a
+    /// face after they arrive; until then, requests ask fontconfig themselves.
+    pub fn prewarm_in_background(&self, families: &[&str], style: &UsedFontStyle) {
+        let families: Vec<String> = families.iter().map(|family| {
+            self.resolve_generic_family(*family)
+        }).collect();
+        let style = style.clone();
+        let (prewarm_chan, prewarm_port) = channel();
+        self.cache.borrow_mut().prewarm_port = Some(prewarm_port);
+        TaskBuilder::new().named("FontPrewarmTask").spawn(proc() {
+            // The handle may have gone, or been invalidated, by now.
+            let _ = prewarm_chan.send_opt(match_families(families, &style));
+        });
+    }
+
+    /// Waits for the matches from `prewarm_in_background`, if any are still
+    /// to come.
+    pub fn finish_prewarm(&self) {
+        let mut cache = self.cache.borrow_mut();
+        let matches = match cache.prewarm_port {
+            Some(ref port) => port.recv_opt().unwrap_or(vec!()),
+            None => return,
+        };
+        cache.prewarm_port = None;
+        cache.prewarmed.push_all_move(matches);
+    }
 
-            FontContextHandle {
-                ctx: Rc::new(FreeTypeLibraryHandle { ctx: ctx }),
+    /// The face fontconfig picked for `name` in `style` in the background,
+    /// merging in any matches that have arrived since the last request.
+    fn prewarmed_match(&self, name: &String, style: &UsedFontStyle) -> Option<FontMatch> {
+        let mut cache = self.cache.borrow_mut();
+        let received = match cache.prewarm_port {
+            Some(ref port) => Some(port.try_recv()),
+            None => None,
+        };
+        match received {
+            Some(Ok(matches)) => {
+                cache.prewarm_port = None;
+                cache.prewarmed.push_all_move(matches);
+            }
+            Some(Err(Disconnected)) => cache.prewarm_port = None,
+            Some(Err(Empty)) | None => {}
+        }
+
+        let key = FontHandleKey::new(name, style);
+        cache.prewarmed.iter().find(|&&(ref prewarmed_key, _)| *prewarmed_key == key)
+                              .map(|&(_, ref font_match)| font_match.clone())
+    }
+
+    /// Loads exactly the family `name`, without falling back to other families.
+    pub fn create_font_from_family(&self, name: String, style: &UsedFontStyle)
+                                   -> Result<FontHandle, FontError> {
+        let key = FontHandleKey::new(&name, style);
+        match self.cache.borrow_mut().faces.find(&key) {
+            Some(face) => {
+                debug!("font handle cache hit for {:s}", name);
+                return FontHandle::from_shared_face(self, face, style.pt_size);
+            }
+            None => {}
+        }
+
+        let font_match = match self.prewarmed_match(&name, style) {
+            Some(font_match) => Ok(font_match),
+            None => {
+                debug!("Creating font handle for {:s}", name);
+                self.cache.borrow_mut().path_lookups += 1;
+                path_from_identifier(name.clone(), style)
+            }
+        };
+        let result = match font_match {
+            Ok(ref font_match) if font_match.exact => {
+                debug!("Opening font face {} ({:u})", font_match.path.display(), font_match.index);
+                self.create_font_from_template(&font_match.path, font_match.index, style)
+            }
+            _ => Err(NotFound(name)),
+        };
+        match result {
+            Ok(ref handle) => {
+                self.cache.borrow_mut().faces.insert(key, handle.shared_face.clone());
             }
+            Err(_) => {}
         }
+        result
+    }
+
+    /// The contents of the font file at `path`, for face `face_index`. The
+    /// file is only opened if no live face already uses it.
+    pub fn font_template(&self, path: &Path, face_index: uint)
+                         -> Result<Rc<FontFileData>, FontError> {
+        let key = (path.clone(), face_index);
+        let mut cache = self.cache.borrow_mut();
+        match cache.templates.find(&key).and_then(|template| template.upgrade()) {
+            Some(file_data) => return Ok(file_data),
+            None => {}
+        }
+
+        debug!("Opening font file {}", path.display());
+        let file_data = match FontFileData::open(path) {
+            Ok(file_data) => Rc::new(file_data),
+            Err(error) => {
+                debug!("opening {} failed: {}", path.display(), error);
+                return Err(IoError(path.display().to_str()));
+            }
+        };
+        // Forget templates whose faces have all gone before adding another.
+        let dead: Vec<(Path, uint)> = cache.templates.iter().filter_map(|(dead_key, template)| {
+            if template.upgrade().is_none() { Some(dead_key.clone()) } else { None }
+        }).collect();
+        for dead_key in dead.iter() {
+            cache.templates.remove(dead_key);
+        }
+        cache.templates.insert(key, file_data.downgrade());
+        Ok(file_data)
+    }
+
+    /// Opens face `face_index` of the font file at `path`, sharing the file's
+    /// contents with every other face opened from it. A face that turns out
+    /// to be broken is blacklisted, and fails straight away from then on.
+    fn create_font_from_template(&self, path: &Path, face_index: uint, style: &UsedFontStyle)
+                                 -> Result<FontHandle, FontError> {
+        let key = (path.clone(), face_index);
+        if self.cache.borrow().blacklist.contains(&key) {
+            return Err(UnsupportedFormat(path.display().to_str(), None));
+        }
+
+        let file_data = try!(self.font_template(path, face_index));
+        let result = FontHandle::new_from_file_data(self, file_data, face_index, path, style);
+        match result {
+            Err(ref error) => match broken_font_file_reason(error) {
+                Some(reason) => {
+                    warn!("Skipping font file {} (face {:u}): {:s} ({})",
+                          path.display(), face_index, reason, error);
+                    self.cache.borrow_mut().blacklist.insert(key);
+                }
+                None => {}
+            },
+            Ok(_) => {}
+        }
+        result
+    }
+
+    /// Whether face `face_index` of the font file at `path` has been found
+    /// to be broken since the last `refresh`.
+    pub fn is_blacklisted(&self, path: &Path, face_index: uint) -> bool {
+        self.cache.borrow().blacklist.contains(&(path.clone(), face_index))
+    }
+
+    /// Loads a face that has a glyph for `codepoint`, for text the
+    /// requested families cannot display.
+    pub fn find_font_for_codepoint(&self, codepoint: char, style: &UsedFontStyle)
+                                   -> Result<FontHandle, FontError> {
+        let block = unicode_block(codepoint);
+        let cached = self.cache.borrow().codepoint_families.find_copy(&block);
+        match cached {
+            Some(family) => {
+                match self.create_font_from_family(family, style) {
+                    Ok(handle) => {
+                        if handle.glyph_index(codepoint).is_some() {
+                            return Ok(handle);
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+            None => {}
+        }
+
+        debug!("Looking for a font covering U+{:04X}", codepoint as u32);
+        self.cache.borrow_mut().path_lookups += 1;
+        let not_found = || NotFound(format!("U+{:04X}", codepoint as u32));
+        let font_match = match path_for_codepoint(codepoint, style, is_emoji(codepoint)) {
+            Ok(font_match) => font_match,
+            Err(()) => return Err(not_found()),
+        };
+        debug!("Opening font face {} ({:u}) for U+{:04X}",
+               font_match.path.display(), font_match.index, codepoint as u32);
+        let handle = try!(self.create_font_from_template(&font_match.path, font_match.index,
+                                                         style));
+        // fontconfig returns its best match even when nothing covers the codepoint.
+        if handle.glyph_index(codepoint).is_none() {
+            return Err(not_found());
+        }
+        self.cache.borrow_mut().codepoint_families.insert(block, font_match.matched_family);
+        Ok(handle)
     }
 }
 
 impl FontContextHandleMethods for FontContextHandle {
     fn create_font_from_identifier(&self, name: String, style: UsedFontStyle)
-                                -> Result<FontHandle, ()> {
-        debug!("Creating font handle for {:s}", name);
-        path_from_identifier(name, &style).and_then(|file_name| {
-            debug!("Opening font face {:s}", file_name);
-            FontHandle::new_from_file(self, file_name.as_slice(), &style)
-        })
+                                -> Result<FontHandle, FontError> {
+        let fallbacks = self.fallback_families(name.as_slice(), &style);
+        self.create_font_with_fallbacks(name, &style, fallbacks)
+    }
+
+    fn create_font_from_buffer(&self, bytes: Arc<Vec<u8>>, face_index: uint,
+                               style: &UsedFontStyle) -> Result<FontHandle, FontError> {
+        debug!("Creating font handle from {:u} bytes (face {:u})", bytes.len(), face_index);
+        let description = format!("font buffer ({:u} bytes, face {:u})", bytes.len(), face_index);
+        let bytes = try!(decode_font_data(bytes, description.as_slice()));
+        FontHandle::new_from_shared_buffer(self, bytes, face_index, style)
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use super::{FONT_HANDLE_CACHE_SIZE, FontContextHandle, FreeTypeLibraryHandle};
+    use super::{HintingNone, HintingSlight, LcdFilterDefault, LcdFilterLight, LcdFilterNone};
+    use super::RasterizationOptions;
+    use font::{FontHandleMethods, FontStyle, IoError, LibraryError, NotFound, test_style};
+    use font_context::FontContextHandleMethods;
+    use platform::font::{FontFileData, FontFileMapped, FontHandle, FontSourceFileData};
+    use platform::font::test::rename_family;
+    use platform::font_list::path_from_identifier;
+    use servo_util::smallvec::SmallVec;
+    use style::computed_values::{font_stretch, font_weight};
+    use text::util::true_type_tag;
+
+    use libc::{c_void, size_t};
+    use std::io::{File, TempDir};
+    use std::ptr;
+    use std::rc::Rc;
+
+    fn lookups(fctx: &FontContextHandle) -> uint {
+        fctx.cache.borrow().path_lookups
+    }
+
+    unsafe extern "C" fn failing_malloc(_size: size_t) -> *mut c_void {
+        ptr::mut_null()
+    }
+
+    #[test]
+    fn test_library_allocation_failure() {
+        unsafe {
+            match FreeTypeLibraryHandle::create_library(failing_malloc) {
+                Err(LibraryError(None)) => {}
+                Err(error) => fail!("unexpected error {}", error),
+                Ok(_) => fail!("created a library without memory"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rasterization_options() {
+        let default = FontContextHandle::new();
+        let unhinted = FontContextHandle::new_with_options(RasterizationOptions {
+            hinting: HintingNone,
+            lcd_filter: LcdFilterNone,
+            autohint: false,
+        });
+        let style = test_style(12.0);
+        let hinted_font = default.create_font_from_family("DejaVu Sans".to_string(),
+                                                          &style).unwrap();
+        let unhinted_font = unhinted.create_font_from_family("DejaVu Sans".to_string(),
+                                                             &style).unwrap();
+        assert_eq!(hinted_font.load_flags, 0);
+        assert!(unhinted_font.load_flags != hinted_font.load_flags);
+        let glyph = hinted_font.glyph_index('A').unwrap();
+        assert!(unhinted_font.glyph_h_advance(glyph).is_some());
+    }
+
+    #[test]
+    fn test_set_rasterization_options() {
+        let fctx = FontContextHandle::new();
+        let options = RasterizationOptions {
+            hinting: HintingSlight,
+            lcd_filter: LcdFilterDefault,
+            autohint: true,
+        };
+        let lcd_filter = fctx.rasterization_options().lcd_filter;
+        fctx.set_rasterization_options(options.clone());
+        let options = RasterizationOptions { lcd_filter: lcd_filter, .. options };
+        assert_eq!(fctx.rasterization_options(), options);
+        let font = fctx.create_font_from_family("DejaVu Sans".to_string(),
+                                                &test_style(12.0)).unwrap();
+        assert_eq!(font.load_flags, options.load_flags());
+        fctx.set_rasterization_options(RasterizationOptions::default());
+    }
+
+    #[test]
+    fn test_lcd_filter_is_process_wide() {
+        let a = FontContextHandle::new();
+        let a_options = a.rasterization_options();
+        let other_filter = match a_options.lcd_filter {
+            LcdFilterLight => LcdFilterDefault,
+            _ => LcdFilterLight,
+        };
+        let b = FontContextHandle::new_with_options(RasterizationOptions {
+            hinting: HintingSlight,
+            lcd_filter: other_filter,
+            autohint: false,
+        });
+        assert_eq!(a.rasterization_options(), a_options);
+        assert_eq!(a.ctx.lcd_filter, a_options.lcd_filter);
+        assert_eq!(b.rasterization_options().lcd_filter, a_options.lcd_filter);
+        assert_eq!(b.rasterization_options().hinting, HintingSlight);
+    }
+
+    #[test]
+    fn test_available_families_are_cached() {
+        let fctx = FontContextHandle::new();
+        let families = fctx.available_families();
+        assert!(families.contains(&"DejaVu Sans".to_string()));
+        assert!(fctx.cache.borrow().families.is_some());
+        assert_eq!(fctx.available_families(), families);
+
+        fctx.invalidate();
+        assert!(fctx.cache.borrow().families.is_none());
+    }
+
+    #[test]
+    fn test_variations_for_family() {
+        let fctx = FontContextHandle::new();
+        let variations = fctx.variations_for_family("DejaVu Sans");
+        assert!(variations.len() > 0);
+        for identifier in variations.move_iter() {
+            assert!(fctx.create_font_from_family(identifier, &test_style(12.0)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_last_resort_font() {
+        let fctx = FontContextHandle::new();
+        let style = test_style(12.0);
+        let fallbacks = vec!("NoSuchFontYYY".to_string());
+        let font = fctx.create_font_with_fallbacks("NoSuchFontZZZ".to_string(), &style,
+                                                   fallbacks).unwrap();
+        assert_eq!(font.family_name(), "DejaVu Sans Mono".to_string());
+        assert!(font.glyph_index('A').is_some());
+        assert!(font.glyph_index('\xe9').is_some());
+        assert!(font.glyph_index('\u0100').is_none());
+
+        // The embedded data is copied once and its face reused.
+        let again = fctx.last_resort_font(&style).unwrap();
+        assert_eq!(again.face, font.face);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(FontContextHandle::try_new().is_ok());
+    }
+
+    #[test]
+    fn test_freetype_memory_is_accounted() {
+        // The library is shared with tests running alongside this one, so
+        // only the lower bound is predictable.
+        let fctx = FontContextHandle::new();
+        let handle = fctx.create_font_from_family("DejaVu Sans".to_string(),
+                                                  &test_style(12.0)).unwrap();
+        assert!(handle.glyph_h_advance(handle.glyph_index('A').unwrap()).is_some());
+        assert!(fctx.ctx.bytes_allocated() > 0);
+    }
+
+    #[test]
+    fn test_library_is_shared_between_threads() {
+        let fctx = FontContextHandle::new();
+        let library = fctx.ctx.ctx as uint;
+
+        let (tx, rx) = channel();
+        for _ in range(0u, 4) {
+            let tx = tx.clone();
+            spawn(proc() {
+                let fctx = FontContextHandle::new();
+                let handle = fctx.create_font_from_family("DejaVu Sans".to_string(),
+                                                          &test_style(12.0)).unwrap();
+                let advance = handle.glyph_h_advance(handle.glyph_index('A').unwrap());
+                tx.send((fctx.ctx.ctx as uint, advance.is_some()));
+            });
+        }
+        for _ in range(0u, 4) {
+            let (thread_library, loaded) = rx.recv();
+            assert_eq!(thread_library, library);
+            assert!(loaded);
+        }
+    }
+
+    #[test]
+    fn test_factory_creates_handles_on_many_tasks() {
+        let factory = FontContextHandle::new().factory();
+        let (tx, rx) = channel();
+        for task in range(0u, 8) {
+            let (tx, factory) = (tx.clone(), factory.clone());
+            spawn(proc() {
+                let fctx = factory.create_handle().unwrap();
+                let mut loaded = 0u;
+                for round in range(0u, 10) {
+                    let style = test_style(8.0 + ((task + round) % 5) as f64 * 4.0);
+                    for family in ["DejaVu Sans", "DejaVu Serif", "DejaVu Sans Mono"].iter() {
+                        let handle = fctx.create_font_from_family(family.to_string(),
+                                                                  &style).unwrap();
+                        let glyph = handle.glyph_index('g').unwrap();
+                        if handle.glyph_h_advance(glyph).is_some() {
+                            loaded += 1;
+                        }
+                    }
+                    // Release the faces too, while other tasks are creating theirs.
+                    fctx.invalidate();
+                }
+                tx.send(loaded);
+            });
+        }
+        for _ in range(0u, 8) {
+            assert_eq!(rx.recv(), 30);
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_shares_face() {
+        let fctx = FontContextHandle::new();
+        let first = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     test_style(12.0)).unwrap();
+        assert_eq!(lookups(&fctx), 1);
+        let second = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                      test_style(12.0)).unwrap();
+        assert_eq!(lookups(&fctx), 1);
+        assert_eq!(first.face, second.face);
+    }
+
+    #[test]
+    fn test_cache_is_shared_between_clones() {
+        let fctx = FontContextHandle::new();
+        let clone = fctx.clone();
+        fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                         test_style(12.0)).unwrap();
+        clone.create_font_from_identifier("DejaVu Sans".to_string(),
+                                          test_style(12.0)).unwrap();
+        assert_eq!(lookups(&fctx), 1);
+    }
+
+    #[test]
+    fn test_cache_keys_on_style() {
+        let fctx = FontContextHandle::new();
+        let regular = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                       test_style(12.0)).unwrap();
+        let mut bold_style = test_style(12.0);
+        bold_style.weight = font_weight::Weight700;
+        let bold = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                    bold_style).unwrap();
+        assert_eq!(lookups(&fctx), 2);
+        assert!(regular.face != bold.face);
+    }
+
+    #[test]
+    fn test_sizes_share_face() {
+        let fctx = FontContextHandle::new();
+        let small = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     test_style(12.0)).unwrap();
+        let large = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     test_style(24.0)).unwrap();
+        assert_eq!(lookups(&fctx), 1);
+        assert_eq!(small.face, large.face);
+        assert!(small.get_metrics().em_size < large.get_metrics().em_size);
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let fctx = FontContextHandle::new();
+        let first = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     test_style(12.0)).unwrap();
+        fctx.invalidate();
+        fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                         test_style(12.0)).unwrap();
+        assert_eq!(lookups(&fctx), 2);
+        // Outstanding handles keep their face.
+        assert!(first.glyph_index('A').is_some());
+    }
+
+    /// A different style for each `i` up to 81.
+    fn numbered_style(i: uint) -> FontStyle {
+        let weights = [font_weight::Weight100, font_weight::Weight200, font_weight::Weight300,
+                       font_weight::Weight400, font_weight::Weight500, font_weight::Weight600,
+                       font_weight::Weight700, font_weight::Weight800, font_weight::Weight900];
+        let stretches = [font_stretch::ultra_condensed, font_stretch::extra_condensed,
+                         font_stretch::condensed, font_stretch::semi_condensed,
+                         font_stretch::normal, font_stretch::semi_expanded,
+                         font_stretch::expanded, font_stretch::extra_expanded,
+                         font_stretch::ultra_expanded];
+        let mut style = test_style(12.0);
+        style.weight = weights[i % 9];
+        style.stretch = stretches[i / 9];
+        style
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let fctx = FontContextHandle::new();
+        for i in range(0, FONT_HANDLE_CACHE_SIZE + 1) {
+            fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                             numbered_style(i)).unwrap();
+        }
+        assert_eq!(lookups(&fctx), FONT_HANDLE_CACHE_SIZE + 1);
+
+        // The most recent style is still cached; the first was evicted.
+        fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                         numbered_style(FONT_HANDLE_CACHE_SIZE)).unwrap();
+        assert_eq!(lookups(&fctx), FONT_HANDLE_CACHE_SIZE + 1);
+        fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                         numbered_style(0)).unwrap();
+        assert_eq!(lookups(&fctx), FONT_HANDLE_CACHE_SIZE + 2);
+    }
+
+    #[test]
+    fn test_unknown_family_is_not_found() {
+        let fctx = FontContextHandle::new();
+        match fctx.create_font_from_family("NoSuchFontZZZ".to_string(), &test_style(12.0)) {
+            Err(NotFound(ref name)) => assert_eq!(name.as_slice(), "NoSuchFontZZZ"),
+            Err(error) => fail!("unexpected error {}", error),
+            Ok(handle) => fail!("found {} instead", handle.family_name()),
+        }
+    }
+
+    #[test]
+    fn test_unknown_family_falls_back_to_generic() {
+        let fctx = FontContextHandle::new();
+        let mut style = test_style(12.0);
+        style.families = vec!("NoSuchFontZZZ".to_string(), "sans-serif".to_string());
+        let handle = fctx.create_font_from_identifier("NoSuchFontZZZ".to_string(),
+                                                      style).unwrap();
+        assert_eq!(handle.family_name(), fctx.resolve_generic_family("sans-serif"));
+    }
+
+    #[test]
+    fn test_unknown_family_falls_back_to_later_family() {
+        let fctx = FontContextHandle::new();
+        let mut style = test_style(12.0);
+        style.families = vec!("NoSuchFontZZZ".to_string(), "monospace".to_string());
+        let handle = fctx.create_font_from_identifier("NoSuchFontZZZ".to_string(),
+                                                      style).unwrap();
+        assert_eq!(handle.family_name(), fctx.resolve_generic_family("monospace"));
+    }
+
+    #[test]
+    fn test_fallback_families() {
+        let fctx = FontContextHandle::new();
+        let mut style = test_style(12.0);
+        style.families = vec!("Skipped".to_string(), "NoSuchFontZZZ".to_string(),
+                              "Other".to_string(), "sans-serif".to_string());
+        let sans = fctx.resolve_generic_family("sans-serif");
+        assert_eq!(fctx.fallback_families("NoSuchFontZZZ", &style),
+                   vec!("Other".to_string(), sans));
+    }
+
+    #[test]
+    fn test_find_font_for_codepoint() {
+        // DejaVu Sans Mono has no Hebrew; something else installed must.
+        let fctx = FontContextHandle::new();
+        let style = test_style(12.0);
+        let mono = fctx.create_font_from_family("DejaVu Sans Mono".to_string(),
+                                                &style).unwrap();
+        assert!(mono.glyph_index('\u05D0').is_none());
+
+        let handle = fctx.find_font_for_codepoint('\u05D0', &style).unwrap();
+        assert!(handle.glyph_index('\u05D0').unwrap() != 0);
+    }
+
+    #[test]
+    fn test_codepoint_family_is_cached_per_block() {
+        let fctx = FontContextHandle::new();
+        let style = test_style(12.0);
+        fctx.find_font_for_codepoint('\u05D0', &style).unwrap();
+        let before = lookups(&fctx);
+        // Bet is in the same block as alef.
+        let handle = fctx.find_font_for_codepoint('\u05D1', &style).unwrap();
+        assert!(handle.glyph_index('\u05D1').is_some());
+        assert_eq!(lookups(&fctx), before + 1);
+
+        fctx.invalidate();
+        assert!(fctx.cache.borrow().codepoint_families.is_empty());
+    }
+
+    #[test]
+    fn test_codepoint_without_glyph_is_not_found() {
+        // Whether or not a CJK font is installed, we never hand back a face
+        // that lacks the glyph.
+        let fctx = FontContextHandle::new();
+        match fctx.find_font_for_codepoint('\u4E2D', &test_style(12.0)) {
+            Ok(handle) => assert!(handle.glyph_index('\u4E2D').is_some()),
+            Err(error) => assert_eq!(error, NotFound("U+4E2D".to_string())),
+        }
+    }
+
+    fn file_data(handle: &FontHandle) -> Rc<FontFileData> {
+        match handle.shared_face.source {
+            FontSourceFileData(ref file_data) => file_data.clone(),
+            _ => fail!("{} was not loaded from a template", handle.face_name()),
+        }
+    }
+
+    #[test]
+    fn test_styles_share_font_template() {
+        // DejaVu Math TeX Gyre only has a regular face, which is emboldened
+        // for bold text.
+        let fctx = FontContextHandle::new();
+        let family = "DejaVu Math TeX Gyre".to_string();
+        let regular = fctx.create_font_from_identifier(family.clone(),
+                                                       test_style(12.0)).unwrap();
+        let mut bold_style = test_style(12.0);
+        bold_style.weight = font_weight::Weight700;
+        let bold = fctx.create_font_from_identifier(family, bold_style).unwrap();
+        assert!(regular.face != bold.face);
+        assert_eq!(&*file_data(&regular) as *FontFileData, &*file_data(&bold) as *FontFileData);
+    }
+
+    #[test]
+    fn test_unused_font_template_is_released() {
+        let fctx = FontContextHandle::new();
+        let handle = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                      test_style(12.0)).unwrap();
+        let file_data = file_data(&handle);
+        assert_eq!(fctx.cache.borrow().templates.len(), 1);
+        let alive = || fctx.cache.borrow().templates.values().any(|t| t.upgrade().is_some());
+
+        // The evicted face still lives on in our handle, so the template stays.
+        fctx.cache.borrow_mut().faces.evict_all();
+        assert!(alive());
+
+        drop(handle);
+        drop(file_data);
+        assert!(!alive());
+    }
+
+    #[test]
+    fn test_missing_font_template() {
+        let fctx = FontContextHandle::new();
+        match fctx.font_template(&Path::new("/nonexistent/font.ttf"), 0) {
+            Err(error) => assert_eq!(error, IoError("/nonexistent/font.ttf".to_string())),
+            Ok(_) => fail!("read a template for a missing file"),
+        }
+    }
+
+    #[test]
+    fn test_font_file_is_mapped() {
+        // The largest font that is always around; it should load from the
+        // mapping exactly as FreeType loads it from disk.
+        let fctx = FontContextHandle::new();
+        let style = test_style(12.0);
+        let font_match = path_from_identifier("DejaVu Sans".to_string(), &style).unwrap();
+        let mapped = fctx.create_font_from_family("DejaVu Sans".to_string(), &style).unwrap();
+        match *file_data(&mapped) {
+            FontFileMapped(_, size) => assert!(size > 0),
+            _ => fail!("font file was read rather than mapped"),
+        }
+
+        let from_disk = FontHandle::new_from_file(&fctx, font_match.path.as_str().unwrap(),
+                                                  font_match.index, &style).unwrap();
+        assert_eq!(mapped.face_name(), from_disk.face_name());
+        for ch in "Aa\u05D0\u2603".chars() {
+            let glyph = mapped.glyph_index(ch);
+            assert_eq!(glyph, from_disk.glyph_index(ch));
+            assert_eq!(mapped.glyph_h_advance(glyph.unwrap()),
+                       from_disk.glyph_h_advance(glyph.unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_refresh_finds_new_fonts() {
+        let fctx = FontContextHandle::new();
+        let style = test_style(16.0);
+        let existing = fctx.create_font_from_family("DejaVu Sans".to_string(), &style).unwrap();
+        // Only the tests carry this font; it isn't installed.
+        let family = "Test Color Emoji".to_string();
+        assert!(fctx.create_font_from_family(family.clone(), &style).is_err());
+
+        let dir = TempDir::new("gfx-font-directory").unwrap();
+        fctx.add_font_directory(dir.path()).unwrap();
+        assert!(fctx.create_font_from_family(family.clone(), &style).is_err());
+
+        let font = include_bin!("fonts/TestColorEmoji.ttf");
+        File::create(&dir.path().join("TestColorEmoji.ttf")).write(font).unwrap();
+        let generation = fctx.generation();
+        fctx.refresh();
+        assert!(fctx.generation() > generation);
+        let handle = fctx.create_font_from_family(family.clone(), &style).unwrap();
+        assert_eq!(handle.family_name(), family);
+
+        // Handles from before the refresh are still usable.
+        assert!(existing.glyph_index('A').is_some());
+    }
+
+    #[test]
+    fn test_new_with_font_dirs() {
+        // Application fonts are seen by the whole process, so this test's
+        // font has a family of its own.
+        let family = "Test Font Folder".to_string();
+        let font = rename_family(include_bin!("fonts/TestColorEmoji.ttf"), "Test Color Emoji",
+                                 family.as_slice());
+        let dir = TempDir::new("gfx-font-directory").unwrap();
+        File::create(&dir.path().join("TestFontFolder.ttf")).write(font.as_slice()).unwrap();
+        let fctx = FontContextHandle::new_with_font_dirs(&[dir.path().clone()]).unwrap();
+
+        assert!(fctx.available_families().contains(&family));
+        let mut style = test_style(16.0);
+        style.families = vec!(family.clone(), "sans-serif".to_string());
+        let handle = fctx.create_font_from_identifier(family.clone(), style).unwrap();
+        assert_eq!(handle.family_name(), family);
+    }
+
+    #[test]
+    fn test_missing_font_directory() {
+        let missing = Path::new("/nonexistent/fonts");
+        match FontContextHandle::new_with_font_dirs(&[missing]) {
+            Err(error) => assert_eq!(error, IoError("/nonexistent/fonts".to_string())),
+            Ok(_) => fail!("a missing font directory was accepted"),
+        }
+    }
+
+    #[test]
+    fn test_prewarm_loads_faces() {
+        let fctx = FontContextHandle::new();
+        let style = test_style(16.0);
+        fctx.prewarm(&["serif", "DejaVu Sans Mono", "NoSuchFontZZZ"], &style);
+        assert_eq!(lookups(&fctx), 3);
+
+        let serif = fctx.resolve_generic_family("serif");
+        fctx.create_font_from_identifier(serif, style.clone()).unwrap();
+        fctx.create_font_from_identifier("DejaVu Sans Mono".to_string(), style).unwrap();
+        assert_eq!(lookups(&fctx), 3);
+    }
+
+    #[test]
+    fn test_prewarm_in_background() {
+        let fctx = FontContextHandle::new();
+        let style = test_style(16.0);
+        fctx.prewarm_in_background(&["sans-serif", "NoSuchFontZZZ"], &style);
+        fctx.finish_prewarm();
+        assert_eq!(fctx.cache.borrow().prewarmed.len(), 1);
+
+        // The match is good for any size.
+        let sans = fctx.resolve_generic_family("sans-serif");
+        let handle = fctx.create_font_from_identifier(sans.clone(), style).unwrap();
+        assert_eq!(handle.family_name(), sans);
+        fctx.create_font_from_family(sans, &test_style(32.0)).unwrap();
+        assert_eq!(lookups(&fctx), 0);
+    }
+
+    #[test]
+    fn test_factory_handles_prewarm_generic_families() {
+        let fctx = FontContextHandle::new().factory().create_handle().unwrap();
+        fctx.finish_prewarm();
+        let style = test_style(12.0);
+        for generic in ["serif", "sans-serif", "monospace"].iter() {
+            let family = fctx.resolve_generic_family(*generic);
+            fctx.create_font_from_family(family, &style).unwrap();
+        }
+        assert_eq!(lookups(&fctx), 0);
+
+        // Prewarmed matches may be for fonts that are gone now.
+        fctx.invalidate();
+        let sans = fctx.resolve_generic_family("sans-serif");
+        fctx.create_font_from_family(sans, &style).unwrap();
+        assert_eq!(lookups(&fctx), 1);
+    }
+
+    #[test]
+    fn test_requests_before_prewarm_finishes() {
+        // Whether or not the matches have arrived, the faces are the same.
+        let fctx = FontContextHandle::new().factory().create_handle().unwrap();
+        let sans = fctx.resolve_generic_family("sans-serif");
+        let early = fctx.create_font_from_family(sans.clone(), &test_style(12.0)).unwrap();
+        fctx.finish_prewarm();
+        let late = fctx.create_font_from_family(sans, &test_style(13.0)).unwrap();
+        assert_eq!(early.face_name(), late.face_name());
+    }
+
+    #[test]
+    fn test_features_are_part_of_cache_key() {
+        let fctx = FontContextHandle::new();
+        let plain = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     test_style(12.0)).unwrap();
+        let small_caps = (true_type_tag('s', 'm', 'c', 'p'), 1);
+        let mut small_caps_style = test_style(12.0);
+        small_caps_style.features.push(small_caps);
+        let featured = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                        small_caps_style.clone()).unwrap();
+        assert_eq!(lookups(&fctx), 2);
+        assert!(plain.face != featured.face);
+        assert!(plain.features().is_empty());
+        assert_eq!(featured.features(), [small_caps].as_slice());
+
+        let again = fctx.create_font_from_identifier("DejaVu Sans".to_string(),
+                                                     small_caps_style).unwrap();
+        assert_eq!(lookups(&fctx), 2);
+        assert_eq!(again.face, featured.face);
+        assert_eq!(again.features(), [small_caps].as_slice());
+    }
+}
@@ -8,18 +8,21 @@ extern crate freetype;
 extern crate fontconfig;
 
 use fontconfig::fontconfig::{
-    FcChar8, FcResultMatch, FcSetSystem, FcPattern,
-    FcResultNoMatch, FcMatchPattern, FC_SLANT_ITALIC, FC_WEIGHT_BOLD, FC_SLANT_OBLIQUE
+    FcChar8, FcChar32, FcTrue, FcResultMatch, FcSetSystem, FcSetApplication, FcPattern,
+    FcResultNoMatch, FcMatchPattern, FC_SLANT_ITALIC, FC_WEIGHT_BOLD, FC_SLANT_OBLIQUE,
+    FcConfig, FcFontSet
 };
 use fontconfig::fontconfig::{
     FcConfigGetCurrent, FcConfigGetFonts, FcPatternGetString,
     FcPatternDestroy, FcFontSetDestroy, FcConfigSubstitute,
     FcDefaultSubstitute, FcPatternCreate, FcPatternAddString, FcPatternAddInteger,
     FcFontMatch, FcFontSetList, FcObjectSetCreate, FcObjectSetDestroy,
-    FcObjectSetAdd, FcPatternGetInteger
+    FcObjectSetAdd, FcPatternGetInteger, FcCharSetCreate, FcCharSetAddChar,
+    FcCharSetDestroy, FcPatternAddCharSet, FcPatternAddBool, FcInitBringUptoDate,
+    FcConfigAppFontAddDir, FcConfigAppFontClear, FcFontSetMatch
 };
 
-use style::computed_values::font_style;
+use style::computed_values::{font_stretch, font_style, font_weight};
 
 
 use font::{FontHandleMethods, UsedFontStyle};
@@ -27,10 +30,14 @@ use font_list::{FontEntry, FontFamily, FontFamilyMap};
 use platform::font::FontHandle;
 use platform::font_context::FontContextHandle;
 
-use std::collections::hashmap::HashMap;
+use std::collections::hashmap::{HashMap, HashSet};
 use libc;
 use libc::{c_int, c_char};
+use std::ascii::StrAsciiExt;
+use std::c_str::CString;
+use std::mem;
 use std::ptr;
+use std::rt::mutex::{StaticNativeMutex, NATIVE_MUTEX_INIT};
 use std::str;
 
 pub struct FontListHandle {
@@ -44,24 +51,11 @@ impl FontListHandle {
 
     pub fn get_available_families(&self) -> FontFamilyMap {
         let mut family_map : FontFamilyMap = HashMap::new();
-        unsafe {
-            let config = FcConfigGetCurrent();
-            let fontSet = FcConfigGetFonts(config, FcSetSystem);
-            for i in range(0, (*fontSet).nfont as int) {
-                let font = (*fontSet).fonts.offset(i);
-                let family: *FcChar8 = ptr::null();
-                let mut v: c_int = 0;
-                "family".to_c_str().with_ref(|FC_FAMILY| {
-                    while FcPatternGetString(*font, FC_FAMILY, v, &family) == FcResultMatch {
-                        let family_name = str::raw::from_c_str(family as *c_char);
-                        debug!("Creating new FontFamily for family: {:s}", family_name);
-                        let new_family = FontFamily::new(family_name.as_slice());
-                        family_map.insert(family_name, new_family);
-                        v += 1;
-                    }
-                });
-            }
-        }
+        get_available_families(|family_name| {
+            debug!("Creating new FontFamily for family: {:s}", family_name);
+            let new_family = FontFamily::new(family_name.as_slice());
+            family_map.insert(family_name, new_family);
+        });
         return family_map;
     }
 
@@ -117,7 +111,8 @@ impl FontListHandle {
                 debug!("variation index: {}", index);
 
                 let font_handle = FontHandle::new_from_file_unstyled(&self.fctx,
-                                                                     file);
+                                                                     file,
+                                                                     index as uint);
                 let font_handle = font_handle.unwrap();
 
                 debug!("Creating new FontEntry for face: {:s}", font_handle.face_name());
@@ -152,7 +147,78 @@ impl Drop for AutoPattern {
     }
 }
 
-pub fn path_from_identifier(name: String, style: &UsedFontStyle) -> Result<String, ()> {
+// fontconfig's weight scale, from fontconfig.h.
+static FC_WEIGHT_THIN: c_int = 0;
+static FC_WEIGHT_EXTRALIGHT: c_int = 40;
+static FC_WEIGHT_LIGHT: c_int = 50;
+static FC_WEIGHT_REGULAR: c_int = 80;
+static FC_WEIGHT_MEDIUM: c_int = 100;
+static FC_WEIGHT_DEMIBOLD: c_int = 180;
+static FC_WEIGHT_EXTRABOLD: c_int = 205;
+static FC_WEIGHT_BLACK: c_int = 210;
+
+/// Maps a CSS font-weight onto fontconfig's weight scale. fontconfig then
+/// picks the closest weight the family actually has.
+fn fc_weight(weight: font_weight::T) -> c_int {
+    match weight {
+        font_weight::Weight100 => FC_WEIGHT_THIN,
+        font_weight::Weight200 => FC_WEIGHT_EXTRALIGHT,
+        font_weight::Weight300 => FC_WEIGHT_LIGHT,
+        font_weight::Weight400 => FC_WEIGHT_REGULAR,
+        font_weight::Weight500 => FC_WEIGHT_MEDIUM,
+        font_weight::Weight600 => FC_WEIGHT_DEMIBOLD,
+        font_weight::Weight700 => FC_WEIGHT_BOLD,
+        font_weight::Weight800 => FC_WEIGHT_EXTRABOLD,
+        font_weight::Weight900 => FC_WEIGHT_BLACK,
+    }
+}
+
+// fontconfig's width scale, from fontconfig.h. It is a percentage of the
+// normal width, rounded down.
+static FC_WIDTH_ULTRACONDENSED: c_int = 50;
+static FC_WIDTH_EXTRACONDENSED: c_int = 63;
+static FC_WIDTH_CONDENSED: c_int = 75;
+static FC_WIDTH_SEMICONDENSED: c_int = 87;
+static FC_WIDTH_NORMAL: c_int = 100;
+static FC_WIDTH_SEMIEXPANDED: c_int = 113;
+static FC_WIDTH_EXPANDED: c_int = 125;
+static FC_WIDTH_EXTRAEXPANDED: c_int = 150;
+static FC_WIDTH_ULTRAEXPANDED: c_int = 200;
+
+/// Maps a CSS font-stretch onto fontconfig's width scale. As with weights,
+/// fontconfig picks the closest width the family has.
+fn fc_width(stretch: font_stretch::T) -> c_int {
+    match stretch {
+        font_stretch::ultra_condensed => FC_WIDTH_ULTRACONDENSED,
+        font_stretch::extra_condensed => FC_WIDTH_EXTRACONDENSED,
+        font_stretch::condensed => FC_WIDTH_CONDENSED,
+        font_stretch::semi_condensed => FC_WIDTH_SEMICONDENSED,
+        font_stretch::normal => FC_WIDTH_NORMAL,
+        font_stretch::semi_expanded => FC_WIDTH_SEMIEXPANDED,
+        font_stretch::expanded => FC_WIDTH_EXPANDED,
+        font_stretch::extra_expanded => FC_WIDTH_EXTRAEXPANDED,
+        font_stretch::ultra_expanded => FC_WIDTH_ULTRAEXPANDED,
+    }
+}
+
+/// The face fontconfig picked for a request.
+#[deriving(Clone, PartialEq)]
+pub struct FontMatch {
+    /// The file holding the face. Not necessarily UTF-8.
+    pub path: Path,
+    /// The index of the face within the file, only non-zero for collections.
+    pub index: uint,
+    /// The family of the face, which fontconfig may have substituted.
+    pub matched_family: String,
+    /// Whether the face is what was asked for rather than a substitute.
+    pub exact: bool,
+}
+
+/// Asks fontconfig for the face for `name` in `style`. fontconfig always
+/// finds something; the match is only exact if it is in the family `name`.
+/// A family in a directory given to `add_font_directory` hides installed
+/// faces of the same family.
+pub fn path_from_identifier(name: String, style: &UsedFontStyle) -> Result<FontMatch, ()> {
     unsafe {
         let config = FcConfigGetCurrent();
         let wrapper = AutoPattern { pattern: FcPatternCreate() };
@@ -167,34 +233,270 @@ pub fn path_from_identifier(name: String, style: &UsedFontStyle) -> Result<Strin
             return Err(());
         }
 
-        match style.style {
-            font_style::normal => (),
-            font_style::italic => {
-                let res = "slant".to_c_str().with_ref(|FC_SLANT| {
-                    FcPatternAddInteger(pattern, FC_SLANT, FC_SLANT_ITALIC)
-                });
-                if res != 1 {
-                    debug!("adding slant to pattern failed");
-                    return Err(());
-                }
-            },
-            font_style::oblique => {
-                let res = "slant".to_c_str().with_ref(|FC_SLANT| {
-                    FcPatternAddInteger(pattern, FC_SLANT, FC_SLANT_OBLIQUE)
+        if add_style_to_pattern(pattern, style).is_err() {
+            return Err(());
+        }
+
+        if FcConfigSubstitute(config, pattern, FcMatchPattern) != 1 {
+            debug!("substitution failed");
+            return Err(());
+        }
+        FcDefaultSubstitute(pattern);
+
+        match match_application_font(config, pattern, name.as_slice()) {
+            Some(font_match) => return Ok(font_match),
+            None => {}
+        }
+
+        let result = FcResultNoMatch;
+        let result_wrapper = AutoPattern { pattern: FcFontMatch(config, pattern, &result) };
+        let result_pattern = result_wrapper.pattern;
+        if result != FcResultMatch && result_pattern.is_null() {
+            debug!("obtaining match to pattern failed");
+            return Err(());
+        }
+
+        let exact = result == FcResultMatch && pattern_has_family(result_pattern, name.as_slice());
+        if !exact {
+            debug!("no font in family {:s}", name);
+        }
+        match_from_pattern(result_pattern, exact)
+    }
+}
+
+/// Looks for a face in the family `name` among the fonts in directories given
+/// to `add_font_directory`. fontconfig itself checks installed fonts first,
+/// so it would pick those when both have the family.
+unsafe fn match_application_font(config: *FcConfig, pattern: *FcPattern, name: &str)
+                                 -> Option<FontMatch> {
+    let app_fonts = FcConfigGetFonts(config, FcSetApplication);
+    if app_fonts.is_null() || (*app_fonts).nfont == 0 {
+        return None;
+    }
+    let result = FcResultNoMatch;
+    let result_wrapper = AutoPattern {
+        pattern: FcFontSetMatch(config, &app_fonts, 1, pattern, &result)
+    };
+    let result_pattern = result_wrapper.pattern;
+    if result != FcResultMatch || result_pattern.is_null() ||
+            !pattern_has_family(result_pattern, name) {
+        return None;
+    }
+    debug!("using application font for {:s}", name);
+    match_from_pattern(result_pattern, true).ok()
+}
+
+/// The fonts fontconfig knows about: the installed ones, then those in
+/// directories given to `add_font_directory`. The latter set is null if
+/// there are none.
+unsafe fn font_sets(config: *FcConfig) -> [*FcFontSet, ..2] {
+    [FcConfigGetFonts(config, FcSetSystem), FcConfigGetFonts(config, FcSetApplication)]
+}
+
+/// Calls `callback` once for every available font family, including the
+/// localized and alternate names that faces list for their family.
+pub fn get_available_families(callback: |String|) {
+    let mut seen = HashSet::new();
+    unsafe {
+        let config = FcConfigGetCurrent();
+        for &fontSet in font_sets(config).iter().filter(|font_set| font_set.is_not_null()) {
+            for i in range(0, (*fontSet).nfont as int) {
+                let font = (*fontSet).fonts.offset(i);
+                let family: *FcChar8 = ptr::null();
+                let mut v: c_int = 0;
+                "family".to_c_str().with_ref(|FC_FAMILY| {
+                    while FcPatternGetString(*font, FC_FAMILY, v, &family) == FcResultMatch {
+                        let family_name = str::raw::from_c_str(family as *c_char);
+                        if seen.insert(family_name.clone()) {
+                            callback(family_name);
+                        }
+                        v += 1;
+                    }
                 });
-                if res != 1 {
-                    debug!("adding slant(oblique) to pattern failed");
-                    return Err(());
-                }
             }
         }
+    }
+}
+
+/// Calls `callback` with the identifier of each face in `family`, as
+/// `FontHandle::face_identifier` would return it. These can be passed to
+/// `path_from_identifier` along with a style.
+pub fn get_variations_for_family(family: &str, callback: |String|) {
+    unsafe {
+        let config = FcConfigGetCurrent();
+        let sets = font_sets(config);
+        let wrapper = AutoPattern { pattern: FcPatternCreate() };
+        let pattern = wrapper.pattern;
+        "family".to_c_str().with_ref(|FC_FAMILY| {
+            family.to_c_str().with_ref(|family_name| {
+                FcPatternAddString(pattern, FC_FAMILY, family_name as *FcChar8);
+            });
+        });
+
+        let object_set = FcObjectSetCreate();
+        assert!(object_set.is_not_null());
+        "family".to_c_str().with_ref(|FC_FAMILY| {
+            FcObjectSetAdd(object_set, FC_FAMILY);
+        });
+
+        // fontconfig skips the application set when it is null.
+        let matches = FcFontSetList(config, sets.as_ptr(), sets.len() as c_int,
+                                    pattern, object_set);
+        for i in range(0, (*matches).nfont as int) {
+            let font = (*matches).fonts.offset(i);
+            let face_family: *FcChar8 = ptr::null();
+            let res = "family".to_c_str().with_ref(|FC_FAMILY| {
+                FcPatternGetString(*font, FC_FAMILY, 0, &face_family)
+            });
+            if res == FcResultMatch {
+                callback(str::raw::from_c_str(face_family as *c_char));
+            }
+        }
+
+        FcFontSetDestroy(matches);
+        FcObjectSetDestroy(object_set);
+    }
+}
+
+/// Guards APP_FONT_DIRECTORIES.
+static APP_FONT_DIRECTORIES_LOCK: StaticNativeMutex = NATIVE_MUTEX_INIT;
+
+/// The directories given to `add_font_directory`, so that `rescan_fonts` can
+/// scan them again. fontconfig's configuration belongs to the process, and so
+/// does this list; it is created on first use and never freed.
+static mut APP_FONT_DIRECTORIES: *mut Vec<String> = 0 as *mut Vec<String>;
+
+/// Makes the fonts in `directory` available to this process only, without
+/// installing them. Returns false if fontconfig couldn't scan it.
+pub fn add_font_directory(directory: &str) -> bool {
+    unsafe {
+        let _guard = APP_FONT_DIRECTORIES_LOCK.lock();
+        if APP_FONT_DIRECTORIES.is_null() {
+            APP_FONT_DIRECTORIES = mem::transmute(box Vec::<String>::new());
+        }
+        let directories = &mut *APP_FONT_DIRECTORIES;
+        if directories.iter().any(|known| known.as_slice() == directory) {
+            return true;
+        }
+        if !scan_app_font_directory(directory) {
+            return false;
+        }
+        directories.push(directory.to_string());
+        true
+    }
+}
+
+/// Reloads fontconfig's configuration if installed fonts or configuration
+/// files have changed, then rescans every directory given to
+/// `add_font_directory`. Returns false if fontconfig failed to reload.
+pub fn rescan_fonts() -> bool {
+    unsafe {
+        // This may replace the current configuration, which forgets any
+        // application fonts.
+        if FcInitBringUptoDate() != 1 {
+            debug!("bringing fontconfig up to date failed");
+            return false;
+        }
+
+        let _guard = APP_FONT_DIRECTORIES_LOCK.lock();
+        if APP_FONT_DIRECTORIES.is_null() {
+            return true;
+        }
+        FcConfigAppFontClear(FcConfigGetCurrent());
+        for directory in (*APP_FONT_DIRECTORIES).iter() {
+            scan_app_font_directory(directory.as_slice());
+        }
+        true
+    }
+}
+
+unsafe fn scan_app_font_directory(directory: &str) -> bool {
+    let res = directory.to_c_str().with_ref(|directory| {
+        FcConfigAppFontAddDir(FcConfigGetCurrent(), directory as *FcChar8)
+    });
+    if res != 1 {
+        debug!("adding font directory {:s} failed", directory);
+    }
+    res == 1
+}
+
+/// Asks fontconfig which installed family it uses for a CSS generic family
+/// such as "sans-serif".
+pub fn family_for_generic(generic: &str) -> Option<String> {
+    unsafe {
+        let config = FcConfigGetCurrent();
+        let wrapper = AutoPattern { pattern: FcPatternCreate() };
+        let pattern = wrapper.pattern;
+        let res = "family".to_c_str().with_ref(|FC_FAMILY| {
+            generic.to_c_str().with_ref(|family| {
+                FcPatternAddString(pattern, FC_FAMILY, family as *FcChar8)
+            })
+        });
+        if res != 1 {
+            debug!("adding family to pattern failed");
+            return None;
+        }
 
-        if style.weight.is_bold() {
-            let res = "weight".to_c_str().with_ref(|FC_WEIGHT| {
-                FcPatternAddInteger(pattern, FC_WEIGHT, FC_WEIGHT_BOLD)
+        if FcConfigSubstitute(config, pattern, FcMatchPattern) != 1 {
+            debug!("substitution failed");
+            return None;
+        }
+        FcDefaultSubstitute(pattern);
+        let result = FcResultNoMatch;
+        let result_wrapper = AutoPattern { pattern: FcFontMatch(config, pattern, &result) };
+        let result_pattern = result_wrapper.pattern;
+        if result != FcResultMatch && result_pattern.is_null() {
+            debug!("obtaining match for generic family {:s} failed", generic);
+            return None;
+        }
+
+        let family: *FcChar8 = ptr::null();
+        let res = "family".to_c_str().with_ref(|FC_FAMILY| {
+            FcPatternGetString(result_pattern, FC_FAMILY, 0, &family)
+        });
+        if res != FcResultMatch {
+            debug!("getting family name for generic family {:s} failed", generic);
+            return None;
+        }
+        Some(str::raw::from_c_str(family as *c_char))
+    }
+}
+
+/// Asks fontconfig for a face that covers `codepoint` in the given style,
+/// favouring color fonts if `prefer_color` is set. fontconfig settles for the
+/// closest face when none covers the codepoint, so callers must check.
+pub fn path_for_codepoint(codepoint: char, style: &UsedFontStyle, prefer_color: bool)
+                          -> Result<FontMatch, ()> {
+    unsafe {
+        let config = FcConfigGetCurrent();
+        let wrapper = AutoPattern { pattern: FcPatternCreate() };
+        let pattern = wrapper.pattern;
+
+        let charset = FcCharSetCreate();
+        let res = if FcCharSetAddChar(charset, codepoint as FcChar32) == 1 {
+            "charset".to_c_str().with_ref(|FC_CHARSET| {
+                FcPatternAddCharSet(pattern, FC_CHARSET, charset)
+            })
+        } else {
+            0
+        };
+        // The pattern holds its own copy of the charset.
+        FcCharSetDestroy(charset);
+        if res != 1 {
+            debug!("adding charset to pattern failed");
+            return Err(());
+        }
+
+        if add_style_to_pattern(pattern, style).is_err() {
+            return Err(());
+        }
+
+        if prefer_color {
+            let res = "color".to_c_str().with_ref(|FC_COLOR| {
+                FcPatternAddBool(pattern, FC_COLOR, FcTrue)
             });
             if res != 1 {
-                debug!("adding weight to pattern failed");
+                debug!("adding color to pattern failed");
                 return Err(());
             }
         }
@@ -208,18 +510,230 @@ pub fn path_from_identifier(name: String, style: &UsedFontStyle) -> Result<Strin
         let result_wrapper = AutoPattern { pattern: FcFontMatch(config, pattern, &result) };
         let result_pattern = result_wrapper.pattern;
         if result != FcResultMatch && result_pattern.is_null() {
-            debug!("obtaining match to pattern failed");
+            debug!("obtaining match for U+{:04X} failed", codepoint as u32);
             return Err(());
         }
+        match_from_pattern(result_pattern, result == FcResultMatch)
+    }
+}
 
-        let file: *FcChar8 = ptr::null();
-        let res = "file".to_c_str().with_ref(|FC_FILE| {
-            FcPatternGetString(result_pattern, FC_FILE, 0, &file)
-        });
-        if res != FcResultMatch {
-            debug!("getting filename for font failed");
-            return Err(());
+/// Adds the slant, weight and width of `style` to a fontconfig pattern.
+unsafe fn add_style_to_pattern(pattern: *FcPattern, style: &UsedFontStyle) -> Result<(), ()> {
+    match style.style {
+        font_style::normal => (),
+        font_style::italic => {
+            let res = "slant".to_c_str().with_ref(|FC_SLANT| {
+                FcPatternAddInteger(pattern, FC_SLANT, FC_SLANT_ITALIC)
+            });
+            if res != 1 {
+                debug!("adding slant to pattern failed");
+                return Err(());
+            }
+        },
+        font_style::oblique => {
+            let res = "slant".to_c_str().with_ref(|FC_SLANT| {
+                FcPatternAddInteger(pattern, FC_SLANT, FC_SLANT_OBLIQUE)
+            });
+            if res != 1 {
+                debug!("adding slant(oblique) to pattern failed");
+                return Err(());
+            }
+        }
+    }
+
+    let res = "weight".to_c_str().with_ref(|FC_WEIGHT| {
+        FcPatternAddInteger(pattern, FC_WEIGHT, fc_weight(style.weight))
+    });
+    if res != 1 {
+        debug!("adding weight to pattern failed");
+        return Err(());
+    }
+
+    let res = "width".to_c_str().with_ref(|FC_WIDTH| {
+        FcPatternAddInteger(pattern, FC_WIDTH, fc_width(style.stretch))
+    });
+    if res != 1 {
+        debug!("adding width to pattern failed");
+        return Err(());
+    }
+    Ok(())
+}
+
+/// Reads the file, face index and family out of a matched pattern.
+unsafe fn match_from_pattern(pattern: *FcPattern, exact: bool) -> Result<FontMatch, ()> {
+    let file: *FcChar8 = ptr::null();
+    let res = "file".to_c_str().with_ref(|FC_FILE| {
+        FcPatternGetString(pattern, FC_FILE, 0, &file)
+    });
+    if res != FcResultMatch {
+        debug!("getting filename for font failed");
+        return Err(());
+    }
+
+    let index: c_int = 0;
+    let res = "index".to_c_str().with_ref(|FC_INDEX| {
+        FcPatternGetInteger(pattern, FC_INDEX, 0, &index)
+    });
+    if res != FcResultMatch {
+        debug!("no face index for font, assuming 0");
+    }
+
+    let family: *FcChar8 = ptr::null();
+    let res = "family".to_c_str().with_ref(|FC_FAMILY| {
+        FcPatternGetString(pattern, FC_FAMILY, 0, &family)
+    });
+    if res != FcResultMatch {
+        debug!("getting family name for font failed");
+        return Err(());
+    }
+
+    // File names are whatever bytes the file system holds.
+    let file = CString::new(file as *c_char, false);
+    Ok(FontMatch {
+        path: Path::new(file.as_bytes_no_nul()),
+        index: index as uint,
+        matched_family: str::raw::from_c_str(family as *c_char),
+        exact: exact,
+    })
+}
+
+unsafe fn pattern_has_family(pattern: *FcPattern, name: &str) -> bool {
+    let family: *FcChar8 = ptr::null();
+    let mut v: c_int = 0;
+    "family".to_c_str().with_ref(|FC_FAMILY| {
+        while FcPatternGetString(pattern, FC_FAMILY, v, &family) == FcResultMatch {
+            let family_name = str::raw::from_c_str(family as *c_char);
+            if family_name.as_slice().eq_ignore_ascii_case(name) {
+                return true;
+            }
+            v += 1;
         }
-        Ok(str::raw::from_c_str(file as *c_char))
+        false
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{add_font_directory, get_available_families, get_variations_for_family};
+    use super::path_from_identifier;
+    use font::{FontStyle, test_style};
+    use style::computed_values::{font_stretch, font_style, font_weight};
+
+    use std::io::TempDir;
+    use std::io::fs;
+
+    #[test]
+    fn test_bold_selects_bold_face() {
+        let bold_style = FontStyle { weight: font_weight::Weight700, ..test_style(12.0) };
+        let regular = path_from_identifier("DejaVu Sans".to_string(), &test_style(12.0));
+        let bold = path_from_identifier("DejaVu Sans".to_string(), &bold_style);
+        let regular = regular.unwrap().path;
+        let bold = bold.unwrap().path;
+        assert!(regular != bold);
+        assert!(bold.as_str().unwrap().contains("Bold"));
+        assert!(!regular.as_str().unwrap().contains("Bold"));
+    }
+
+    #[test]
+    fn test_italic_selects_slanted_face() {
+        let italic_style = FontStyle { style: font_style::italic, ..test_style(12.0) };
+        let regular = path_from_identifier("DejaVu Sans".to_string(), &test_style(12.0));
+        let italic = path_from_identifier("DejaVu Sans".to_string(), &italic_style);
+        assert!(regular.unwrap() != italic.unwrap());
+    }
+
+    #[test]
+    fn test_available_families() {
+        let mut families = vec!();
+        get_available_families(|family| families.push(family));
+        assert!(families.len() > 0);
+
+        let mut sorted = families.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), families.len());
+    }
+
+    #[test]
+    fn test_variations_are_resolvable() {
+        let mut variations = vec!();
+        get_variations_for_family("DejaVu Sans", |identifier| variations.push(identifier));
+        assert!(variations.len() > 0);
+        for identifier in variations.move_iter() {
+            assert!(path_from_identifier(identifier, &test_style(12.0)).unwrap().exact);
+        }
+    }
+
+    #[test]
+    fn test_missing_weight_uses_closest() {
+        // DejaVu Sans Mono has no black face; the bold one is closest.
+        let black_style = FontStyle { weight: font_weight::Weight900, ..test_style(12.0) };
+        let bold_style = FontStyle { weight: font_weight::Weight700, ..test_style(12.0) };
+        let black = path_from_identifier("DejaVu Sans Mono".to_string(), &black_style);
+        let bold = path_from_identifier("DejaVu Sans Mono".to_string(), &bold_style);
+        assert!(black.unwrap() == bold.unwrap());
+    }
+
+    #[test]
+    fn test_condensed_selects_condensed_face() {
+        let normal = test_style(12.0);
+        let mut condensed = normal.clone();
+        condensed.stretch = font_stretch::condensed;
+        let normal = path_from_identifier("DejaVu Sans".to_string(), &normal).unwrap();
+        let condensed = path_from_identifier("DejaVu Sans".to_string(), &condensed).unwrap();
+        assert!(condensed.exact);
+        assert!(normal.path != condensed.path);
+        assert!(condensed.path.as_str().unwrap().contains("Condensed"));
+        assert!(!normal.path.as_str().unwrap().contains("Condensed"));
+    }
+
+    #[test]
+    fn test_missing_width_uses_closest() {
+        // DejaVu Sans Mono only comes in the normal width.
+        let normal = test_style(12.0);
+        let mut condensed = normal.clone();
+        condensed.stretch = font_stretch::ultra_condensed;
+        let normal = path_from_identifier("DejaVu Sans Mono".to_string(), &normal);
+        let condensed = path_from_identifier("DejaVu Sans Mono".to_string(), &condensed);
+        let condensed = condensed.unwrap();
+        assert!(condensed.exact);
+        assert!(condensed == normal.unwrap());
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let font_match = path_from_identifier("DejaVu Sans".to_string(), &test_style(12.0));
+        let font_match = font_match.unwrap();
+        assert!(font_match.exact);
+        assert_eq!(font_match.matched_family, "DejaVu Sans".to_string());
+        assert_eq!(font_match.index, 0);
+        assert!(font_match.path.exists());
+    }
+
+    #[test]
+    fn test_substituted_match() {
+        let font_match = path_from_identifier("NoSuchFontZZZ".to_string(), &test_style(12.0));
+        let font_match = font_match.unwrap();
+        assert!(!font_match.exact);
+        assert!(font_match.matched_family != "NoSuchFontZZZ".to_string());
+        assert!(font_match.matched_family.len() > 0);
+    }
+
+    #[test]
+    fn test_application_font_wins() {
+        // A family no other test uses, since application fonts are seen by the
+        // whole process.
+        let family = "DejaVu Math TeX Gyre".to_string();
+        let regular = test_style(12.0);
+        let installed = path_from_identifier(family.clone(), &regular).unwrap();
+        assert!(installed.exact);
+
+        let dir = TempDir::new("gfx-font-list-test").unwrap();
+        let copy = dir.path().join("copy.ttf");
+        fs::copy(&installed.path, &copy).unwrap();
+        assert!(add_font_directory(dir.path().as_str().unwrap()));
+        let font_match = path_from_identifier(family.clone(), &regular).unwrap();
+        assert!(font_match.exact);
+        assert!(font_match.path == copy);
     }
 }
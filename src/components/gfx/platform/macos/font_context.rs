@@ -2,11 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use font::UsedFontStyle;
+use font::{FontError, FontHandleMethods, NotFound, UnsupportedFormat, UsedFontStyle};
 use font_context::FontContextHandleMethods;
 use platform::macos::font::FontHandle;
+use webfont::decode_font_data;
 
 use core_text;
+use sync::Arc;
 
 #[deriving(Clone)]
 pub struct FontContextHandle {
@@ -21,14 +23,46 @@ impl FontContextHandle {
     }
 }
 
+/// Creates a `FontContextHandle` on whichever task needs one.
+#[deriving(Clone)]
+pub struct FontContextHandleFactory;
+
+impl FontContextHandleFactory {
+    pub fn new() -> FontContextHandleFactory {
+        FontContextHandleFactory
+    }
+
+    pub fn create_handle(&self) -> Result<FontContextHandle, FontError> {
+        Ok(FontContextHandle::new())
+    }
+}
+
 impl FontContextHandleMethods for FontContextHandle {
     fn create_font_from_identifier(&self,
                                    name: String,
                                    style: UsedFontStyle)
-                                -> Result<FontHandle, ()> {
+                                -> Result<FontHandle, FontError> {
         let ctfont_result = core_text::font::new_from_name(name.as_slice(), style.pt_size);
-        ctfont_result.and_then(|ctfont| {
-            FontHandle::new_from_CTFont(self, ctfont)
+        match ctfont_result {
+            Ok(ctfont) => {
+                FontHandle::new_from_CTFont(self, ctfont).map_err(|()| {
+                    UnsupportedFormat(name.clone(), None)
+                })
+            }
+            Err(()) => Err(NotFound(name)),
+        }
+    }
+
+    fn create_font_from_buffer(&self, bytes: Arc<Vec<u8>>, face_index: uint,
+                               style: &UsedFontStyle) -> Result<FontHandle, FontError> {
+        // TODO: select faces other than the first within a font collection.
+        let description = format!("font buffer ({:u} bytes, face {:u})", bytes.len(), face_index);
+        if face_index != 0 {
+            return Err(UnsupportedFormat(description, None));
+        }
+        let bytes = try!(decode_font_data(bytes, description.as_slice()));
+        FontHandleMethods::new_from_buffer(self, (*bytes).clone(), style).map_err(|()| {
+            UnsupportedFormat(description.clone(), None)
         })
     }
 }
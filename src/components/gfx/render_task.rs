@@ -7,7 +7,7 @@
 use buffer_map::BufferMap;
 use display_list::optimizer::DisplayListOptimizer;
 use display_list::DisplayList;
-use font_context::{FontContext, FontContextInfo};
+use font_context::{FontContext, FontContextHandleFactory, FontContextInfo};
 use render_context::RenderContext;
 
 use azure::azure_hl::{B8G8R8A8, Color, DrawTarget, StolenGLResources};
@@ -24,6 +24,7 @@ use servo_msg::constellation_msg::{ConstellationChan, Failure, FailureMsg, Pipel
 use servo_msg::constellation_msg::{RendererReadyMsg};
 use servo_msg::platform::surface::NativeSurfaceAzureMethods;
 use servo_util::geometry;
+use servo_util::memory::{MemoryReport, MemoryReporter, MemoryReportsChan};
 use servo_util::opts::Opts;
 use servo_util::smallvec::{SmallVec, SmallVec1};
 use servo_util::task::send_on_failure;
@@ -53,6 +54,8 @@ pub enum Msg {
     UnusedBufferMsg(Vec<Box<LayerBuffer>>),
     PaintPermissionGranted,
     PaintPermissionRevoked,
+    /// Sends the memory used by the font cache to the memory profiler.
+    CollectMemoryReportsMsg(MemoryReportsChan),
     ExitMsg(Option<Sender<()>>),
 }
 
@@ -93,6 +96,12 @@ impl RenderChan {
     }
 }
 
+impl MemoryReporter for RenderChan {
+    fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool {
+        self.send_opt(CollectMemoryReportsMsg(reports_chan)).is_ok()
+    }
+}
+
 /// If we're using GPU rendering, this provides the metadata needed to create a GL context that
 /// is compatible with that of the main thread.
 pub enum GraphicsContext {
@@ -183,6 +192,8 @@ impl<C:RenderListener + Send> RenderTask<C> {
                         backend: opts.render_backend.clone(),
                         needs_font_list: false,
                         time_profiler_chan: time_profiler_chan.clone(),
+                        handle_factory: FontContextHandleFactory::new(),
+                        print_cache_misses: opts.debug.gfx_fonts,
                     }),
                     opts: opts,
                     time_profiler_chan: time_profiler_chan,
@@ -266,6 +277,13 @@ impl<C:RenderListener + Send> RenderTask<C> {
                 PaintPermissionRevoked => {
                     self.paint_permission = false;
                 }
+                CollectMemoryReportsMsg(reports_chan) => {
+                    let PipelineId(id) = self.id;
+                    reports_chan.send(vec!(MemoryReport {
+                        path: format!("pipeline-{}/render-font-cache", id),
+                        size: self.font_ctx.heap_size() as u64,
+                    }));
+                }
                 ExitMsg(response_ch) => {
                     debug!("render_task: exitmsg response send");
                     response_ch.map(|ch| ch.send(()));
@@ -280,7 +298,11 @@ impl<C:RenderListener + Send> RenderTask<C> {
     /// FIXME(pcwalton): We will probably want to eventually send all layers belonging to a page in
     /// one transaction, to avoid the user seeing inconsistent states.
     fn render(&mut self, tiles: Vec<BufferRequest>, scale: f32, layer_id: LayerId) {
-        time::profile(time::RenderingCategory, self.time_profiler_chan.clone(), || {
+        let PipelineId(pipeline_id) = self.id;
+        time::profile(time::RenderingCategory,
+                      Some(pipeline_id),
+                      self.time_profiler_chan.clone(),
+                      || {
             // FIXME: Try not to create a new array here.
             let mut new_buffers = vec!();
 
@@ -345,7 +367,10 @@ impl<C:RenderListener + Send> RenderTask<C> {
                     ctx.clear();
 
                     // Draw the display list.
-                    profile(time::RenderingDrawingCategory, self.time_profiler_chan.clone(), || {
+                    profile(time::RenderingDrawingCategory,
+                            None,
+                            self.time_profiler_chan.clone(),
+                            || {
                         display_list.draw_into_context(&mut ctx);
                         ctx.draw_target.flush();
                     });
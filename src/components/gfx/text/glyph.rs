@@ -4,7 +4,7 @@
 
 use servo_util::vec::*;
 use servo_util::range;
-use servo_util::range::{Range, RangeIndex, IntRangeIndex, EachIndex};
+use servo_util::range::{CharIndex, EachIndex, IntRangeIndex, Range};
 use servo_util::geometry::Au;
 
 use std::cmp::{PartialOrd, PartialEq};
@@ -313,6 +313,11 @@ impl<'a> DetailedGlyphStore {
         }
     }
 
+    fn heap_size(&self) -> uint {
+        self.detail_buffer.capacity() * mem::size_of::<DetailedGlyph>() +
+            self.detail_lookup.capacity() * mem::size_of::<DetailedGlyphRecord>()
+    }
+
     fn add_detailed_glyphs_for_entry(&mut self, entry_offset: CharIndex, glyphs: &[DetailedGlyph]) {
         let entry = DetailedGlyphRecord {
             entry_offset: entry_offset,
@@ -510,12 +515,6 @@ pub struct GlyphStore {
     is_whitespace: bool,
 }
 
-int_range_index! {
-    #[doc = "An index that refers to a character in a text run. This could \
-             point to the middle of a glyph."]
-    struct CharIndex(int)
-}
-
 impl<'a> GlyphStore {
     // Initializes the glyph store, but doesn't actually shape anything.
     // Use the set_glyph, set_glyphs() methods to store glyph data.
@@ -537,6 +536,11 @@ impl<'a> GlyphStore {
         self.is_whitespace
     }
 
+    /// The number of bytes this store has allocated on the heap.
+    pub fn heap_size(&self) -> uint {
+        self.entry_buffer.capacity() * mem::size_of::<GlyphEntry>() + self.detail_store.heap_size()
+    }
+
     pub fn finalize_changes(&mut self) {
         self.detail_store.ensure_sorted();
     }
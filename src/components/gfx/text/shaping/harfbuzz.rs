@@ -6,7 +6,7 @@ extern crate harfbuzz;
 
 use font::{Font, FontHandleMethods, FontTableMethods, FontTableTag};
 use platform::font::FontTable;
-use text::glyph::{CharIndex, GlyphStore, GlyphId, GlyphData};
+use text::glyph::{GlyphStore, GlyphId, GlyphData};
 use text::shaping::ShaperMethods;
 use text::util::{float_to_fixed, fixed_to_float};
 
@@ -20,7 +20,7 @@ use harfbuzz::{hb_buffer_destroy};
 use harfbuzz::{hb_buffer_get_glyph_positions};
 use harfbuzz::{hb_buffer_set_direction};
 use harfbuzz::{hb_face_destroy};
-use harfbuzz::{hb_face_t, hb_font_t};
+use harfbuzz::{hb_face_t, hb_feature_t, hb_font_t};
 use harfbuzz::{hb_font_create};
 use harfbuzz::{hb_font_destroy, hb_buffer_create};
 use harfbuzz::{hb_font_funcs_create};
@@ -37,7 +37,8 @@ use harfbuzz::{hb_position_t, hb_tag_t};
 use harfbuzz::{hb_shape, hb_buffer_get_glyph_infos};
 use libc::{c_uint, c_int, c_void, c_char};
 use servo_util::geometry::Au;
-use servo_util::range::Range;
+use servo_util::range::{CharIndex, Range};
+use servo_util::smallvec::SmallVec;
 use std::mem;
 use std::char;
 use std::cmp;
@@ -46,6 +47,9 @@ use std::ptr::null;
 static NO_GLYPH: i32 = -1;
 static CONTINUATION_BYTE: i32 = -2;
 
+/// The end of a feature range that covers the whole buffer.
+static HB_FEATURE_GLOBAL_END: c_uint = 0xffffffff;
+
 pub struct ShapedGlyphData {
     count: int,
     glyph_infos: *hb_glyph_info_t,
@@ -136,6 +140,8 @@ pub struct Shaper {
     hb_face: *hb_face_t,
     hb_font: *hb_font_t,
     hb_funcs: *hb_font_funcs_t,
+    /// The font style's OpenType features, applied to every run.
+    features: Vec<hb_feature_t>,
 }
 
 #[unsafe_destructor]
@@ -180,10 +186,20 @@ impl Shaper {
             hb_font_funcs_set_glyph_h_advance_func(hb_funcs, glyph_h_advance_func, null(), None);
             hb_font_set_funcs(hb_font, hb_funcs, font_ptr as *c_void, None);
 
+            let features = font.style.features.iter().map(|&(tag, value)| {
+                hb_feature_t {
+                    tag: tag,
+                    value: value,
+                    start: 0,
+                    end: HB_FEATURE_GLOBAL_END,
+                }
+            }).collect();
+
             Shaper {
                 hb_face: hb_face,
                 hb_font: hb_font,
                 hb_funcs: hb_funcs,
+                features: features,
             }
         }
     }
@@ -212,7 +228,8 @@ impl ShaperMethods for Shaper {
                                0,
                                text.len() as c_int);
 
-            hb_shape(self.hb_font, hb_buffer, null(), 0);
+            hb_shape(self.hb_font, hb_buffer, self.features.as_ptr(),
+                     self.features.len() as c_uint);
             self.save_glyph_results(text, glyphs, hb_buffer);
             hb_buffer_destroy(hb_buffer);
         }
@@ -4,12 +4,12 @@
 
 use font::{Font, FontDescriptor, RunMetrics, FontStyle, FontMetrics};
 use servo_util::geometry::Au;
-use servo_util::range::Range;
+use servo_util::range::{ByteIndex, CharIndex, Range};
 use servo_util::vec::{Comparator, FullBinarySearchMethods};
 use std::slice::Items;
 use style::computed_values::text_decoration;
 use sync::Arc;
-use text::glyph::{CharIndex, GlyphStore};
+use text::glyph::GlyphStore;
 
 /// A single "paragraph" of text in one font size and style.
 #[deriving(Clone)]
@@ -85,8 +85,12 @@ impl<'a> Iterator<Range<CharIndex>> for LineIterator<'a> {
             match self.slices.next() {
                 Some((glyphs, offset, slice_range)) => {
                     match (glyphs.is_whitespace(), self.clump) {
-                        (false, Some(ref mut c))  => {
-                            c.extend_by(slice_range.length());
+                        (false, Some(c))  => {
+                            // `c` is a copy, so the clump has to be stored back.
+                            let mut slice_range = slice_range;
+                            slice_range.shift_by(offset);
+                            self.clump = Some(c.merge_adjacent(&slice_range)
+                                               .expect("slices should be adjacent"));
                         }
                         (false, None) => {
                             let mut c = slice_range;
@@ -135,14 +139,11 @@ impl<'a> TextRun {
         // TODO(Issue #230): do a better job. See Gecko's LineBreaker.
 
         let mut glyphs = vec!();
-        let (mut byte_i, mut char_i) = (0u, CharIndex(0));
         let mut cur_slice_is_whitespace = false;
-        let (mut byte_last_boundary, mut char_last_boundary) = (0, CharIndex(0));
-        while byte_i < text.len() {
-            let range = text.char_range_at(byte_i);
-            let ch = range.ch;
-            let next = range.next;
-
+        // The bytes and characters of the slice being built, which always cover the same text.
+        let mut byte_range = Range::new(ByteIndex(0), ByteIndex(0));
+        let mut char_range = Range::new(CharIndex(0), CharIndex(0));
+        for ch in text.chars() {
             // Slices alternate between whitespace and non-whitespace,
             // representing line break opportunities.
             let can_break_before = if cur_slice_is_whitespace {
@@ -164,30 +165,30 @@ impl<'a> TextRun {
             };
 
             // Create a glyph store for this slice if it's nonempty.
-            if can_break_before && byte_i > byte_last_boundary {
-                let slice = text.slice(byte_last_boundary, byte_i).to_string();
-                debug!("creating glyph store for slice {} (ws? {}), {} - {} in run {}",
-                        slice, !cur_slice_is_whitespace, byte_last_boundary, byte_i, text);
+            if can_break_before && !byte_range.is_empty() {
+                let slice = byte_range.slice(text).to_string();
+                debug!("creating glyph store for slice {} (ws? {}), {} in run {}",
+                        slice, !cur_slice_is_whitespace, byte_range, text);
                 glyphs.push(GlyphRun {
                     glyph_store: font.shape_text(slice, !cur_slice_is_whitespace),
-                    range: Range::new(char_last_boundary, char_i - char_last_boundary),
+                    range: char_range,
                 });
-                byte_last_boundary = byte_i;
-                char_last_boundary = char_i;
+                byte_range.reset(byte_range.end(), ByteIndex(0));
+                char_range.reset(char_range.end(), CharIndex(0));
             }
 
-            byte_i = next;
-            char_i = char_i + CharIndex(1);
+            byte_range.extend_by(ByteIndex(ch.len_utf8_bytes() as int));
+            char_range.extend_by(CharIndex(1));
         }
 
         // Create a glyph store for the final slice if it's nonempty.
-        if byte_i > byte_last_boundary {
-            let slice = text.slice_from(byte_last_boundary).to_string();
-            debug!("creating glyph store for final slice {} (ws? {}), {} - {} in run {}",
-                slice, cur_slice_is_whitespace, byte_last_boundary, text.len(), text);
+        if !byte_range.is_empty() {
+            let slice = byte_range.slice(text).to_string();
+            debug!("creating glyph store for final slice {} (ws? {}), {} in run {}",
+                slice, cur_slice_is_whitespace, byte_range, text);
             glyphs.push(GlyphRun {
                 glyph_store: font.shape_text(slice, cur_slice_is_whitespace),
-                range: Range::new(char_last_boundary, char_i - char_last_boundary),
+                range: char_range,
             });
         }
 
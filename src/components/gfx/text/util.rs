@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use text::glyph::CharIndex;
+use servo_util::range::CharIndex;
 
 #[deriving(PartialEq)]
 pub enum CompressionMode {
@@ -135,6 +135,28 @@ pub fn true_type_tag(a: char, b: char, c: char, d: char) -> u32 {
     (a << 24 | b << 16 | c << 8 | d) as u32
 }
 
+/* Whether a codepoint is usually drawn as emoji, and so is best taken from
+   a color font */
+pub fn is_emoji(ch: char) -> bool {
+    match ch as u32 {
+        0x2600..0x27BF |    // Miscellaneous Symbols, Dingbats
+        0x1F1E6..0x1F1FF |  // Regional indicators, which pair up into flags
+        0x1F300..0x1F64F |  // Miscellaneous Symbols and Pictographs, Emoticons
+        0x1F680..0x1F6FF |  // Transport and Map Symbols
+        0x1F900..0x1F9FF |  // Supplemental Symbols and Pictographs
+        0x1FA70..0x1FAFF => true,
+        _ => false
+    }
+}
+
+#[test]
+fn test_is_emoji() {
+    assert!(is_emoji('\U0001F600'));
+    assert!(is_emoji('\u2603'));
+    assert!(!is_emoji('a'));
+    assert!(!is_emoji('\u05D0'));
+}
+
 #[test]
 fn test_true_type_tag() {
     assert_eq!(true_type_tag('c', 'm', 'a', 'p'), 0x_63_6D_61_70_u32);
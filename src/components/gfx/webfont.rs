@@ -0,0 +1,523 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Font data as it comes from `@font-face` sources. Most of it is WOFF: an
+//! sfnt font (TrueType or OpenType) with its tables compressed one by one,
+//! which platform font loaders don't read. WOFF is turned back into the sfnt
+//! font it wraps before it is loaded.
+
+use font::{FontError, UnsupportedFormat};
+
+use libc::{c_int, c_void, size_t};
+use sync::Arc;
+
+/// What a font buffer holds, going by its signature.
+#[deriving(Clone, PartialEq, Show)]
+pub enum FontDataFormat {
+    /// TrueType, OpenType or a TrueType collection, which can be loaded as is.
+    SfntFormat,
+    WoffFormat,
+    Woff2Format,
+    UnknownFormat,
+}
+
+// 'wOFF' and 'wOF2'.
+static WOFF_SIGNATURE: u32 = 0x774F4646;
+static WOFF2_SIGNATURE: u32 = 0x774F4632;
+
+/// The versions that sfnt fonts start with: 1.0 for TrueType outlines, then
+/// 'OTTO' for CFF outlines, 'true' and 'typ1' for Apple's, and 'ttcf' for
+/// collections.
+static SFNT_SIGNATURES: &'static [u32] = &[
+    0x00010000, 0x4F54544F, 0x74727565, 0x74797031, 0x74746366
+];
+
+static WOFF_HEADER_SIZE: uint = 44;
+/// Where the WOFF header gives the size of the sfnt font it holds.
+static WOFF_TOTAL_SFNT_SIZE_OFFSET: uint = 16;
+static WOFF_TABLE_ENTRY_SIZE: uint = 20;
+static SFNT_HEADER_SIZE: uint = 12;
+static SFNT_TABLE_RECORD_SIZE: uint = 16;
+
+/// The 'head' table, which holds a checksum of the whole font.
+static HEAD_TAG: u32 = 0x68656164;
+static HEAD_CHECKSUM_ADJUSTMENT_OFFSET: uint = 8;
+
+/// What the checksum of a font with a correct 'head' table adds up to.
+static SFNT_CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
+
+/// The largest sfnt font we'll rebuild from WOFF. Real fonts, even ones
+/// covering most of Unicode, are well under this.
+static MAX_SFNT_SIZE: u64 = 30 * 1024 * 1024;
+
+// miniz, which the flate crate links in. Unlike `flate::inflate_bytes_zlib`,
+// this inflates into a buffer we provide, and fails rather than writing
+// past its end.
+static TINFL_FLAG_PARSE_ZLIB_HEADER: c_int = 1;
+static TINFL_DECOMPRESS_MEM_TO_MEM_FAILED: size_t = !0;
+extern {
+    fn tinfl_decompress_mem_to_mem(out_buf: *mut c_void, out_buf_len: size_t,
+                                   src_buf: *c_void, src_buf_len: size_t,
+                                   flags: c_int) -> size_t;
+}
+
+/// Inflates the zlib stream `data` onto the end of `out`, writing at most
+/// `max_length` bytes. Data that would inflate to more than that is as much
+/// of an error as data that doesn't inflate at all. Returns how many bytes
+/// were written.
+fn inflate_bounded(data: &[u8], max_length: uint, out: &mut Vec<u8>) -> Option<uint> {
+    let start = out.len();
+    out.reserve_additional(max_length);
+    let written = unsafe {
+        tinfl_decompress_mem_to_mem(out.as_mut_ptr().offset(start as int) as *mut c_void,
+                                    max_length as size_t,
+                                    data.as_ptr() as *c_void,
+                                    data.len() as size_t,
+                                    TINFL_FLAG_PARSE_ZLIB_HEADER)
+    };
+    if written == TINFL_DECOMPRESS_MEM_TO_MEM_FAILED {
+        return None;
+    }
+    unsafe {
+        out.set_len(start + written as uint);
+    }
+    Some(written as uint)
+}
+
+/// Big-endian sfnt fields at byte offset `at`. The font tests use these to
+/// patch fonts too.
+pub fn read_u16(data: &[u8], at: uint) -> u16 {
+    (data[at] as u16 << 8) | data[at + 1] as u16
+}
+
+pub fn read_u32(data: &[u8], at: uint) -> u32 {
+    (read_u16(data, at) as u32 << 16) | read_u16(data, at + 2) as u32
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.push((value >> 8) as u8);
+    data.push(value as u8);
+}
+
+pub fn write_u16(data: &mut Vec<u8>, at: uint, value: u16) {
+    *data.get_mut(at) = (value >> 8) as u8;
+    *data.get_mut(at + 1) = value as u8;
+}
+
+pub fn write_u32(data: &mut Vec<u8>, at: uint, value: u32) {
+    for i in range(0u, 4) {
+        *data.get_mut(at + i) = (value >> (24 - 8 * i)) as u8;
+    }
+}
+
+/// The sfnt checksum of `table`: the sum of its big-endian 32-bit words,
+/// with the last padded with zeros.
+fn checksum(table: &[u8]) -> u32 {
+    let mut sum = 0u32;
+    for word in table.chunks(4) {
+        let mut value = 0u32;
+        for (i, &byte) in word.iter().enumerate() {
+            value |= byte as u32 << (24 - 8 * i);
+        }
+        sum += value;
+    }
+    sum
+}
+
+/// Tells font formats apart by their first four bytes.
+pub fn sniff(bytes: &[u8]) -> FontDataFormat {
+    if bytes.len() < 4 {
+        return UnknownFormat;
+    }
+    let signature = read_u32(bytes, 0);
+    if signature == WOFF_SIGNATURE {
+        WoffFormat
+    } else if signature == WOFF2_SIGNATURE {
+        Woff2Format
+    } else if SFNT_SIGNATURES.contains(&signature) {
+        SfntFormat
+    } else {
+        UnknownFormat
+    }
+}
+
+/// Gets `bytes` ready to be loaded. sfnt fonts are returned untouched, and
+/// WOFF fonts are rebuilt into the sfnt font they hold. Anything else is an
+/// error naming `description`.
+pub fn decode_font_data(bytes: Arc<Vec<u8>>, description: &str)
+                        -> Result<Arc<Vec<u8>>, FontError> {
+    let result = match sniff(bytes.as_slice()) {
+        SfntFormat => return Ok(bytes),
+        WoffFormat => decode_woff(bytes.as_slice()),
+        Woff2Format => Err("WOFF 2.0 fonts are not supported"),
+        UnknownFormat => Err("not a WOFF or sfnt font"),
+    };
+    match result {
+        Ok(sfnt) => Ok(Arc::new(sfnt)),
+        Err(reason) => {
+            debug!("unable to decode {:s}: {:s}", description, reason);
+            Err(UnsupportedFormat(format!("{:s}: {:s}", description, reason), None))
+        }
+    }
+}
+
+/// A table as the WOFF directory describes it.
+struct WoffTable {
+    tag: u32,
+    offset: uint,
+    compressed_length: uint,
+    length: uint,
+}
+
+/// Rebuilds the sfnt font inside `woff`, or says what is wrong with it.
+pub fn decode_woff(woff: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if woff.len() < WOFF_HEADER_SIZE || read_u32(woff, 0) != WOFF_SIGNATURE {
+        return Err("not a WOFF font");
+    }
+    let flavor = read_u32(woff, 4);
+    if read_u32(woff, 8) as uint != woff.len() {
+        return Err("the WOFF header gives the wrong length");
+    }
+    let num_tables = read_u16(woff, 12) as uint;
+    if num_tables == 0 {
+        return Err("the WOFF font has no tables");
+    }
+    if read_u16(woff, 14) != 0 {
+        return Err("the reserved WOFF header field is set");
+    }
+    let directory_end = WOFF_HEADER_SIZE + num_tables * WOFF_TABLE_ENTRY_SIZE;
+    if directory_end > woff.len() {
+        return Err("the WOFF table directory is truncated");
+    }
+
+    // The sizes come from the font, so add them up in 64 bits, where even
+    // 65535 tables of 4GB each can't wrap, and check them against
+    // MAX_SFNT_SIZE before allocating anything.
+    let mut tables = vec!();
+    let mut sfnt_size = (SFNT_HEADER_SIZE + num_tables * SFNT_TABLE_RECORD_SIZE) as u64;
+    for i in range(0, num_tables) {
+        let entry = WOFF_HEADER_SIZE + i * WOFF_TABLE_ENTRY_SIZE;
+        let table = WoffTable {
+            tag: read_u32(woff, entry),
+            offset: read_u32(woff, entry + 4) as uint,
+            compressed_length: read_u32(woff, entry + 8) as uint,
+            length: read_u32(woff, entry + 12) as uint,
+        };
+        // In 64 bits, so that lengths near the top of the range can't wrap.
+        let table_end = table.offset as u64 + table.compressed_length as u64;
+        if table.offset < directory_end || table_end > woff.len() as u64 {
+            return Err("a WOFF table lies outside the font");
+        }
+        if table.compressed_length > table.length {
+            return Err("a WOFF table is bigger compressed than uncompressed");
+        }
+        sfnt_size += (table.length as u64 + 3) & !3;
+        if sfnt_size > MAX_SFNT_SIZE {
+            return Err("the WOFF font is too large");
+        }
+        tables.push(table);
+    }
+    if sfnt_size != read_u32(woff, WOFF_TOTAL_SFNT_SIZE_OFFSET) as u64 {
+        return Err("the WOFF header gives the wrong sfnt size");
+    }
+
+    // The WOFF directory is sorted by tag, as the sfnt one must be, so the
+    // tables keep their order.
+    let mut sfnt = Vec::with_capacity(sfnt_size as uint);
+    let mut entry_selector = 0u;
+    while 1u << (entry_selector + 1) <= num_tables {
+        entry_selector += 1;
+    }
+    let search_range = (1u << entry_selector) * SFNT_TABLE_RECORD_SIZE;
+    push_u16(&mut sfnt, (flavor >> 16) as u16);
+    push_u16(&mut sfnt, flavor as u16);
+    push_u16(&mut sfnt, num_tables as u16);
+    push_u16(&mut sfnt, search_range as u16);
+    push_u16(&mut sfnt, entry_selector as u16);
+    push_u16(&mut sfnt, (num_tables * SFNT_TABLE_RECORD_SIZE - search_range) as u16);
+    sfnt.grow(num_tables * SFNT_TABLE_RECORD_SIZE, &0u8);
+
+    let mut head_offset = None;
+    for (i, table) in tables.iter().enumerate() {
+        let data = woff.slice(table.offset, table.offset + table.compressed_length);
+        let offset = sfnt.len();
+        if table.compressed_length == table.length {
+            sfnt.push_all(data);
+        } else {
+            match inflate_bounded(data, table.length, &mut sfnt) {
+                Some(length) if length == table.length => {}
+                Some(_) => return Err("a WOFF table decompresses to the wrong length"),
+                None => return Err("a WOFF table doesn't decompress"),
+            }
+        }
+
+        // The whole font's checksum goes in 'head', which is summed without it.
+        if table.tag == HEAD_TAG && table.length >= HEAD_CHECKSUM_ADJUSTMENT_OFFSET + 4 {
+            write_u32(&mut sfnt, offset + HEAD_CHECKSUM_ADJUSTMENT_OFFSET, 0);
+            head_offset = Some(offset);
+        }
+        let table_checksum = checksum(sfnt.slice_from(offset));
+
+        let record = SFNT_HEADER_SIZE + i * SFNT_TABLE_RECORD_SIZE;
+        write_u32(&mut sfnt, record, table.tag);
+        write_u32(&mut sfnt, record + 4, table_checksum);
+        write_u32(&mut sfnt, record + 8, offset as u32);
+        write_u32(&mut sfnt, record + 12, table.length as u32);
+        while sfnt.len() % 4 != 0 {
+            sfnt.push(0);
+        }
+    }
+
+    match head_offset {
+        Some(head_offset) => {
+            let adjustment = SFNT_CHECKSUM_MAGIC - checksum(sfnt.as_slice());
+            write_u32(&mut sfnt, head_offset + HEAD_CHECKSUM_ADJUSTMENT_OFFSET, adjustment);
+        }
+        None => {}
+    }
+    Ok(sfnt)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SfntFormat, UnknownFormat, Woff2Format, WoffFormat};
+    use super::{checksum, decode_font_data, decode_woff, read_u16, read_u32, sniff, write_u32};
+    use font::UnsupportedFormat;
+    use flate;
+    use sync::Arc;
+
+    /// TestColorEmoji.ttf as WOFF. Most of its tables are compressed; 'hmtx'
+    /// and 'maxp' are too small to gain from it and are stored as they are.
+    static TEST_WOFF: &'static [u8] = include_bin!("platform/linux/fonts/TestColorEmoji.woff");
+    static TEST_SFNT: &'static [u8] = include_bin!("platform/linux/fonts/TestColorEmoji.ttf");
+
+    fn test_woff() -> Vec<u8> {
+        TEST_WOFF.iter().map(|&byte| byte).collect()
+    }
+
+    /// The tag and data of each table of an sfnt font.
+    fn sfnt_tables<'a>(sfnt: &'a [u8]) -> Vec<(u32, &'a [u8])> {
+        range(0, read_u16(sfnt, 4) as uint).map(|i| {
+            let record = 12 + 16 * i;
+            let offset = read_u32(sfnt, record + 8) as uint;
+            let length = read_u32(sfnt, record + 12) as uint;
+            (read_u32(sfnt, record), sfnt.slice(offset, offset + length))
+        }).collect()
+    }
+
+    /// Where the directory entry of the table tagged `tag` is.
+    fn woff_entry(woff: &[u8], tag: &str) -> uint {
+        range(0, read_u16(woff, 12) as uint).map(|i| 44 + 20 * i).find(|&entry| {
+            woff.slice(entry, entry + 4) == tag.as_bytes()
+        }).unwrap()
+    }
+
+    /// Sets the uncompressed length of the table at `entry`, keeping the
+    /// size of the sfnt font in the header in step.
+    fn set_table_length(woff: &mut Vec<u8>, entry: uint, length: u32) {
+        let old_length = read_u32(woff.as_slice(), entry + 12);
+        let total = read_u32(woff.as_slice(), 16);
+        write_u32(woff, 16, total - ((old_length + 3) & !3) + ((length + 3) & !3));
+        write_u32(woff, entry + 12, length);
+    }
+
+    fn assert_error(woff: Vec<u8>, reason: &str) {
+        match decode_woff(woff.as_slice()) {
+            Err(error) => assert_eq!(error, reason),
+            Ok(_) => fail!("decoded a bad WOFF font, expecting \"{:s}\"", reason),
+        }
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff(TEST_WOFF), WoffFormat);
+        assert_eq!(sniff(TEST_SFNT), SfntFormat);
+        assert_eq!(sniff("OTTO".as_bytes()), SfntFormat);
+        assert_eq!(sniff("ttcf".as_bytes()), SfntFormat);
+        assert_eq!(sniff("wOF2".as_bytes()), Woff2Format);
+        assert_eq!(sniff("<htm".as_bytes()), UnknownFormat);
+        assert_eq!(sniff("wOF".as_bytes()), UnknownFormat);
+    }
+
+    #[test]
+    fn test_sfnt_passes_through() {
+        let sfnt = Arc::new(TEST_SFNT.iter().map(|&byte| byte).collect());
+        let decoded = decode_font_data(sfnt.clone(), "test font").unwrap();
+        assert_eq!(&*sfnt as *Vec<u8>, &*decoded as *Vec<u8>);
+    }
+
+    #[test]
+    fn test_decode_woff() {
+        let sfnt = decode_woff(TEST_WOFF).unwrap();
+        assert_eq!(sniff(sfnt.as_slice()), SfntFormat);
+        assert_eq!(read_u16(sfnt.as_slice(), 4), 10);
+        // Searching the directory covers 8 of its 10 records.
+        assert_eq!(read_u16(sfnt.as_slice(), 6), 8 * 16);
+        assert_eq!(read_u16(sfnt.as_slice(), 8), 3);
+        assert_eq!(read_u16(sfnt.as_slice(), 10), 2 * 16);
+
+        let decoded = sfnt_tables(sfnt.as_slice());
+        let original = sfnt_tables(TEST_SFNT);
+        assert_eq!(decoded.len(), original.len());
+        for &(tag, data) in decoded.iter() {
+            let &(_, original_data) = original.iter().find(|&&(t, _)| t == tag).unwrap();
+            assert_eq!(data.len(), original_data.len());
+            if tag == super::HEAD_TAG {
+                assert!(data.slice_to(8) == original_data.slice_to(8));
+                assert!(data.slice_from(12) == original_data.slice_from(12));
+            } else {
+                assert!(data == original_data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoded_checksums() {
+        let sfnt = decode_woff(TEST_WOFF).unwrap();
+        for (i, &(tag, data)) in sfnt_tables(sfnt.as_slice()).iter().enumerate() {
+            let mut data = Vec::from_slice(data);
+            if tag == super::HEAD_TAG {
+                write_u32(&mut data, 8, 0);
+            }
+            assert_eq!(read_u32(sfnt.as_slice(), 12 + 16 * i + 4), checksum(data.as_slice()));
+        }
+        assert_eq!(checksum(sfnt.as_slice()), super::SFNT_CHECKSUM_MAGIC);
+    }
+
+    #[test]
+    fn test_truncated_woff() {
+        // Shortening the data or the length in the header alone is caught
+        // straight away, so shorten both.
+        let woff = test_woff();
+        let last_entry = 44 + 20 * (read_u16(woff.as_slice(), 12) as uint - 1);
+        let end = read_u32(woff.as_slice(), last_entry + 4) as uint +
+            read_u32(woff.as_slice(), last_entry + 8) as uint;
+        for length in range(0, end) {
+            let mut truncated = Vec::from_slice(woff.slice_to(length));
+            if length >= 12 {
+                write_u32(&mut truncated, 8, length as u32);
+            }
+            assert!(decode_woff(truncated.as_slice()).is_err());
+        }
+
+        let truncated = Vec::from_slice(woff.slice_to(woff.len() - 1));
+        assert_error(truncated, "the WOFF header gives the wrong length");
+    }
+
+    #[test]
+    fn test_corrupt_woff() {
+        let woff = test_woff();
+
+        let mut no_tables = woff.clone();
+        *no_tables.get_mut(12) = 0;
+        *no_tables.get_mut(13) = 0;
+        assert_error(no_tables, "the WOFF font has no tables");
+
+        let mut reserved = woff.clone();
+        *reserved.get_mut(15) = 1;
+        assert_error(reserved, "the reserved WOFF header field is set");
+
+        let entry = woff_entry(woff.as_slice(), "name");
+        let (compressed_length, length) = (read_u32(woff.as_slice(), entry + 8),
+                                           read_u32(woff.as_slice(), entry + 12));
+        assert!(compressed_length < length);
+
+        let mut outside = woff.clone();
+        write_u32(&mut outside, entry + 4, 0xFFFFFFFF);
+        assert_error(outside, "a WOFF table lies outside the font");
+
+        let mut inside_directory = woff.clone();
+        write_u32(&mut inside_directory, entry + 4, 44);
+        assert_error(inside_directory, "a WOFF table lies outside the font");
+
+        let mut bigger = woff.clone();
+        write_u32(&mut bigger, entry + 12, compressed_length - 1);
+        assert_error(bigger, "a WOFF table is bigger compressed than uncompressed");
+
+        let mut wrong_length = woff.clone();
+        set_table_length(&mut wrong_length, entry, length + 1);
+        assert_error(wrong_length, "a WOFF table decompresses to the wrong length");
+
+        let mut wrong_total = woff.clone();
+        write_u32(&mut wrong_total, 16, read_u32(woff.as_slice(), 16) + 4);
+        assert_error(wrong_total, "the WOFF header gives the wrong sfnt size");
+
+        let mut garbage = woff.clone();
+        let offset = read_u32(woff.as_slice(), entry + 4) as uint;
+        for i in range(0, compressed_length as uint) {
+            *garbage.get_mut(offset + i) = 0xFF;
+        }
+        assert_error(garbage, "a WOFF table doesn't decompress");
+    }
+
+    #[test]
+    fn test_oversized_woff() {
+        let woff = test_woff();
+        let num_tables = read_u16(woff.as_slice(), 12) as uint;
+
+        // A tiny font whose tables claim to be nearly 4GB each must be
+        // turned down before anything is allocated for them.
+        let mut huge = woff.clone();
+        for i in range(0, num_tables) {
+            write_u32(&mut huge, 44 + 20 * i + 12, 0xFFFFFFFF);
+        }
+        assert_error(huge, "the WOFF font is too large");
+
+        // As must one whose tables are each of a plausible size, but too
+        // large together, even when the header agrees with them.
+        let mut large = woff.clone();
+        for i in range(0, num_tables) {
+            set_table_length(&mut large, 44 + 20 * i, 4 * 1024 * 1024);
+        }
+        assert_error(large, "the WOFF font is too large");
+    }
+
+    #[test]
+    fn test_zlib_bomb() {
+        // Swap 'name' for a megabyte of zeros, which compresses to about a
+        // kilobyte, while claiming it inflates to just that kilobyte.
+        let zeros = Vec::from_elem(1024 * 1024, 0u8);
+        let bomb = flate::deflate_bytes_zlib(zeros.as_slice()).unwrap();
+        let mut woff = test_woff();
+        let entry = woff_entry(woff.as_slice(), "name");
+        while woff.len() % 4 != 0 {
+            woff.push(0);
+        }
+        let offset = woff.len();
+        woff.push_all(bomb.as_slice());
+        let woff_length = woff.len();
+        write_u32(&mut woff, 8, woff_length as u32);
+        write_u32(&mut woff, entry + 4, offset as u32);
+        write_u32(&mut woff, entry + 8, bomb.len() as u32);
+        set_table_length(&mut woff, entry, bomb.len() as u32);
+        assert_error(woff, "a WOFF table doesn't decompress");
+    }
+
+    #[test]
+    fn test_flipped_bytes_never_crash() {
+        let woff = test_woff();
+        for i in range(0, woff.len()) {
+            let mut corrupt = woff.clone();
+            *corrupt.get_mut(i) ^= 0xFF;
+            // Most of these are still well formed, so all that matters is
+            // getting an answer.
+            let _ = decode_woff(corrupt.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_bad_data_is_a_font_error() {
+        let html = Arc::new(Vec::from_slice("<html></html>".as_bytes()));
+        match decode_font_data(html, "font buffer") {
+            Err(UnsupportedFormat(ref description, None)) => {
+                assert_eq!(description.as_slice(), "font buffer: not a WOFF or sfnt font");
+            }
+            _ => fail!("decoded HTML as a font"),
+        }
+
+        let mut truncated = test_woff();
+        truncated.truncate(100);
+        assert!(decode_font_data(Arc::new(truncated), "font buffer").is_err());
+    }
+}
@@ -7,7 +7,9 @@ use compositing::compositor_layer::CompositorLayer;
 use compositing::*;
 use pipeline::CompositionPipeline;
 use platform::{Application, Window};
-use windowing::{FinishedWindowEvent, IdleWindowEvent, LoadUrlWindowEvent, MouseWindowClickEvent};
+use windowing::{CompositionWindowEvent, FinishedWindowEvent, IdleWindowEvent, KeyWindowEvent};
+use windowing::LoadUrlWindowEvent;
+use windowing::MouseWindowClickEvent;
 use windowing::{MouseWindowEvent, MouseWindowEventClass, MouseWindowMouseDownEvent};
 use windowing::{MouseWindowMouseUpEvent, MouseWindowMoveEventClass, NavigationWindowEvent};
 use windowing::{QuitWindowEvent, RefreshWindowEvent, ResizeWindowEvent, ScrollWindowEvent};
@@ -30,7 +32,9 @@ use opengles::gl2;
 use png;
 use servo_msg::compositor_msg::{Blank, Epoch, FinishedLoading, IdleRenderState, LayerBufferSet};
 use servo_msg::compositor_msg::{LayerId, ReadyState, RenderState, ScrollPolicy, Scrollable};
-use servo_msg::constellation_msg::{ConstellationChan, ExitMsg, LoadUrlMsg, NavigateMsg};
+use servo_msg::constellation_msg::{CompositionInput, ConstellationChan, ExitMsg, KeyInput};
+use servo_msg::constellation_msg::{LoadUrlMsg, NavigateMsg};
+use servo_msg::constellation_msg::{KeyModifiers, MouseInput, WheelInput};
 use servo_msg::constellation_msg::{PipelineId, ResizedWindowMsg, WindowSizeData};
 use servo_msg::constellation_msg;
 use servo_util::geometry::{DevicePixel, PagePx, ScreenPx, ViewportPx};
@@ -121,7 +125,10 @@ pub struct IOCompositor {
     memory_profiler_chan: MemoryProfilerChan,
 
     /// Pending scroll to fragment event, if any
-    fragment_point: Option<Point2D<f32>>
+    fragment_point: Option<Point2D<f32>>,
+
+    /// The mouse buttons held down, as a DOM `MouseEvent.buttons` bit set.
+    mouse_buttons: u16,
 }
 
 impl IOCompositor {
@@ -166,7 +173,8 @@ impl IOCompositor {
             constellation_chan: constellation_chan,
             time_profiler_chan: time_profiler_chan,
             memory_profiler_chan: memory_profiler_chan,
-            fragment_point: None
+            fragment_point: None,
+            mouse_buttons: 0,
         }
     }
 
@@ -318,6 +326,10 @@ impl IOCompositor {
                     self.scroll_fragment_to_point(pipeline_id, layer_id, point);
                 }
 
+                (Ok(Scroll(delta, window_point)), false) => {
+                    self.scroll(delta, window_point);
+                }
+
                 (Ok(LoadComplete(..)), false) => {
                     self.load_complete = true;
                 }
@@ -559,12 +571,12 @@ impl IOCompositor {
                 self.on_load_url_window_event(url_string);
             }
 
-            MouseWindowEventClass(mouse_window_event) => {
-                self.on_mouse_window_event_class(mouse_window_event);
+            MouseWindowEventClass(mouse_window_event, modifiers) => {
+                self.on_mouse_window_event_class(mouse_window_event, modifiers);
             }
 
-            MouseWindowMoveEventClass(cursor) => {
-                self.on_mouse_window_move_event_class(cursor);
+            MouseWindowMoveEventClass(cursor, modifiers) => {
+                self.on_mouse_window_move_event_class(cursor, modifiers);
             }
 
             ScrollWindowEvent(delta, cursor) => {
@@ -583,6 +595,14 @@ impl IOCompositor {
                 self.on_navigation_window_event(direction);
             }
 
+            KeyWindowEvent(input) => {
+                self.on_key_window_event(input);
+            }
+
+            CompositionWindowEvent(input) => {
+                self.on_composition_window_event(input);
+            }
+
             FinishedWindowEvent => {
                 let exit = self.opts.exit_after_load;
                 if exit {
@@ -631,32 +651,82 @@ impl IOCompositor {
         chan.send(msg);
     }
 
-    fn on_mouse_window_event_class(&self, mouse_window_event: MouseWindowEvent) {
-        let scale = self.device_pixels_per_page_px();
-        let point = match mouse_window_event {
-            MouseWindowClickEvent(_, p) => p / scale,
-            MouseWindowMouseDownEvent(_, p) => p / scale,
-            MouseWindowMouseUpEvent(_, p) => p / scale,
+    fn on_mouse_window_event_class(&mut self,
+                                   mouse_window_event: MouseWindowEvent,
+                                   modifiers: KeyModifiers) {
+        let (button, point) = match mouse_window_event {
+            MouseWindowClickEvent(button, p) => (button, p),
+            MouseWindowMouseDownEvent(button, p) => (button, p),
+            MouseWindowMouseUpEvent(button, p) => (button, p),
         };
+
+        // The windowing layer numbers buttons left, right, middle, which is also the order of the
+        // bits in `MouseEvent.buttons`. `MouseEvent.button` numbers them left, middle, right.
+        let bit = 1u16 << button;
+        match mouse_window_event {
+            MouseWindowMouseDownEvent(..) => self.mouse_buttons |= bit,
+            MouseWindowMouseUpEvent(..) => self.mouse_buttons &= !bit,
+            MouseWindowClickEvent(..) => {}
+        }
+        let dom_button = match button {
+            1 => 2,
+            2 => 1,
+            button => button as i16,
+        };
+
+        let input = self.mouse_input(point, dom_button, modifiers);
+        let scale = self.device_pixels_per_page_px();
         for layer in self.compositor_layer.iter() {
-            layer.send_mouse_event(mouse_window_event, point);
+            layer.send_mouse_event(mouse_window_event, point / scale, input.clone());
         }
     }
 
-    fn on_mouse_window_move_event_class(&self, cursor: TypedPoint2D<DevicePixel, f32>) {
+    fn on_mouse_window_move_event_class(&self,
+                                        cursor: TypedPoint2D<DevicePixel, f32>,
+                                        modifiers: KeyModifiers) {
+        let input = self.mouse_input(cursor, 0, modifiers);
         let scale = self.device_pixels_per_page_px();
         for layer in self.compositor_layer.iter() {
-            layer.send_mouse_move_event(cursor / scale);
+            layer.send_mouse_move_event(cursor / scale, input.clone());
+        }
+    }
+
+    /// Describes a mouse event at `point` for script. The layer that gets the event fills in the
+    /// client and page coordinates, which depend on where it is and how far it is scrolled.
+    fn mouse_input(&self, point: TypedPoint2D<DevicePixel, f32>, button: i16,
+                   modifiers: KeyModifiers) -> MouseInput {
+        let screen_point = self.window.screen_origin() + point / self.hidpi_factor;
+        MouseInput {
+            client_point: Point2D(0.0, 0.0),
+            page_point: Point2D(0.0, 0.0),
+            screen_point: screen_point.to_untyped(),
+            button: button,
+            buttons: self.mouse_buttons,
+            modifiers: modifiers,
         }
     }
 
-    fn on_scroll_window_event(&mut self,
+    /// Sends a turn of the scroll wheel to script, which fires a `wheel` event and then has us
+    /// scroll, through `scroll`, unless a listener canceled it.
+    fn on_scroll_window_event(&self,
                               delta: TypedPoint2D<DevicePixel, f32>,
                               cursor: TypedPoint2D<DevicePixel, i32>) {
         let scale = self.device_pixels_per_page_px();
-        // TODO: modify delta to snap scroll to pixels.
-        let page_delta = delta / scale;
         let page_cursor = cursor.as_f32() / scale;
+        let input = WheelInput {
+            mouse: self.mouse_input(cursor.as_f32(), 0, KeyModifiers::none()),
+            delta: (delta / scale).to_untyped(),
+            window_point: page_cursor.to_untyped(),
+        };
+        for layer in self.compositor_layer.iter() {
+            layer.send_wheel_event(page_cursor, input.clone());
+        }
+    }
+
+    fn scroll(&mut self, delta: Point2D<f32>, window_point: Point2D<f32>) {
+        // TODO: modify delta to snap scroll to pixels.
+        let page_delta: TypedPoint2D<PagePx, f32> = Point2D::from_untyped(&delta);
+        let page_cursor: TypedPoint2D<PagePx, f32> = Point2D::from_untyped(&window_point);
         let page_window = self.page_window();
         let mut scroll = false;
         for layer in self.compositor_layer.mut_iter() {
@@ -726,6 +796,21 @@ impl IOCompositor {
         chan.send(NavigateMsg(direction))
     }
 
+    fn on_key_window_event(&self, input: KeyInput) {
+        // Focus is tracked by script, so keys go to the root page, which passes them on to the
+        // focused element.
+        for layer in self.compositor_layer.iter() {
+            layer.send_key_event(input.clone());
+        }
+    }
+
+    fn on_composition_window_event(&self, input: CompositionInput) {
+        // Composed text goes wherever keys go.
+        for layer in self.compositor_layer.iter() {
+            layer.send_composition_event(input.clone());
+        }
+    }
+
     /// Get BufferRequests from each layer.
     fn ask_for_tiles(&mut self) {
         let scale = self.device_pixels_per_page_px();
@@ -745,7 +830,7 @@ impl IOCompositor {
     }
 
     fn composite(&mut self) {
-        profile(time::CompositingCategory, self.time_profiler_chan.clone(), || {
+        profile(time::CompositingCategory, None, self.time_profiler_chan.clone(), || {
             debug!("compositor: compositing");
             // Adjust the layer dimensions as necessary to correspond to the size of the window.
             self.scene.size = self.window_size.as_f32().to_untyped();
@@ -19,11 +19,14 @@ use layers::layers::{ContainerLayerKind, ContainerLayer, Flip, NoFlip, TextureLa
 use layers::layers::TextureLayerKind;
 use layers::platform::surface::{NativeCompositingGraphicsContext, NativeSurfaceMethods};
 use layers::texturegl::{Texture, TextureTarget};
-use script::dom::event::{ClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent};
+use script::dom::event::{ClickEvent, CompositionInputEvent, KeyEvent, MouseDownEvent};
+use script::dom::event::{MouseMoveEvent, MouseUpEvent};
+use script::dom::event::MouseWheelEvent;
 use script::script_task::{ScriptChan, SendEventMsg};
 use servo_msg::compositor_msg::{Epoch, FixedPosition, LayerBuffer, LayerBufferSet, LayerId};
 use servo_msg::compositor_msg::{ScrollPolicy, Tile};
-use servo_msg::constellation_msg::PipelineId;
+use servo_msg::constellation_msg::{CompositionInput, KeyInput, MouseInput, PipelineId};
+use servo_msg::constellation_msg::WheelInput;
 use servo_util::geometry::PagePx;
 use std::rc::Rc;
 
@@ -387,11 +390,14 @@ impl CompositorLayer {
         result
     }
 
-    // Takes in a MouseWindowEvent, determines if it should be passed to children, and
-    // sends the event off to the appropriate pipeline. NB: the cursor position is in
-    // page coordinates.
-    pub fn send_mouse_event(&self, event: MouseWindowEvent, cursor: TypedPoint2D<PagePx, f32>) {
-        let cursor = cursor - self.scroll_offset;
+    /// Finds the layer that a cursor at `cursor` is over, descending into whichever child's
+    /// scissor box contains it. Returns that layer along with the cursor position relative to the
+    /// layer's viewport (client coordinates) and to its scrolled content (page coordinates).
+    fn mouse_event_target<'a>(&'a self, cursor: TypedPoint2D<PagePx, f32>)
+                              -> (&'a CompositorLayer,
+                                  TypedPoint2D<PagePx, f32>,
+                                  TypedPoint2D<PagePx, f32>) {
+        let page_cursor = cursor - self.scroll_offset;
         for child in self.children.iter().filter(|&x| !x.child.hidden) {
             match *child.container.scissor.borrow() {
                 None => {
@@ -399,29 +405,79 @@ impl CompositorLayer {
                 }
                 Some(rect) => {
                     let rect: TypedRect<PagePx, f32> = Rect::from_untyped(&rect);
-                    if cursor.x >= rect.origin.x && cursor.x < rect.origin.x + rect.size.width
-                        && cursor.y >= rect.origin.y && cursor.y < rect.origin.y + rect.size.height {
-                        child.child.send_mouse_event(event, cursor - rect.origin);
-                        return;
+                    if page_cursor.x >= rect.origin.x &&
+                            page_cursor.x < rect.origin.x + rect.size.width &&
+                            page_cursor.y >= rect.origin.y &&
+                            page_cursor.y < rect.origin.y + rect.size.height {
+                        return child.child.mouse_event_target(page_cursor - rect.origin);
                     }
                 }
             }
         }
 
         // This mouse event is mine!
+        (self, cursor, page_cursor)
+    }
+
+    // Takes in a MouseWindowEvent, determines if it should be passed to children, and
+    // sends the event off to the appropriate pipeline. NB: the cursor position is in
+    // page coordinates. `input` comes with only its screen position, buttons and modifiers
+    // filled in.
+    pub fn send_mouse_event(&self,
+                            event: MouseWindowEvent,
+                            cursor: TypedPoint2D<PagePx, f32>,
+                            input: MouseInput) {
+        let (layer, client_point, page_point) = self.mouse_event_target(cursor);
+        let input = MouseInput {
+            client_point: client_point.to_untyped(),
+            page_point: page_point.to_untyped(),
+            ..input
+        };
         let message = match event {
-            MouseWindowClickEvent(button, _) => ClickEvent(button, cursor.to_untyped()),
-            MouseWindowMouseDownEvent(button, _) => MouseDownEvent(button, cursor.to_untyped()),
-            MouseWindowMouseUpEvent(button, _) => MouseUpEvent(button, cursor.to_untyped()),
+            MouseWindowClickEvent(..) => ClickEvent(input),
+            MouseWindowMouseDownEvent(..) => MouseDownEvent(input),
+            MouseWindowMouseUpEvent(..) => MouseUpEvent(input),
         };
+        let ScriptChan(ref chan) = layer.pipeline.script_chan;
+        let _ = chan.send_opt(SendEventMsg(layer.pipeline.id.clone(), message));
+    }
+
+    pub fn send_mouse_move_event(&self, cursor: TypedPoint2D<PagePx, f32>, input: MouseInput) {
+        let (layer, client_point, page_point) = self.mouse_event_target(cursor);
+        let input = MouseInput {
+            client_point: client_point.to_untyped(),
+            page_point: page_point.to_untyped(),
+            ..input
+        };
+        let ScriptChan(ref chan) = layer.pipeline.script_chan;
+        let _ = chan.send_opt(SendEventMsg(layer.pipeline.id.clone(), MouseMoveEvent(input)));
+    }
+
+    /// Sends a turn of the scroll wheel, with the cursor at `cursor` in page coordinates, to the
+    /// pipeline of the layer under the cursor.
+    pub fn send_wheel_event(&self, cursor: TypedPoint2D<PagePx, f32>, input: WheelInput) {
+        let (layer, client_point, page_point) = self.mouse_event_target(cursor);
+        let input = WheelInput {
+            mouse: MouseInput {
+                client_point: client_point.to_untyped(),
+                page_point: page_point.to_untyped(),
+                ..input.mouse
+            },
+            ..input
+        };
+        let ScriptChan(ref chan) = layer.pipeline.script_chan;
+        let _ = chan.send_opt(SendEventMsg(layer.pipeline.id.clone(), MouseWheelEvent(input)));
+    }
+
+    pub fn send_key_event(&self, input: KeyInput) {
         let ScriptChan(ref chan) = self.pipeline.script_chan;
-        let _ = chan.send_opt(SendEventMsg(self.pipeline.id.clone(), message));
+        let _ = chan.send_opt(SendEventMsg(self.pipeline.id.clone(), KeyEvent(input)));
     }
 
-    pub fn send_mouse_move_event(&self, cursor: TypedPoint2D<PagePx, f32>) {
-        let message = MouseMoveEvent(cursor.to_untyped());
+    pub fn send_composition_event(&self, input: CompositionInput) {
         let ScriptChan(ref chan) = self.pipeline.script_chan;
-        let _ = chan.send_opt(SendEventMsg(self.pipeline.id.clone(), message));
+        let _ = chan.send_opt(SendEventMsg(self.pipeline.id.clone(),
+                                           CompositionInputEvent(input)));
     }
 
     // Given the current window size, determine which tiles need to be (re-)rendered and sends them
@@ -53,6 +53,10 @@ impl ScriptListener for CompositorChan {
 	    self.chan.send(ScrollFragmentPoint(pipeline_id, layer_id, point));
     }
 
+    fn scroll(&self, delta: Point2D<f32>, window_point: Point2D<f32>) {
+        self.chan.send(Scroll(delta, window_point));
+    }
+
     fn close(&self) {
         let (chan, port) = channel();
         self.chan.send(Exit(chan));
@@ -174,6 +178,8 @@ pub enum Msg {
     SetLayerClipRect(PipelineId, LayerId, Rect<f32>),
     /// Scroll a page in a window
     ScrollFragmentPoint(PipelineId, LayerId, Point2D<f32>),
+    /// Scroll whichever layer is under a point in the window by a delta, both in CSS px.
+    Scroll(Point2D<f32>, Point2D<f32>),
     /// Requests that the compositor paint the given layer buffer set for the given page size.
     Paint(PipelineId, LayerId, Box<LayerBufferSet>, Epoch),
     /// Alerts the compositor to the current status of page loading.
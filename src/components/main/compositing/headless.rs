@@ -89,7 +89,7 @@ impl NullCompositor {
                 CreateDescendantCompositorLayerIfNecessary(..) | SetLayerPageSize(..) |
                 SetLayerClipRect(..) | Paint(..) |
                 ChangeReadyState(..) | ChangeRenderState(..) | ScrollFragmentPoint(..) |
-                SetUnRenderedColor(..) | LoadComplete(..) => ()
+                Scroll(..) | SetUnRenderedColor(..) | LoadComplete(..) => ()
             }
         }
     }
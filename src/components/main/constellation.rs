@@ -5,19 +5,23 @@
 use compositing::{CompositorChan, LoadComplete, SetIds, SetLayerClipRect, ShutdownComplete};
 
 use std::collections::hashmap::{HashMap, HashSet};
+use geom::point::Point2D;
 use geom::rect::{Rect, TypedRect};
 use geom::scale_factor::ScaleFactor;
 use geom::size::TypedSize2D;
 use gfx::render_task;
 use libc;
 use pipeline::{Pipeline, CompositionPipeline};
-use script::script_task::{ResizeMsg, ResizeInactiveMsg, ExitPipelineMsg};
+use script::dom::event::{ClickEvent, MouseDownEvent, MouseUpEvent};
+use script::script_task::{DispatchCustomEventMsg, ResizeMsg, ResizeInactiveMsg, ExitPipelineMsg};
+use script::script_task::SendEventMsg;
 use script::layout_interface;
 use script::layout_interface::LayoutChan;
 use script::script_task::ScriptChan;
 use servo_msg::compositor_msg::LayerId;
 use servo_msg::constellation_msg::{ConstellationChan, ExitMsg, FailureMsg, Failure, FrameRectMsg};
 use servo_msg::constellation_msg::{IFrameSandboxState, IFrameUnsandboxed, InitLoadUrlMsg};
+use servo_msg::constellation_msg::{KeyModifiers, MouseInput};
 use servo_msg::constellation_msg::{LoadCompleteMsg, LoadIframeUrlMsg, LoadUrlMsg, Msg, NavigateMsg};
 use servo_msg::constellation_msg::{NavigationType, PipelineId, RendererReadyMsg, ResizedWindowMsg};
 use servo_msg::constellation_msg::{SubpageId, WindowSizeData};
@@ -26,6 +30,7 @@ use servo_net::image_cache_task::{ImageCacheTask, ImageCacheTaskClient};
 use servo_net::resource_task::ResourceTask;
 use servo_net::resource_task;
 use servo_util::geometry::PagePx;
+use servo_util::memory::MemoryProfilerChan;
 use servo_util::opts::Opts;
 use servo_util::time::TimeProfilerChan;
 use servo_util::url::parse_url;
@@ -49,6 +54,7 @@ pub struct Constellation {
     pending_frames: Vec<FrameChange>,
     pending_sizes: HashMap<(PipelineId, SubpageId), TypedRect<PagePx, f32>>,
     pub time_profiler_chan: TimeProfilerChan,
+    pub memory_profiler_chan: MemoryProfilerChan,
     pub window_size: WindowSizeData,
     pub opts: Opts,
 }
@@ -244,7 +250,8 @@ impl Constellation {
                  opts: &Opts,
                  resource_task: ResourceTask,
                  image_cache_task: ImageCacheTask,
-                 time_profiler_chan: TimeProfilerChan)
+                 time_profiler_chan: TimeProfilerChan,
+                 memory_profiler_chan: MemoryProfilerChan)
                  -> ConstellationChan {
         let (constellation_port, constellation_chan) = ConstellationChan::new();
         let constellation_chan_clone = constellation_chan.clone();
@@ -262,6 +269,7 @@ impl Constellation {
                 pending_frames: vec!(),
                 pending_sizes: HashMap::new(),
                 time_profiler_chan: time_profiler_chan,
+                memory_profiler_chan: memory_profiler_chan,
                 window_size: WindowSizeData {
                     visible_viewport: TypedSize2D(800_f32, 600_f32),
                     initial_viewport: TypedSize2D(800_f32, 600_f32),
@@ -343,6 +351,8 @@ impl Constellation {
             // script, and reflow messages have been sent.
             LoadCompleteMsg(pipeline_id, url) => {
                 debug!("constellation got load complete message");
+                self.dispatch_custom_events(pipeline_id);
+                self.dispatch_clicks(pipeline_id);
                 self.compositor_chan.send(LoadComplete(pipeline_id, url));
             }
             // Handle a forward or back request
@@ -363,6 +373,52 @@ impl Constellation {
         true
     }
 
+    /// Fires the events given with `--custom-event` at a top-level page that has just loaded.
+    fn dispatch_custom_events(&self, pipeline_id: PipelineId) {
+        let pipeline = match self.pipelines.find(&pipeline_id) {
+            Some(pipeline) if pipeline.subpage_id.is_none() => pipeline,
+            _ => return,
+        };
+        let ScriptChan(ref chan) = pipeline.script_chan;
+        for &(ref type_, ref detail) in self.opts.custom_events.iter() {
+            let (reply_chan, reply_port) = channel();
+            chan.send(DispatchCustomEventMsg(pipeline_id, type_.clone(), detail.clone(),
+                                             reply_chan));
+            let type_ = type_.clone();
+            spawn_named("CustomEventReply", proc() {
+                match reply_port.recv_opt() {
+                    Ok(canceled) => debug!("{:s} event canceled: {}", type_, canceled),
+                    Err(()) => debug!("{:s} event not dispatched", type_),
+                }
+            });
+        }
+    }
+
+    /// Clicks at the points given with `--click` on a top-level page that has just loaded. The page
+    /// hasn't been scrolled yet, so the points are the same in every coordinate space.
+    fn dispatch_clicks(&self, pipeline_id: PipelineId) {
+        let pipeline = match self.pipelines.find(&pipeline_id) {
+            Some(pipeline) if pipeline.subpage_id.is_none() => pipeline,
+            _ => return,
+        };
+        let ScriptChan(ref chan) = pipeline.script_chan;
+        for &(x, y) in self.opts.clicks.iter() {
+            let input = |buttons| {
+                MouseInput {
+                    client_point: Point2D(x, y),
+                    page_point: Point2D(x, y),
+                    screen_point: Point2D(x, y),
+                    button: 0,
+                    buttons: buttons,
+                    modifiers: KeyModifiers::none(),
+                }
+            };
+            chan.send(SendEventMsg(pipeline_id, MouseDownEvent(input(1))));
+            chan.send(SendEventMsg(pipeline_id, MouseUpEvent(input(0))));
+            chan.send(SendEventMsg(pipeline_id, ClickEvent(input(0))));
+        }
+    }
+
     fn handle_exit(&self) {
         for (_id, ref pipeline) in self.pipelines.iter() {
             pipeline.exit();
@@ -425,6 +481,7 @@ impl Constellation {
                                         self.image_cache_task.clone(),
                                         self.resource_task.clone(),
                                         self.time_profiler_chan.clone(),
+                                        self.memory_profiler_chan.clone(),
                                         self.window_size,
                                         self.opts.clone(),
                                         parse_url("about:failure", None));
@@ -452,6 +509,7 @@ impl Constellation {
                                         self.image_cache_task.clone(),
                                         self.resource_task.clone(),
                                         self.time_profiler_chan.clone(),
+                                        self.memory_profiler_chan.clone(),
                                         self.window_size,
                                         self.opts.clone(),
                                         url);
@@ -577,6 +635,7 @@ impl Constellation {
                                   self.compositor_chan.clone(),
                                   self.image_cache_task.clone(),
                                   self.time_profiler_chan.clone(),
+                                  self.memory_profiler_chan.clone(),
                                   self.opts.clone(),
                                   source_pipeline.clone(),
                                   url)
@@ -590,6 +649,7 @@ impl Constellation {
                              self.image_cache_task.clone(),
                              self.resource_task.clone(),
                              self.time_profiler_chan.clone(),
+                             self.memory_profiler_chan.clone(),
                              self.window_size,
                              self.opts.clone(),
                              url)
@@ -646,6 +706,7 @@ impl Constellation {
                                         self.image_cache_task.clone(),
                                         self.resource_task.clone(),
                                         self.time_profiler_chan.clone(),
+                                        self.memory_profiler_chan.clone(),
                                         self.window_size,
                                         self.opts.clone(),
                                         url);
@@ -12,14 +12,16 @@ use layout::util::{LayoutDataAccess, LayoutDataWrapper};
 use layout::wrapper::{LayoutElement, LayoutNode, PostorderNodeMutTraversal, ThreadSafeLayoutNode};
 
 use gfx::font_context::FontContext;
+use servo_util::atom::Atom;
+use servo_util::bloom::BloomFilter;
 use servo_util::cache::{Cache, LRUCache, SimpleHashCache};
 use servo_util::namespace::Null;
 use servo_util::smallvec::{SmallVec, SmallVec16};
-use servo_util::str::DOMString;
 use std::mem;
 use std::hash::{Hash, sip};
 use std::slice::Items;
 use style::{After, Before, ComputedValues, MatchedProperty, Stylist, TElement, TNode, cascade};
+use style;
 use sync::Arc;
 
 pub struct ApplicableDeclarations {
@@ -163,10 +165,9 @@ pub struct StyleSharingCandidate {
     pub style: Arc<ComputedValues>,
     pub parent_style: Arc<ComputedValues>,
 
-    // TODO(pcwalton): Intern.
-    pub local_name: DOMString,
+    pub local_name: Atom,
 
-    pub class: Option<DOMString>,
+    pub class: Option<String>,
 }
 
 impl PartialEq for StyleSharingCandidate {
@@ -224,14 +225,14 @@ impl StyleSharingCandidate {
         Some(StyleSharingCandidate {
             style: style.take_unwrap(),
             parent_style: parent_style.take_unwrap(),
-            local_name: element.get_local_name().to_str(),
+            local_name: element.get_local_name().clone(),
             class: element.get_attr(&Null, "class")
                           .map(|string| string.to_str()),
         })
     }
 
     fn can_share_style_with(&self, element: &LayoutElement) -> bool {
-        if element.get_local_name() != self.local_name.as_slice() {
+        if *element.get_local_name() != self.local_name {
             return false
         }
         match (&self.class, element.get_attr(&Null, "class")) {
@@ -282,7 +283,7 @@ pub enum StyleSharingResult<'ln> {
 
 pub trait MatchMethods {
     /// Performs aux initialization, selector matching, cascading, and flow construction
-    /// sequentially.
+    /// sequentially. `bloom_filter` must hold the ancestors of this node, and is left that way.
     fn recalc_style_for_subtree(&self,
                                 stylist: &Stylist,
                                 layout_context: &mut LayoutContext,
@@ -290,14 +291,23 @@ pub trait MatchMethods {
                                 applicable_declarations: &mut ApplicableDeclarations,
                                 applicable_declarations_cache: &mut ApplicableDeclarationsCache,
                                 style_sharing_candidate_cache: &mut StyleSharingCandidateCache,
+                                bloom_filter: &mut BloomFilter,
                                 parent: Option<LayoutNode>)
                                 -> Box<FontContext>;
 
+    /// Performs selector matching. `parent_bf`, if present, holds the ancestors of this node.
     fn match_node(&self,
                   stylist: &Stylist,
+                  parent_bf: Option<&BloomFilter>,
                   applicable_declarations: &mut ApplicableDeclarations,
                   shareable: &mut bool);
 
+    /// Adds this node to a Bloom filter of ancestors for selector matching, if it is an element.
+    fn insert_into_bloom_filter(&self, bf: &mut BloomFilter);
+
+    /// Undoes `insert_into_bloom_filter`.
+    fn remove_from_bloom_filter(&self, bf: &mut BloomFilter);
+
     /// Attempts to share a style with another node. This method is unsafe because it depends on
     /// the `style_sharing_candidate_cache` having only live nodes in it, and we have no way to
     /// guarantee that at the type system level yet.
@@ -410,20 +420,24 @@ impl<'ln> PrivateMatchMethods for LayoutNode<'ln> {
 impl<'ln> MatchMethods for LayoutNode<'ln> {
     fn match_node(&self,
                   stylist: &Stylist,
+                  parent_bf: Option<&BloomFilter>,
                   applicable_declarations: &mut ApplicableDeclarations,
                   shareable: &mut bool) {
         let style_attribute = self.as_element().style_attribute().as_ref();
 
         applicable_declarations.normal_shareable =
             stylist.push_applicable_declarations(self,
+                                                 parent_bf,
                                                  style_attribute,
                                                  None,
                                                  &mut applicable_declarations.normal);
         stylist.push_applicable_declarations(self,
+                                             parent_bf,
                                              None,
                                              Some(Before),
                                              &mut applicable_declarations.before);
         stylist.push_applicable_declarations(self,
+                                             parent_bf,
                                              None,
                                              Some(After),
                                              &mut applicable_declarations.after);
@@ -431,6 +445,18 @@ impl<'ln> MatchMethods for LayoutNode<'ln> {
         *shareable = applicable_declarations.normal_shareable
     }
 
+    fn insert_into_bloom_filter(&self, bf: &mut BloomFilter) {
+        if self.is_element() {
+            style::insert_into_bloom_filter(&self.as_element(), bf)
+        }
+    }
+
+    fn remove_from_bloom_filter(&self, bf: &mut BloomFilter) {
+        if self.is_element() {
+            style::remove_from_bloom_filter(&self.as_element(), bf)
+        }
+    }
+
     unsafe fn share_style_if_possible(&self,
                                       style_sharing_candidate_cache:
                                         &mut StyleSharingCandidateCache,
@@ -469,6 +495,7 @@ impl<'ln> MatchMethods for LayoutNode<'ln> {
                                 applicable_declarations: &mut ApplicableDeclarations,
                                 applicable_declarations_cache: &mut ApplicableDeclarationsCache,
                                 style_sharing_candidate_cache: &mut StyleSharingCandidateCache,
+                                bloom_filter: &mut BloomFilter,
                                 parent: Option<LayoutNode>)
                                 -> Box<FontContext> {
         self.initialize_layout_data(layout_context.layout_chan.clone());
@@ -482,7 +509,10 @@ impl<'ln> MatchMethods for LayoutNode<'ln> {
         match sharing_result {
             CannotShare(mut shareable) => {
                 if self.is_element() {
-                    self.match_node(stylist, applicable_declarations, &mut shareable)
+                    self.match_node(stylist,
+                                    Some(&*bloom_filter),
+                                    applicable_declarations,
+                                    &mut shareable)
                 }
 
                 unsafe {
@@ -501,6 +531,7 @@ impl<'ln> MatchMethods for LayoutNode<'ln> {
             StyleWasShared(index) => style_sharing_candidate_cache.touch(index),
         }
 
+        self.insert_into_bloom_filter(bloom_filter);
         for kid in self.children() {
             font_context = kid.recalc_style_for_subtree(stylist,
                                                         layout_context,
@@ -508,8 +539,10 @@ impl<'ln> MatchMethods for LayoutNode<'ln> {
                                                         applicable_declarations,
                                                         applicable_declarations_cache,
                                                         style_sharing_candidate_cache,
+                                                        bloom_filter,
                                                         Some(self.clone()))
         }
+        self.remove_from_bloom_filter(bloom_filter);
 
         // Construct flows.
         let layout_node = ThreadSafeLayoutNode::new(self);
@@ -86,6 +86,9 @@ pub struct LayoutContext {
 
     /// The dirty rectangle, used during display list building.
     pub dirty: Rect<Au>,
+
+    /// The reflow this context belongs to. See `LayoutTask::generation`.
+    pub generation: uint,
 }
 
 #[cfg(not(target_os="android"))]
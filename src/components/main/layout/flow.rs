@@ -353,6 +353,9 @@ pub trait ImmutableFlowUtils {
 
     /// Dumps the flow tree for debugging, with a prefix to indicate that we're at the given level.
     fn dump_with_level(self, level: uint);
+
+    /// Prints the flow tree to standard output whatever the log level (`--debug=layout.flows`).
+    fn print_tree(self);
 }
 
 pub trait MutableFlowUtils {
@@ -902,14 +905,24 @@ impl<'a> ImmutableFlowUtils for &'a Flow {
 
     /// Dumps the flow tree for debugging, with a prefix to indicate that we're at the given level.
     fn dump_with_level(self, level: uint) {
-        let mut indent = String::new();
-        for _ in range(0, level) {
-            indent.push_str("| ")
-        }
-        debug!("{}+ {}", indent, self.to_str());
-        for kid in imm_child_iter(self) {
-            kid.dump_with_level(level + 1)
-        }
+        each_tree_line(self, level, |line| debug!("{}", line))
+    }
+
+    fn print_tree(self) {
+        each_tree_line(self, 0, |line| println!("{}", line))
+    }
+}
+
+/// Calls `f` with one line for each flow in the tree, indented to show its depth.
+fn each_tree_line(flow: &Flow, level: uint, f: |&str|) {
+    let mut line = String::new();
+    for _ in range(0, level) {
+        line.push_str("| ")
+    }
+    line.push_str(format!("+ {}", flow.to_str()).as_slice());
+    f(line.as_slice());
+    for kid in imm_child_iter(flow) {
+        each_tree_line(kid, level + 1, |line| f(line))
     }
 }
 
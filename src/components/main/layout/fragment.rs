@@ -30,7 +30,6 @@ use gfx::display_list::{LineDisplayItemClass, OpaqueNode, PseudoDisplayItemClass
 use gfx::display_list::{SolidColorDisplayItem, SolidColorDisplayItemClass, StackingLevel};
 use gfx::display_list::{TextDecorations, TextDisplayItem, TextDisplayItemClass};
 use gfx::font::FontStyle;
-use gfx::text::glyph::CharIndex;
 use gfx::text::text_run::TextRun;
 use servo_msg::constellation_msg::{ConstellationChan, FrameRectMsg, PipelineId, SubpageId};
 use servo_net::image::holder::ImageHolder;
@@ -1120,9 +1119,10 @@ impl Fragment {
                 let mut new_line_pos = self.new_line_pos.clone();
                 let cur_new_line_pos = new_line_pos.shift().unwrap();
 
-                let left_range = Range::new(text_fragment_info.range.begin(), cur_new_line_pos);
-                let right_range = Range::new(text_fragment_info.range.begin() + cur_new_line_pos + CharIndex(1),
-                                             text_fragment_info.range.length() - (cur_new_line_pos + CharIndex(1)));
+                let range = text_fragment_info.range;
+                let (left_range, rest) = range.split_at(range.begin() + cur_new_line_pos);
+                // The new-line character itself belongs to neither side.
+                let (_, right_range) = rest.split_at(rest.begin() + CharIndex(1));
 
                 // Left fragment is for left text of first founded new-line character.
                 let left_fragment = SplitInfo::new(left_range, text_fragment_info);
@@ -1202,8 +1202,8 @@ impl Fragment {
                             if slice_end < text_fragment_info.range.end() {
                                 debug!("split_to_width: case=skipping trimmable trailing \
                                         whitespace, then split remainder");
-                                let right_range_end = text_fragment_info.range.end() - slice_end;
-                                right_range = Some(Range::new(slice_end, right_range_end));
+                                let (_, remainder) = text_fragment_info.range.split_at(slice_end);
+                                right_range = Some(remainder);
                             } else {
                                 debug!("split_to_width: case=skipping trimmable trailing \
                                         whitespace");
@@ -1211,8 +1211,8 @@ impl Fragment {
                         } else if slice_begin < text_fragment_info.range.end() {
                             // There are still some things left over at the end of the line. Create
                             // the right chunk.
-                            let right_range_end = text_fragment_info.range.end() - slice_begin;
-                            right_range = Some(Range::new(slice_begin, right_range_end));
+                            let (_, remainder) = text_fragment_info.range.split_at(slice_begin);
+                            right_range = Some(remainder);
                             debug!("split_to_width: case=splitting remainder with right range={:?}",
                                    right_range);
                         }
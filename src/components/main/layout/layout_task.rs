@@ -27,7 +27,7 @@ use geom::rect::Rect;
 use geom::size::Size2D;
 use gfx::display_list::{ClipDisplayItemClass, ContentStackingLevel, DisplayItem};
 use gfx::display_list::{DisplayItemIterator, DisplayList, OpaqueNode};
-use gfx::font_context::{FontContext, FontContextInfo};
+use gfx::font_context::{FontContext, FontContextHandleFactory, FontContextInfo};
 use gfx::render_task::{RenderMsg, RenderChan, RenderLayer};
 use gfx::{render_task, color};
 use script::dom::bindings::js::JS;
@@ -45,22 +45,29 @@ use servo_msg::compositor_msg::Scrollable;
 use servo_msg::constellation_msg::{ConstellationChan, PipelineId, Failure, FailureMsg};
 use servo_net::image_cache_task::{ImageCacheTask, ImageResponseMsg};
 use servo_net::local_image_cache::{ImageResponder, LocalImageCache};
+use servo_util::bloom::BloomFilter;
 use servo_util::geometry::Au;
 use servo_util::geometry;
 use servo_util::opts::Opts;
 use servo_util::smallvec::{SmallVec, SmallVec1};
-use servo_util::time::{TimeProfilerChan, profile};
+use servo_util::time::{TimeProfilerChan, WorkerStatsMsg, profile};
 use servo_util::time;
 use servo_util::task::send_on_failure;
 use servo_util::workqueue::WorkQueue;
+use std::cmp;
 use std::comm::{channel, Sender, Receiver};
 use std::mem;
 use std::ptr;
+use std::sync::atomics::{AtomicUint, INIT_ATOMIC_UINT, SeqCst};
 use std::task::TaskBuilder;
 use style::{AuthorOrigin, Stylesheet, Stylist};
 use sync::{Arc, Mutex};
 use url::Url;
 
+/// The number of layout tasks with workers for parallel layout. They split the
+/// `--layout-threads` workers between them, so that many pipelines don't swamp the CPUs.
+static mut PARALLEL_LAYOUT_TASK_COUNT: AtomicUint = INIT_ATOMIC_UINT;
+
 /// Information needed by the layout task.
 pub struct LayoutTask {
     /// The ID of the pipeline that we belong to.
@@ -106,6 +113,10 @@ pub struct LayoutTask {
 
     /// The dirty rect. Used during display list construction.
     pub dirty: Rect<Au>,
+
+    /// The number of reflows started so far. Layout workers use it to tell whether state they
+    /// kept from an earlier traversal is stale.
+    pub generation: uint,
 }
 
 /// The damage computation traversal.
@@ -315,6 +326,9 @@ impl LayoutTask {
         let local_image_cache = Arc::new(Mutex::new(LocalImageCache(image_cache_task.clone())));
         let screen_size = Size2D(Au(0), Au(0));
         let parallel_traversal = if opts.layout_threads != 1 {
+            unsafe {
+                PARALLEL_LAYOUT_TASK_COUNT.fetch_add(1, SeqCst);
+            }
             Some(WorkQueue::new("LayoutWorker", opts.layout_threads, ptr::mut_null()))
         } else {
             None
@@ -337,6 +351,7 @@ impl LayoutTask {
             time_profiler_chan: time_profiler_chan,
             opts: opts.clone(),
             dirty: Rect::zero(),
+            generation: 0,
         }
     }
 
@@ -353,6 +368,8 @@ impl LayoutTask {
             backend: self.opts.render_backend,
             needs_font_list: true,
             time_profiler_chan: self.time_profiler_chan.clone(),
+            handle_factory: FontContextHandleFactory::new(),
+            print_cache_misses: self.opts.debug.gfx_fonts,
         };
 
         LayoutContext {
@@ -366,6 +383,7 @@ impl LayoutTask {
             reflow_root: OpaqueNodeMethods::from_layout_node(reflow_root),
             opts: self.opts.clone(),
             dirty: Rect::zero(),
+            generation: self.generation,
         }
     }
 
@@ -374,13 +392,21 @@ impl LayoutTask {
         match self.port.recv() {
             AddStylesheetMsg(sheet) => self.handle_add_stylesheet(sheet),
             ReflowMsg(data) => {
-                profile(time::LayoutPerformCategory, self.time_profiler_chan.clone(), || {
+                let PipelineId(pipeline_id) = self.id;
+                profile(time::LayoutPerformCategory,
+                        Some(pipeline_id),
+                        self.time_profiler_chan.clone(),
+                        || {
                     self.handle_reflow(data);
                 });
             }
             QueryMsg(query) => {
                 let mut query = Some(query);
-                profile(time::LayoutQueryCategory, self.time_profiler_chan.clone(), || {
+                let PipelineId(pipeline_id) = self.id;
+                profile(time::LayoutQueryCategory,
+                        Some(pipeline_id),
+                        self.time_profiler_chan.clone(),
+                        || {
                     self.handle_query(query.take_unwrap());
                 });
             }
@@ -436,7 +462,12 @@ impl LayoutTask {
 
         match self.parallel_traversal {
             None => {}
-            Some(ref mut traversal) => traversal.shutdown(),
+            Some(ref mut traversal) => {
+                traversal.shutdown();
+                unsafe {
+                    PARALLEL_LAYOUT_TASK_COUNT.fetch_sub(1, SeqCst);
+                }
+            }
         }
 
         self.render_chan.send(render_task::ExitMsg(Some(response_chan)));
@@ -586,7 +617,10 @@ impl LayoutTask {
         }
         self.screen_size = current_screen_size;
 
+        self.resize_parallel_traversal();
+
         // Create a layout context for use throughout the following passes.
+        self.generation += 1;
         let mut layout_ctx = self.build_layout_context(node, &data.url);
 
         // Create a font context, if this is sequential.
@@ -600,6 +634,7 @@ impl LayoutTask {
         };
 
         let mut layout_root = profile(time::LayoutStyleRecalcCategory,
+                                      None,
                                       self.time_profiler_chan.clone(),
                                       || {
             // Perform CSS selector matching and flow construction.
@@ -608,12 +643,14 @@ impl LayoutTask {
                     let mut applicable_declarations = ApplicableDeclarations::new();
                     let mut applicable_declarations_cache = ApplicableDeclarationsCache::new();
                     let mut style_sharing_candidate_cache = StyleSharingCandidateCache::new();
+                    let mut bloom_filter = box BloomFilter::new();
                     drop(node.recalc_style_for_subtree(self.stylist,
                                                        &mut layout_ctx,
                                                        font_context_opt.take_unwrap(),
                                                        &mut applicable_declarations,
                                                        &mut applicable_declarations_cache,
                                                        &mut style_sharing_candidate_cache,
+                                                       &mut *bloom_filter,
                                                        None))
                 }
                 Some(ref mut traversal) => {
@@ -630,7 +667,7 @@ impl LayoutTask {
         self.verify_flow_tree(&mut layout_root);
 
         // Propagate damage.
-        profile(time::LayoutDamagePropagateCategory, self.time_profiler_chan.clone(), || {
+        profile(time::LayoutDamagePropagateCategory, None, self.time_profiler_chan.clone(), || {
             layout_root.get_mut().traverse_preorder(&mut PropagateDamageTraversal {
                 all_style_damage: all_style_damage
             });
@@ -639,7 +676,7 @@ impl LayoutTask {
 
         // Perform the primary layout passes over the flow tree to compute the locations of all
         // the boxes.
-        profile(time::LayoutMainCategory, self.time_profiler_chan.clone(), || {
+        profile(time::LayoutMainCategory, None, self.time_profiler_chan.clone(), || {
             match self.parallel_traversal {
                 None => {
                     // Sequential mode.
@@ -652,9 +689,16 @@ impl LayoutTask {
             }
         });
 
+        if self.opts.debug.layout_flows {
+            layout_root.get().print_tree();
+        }
+
         // Build the display list if necessary, and send it to the renderer.
         if data.goal == ReflowForDisplay {
-            profile(time::LayoutDispListBuildCategory, self.time_profiler_chan.clone(), || {
+            profile(time::LayoutDispListBuildCategory,
+                    None,
+                    self.time_profiler_chan.clone(),
+                    || {
                 layout_ctx.dirty = flow::base(layout_root.get()).position.clone();
 
                 match self.parallel_traversal {
@@ -737,6 +781,38 @@ impl LayoutTask {
         data.script_join_chan.send(());
         let ScriptChan(ref chan) = data.script_chan;
         chan.send(ReflowCompleteMsg(self.id, data.id));
+
+        self.report_worker_stats();
+    }
+
+    /// Gives this task its share of the layout threads, now that the number of pipelines laying
+    /// out in parallel may have changed.
+    fn resize_parallel_traversal(&mut self) {
+        let layout_task_count = unsafe {
+            PARALLEL_LAYOUT_TASK_COUNT.load(SeqCst)
+        };
+        let thread_count = cmp::max(self.opts.layout_threads / cmp::max(layout_task_count, 1), 1);
+        match self.parallel_traversal {
+            Some(ref mut traversal) if traversal.thread_count() != thread_count => {
+                debug!("layout: using {} workers for {} parallel layout tasks",
+                       thread_count, layout_task_count);
+                traversal.set_thread_count(thread_count)
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends what the workers did during the reflow to the time profiler.
+    fn report_worker_stats(&mut self) {
+        match self.parallel_traversal {
+            Some(ref mut traversal) => {
+                let PipelineId(pipeline_id) = self.id;
+                self.time_profiler_chan.send(WorkerStatsMsg(pipeline_id,
+                                                            traversal.worker_stats().to_vec()));
+                traversal.reset_worker_stats();
+            }
+            None => {}
+        }
     }
 
     /// Handles a query from the script task. This is the main routine that DOM functions like
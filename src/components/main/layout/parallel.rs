@@ -20,6 +20,7 @@ use layout::wrapper::{layout_node_to_unsafe_layout_node, layout_node_from_unsafe
 use layout::wrapper::{ThreadSafeLayoutNode, UnsafeLayoutNode};
 
 use gfx::display_list::OpaqueNode;
+use servo_util::bloom::BloomFilter;
 use servo_util::time::{TimeProfilerChan, profile};
 use servo_util::time;
 use servo_util::workqueue::{WorkQueue, WorkUnit, WorkerProxy};
@@ -213,6 +214,141 @@ impl<'a> ParallelPreorderFlowTraversal for AssignWidthsTraversal<'a> {
 
 impl<'a> ParallelPostorderFlowTraversal for AssignHeightsAndStoreOverflowTraversal<'a> {}
 
+/// The Bloom filter of ancestors that this worker used last, the node it holds along with that
+/// node's ancestors, and the reflow it belongs to. Keeping it lets a worker that moves on to a
+/// sibling or cousin of the last node it styled update the filter rather than rebuild it.
+local_data_key!(style_bloom: (Box<BloomFilter>, UnsafeLayoutNode, uint))
+
+/// Returns a Bloom filter holding `parent` and its ancestors, for styling a child of `parent`.
+fn take_task_local_bloom_filter(parent: Option<LayoutNode>, layout_context: &LayoutContext)
+                                -> Box<BloomFilter> {
+    let cached = style_bloom.replace(None);
+    let parent = match parent {
+        None => {
+            // The reflow root has no ancestors to consider. Reuse the allocation if there is one.
+            return match cached {
+                Some((mut bf, _, _)) => {
+                    bf.clear();
+                    bf
+                }
+                None => box BloomFilter::new(),
+            }
+        }
+        Some(parent) => parent,
+    };
+
+    match cached {
+        Some((mut bf, old_node, old_generation)) => {
+            if old_generation != layout_context.generation {
+                // This was left over from an earlier reflow, and the nodes in it may be gone.
+                bf.clear();
+                insert_ancestors_into_bloom_filter(&mut *bf, parent);
+            } else if old_node != layout_node_to_unsafe_layout_node(&parent) {
+                move_bloom_filter(&mut *bf, old_node, parent);
+            }
+            bf
+        }
+        None => {
+            let mut bf = box BloomFilter::new();
+            insert_ancestors_into_bloom_filter(&mut *bf, parent);
+            bf
+        }
+    }
+}
+
+/// Stores `bf`, which holds `unsafe_layout_node` and its ancestors, for the next node this worker
+/// styles.
+fn put_task_local_bloom_filter(bf: Box<BloomFilter>,
+                               unsafe_layout_node: UnsafeLayoutNode,
+                               layout_context: &LayoutContext) {
+    style_bloom.replace(Some((bf, unsafe_layout_node, layout_context.generation)));
+}
+
+/// Takes a node whose subtree has been styled back out of this worker's Bloom filter, if the
+/// filter holds it, so that the filter is ready for the node's siblings.
+fn remove_from_task_local_bloom_filter(node: &LayoutNode,
+                                       unsafe_layout_node: UnsafeLayoutNode,
+                                       layout_context: &LayoutContext) {
+    let (mut bf, old_node, old_generation) = match style_bloom.replace(None) {
+        None => return,
+        Some(cached) => cached,
+    };
+    if old_node != unsafe_layout_node || old_generation != layout_context.generation {
+        style_bloom.replace(Some((bf, old_node, old_generation)));
+        return
+    }
+
+    node.remove_from_bloom_filter(&mut *bf);
+    let opaque_node: OpaqueNode = OpaqueNodeMethods::from_layout_node(node);
+    if opaque_node == layout_context.reflow_root {
+        // The traversal is over, and this filter only ever held nodes under the reflow root.
+        return
+    }
+    match node.parent_node() {
+        Some(parent) => {
+            let unsafe_parent = layout_node_to_unsafe_layout_node(&parent);
+            put_task_local_bloom_filter(bf, unsafe_parent, layout_context)
+        }
+        None => {}
+    }
+}
+
+/// Adds `node` and all of its ancestors to `bf`.
+fn insert_ancestors_into_bloom_filter(bf: &mut BloomFilter, node: LayoutNode) {
+    let mut node = Some(node);
+    loop {
+        match node {
+            None => break,
+            Some(current) => {
+                current.insert_into_bloom_filter(bf);
+                node = current.parent_node();
+            }
+        }
+    }
+}
+
+/// Changes `bf` from holding `old_node` and its ancestors to holding `new_node` and its ancestors.
+/// The ancestors that the two have in common stay in the filter, so only the paths from each node
+/// up to their nearest common ancestor are touched.
+fn move_bloom_filter(bf: &mut BloomFilter, old_node: UnsafeLayoutNode, new_node: LayoutNode) {
+    // `new_node` and its ancestors, nearest first.
+    let mut new_path = vec!();
+    let mut node = Some(new_node);
+    loop {
+        match node {
+            None => break,
+            Some(current) => {
+                new_path.push(layout_node_to_unsafe_layout_node(&current));
+                node = current.parent_node();
+            }
+        }
+    }
+
+    // Take out the old nodes until reaching one on the new path. Everything above it is shared.
+    let mut shared_index = new_path.len();
+    let mut node = Some(layout_node_from_unsafe_layout_node(&old_node));
+    loop {
+        let current = match node {
+            None => break,
+            Some(current) => current,
+        };
+        let unsafe_current = layout_node_to_unsafe_layout_node(&current);
+        match new_path.iter().position(|unsafe_node| *unsafe_node == unsafe_current) {
+            Some(index) => {
+                shared_index = index;
+                break
+            }
+            None => {}
+        }
+        current.remove_from_bloom_filter(bf);
+        node = current.parent_node();
+    }
+
+    for unsafe_node in new_path.slice_to(shared_index).iter() {
+        layout_node_from_unsafe_layout_node(unsafe_node).insert_into_bloom_filter(bf);
+    }
+}
+
 fn recalc_style_for_node(unsafe_layout_node: UnsafeLayoutNode,
                          proxy: &mut WorkerProxy<*mut LayoutContext,UnsafeLayoutNode>) {
     unsafe {
@@ -235,6 +371,9 @@ fn recalc_style_for_node(unsafe_layout_node: UnsafeLayoutNode,
             node.parent_node()
         };
 
+        // Get a Bloom filter of our ancestors for selector matching.
+        let mut bf = take_task_local_bloom_filter(parent_opt.clone(), layout_context);
+
         // First, check to see whether we can share a style with someone.
         let style_sharing_candidate_cache = layout_context.style_sharing_candidate_cache();
         let sharing_result = node.share_style_if_possible(style_sharing_candidate_cache,
@@ -248,7 +387,10 @@ fn recalc_style_for_node(unsafe_layout_node: UnsafeLayoutNode,
                 if node.is_element() {
                     // Perform the CSS selector matching.
                     let stylist: &Stylist = mem::transmute(layout_context.stylist);
-                    node.match_node(stylist, &mut applicable_declarations, &mut shareable);
+                    node.match_node(stylist,
+                                    Some(&*bf),
+                                    &mut applicable_declarations,
+                                    &mut shareable);
                 }
 
                 // Perform the CSS cascade.
@@ -264,6 +406,11 @@ fn recalc_style_for_node(unsafe_layout_node: UnsafeLayoutNode,
             StyleWasShared(index) => style_sharing_candidate_cache.touch(index),
         }
 
+        // Our children need us in the filter. Keep it for the next node this worker styles, which
+        // is likely to be one of them.
+        node.insert_into_bloom_filter(&mut *bf);
+        put_task_local_bloom_filter(bf, unsafe_layout_node, layout_context);
+
         // Prepare for flow construction by counting the node's children and storing that count.
         let mut child_count = 0;
         for _ in node.children() {
@@ -317,6 +464,9 @@ fn construct_flows(mut unsafe_layout_node: UnsafeLayoutNode,
             flow_constructor.process(&ThreadSafeLayoutNode::new(&node));
         }
 
+        // This node's subtree is done, so it no longer belongs in the Bloom filter.
+        remove_from_task_local_bloom_filter(&node, unsafe_layout_node, layout_context);
+
         // Reset the count of children for the next traversal.
         //
         // FIXME(pcwalton): Use children().len() when the implementation of that is efficient.
@@ -533,7 +683,7 @@ pub fn traverse_flow_tree_preorder(root: &mut FlowRef,
         queue.data = mem::transmute(layout_context)
     }
 
-    profile(time::LayoutParallelWarmupCategory, time_profiler_chan, || {
+    profile(time::LayoutParallelWarmupCategory, None, time_profiler_chan, || {
         queue.push(WorkUnit {
             fun: assign_widths,
             data: mut_owned_flow_to_unsafe_flow(root),
@@ -553,7 +703,7 @@ pub fn build_display_list_for_subtree(root: &mut FlowRef,
         queue.data = mem::transmute(layout_context)
     }
 
-    profile(time::LayoutParallelWarmupCategory, time_profiler_chan, || {
+    profile(time::LayoutParallelWarmupCategory, None, time_profiler_chan, || {
         queue.push(WorkUnit {
             fun: compute_absolute_position,
             data: mut_owned_flow_to_unsafe_flow(root),
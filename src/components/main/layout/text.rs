@@ -11,11 +11,11 @@ use layout::fragment::{Fragment, ScannedTextFragment, ScannedTextFragmentInfo, U
 
 use gfx::font::{FontMetrics, FontStyle};
 use gfx::font_context::FontContext;
-use gfx::text::glyph::CharIndex;
 use gfx::text::text_run::TextRun;
 use gfx::text::util::{CompressWhitespaceNewline, transform_text, CompressNone};
 use servo_util::geometry::Au;
-use servo_util::range::Range;
+use servo_util::range::{CharIndex, Range};
+use servo_util::smallvec::SmallVec4;
 use style::ComputedValues;
 use style::computed_values::{font_family, line_height, white_space};
 use sync::Arc;
@@ -284,7 +284,9 @@ pub fn computed_style_to_font_style(style: &ComputedValues) -> FontStyle {
         pt_size: font_size,
         weight: style.get_font().font_weight,
         style: style.get_font().font_style,
+        stretch: style.get_font().font_stretch,
         families: font_families.collect(),
+        features: SmallVec4::new(),
     }
 }
 
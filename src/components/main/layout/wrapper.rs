@@ -47,9 +47,9 @@ use script::dom::node::{DocumentNodeTypeId, ElementNodeTypeId, Node, NodeTypeId}
 use script::dom::node::{LayoutNodeHelpers, RawLayoutNodeHelpers, TextNodeTypeId};
 use script::dom::text::Text;
 use servo_msg::constellation_msg::{PipelineId, SubpageId};
+use servo_util::atom::Atom;
 use servo_util::namespace::Namespace;
 use servo_util::namespace;
-use servo_util::str::is_whitespace;
 use std::cell::{RefCell, Ref, RefMut};
 use std::kinds::marker::ContravariantLifetime;
 use std::mem;
@@ -188,7 +188,8 @@ impl<'ln> TLayoutNode for LayoutNode<'ln> {
                 fail!("not text!")
             }
             let text: JS<Text> = self.get_jsmanaged().transmute_copy();
-            (*text.unsafe_get()).characterdata.data.deref().borrow().clone()
+            // Unpaired surrogates, which only script can put in a text node, render as U+FFFD.
+            (*text.unsafe_get()).characterdata.data.deref().borrow().to_string_lossy()
         }
     }
 }
@@ -267,15 +268,15 @@ impl<'ln> TNode<LayoutElement<'ln>> for LayoutNode<'ln> {
         let name = unsafe {
             let element: JS<Element> = self.node.transmute_copy();
             if element.html_element_in_html_document_for_layout() {
-                attr.lower_name.as_slice()
+                &attr.lower_name
             } else {
-                attr.name.as_slice()
+                &attr.name
             }
         };
         match attr.namespace {
             SpecificNamespace(ref ns) => {
                 let element = self.as_element();
-                element.get_attr(ns, name)
+                unsafe { element.element.get_attr_val_by_atom_for_layout(ns, name) }
                         .map_or(false, |attr| test(attr))
             },
             // FIXME: https://github.com/mozilla/servo/issues/1558
@@ -359,8 +360,8 @@ impl<'le> LayoutElement<'le> {
 
 impl<'le> TElement for LayoutElement<'le> {
     #[inline]
-    fn get_local_name<'a>(&'a self) -> &'a str {
-        self.element.local_name.as_slice()
+    fn get_local_name<'a>(&'a self) -> &'a Atom {
+        &self.element.local_name
     }
 
     #[inline]
@@ -373,6 +374,30 @@ impl<'le> TElement for LayoutElement<'le> {
         unsafe { self.element.get_attr_val_for_layout(namespace, name) }
     }
 
+    #[inline]
+    fn get_id(&self) -> Option<Atom> {
+        unsafe { self.element.get_id_for_layout() }
+    }
+
+    #[inline]
+    fn each_class(&self, callback: |&Atom|) {
+        match unsafe { self.element.get_classes_for_layout() } {
+            Some(classes) => {
+                for class in classes.iter() {
+                    callback(class)
+                }
+            }
+            None => {}
+        }
+    }
+
+    #[inline]
+    fn has_class(&self, name: &Atom) -> bool {
+        unsafe { self.element.get_classes_for_layout() }.map_or(false, |classes| {
+            classes.iter().any(|class| *class == *name)
+        })
+    }
+
     fn get_link(&self) -> Option<&'static str> {
         // FIXME: This is HTML only.
         match self.element.node.type_id {
@@ -494,7 +519,7 @@ impl<'ln> TLayoutNode for ThreadSafeLayoutNode<'ln> {
                 fail!("not text!")
             }
             let text: JS<Text> = self.get_jsmanaged().transmute_copy();
-            (*text.unsafe_get()).characterdata.data.deref().borrow().clone()
+            (*text.unsafe_get()).characterdata.data.deref().borrow().to_string_lossy()
         }
     }
 }
@@ -636,7 +661,8 @@ impl<'ln> ThreadSafeLayoutNode<'ln> {
             Some(TextNodeTypeId) => {
                 unsafe {
                     let text: JS<Text> = self.get_jsmanaged().transmute_copy();
-                    if !is_whitespace((*text.unsafe_get()).characterdata.data.deref().borrow().as_slice()) {
+                    let data = (*text.unsafe_get()).characterdata.data.deref().borrow();
+                    if !data.is_whitespace() {
                         return false
                     }
 
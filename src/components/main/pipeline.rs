@@ -15,6 +15,7 @@ use servo_msg::constellation_msg::{ConstellationChan, Failure, PipelineId, Subpa
 use servo_msg::constellation_msg::WindowSizeData;
 use servo_net::image_cache_task::ImageCacheTask;
 use servo_net::resource_task::ResourceTask;
+use servo_util::memory::{MemoryProfilerChan, RegisterReporterMsg};
 use servo_util::opts::Opts;
 use servo_util::time::TimeProfilerChan;
 use std::rc::Rc;
@@ -50,6 +51,7 @@ impl Pipeline {
                        compositor_chan: CompositorChan,
                        image_cache_task: ImageCacheTask,
                        time_profiler_chan: TimeProfilerChan,
+                       memory_profiler_chan: MemoryProfilerChan,
                        opts: Opts,
                        script_pipeline: Rc<Pipeline>,
                        url: Url)
@@ -85,6 +87,11 @@ impl Pipeline {
                            time_profiler_chan,
                            layout_shutdown_chan);
 
+        // The script task's reporter was registered by the pipeline that created it.
+        let PipelineId(pipeline_id) = id;
+        memory_profiler_chan.send(RegisterReporterMsg(format!("render-task-{}", pipeline_id),
+                                                      box render_chan.clone()));
+
         let new_layout_info = NewLayoutInfo {
             old_pipeline_id: script_pipeline.id.clone(),
             new_pipeline_id: id,
@@ -112,6 +119,7 @@ impl Pipeline {
                   image_cache_task: ImageCacheTask,
                   resource_task: ResourceTask,
                   time_profiler_chan: TimeProfilerChan,
+                  memory_profiler_chan: MemoryProfilerChan,
                   window_size: WindowSizeData,
                   opts: Opts,
                   url: Url)
@@ -167,6 +175,13 @@ impl Pipeline {
                            time_profiler_chan,
                            layout_shutdown_chan);
 
+        // The reporters are dropped by the memory profiler once their tasks are gone.
+        let PipelineId(pipeline_id) = id;
+        memory_profiler_chan.send(RegisterReporterMsg(format!("script-task-{}", pipeline_id),
+                                                      box script_chan.clone()));
+        memory_profiler_chan.send(RegisterReporterMsg(format!("render-task-{}", pipeline_id),
+                                                      box render_chan.clone()));
+
         pipeline
     }
 
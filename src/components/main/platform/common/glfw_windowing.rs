@@ -6,10 +6,9 @@
 
 use windowing::{ApplicationMethods, WindowEvent, WindowMethods};
 use windowing::{IdleWindowEvent, ResizeWindowEvent, LoadUrlWindowEvent, MouseWindowEventClass,  MouseWindowMoveEventClass};
-use windowing::{ScrollWindowEvent, ZoomWindowEvent, PinchZoomWindowEvent, NavigationWindowEvent, FinishedWindowEvent};
+use windowing::{ScrollWindowEvent, ZoomWindowEvent, PinchZoomWindowEvent, FinishedWindowEvent};
 use windowing::{QuitWindowEvent, MouseWindowClickEvent, MouseWindowMouseDownEvent, MouseWindowMouseUpEvent};
-use windowing::RefreshWindowEvent;
-use windowing::{Forward, Back};
+use windowing::{CompositionWindowEvent, KeyWindowEvent, RefreshWindowEvent};
 
 use alert::{Alert, AlertMethods};
 use libc::{exit, c_int};
@@ -24,6 +23,8 @@ use geom::scale_factor::ScaleFactor;
 use geom::size::TypedSize2D;
 use servo_msg::compositor_msg::{IdleRenderState, RenderState, RenderingRenderState};
 use servo_msg::compositor_msg::{FinishedLoading, Blank, Loading, PerformingLayout, ReadyState};
+use servo_msg::constellation_msg::{CompositionCommitted, CompositionStarted};
+use servo_msg::constellation_msg::{KeyInput, KeyModifiers, KeyPressed, KeyReleased, KeyRepeated};
 use servo_util::geometry::{ScreenPx, DevicePixel};
 
 use glfw;
@@ -136,6 +137,7 @@ impl WindowMethods<Application> for Window {
         window.glfw_window.set_framebuffer_size_polling(true);
         window.glfw_window.set_refresh_polling(true);
         window.glfw_window.set_key_polling(true);
+        window.glfw_window.set_char_polling(true);
         window.glfw_window.set_mouse_button_polling(true);
         window.glfw_window.set_cursor_pos_polling(true);
         window.glfw_window.set_scroll_polling(true);
@@ -157,6 +159,12 @@ impl WindowMethods<Application> for Window {
         TypedSize2D(width as f32, height as f32)
     }
 
+    /// Returns the position of the window's top left corner on the screen.
+    fn screen_origin(&self) -> TypedPoint2D<ScreenPx, f32> {
+        let (x, y) = self.glfw_window.get_pos();
+        TypedPoint2D(x as f32, y as f32)
+    }
+
     /// Presents the window to the screen (perhaps by page flipping).
     fn present(&self) {
         self.glfw_window.swap_buffers();
@@ -212,8 +220,31 @@ impl Window {
     fn handle_window_event(&self, window: &glfw::Window, event: glfw::WindowEvent) {
         match event {
             glfw::KeyEvent(key, _, action, mods) => {
-                if action == glfw::Press {
-                    self.handle_key(key, mods)
+                if action == glfw::Press && self.handle_key(key, mods) {
+                    return
+                }
+                let state = match action {
+                    glfw::Press => KeyPressed,
+                    glfw::Repeat => KeyRepeated,
+                    glfw::Release => KeyReleased,
+                };
+                let (dom_key, code, location) = dom_key_values(key, mods);
+                self.event_queue.borrow_mut().push(KeyWindowEvent(KeyInput {
+                    key: dom_key,
+                    code: code,
+                    location: location,
+                    state: state,
+                    modifiers: key_modifiers(mods),
+                }));
+            },
+            glfw::CharEvent(c) => {
+                // Key events already type printable ASCII. Anything else came from an input
+                // method or a dead key; GLFW only tells us the result, so it arrives as a
+                // composition that commits at once.
+                if c < ' ' || c > '~' {
+                    let mut event_queue = self.event_queue.borrow_mut();
+                    event_queue.push(CompositionWindowEvent(CompositionStarted));
+                    event_queue.push(CompositionWindowEvent(CompositionCommitted(c.to_str())));
                 }
             },
             glfw::FramebufferSizeEvent(width, height) => {
@@ -223,7 +254,7 @@ impl Window {
             glfw::RefreshEvent => {
                 self.event_queue.borrow_mut().push(RefreshWindowEvent);
             },
-            glfw::MouseButtonEvent(button, action, _mods) => {
+            glfw::MouseButtonEvent(button, action, mods) => {
                 let (x, y) = window.get_cursor_pos();
                 //handle hidpi displays, since GLFW returns non-hi-def coordinates.
                 let (backing_size, _) = window.get_framebuffer_size();
@@ -231,13 +262,20 @@ impl Window {
                 let hidpi = (backing_size as f32) / (window_size as f32);
                 let x = x as f32 * hidpi;
                 let y = y as f32 * hidpi;
-                if button == glfw::MouseButtonLeft || button == glfw::MouseButtonRight {
-                    self.handle_mouse(button, action, x as i32, y as i32);
+                if button == glfw::MouseButtonLeft || button == glfw::MouseButtonRight ||
+                        button == glfw::MouseButtonMiddle {
+                    self.handle_mouse(button, action, x as i32, y as i32, key_modifiers(mods));
                 }
             },
             glfw::CursorPosEvent(xpos, ypos) => {
+                //handle hidpi displays, since GLFW returns non-hi-def coordinates.
+                let (backing_size, _) = window.get_framebuffer_size();
+                let (window_size, _) = window.get_size();
+                let hidpi = (backing_size as f32) / (window_size as f32);
+                let x = xpos as f32 * hidpi;
+                let y = ypos as f32 * hidpi;
                 self.event_queue.borrow_mut().push(
-                    MouseWindowMoveEventClass(TypedPoint2D(xpos as f32, ypos as f32)));
+                    MouseWindowMoveEventClass(TypedPoint2D(x, y), held_modifiers(window)));
             },
             glfw::ScrollEvent(xpos, ypos) => {
                 match (window.get_key(glfw::KeyLeftControl),
@@ -303,8 +341,10 @@ impl Window {
         }
     }
 
-    /// Helper function to handle keyboard events.
-    fn handle_key(&self, key: glfw::Key, mods: glfw::Modifiers) {
+    /// Helper function to handle browser shortcuts. Returns true if the key press was one, in
+    /// which case the page doesn't see it. Backspace navigation is left to script, so that it
+    /// doesn't happen while the user is typing into a form field.
+    fn handle_key(&self, key: glfw::Key, mods: glfw::Modifiers) -> bool {
         match key {
             glfw::KeyEscape => self.glfw_window.set_should_close(true),
             glfw::KeyL if mods.contains(glfw::Control) => self.load_url(), // Ctrl+L
@@ -314,18 +354,14 @@ impl Window {
             glfw::KeyMinus if mods.contains(glfw::Control) => { // Ctrl--
                 self.event_queue.borrow_mut().push(ZoomWindowEvent(1.0/1.1));
             }
-            glfw::KeyBackspace if mods.contains(glfw::Shift) => { // Shift-Backspace
-                self.event_queue.borrow_mut().push(NavigationWindowEvent(Forward));
-            }
-            glfw::KeyBackspace => { // Backspace
-                self.event_queue.borrow_mut().push(NavigationWindowEvent(Back));
-            }
-            _ => {}
+            _ => return false,
         }
+        true
     }
 
     /// Helper function to handle a click
-    fn handle_mouse(&self, button: glfw::MouseButton, action: glfw::Action, x: c_int, y: c_int) {
+    fn handle_mouse(&self, button: glfw::MouseButton, action: glfw::Action, x: c_int, y: c_int,
+                    modifiers: KeyModifiers) {
         // FIXME(tkuehn): max pixel dist should be based on pixel density
         let max_pixel_dist = 10f64;
         let event = match action {
@@ -344,7 +380,8 @@ impl Window {
                         if pixel_dist < max_pixel_dist {
                             let click_event = MouseWindowClickEvent(button as uint,
                                                                     TypedPoint2D(x as f32, y as f32));
-                            self.event_queue.borrow_mut().push(MouseWindowEventClass(click_event));
+                            self.event_queue.borrow_mut().push(
+                                MouseWindowEventClass(click_event, modifiers.clone()));
                         }
                     }
                     Some(_) => (),
@@ -353,7 +390,7 @@ impl Window {
             }
             _ => fail!("I cannot recognize the type of mouse action that occured. :-(")
         };
-        self.event_queue.borrow_mut().push(MouseWindowEventClass(event));
+        self.event_queue.borrow_mut().push(MouseWindowEventClass(event, modifiers));
     }
 
     /// Helper function to pop up an alert box prompting the user to load a URL.
@@ -369,3 +406,155 @@ impl Window {
         }
     }
 }
+
+fn key_modifiers(mods: glfw::Modifiers) -> KeyModifiers {
+    KeyModifiers {
+        shift: mods.contains(glfw::Shift),
+        control: mods.contains(glfw::Control),
+        alt: mods.contains(glfw::Alt),
+        meta: mods.contains(glfw::Super),
+    }
+}
+
+/// The modifier keys held down right now, for events such as cursor moves that GLFW doesn't
+/// report them with.
+fn held_modifiers(window: &glfw::Window) -> KeyModifiers {
+    let held = |left: glfw::Key, right: glfw::Key| {
+        window.get_key(left) == glfw::Press || window.get_key(right) == glfw::Press
+    };
+    KeyModifiers {
+        shift: held(glfw::KeyLeftShift, glfw::KeyRightShift),
+        control: held(glfw::KeyLeftControl, glfw::KeyRightControl),
+        alt: held(glfw::KeyLeftAlt, glfw::KeyRightAlt),
+        meta: held(glfw::KeyLeftSuper, glfw::KeyRightSuper),
+    }
+}
+
+/// Translates a GLFW key into the DOM's `key`, `code` and `location` values. GLFW reports which
+/// key was pressed rather than which character it produced, so printable keys are translated as
+/// if the keyboard had a US layout.
+fn dom_key_values(key: glfw::Key, mods: glfw::Modifiers) -> (String, String, u32) {
+    static STANDARD: u32 = 0;
+    static LEFT: u32 = 1;
+    static RIGHT: u32 = 2;
+    static NUMPAD: u32 = 3;
+
+    let number = key as int;
+
+    // GLFW numbers printable keys by the ASCII code of the character they produce unshifted
+    // (upper case, for letters).
+    if number >= 32 && number < 127 {
+        let unshifted = (number as u8 as char).to_lowercase();
+        let shifted = if mods.contains(glfw::Shift) {
+            shifted_us_char(unshifted)
+        } else {
+            unshifted
+        };
+        let code = match unshifted {
+            'a'..'z' => format!("Key{}", unshifted.to_uppercase()),
+            '0'..'9' => format!("Digit{}", unshifted),
+            ' ' => "Space".to_string(),
+            '`' => "Backquote".to_string(),
+            '-' => "Minus".to_string(),
+            '=' => "Equal".to_string(),
+            '[' => "BracketLeft".to_string(),
+            ']' => "BracketRight".to_string(),
+            '\\' => "Backslash".to_string(),
+            ';' => "Semicolon".to_string(),
+            '\'' => "Quote".to_string(),
+            ',' => "Comma".to_string(),
+            '.' => "Period".to_string(),
+            '/' => "Slash".to_string(),
+            _ => "Unidentified".to_string(),
+        };
+        return (shifted.to_str(), code, STANDARD)
+    }
+    if number >= glfw::KeyF1 as int && number <= glfw::KeyF25 as int {
+        let name = format!("F{}", number - glfw::KeyF1 as int + 1);
+        return (name.clone(), name, STANDARD)
+    }
+    if number >= glfw::KeyKp0 as int && number <= glfw::KeyKp9 as int {
+        let digit = number - glfw::KeyKp0 as int;
+        return (digit.to_str(), format!("Numpad{}", digit), NUMPAD)
+    }
+
+    let (key, code, location) = match key {
+        glfw::KeyEscape => ("Escape", "Escape", STANDARD),
+        glfw::KeyEnter => ("Enter", "Enter", STANDARD),
+        glfw::KeyKpEnter => ("Enter", "NumpadEnter", NUMPAD),
+        glfw::KeyTab => ("Tab", "Tab", STANDARD),
+        glfw::KeyBackspace => ("Backspace", "Backspace", STANDARD),
+        glfw::KeyInsert => ("Insert", "Insert", STANDARD),
+        glfw::KeyDelete => ("Delete", "Delete", STANDARD),
+        glfw::KeyRight => ("ArrowRight", "ArrowRight", STANDARD),
+        glfw::KeyLeft => ("ArrowLeft", "ArrowLeft", STANDARD),
+        glfw::KeyDown => ("ArrowDown", "ArrowDown", STANDARD),
+        glfw::KeyUp => ("ArrowUp", "ArrowUp", STANDARD),
+        glfw::KeyPageUp => ("PageUp", "PageUp", STANDARD),
+        glfw::KeyPageDown => ("PageDown", "PageDown", STANDARD),
+        glfw::KeyHome => ("Home", "Home", STANDARD),
+        glfw::KeyEnd => ("End", "End", STANDARD),
+        glfw::KeyCapsLock => ("CapsLock", "CapsLock", STANDARD),
+        glfw::KeyLeftShift => ("Shift", "ShiftLeft", LEFT),
+        glfw::KeyRightShift => ("Shift", "ShiftRight", RIGHT),
+        glfw::KeyLeftControl => ("Control", "ControlLeft", LEFT),
+        glfw::KeyRightControl => ("Control", "ControlRight", RIGHT),
+        glfw::KeyLeftAlt => ("Alt", "AltLeft", LEFT),
+        glfw::KeyRightAlt => ("Alt", "AltRight", RIGHT),
+        glfw::KeyLeftSuper => ("Meta", "MetaLeft", LEFT),
+        glfw::KeyRightSuper => ("Meta", "MetaRight", RIGHT),
+        _ => ("Unidentified", "Unidentified", STANDARD),
+    };
+    (key.to_string(), code.to_string(), location)
+}
+
+/// The character a shifted key produces on a US keyboard, given the unshifted one.
+fn shifted_us_char(c: char) -> char {
+    match c {
+        'a'..'z' => c.to_uppercase(),
+        '`' => '~',
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        '\\' => '|',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        _ => c,
+    }
+}
+
+#[test]
+fn test_dom_key_values() {
+    let none = glfw::Modifiers::empty();
+    let check = |key: glfw::Key, mods: glfw::Modifiers, expected: (&str, &str, u32)| {
+        let (key, code, location) = dom_key_values(key, mods);
+        assert_eq!((key.as_slice(), code.as_slice(), location), expected);
+    };
+
+    check(glfw::KeyA, none, ("a", "KeyA", 0));
+    check(glfw::KeyA, glfw::Shift, ("A", "KeyA", 0));
+    check(glfw::Key1, glfw::Shift, ("!", "Digit1", 0));
+    check(glfw::KeySlash, glfw::Shift, ("?", "Slash", 0));
+    check(glfw::KeySpace, none, (" ", "Space", 0));
+    check(glfw::KeyF12, none, ("F12", "F12", 0));
+    check(glfw::KeyKp7, none, ("7", "Numpad7", 3));
+    check(glfw::KeyKpEnter, none, ("Enter", "NumpadEnter", 3));
+    check(glfw::KeyBackspace, none, ("Backspace", "Backspace", 0));
+    check(glfw::KeyLeftShift, glfw::Shift, ("Shift", "ShiftLeft", 1));
+    check(glfw::KeyRightAlt, none, ("Alt", "AltRight", 2));
+    check(glfw::KeyMenu, none, ("Unidentified", "Unidentified", 0));
+}
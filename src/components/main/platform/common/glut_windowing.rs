@@ -6,9 +6,8 @@
 
 use windowing::{ApplicationMethods, WindowEvent, WindowMethods};
 use windowing::{IdleWindowEvent, ResizeWindowEvent, LoadUrlWindowEvent, MouseWindowEventClass};
-use windowing::{ScrollWindowEvent, ZoomWindowEvent, NavigationWindowEvent, FinishedWindowEvent};
+use windowing::{ScrollWindowEvent, ZoomWindowEvent, KeyWindowEvent, FinishedWindowEvent};
 use windowing::{MouseWindowClickEvent, MouseWindowMouseDownEvent, MouseWindowMouseUpEvent};
-use windowing::{Forward, Back};
 
 use alert::{Alert, AlertMethods};
 use libc::{c_int, c_uchar};
@@ -19,9 +18,10 @@ use geom::scale_factor::ScaleFactor;
 use geom::size::TypedSize2D;
 use servo_msg::compositor_msg::{IdleRenderState, RenderState, RenderingRenderState};
 use servo_msg::compositor_msg::{FinishedLoading, Blank, ReadyState};
+use servo_msg::constellation_msg::{KeyInput, KeyModifiers, KeyPressed, KeyReleased};
 use servo_util::geometry::{ScreenPx, DevicePixel};
 
-use glut::glut::{ACTIVE_SHIFT, DOUBLE, WindowHeight};
+use glut::glut::{ACTIVE_ALT, ACTIVE_CTRL, ACTIVE_SHIFT, DOUBLE, WindowHeight};
 use glut::glut::WindowWidth;
 use glut::glut;
 
@@ -154,6 +154,12 @@ impl WindowMethods<Application> for Window {
         self.framebuffer_size().as_f32() / self.hidpi_factor()
     }
 
+    /// Returns the position of the window's top left corner on the screen. GLUT is only used on
+    /// Android, where the window fills the screen.
+    fn screen_origin(&self) -> TypedPoint2D<ScreenPx, f32> {
+        TypedPoint2D(0.0, 0.0)
+    }
+
     /// Presents the window to the screen (perhaps by page flipping).
     fn present(&self) {
         glut::swap_buffers();
@@ -224,7 +230,6 @@ impl Window {
     /// Helper function to handle keyboard events.
     fn handle_key(&self, key: u8) {
         debug!("got key: {}", key);
-        let modifiers = glut::get_modifiers();
         match key {
             42 => self.load_url(),
             43 => self.event_queue.borrow_mut().push(ZoomWindowEvent(1.1)),
@@ -233,15 +238,29 @@ impl Window {
                                                                        TypedPoint2D(0.0 as i32, 5.0 as i32))),
             50 => self.event_queue.borrow_mut().push(ScrollWindowEvent(TypedPoint2D(0.0, -5.0 as f32),
                                                                        TypedPoint2D(0.0 as i32, -5.0 as i32))),
-            127 => {
-                if (modifiers & ACTIVE_SHIFT) != 0 {
-                    self.event_queue.borrow_mut().push(NavigationWindowEvent(Forward));
-                }
-                else {
-                    self.event_queue.borrow_mut().push(NavigationWindowEvent(Back));
+            _ => {
+                // GLUT only reports the character a key produced, and only when it goes down, so
+                // the page sees each key released as soon as it is pressed.
+                let (dom_key, code) = match key {
+                    // The backspace key sends DEL on Mac OS and on many X terminals.
+                    8 | 127 => ("Backspace".to_string(), "Backspace".to_string()),
+                    9 => ("Tab".to_string(), "Tab".to_string()),
+                    13 => ("Enter".to_string(), "Enter".to_string()),
+                    27 => ("Escape".to_string(), "Escape".to_string()),
+                    32..126 => ((key as char).to_str(), "Unidentified".to_string()),
+                    _ => ("Unidentified".to_string(), "Unidentified".to_string()),
+                };
+                let modifiers = key_modifiers();
+                for &state in [KeyPressed, KeyReleased].iter() {
+                    self.event_queue.borrow_mut().push(KeyWindowEvent(KeyInput {
+                        key: dom_key.clone(),
+                        code: code.clone(),
+                        location: 0,
+                        state: state,
+                        modifiers: modifiers.clone(),
+                    }));
                 }
             }
-            _ => {}
         }
     }
 
@@ -249,6 +268,13 @@ impl Window {
     fn handle_mouse(&self, button: c_int, state: c_int, x: c_int, y: c_int) {
         // FIXME(tkuehn): max pixel dist should be based on pixel density
         let max_pixel_dist = 10f32;
+        let modifiers = key_modifiers();
+        // GLUT numbers buttons left, middle, right; the windowing layer uses left, right, middle.
+        let button = match button {
+            1 => 2,
+            2 => 1,
+            _ => 0,
+        };
         let event = match state {
             glut::MOUSE_DOWN => {
                 self.mouse_down_point.set(Point2D(x, y));
@@ -263,14 +289,15 @@ impl Window {
                     if pixel_dist < max_pixel_dist {
                         let click_event = MouseWindowClickEvent(button as uint,
                                                            TypedPoint2D(x as f32, y as f32));
-                        self.event_queue.borrow_mut().push(MouseWindowEventClass(click_event));
+                        self.event_queue.borrow_mut().push(
+                            MouseWindowEventClass(click_event, modifiers.clone()));
                     }
                 }
                 MouseWindowMouseUpEvent(button as uint, TypedPoint2D(x as f32, y as f32))
             }
             _ => fail!("I cannot recognize the type of mouse action that occured. :-(")
         };
-        self.event_queue.borrow_mut().push(MouseWindowEventClass(event));
+        self.event_queue.borrow_mut().push(MouseWindowEventClass(event, modifiers));
     }
 
     /// Helper function to pop up an alert box prompting the user to load a URL.
@@ -287,6 +314,18 @@ impl Window {
     }
 }
 
+/// The modifier keys held down, which GLUT can only tell us from inside a keyboard or mouse
+/// callback.
+fn key_modifiers() -> KeyModifiers {
+    let modifiers = glut::get_modifiers();
+    KeyModifiers {
+        shift: (modifiers & ACTIVE_SHIFT) != 0,
+        control: (modifiers & ACTIVE_CTRL) != 0,
+        alt: (modifiers & ACTIVE_ALT) != 0,
+        meta: false,
+    }
+}
+
 local_data_key!(TLS_KEY: Rc<Window>)
 
 fn install_local_window(window: Rc<Window>) {
@@ -64,9 +64,11 @@ use servo_net::image_cache_task::{ImageCacheTask, SyncImageCacheTask};
 #[cfg(not(test))]
 use servo_net::resource_task::ResourceTask;
 #[cfg(not(test))]
+use servo_util::taskpool::TaskPool;
+#[cfg(not(test))]
 use servo_util::time::TimeProfiler;
 #[cfg(not(test))]
-use servo_util::memory::MemoryProfiler;
+use servo_util::memory::{MemoryProfiler, RegisterReporterMsg};
 
 #[cfg(not(test))]
 use servo_util::opts;
@@ -81,6 +83,8 @@ use std::str;
 #[cfg(not(test))]
 use rustrt::task::TaskOpts;
 #[cfg(not(test))]
+use std::rt;
+#[cfg(not(test))]
 use url::Url;
 
 
@@ -171,30 +175,36 @@ pub fn run(opts: opts::Opts) {
     let mut pool = green::SchedPool::new(pool_config);
 
     let (compositor_port, compositor_chan) = CompositorChan::new();
-    let time_profiler_chan = TimeProfiler::create(opts.time_profiler_period);
+    let time_profiler_chan = TimeProfiler::create(opts.time_profiler_period,
+                                                  opts.time_profiler_output.clone());
     let memory_profiler_chan = MemoryProfiler::create(opts.memory_profiler_period);
 
     let opts_clone = opts.clone();
     let time_profiler_chan_clone = time_profiler_chan.clone();
+    let memory_profiler_chan_clone = memory_profiler_chan.clone();
 
     let (result_chan, result_port) = channel();
     pool.spawn(TaskOpts::new(), proc() {
         let opts = &opts_clone;
         // Create a Servo instance.
-        let resource_task = ResourceTask();
+        let task_pool = TaskPool::new(rt::default_sched_threads());
+        let resource_task = ResourceTask(opts.debug.clone());
         // If we are emitting an output file, then we need to block on
         // image load or we risk emitting an output file missing the
         // image.
         let image_cache_task = if opts.output_file.is_some() {
-                SyncImageCacheTask(resource_task.clone())
+                SyncImageCacheTask(resource_task.clone(), task_pool)
             } else {
-                ImageCacheTask(resource_task.clone())
+                ImageCacheTask(resource_task.clone(), task_pool)
             };
+        memory_profiler_chan_clone.send(RegisterReporterMsg("image-cache".to_string(),
+                                                            box image_cache_task.clone()));
         let constellation_chan = Constellation::start(compositor_chan,
                                                       opts,
                                                       resource_task,
                                                       image_cache_task,
-                                                      time_profiler_chan_clone);
+                                                      time_profiler_chan_clone,
+                                                      memory_profiler_chan_clone);
 
         // Send the URL command to the constellation.
         for filename in opts.urls.iter() {
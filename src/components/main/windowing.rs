@@ -8,9 +8,12 @@ use geom::point::TypedPoint2D;
 use geom::scale_factor::ScaleFactor;
 use geom::size::TypedSize2D;
 use servo_msg::compositor_msg::{ReadyState, RenderState};
+use servo_msg::constellation_msg::{CompositionInput, KeyInput, KeyModifiers};
 use servo_util::geometry::{ScreenPx, DevicePixel};
 use std::rc::Rc;
 
+/// A mouse button going down or up, or both in one place (a click). Buttons are numbered left,
+/// right, middle.
 pub enum MouseWindowEvent {
     MouseWindowClickEvent(uint, TypedPoint2D<DevicePixel, f32>),
     MouseWindowMouseDownEvent(uint, TypedPoint2D<DevicePixel, f32>),
@@ -35,10 +38,10 @@ pub enum WindowEvent {
     ResizeWindowEvent(TypedSize2D<DevicePixel, uint>),
     /// Sent when a new URL is to be loaded.
     LoadUrlWindowEvent(String),
-    /// Sent when a mouse hit test is to be performed.
-    MouseWindowEventClass(MouseWindowEvent),
-    /// Sent when a mouse move.
-    MouseWindowMoveEventClass(TypedPoint2D<DevicePixel, f32>),
+    /// Sent when a mouse hit test is to be performed, with the modifier keys held at the time.
+    MouseWindowEventClass(MouseWindowEvent, KeyModifiers),
+    /// Sent when a mouse move, with the modifier keys held at the time.
+    MouseWindowMoveEventClass(TypedPoint2D<DevicePixel, f32>, KeyModifiers),
     /// Sent when the user scrolls. Includes the current cursor position.
     ScrollWindowEvent(TypedPoint2D<DevicePixel, f32>, TypedPoint2D<DevicePixel, i32>),
     /// Sent when the user zooms.
@@ -47,6 +50,10 @@ pub enum WindowEvent {
     PinchZoomWindowEvent(f32),
     /// Sent when the user uses chrome navigation (i.e. backspace or shift-backspace).
     NavigationWindowEvent(WindowNavigateMsg),
+    /// Sent when a key goes down, repeats or comes up, unless the window used it as a shortcut.
+    KeyWindowEvent(KeyInput),
+    /// Sent as an input method composes text.
+    CompositionWindowEvent(CompositionInput),
     /// Sent when rendering is finished.
     FinishedWindowEvent,
     /// Sent when the user quits the application
@@ -65,6 +72,8 @@ pub trait WindowMethods<A> {
     fn framebuffer_size(&self) -> TypedSize2D<DevicePixel, uint>;
     /// Returns the size of the window in density-independent "px" units.
     fn size(&self) -> TypedSize2D<ScreenPx, f32>;
+    /// Returns the position of the window's top left corner on the screen.
+    fn screen_origin(&self) -> TypedPoint2D<ScreenPx, f32>;
     /// Presents the window to the screen (perhaps by page flipping).
     fn present(&self);
 
@@ -151,6 +151,9 @@ pub trait ScriptListener : Clone {
                              pipeline_id: PipelineId,
                              layer_id: LayerId,
                              point: Point2D<f32>);
+    /// Scrolls whichever layer is under `window_point` by `delta`, once script has fired a `wheel`
+    /// event for it that wasn't canceled. Both are in CSS px.
+    fn scroll(&self, delta: Point2D<f32>, window_point: Point2D<f32>);
     fn close(&self);
     fn dup(&self) -> Box<ScriptListener>;
 }
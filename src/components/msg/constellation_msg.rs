@@ -5,6 +5,7 @@
 //! The high-level interface from script to constellation. Using this abstract interface helps reduce
 /// coupling between these two components
 
+use geom::point::Point2D;
 use geom::rect::Rect;
 use geom::size::TypedSize2D;
 use geom::scale_factor::ScaleFactor;
@@ -79,3 +80,88 @@ pub struct PipelineId(pub uint);
 
 #[deriving(Clone, PartialEq, Eq, Hash, Encodable)]
 pub struct SubpageId(pub uint);
+
+/// Whether a key went down, came up, or is auto-repeating because it is held down.
+#[deriving(Clone, PartialEq, Show)]
+pub enum KeyState {
+    KeyPressed,
+    KeyReleased,
+    KeyRepeated,
+}
+
+/// The modifier keys held down during an input event.
+#[deriving(Clone, PartialEq, Show)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    /// The Windows or Command key.
+    pub meta: bool,
+}
+
+impl KeyModifiers {
+    pub fn none() -> KeyModifiers {
+        KeyModifiers {
+            shift: false,
+            control: false,
+            alt: false,
+            meta: false,
+        }
+    }
+}
+
+/// A key event from the windowing system, already translated from the platform's key codes into
+/// the values a DOM `KeyboardEvent` carries.
+#[deriving(Clone, Show)]
+pub struct KeyInput {
+    /// The character the key produces, such as "a" or "A", or a named key value such as "Enter"
+    /// or "ArrowLeft". "Unidentified" if the platform key has no DOM equivalent.
+    pub key: String,
+    /// The physical key, independent of layout and modifiers, such as "KeyA" or "ShiftLeft".
+    pub code: String,
+    /// One of the `KeyboardEvent.DOM_KEY_LOCATION_*` constants.
+    pub location: u32,
+    pub state: KeyState,
+    pub modifiers: KeyModifiers,
+}
+
+/// A step in composing text with an input method (IME), from the windowing system.
+#[deriving(Clone, Show)]
+pub enum CompositionInput {
+    /// The input method started composing text.
+    CompositionStarted,
+    /// The text being composed changed to the given string.
+    CompositionUpdated(String),
+    /// The input method committed the given text, which ends the composition.
+    CompositionCommitted(String),
+}
+
+/// A mouse event for script, with the cursor's position in each of the coordinate spaces a DOM
+/// `MouseEvent` exposes.
+#[deriving(Clone)]
+pub struct MouseInput {
+    /// Relative to the page's viewport, in CSS px.
+    pub client_point: Point2D<f32>,
+    /// Relative to the page's origin, so including how far it has been scrolled, in CSS px.
+    pub page_point: Point2D<f32>,
+    /// Relative to the screen's origin, in screen px.
+    pub screen_point: Point2D<f32>,
+    /// The button that went down or up, numbered as `MouseEvent.button` is: 0 for the primary
+    /// button, 1 for the middle one and 2 for the secondary one. 0 for moves.
+    pub button: i16,
+    /// The buttons held down after the event, as a `MouseEvent.buttons` bit set.
+    pub buttons: u16,
+    pub modifiers: KeyModifiers,
+}
+
+/// A turn of the scroll wheel for script, which fires a `wheel` event at the element under the
+/// cursor and, unless a listener cancels it, asks the compositor to scroll.
+#[deriving(Clone)]
+pub struct WheelInput {
+    pub mouse: MouseInput,
+    /// How far to scroll, in CSS px.
+    pub delta: Point2D<f32>,
+    /// The cursor's position in the compositor's window, in CSS px, which tells the compositor
+    /// which layer to scroll.
+    pub window_point: Point2D<f32>,
+}
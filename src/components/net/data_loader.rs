@@ -4,18 +4,15 @@
 
 use resource_task::{Done, Payload, Metadata, LoadData, LoadResponse, LoaderTask, start_sending};
 
+use servo_util::opts::DebugOptions;
+
 use serialize::base64::FromBase64;
 
 use http::headers::test_utils::from_stream_with_str;
 use http::headers::content_type::MediaType;
 
-pub fn factory() -> LoaderTask {
-    proc(url, start_chan) {
-        // NB: we don't spawn a new task.
-        // Hypothesis: data URLs are too small for parallel base64 etc. to be worth it.
-        // Should be tested at some point.
-        load(url, start_chan)
-    }
+pub fn factory(_: &DebugOptions) -> LoaderTask {
+    proc(url, start_chan) load(url, start_chan)
 }
 
 fn load(load_data: LoadData, start_chan: Sender<LoadResponse>) {
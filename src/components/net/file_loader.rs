@@ -4,9 +4,10 @@
 
 use resource_task::{ProgressMsg, Metadata, Payload, Done, LoaderTask, start_sending};
 
+use servo_util::opts::DebugOptions;
+
 use std::io;
 use std::io::File;
-use servo_util::task::spawn_named;
 
 //FIXME: https://github.com/mozilla/rust/issues/12892
 static READ_SIZE: uint = 1;
@@ -25,22 +26,20 @@ fn read_all(reader: &mut io::Stream, progress_chan: &Sender<ProgressMsg>)
     }
 }
 
-pub fn factory() -> LoaderTask {
+pub fn factory(_: &DebugOptions) -> LoaderTask {
     let f: LoaderTask = proc(load_data, start_chan) {
         let url = load_data.url;
         assert!("file" == url.scheme.as_slice());
         let progress_chan = start_sending(start_chan, Metadata::default(url.clone()));
-        spawn_named("file_loader", proc() {
-            match File::open_mode(&Path::new(url.path), io::Open, io::Read) {
-                Ok(ref mut reader) => {
-                    let res = read_all(reader as &mut io::Stream, &progress_chan);
-                    progress_chan.send(Done(res));
-                }
-                Err(e) => {
-                    progress_chan.send(Done(Err(e.desc.to_string())));
-                }
-            };
-        });
+        match File::open_mode(&Path::new(url.path), io::Open, io::Read) {
+            Ok(ref mut reader) => {
+                let res = read_all(reader as &mut io::Stream, &progress_chan);
+                progress_chan.send(Done(res));
+            }
+            Err(e) => {
+                progress_chan.send(Done(Err(e.desc.to_string())));
+            }
+        };
     };
     f
 }
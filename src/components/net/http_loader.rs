@@ -4,17 +4,17 @@
 
 use resource_task::{Metadata, Payload, Done, LoadResponse, LoadData, LoaderTask, start_sending_opt};
 
+use servo_util::opts::DebugOptions;
+
 use std::collections::hashmap::HashSet;
 use http::client::{RequestWriter, NetworkStream};
 use http::headers::HeaderEnum;
 use std::io::Reader;
-use servo_util::task::spawn_named;
 use url::Url;
 
-pub fn factory() -> LoaderTask {
-    let f: LoaderTask = proc(url, start_chan) {
-        spawn_named("http_loader", proc() load(url, start_chan))
-    };
+pub fn factory(debug: &DebugOptions) -> LoaderTask {
+    let print_headers = debug.net_http;
+    let f: LoaderTask = proc(url, start_chan) load(url, start_chan, print_headers);
     f
 }
 
@@ -25,7 +25,9 @@ fn send_error(url: Url, err: String, start_chan: Sender<LoadResponse>) {
     };
 }
 
-fn load(load_data: LoadData, start_chan: Sender<LoadResponse>) {
+/// Loads `load_data.url`, following redirects. If `print_headers` is set, each request and the
+/// response headers are printed for `--debug=net.http`.
+fn load(load_data: LoadData, start_chan: Sender<LoadResponse>, print_headers: bool) {
     // FIXME: At the time of writing this FIXME, servo didn't have any central
     //        location for configuration. If you're reading this and such a
     //        repository DOES exist, please update this constant to use it.
@@ -57,6 +59,9 @@ fn load(load_data: LoadData, start_chan: Sender<LoadResponse>) {
         }
 
         info!("requesting {:s}", url.to_str());
+        if print_headers {
+            println!("{} {}", load_data.method, url);
+        }
 
         let request = RequestWriter::<NetworkStream>::new(load_data.method.clone(), url.clone());
         let mut writer = match request {
@@ -102,6 +107,12 @@ fn load(load_data: LoadData, start_chan: Sender<LoadResponse>) {
             for header in response.headers.iter() {
                 info!(" - {:s}: {:s}", header.header_name(), header.header_value());
             });
+        if print_headers {
+            println!("{} {}", response.status, url);
+            for header in response.headers.iter() {
+                println!(" - {:s}: {:s}", header.header_name(), header.header_value());
+            }
+        }
 
         if 3 == (response.status.code() / 100) {
             match response.headers.location {
@@ -5,6 +5,8 @@
 use image::base::{Image, load_from_memory};
 use resource_task;
 use resource_task::{LoadData, ResourceTask};
+use servo_util::memory::{MemoryReport, MemoryReporter, MemoryReportsChan};
+use servo_util::taskpool::{TaskPool, BackgroundPriority};
 use servo_util::url::{UrlMap, url_map};
 
 use std::comm::{channel, Receiver, Sender};
@@ -41,6 +43,9 @@ pub enum Msg {
     /// Used by the decoder tasks to post decoded images back to the cache
     StoreImage(Url, Option<Arc<Box<Image>>>),
 
+    /// Sends the memory used by fetched and decoded images to the memory profiler.
+    CollectMemoryReports(MemoryReportsChan),
+
     /// For testing
     WaitForStore(Sender<()>),
 
@@ -80,7 +85,8 @@ impl<E, S: Encoder<E>> Encodable<S, E> for ImageCacheTask {
 
 type DecoderFactory = fn() -> proc(&[u8]) -> Option<Image>;
 
-pub fn ImageCacheTask(resource_task: ResourceTask) -> ImageCacheTask {
+/// Creates an image cache that fetches through `resource_task` and decodes on `pool`.
+pub fn ImageCacheTask(resource_task: ResourceTask, pool: TaskPool) -> ImageCacheTask {
     let (chan, port) = channel();
     let chan_clone = chan.clone();
 
@@ -89,6 +95,7 @@ pub fn ImageCacheTask(resource_task: ResourceTask) -> ImageCacheTask {
             resource_task: resource_task.clone(),
             port: port,
             chan: chan_clone,
+            pool: pool,
             state_map: url_map(),
             wait_map: url_map(),
             need_exit: None
@@ -102,11 +109,11 @@ pub fn ImageCacheTask(resource_task: ResourceTask) -> ImageCacheTask {
 }
 
 // FIXME: make this priv after visibility rules change
-pub fn SyncImageCacheTask(resource_task: ResourceTask) -> ImageCacheTask {
+pub fn SyncImageCacheTask(resource_task: ResourceTask, pool: TaskPool) -> ImageCacheTask {
     let (chan, port) = channel();
 
     spawn(proc() {
-        let inner_cache = ImageCacheTask(resource_task.clone());
+        let inner_cache = ImageCacheTask(resource_task.clone(), pool);
 
         loop {
             let msg: Msg = port.recv();
@@ -136,6 +143,8 @@ struct ImageCache {
     port: Receiver<Msg>,
     /// A copy of the shared chan to give to child tasks
     chan: Sender<Msg>,
+    /// The pool that images are decoded on
+    pool: TaskPool,
     /// The state of processsing an image for a URL
     state_map: UrlMap<ImageState>,
     /// List of clients waiting on a WaitForImage response
@@ -192,6 +201,7 @@ impl ImageCache {
                 WaitForImage(url, response) => {
                     self.wait_for_image(url, response)
                 }
+                CollectMemoryReports(reports_chan) => self.collect_memory_reports(reports_chan),
                 WaitForStore(chan) => store_chan = Some(chan),
                 WaitForStorePrefetched(chan) => store_prefetched_chan = Some(chan),
                 Exit(response) => {
@@ -239,6 +249,28 @@ impl ImageCache {
         self.state_map.insert(url, state);
     }
 
+    fn collect_memory_reports(&self, reports_chan: MemoryReportsChan) {
+        let mut fetched = 0u64;
+        let mut decoded = 0u64;
+        for (_, state) in self.state_map.iter() {
+            match *state {
+                Prefetched(ref data) => fetched += data.capacity() as u64,
+                Decoded(ref image) => decoded += image.pixels.capacity() as u64,
+                Init | Prefetching(..) | Decoding | Failed => {}
+            }
+        }
+        reports_chan.send(vec!(
+            MemoryReport {
+                path: "image-cache/fetched".to_string(),
+                size: fetched,
+            },
+            MemoryReport {
+                path: "image-cache/decoded".to_string(),
+                size: decoded,
+            }
+        ));
+    }
+
     fn prefetch(&mut self, url: Url) {
         match self.get_state(url.clone()) {
             Init => {
@@ -246,6 +278,8 @@ impl ImageCache {
                 let resource_task = self.resource_task.clone();
                 let url_clone = url.clone();
 
+                // This blocks until the resource task has loaded the image, so it gets a task of
+                // its own rather than a pool worker.
                 spawn(proc() {
                     let url = url_clone;
                     debug!("image_cache_task: started fetch for {:s}", url.to_str());
@@ -315,7 +349,7 @@ impl ImageCache {
                 let to_cache = self.chan.clone();
                 let url_clone = url.clone();
 
-                spawn(proc() {
+                self.pool.execute(BackgroundPriority, proc() {
                     let url = url_clone;
                     debug!("image_cache_task: started image decode for {:s}", url.to_str());
                     let image = load_from_memory(data.as_slice());
@@ -452,6 +486,12 @@ impl ImageCacheTask {
     }
 }
 
+impl MemoryReporter for ImageCacheTask {
+    fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool {
+        self.chan.send_opt(CollectMemoryReports(reports_chan)).is_ok()
+    }
+}
+
 fn load_image_data(url: Url, resource_task: ResourceTask) -> Result<Vec<u8>, ()> {
     let (response_chan, response_port) = channel();
     resource_task.send(resource_task::Load(LoadData::new(url), response_chan));
@@ -494,6 +534,8 @@ mod tests {
     use resource_task;
     use resource_task::{ResourceTask, Metadata, start_sending};
     use image::base::test_image_bin;
+    use servo_util::memory::{MemoryReporter, MemoryReportsChan};
+    use servo_util::taskpool::TaskPool;
     use servo_util::url::parse_url;
     use std::comm;
 
@@ -581,7 +623,7 @@ mod tests {
     fn should_exit_on_request() {
         let mock_resource_task = mock_resource_task(box DoesNothing);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let _url = parse_url("file", None);
 
         image_cache_task.exit();
@@ -593,7 +635,7 @@ mod tests {
     fn should_fail_if_unprefetched_image_is_requested() {
         let mock_resource_task = mock_resource_task(box DoesNothing);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let (chan, port) = channel();
@@ -607,7 +649,7 @@ mod tests {
 
         let mock_resource_task = mock_resource_task(box JustSendOK { url_requested_chan: url_requested_chan});
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url));
@@ -622,7 +664,7 @@ mod tests {
 
         let mock_resource_task = mock_resource_task(box JustSendOK { url_requested_chan: url_requested_chan});
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -642,7 +684,7 @@ mod tests {
 
         let mock_resource_task = mock_resource_task(box WaitSendTestImage{wait_port: wait_port});
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -659,7 +701,7 @@ mod tests {
     fn should_return_decoded_image_data_if_data_has_arrived() {
         let mock_resource_task = mock_resource_task(box SendTestImage);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store();
@@ -685,7 +727,7 @@ mod tests {
     fn should_return_decoded_image_data_for_multiple_requests() {
         let mock_resource_task = mock_resource_task(box SendTestImage);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store();
@@ -732,7 +774,7 @@ mod tests {
             }
         });
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -778,7 +820,7 @@ mod tests {
             }
         });
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -807,7 +849,7 @@ mod tests {
     fn should_return_failed_if_image_bin_cannot_be_fetched() {
         let mock_resource_task = mock_resource_task(box SendTestImageErr);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store_prefetched();
@@ -833,7 +875,7 @@ mod tests {
     fn should_return_failed_for_multiple_get_image_requests_if_image_bin_cannot_be_fetched() {
         let mock_resource_task = mock_resource_task(box SendTestImageErr);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store_prefetched();
@@ -867,7 +909,7 @@ mod tests {
     fn should_return_failed_if_image_decode_fails() {
         let mock_resource_task = mock_resource_task(box SendBogusImage);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store();
@@ -895,7 +937,7 @@ mod tests {
     fn should_return_image_on_wait_if_image_is_already_loaded() {
         let mock_resource_task = mock_resource_task(box SendTestImage);
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         let join_port = image_cache_task.wait_for_store();
@@ -923,7 +965,7 @@ mod tests {
 
         let mock_resource_task = mock_resource_task(box WaitSendTestImage {wait_port: wait_port});
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -949,7 +991,7 @@ mod tests {
 
         let mock_resource_task = mock_resource_task(box WaitSendTestImageErr{wait_port: wait_port});
 
-        let image_cache_task = ImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -973,7 +1015,7 @@ mod tests {
     fn sync_cache_should_wait_for_images() {
         let mock_resource_task = mock_resource_task(box SendTestImage);
 
-        let image_cache_task = SyncImageCacheTask(mock_resource_task.clone());
+        let image_cache_task = SyncImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
         let url = parse_url("file", None);
 
         image_cache_task.send(Prefetch(url.clone()));
@@ -989,4 +1031,32 @@ mod tests {
         image_cache_task.exit();
         mock_resource_task.send(resource_task::Exit);
     }
+
+    #[test]
+    fn should_report_decoded_image_memory() {
+        let mock_resource_task = mock_resource_task(box SendTestImage);
+
+        let image_cache_task = ImageCacheTask(mock_resource_task.clone(), TaskPool::new(1));
+        let url = parse_url("file", None);
+
+        let join_port = image_cache_task.wait_for_store();
+
+        image_cache_task.send(Prefetch(url.clone()));
+        image_cache_task.send(Decode(url.clone()));
+
+        // Wait until our mock resource task has sent the image to the image cache
+        join_port.recv();
+
+        let (reports_chan, reports_port) = comm::channel();
+        assert!(image_cache_task.collect_reports(MemoryReportsChan(reports_chan)));
+        let reports = reports_port.recv();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports.get(0).path.as_slice(), "image-cache/fetched");
+        assert_eq!(reports.get(0).size, 0);
+        assert_eq!(reports.get(1).path.as_slice(), "image-cache/decoded");
+        assert!(reports.get(1).size > 0);
+
+        image_cache_task.exit();
+        mock_resource_task.send(resource_task::Exit);
+    }
 }
@@ -8,6 +8,9 @@ use file_loader;
 use http_loader;
 use data_loader;
 
+use servo_util::opts::DebugOptions;
+use servo_util::task::spawn_named;
+
 use std::comm::{channel, Receiver, Sender};
 use std::task::TaskBuilder;
 use http::headers::content_type::MediaType;
@@ -160,27 +163,29 @@ pub type LoaderTask = proc(load_data: LoadData, Sender<LoadResponse>);
 Creates a task to load a specific resource
 
 The ResourceManager delegates loading to a different type of loader task for
-each URL scheme
+each URL scheme, and runs each load in a task of its own, since loaders block on I/O
 */
-type LoaderTaskFactory = extern "Rust" fn() -> LoaderTask;
+type LoaderTaskFactory = extern "Rust" fn(&DebugOptions) -> LoaderTask;
 
-/// Create a ResourceTask with the default loaders
-pub fn ResourceTask() -> ResourceTask {
+/// Create a ResourceTask with the default loaders. `debug` says what the loaders should print.
+pub fn ResourceTask(debug: DebugOptions) -> ResourceTask {
     let loaders = vec!(
         ("file".to_string(), file_loader::factory),
         ("http".to_string(), http_loader::factory),
         ("data".to_string(), data_loader::factory),
     );
-    create_resource_task_with_loaders(loaders)
+    create_resource_task_with_loaders(loaders, debug)
 }
 
-fn create_resource_task_with_loaders(loaders: Vec<(String, LoaderTaskFactory)>) -> ResourceTask {
+fn create_resource_task_with_loaders(loaders: Vec<(String, LoaderTaskFactory)>,
+                                     debug: DebugOptions)
+                                     -> ResourceTask {
     let (setup_chan, setup_port) = channel();
     let builder = TaskBuilder::new().named("ResourceManager");
     builder.spawn(proc() {
         let (chan, port) = channel();
         setup_chan.send(chan);
-        ResourceManager(port, loaders).start();
+        ResourceManager(port, loaders, debug).start();
     });
     setup_port.recv()
 }
@@ -189,14 +194,18 @@ struct ResourceManager {
     from_client: Receiver<ControlMsg>,
     /// Per-scheme resource loaders
     loaders: Vec<(String, LoaderTaskFactory)>,
+    /// What the loaders should print for debugging
+    debug: DebugOptions,
 }
 
 
 fn ResourceManager(from_client: Receiver<ControlMsg>,
-                   loaders: Vec<(String, LoaderTaskFactory)>) -> ResourceManager {
+                   loaders: Vec<(String, LoaderTaskFactory)>,
+                   debug: DebugOptions) -> ResourceManager {
     ResourceManager {
         from_client : from_client,
         loaders : loaders,
+        debug : debug,
     }
 }
 
@@ -219,7 +228,9 @@ impl ResourceManager {
         match self.get_loader_factory(&load_data) {
             Some(loader_factory) => {
                 debug!("resource_task: loading url: {:s}", load_data.url.to_str());
-                loader_factory(load_data, start_chan);
+                spawn_named("ResourceLoader", proc() {
+                    loader_factory(load_data, start_chan)
+                });
             }
             None => {
                 debug!("resource_task: no loader for scheme {:s}", load_data.url.scheme);
@@ -233,7 +244,7 @@ impl ResourceManager {
             match *scheme_loader {
                 (ref scheme, ref loader_factory) => {
 	            if (*scheme) == load_data.url.scheme {
-                        return Some((*loader_factory)());
+                        return Some((*loader_factory)(&self.debug));
                     }
 	        }
             }
@@ -244,13 +255,13 @@ impl ResourceManager {
 
 #[test]
 fn test_exit() {
-    let resource_task = ResourceTask();
+    let resource_task = ResourceTask(DebugOptions::new());
     resource_task.send(Exit);
 }
 
 #[test]
 fn test_bad_scheme() {
-    let resource_task = ResourceTask();
+    let resource_task = ResourceTask(DebugOptions::new());
     let (start_chan, start) = channel();
     resource_task.send(Load(LoadData::new(FromStr::from_str("bogus://whatever").unwrap()), start_chan));
     let response = start.recv();
@@ -265,7 +276,7 @@ fn test_bad_scheme() {
 static snicklefritz_payload: [u8, ..3] = [1, 2, 3];
 
 #[cfg(test)]
-fn snicklefritz_loader_factory() -> LoaderTask {
+fn snicklefritz_loader_factory(_: &DebugOptions) -> LoaderTask {
     let f: LoaderTask = proc(load_data: LoadData, start_chan: Sender<LoadResponse>) {
         let progress_chan = start_sending(start_chan, Metadata::default(load_data.url));
         progress_chan.send(Payload(Vec::from_slice(snicklefritz_payload)));
@@ -277,7 +288,7 @@ fn snicklefritz_loader_factory() -> LoaderTask {
 #[test]
 fn should_delegate_to_scheme_loader() {
     let loader_factories = vec!(("snicklefritz".to_string(), snicklefritz_loader_factory));
-    let resource_task = create_resource_task_with_loaders(loader_factories);
+    let resource_task = create_resource_task_with_loaders(loader_factories, DebugOptions::new());
     let (start_chan, start) = channel();
     resource_task.send(Load(LoadData::new(FromStr::from_str("snicklefritz://heya").unwrap()), start_chan));
 
@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::AnimationEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, AnimationEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, AnimationEventTypeId};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct AnimationEvent {
+    event: Event,
+    animation_name: Traceable<RefCell<DOMString>>,
+    elapsed_time: Traceable<Cell<f32>>,
+    pseudo_element: Traceable<RefCell<DOMString>>,
+}
+
+impl AnimationEventDerived for Event {
+    fn is_animationevent(&self) -> bool {
+        self.type_id == AnimationEventTypeId
+    }
+}
+
+impl AnimationEvent {
+    pub fn new_inherited() -> AnimationEvent {
+        AnimationEvent {
+            event: Event::new_inherited(AnimationEventTypeId),
+            animation_name: Traceable::new(RefCell::new(DOMString::new())),
+            elapsed_time: Traceable::new(Cell::new(0.0)),
+            pseudo_element: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<AnimationEvent> {
+        reflect_dom_object(box AnimationEvent::new_inherited(),
+                           &global::Window(window),
+                           AnimationEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               animation_name: DOMString, elapsed_time: f32,
+               pseudo_element: DOMString) -> Temporary<AnimationEvent> {
+        let ev = AnimationEvent::new_uninitialized(window).root();
+        *ev.deref().animation_name.deref().borrow_mut() = animation_name;
+        ev.deref().elapsed_time.deref().set(elapsed_time);
+        *ev.deref().pseudo_element.deref().borrow_mut() = pseudo_element;
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &AnimationEventBinding::AnimationEventInit)
+                       -> Fallible<Temporary<AnimationEvent>> {
+        Ok(AnimationEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                               init.animationName.clone(), init.elapsedTime,
+                               init.pseudoElement.clone()))
+    }
+}
+
+pub trait AnimationEventMethods {
+    fn AnimationName(&self) -> DOMString;
+    fn ElapsedTime(&self) -> f32;
+    fn PseudoElement(&self) -> DOMString;
+}
+
+impl<'a> AnimationEventMethods for JSRef<'a, AnimationEvent> {
+    fn AnimationName(&self) -> DOMString {
+        self.animation_name.deref().borrow().clone()
+    }
+
+    fn ElapsedTime(&self) -> f32 {
+        self.elapsed_time.deref().get()
+    }
+
+    fn PseudoElement(&self) -> DOMString {
+        self.pseudo_element.deref().borrow().clone()
+    }
+}
+
+impl Reflectable for AnimationEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -4,6 +4,7 @@
 
 use dom::bindings::codegen::Bindings::AttrBinding;
 use dom::bindings::codegen::InheritTypes::NodeCast;
+use dom::bindings::global;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
@@ -11,9 +12,10 @@ use dom::element::{Element, AttributeHandlers};
 use dom::node::Node;
 use dom::window::Window;
 use dom::virtualmethods::vtable_for;
+use servo_util::atom::Atom;
 use servo_util::namespace;
 use servo_util::namespace::Namespace;
-use servo_util::str::{DOMString, HTML_SPACE_CHARACTERS};
+use servo_util::str::{DOMString, split_html_space_chars};
 use std::cell::{Ref, Cell, RefCell};
 use std::mem;
 
@@ -22,27 +24,32 @@ pub enum AttrSettingType {
     ReplacedAttr,
 }
 
+/// The value of an attribute. Values are kept as UTF-8 rather than as a `DOMString`, since layout
+/// and selector matching read them as `&str`; so unlike text, a value set from script loses any
+/// unpaired surrogates, each becoming U+FFFD REPLACEMENT CHARACTER.
 #[deriving(PartialEq, Clone, Encodable)]
 pub enum AttrValue {
-    StringAttrValue(DOMString),
-    TokenListAttrValue(DOMString, Vec<(uint, uint)>),
-    UIntAttrValue(DOMString, u32),
+    StringAttrValue(String),
+    /// A space-separated list, such as `class`, with each token interned for selector matching.
+    TokenListAttrValue(String, Vec<Atom>),
+    UIntAttrValue(String, u32),
+    /// A single name, such as `id`, interned for selector matching.
+    AtomAttrValue(Atom),
 }
 
 impl AttrValue {
-    pub fn from_tokenlist(list: DOMString) -> AttrValue {
-        let mut indexes = vec![];
-        let mut last_index: uint = 0;
-        for (index, ch) in list.as_slice().char_indices() {
-            if HTML_SPACE_CHARACTERS.iter().any(|&space| space == ch) {
-                indexes.push((last_index, index));
-                last_index = index + 1;
-            }
-        }
-        return TokenListAttrValue(list, indexes);
+    pub fn from_tokenlist(list: String) -> AttrValue {
+        let tokens = split_html_space_chars(list.as_slice()).map(|token| {
+            Atom::from_slice(token)
+        }).collect();
+        TokenListAttrValue(list, tokens)
+    }
+
+    pub fn from_atomic(string: String) -> AttrValue {
+        AtomAttrValue(Atom::from_string(string))
     }
 
-    pub fn from_u32(string: DOMString, default: u32) -> AttrValue {
+    pub fn from_u32(string: String, default: u32) -> AttrValue {
         let result: u32 = from_str(string.as_slice()).unwrap_or(default);
         UIntAttrValue(string, result)
     }
@@ -52,6 +59,7 @@ impl AttrValue {
             StringAttrValue(ref value) |
             TokenListAttrValue(ref value, _) |
             UIntAttrValue(ref value, _) => value.as_slice(),
+            AtomAttrValue(ref value) => value.as_slice(),
         }
     }
 }
@@ -59,11 +67,11 @@ impl AttrValue {
 #[deriving(Encodable)]
 pub struct Attr {
     pub reflector_: Reflector,
-    pub local_name: DOMString,
+    pub local_name: Atom,
     value: Traceable<RefCell<AttrValue>>,
-    pub name: DOMString,
+    pub name: String,
     pub namespace: Namespace,
-    pub prefix: Option<DOMString>,
+    pub prefix: Option<String>,
 
     /// the element that owns this attribute.
     pub owner: Cell<JS<Element>>,
@@ -76,9 +84,9 @@ impl Reflectable for Attr {
 }
 
 impl Attr {
-    fn new_inherited(local_name: DOMString, value: AttrValue,
-                     name: DOMString, namespace: Namespace,
-                     prefix: Option<DOMString>, owner: &JSRef<Element>) -> Attr {
+    fn new_inherited(local_name: Atom, value: AttrValue,
+                     name: String, namespace: Namespace,
+                     prefix: Option<String>, owner: &JSRef<Element>) -> Attr {
         Attr {
             reflector_: Reflector::new(),
             local_name: local_name,
@@ -90,11 +98,11 @@ impl Attr {
         }
     }
 
-    pub fn new(window: &JSRef<Window>, local_name: DOMString, value: AttrValue,
-               name: DOMString, namespace: Namespace,
-               prefix: Option<DOMString>, owner: &JSRef<Element>) -> Temporary<Attr> {
+    pub fn new(window: &JSRef<Window>, local_name: Atom, value: AttrValue,
+               name: String, namespace: Namespace,
+               prefix: Option<String>, owner: &JSRef<Element>) -> Temporary<Attr> {
         let attr = Attr::new_inherited(local_name, value, name, namespace, prefix, owner);
-        reflect_dom_object(box attr, window, AttrBinding::Wrap)
+        reflect_dom_object(box attr, &global::Window(window), AttrBinding::Wrap)
     }
 
     pub fn set_value(&self, set_type: AttrSettingType, value: AttrValue) {
@@ -105,7 +113,7 @@ impl Attr {
         match set_type {
             ReplacedAttr => {
                 if namespace_is_null {
-                    vtable_for(node).before_remove_attr(self.local_name.clone(), self.value.deref().borrow().as_slice().to_string());
+                    vtable_for(node).before_remove_attr(self.local_name.as_slice().to_string(), self.value.deref().borrow().as_slice().to_string());
                 }
             }
             FirstSetAttr => {}
@@ -114,7 +122,7 @@ impl Attr {
         *self.value.deref().borrow_mut() = value;
 
         if namespace_is_null {
-            vtable_for(node).after_set_attr(self.local_name.clone(), self.value.deref().borrow().as_slice().to_string());
+            vtable_for(node).after_set_attr(self.local_name.as_slice().to_string(), self.value.deref().borrow().as_slice().to_string());
         }
     }
 
@@ -134,38 +142,40 @@ pub trait AttrMethods {
 
 impl<'a> AttrMethods for JSRef<'a, Attr> {
     fn LocalName(&self) -> DOMString {
-        self.local_name.clone()
+        DOMString::from_slice(self.local_name.as_slice())
     }
 
     fn Value(&self) -> DOMString {
-        self.value.deref().borrow().as_slice().to_string()
+        DOMString::from_slice(self.value.deref().borrow().as_slice())
     }
 
     fn SetValue(&self, value: DOMString) {
         let owner = self.owner.get().root();
         let value = owner.deref().parse_attribute(
-            &self.namespace, self.deref().local_name.as_slice(), value);
+            &self.namespace, self.deref().local_name.as_slice(), value.to_string_lossy());
         self.set_value(ReplacedAttr, value);
     }
 
     fn Name(&self) -> DOMString {
-        self.name.clone()
+        DOMString::from_slice(self.name.as_slice())
     }
 
     fn GetNamespaceURI(&self) -> Option<DOMString> {
         match self.namespace.to_str() {
             "" => None,
-            url => Some(url.to_string()),
+            url => Some(DOMString::from_slice(url)),
         }
     }
 
     fn GetPrefix(&self) -> Option<DOMString> {
-        self.prefix.clone()
+        self.prefix.as_ref().map(|prefix| DOMString::from_slice(prefix.as_slice()))
     }
 }
 
 pub trait AttrHelpersForLayout {
     unsafe fn value_ref_forever(&self) -> &'static str;
+    unsafe fn value_atom_forever(&self) -> Option<Atom>;
+    unsafe fn value_tokens_forever(&self) -> Option<&'static [Atom]>;
 }
 
 impl AttrHelpersForLayout for Attr {
@@ -174,4 +184,20 @@ impl AttrHelpersForLayout for Attr {
         let value = mem::transmute::<&RefCell<AttrValue>, &AttrValue>(self.value.deref());
         value.as_slice()
     }
+
+    unsafe fn value_atom_forever(&self) -> Option<Atom> {
+        let value = mem::transmute::<&RefCell<AttrValue>, &AttrValue>(self.value.deref());
+        match *value {
+            AtomAttrValue(ref value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    unsafe fn value_tokens_forever(&self) -> Option<&'static [Atom]> {
+        let value = mem::transmute::<&RefCell<AttrValue>, &AttrValue>(self.value.deref());
+        match *value {
+            TokenListAttrValue(_, ref tokens) => Some(tokens.as_slice()),
+            _ => None,
+        }
+    }
 }
@@ -4,6 +4,7 @@
 
 use dom::attr::Attr;
 use dom::bindings::codegen::Bindings::AttrListBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::element::Element;
@@ -27,7 +28,7 @@ impl AttrList {
 
     pub fn new(window: &JSRef<Window>, elem: &JSRef<Element>) -> Temporary<AttrList> {
         reflect_dom_object(box AttrList::new_inherited(window, elem),
-                           window, AttrListBinding::Wrap)
+                           &global::Window(window), AttrListBinding::Wrap)
     }
 }
 
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::BeforeUnloadEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, EventTargetCast, BeforeUnloadEventDerived};
+use dom::bindings::global;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, BeforeUnloadEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::RefCell;
+
+#[deriving(Encodable)]
+pub struct BeforeUnloadEvent {
+    event: Event,
+    return_value: Traceable<RefCell<DOMString>>,
+}
+
+impl BeforeUnloadEventDerived for Event {
+    fn is_beforeunloadevent(&self) -> bool {
+        self.type_id == BeforeUnloadEventTypeId
+    }
+}
+
+impl BeforeUnloadEvent {
+    pub fn new_inherited() -> BeforeUnloadEvent {
+        BeforeUnloadEvent {
+            event: Event::new_inherited(BeforeUnloadEventTypeId),
+            return_value: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new(window: &JSRef<Window>) -> Temporary<BeforeUnloadEvent> {
+        let ev = reflect_dom_object(box BeforeUnloadEvent::new_inherited(),
+                                    &global::Window(window),
+                                    BeforeUnloadEventBinding::Wrap).root();
+        {
+            let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+            event.InitEvent(DOMString::from_slice("beforeunload"), false, true);
+        }
+        Temporary::from_rooted(&*ev)
+    }
+
+    /// Fires a trusted "beforeunload" event at `window` ahead of navigating away from its
+    /// document. Returns false if a listener asked for the navigation to be blocked, either
+    /// by canceling the event or by setting a non-empty returnValue.
+    pub fn dispatch(window: &JSRef<Window>) -> bool {
+        let beforeunloadevent = BeforeUnloadEvent::new(window).root();
+        let event: &JSRef<Event> = EventCast::from_ref(&*beforeunloadevent);
+        event.deref().trusted.deref().set(true);
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(window);
+        let _ = target.dispatch_event_with_target(None, event);
+        !event.DefaultPrevented() && beforeunloadevent.deref().return_value.deref().borrow().is_empty()
+    }
+}
+
+pub trait BeforeUnloadEventMethods {
+    fn ReturnValue(&self) -> DOMString;
+    fn SetReturnValue(&self, value: DOMString);
+}
+
+impl<'a> BeforeUnloadEventMethods for JSRef<'a, BeforeUnloadEvent> {
+    fn ReturnValue(&self) -> DOMString {
+        self.return_value.deref().borrow().clone()
+    }
+
+    fn SetReturnValue(&self, value: DOMString) {
+        *self.return_value.deref().borrow_mut() = value;
+    }
+}
+
+impl Reflectable for BeforeUnloadEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
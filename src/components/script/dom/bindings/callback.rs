@@ -2,11 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::error::throw_type_error;
 use dom::bindings::js::JSRef;
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, global_object_for_js_object};
 use js::jsapi::{JSContext, JSObject, JS_WrapObject, JS_ObjectIsCallable};
-use js::jsapi::JS_GetProperty;
+use js::jsapi::{JS_GetProperty, JS_IsExceptionPending, JS_ReportPendingException};
 use js::jsval::{JSVal, UndefinedValue};
 
 use std::ptr;
@@ -85,7 +86,8 @@ impl CallbackInterface {
 
             if !callable.is_object() ||
                JS_ObjectIsCallable(cx, callable.to_object()) == 0 {
-                //ThrowErrorMessage(cx, MSG_NOT_CALLABLE, description.get());
+                throw_type_error(cx, format!("The value of the property '{:s}' is not callable",
+                                             name).as_slice());
                 return Err(());
             }
         }
@@ -126,3 +128,19 @@ impl CallSetup {
         self.cx
     }
 }
+
+impl Drop for CallSetup {
+    fn drop(&mut self) {
+        if self.cx.is_null() {
+            return;
+        }
+        match self.handling {
+            ReportExceptions => unsafe {
+                if JS_IsExceptionPending(self.cx) != 0 {
+                    JS_ReportPendingException(self.cx);
+                }
+            },
+            _ => (),
+        }
+    }
+}
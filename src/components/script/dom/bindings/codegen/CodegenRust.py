@@ -637,7 +637,7 @@ def getJSToNativeConversionTemplate(type, descriptorProvider, failureCode=None,
             default = "None"
         else:
             assert defaultValue.type.tag() == IDLType.Tags.domstring
-            value = "str::from_utf8(data).unwrap().to_string()"
+            value = "DOMString::from_slice(str::from_utf8(data).unwrap())"
             if type.nullable():
                 value = "Some(%s)" % value
 
@@ -1756,7 +1756,7 @@ def CreateBindingJSObject(descriptor, parent=None):
     if descriptor.proxy:
         assert not descriptor.createGlobal
         create += """
-let js_info = aScope.deref().page().js_info();
+let js_info = aScope.as_window().page().js_info();
 let handler = js_info.get_ref().dom_static.proxy_handlers.deref().get(&(PrototypeList::id::%s as uint));
 let mut private = PrivateValue(squirrel_away_unique(aObject) as *libc::c_void);
 let obj = with_compartment(aCx, proto, || {
@@ -1786,7 +1786,7 @@ class CGWrapMethod(CGAbstractMethod):
     def __init__(self, descriptor):
         assert descriptor.interface.hasInterfacePrototypeObject()
         if not descriptor.createGlobal:
-            args = [Argument('*mut JSContext', 'aCx'), Argument('&JSRef<Window>', 'aScope'),
+            args = [Argument('*mut JSContext', 'aCx'), Argument('&GlobalRef', 'aScope'),
                     Argument("Box<%s>" % descriptor.concreteType, 'aObject', mutable=True)]
         else:
             args = [Argument('*mut JSContext', 'aCx'),
@@ -2705,6 +2705,7 @@ class CGEnum(CGThing):
 use dom::bindings::conversions::ToJSValConvertible;
 use js::jsapi::JSContext;
 use js::jsval::JSVal;
+use servo_util::str::DOMString;
 
 #[repr(uint)]
 #[deriving(Encodable, PartialEq)]
@@ -2718,7 +2719,7 @@ pub static strings: &'static [&'static str] = &[
 
 impl ToJSValConvertible for valuelist {
   fn to_jsval(&self, cx: *mut JSContext) -> JSVal {
-    strings[*self as uint].to_string().to_jsval(cx)
+    DOMString::from_slice(strings[*self as uint]).to_jsval(cx)
   }
 }
 """ % (",\n  ".join(map(getEnumValueName, enum.values())),
@@ -3907,7 +3908,7 @@ let global = global_object_for_js_object(JS_CALLEE(cx, vp).to_object()).root();
 let obj = global.deref().reflector().get_jsobject();
 """)
         nativeName = MakeNativeName(self._ctor.identifier.name)
-        callGenerator = CGMethodCall(["&global.root_ref()"], nativeName, True,
+        callGenerator = CGMethodCall(["&global::Window(&global.root_ref())"], nativeName, True,
                                      self.descriptor, self._ctor)
         return CGList([preamble, callGenerator])
 
@@ -4362,6 +4363,8 @@ class CGBindingRoot(CGThing):
             'js::rust::with_compartment',
             'dom::types::*',
             'dom::bindings',
+            'dom::bindings::global',
+            'dom::bindings::global::GlobalRef',
             'dom::bindings::js::{JS, JSRef, Root, RootedReference, Temporary}',
             'dom::bindings::js::{OptionalRootable, OptionalRootedRootable, ResultRootable}',
             'dom::bindings::js::{OptionalRootedReference, OptionalOptionalRootedRootable}',
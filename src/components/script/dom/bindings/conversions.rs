@@ -5,7 +5,7 @@
 use dom::bindings::js::{JS, JSRef, Root};
 use dom::bindings::str::ByteString;
 use dom::bindings::utils::{Reflectable, Reflector};
-use dom::bindings::utils::jsstring_to_str;
+use dom::bindings::utils::jsstring_to_domstring;
 use dom::bindings::utils::unwrap_jsmanaged;
 use servo_util::str::DOMString;
 
@@ -218,8 +218,8 @@ impl FromJSValConvertible<()> for f64 {
 impl ToJSValConvertible for DOMString {
     fn to_jsval(&self, cx: *mut JSContext) -> JSVal {
         unsafe {
-            let string_utf16 = self.to_utf16();
-            let jsstr = JS_NewUCStringCopyN(cx, string_utf16.as_ptr(), string_utf16.len() as libc::size_t);
+            let units = self.as_utf16();
+            let jsstr = JS_NewUCStringCopyN(cx, units.as_ptr(), units.len() as libc::size_t);
             if jsstr.is_null() {
                 fail!("JS_NewUCStringCopyN failed");
             }
@@ -243,14 +243,14 @@ impl Default for StringificationBehavior {
 impl FromJSValConvertible<StringificationBehavior> for DOMString {
     fn from_jsval(cx: *mut JSContext, value: JSVal, nullBehavior: StringificationBehavior) -> Result<DOMString, ()> {
         if nullBehavior == Empty && value.is_null() {
-            Ok("".to_string())
+            Ok(DOMString::new())
         } else {
             let jsstr = unsafe { JS_ValueToString(cx, value) };
             if jsstr.is_null() {
                 debug!("JS_ValueToString failed");
                 Err(())
             } else {
-                Ok(jsstring_to_str(cx, jsstr))
+                Ok(jsstring_to_domstring(cx, jsstr))
             }
         }
     }
@@ -15,22 +15,34 @@ use js::glue::{ReportError};
 use libc;
 use std::ptr;
 
+/// An error that a DOM method can report to script. Every variant but `Type` and
+/// `FailureUnknown` is thrown as the DOMException of the same name.
 #[deriving(Show)]
 pub enum Error {
     IndexSize,
     FailureUnknown,
     NotFound,
     HierarchyRequest,
+    WrongDocument,
     InvalidCharacter,
+    NoModificationAllowed,
     NotSupported,
     InvalidState,
     Syntax,
+    InvalidModification,
     NamespaceError,
     InvalidAccess,
     Security,
     Network,
     Abort,
-    Timeout
+    URLMismatch,
+    QuotaExceeded,
+    Timeout,
+    InvalidNodeType,
+    DataClone,
+    Encoding,
+    /// Thrown as a JavaScript TypeError with the given message.
+    Type(String)
 }
 
 pub type Fallible<T> = Result<T, Error>;
@@ -40,6 +52,10 @@ pub type ErrorResult = Fallible<()>;
 pub fn throw_dom_exception(cx: *mut JSContext, global: &JSRef<Window>,
                            result: Error) {
     assert!(unsafe { JS_IsExceptionPending(cx) } == 0);
+    let result = match result {
+        Type(message) => return throw_type_error(cx, message.as_slice()),
+        result => result,
+    };
     let exception = DOMException::new_from_error(global, result).root();
     let thrown = exception.to_jsval(cx);
     unsafe {
@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Abstractions for global scopes.
+//!
+//! DOM objects are created in, and reflected into, a global scope. The only
+//! such scope in this tree is a `Window`: there are no workers yet, so events
+//! can't be constructed or dispatched in a worker, and nothing here pretends
+//! otherwise. Code that does not actually need a window (events, most
+//! notably) still takes a `GlobalRef`, so that supporting workers comes down
+//! to adding a `WorkerGlobalScope` interface, a variant for it here and an arm
+//! for it in each accessor below. `as_window` is the one accessor that will
+//! fail for a worker; its callers are the interfaces that really need a window.
+
+use dom::bindings::js::JSRef;
+use dom::bindings::utils::{Reflectable, Reflector};
+use dom::window;
+
+use js::jsapi::JSContext;
+
+/// A stack-based rooted reference to a global object.
+pub enum GlobalRef<'a, 'b> {
+    Window(&'a JSRef<'b, window::Window>),
+}
+
+impl<'a, 'b> GlobalRef<'a, 'b> {
+    /// Get the `JSContext` for the `JSRuntime` associated with the thread
+    /// this global object is on.
+    pub fn get_cx(&self) -> *mut JSContext {
+        match *self {
+            Window(ref window) => window.get_cx(),
+        }
+    }
+
+    /// Extract a `Window`, failing if the global object is not a `Window`. Every global is a
+    /// `Window` for now, so this can't fail yet.
+    pub fn as_window<'c>(&'c self) -> &'c JSRef<'b, window::Window> {
+        match *self {
+            Window(window) => window,
+        }
+    }
+}
+
+impl<'a, 'b> Reflectable for GlobalRef<'a, 'b> {
+    fn reflector<'c>(&'c self) -> &'c Reflector {
+        match *self {
+            Window(ref window) => window.reflector(),
+        }
+    }
+}
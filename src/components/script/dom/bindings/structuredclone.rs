@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The structured clone algorithm, which copies a JavaScript value out of one global so that it
+//! can be recreated in another, possibly on another task.
+//!
+//! http://www.whatwg.org/html/#safe-passing-of-structured-data
+//!
+//! SpiderMonkey does the serialization. It handles primitives, strings, plain objects, arrays,
+//! `Date`s, `RegExp`s, typed arrays and `ArrayBuffer`s, and keeps shared and cyclic references
+//! intact. Anything else, functions and DOM objects included, cannot be cloned and is reported as
+//! a `DataCloneError`.
+
+use dom::bindings::error::{Fallible, DataClone};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::utils::Reflectable;
+
+use js::jsapi::{JSContext, JS_ClearPendingException};
+use js::jsapi::{JS_WriteStructuredClone, JS_ReadStructuredClone, JS_ClearStructuredClone};
+use js::jsapi::JS_STRUCTURED_CLONE_VERSION;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::with_compartment;
+
+use libc::size_t;
+use std::ptr;
+use std::slice;
+
+/// A value serialized by the structured clone algorithm. It holds no references into any
+/// JavaScript heap, so it can be sent to another task and read into any global.
+#[deriving(Clone)]
+pub struct StructuredCloneData {
+    data: Vec<u64>,
+    nbytes: uint,
+}
+
+impl StructuredCloneData {
+    /// Serializes `value`, which must belong to the compartment `cx` has entered. Throws
+    /// `DataCloneError` if `value` is, or contains, something that cannot be cloned.
+    pub fn write(cx: *mut JSContext, value: JSVal) -> Fallible<StructuredCloneData> {
+        let mut data = ptr::mut_null();
+        let mut nbytes = 0;
+        unsafe {
+            if JS_WriteStructuredClone(cx, value, &mut data, &mut nbytes,
+                                       ptr::null(), ptr::mut_null()) == 0 {
+                JS_ClearPendingException(cx);
+                return Err(DataClone);
+            }
+
+            // Copy the buffer out of the JS allocator so that it is ours to send and drop.
+            let words = (nbytes as uint + 7) / 8;
+            let copy = slice::raw::buf_as_slice(data as *u64, words, |words| words.to_vec());
+            JS_ClearStructuredClone(data as *u64, nbytes);
+            Ok(StructuredCloneData {
+                data: copy,
+                nbytes: nbytes as uint,
+            })
+        }
+    }
+
+    /// Recreates the value in the compartment of `global`. The data can be read any number of
+    /// times, and each read makes a new copy.
+    pub fn read(&self, global: &GlobalRef) -> JSVal {
+        let cx = global.get_cx();
+        let mut value = UndefinedValue();
+        with_compartment(cx, global.reflector().get_jsobject(), || {
+            unsafe {
+                // Reading back what SpiderMonkey itself wrote only fails if we run out of memory.
+                assert!(JS_ReadStructuredClone(cx, self.data.as_ptr(), self.nbytes as size_t,
+                                               JS_STRUCTURED_CLONE_VERSION, &mut value,
+                                               ptr::null(), ptr::mut_null()) != 0);
+            }
+        });
+        value
+    }
+}
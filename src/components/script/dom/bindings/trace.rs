@@ -5,8 +5,9 @@
 use dom::bindings::js::JS;
 use dom::bindings::utils::{Reflectable, Reflector};
 
-use js::jsapi::{JSObject, JSTracer, JS_CallTracer, JSTRACE_OBJECT};
-use js::jsval::JSVal;
+use js::jsapi::{JSContext, JSObject, JSTracer, JS_CallTracer, JSTRACE_OBJECT};
+use js::jsapi::{JSRuntime, JS_GetGCParameter, JSGC_MODE, JSGC_MODE_INCREMENTAL};
+use js::jsval::{JSVal, NullValue};
 
 use libc;
 use std::mem;
@@ -167,6 +168,66 @@ impl<S: Encoder<E>, E> Encodable<S, E> for Traceable<JSVal> {
     }
 }
 
+/// A mutable JSVal slot in a DOM object.
+///
+/// This is the one way a DOM struct should hold a JSVal that can change after
+/// construction (event details, message data, history state and the like).
+/// Deriving `Encodable` on the owning struct is enough to have the value
+/// traced; writes go through `set`, which is where a write barrier belongs.
+/// None is issued: see `assert_write_barriers_unneeded` for why that is
+/// sound, and for the check that keeps it so.
+/// Hand-rolled `Traceable<Cell<Traceable<JSVal>>>` fields should be ported
+/// to this type rather than copied.
+pub struct MutHeapJSVal {
+    val: Cell<JSVal>,
+}
+
+impl MutHeapJSVal {
+    /// Create a slot holding `null`.
+    pub fn new() -> MutHeapJSVal {
+        MutHeapJSVal {
+            val: Cell::new(NullValue()),
+        }
+    }
+
+    /// Get the current value.
+    pub fn get(&self) -> JSVal {
+        self.val.get()
+    }
+
+    /// Replace the current value. The context is taken so that a barrier can
+    /// be issued here without changing every caller, should the collector
+    /// ever need one.
+    pub fn set(&self, _cx: *mut JSContext, val: JSVal) {
+        self.val.set(val)
+    }
+}
+
+/// Check that the collector of `rt` needs no write barriers on the JSVals
+/// and object pointers DOM objects store outside the JS heap.
+///
+/// An incremental collector needs a pre-write barrier on every overwritten
+/// value, and a generational one a post-write barrier on every pointer into
+/// the nursery; SpiderMonkey only issues these for `JS::Heap` fields it
+/// knows about. Our mozjs is configured without `--enable-gcgenerational`,
+/// so there is no nursery, and a runtime only collects incrementally once
+/// its embedder sets `JSGC_MODE` to `JSGC_MODE_INCREMENTAL`. The script task
+/// calls this on every runtime it creates, so that turning on incremental
+/// GC fails here rather than corrupting the heap; `MutHeapJSVal::set` must
+/// issue barriers before that assertion can be relaxed.
+pub fn assert_write_barriers_unneeded(rt: *mut JSRuntime) {
+    let mode = unsafe { JS_GetGCParameter(rt, JSGC_MODE) };
+    assert!(mode != JSGC_MODE_INCREMENTAL as u32,
+            "incremental GC needs write barriers in MutHeapJSVal::set");
+}
+
+impl<S: Encoder<E>, E> Encodable<S, E> for MutHeapJSVal {
+    fn encode(&self, s: &mut S) -> Result<(), E> {
+        trace_jsval(get_jstracer(s), "val", self.get());
+        Ok(())
+    }
+}
+
 /// for a field which contains DOMType
 impl<T: Reflectable+Encodable<S, E>, S: Encoder<E>, E> Encodable<S, E> for Cell<JS<T>> {
     fn encode(&self, s: &mut S) -> Result<(), E> {
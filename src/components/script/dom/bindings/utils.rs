@@ -5,7 +5,8 @@
 use dom::bindings::codegen::PrototypeList;
 use dom::bindings::codegen::PrototypeList::MAX_PROTO_CHAIN_LENGTH;
 use dom::bindings::conversions::{FromJSValConvertible, IDLInterface};
-use dom::bindings::js::{JS, JSRef, Temporary, Root};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Temporary, Root};
 use dom::bindings::trace::Untraceable;
 use dom::browsercontext;
 use dom::window;
@@ -20,7 +21,6 @@ use std::cmp::PartialEq;
 use std::ptr;
 use std::ptr::null;
 use std::slice;
-use std::str;
 use js::glue::{js_IsObjectProxyClass, js_IsFunctionProxyClass, IsProxyHandlerFamily};
 use js::glue::{GetGlobalForObjectCrossCompartment, UnwrapObject, GetProxyHandlerExtra};
 use js::glue::{IsWrapper, RUST_JSID_TO_STRING, RUST_JSID_IS_INT};
@@ -146,12 +146,13 @@ pub unsafe fn squirrel_away_unique<T>(x: Box<T>) -> *T {
     mem::transmute(x)
 }
 
-pub fn jsstring_to_str(cx: *mut JSContext, s: *mut JSString) -> DOMString {
+/// Converts a JS string to a `DOMString`, keeping any unpaired surrogates.
+pub fn jsstring_to_domstring(cx: *mut JSContext, s: *mut JSString) -> DOMString {
     unsafe {
         let mut length = 0;
         let chars = JS_GetStringCharsAndLength(cx, s, &mut length);
         slice::raw::buf_as_slice(chars, length as uint, |char_vec| {
-            str::from_utf16(char_vec).unwrap()
+            DOMString::from_utf16(Vec::from_slice(char_vec))
         })
     }
 }
@@ -159,7 +160,7 @@ pub fn jsstring_to_str(cx: *mut JSContext, s: *mut JSString) -> DOMString {
 pub fn jsid_to_str(cx: *mut JSContext, id: jsid) -> DOMString {
     unsafe {
         assert!(RUST_JSID_IS_STRING(id) != 0);
-        jsstring_to_str(cx, RUST_JSID_TO_STRING(id))
+        jsstring_to_domstring(cx, RUST_JSID_TO_STRING(id))
     }
 }
 
@@ -376,10 +377,10 @@ pub trait Reflectable {
 
 pub fn reflect_dom_object<T: Reflectable>
         (obj:     Box<T>,
-         window:  &JSRef<window::Window>,
-         wrap_fn: extern "Rust" fn(*mut JSContext, &JSRef<window::Window>, Box<T>) -> JS<T>)
+         global:  &GlobalRef,
+         wrap_fn: extern "Rust" fn(*mut JSContext, &GlobalRef, Box<T>) -> JS<T>)
          -> Temporary<T> {
-    Temporary::new(wrap_fn(window.deref().get_cx(), window, obj))
+    Temporary::new(wrap_fn(global.get_cx(), global, obj))
 }
 
 #[allow(raw_pointer_deriving)]
@@ -3,6 +3,8 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::InheritTypes::FileDerived;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::bindings::error::Fallible;
@@ -33,12 +35,12 @@ impl Blob {
 
     pub fn new(window: &JSRef<Window>) -> Temporary<Blob> {
         reflect_dom_object(box Blob::new_inherited(window),
-                           window,
+                           &global::Window(window),
                            BlobBinding::Wrap)
     }
 
-    pub fn Constructor(window: &JSRef<Window>) -> Fallible<Temporary<Blob>> {
-        Ok(Blob::new(window))
+    pub fn Constructor(global: &GlobalRef) -> Fallible<Temporary<Blob>> {
+        Ok(Blob::new(global.as_window()))
     }
 }
 
@@ -15,6 +15,7 @@ use dom::node::{CommentNodeTypeId, Node, NodeTypeId, TextNodeTypeId, ProcessingI
 use servo_util::str::DOMString;
 
 use std::cell::RefCell;
+use std::cmp;
 
 #[deriving(Encodable)]
 pub struct CharacterData {
@@ -68,12 +69,16 @@ impl<'a> CharacterDataMethods for JSRef<'a, CharacterData> {
         self.data.deref().borrow().len() as u32
     }
 
+    // http://dom.spec.whatwg.org/#dom-characterdata-substringdata
     fn SubstringData(&self, offset: u32, count: u32) -> Fallible<DOMString> {
-        Ok(self.data.deref().borrow().as_slice().slice(offset as uint, count as uint).to_string())
+        match self.data.deref().borrow().substring(offset as uint, count as uint) {
+            Some(substring) => Ok(substring),
+            None => Err(IndexSize),
+        }
     }
 
     fn AppendData(&self, arg: DOMString) -> ErrorResult {
-        self.data.deref().borrow_mut().push_str(arg.as_slice());
+        self.data.deref().borrow_mut().push_domstring(&arg);
         Ok(())
     }
 
@@ -82,22 +87,20 @@ impl<'a> CharacterDataMethods for JSRef<'a, CharacterData> {
     }
 
     fn DeleteData(&self, offset: u32, count: u32) -> ErrorResult {
-        self.ReplaceData(offset, count, "".to_string())
+        self.ReplaceData(offset, count, DOMString::new())
     }
 
+    // http://dom.spec.whatwg.org/#dom-characterdata-replacedata
     fn ReplaceData(&self, offset: u32, count: u32, arg: DOMString) -> ErrorResult {
-        let length = self.data.deref().borrow().len() as u32;
+        let old_data = self.data.deref().borrow().clone();
+        let (offset, count, length) = (offset as uint, count as uint, old_data.len());
         if offset > length {
             return Err(IndexSize);
         }
-        let count = if offset + count > length {
-            length - offset
-        } else {
-            count
-        };
-        let mut data = self.data.deref().borrow().as_slice().slice(0, offset as uint).to_string();
-        data.push_str(arg.as_slice());
-        data.push_str(self.data.deref().borrow().as_slice().slice((offset + count) as uint, length as uint));
+        let end = offset + cmp::min(count, length - offset);
+        let mut data = old_data.substring(0, offset).unwrap();
+        data.push_domstring(&arg);
+        data.push_domstring(&old_data.substring(end, length - end).unwrap());
         *self.data.deref().borrow_mut() = data;
         // FIXME: Once we have `Range`, we should implement step7 to step11
         Ok(())
@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::CloseEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, CloseEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, CloseEventTypeId};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct CloseEvent {
+    event: Event,
+    was_clean: Traceable<Cell<bool>>,
+    code: Traceable<Cell<u16>>,
+    reason: Traceable<RefCell<DOMString>>,
+}
+
+impl CloseEventDerived for Event {
+    fn is_closeevent(&self) -> bool {
+        self.type_id == CloseEventTypeId
+    }
+}
+
+impl CloseEvent {
+    pub fn new_inherited() -> CloseEvent {
+        CloseEvent {
+            event: Event::new_inherited(CloseEventTypeId),
+            was_clean: Traceable::new(Cell::new(false)),
+            code: Traceable::new(Cell::new(0)),
+            reason: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<CloseEvent> {
+        reflect_dom_object(box CloseEvent::new_inherited(),
+                           &global::Window(window),
+                           CloseEventBinding::Wrap)
+    }
+
+    /// Creates a CloseEvent. The code is not range-checked here: WebSocket's close()
+    /// validates the codes script may send, but a peer can close with any code.
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               was_clean: bool, code: u16, reason: DOMString) -> Temporary<CloseEvent> {
+        let ev = CloseEvent::new_uninitialized(window).root();
+        ev.deref().was_clean.deref().set(was_clean);
+        ev.deref().code.deref().set(code);
+        *ev.deref().reason.deref().borrow_mut() = reason;
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &CloseEventBinding::CloseEventInit)
+                       -> Fallible<Temporary<CloseEvent>> {
+        Ok(CloseEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                           init.wasClean, init.code, init.reason.clone()))
+    }
+}
+
+pub trait CloseEventMethods {
+    fn WasClean(&self) -> bool;
+    fn Code(&self) -> u16;
+    fn Reason(&self) -> DOMString;
+}
+
+impl<'a> CloseEventMethods for JSRef<'a, CloseEvent> {
+    fn WasClean(&self) -> bool {
+        self.was_clean.deref().get()
+    }
+
+    fn Code(&self) -> u16 {
+        self.code.deref().get()
+    }
+
+    fn Reason(&self) -> DOMString {
+        self.reason.deref().borrow().clone()
+    }
+}
+
+impl Reflectable for CloseEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::CompositionEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, UIEventCast, CompositionEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, RootedReference, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, CompositionEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::uievent::{UIEvent, UIEventMethods};
+use dom::window::Window;
+use servo_util::str::DOMString;
+use std::cell::RefCell;
+
+#[deriving(Encodable)]
+pub struct CompositionEvent {
+    pub uievent: UIEvent,
+    data: Traceable<RefCell<DOMString>>,
+}
+
+impl CompositionEventDerived for Event {
+    fn is_compositionevent(&self) -> bool {
+        self.type_id == CompositionEventTypeId
+    }
+}
+
+impl CompositionEvent {
+    pub fn new_inherited() -> CompositionEvent {
+        CompositionEvent {
+            uievent: UIEvent::new_inherited(CompositionEventTypeId),
+            data: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<CompositionEvent> {
+        reflect_dom_object(box CompositionEvent::new_inherited(),
+                           &global::Window(window),
+                           CompositionEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>,
+               type_: DOMString,
+               can_bubble: bool,
+               cancelable: bool,
+               view: Option<JSRef<Window>>,
+               detail: i32,
+               data: DOMString) -> Temporary<CompositionEvent> {
+        let ev = CompositionEvent::new_uninitialized(window).root();
+        {
+            let uievent: &JSRef<UIEvent> = UIEventCast::from_ref(&*ev);
+            uievent.InitUIEvent(type_, can_bubble, cancelable, view, detail);
+        }
+        *ev.deref().data.deref().borrow_mut() = data;
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &CompositionEventBinding::CompositionEventInit)
+                       -> Fallible<Temporary<CompositionEvent>> {
+        let event = CompositionEvent::new(global.as_window(), type_,
+                                          init.parent.parent.bubbles,
+                                          init.parent.parent.cancelable,
+                                          init.parent.view.root_ref(),
+                                          init.parent.detail,
+                                          init.data.clone());
+        Ok(event)
+    }
+
+    /// Fires a trusted "compositionstart", "compositionupdate" or "compositionend" event at
+    /// `target` during an IME session. `data` is the composition string so far, which is
+    /// empty for "compositionstart". All three bubble; only "compositionstart" is
+    /// cancelable. Returns false if the event was canceled.
+    pub fn dispatch(target: &JSRef<EventTarget>, window: &JSRef<Window>, type_: &str,
+                    data: DOMString) -> bool {
+        let cancelable = type_ == "compositionstart";
+        let compositionevent = CompositionEvent::new(window, DOMString::from_slice(type_), true,
+                                                     cancelable, Some(window.clone()), 0,
+                                                     data).root();
+        let event: &JSRef<Event> = EventCast::from_ref(&*compositionevent);
+        event.deref().trusted.deref().set(true);
+        target.dispatch_event_with_target(None, event).unwrap_or(true)
+    }
+}
+
+pub trait CompositionEventMethods {
+    fn Data(&self) -> DOMString;
+}
+
+impl<'a> CompositionEventMethods for JSRef<'a, CompositionEvent> {
+    fn Data(&self) -> DOMString {
+        self.data.deref().borrow().clone()
+    }
+}
+
+impl Reflectable for CompositionEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.uievent.reflector()
+    }
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::ConsoleBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::window::Window;
@@ -21,7 +22,7 @@ impl Console {
     }
 
     pub fn new(window: &JSRef<Window>) -> Temporary<Console> {
-        reflect_dom_object(box Console::new_inherited(), window, ConsoleBinding::Wrap)
+        reflect_dom_object(box Console::new_inherited(), &global::Window(window), ConsoleBinding::Wrap)
     }
 }
 
@@ -36,32 +37,32 @@ pub trait ConsoleMethods {
 
 impl<'a> ConsoleMethods for JSRef<'a, Console> {
     fn Log(&self, message: DOMString) {
-        println!("{:s}", message);
+        println!("{}", message);
     }
 
     fn Debug(&self, message: DOMString) {
-        println!("{:s}", message);
+        println!("{}", message);
     }
 
     fn Info(&self, message: DOMString) {
-        println!("{:s}", message);
+        println!("{}", message);
     }
 
     fn Warn(&self, message: DOMString) {
-        println!("{:s}", message);
+        println!("{}", message);
     }
 
     fn Error(&self, message: DOMString) {
-        println!("{:s}", message);
+        println!("{}", message);
     }
 
     fn Assert(&self, condition: bool, message: Option<DOMString>) {
         if !condition {
             let message = match message {
-                Some(ref message) => message.as_slice(),
-                None => "no message",
+                Some(message) => message,
+                None => DOMString::from_slice("no message"),
             };
-            println!("Assertion failed: {:s}", message);
+            println!("Assertion failed: {}", message);
         }
     }
 }
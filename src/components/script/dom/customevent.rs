@@ -5,21 +5,25 @@
 use dom::bindings::codegen::Bindings::CustomEventBinding;
 use dom::bindings::codegen::InheritTypes::{EventCast, CustomEventDerived};
 use dom::bindings::js::{JSRef, Temporary};
-use dom::bindings::error::Fallible;
-use dom::bindings::trace::Traceable;
+use dom::bindings::error::{DataClone, ErrorResult, Fallible};
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::bindings::trace::MutHeapJSVal;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::utils::global_object_for_js_object;
 use dom::event::{Event, EventMethods, EventTypeId, CustomEventTypeId};
-use dom::window::Window;
+use js::glue::{GetGlobalForObjectCrossCompartment, IsWrapper, UnwrapObject};
 use js::jsapi::JSContext;
-use js::jsval::{JSVal, NullValue};
+use js::jsval::{JSVal, ObjectValue};
+use js::rust::with_compartment;
 use servo_util::str::DOMString;
-
-use std::cell::Cell;
+use std::ptr;
 
 #[deriving(Encodable)]
 pub struct CustomEvent {
     event: Event,
-    detail: Traceable<Cell<Traceable<JSVal>>>,
+    detail: MutHeapJSVal,
 }
 
 impl CustomEventDerived for Event {
@@ -32,48 +36,90 @@ pub trait CustomEventMethods {
     fn Detail(&self, _cx: *mut JSContext) -> JSVal;
     fn InitCustomEvent(&self, _cx: *mut JSContext,
                        type_: DOMString, can_bubble: bool,
-                       cancelable: bool, detail: JSVal);
+                       cancelable: bool, detail: JSVal) -> ErrorResult;
 }
 
 impl CustomEvent {
     pub fn new_inherited(type_id: EventTypeId) -> CustomEvent {
         CustomEvent {
             event: Event::new_inherited(type_id),
-            detail: Traceable::new(Cell::new(Traceable::new(NullValue()))),
+            detail: MutHeapJSVal::new(),
         }
     }
 
-    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<CustomEvent> {
+    pub fn new_uninitialized(global: &GlobalRef) -> Temporary<CustomEvent> {
         reflect_dom_object(box CustomEvent::new_inherited(CustomEventTypeId),
-                           window,
+                           global,
                            CustomEventBinding::Wrap)
     }
-    pub fn new(window: &JSRef<Window>, type_: DOMString, bubbles: bool, cancelable: bool, detail: JSVal) -> Temporary<CustomEvent> {
-        let ev = CustomEvent::new_uninitialized(window).root();
-        ev.deref().InitCustomEvent(window.deref().get_cx(), type_, bubbles, cancelable, detail);
-        Temporary::from_rooted(&*ev)
+    pub fn new(global: &GlobalRef, type_: DOMString, bubbles: bool, cancelable: bool, detail: JSVal) -> Fallible<Temporary<CustomEvent>> {
+        let ev = CustomEvent::new_uninitialized(global).root();
+        try!(ev.deref().InitCustomEvent(global.get_cx(), type_, bubbles, cancelable, detail));
+        Ok(Temporary::from_rooted(&*ev))
     }
-    pub fn Constructor(owner: &JSRef<Window>,
+    pub fn Constructor(global: &GlobalRef,
                        type_: DOMString,
                        init: &CustomEventBinding::CustomEventInit) -> Fallible<Temporary<CustomEvent>>{
-        Ok(CustomEvent::new(owner, type_, init.parent.bubbles, init.parent.cancelable, init.detail))
+        CustomEvent::new(global, type_, init.parent.bubbles, init.parent.cancelable, init.detail)
+    }
+}
+
+trait PrivateCustomEventHelpers {
+    fn adopt_detail(&self, cx: *mut JSContext, detail: JSVal) -> Fallible<JSVal>;
+}
+
+impl<'a> PrivateCustomEventHelpers for JSRef<'a, CustomEvent> {
+    /// What to store as the detail when script passes `detail`. An object from another global,
+    /// such as a parent or child frame, is not kept as a reference across globals: it is copied
+    /// into the event's global with the structured clone algorithm, as `postMessage` would copy
+    /// it. Throws `DataCloneError` if it can't be copied.
+    fn adopt_detail(&self, cx: *mut JSContext, detail: JSVal) -> Fallible<JSVal> {
+        if !detail.is_object() {
+            return Ok(detail);
+        }
+        let event_object = self.reflector().get_jsobject();
+        let object = unsafe {
+            let mut object = detail.to_object();
+            if IsWrapper(object) == 1 {
+                object = UnwrapObject(object, /* stopAtOuter = */ 0, ptr::null());
+                if object.is_null() {
+                    return Err(DataClone);
+                }
+            }
+            if GetGlobalForObjectCrossCompartment(object) ==
+                    GetGlobalForObjectCrossCompartment(event_object) {
+                return Ok(detail);
+            }
+            object
+        };
+
+        let data = try!(with_compartment(cx, object, || {
+            StructuredCloneData::write(cx, ObjectValue(unsafe { &*object }))
+        }));
+        let window = global_object_for_js_object(event_object).root();
+        Ok(data.read(&global::Window(&*window)))
     }
 }
 
 impl<'a> CustomEventMethods for JSRef<'a, CustomEvent> {
     fn Detail(&self, _cx: *mut JSContext) -> JSVal {
-        *self.detail.deref().get()
+        self.detail.get()
     }
 
     fn InitCustomEvent(&self,
-                       _cx: *mut JSContext,
+                       cx: *mut JSContext,
                        type_: DOMString,
                        can_bubble: bool,
                        cancelable: bool,
-                       detail: JSVal) {
-        self.detail.deref().set(Traceable::new(detail));
+                       detail: JSVal) -> ErrorResult {
         let event: &JSRef<Event> = EventCast::from_ref(self);
+        if event.deref().dispatching.deref().get() {
+            return Ok(());
+        }
+        let detail = try!(self.adopt_detail(cx, detail));
+        self.detail.set(cx, detail);
         event.InitEvent(type_, can_bubble, cancelable);
+        Ok(())
     }
 }
 
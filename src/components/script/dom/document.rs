@@ -8,8 +8,10 @@ use dom::bindings::codegen::InheritTypes::{HTMLHeadElementCast, TextCast, Elemen
 use dom::bindings::codegen::InheritTypes::{DocumentTypeCast, HTMLHtmlElementCast, NodeCast};
 use dom::bindings::codegen::InheritTypes::EventTargetCast;
 use dom::bindings::codegen::Bindings::DocumentBinding;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, Temporary, OptionalSettable, TemporaryPushable};
-use dom::bindings::js::OptionalRootable;
+use dom::bindings::js::{OptionalRootable, OptionalRootedRootable};
 use dom::bindings::trace::{Traceable, Untraceable};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::bindings::error::{ErrorResult, Fallible, NotSupported, InvalidCharacter};
@@ -25,6 +27,7 @@ use dom::element::{HTMLHtmlElementTypeId, HTMLHeadElementTypeId, HTMLTitleElemen
 use dom::element::{HTMLBodyElementTypeId, HTMLFrameSetElementTypeId};
 use dom::event::Event;
 use dom::eventtarget::{EventTarget, NodeTargetTypeId, EventTargetHelpers};
+use dom::focusevent::FocusEvent;
 use dom::htmlcollection::{HTMLCollection, CollectionFilter};
 use dom::htmlelement::HTMLElement;
 use dom::htmlheadelement::HTMLHeadElement;
@@ -63,13 +66,15 @@ pub struct Document {
     pub node: Node,
     pub reflector_: Reflector,
     pub window: JS<Window>,
-    idmap: Traceable<RefCell<HashMap<DOMString, Vec<JS<Element>>>>>,
+    idmap: Traceable<RefCell<HashMap<String, Vec<JS<Element>>>>>,
     pub implementation: Cell<Option<JS<DOMImplementation>>>,
-    pub content_type: DOMString,
-    pub encoding_name: Traceable<RefCell<DOMString>>,
+    pub content_type: String,
+    pub encoding_name: Traceable<RefCell<String>>,
     pub is_html_document: bool,
     pub url: Untraceable<Url>,
     pub quirks_mode: Untraceable<Cell<QuirksMode>>,
+    /// The element that currently has focus, if any.
+    focused: Cell<Option<JS<Element>>>,
 }
 
 impl DocumentDerived for EventTarget {
@@ -82,13 +87,15 @@ pub trait DocumentHelpers {
     fn url<'a>(&'a self) -> &'a Url;
     fn quirks_mode(&self) -> QuirksMode;
     fn set_quirks_mode(&self, mode: QuirksMode);
-    fn set_encoding_name(&self, name: DOMString);
+    fn set_encoding_name(&self, name: String);
     fn content_changed(&self);
     fn damage_and_reflow(&self, damage: DocumentDamageLevel);
     fn wait_until_safe_to_modify_dom(&self);
-    fn unregister_named_element(&self, to_unregister: &JSRef<Element>, id: DOMString);
-    fn register_named_element(&self, element: &JSRef<Element>, id: DOMString);
-    fn load_anchor_href(&self, href: DOMString);
+    fn unregister_named_element(&self, to_unregister: &JSRef<Element>, id: String);
+    fn register_named_element(&self, element: &JSRef<Element>, id: String);
+    fn load_anchor_href(&self, href: String);
+    fn get_focused_element(&self) -> Option<Temporary<Element>>;
+    fn set_focused_element(&self, element: Option<&JSRef<Element>>);
 }
 
 impl<'a> DocumentHelpers for JSRef<'a, Document> {
@@ -104,7 +111,7 @@ impl<'a> DocumentHelpers for JSRef<'a, Document> {
         self.quirks_mode.deref().set(mode);
     }
 
-    fn set_encoding_name(&self, name: DOMString) {
+    fn set_encoding_name(&self, name: String) {
         *self.encoding_name.deref().borrow_mut() = name;
     }
 
@@ -124,7 +131,7 @@ impl<'a> DocumentHelpers for JSRef<'a, Document> {
     /// Remove any existing association between the provided id and any elements in this document.
     fn unregister_named_element(&self,
                                 to_unregister: &JSRef<Element>,
-                                id: DOMString) {
+                                id: String) {
         let mut idmap = self.idmap.deref().borrow_mut();
         let is_empty = match idmap.find_mut(&id) {
             None => false,
@@ -145,7 +152,7 @@ impl<'a> DocumentHelpers for JSRef<'a, Document> {
     /// Associate an element present in this document with the provided id.
     fn register_named_element(&self,
                               element: &JSRef<Element>,
-                              id: DOMString) {
+                              id: String) {
         assert!({
             let node: &JSRef<Node> = NodeCast::from_ref(element);
             node.is_in_doc()
@@ -185,19 +192,55 @@ impl<'a> DocumentHelpers for JSRef<'a, Document> {
         idmap.insert(id, elements);
     }
 
-    fn load_anchor_href(&self, href: DOMString) {
+    fn load_anchor_href(&self, href: String) {
         let window = self.window.root();
-        window.load_url(href);
+        if !window.load_url(href.clone()) {
+            debug!("following a link to {:s} was blocked by a beforeunload listener", href);
+        }
+    }
+
+    fn get_focused_element(&self) -> Option<Temporary<Element>> {
+        self.focused.get().map(|element| Temporary::new(element))
+    }
+
+    /// Moves focus to `element`, or away from any element if it is None, firing blur and
+    /// focusout at the element losing focus and then focus and focusin at the one gaining
+    /// it. Each event's relatedTarget is the other element.
+    fn set_focused_element(&self, element: Option<&JSRef<Element>>) {
+        let old_focused = self.focused.get().root();
+        if old_focused.as_ref().map(|old| &**old) == element {
+            return;
+        }
+        self.focused.assign(element.map(|element| element.clone()));
+
+        let window = self.window.root();
+        let new_target = element.map(|element| {
+            let target: &JSRef<EventTarget> = EventTargetCast::from_ref(element);
+            target.clone()
+        });
+        let old_target = old_focused.as_ref().map(|old| {
+            let target: &JSRef<EventTarget> = EventTargetCast::from_ref(&**old);
+            target.clone()
+        });
+
+        for target in old_target.iter() {
+            FocusEvent::dispatch(target, &*window, "blur", new_target.clone());
+            FocusEvent::dispatch(target, &*window, "focusout", new_target.clone());
+        }
+        for target in new_target.iter() {
+            FocusEvent::dispatch(target, &*window, "focus", old_target.clone());
+            FocusEvent::dispatch(target, &*window, "focusin", old_target.clone());
+        }
     }
 }
 
 impl Document {
     pub fn reflect_document(document: Box<Document>,
                             window: &JSRef<Window>,
-                            wrap_fn: extern "Rust" fn(*mut JSContext, &JSRef<Window>, Box<Document>) -> JS<Document>)
+                            wrap_fn: extern "Rust" fn(*mut JSContext, &GlobalRef, Box<Document>) -> JS<Document>)
              -> Temporary<Document> {
         assert!(document.reflector().get_jsobject().is_null());
-        let raw_doc = reflect_dom_object(document, window, wrap_fn).root();
+        let raw_doc = reflect_dom_object(document, &global::Window(window), wrap_fn).root();
         assert!(raw_doc.reflector().get_jsobject().is_not_null());
 
         let doc_alias = raw_doc.clone();
@@ -209,7 +252,7 @@ impl Document {
     pub fn new_inherited(window: &JSRef<Window>,
                          url: Option<Url>,
                          is_html_document: IsHTMLDocument,
-                         content_type: Option<DOMString>) -> Document {
+                         content_type: Option<String>) -> Document {
         let url = url.unwrap_or_else(|| from_str("about:blank").unwrap());
 
         Document {
@@ -233,15 +276,16 @@ impl Document {
             // http://dom.spec.whatwg.org/#concept-document-encoding
             encoding_name: Traceable::new(RefCell::new("utf-8".to_string())),
             is_html_document: is_html_document == HTMLDocument,
+            focused: Cell::new(None),
         }
     }
 
     // http://dom.spec.whatwg.org/#dom-document
-    pub fn Constructor(owner: &JSRef<Window>) -> Fallible<Temporary<Document>> {
-        Ok(Document::new(owner, None, NonHTMLDocument, None))
+    pub fn Constructor(global: &GlobalRef) -> Fallible<Temporary<Document>> {
+        Ok(Document::new(global.as_window(), None, NonHTMLDocument, None))
     }
 
-    pub fn new(window: &JSRef<Window>, url: Option<Url>, doctype: IsHTMLDocument, content_type: Option<DOMString>) -> Temporary<Document> {
+    pub fn new(window: &JSRef<Window>, url: Option<Url>, doctype: IsHTMLDocument, content_type: Option<String>) -> Temporary<Document> {
         let document = Document::new_inherited(window, url, doctype, content_type);
         Document::reflect_document(box document, window, DocumentBinding::Wrap)
     }
@@ -347,7 +391,7 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
 
     // http://dom.spec.whatwg.org/#dom-document-url
     fn URL(&self) -> DOMString {
-        self.url().to_str()
+        DOMString::from_string(self.url().to_str())
     }
 
     // http://dom.spec.whatwg.org/#dom-document-documenturi
@@ -358,19 +402,19 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     // http://dom.spec.whatwg.org/#dom-document-compatmode
     fn CompatMode(&self) -> DOMString {
         match self.quirks_mode.deref().get() {
-            NoQuirks => "CSS1Compat".to_string(),
-            LimitedQuirks | FullQuirks => "BackCompat".to_string()
+            NoQuirks => DOMString::from_slice("CSS1Compat"),
+            LimitedQuirks | FullQuirks => DOMString::from_slice("BackCompat")
         }
     }
 
     // http://dom.spec.whatwg.org/#dom-document-characterset
     fn CharacterSet(&self) -> DOMString {
-        self.encoding_name.deref().borrow().as_slice().to_ascii_lower()
+        DOMString::from_string(self.encoding_name.deref().borrow().as_slice().to_ascii_lower())
     }
 
     // http://dom.spec.whatwg.org/#dom-document-content_type
     fn ContentType(&self) -> DOMString {
-        self.content_type.clone()
+        DOMString::from_slice(self.content_type.as_slice())
     }
 
     // http://dom.spec.whatwg.org/#dom-document-doctype
@@ -393,7 +437,7 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     // http://dom.spec.whatwg.org/#dom-document-getelementsbytagname
     fn GetElementsByTagName(&self, tag_name: DOMString) -> Temporary<HTMLCollection> {
         let window = self.window.root();
-        HTMLCollection::by_tag_name(&*window, NodeCast::from_ref(self), tag_name)
+        HTMLCollection::by_tag_name(&*window, NodeCast::from_ref(self), tag_name.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-document-getelementsbytagnamens
@@ -401,21 +445,23 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
         let window = self.window.root();
 
         let namespace = match maybe_ns {
-            Some(namespace) => Namespace::from_str(namespace.as_slice()),
+            Some(namespace) => Namespace::from_str(namespace.to_string_lossy().as_slice()),
             None => Null
         };
-        HTMLCollection::by_tag_name_ns(&*window, NodeCast::from_ref(self), tag_name, namespace)
+        HTMLCollection::by_tag_name_ns(&*window, NodeCast::from_ref(self),
+                                       tag_name.to_string_lossy(), namespace)
     }
 
     // http://dom.spec.whatwg.org/#dom-document-getelementsbyclassname
     fn GetElementsByClassName(&self, classes: DOMString) -> Temporary<HTMLCollection> {
         let window = self.window.root();
 
-        HTMLCollection::by_class_name(&*window, NodeCast::from_ref(self), classes)
+        HTMLCollection::by_class_name(&*window, NodeCast::from_ref(self), classes.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-nonelementparentnode-getelementbyid
     fn GetElementById(&self, id: DOMString) -> Option<Temporary<Element>> {
+        let id = id.to_string_lossy();
         match self.idmap.deref().borrow().find_equiv(&id) {
             None => None,
             Some(ref elements) => Some(Temporary::new(elements.get(0).clone())),
@@ -424,6 +470,7 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
 
     // http://dom.spec.whatwg.org/#dom-document-createelement
     fn CreateElement(&self, local_name: DOMString) -> Fallible<Temporary<Element>> {
+        let local_name = local_name.to_string_lossy();
         if xml_name_type(local_name.as_slice()) == InvalidXMLName {
             debug!("Not a valid element name");
             return Err(InvalidCharacter);
@@ -436,6 +483,8 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     fn CreateElementNS(&self,
                        namespace: Option<DOMString>,
                        qualified_name: DOMString) -> Fallible<Temporary<Element>> {
+        let namespace = namespace.map(|namespace| namespace.to_string_lossy());
+        let qualified_name = qualified_name.to_string_lossy();
         let ns = Namespace::from_str(null_str_as_empty_ref(&namespace));
         match xml_name_type(qualified_name.as_slice()) {
             InvalidXMLName => {
@@ -498,12 +547,13 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     fn CreateProcessingInstruction(&self, target: DOMString,
                                        data: DOMString) -> Fallible<Temporary<ProcessingInstruction>> {
         // Step 1.
+        let target = target.to_string_lossy();
         if xml_name_type(target.as_slice()) == InvalidXMLName {
             return Err(InvalidCharacter);
         }
 
         // Step 2.
-        if data.as_slice().contains("?>") {
+        if data.to_string_lossy().as_slice().contains("?>") {
             return Err(InvalidCharacter);
         }
 
@@ -545,12 +595,12 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     fn CreateEvent(&self, interface: DOMString) -> Fallible<Temporary<Event>> {
         let window = self.window.root();
 
-        match interface.as_slice().to_ascii_lower().as_slice() {
+        match interface.to_string_lossy().as_slice().to_ascii_lower().as_slice() {
             // FIXME: Implement CustomEvent (http://dom.spec.whatwg.org/#customevent)
             "uievents" | "uievent" => Ok(EventCast::from_temporary(UIEvent::new_uninitialized(&*window))),
             "mouseevents" | "mouseevent" => Ok(EventCast::from_temporary(MouseEvent::new_uninitialized(&*window))),
-            "customevent" => Ok(EventCast::from_temporary(CustomEvent::new_uninitialized(&*window))),
-            "htmlevents" | "events" | "event" => Ok(Event::new_uninitialized(&*window)),
+            "customevent" => Ok(EventCast::from_temporary(CustomEvent::new_uninitialized(&global::Window(&*window)))),
+            "htmlevents" | "events" | "event" => Ok(Event::new_uninitialized(&global::Window(&*window))),
             _ => Err(NotSupported)
         }
     }
@@ -566,14 +616,15 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
                     for child in title_elem.children() {
                         if child.is_text() {
                             let text: &JSRef<Text> = TextCast::to_ref(&child).unwrap();
-                            title.push_str(text.deref().characterdata.data.deref().borrow().as_slice());
+                            let data = text.deref().characterdata.data.deref().borrow();
+                            title.push_str(data.to_string_lossy().as_slice());
                         }
                     }
                 });
         });
         let v: Vec<&str> = title.as_slice().words().collect();
         let title = v.connect(" ");
-        title.as_slice().trim().to_string()
+        DOMString::from_slice(title.as_slice().trim())
     }
 
     // http://www.whatwg.org/specs/web-apps/current-work/#document.title
@@ -687,6 +738,7 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
 
     // http://www.whatwg.org/specs/web-apps/current-work/#dom-document-getelementsbyname
     fn GetElementsByName(&self, name: DOMString) -> Temporary<NodeList> {
+        let name = name.to_string_lossy();
         self.createNodeList(|node| {
             if !node.is_element() {
                 return false;
@@ -817,13 +869,13 @@ impl<'a> DocumentMethods for JSRef<'a, Document> {
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselector
     fn QuerySelector(&self, selectors: DOMString) -> Fallible<Option<Temporary<Element>>> {
         let root: &JSRef<Node> = NodeCast::from_ref(self);
-        root.query_selector(selectors)
+        root.query_selector(selectors.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselectorall
     fn QuerySelectorAll(&self, selectors: DOMString) -> Fallible<Temporary<NodeList>> {
         let root: &JSRef<Node> = NodeCast::from_ref(self);
-        root.query_selector_all(selectors)
+        root.query_selector_all(selectors.to_string_lossy())
     }
 
     fn GetOnclick(&self) -> Option<EventHandlerNonNull> {
@@ -15,9 +15,9 @@ use servo_util::str::DOMString;
 #[deriving(Encodable)]
 pub struct DocumentType {
     pub node: Node,
-    pub name: DOMString,
-    pub public_id: DOMString,
-    pub system_id: DOMString,
+    pub name: String,
+    pub public_id: String,
+    pub system_id: String,
 }
 
 impl DocumentTypeDerived for EventTarget {
@@ -27,9 +27,9 @@ impl DocumentTypeDerived for EventTarget {
 }
 
 impl DocumentType {
-    pub fn new_inherited(name: DOMString,
-                         public_id: Option<DOMString>,
-                         system_id: Option<DOMString>,
+    pub fn new_inherited(name: String,
+                         public_id: Option<String>,
+                         system_id: Option<String>,
                          document: &JSRef<Document>)
             -> DocumentType {
         DocumentType {
@@ -40,9 +40,9 @@ impl DocumentType {
         }
     }
 
-    pub fn new(name: DOMString,
-               public_id: Option<DOMString>,
-               system_id: Option<DOMString>,
+    pub fn new(name: String,
+               public_id: Option<String>,
+               system_id: Option<String>,
                document: &JSRef<Document>)
                -> Temporary<DocumentType> {
         let documenttype = DocumentType::new_inherited(name,
@@ -62,15 +62,15 @@ pub trait DocumentTypeMethods {
 
 impl<'a> DocumentTypeMethods for JSRef<'a, DocumentType> {
     fn Name(&self) -> DOMString {
-        self.name.clone()
+        DOMString::from_slice(self.name.as_slice())
     }
 
     fn PublicId(&self) -> DOMString {
-        self.public_id.clone()
+        DOMString::from_slice(self.public_id.as_slice())
     }
 
     fn SystemId(&self) -> DOMString {
-        self.system_id.clone()
+        DOMString::from_slice(self.system_id.as_slice())
     }
 
     // http://dom.spec.whatwg.org/#dom-childnode-remove
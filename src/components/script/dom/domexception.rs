@@ -6,6 +6,7 @@ use dom::bindings::codegen::Bindings::DOMExceptionBinding;
 use dom::bindings::codegen::Bindings::DOMExceptionBinding::DOMExceptionConstants;
 use dom::bindings::error;
 use dom::bindings::error::Error;
+use dom::bindings::global;
 use dom::bindings::js::{JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::window::Window;
@@ -43,17 +44,25 @@ impl DOMErrorName {
             error::IndexSize => IndexSizeError,
             error::NotFound => NotFoundError,
             error::HierarchyRequest => HierarchyRequestError,
+            error::WrongDocument => WrongDocumentError,
             error::InvalidCharacter => InvalidCharacterError,
+            error::NoModificationAllowed => NoModificationAllowedError,
             error::NotSupported => NotSupportedError,
             error::InvalidState => InvalidStateError,
             error::Syntax => SyntaxError,
+            error::InvalidModification => InvalidModificationError,
             error::NamespaceError => NamespaceError,
             error::InvalidAccess => InvalidAccessError,
             error::Security => SecurityError,
             error::Network => NetworkError,
             error::Abort => AbortError,
+            error::URLMismatch => URLMismatchError,
+            error::QuotaExceeded => QuotaExceededError,
             error::Timeout => TimeoutError,
-            error::FailureUnknown => fail!(),
+            error::InvalidNodeType => InvalidNodeTypeError,
+            error::DataClone => DataCloneError,
+            error::Encoding => EncodingError,
+            error::Type(_) | error::FailureUnknown => fail!(),
         }
     }
 }
@@ -73,7 +82,7 @@ impl DOMException {
     }
 
     pub fn new(window: &JSRef<Window>, code: DOMErrorName) -> Temporary<DOMException> {
-        reflect_dom_object(box DOMException::new_inherited(code), window, DOMExceptionBinding::Wrap)
+        reflect_dom_object(box DOMException::new_inherited(code), &global::Window(window), DOMExceptionBinding::Wrap)
     }
 
     pub fn new_from_error(window: &JSRef<Window>, code: Error) -> Temporary<DOMException> {
@@ -105,33 +114,34 @@ impl<'a> DOMExceptionMethods for JSRef<'a, DOMException> {
 
     // http://dom.spec.whatwg.org/#error-names-0
     fn Name(&self) -> DOMString {
-        self.code.to_str()
+        DOMString::from_string(self.code.to_str())
     }
 
     // http://dom.spec.whatwg.org/#error-names-0
     fn Message(&self) -> DOMString {
-        match self.code {
-            IndexSizeError => "The index is not in the allowed range.".to_string(),
-            HierarchyRequestError => "The operation would yield an incorrect node tree.".to_string(),
-            WrongDocumentError => "The object is in the wrong document.".to_string(),
-            InvalidCharacterError => "The string contains invalid characters.".to_string(),
-            NoModificationAllowedError => "The object can not be modified.".to_string(),
-            NotFoundError => "The object can not be found here.".to_string(),
-            NotSupportedError => "The operation is not supported.".to_string(),
-            InvalidStateError => "The object is in an invalid state.".to_string(),
-            SyntaxError => "The string did not match the expected pattern.".to_string(),
-            InvalidModificationError => "The object can not be modified in this way.".to_string(),
-            NamespaceError => "The operation is not allowed by Namespaces in XML.".to_string(),
-            InvalidAccessError => "The object does not support the operation or argument.".to_string(),
-            SecurityError => "The operation is insecure.".to_string(),
-            NetworkError => "A network error occurred.".to_string(),
-            AbortError => "The operation was aborted.".to_string(),
-            URLMismatchError => "The given URL does not match another URL.".to_string(),
-            QuotaExceededError => "The quota has been exceeded.".to_string(),
-            TimeoutError => "The operation timed out.".to_string(),
-            InvalidNodeTypeError => "The supplied node is incorrect or has an incorrect ancestor for this operation.".to_string(),
-            DataCloneError => "The object can not be cloned.".to_string(),
-            EncodingError => "The encoding operation (either encoded or decoding) failed.".to_string()
-        }
+        let message = match self.code {
+            IndexSizeError => "The index is not in the allowed range.",
+            HierarchyRequestError => "The operation would yield an incorrect node tree.",
+            WrongDocumentError => "The object is in the wrong document.",
+            InvalidCharacterError => "The string contains invalid characters.",
+            NoModificationAllowedError => "The object can not be modified.",
+            NotFoundError => "The object can not be found here.",
+            NotSupportedError => "The operation is not supported.",
+            InvalidStateError => "The object is in an invalid state.",
+            SyntaxError => "The string did not match the expected pattern.",
+            InvalidModificationError => "The object can not be modified in this way.",
+            NamespaceError => "The operation is not allowed by Namespaces in XML.",
+            InvalidAccessError => "The object does not support the operation or argument.",
+            SecurityError => "The operation is insecure.",
+            NetworkError => "A network error occurred.",
+            AbortError => "The operation was aborted.",
+            URLMismatchError => "The given URL does not match another URL.",
+            QuotaExceededError => "The quota has been exceeded.",
+            TimeoutError => "The operation timed out.",
+            InvalidNodeTypeError => "The supplied node is incorrect or has an incorrect ancestor for this operation.",
+            DataCloneError => "The object can not be cloned.",
+            EncodingError => "The encoding operation (either encoded or decoding) failed."
+        };
+        DOMString::from_slice(message)
     }
 }
@@ -4,6 +4,7 @@
 
 use dom::bindings::codegen::Bindings::DOMImplementationBinding;
 use dom::bindings::codegen::InheritTypes::NodeCast;
+use dom::bindings::global;
 use dom::bindings::js::{JS, JSRef, Root, Temporary, OptionalRootable};
 use dom::bindings::utils::{Reflector, Reflectable, reflect_dom_object};
 use dom::bindings::error::{Fallible, InvalidCharacter, NamespaceError};
@@ -34,7 +35,7 @@ impl DOMImplementation {
     }
 
     pub fn new(owner: &JSRef<Window>) -> Temporary<DOMImplementation> {
-        reflect_dom_object(box DOMImplementation::new_inherited(owner), owner,
+        reflect_dom_object(box DOMImplementation::new_inherited(owner), &global::Window(owner),
                            DOMImplementationBinding::Wrap)
     }
 }
@@ -56,6 +57,7 @@ pub trait DOMImplementationMethods {
 impl<'a> DOMImplementationMethods for JSRef<'a, DOMImplementation> {
     // http://dom.spec.whatwg.org/#dom-domimplementation-createdocumenttype
     fn CreateDocumentType(&self, qname: DOMString, pubid: DOMString, sysid: DOMString) -> Fallible<Temporary<DocumentType>> {
+        let qname = qname.to_string_lossy();
         match xml_name_type(qname.as_slice()) {
             // Step 1.
             InvalidXMLName => Err(InvalidCharacter),
@@ -65,7 +67,8 @@ impl<'a> DOMImplementationMethods for JSRef<'a, DOMImplementation> {
             QName => {
                 let owner = self.owner.root();
                 let document = owner.deref().Document().root();
-                Ok(DocumentType::new(qname, Some(pubid), Some(sysid), &*document))
+                Ok(DocumentType::new(qname, Some(pubid.to_string_lossy()),
+                                     Some(sysid.to_string_lossy()), &*document))
             }
         }
     }
@@ -4,6 +4,7 @@
 
 use dom::attr::{Attr, TokenListAttrValue};
 use dom::bindings::codegen::Bindings::DOMTokenListBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JS, JSRef, Temporary, OptionalRootable};
 use dom::bindings::utils::{Reflector, Reflectable, reflect_dom_object};
 use dom::element::{Element, AttributeHandlers};
@@ -33,7 +34,7 @@ impl DOMTokenList {
                local_name: &'static str) -> Temporary<DOMTokenList> {
         let window = window_from_node(element).root();
         reflect_dom_object(box DOMTokenList::new_inherited(element, local_name),
-                           &*window, DOMTokenListBinding::Wrap)
+                           &global::Window(&*window), DOMTokenListBinding::Wrap)
     }
 }
 
@@ -68,7 +69,7 @@ impl<'a> DOMTokenListMethods for JSRef<'a, DOMTokenList> {
         match attribute {
             Some(attribute) => {
                 match *attribute.deref().value() {
-                    TokenListAttrValue(_, ref indexes) => indexes.len() as u32,
+                    TokenListAttrValue(_, ref tokens) => tokens.len() as u32,
                     _ => fail!("Expected a TokenListAttrValue"),
                 }
             }
@@ -81,9 +82,9 @@ impl<'a> DOMTokenListMethods for JSRef<'a, DOMTokenList> {
         let attribute = self.attribute().root();
         attribute.and_then(|attribute| {
             match *attribute.deref().value() {
-                TokenListAttrValue(ref value, ref indexes) => {
-                    indexes.as_slice().get(index as uint).map(|&(start, end)| {
-                        value.as_slice().slice(start, end).to_string()
+                TokenListAttrValue(_, ref tokens) => {
+                    tokens.as_slice().get(index as uint).map(|token| {
+                        DOMString::from_slice(token.as_slice())
                     })
                 },
                 _ => fail!("Expected a TokenListAttrValue"),
@@ -5,7 +5,7 @@
 //! Element nodes.
 
 use dom::attr::{Attr, ReplacedAttr, FirstSetAttr, AttrMethods, AttrHelpersForLayout};
-use dom::attr::{AttrValue, StringAttrValue, UIntAttrValue};
+use dom::attr::{AttrValue, AtomAttrValue, StringAttrValue, TokenListAttrValue, UIntAttrValue};
 use dom::attrlist::AttrList;
 use dom::bindings::codegen::Bindings::ElementBinding;
 use dom::bindings::codegen::InheritTypes::{ElementDerived, NodeCast};
@@ -29,6 +29,7 @@ use dom::virtualmethods::{VirtualMethods, vtable_for};
 use layout_interface::ContentChangedDocumentDamage;
 use layout_interface::MatchSelectorsDocumentDamage;
 use style;
+use servo_util::atom::Atom;
 use servo_util::namespace;
 use servo_util::namespace::{Namespace, Null};
 use servo_util::str::{DOMString, null_str_as_empty_ref, split_html_space_chars};
@@ -40,9 +41,9 @@ use std::mem;
 #[deriving(Encodable)]
 pub struct Element {
     pub node: Node,
-    pub local_name: DOMString,     // TODO: This should be an atom, not a DOMString.
+    pub local_name: Atom,
     pub namespace: Namespace,
-    pub prefix: Option<DOMString>,
+    pub prefix: Option<String>,
     pub attrs: RefCell<Vec<JS<Attr>>>,
     pub style_attribute: Traceable<RefCell<Option<style::PropertyDeclarationBlock>>>,
     pub attr_list: Cell<Option<JS<AttrList>>>,
@@ -142,10 +143,10 @@ pub enum ElementTypeId {
 //
 
 impl Element {
-    pub fn new_inherited(type_id: ElementTypeId, local_name: DOMString, namespace: Namespace, prefix: Option<DOMString>, document: &JSRef<Document>) -> Element {
+    pub fn new_inherited(type_id: ElementTypeId, local_name: String, namespace: Namespace, prefix: Option<String>, document: &JSRef<Document>) -> Element {
         Element {
             node: Node::new_inherited(ElementNodeTypeId(type_id), document),
-            local_name: local_name,
+            local_name: Atom::from_string(local_name),
             namespace: namespace,
             prefix: prefix,
             attrs: RefCell::new(vec!()),
@@ -155,7 +156,7 @@ impl Element {
         }
     }
 
-    pub fn new(local_name: DOMString, namespace: Namespace, prefix: Option<DOMString>, document: &JSRef<Document>) -> Temporary<Element> {
+    pub fn new(local_name: String, namespace: Namespace, prefix: Option<String>, document: &JSRef<Document>) -> Temporary<Element> {
         let element = Element::new_inherited(ElementTypeId, local_name, namespace, prefix, document);
         Node::reflect_node(box element, document, ElementBinding::Wrap)
     }
@@ -163,21 +164,52 @@ impl Element {
 
 pub trait RawLayoutElementHelpers {
     unsafe fn get_attr_val_for_layout(&self, namespace: &Namespace, name: &str) -> Option<&'static str>;
+    unsafe fn get_attr_val_by_atom_for_layout(&self, namespace: &Namespace, name: &Atom)
+                                              -> Option<&'static str>;
+    unsafe fn get_id_for_layout(&self) -> Option<Atom>;
+    unsafe fn get_classes_for_layout(&self) -> Option<&'static [Atom]>;
+}
+
+/// Finds the first of `element`'s attributes that passes `test`, without borrowing the list.
+#[inline]
+unsafe fn find_attr_for_layout(element: &Element, test: |&Attr| -> bool) -> Option<&'static Attr> {
+    // cast to point to T in RefCell<T> directly
+    let attrs: *Vec<JS<Attr>> = mem::transmute(&element.attrs);
+    (*attrs).iter().map(|attr| {
+        let attr: &'static Attr = mem::transmute(attr.unsafe_get());
+        attr
+    }).find(|attr| test(*attr))
 }
 
 impl RawLayoutElementHelpers for Element {
     #[inline]
     unsafe fn get_attr_val_for_layout(&self, namespace: &Namespace, name: &str)
                                       -> Option<&'static str> {
-        // cast to point to T in RefCell<T> directly
-        let attrs: *Vec<JS<Attr>> = mem::transmute(&self.attrs);
-        (*attrs).iter().find(|attr: & &JS<Attr>| {
-            let attr = attr.unsafe_get();
-            name == (*attr).local_name.as_slice() && (*attr).namespace == *namespace
-        }).map(|attr| {
-            let attr = attr.unsafe_get();
-            (*attr).value_ref_forever()
-        })
+        find_attr_for_layout(self, |attr| {
+            name == attr.local_name.as_slice() && attr.namespace == *namespace
+        }).map(|attr| attr.value_ref_forever())
+    }
+
+    #[inline]
+    unsafe fn get_attr_val_by_atom_for_layout(&self, namespace: &Namespace, name: &Atom)
+                                              -> Option<&'static str> {
+        find_attr_for_layout(self, |attr| {
+            *name == attr.local_name && attr.namespace == *namespace
+        }).map(|attr| attr.value_ref_forever())
+    }
+
+    #[inline]
+    unsafe fn get_id_for_layout(&self) -> Option<Atom> {
+        find_attr_for_layout(self, |attr| {
+            attr.local_name.as_slice() == "id" && attr.namespace == namespace::Null
+        }).and_then(|attr| attr.value_atom_forever())
+    }
+
+    #[inline]
+    unsafe fn get_classes_for_layout(&self) -> Option<&'static [Atom]> {
+        find_attr_for_layout(self, |attr| {
+            attr.local_name.as_slice() == "class" && attr.namespace == namespace::Null
+        }).and_then(|attr| attr.value_tokens_forever())
     }
 }
 
@@ -198,7 +230,7 @@ impl LayoutElementHelpers for JS<Element> {
 
 pub trait ElementHelpers {
     fn html_element_in_html_document(&self) -> bool;
-    fn get_local_name<'a>(&'a self) -> &'a str;
+    fn get_local_name<'a>(&'a self) -> &'a Atom;
     fn get_namespace<'a>(&'a self) -> &'a Namespace;
 }
 
@@ -209,8 +241,8 @@ impl<'a> ElementHelpers for JSRef<'a, Element> {
         is_html && node.owner_doc().root().is_html_document
     }
 
-    fn get_local_name<'a>(&'a self) -> &'a str {
-        self.deref().local_name.as_slice()
+    fn get_local_name<'a>(&'a self) -> &'a Atom {
+        &self.deref().local_name
     }
 
     fn get_namespace<'a>(&'a self) -> &'a Namespace {
@@ -220,26 +252,27 @@ impl<'a> ElementHelpers for JSRef<'a, Element> {
 
 pub trait AttributeHandlers {
     fn get_attribute(&self, namespace: Namespace, name: &str) -> Option<Temporary<Attr>>;
-    fn set_attribute_from_parser(&self, local_name: DOMString,
-                                 value: DOMString, namespace: Namespace,
-                                 prefix: Option<DOMString>);
+    fn set_attribute_from_parser(&self, local_name: String,
+                                 value: String, namespace: Namespace,
+                                 prefix: Option<String>);
     fn set_attribute(&self, name: &str, value: AttrValue);
-    fn do_set_attribute(&self, local_name: DOMString, value: AttrValue,
-                        name: DOMString, namespace: Namespace,
-                        prefix: Option<DOMString>, cb: |&JSRef<Attr>| -> bool);
+    fn do_set_attribute(&self, local_name: String, value: AttrValue,
+                        name: String, namespace: Namespace,
+                        prefix: Option<String>, cb: |&JSRef<Attr>| -> bool);
     fn parse_attribute(&self, namespace: &Namespace, local_name: &str,
-                       value: DOMString) -> AttrValue;
+                       value: String) -> AttrValue;
 
-    fn remove_attribute(&self, namespace: Namespace, name: DOMString) -> ErrorResult;
-    fn notify_attribute_changed(&self, local_name: DOMString);
+    fn remove_attribute(&self, namespace: Namespace, name: String) -> ErrorResult;
+    fn notify_attribute_changed(&self, local_name: String);
     fn has_class(&self, name: &str) -> bool;
 
     // http://www.whatwg.org/html/#reflecting-content-attributes-in-idl-attributes
-    fn get_url_attribute(&self, name: &str) -> DOMString;
-    fn set_url_attribute(&self, name: &str, value: DOMString);
-    fn get_string_attribute(&self, name: &str) -> DOMString;
-    fn set_string_attribute(&self, name: &str, value: DOMString);
-    fn set_tokenlist_attribute(&self, name: &str, value: DOMString);
+    fn get_url_attribute(&self, name: &str) -> String;
+    fn set_url_attribute(&self, name: &str, value: String);
+    fn get_string_attribute(&self, name: &str) -> String;
+    fn set_string_attribute(&self, name: &str, value: String);
+    fn set_tokenlist_attribute(&self, name: &str, value: String);
+    fn set_atomic_attribute(&self, name: &str, value: String);
     fn get_uint_attribute(&self, name: &str) -> u32;
     fn set_uint_attribute(&self, name: &str, value: u32);
 }
@@ -251,7 +284,7 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
 
         element.attrs.borrow().iter().map(|attr| attr.root()).find(|attr| {
             let same_name = if is_html_element {
-                name.to_ascii_lower() == attr.local_name
+                name.to_ascii_lower().as_slice() == attr.local_name.as_slice()
             } else {
                 name == attr.local_name.as_slice()
             };
@@ -260,9 +293,9 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
         }).map(|x| Temporary::from_rooted(&*x))
     }
 
-    fn set_attribute_from_parser(&self, local_name: DOMString,
-                                 value: DOMString, namespace: Namespace,
-                                 prefix: Option<DOMString>) {
+    fn set_attribute_from_parser(&self, local_name: String,
+                                 value: String, namespace: Namespace,
+                                 prefix: Option<String>) {
         let name = match prefix {
             None => local_name.clone(),
             Some(ref prefix) => format!("{:s}:{:s}", *prefix, local_name),
@@ -283,9 +316,9 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
             |attr| attr.deref().local_name.as_slice() == name);
     }
 
-    fn do_set_attribute(&self, local_name: DOMString, value: AttrValue,
-                        name: DOMString, namespace: Namespace,
-                        prefix: Option<DOMString>, cb: |&JSRef<Attr>| -> bool) {
+    fn do_set_attribute(&self, local_name: String, value: AttrValue,
+                        name: String, namespace: Namespace,
+                        prefix: Option<String>, cb: |&JSRef<Attr>| -> bool) {
         let idx = self.deref().attrs.borrow().iter()
                                     .map(|attr| attr.root())
                                     .position(|attr| cb(&*attr));
@@ -293,7 +326,7 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
             Some(idx) => (idx, ReplacedAttr),
             None => {
                 let window = window_from_node(self).root();
-                let attr = Attr::new(&*window, local_name.clone(), value.clone(),
+                let attr = Attr::new(&*window, Atom::from_string(local_name), value.clone(),
                                      name, namespace.clone(), prefix, self);
                 self.deref().attrs.borrow_mut().push_unrooted(&attr);
                 (self.deref().attrs.borrow().len() - 1, FirstSetAttr)
@@ -304,7 +337,7 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
     }
 
     fn parse_attribute(&self, namespace: &Namespace, local_name: &str,
-                       value: DOMString) -> AttrValue {
+                       value: String) -> AttrValue {
         if *namespace == namespace::Null {
             vtable_for(NodeCast::from_ref(self))
                 .parse_plain_attribute(local_name, value)
@@ -313,11 +346,11 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
         }
     }
 
-    fn remove_attribute(&self, namespace: Namespace, name: DOMString) -> ErrorResult {
+    fn remove_attribute(&self, namespace: Namespace, name: String) -> ErrorResult {
         let (_, local_name) = get_attribute_parts(name.clone());
 
         let idx = self.deref().attrs.borrow().iter().map(|attr| attr.root()).position(|attr| {
-            attr.local_name == local_name
+            attr.local_name.as_slice() == local_name.as_slice()
         });
 
         match idx {
@@ -329,7 +362,8 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
                 }
 
                 if namespace == namespace::Null {
-                    let removed_raw_value = self.deref().attrs.borrow().get(idx).root().Value();
+                    let removed_attr = self.deref().attrs.borrow().get(idx).root();
+                    let removed_raw_value = removed_attr.deref().value().as_slice().to_string();
                     vtable_for(NodeCast::from_ref(self))
                         .before_remove_attr(local_name.clone(), removed_raw_value);
                 }
@@ -341,7 +375,7 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
         Ok(())
     }
 
-    fn notify_attribute_changed(&self, local_name: DOMString) {
+    fn notify_attribute_changed(&self, local_name: String) {
         let node: &JSRef<Node> = NodeCast::from_ref(self);
         if node.is_in_doc() {
             let damage = match local_name.as_slice() {
@@ -359,33 +393,38 @@ impl<'a> AttributeHandlers for JSRef<'a, Element> {
         classes.any(|class| name == class)
     }
 
-    fn get_url_attribute(&self, name: &str) -> DOMString {
+    fn get_url_attribute(&self, name: &str) -> String {
         // XXX Resolve URL.
         self.get_string_attribute(name)
     }
-    fn set_url_attribute(&self, name: &str, value: DOMString) {
+    fn set_url_attribute(&self, name: &str, value: String) {
         self.set_string_attribute(name, value);
     }
 
-    fn get_string_attribute(&self, name: &str) -> DOMString {
+    fn get_string_attribute(&self, name: &str) -> String {
         match self.get_attribute(Null, name) {
             Some(x) => {
                 let x = x.root();
-                x.deref().Value()
+                x.deref().value().as_slice().to_string()
             }
             None => "".to_string()
         }
     }
-    fn set_string_attribute(&self, name: &str, value: DOMString) {
+    fn set_string_attribute(&self, name: &str, value: String) {
         assert!(name == name.to_ascii_lower().as_slice());
         self.set_attribute(name, StringAttrValue(value));
     }
 
-    fn set_tokenlist_attribute(&self, name: &str, value: DOMString) {
+    fn set_tokenlist_attribute(&self, name: &str, value: String) {
         assert!(name == name.to_ascii_lower().as_slice());
         self.set_attribute(name, AttrValue::from_tokenlist(value));
     }
 
+    fn set_atomic_attribute(&self, name: &str, value: String) {
+        assert!(name == name.to_ascii_lower().as_slice());
+        self.set_attribute(name, AttrValue::from_atomic(value));
+    }
+
     fn get_uint_attribute(&self, name: &str) -> u32 {
         assert!(name == name.to_ascii_lower().as_slice());
         let attribute = self.get_attribute(Null, name).root();
@@ -456,21 +495,21 @@ pub trait ElementMethods {
 impl<'a> ElementMethods for JSRef<'a, Element> {
     // http://dom.spec.whatwg.org/#dom-element-namespaceuri
     fn NamespaceURI(&self) -> DOMString {
-        self.namespace.to_str().to_string()
+        DOMString::from_slice(self.namespace.to_str())
     }
 
     fn LocalName(&self) -> DOMString {
-        self.local_name.clone()
+        DOMString::from_slice(self.local_name.as_slice())
     }
 
     // http://dom.spec.whatwg.org/#dom-element-prefix
     fn GetPrefix(&self) -> Option<DOMString> {
-        self.prefix.clone()
+        self.prefix.as_ref().map(|prefix| DOMString::from_slice(prefix.as_slice()))
     }
 
     // http://dom.spec.whatwg.org/#dom-element-tagname
     fn TagName(&self) -> DOMString {
-        match self.prefix {
+        let name = match self.prefix {
             None => {
                 self.local_name.as_slice().to_ascii_upper()
             }
@@ -478,27 +517,28 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
                 let s = format!("{}:{}", prefix_str, self.local_name);
                 s.as_slice().to_ascii_upper()
             }
-        }
+        };
+        DOMString::from_string(name)
     }
 
     // http://dom.spec.whatwg.org/#dom-element-id
     fn Id(&self) -> DOMString {
-        self.get_string_attribute("id")
+        DOMString::from_string(self.get_string_attribute("id"))
     }
 
     // http://dom.spec.whatwg.org/#dom-element-id
     fn SetId(&self, id: DOMString) {
-        self.set_string_attribute("id", id);
+        self.set_atomic_attribute("id", id.to_string_lossy());
     }
 
     // http://dom.spec.whatwg.org/#dom-element-classname
     fn ClassName(&self) -> DOMString {
-        self.get_string_attribute("class")
+        DOMString::from_string(self.get_string_attribute("class"))
     }
 
     // http://dom.spec.whatwg.org/#dom-element-classname
     fn SetClassName(&self, class: DOMString) {
-        self.set_tokenlist_attribute("class", class);
+        self.set_tokenlist_attribute("class", class.to_string_lossy());
     }
 
     // http://dom.spec.whatwg.org/#dom-element-classlist
@@ -532,6 +572,7 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
 
     // http://dom.spec.whatwg.org/#dom-element-getattribute
     fn GetAttribute(&self, name: DOMString) -> Option<DOMString> {
+        let name = name.to_string_lossy();
         let name = if self.html_element_in_html_document() {
             name.as_slice().to_ascii_lower()
         } else {
@@ -545,8 +586,9 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
     fn GetAttributeNS(&self,
                       namespace: Option<DOMString>,
                       local_name: DOMString) -> Option<DOMString> {
+        let namespace = namespace.map(|namespace| namespace.to_string_lossy());
         let namespace = Namespace::from_str(null_str_as_empty_ref(&namespace));
-        self.get_attribute(namespace, local_name.as_slice()).root()
+        self.get_attribute(namespace, local_name.to_string_lossy().as_slice()).root()
                      .map(|attr| attr.deref().Value())
     }
 
@@ -559,6 +601,10 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
             node.wait_until_safe_to_modify_dom();
         }
 
+        // Lossy, since attribute values are UTF-8; see `AttrValue`.
+        let name = name.to_string_lossy();
+        let value = value.to_string_lossy();
+
         // Step 1.
         match xml_name_type(name.as_slice()) {
             InvalidXMLName => return Err(InvalidCharacter),
@@ -590,6 +636,11 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
             node.wait_until_safe_to_modify_dom();
         }
 
+        // Lossy, since attribute values are UTF-8; see `AttrValue`.
+        let namespace_url = namespace_url.map(|namespace| namespace.to_string_lossy());
+        let name = name.to_string_lossy();
+        let value = value.to_string_lossy();
+
         // Step 1.
         let namespace = Namespace::from_str(null_str_as_empty_ref(&namespace_url));
 
@@ -637,7 +688,7 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
         // Step 9.
         let value = self.parse_attribute(&namespace, local_name.as_slice(), value);
         self.do_set_attribute(local_name.clone(), value, name, namespace.clone(), prefix, |attr| {
-            attr.deref().local_name == local_name &&
+            attr.deref().local_name.as_slice() == local_name.as_slice() &&
             attr.deref().namespace == namespace
         });
         Ok(())
@@ -646,6 +697,7 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
     // http://dom.spec.whatwg.org/#dom-element-removeattribute
     fn RemoveAttribute(&self,
                        name: DOMString) -> ErrorResult {
+        let name = name.to_string_lossy();
         let name = if self.html_element_in_html_document() {
             name.as_slice().to_ascii_lower()
         } else {
@@ -658,8 +710,9 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
     fn RemoveAttributeNS(&self,
                          namespace: Option<DOMString>,
                          localname: DOMString) -> ErrorResult {
+        let namespace = namespace.map(|namespace| namespace.to_string_lossy());
         let namespace = Namespace::from_str(null_str_as_empty_ref(&namespace));
-        self.remove_attribute(namespace, localname)
+        self.remove_attribute(namespace, localname.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-element-hasattribute
@@ -677,22 +730,25 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
 
     fn GetElementsByTagName(&self, localname: DOMString) -> Temporary<HTMLCollection> {
         let window = window_from_node(self).root();
-        HTMLCollection::by_tag_name(&*window, NodeCast::from_ref(self), localname)
+        HTMLCollection::by_tag_name(&*window, NodeCast::from_ref(self),
+                                    localname.to_string_lossy())
     }
 
     fn GetElementsByTagNameNS(&self, maybe_ns: Option<DOMString>,
                               localname: DOMString) -> Temporary<HTMLCollection> {
         let namespace = match maybe_ns {
-            Some(namespace) => Namespace::from_str(namespace.as_slice()),
+            Some(namespace) => Namespace::from_str(namespace.to_string_lossy().as_slice()),
             None => Null
         };
         let window = window_from_node(self).root();
-        HTMLCollection::by_tag_name_ns(&*window, NodeCast::from_ref(self), localname, namespace)
+        HTMLCollection::by_tag_name_ns(&*window, NodeCast::from_ref(self),
+                                       localname.to_string_lossy(), namespace)
     }
 
     fn GetElementsByClassName(&self, classes: DOMString) -> Temporary<HTMLCollection> {
         let window = window_from_node(self).root();
-        HTMLCollection::by_class_name(&*window, NodeCast::from_ref(self), classes)
+        HTMLCollection::by_class_name(&*window, NodeCast::from_ref(self),
+                                      classes.to_string_lossy())
     }
 
     // http://dev.w3.org/csswg/cssom-view/#dom-element-getclientrects
@@ -727,11 +783,13 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
 
     fn GetInnerHTML(&self) -> Fallible<DOMString> {
         //XXX TODO: XML case
-        Ok(serialize(&mut NodeIterator::new(NodeCast::from_ref(self), false, false)))
+        Ok(DOMString::from_string(serialize(&mut NodeIterator::new(NodeCast::from_ref(self),
+                                                                   false, false))))
     }
 
     fn GetOuterHTML(&self) -> Fallible<DOMString> {
-        Ok(serialize(&mut NodeIterator::new(NodeCast::from_ref(self), true, false)))
+        Ok(DOMString::from_string(serialize(&mut NodeIterator::new(NodeCast::from_ref(self),
+                                                                   true, false))))
     }
 
     // http://dom.spec.whatwg.org/#dom-parentnode-children
@@ -743,13 +801,13 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselector
     fn QuerySelector(&self, selectors: DOMString) -> Fallible<Option<Temporary<Element>>> {
         let root: &JSRef<Node> = NodeCast::from_ref(self);
-        root.query_selector(selectors)
+        root.query_selector(selectors.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselectorall
     fn QuerySelectorAll(&self, selectors: DOMString) -> Fallible<Temporary<NodeList>> {
         let root: &JSRef<Node> = NodeCast::from_ref(self);
-        root.query_selector_all(selectors)
+        root.query_selector_all(selectors.to_string_lossy())
     }
 
     // http://dom.spec.whatwg.org/#dom-childnode-remove
@@ -759,7 +817,7 @@ impl<'a> ElementMethods for JSRef<'a, Element> {
     }
 }
 
-pub fn get_attribute_parts(name: DOMString) -> (Option<String>, String) {
+pub fn get_attribute_parts(name: String) -> (Option<String>, String) {
     //FIXME: Throw for XML-invalid names
     //FIXME: Throw for XMLNS-invalid names
     let (prefix, local_name) = if name.as_slice().contains(":")  {
@@ -778,7 +836,7 @@ impl<'a> VirtualMethods for JSRef<'a, Element> {
         Some(node as &VirtualMethods+)
     }
 
-    fn after_set_attr(&self, name: DOMString, value: DOMString) {
+    fn after_set_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.after_set_attr(name.clone(), value.clone()),
             _ => (),
@@ -804,7 +862,7 @@ impl<'a> VirtualMethods for JSRef<'a, Element> {
         self.notify_attribute_changed(name);
     }
 
-    fn before_remove_attr(&self, name: DOMString, value: DOMString) {
+    fn before_remove_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.before_remove_attr(name.clone(), value.clone()),
             _ => (),
@@ -827,8 +885,9 @@ impl<'a> VirtualMethods for JSRef<'a, Element> {
         self.notify_attribute_changed(name);
     }
 
-    fn parse_plain_attribute(&self, name: &str, value: DOMString) -> AttrValue {
+    fn parse_plain_attribute(&self, name: &str, value: String) -> AttrValue {
         match name {
+            "id" => AttrValue::from_atomic(value),
             "class" => AttrValue::from_tokenlist(value),
             _ => self.super_type().unwrap().parse_plain_attribute(name, value),
         }
@@ -843,7 +902,8 @@ impl<'a> VirtualMethods for JSRef<'a, Element> {
         match self.get_attribute(Null, "id").root() {
             Some(attr) => {
                 let doc = document_from_node(self).root();
-                doc.deref().register_named_element(self, attr.deref().Value());
+                let value = attr.deref().value().as_slice().to_string();
+                doc.deref().register_named_element(self, value);
             }
             _ => ()
         }
@@ -858,7 +918,8 @@ impl<'a> VirtualMethods for JSRef<'a, Element> {
         match self.get_attribute(Null, "id").root() {
             Some(attr) => {
                 let doc = document_from_node(self).root();
-                doc.deref().unregister_named_element(self, attr.deref().Value());
+                let value = attr.deref().value().as_slice().to_string();
+                doc.deref().unregister_named_element(self, value);
             }
             _ => ()
         }
@@ -883,9 +944,37 @@ impl<'a> style::TElement for JSRef<'a, Element> {
             _ => None,
          }
     }
-    fn get_local_name<'a>(&'a self) -> &'a str {
+    fn get_local_name<'a>(&'a self) -> &'a Atom {
         (self as &ElementHelpers).get_local_name()
     }
+    fn get_id(&self) -> Option<Atom> {
+        self.get_attribute(namespace::Null, "id").root().and_then(|attr| {
+            match *attr.deref().value() {
+                AtomAttrValue(ref id) => Some(id.clone()),
+                _ => None,
+            }
+        })
+    }
+    fn each_class(&self, callback: |&Atom|) {
+        match self.get_attribute(namespace::Null, "class").root() {
+            Some(attr) => {
+                match *attr.deref().value() {
+                    TokenListAttrValue(_, ref classes) => {
+                        for class in classes.iter() {
+                            callback(class)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            None => {}
+        }
+    }
+    fn has_class(&self, name: &Atom) -> bool {
+        let mut found = false;
+        self.each_class(|class| found = found || *class == *name);
+        found
+    }
     fn get_namespace<'a>(&'a self) -> &'a Namespace {
         (self as &ElementHelpers).get_namespace()
     }
@@ -0,0 +1,130 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::ErrorEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, EventTargetCast, ErrorEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::{MutHeapJSVal, Traceable};
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, ErrorEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::window::Window;
+use js::jsapi::JSContext;
+use js::jsval::JSVal;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct ErrorEvent {
+    event: Event,
+    message: Traceable<RefCell<DOMString>>,
+    filename: Traceable<RefCell<DOMString>>,
+    lineno: Traceable<Cell<u32>>,
+    colno: Traceable<Cell<u32>>,
+    error: MutHeapJSVal,
+}
+
+impl ErrorEventDerived for Event {
+    fn is_errorevent(&self) -> bool {
+        self.type_id == ErrorEventTypeId
+    }
+}
+
+impl ErrorEvent {
+    pub fn new_inherited() -> ErrorEvent {
+        ErrorEvent {
+            event: Event::new_inherited(ErrorEventTypeId),
+            message: Traceable::new(RefCell::new(DOMString::new())),
+            filename: Traceable::new(RefCell::new(DOMString::new())),
+            lineno: Traceable::new(Cell::new(0)),
+            colno: Traceable::new(Cell::new(0)),
+            error: MutHeapJSVal::new(),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<ErrorEvent> {
+        reflect_dom_object(box ErrorEvent::new_inherited(),
+                           &global::Window(window),
+                           ErrorEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               message: DOMString, filename: DOMString,
+               lineno: u32, colno: u32, error: JSVal) -> Temporary<ErrorEvent> {
+        let ev = ErrorEvent::new_uninitialized(window).root();
+        *ev.deref().message.deref().borrow_mut() = message;
+        *ev.deref().filename.deref().borrow_mut() = filename;
+        ev.deref().lineno.deref().set(lineno);
+        ev.deref().colno.deref().set(colno);
+        ev.deref().error.set(window.get_cx(), error);
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &ErrorEventBinding::ErrorEventInit)
+                       -> Fallible<Temporary<ErrorEvent>> {
+        Ok(ErrorEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                           init.message.clone(), init.filename.clone(),
+                           init.lineno, init.colno, init.error))
+    }
+
+    /// Reports an uncaught script error by firing a trusted, cancelable "error" event at
+    /// `window`. Returns false if a handler canceled the event, in which case the caller
+    /// should not report the error itself.
+    pub fn report(window: &JSRef<Window>, message: String, filename: String,
+                  lineno: u32, colno: u32, error: JSVal) -> bool {
+        let errorevent = ErrorEvent::new(window, DOMString::from_slice("error"), false, true,
+                                         DOMString::from_string(message),
+                                         DOMString::from_string(filename),
+                                         lineno, colno, error).root();
+        let event: &JSRef<Event> = EventCast::from_ref(&*errorevent);
+        event.deref().trusted.deref().set(true);
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(window);
+        target.dispatch_event_with_target(None, event).unwrap_or(true)
+    }
+}
+
+pub trait ErrorEventMethods {
+    fn Message(&self) -> DOMString;
+    fn Filename(&self) -> DOMString;
+    fn Lineno(&self) -> u32;
+    fn Colno(&self) -> u32;
+    fn Error(&self, _cx: *mut JSContext) -> JSVal;
+}
+
+impl<'a> ErrorEventMethods for JSRef<'a, ErrorEvent> {
+    fn Message(&self) -> DOMString {
+        self.message.deref().borrow().clone()
+    }
+
+    fn Filename(&self) -> DOMString {
+        self.filename.deref().borrow().clone()
+    }
+
+    fn Lineno(&self) -> u32 {
+        self.lineno.deref().get()
+    }
+
+    fn Colno(&self) -> u32 {
+        self.colno.deref().get()
+    }
+
+    fn Error(&self, _cx: *mut JSContext) -> JSVal {
+        self.error.get()
+    }
+}
+
+impl Reflectable for ErrorEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -5,26 +5,55 @@
 use dom::bindings::codegen::Bindings::EventBinding;
 use dom::bindings::codegen::Bindings::EventBinding::EventConstants;
 use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::eventtarget::EventTarget;
-use dom::window::Window;
+use servo_msg::constellation_msg::{CompositionInput, KeyInput, MouseInput, WheelInput};
 use servo_msg::constellation_msg::WindowSizeData;
 use servo_util::str::DOMString;
 use std::cell::{Cell, RefCell};
 
-use geom::point::Point2D;
-
 use time;
 
+/// The bubbles and cancelable flags the specifications give events of each type that the
+/// user agent fires as plain `Event`s. See `simple_event_flags`.
+static SIMPLE_EVENT_FLAGS: &'static [(&'static str, bool, bool)] = &[
+    // (type, bubbles, cancelable)
+    ("DOMContentLoaded", true, false),
+    ("abort", false, false),
+    ("change", true, false),
+    ("error", false, false),
+    ("input", true, false),
+    ("invalid", false, true),
+    ("load", false, false),
+    ("readystatechange", false, false),
+    ("reset", true, true),
+    ("resize", false, false),
+    ("select", true, false),
+    ("submit", true, true),
+    ("unload", false, false),
+];
+
+/// Looks up the (bubbles, cancelable) flags for a simple event of type `type_`, if it is one
+/// we know about.
+pub fn simple_event_flags(type_: &str) -> Option<(bool, bool)> {
+    SIMPLE_EVENT_FLAGS.iter()
+                      .find(|&&(name, _, _)| name == type_)
+                      .map(|&(_, bubbles, cancelable)| (bubbles, cancelable))
+}
+
 pub enum Event_ {
     ResizeEvent(WindowSizeData),
     ReflowEvent,
-    ClickEvent(uint, Point2D<f32>),
-    MouseDownEvent(uint, Point2D<f32>),
-    MouseUpEvent(uint, Point2D<f32>),
-    MouseMoveEvent(Point2D<f32>)
+    ClickEvent(MouseInput),
+    MouseDownEvent(MouseInput),
+    MouseUpEvent(MouseInput),
+    MouseMoveEvent(MouseInput),
+    MouseWheelEvent(WheelInput),
+    KeyEvent(KeyInput),
+    CompositionInputEvent(CompositionInput),
 }
 
 #[deriving(Encodable)]
@@ -37,12 +66,24 @@ pub enum EventPhase {
 
 #[deriving(PartialEq, Encodable)]
 pub enum EventTypeId {
+    AnimationEventTypeId,
+    BeforeUnloadEventTypeId,
+    CloseEventTypeId,
+    CompositionEventTypeId,
     CustomEventTypeId,
+    ErrorEventTypeId,
+    FocusEventTypeId,
     HTMLEventTypeId,
-    KeyEventTypeId,
+    HashChangeEventTypeId,
+    KeyboardEventTypeId,
+    MessageEventTypeId,
     MouseEventTypeId,
+    PopStateEventTypeId,
     ProgressEventTypeId,
-    UIEventTypeId
+    StorageEventTypeId,
+    TransitionEventTypeId,
+    UIEventTypeId,
+    WheelEventTypeId
 }
 
 #[deriving(Encodable)]
@@ -61,18 +102,20 @@ pub struct Event {
     pub trusted: Traceable<Cell<bool>>,
     pub dispatching: Traceable<Cell<bool>>,
     pub initialized: Traceable<Cell<bool>>,
+    /// The event's creation time, in milliseconds since the Unix epoch.
     pub timestamp: u64,
 }
 
 impl Event {
     pub fn new_inherited(type_id: EventTypeId) -> Event {
+        let now = time::get_time();
         Event {
             type_id: type_id,
             reflector_: Reflector::new(),
             current_target: Cell::new(None),
             target: Cell::new(None),
             phase: Traceable::new(Cell::new(PhaseNone)),
-            type_: Traceable::new(RefCell::new("".to_string())),
+            type_: Traceable::new(RefCell::new(DOMString::new())),
             canceled: Traceable::new(Cell::new(false)),
             cancelable: Traceable::new(Cell::new(true)),
             bubbles: Traceable::new(Cell::new(false)),
@@ -81,30 +124,35 @@ impl Event {
             stop_propagation: Traceable::new(Cell::new(false)),
             stop_immediate: Traceable::new(Cell::new(false)),
             initialized: Traceable::new(Cell::new(false)),
-            timestamp: time::get_time().sec as u64,
+            timestamp: now.sec as u64 * 1000 + now.nsec as u64 / 1000000,
         }
     }
 
-    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<Event> {
+    pub fn new_uninitialized(global: &GlobalRef) -> Temporary<Event> {
         reflect_dom_object(box Event::new_inherited(HTMLEventTypeId),
-                           window,
+                           global,
                            EventBinding::Wrap)
     }
 
-    pub fn new(window: &JSRef<Window>,
+    pub fn new(global: &GlobalRef,
                type_: DOMString,
                can_bubble: bool,
                cancelable: bool) -> Temporary<Event> {
-        let event = Event::new_uninitialized(window).root();
+        let event = Event::new_uninitialized(global).root();
         event.deref().InitEvent(type_, can_bubble, cancelable);
         Temporary::from_rooted(&*event)
     }
 
-    pub fn Constructor(global: &JSRef<Window>,
+    pub fn Constructor(global: &GlobalRef,
                        type_: DOMString,
                        init: &EventBinding::EventInit) -> Fallible<Temporary<Event>> {
         Ok(Event::new(global, type_, init.bubbles, init.cancelable))
     }
+
+    /// Returns true if this event's type is `type_`, without copying the type.
+    pub fn type_is(&self, type_: &str) -> bool {
+        self.type_.deref().borrow().eq_slice(type_)
+    }
 }
 
 pub trait EventMethods {
@@ -121,6 +169,11 @@ pub trait EventMethods {
     fn TimeStamp(&self) -> u64;
     fn InitEvent(&self, type_: DOMString, bubbles: bool, cancelable: bool);
     fn IsTrusted(&self) -> bool;
+    fn GetSrcElement(&self) -> Option<Temporary<EventTarget>>;
+    fn CancelBubble(&self) -> bool;
+    fn SetCancelBubble(&self, value: bool);
+    fn ReturnValue(&self) -> bool;
+    fn SetReturnValue(&self, value: bool);
 }
 
 impl<'a> EventMethods for JSRef<'a, Event> {
@@ -192,6 +245,32 @@ impl<'a> EventMethods for JSRef<'a, Event> {
     fn IsTrusted(&self) -> bool {
         self.trusted.deref().get()
     }
+
+    fn GetSrcElement(&self) -> Option<Temporary<EventTarget>> {
+        self.GetTarget()
+    }
+
+    fn CancelBubble(&self) -> bool {
+        self.stop_propagation.deref().get()
+    }
+
+    // Setting cancelBubble to false never clears an earlier stopPropagation().
+    fn SetCancelBubble(&self, value: bool) {
+        if value {
+            self.StopPropagation();
+        }
+    }
+
+    fn ReturnValue(&self) -> bool {
+        !self.DefaultPrevented()
+    }
+
+    // Setting returnValue to true never clears an earlier preventDefault().
+    fn SetReturnValue(&self, value: bool) {
+        if !value {
+            self.PreventDefault();
+        }
+    }
 }
 
 impl Reflectable for Event {
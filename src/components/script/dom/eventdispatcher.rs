@@ -2,13 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use dom::bindings::callback::ReportExceptions;
-use dom::bindings::codegen::InheritTypes::{EventTargetCast, NodeCast, NodeDerived};
+use dom::bindings::callback::{CallbackContainer, ReportExceptions};
+use dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNonNull;
+use dom::bindings::codegen::Bindings::EventListenerBinding::EventListener;
+use dom::bindings::codegen::InheritTypes::{EventTargetCast, ErrorEventCast, NodeCast, NodeDerived};
+use dom::bindings::codegen::InheritTypes::WindowCast;
+use dom::bindings::codegen::UnionTypes::EventOrString::eString;
 use dom::bindings::js::{JS, JSRef, OptionalSettable, OptionalRootable, Root};
-use dom::eventtarget::{Capturing, Bubbling, EventTarget};
+use dom::errorevent::{ErrorEvent, ErrorEventMethods};
+use dom::eventtarget::{Capturing, Bubbling, EventTarget, EventTargetHelpers};
 use dom::event::{Event, PhaseAtTarget, PhaseNone, PhaseBubbling, PhaseCapturing, EventMethods};
 use dom::node::{Node, NodeHelpers};
 use dom::virtualmethods::vtable_for;
+use dom::window::Window;
+use servo_util::str::DOMString;
 
 // See http://dom.spec.whatwg.org/#concept-event-dispatch for the full dispatch algorithm
 pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
@@ -37,14 +44,20 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
 
     event.deref().phase.deref().set(PhaseCapturing);
 
-    //FIXME: The "callback this value" should be currentTarget
+    // Each node's listeners run with currentTarget (and the callback this value) set to that
+    // node. The chain is rooted for the whole dispatch, so the JS<EventTarget> stored in
+    // current_target stays alive while listeners run; it is cleared again once we're done.
 
     /* capturing */
     for cur_target in chain.as_slice().iter().rev() {
-        let stopped = match cur_target.get_listeners_for(type_.as_slice(), Capturing) {
+        let stopped = match cur_target.get_listeners_for(&type_, Capturing) {
             Some(listeners) => {
                 event.current_target.assign(Some(cur_target.deref().clone()));
-                for listener in listeners.iter() {
+                for entry in listeners.iter() {
+                    if entry.is_removed() {
+                        continue;
+                    }
+                    let listener = entry.listener.get_listener();
                     // Explicitly drop any exception on the floor.
                     let _ = listener.HandleEvent_(&**cur_target, event, ReportExceptions);
 
@@ -68,11 +81,28 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
         event.phase.deref().set(PhaseAtTarget);
         event.current_target.assign(Some(target.clone()));
 
-        let opt_listeners = target.deref().get_listeners(type_.as_slice());
+        // Window's onerror handler is special: it receives the error's details instead
+        // of the event, and returning true from it cancels the event.
+        let errorevent: Option<&JSRef<ErrorEvent>> = ErrorEventCast::to_ref(event);
+        let window: Option<&JSRef<Window>> = WindowCast::to_ref(target);
+        let onerror = match (errorevent, window) {
+            (Some(_), Some(_)) => target.get_inline_event_listener(DOMString::from_slice("error")),
+            _ => None,
+        };
+
+        let opt_listeners = target.deref().get_listeners(&type_);
         for listeners in opt_listeners.iter() {
-            for listener in listeners.iter() {
-                // Explicitly drop any exception on the floor.
-                let _ = listener.HandleEvent_(target, event, ReportExceptions);
+            for entry in listeners.iter() {
+                if entry.is_removed() {
+                    continue;
+                }
+                let listener = entry.listener.get_listener();
+                if onerror == Some(listener) {
+                    invoke_onerror_handler(target, event, errorevent.unwrap(), &listener);
+                } else {
+                    // Explicitly drop any exception on the floor.
+                    let _ = listener.HandleEvent_(target, event, ReportExceptions);
+                }
 
                 if event.deref().stop_immediate.deref().get() {
                     break;
@@ -86,10 +116,14 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
         event.deref().phase.deref().set(PhaseBubbling);
 
         for cur_target in chain.iter() {
-            let stopped = match cur_target.deref().get_listeners_for(type_.as_slice(), Bubbling) {
+            let stopped = match cur_target.deref().get_listeners_for(&type_, Bubbling) {
                 Some(listeners) => {
                     event.deref().current_target.assign(Some(cur_target.deref().clone()));
-                    for listener in listeners.iter() {
+                    for entry in listeners.iter() {
+                        if entry.is_removed() {
+                            continue;
+                        }
+                        let listener = entry.listener.get_listener();
                         // Explicitly drop any exception on the floor.
                         let _ = listener.HandleEvent_(&**cur_target, event, ReportExceptions);
 
@@ -136,3 +170,22 @@ pub fn dispatch_event<'a, 'b>(target: &JSRef<'a, EventTarget>,
 
     !event.DefaultPrevented()
 }
+
+// See http://www.whatwg.org/html/#the-event-handler-processing-algorithm
+fn invoke_onerror_handler(target: &JSRef<EventTarget>, event: &JSRef<Event>,
+                          errorevent: &JSRef<ErrorEvent>, listener: &EventListener) {
+    let handler: OnErrorEventHandlerNonNull = CallbackContainer::new(listener.parent.callback());
+    let window: &JSRef<Window> = WindowCast::to_ref(target).unwrap();
+    let result = handler.Call_(target,
+                               &eString(errorevent.Message()),
+                               Some(errorevent.Filename()),
+                               Some(errorevent.Lineno()),
+                               Some(errorevent.Colno()),
+                               Some(errorevent.Error(window.deref().get_cx())),
+                               ReportExceptions);
+    // Explicitly drop any exception on the floor.
+    match result {
+        Ok(true) => event.PreventDefault(),
+        _ => (),
+    }
+}
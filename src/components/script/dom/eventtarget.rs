@@ -5,26 +5,28 @@
 use dom::bindings::callback::CallbackContainer;
 use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::codegen::Bindings::EventListenerBinding::EventListener;
-use dom::bindings::error::{Fallible, InvalidState};
+use dom::bindings::error::{Fallible, InvalidState, NotSupported};
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::JSRef;
-use dom::bindings::trace::Traceable;
+use dom::bindings::trace::{Traceable, Untraceable};
 use dom::bindings::utils::{Reflectable, Reflector};
-use dom::event::Event;
+use dom::event::{Event, simple_event_flags};
 use dom::eventdispatcher::dispatch_event;
 use dom::node::NodeTypeId;
 use dom::xmlhttprequest::XMLHttpRequestId;
 use dom::virtualmethods::VirtualMethods;
 use js::jsapi::{JS_CompileUCFunction, JS_GetFunctionObject, JS_CloneFunctionObject};
 use js::jsapi::{JSContext, JSObject};
-use servo_util::str::DOMString;
 use libc::{c_char, size_t};
-use std::cell::RefCell;
+use servo_util::str::DOMString;
+use std::cell::{Cell, RefCell};
 use std::ptr;
+use std::rc::Rc;
 use url::Url;
 
 use std::collections::hashmap::HashMap;
 
-#[deriving(PartialEq,Encodable)]
+#[deriving(Clone,PartialEq,Encodable)]
 pub enum ListenerPhase {
     Capturing,
     Bubbling,
@@ -37,24 +39,64 @@ pub enum EventTargetTypeId {
     XMLHttpRequestTargetTypeId(XMLHttpRequestId)
 }
 
-#[deriving(PartialEq, Encodable)]
+#[deriving(Clone, PartialEq, Encodable)]
 pub enum EventListenerType {
     Additive(EventListener),
     Inline(EventListener),
 }
 
 impl EventListenerType {
-    fn get_listener(&self) -> EventListener {
+    pub fn get_listener(&self) -> EventListener {
         match *self {
             Additive(listener) | Inline(listener) => listener
         }
     }
 }
 
-#[deriving(PartialEq,Encodable)]
+#[deriving(Encodable)]
 pub struct EventListenerEntry {
     pub phase: ListenerPhase,
-    pub listener: EventListenerType
+    pub listener: EventListenerType,
+    /// Shared by every snapshot of this entry, and set when the listener is removed, so that
+    /// dispatches already under way skip it even if an equal listener is added back.
+    removed: Untraceable<Rc<Cell<bool>>>,
+}
+
+impl EventListenerEntry {
+    pub fn new(phase: ListenerPhase, listener: EventListenerType) -> EventListenerEntry {
+        EventListenerEntry {
+            phase: phase,
+            listener: listener,
+            removed: Untraceable::new(Rc::new(Cell::new(false))),
+        }
+    }
+
+    /// Whether this listener has been removed from its target since it was registered.
+    pub fn is_removed(&self) -> bool {
+        self.removed.deref().get()
+    }
+
+    fn mark_removed(&self) {
+        self.removed.deref().set(true);
+    }
+}
+
+impl Clone for EventListenerEntry {
+    fn clone(&self) -> EventListenerEntry {
+        EventListenerEntry {
+            phase: self.phase.clone(),
+            listener: self.listener.clone(),
+            removed: Untraceable::new(self.removed.deref().clone()),
+        }
+    }
+}
+
+/// Entries are equal when they register the same listener for the same phase, whatever their
+/// removal state.
+impl PartialEq for EventListenerEntry {
+    fn eq(&self, other: &EventListenerEntry) -> bool {
+        self.phase == other.phase && self.listener == other.listener
+    }
 }
 
 #[deriving(Encodable)]
@@ -73,17 +115,20 @@ impl EventTarget {
         }
     }
 
-    pub fn get_listeners(&self, type_: &str) -> Option<Vec<EventListener>> {
-        self.handlers.deref().borrow().find_equiv(&type_).map(|listeners| {
-            listeners.iter().map(|entry| entry.listener.get_listener()).collect()
+    /// Returns a snapshot of the listeners registered for `type_`, so that listeners added
+    /// while an event is being dispatched don't run for that event. Dispatch checks
+    /// `is_removed` before running each entry, so listeners removed mid-dispatch don't run.
+    pub fn get_listeners(&self, type_: &DOMString) -> Option<Vec<EventListenerEntry>> {
+        self.handlers.deref().borrow().find(type_).map(|listeners| {
+            listeners.clone()
         })
     }
 
-    pub fn get_listeners_for(&self, type_: &str, desired_phase: ListenerPhase)
-        -> Option<Vec<EventListener>> {
-        self.handlers.deref().borrow().find_equiv(&type_).map(|listeners| {
+    pub fn get_listeners_for(&self, type_: &DOMString, desired_phase: ListenerPhase)
+        -> Option<Vec<EventListenerEntry>> {
+        self.handlers.deref().borrow().find(type_).map(|listeners| {
             let filtered = listeners.iter().filter(|entry| entry.phase == desired_phase);
-            filtered.map(|entry| entry.listener.get_listener()).collect()
+            filtered.map(|entry| entry.clone()).collect()
         })
     }
 }
@@ -101,12 +146,26 @@ pub trait EventTargetHelpers {
                                     url: Url,
                                     scope: *mut JSObject,
                                     ty: &str,
-                                    source: DOMString);
+                                    source: String);
     fn set_event_handler_common<T: CallbackContainer>(&self, ty: &str,
                                                       listener: Option<T>);
     fn get_event_handler_common<T: CallbackContainer>(&self, ty: &str) -> Option<T>;
 
     fn has_handlers(&self) -> bool;
+
+    fn fire_trusted_event<'a>(&self,
+                              target: Option<JSRef<'a, EventTarget>>,
+                              event: &JSRef<Event>) -> bool;
+    fn fire_simple_event<'a>(&self,
+                             target: Option<JSRef<'a, EventTarget>>,
+                             global: &GlobalRef,
+                             type_: &str,
+                             bubbles: bool,
+                             cancelable: bool) -> bool;
+    fn fire_event<'a>(&self,
+                      target: Option<JSRef<'a, EventTarget>>,
+                      global: &GlobalRef,
+                      type_: &str) -> Fallible<bool>;
 }
 
 impl<'a> EventTargetHelpers for JSRef<'a, EventTarget> {
@@ -136,16 +195,13 @@ impl<'a> EventTargetHelpers for JSRef<'a, EventTarget> {
                 match listener {
                     Some(listener) => entries.get_mut(idx).listener = Inline(listener),
                     None => {
-                        entries.remove(idx);
+                        entries.remove(idx).map(|entry| entry.mark_removed());
                     }
                 }
             }
             None => {
                 if listener.is_some() {
-                    entries.push(EventListenerEntry {
-                        phase: Bubbling,
-                        listener: Inline(listener.unwrap()),
-                    });
+                    entries.push(EventListenerEntry::new(Bubbling, Inline(listener.unwrap())));
                 }
             }
         }
@@ -167,7 +223,7 @@ impl<'a> EventTargetHelpers for JSRef<'a, EventTarget> {
                                     url: Url,
                                     scope: *mut JSObject,
                                     ty: &str,
-                                    source: DOMString) {
+                                    source: String) {
         let url = url.to_str().to_c_str();
         let name = ty.to_c_str();
         let lineno = 0; //XXXjdm need to get a real number here
@@ -198,17 +254,52 @@ impl<'a> EventTargetHelpers for JSRef<'a, EventTarget> {
     {
         let event_listener = listener.map(|listener|
                                           EventListener::new(listener.callback()));
-        self.set_inline_event_listener(ty.to_string(), event_listener);
+        self.set_inline_event_listener(DOMString::from_slice(ty), event_listener);
     }
 
     fn get_event_handler_common<T: CallbackContainer>(&self, ty: &str) -> Option<T> {
-        let listener = self.get_inline_event_listener(ty.to_string());
+        let listener = self.get_inline_event_listener(DOMString::from_slice(ty));
         listener.map(|listener| CallbackContainer::new(listener.parent.callback()))
     }
 
     fn has_handlers(&self) -> bool {
         !self.handlers.deref().borrow().is_empty()
     }
+
+    /// Marks `event` trusted, dispatches it at this target (with `target` as the event's target,
+    /// if given) and returns true if a listener prevented its default action.
+    fn fire_trusted_event<'b>(&self,
+                              target: Option<JSRef<'b, EventTarget>>,
+                              event: &JSRef<Event>) -> bool {
+        event.deref().trusted.deref().set(true);
+        !self.dispatch_event_with_target(target, event).unwrap_or(true)
+    }
+
+    // http://www.whatwg.org/html/#fire-a-simple-event
+    /// Creates a trusted `Event` of type `type_` and fires it as `fire_trusted_event` does.
+    fn fire_simple_event<'b>(&self,
+                             target: Option<JSRef<'b, EventTarget>>,
+                             global: &GlobalRef,
+                             type_: &str,
+                             bubbles: bool,
+                             cancelable: bool) -> bool {
+        let event = Event::new(global, DOMString::from_slice(type_), bubbles, cancelable).root();
+        self.fire_trusted_event(target, &*event)
+    }
+
+    /// Like `fire_simple_event`, with the flags the specifications give events of this type.
+    /// Fails with `NotSupported` for a type missing from the table in `dom::event`.
+    fn fire_event<'b>(&self,
+                      target: Option<JSRef<'b, EventTarget>>,
+                      global: &GlobalRef,
+                      type_: &str) -> Fallible<bool> {
+        match simple_event_flags(type_) {
+            Some((bubbles, cancelable)) => {
+                Ok(self.fire_simple_event(target, global, type_, bubbles, cancelable))
+            }
+            None => Err(NotSupported),
+        }
+    }
 }
 
 pub trait EventTargetMethods {
@@ -233,10 +324,7 @@ impl<'a> EventTargetMethods for JSRef<'a, EventTarget> {
                 let mut handlers = self.handlers.deref().borrow_mut();
                 let entry = handlers.find_or_insert_with(ty, |_| vec!());
                 let phase = if capture { Capturing } else { Bubbling };
-                let new_entry = EventListenerEntry {
-                    phase: phase,
-                    listener: Additive(listener)
-                };
+                let new_entry = EventListenerEntry::new(phase, Additive(listener));
                 if entry.as_slice().position_elem(&new_entry).is_none() {
                     entry.push(new_entry);
                 }
@@ -255,13 +343,10 @@ impl<'a> EventTargetMethods for JSRef<'a, EventTarget> {
                 let mut entry = handlers.find_mut(&ty);
                 for entry in entry.mut_iter() {
                     let phase = if capture { Capturing } else { Bubbling };
-                    let old_entry = EventListenerEntry {
-                        phase: phase,
-                        listener: Additive(listener)
-                    };
+                    let old_entry = EventListenerEntry::new(phase, Additive(listener));
                     let position = entry.as_slice().position_elem(&old_entry);
                     for &position in position.iter() {
-                        entry.remove(position);
+                        entry.remove(position).map(|removed| removed.mark_removed());
                     }
                 }
             },
@@ -270,7 +355,15 @@ impl<'a> EventTargetMethods for JSRef<'a, EventTarget> {
     }
 
     fn DispatchEvent(&self, event: &JSRef<Event>) -> Fallible<bool> {
-        self.dispatch_event_with_target(None, event)
+        // Events dispatched from script are never trusted, whoever created them. An event that
+        // is refused, say because it is still being dispatched, keeps its flag.
+        let trusted = event.deref().trusted.deref().get();
+        event.deref().trusted.deref().set(false);
+        let result = self.dispatch_event_with_target(None, event);
+        if result.is_err() {
+            event.deref().trusted.deref().set(trusted);
+        }
+        result
     }
 }
 
@@ -6,6 +6,8 @@ use dom::bindings::codegen::Bindings::FormDataBinding;
 use dom::bindings::codegen::InheritTypes::FileCast;
 use dom::bindings::codegen::UnionTypes::FileOrString::{FileOrString, eFile, eString};
 use dom::bindings::error::{Fallible};
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
@@ -42,11 +44,11 @@ impl FormData {
     }
 
     pub fn new(form: Option<JSRef<HTMLFormElement>>, window: &JSRef<Window>) -> Temporary<FormData> {
-        reflect_dom_object(box FormData::new_inherited(form, window), window, FormDataBinding::Wrap)
+        reflect_dom_object(box FormData::new_inherited(form, window), &global::Window(window), FormDataBinding::Wrap)
     }
 
-    pub fn Constructor(window: &JSRef<Window>, form: Option<JSRef<HTMLFormElement>>) -> Fallible<Temporary<FormData>> {
-        Ok(FormData::new(form, window))
+    pub fn Constructor(global: &GlobalRef, form: Option<JSRef<HTMLFormElement>>) -> Fallible<Temporary<FormData>> {
+        Ok(FormData::new(form, global.as_window()))
     }
 }
 
@@ -77,7 +79,7 @@ impl<'a> FormDataMethods for JSRef<'a, FormData> {
     }
 
     fn Get(&self, name: DOMString) -> Option<FileOrString> {
-        if self.data.deref().borrow().contains_key_equiv(&name) {
+        if self.data.deref().borrow().contains_key(&name) {
             match self.data.deref().borrow().get(&name).get(0).clone() {
                 StringData(ref s) => Some(eString(s.clone())),
                 FileData(ref f) => {
@@ -90,7 +92,7 @@ impl<'a> FormDataMethods for JSRef<'a, FormData> {
     }
 
     fn Has(&self, name: DOMString) -> bool {
-        self.data.deref().borrow().contains_key_equiv(&name)
+        self.data.deref().borrow().contains_key(&name)
     }
 
     fn Set(&self, name: DOMString, value: &JSRef<Blob>, filename: Option<DOMString>) {
@@ -117,7 +119,8 @@ impl PrivateFormDataHelpers for FormData {
     fn get_file_from_blob(&self, value: &JSRef<Blob>, filename: Option<DOMString>) -> Temporary<File> {
         let global = self.window.root();
         let f: Option<&JSRef<File>> = FileCast::to_ref(value);
-        let name = filename.unwrap_or(f.map(|inner| inner.name.clone()).unwrap_or("blob".to_string()));
+        let name = filename.unwrap_or(f.map(|inner| inner.name.clone())
+                                       .unwrap_or(DOMString::from_slice("blob")));
         File::new(&*global, value, name)
     }
 }
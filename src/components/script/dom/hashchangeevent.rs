@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::HashChangeEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, EventTargetCast, HashChangeEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, HashChangeEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::RefCell;
+
+#[deriving(Encodable)]
+pub struct HashChangeEvent {
+    event: Event,
+    old_url: Traceable<RefCell<DOMString>>,
+    new_url: Traceable<RefCell<DOMString>>,
+}
+
+impl HashChangeEventDerived for Event {
+    fn is_hashchangeevent(&self) -> bool {
+        self.type_id == HashChangeEventTypeId
+    }
+}
+
+impl HashChangeEvent {
+    pub fn new_inherited() -> HashChangeEvent {
+        HashChangeEvent {
+            event: Event::new_inherited(HashChangeEventTypeId),
+            old_url: Traceable::new(RefCell::new(DOMString::new())),
+            new_url: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<HashChangeEvent> {
+        reflect_dom_object(box HashChangeEvent::new_inherited(),
+                           &global::Window(window),
+                           HashChangeEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               old_url: DOMString, new_url: DOMString) -> Temporary<HashChangeEvent> {
+        let ev = HashChangeEvent::new_uninitialized(window).root();
+        *ev.deref().old_url.deref().borrow_mut() = old_url;
+        *ev.deref().new_url.deref().borrow_mut() = new_url;
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &HashChangeEventBinding::HashChangeEventInit)
+                       -> Fallible<Temporary<HashChangeEvent>> {
+        Ok(HashChangeEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                                init.oldURL.clone(), init.newURL.clone()))
+    }
+
+    /// Fires a trusted "hashchange" event at `window` after a navigation that only changed
+    /// the fragment.
+    pub fn dispatch(window: &JSRef<Window>, old_url: String, new_url: String) {
+        let hashchangeevent = HashChangeEvent::new(window, DOMString::from_slice("hashchange"),
+                                                   true, false,
+                                                   DOMString::from_string(old_url),
+                                                   DOMString::from_string(new_url)).root();
+        let event: &JSRef<Event> = EventCast::from_ref(&*hashchangeevent);
+        event.deref().trusted.deref().set(true);
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(window);
+        target.dispatch_event_with_target(None, event).ok();
+    }
+}
+
+pub trait HashChangeEventMethods {
+    fn OldURL(&self) -> DOMString;
+    fn NewURL(&self) -> DOMString;
+}
+
+impl<'a> HashChangeEventMethods for JSRef<'a, HashChangeEvent> {
+    fn OldURL(&self) -> DOMString {
+        self.old_url.deref().borrow().clone()
+    }
+
+    fn NewURL(&self) -> DOMString {
+        self.new_url.deref().borrow().clone()
+    }
+}
+
+impl Reflectable for HashChangeEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -8,7 +8,6 @@ use dom::bindings::codegen::InheritTypes::{ElementCast, HTMLElementCast, NodeCas
 use dom::bindings::js::{JSRef, Temporary, OptionalRootable};
 use dom::bindings::utils::{Reflectable, Reflector};
 use dom::document::{Document, DocumentHelpers};
-use dom::attr::AttrMethods;
 use dom::element::{Element, AttributeHandlers, HTMLAnchorElementTypeId};
 use dom::event::{Event, EventMethods};
 use dom::eventtarget::{EventTarget, NodeTargetTypeId};
@@ -16,7 +15,6 @@ use dom::htmlelement::HTMLElement;
 use dom::node::{Node, NodeHelpers, ElementNodeTypeId};
 use dom::virtualmethods::VirtualMethods;
 use servo_util::namespace::Null;
-use servo_util::str::DOMString;
 
 #[deriving(Encodable)]
 pub struct HTMLAnchorElement {
@@ -30,13 +28,13 @@ impl HTMLAnchorElementDerived for EventTarget {
 }
 
 impl HTMLAnchorElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLAnchorElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLAnchorElement {
         HTMLAnchorElement {
             htmlelement: HTMLElement::new_inherited(HTMLAnchorElementTypeId, localName, document)
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLAnchorElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLAnchorElement> {
         let element = HTMLAnchorElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLAnchorElementBinding::Wrap)
     }
@@ -51,12 +49,12 @@ trait PrivateHTMLAnchorElementHelpers {
 
 impl<'a> PrivateHTMLAnchorElementHelpers for JSRef<'a, HTMLAnchorElement> {
     fn handle_event_impl(&self, event: &JSRef<Event>) {
-        if "click" == event.Type().as_slice() && !event.DefaultPrevented() {
+        if event.deref().type_is("click") && !event.DefaultPrevented() {
             let element: &JSRef<Element> = ElementCast::from_ref(self);
             let attr = element.get_attribute(Null, "href").root();
             match attr {
                 Some(ref href) => {
-                    let value = href.Value();
+                    let value = href.value().as_slice().to_string();
                     debug!("clicked on link to {:s}", value);
                     let node: &JSRef<Node> = NodeCast::from_ref(self);
                     let doc = node.owner_doc().root();
@@ -4,11 +4,13 @@
 
 use dom::bindings::codegen::InheritTypes::{ElementCast, NodeCast};
 use dom::bindings::codegen::Bindings::HTMLCollectionBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JS, JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::element::{Element, AttributeHandlers};
 use dom::node::{Node, NodeHelpers};
 use dom::window::Window;
+use servo_util::atom::Atom;
 use servo_util::namespace::Namespace;
 use servo_util::str::{DOMString, split_html_space_chars};
 
@@ -48,7 +50,7 @@ impl HTMLCollection {
 
     pub fn new(window: &JSRef<Window>, collection: CollectionTypeId) -> Temporary<HTMLCollection> {
         reflect_dom_object(box HTMLCollection::new_inherited(window, collection),
-                           window, HTMLCollectionBinding::Wrap)
+                           &global::Window(window), HTMLCollectionBinding::Wrap)
     }
 }
 
@@ -58,10 +60,10 @@ impl HTMLCollection {
         HTMLCollection::new(window, Live(JS::from_rooted(root), filter))
     }
 
-    pub fn by_tag_name(window: &JSRef<Window>, root: &JSRef<Node>, tag: DOMString)
+    pub fn by_tag_name(window: &JSRef<Window>, root: &JSRef<Node>, tag: String)
                        -> Temporary<HTMLCollection> {
         struct TagNameFilter {
-            tag: DOMString
+            tag: Atom
         }
         impl CollectionFilter for TagNameFilter {
             fn filter(&self, elem: &JSRef<Element>, _root: &JSRef<Node>) -> bool {
@@ -69,15 +71,15 @@ impl HTMLCollection {
             }
         }
         let filter = TagNameFilter {
-            tag: tag
+            tag: Atom::from_string(tag)
         };
         HTMLCollection::create(window, root, box filter)
     }
 
-    pub fn by_tag_name_ns(window: &JSRef<Window>, root: &JSRef<Node>, tag: DOMString,
+    pub fn by_tag_name_ns(window: &JSRef<Window>, root: &JSRef<Node>, tag: String,
                           namespace: Namespace) -> Temporary<HTMLCollection> {
         struct TagNameNSFilter {
-            tag: DOMString,
+            tag: Atom,
             namespace: Namespace
         }
         impl CollectionFilter for TagNameNSFilter {
@@ -86,16 +88,16 @@ impl HTMLCollection {
             }
         }
         let filter = TagNameNSFilter {
-            tag: tag,
+            tag: Atom::from_string(tag),
             namespace: namespace
         };
         HTMLCollection::create(window, root, box filter)
     }
 
-    pub fn by_class_name(window: &JSRef<Window>, root: &JSRef<Node>, classes: DOMString)
+    pub fn by_class_name(window: &JSRef<Window>, root: &JSRef<Node>, classes: String)
                          -> Temporary<HTMLCollection> {
         struct ClassNameFilter {
-            classes: Vec<DOMString>
+            classes: Vec<String>
         }
         impl CollectionFilter for ClassNameFilter {
             fn filter(&self, elem: &JSRef<Element>, _root: &JSRef<Node>) -> bool {
@@ -167,6 +169,8 @@ impl<'a> HTMLCollectionMethods for JSRef<'a, HTMLCollection> {
 
     // http://dom.spec.whatwg.org/#dom-htmlcollection-nameditem
     fn NamedItem(&self, key: DOMString) -> Option<Temporary<Element>> {
+        let key = key.to_string_lossy();
+
         // Step 1.
         if key.is_empty() {
             return None;
@@ -5,18 +5,17 @@
 use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::codegen::Bindings::HTMLElementBinding;
 use dom::bindings::codegen::InheritTypes::{ElementCast, HTMLFrameSetElementDerived};
-use dom::bindings::codegen::InheritTypes::EventTargetCast;
+use dom::bindings::codegen::InheritTypes::{EventTargetCast, NodeCast};
 use dom::bindings::codegen::InheritTypes::{HTMLElementDerived, HTMLBodyElementDerived};
-use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::js::{JSRef, Temporary, OptionalRootable};
 use dom::bindings::utils::{Reflectable, Reflector};
-use dom::document::Document;
+use dom::document::{Document, DocumentHelpers};
 use dom::element::{Element, ElementTypeId, HTMLElementTypeId};
 use dom::eventtarget::{EventTarget, EventTargetHelpers, NodeTargetTypeId};
-use dom::node::{Node, ElementNodeTypeId, window_from_node};
+use dom::node::{Node, NodeHelpers, ElementNodeTypeId, window_from_node};
 use dom::virtualmethods::VirtualMethods;
 use dom::window::WindowMethods;
 use servo_util::namespace;
-use servo_util::str::DOMString;
 
 #[deriving(Encodable)]
 pub struct HTMLElement {
@@ -34,13 +33,13 @@ impl HTMLElementDerived for EventTarget {
 }
 
 impl HTMLElement {
-    pub fn new_inherited(type_id: ElementTypeId, tag_name: DOMString, document: &JSRef<Document>) -> HTMLElement {
+    pub fn new_inherited(type_id: ElementTypeId, tag_name: String, document: &JSRef<Document>) -> HTMLElement {
         HTMLElement {
             element: Element::new_inherited(type_id, tag_name, namespace::HTML, None, document)
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLElement> {
         let element = HTMLElement::new_inherited(HTMLElementTypeId, localName, document);
         Node::reflect_node(box element, document, HTMLElementBinding::Wrap)
     }
@@ -62,6 +61,8 @@ pub trait HTMLElementMethods {
     fn SetOnclick(&self, listener: Option<EventHandlerNonNull>);
     fn GetOnload(&self) -> Option<EventHandlerNonNull>;
     fn SetOnload(&self, listener: Option<EventHandlerNonNull>);
+    fn Focus(&self);
+    fn Blur(&self);
 }
 
 impl<'a> HTMLElementMethods for JSRef<'a, HTMLElement> {
@@ -90,6 +91,26 @@ impl<'a> HTMLElementMethods for JSRef<'a, HTMLElement> {
             win.deref().SetOnload(listener)
         }
     }
+
+    // http://www.whatwg.org/html/#dom-focus
+    fn Focus(&self) {
+        // FIXME: Only focusable areas should be able to receive focus.
+        let element: &JSRef<Element> = ElementCast::from_ref(self);
+        let node: &JSRef<Node> = NodeCast::from_ref(self);
+        let document = node.owner_doc().root();
+        document.set_focused_element(Some(element));
+    }
+
+    // http://www.whatwg.org/html/#dom-blur
+    fn Blur(&self) {
+        let element: &JSRef<Element> = ElementCast::from_ref(self);
+        let node: &JSRef<Node> = NodeCast::from_ref(self);
+        let document = node.owner_doc().root();
+        let focused = document.get_focused_element().root();
+        if focused.as_ref().map(|focused| &**focused) == Some(element) {
+            document.set_focused_element(None);
+        }
+    }
 }
 
 impl<'a> VirtualMethods for JSRef<'a, HTMLElement> {
@@ -98,7 +119,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLElement> {
         Some(element as &VirtualMethods+)
     }
 
-    fn after_set_attr(&self, name: DOMString, value: DOMString) {
+    fn after_set_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.after_set_attr(name.clone(), value.clone()),
             _ => (),
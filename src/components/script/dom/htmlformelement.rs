@@ -3,15 +3,18 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::HTMLFormElementBinding;
-use dom::bindings::codegen::InheritTypes::HTMLFormElementDerived;
+use dom::bindings::codegen::InheritTypes::{EventTargetCast, HTMLFormElementDerived};
+use dom::bindings::codegen::InheritTypes::{HTMLInputElementCast, HTMLTextAreaElementCast, NodeCast};
+use dom::bindings::global;
 use dom::bindings::js::{JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector};
 use dom::document::Document;
 use dom::element::HTMLFormElementTypeId;
-use dom::eventtarget::{EventTarget, NodeTargetTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
-use dom::node::{Node, ElementNodeTypeId};
-use servo_util::str::DOMString;
+use dom::htmlinputelement::HTMLInputElementHelpers;
+use dom::htmltextareaelement::HTMLTextAreaElementHelpers;
+use dom::node::{Node, NodeHelpers, ElementNodeTypeId, window_from_node};
 
 #[deriving(Encodable)]
 pub struct HTMLFormElement {
@@ -25,19 +28,43 @@ impl HTMLFormElementDerived for EventTarget {
 }
 
 impl HTMLFormElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLFormElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLFormElement {
         HTMLFormElement {
             htmlelement: HTMLElement::new_inherited(HTMLFormElementTypeId, localName, document)
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLFormElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLFormElement> {
         let element = HTMLFormElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLFormElementBinding::Wrap)
     }
 }
 
 pub trait HTMLFormElementMethods {
+    fn Reset(&self);
+}
+
+impl<'a> HTMLFormElementMethods for JSRef<'a, HTMLFormElement> {
+    // http://www.whatwg.org/html/#dom-form-reset
+    fn Reset(&self) {
+        let window = window_from_node(self).root();
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(self);
+        match target.fire_event(None, &global::Window(&*window), "reset") {
+            Ok(false) => (),
+            _ => return,
+        }
+        // Form owners aren't tracked yet, so every field under the form is taken to be its own.
+        let node: &JSRef<Node> = NodeCast::from_ref(self);
+        for child in node.traverse_preorder() {
+            match HTMLInputElementCast::to_ref(&child) {
+                Some(input) => input.reset(),
+                None => match HTMLTextAreaElementCast::to_ref(&child) {
+                    Some(textarea) => textarea.reset(),
+                    None => (),
+                },
+            }
+        }
+    }
 }
 
 impl Reflectable for HTMLFormElement {
@@ -17,10 +17,10 @@ use dom::virtualmethods::VirtualMethods;
 use dom::window::Window;
 use page::IterablePage;
 use servo_msg::constellation_msg::{PipelineId, SubpageId};
+use servo_util::str::DOMString;
 use servo_msg::constellation_msg::{IFrameSandboxed, IFrameUnsandboxed};
 use servo_msg::constellation_msg::{ConstellationChan, LoadIframeUrlMsg};
 use servo_util::namespace::Null;
-use servo_util::str::DOMString;
 use servo_util::url::try_parse_url;
 
 use std::ascii::StrAsciiExt;
@@ -77,7 +77,7 @@ impl<'a> HTMLIFrameElementHelpers for JSRef<'a, HTMLIFrameElement> {
 }
 
 impl HTMLIFrameElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLIFrameElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLIFrameElement {
         HTMLIFrameElement {
             htmlelement: HTMLElement::new_inherited(HTMLIFrameElementTypeId, localName, document),
             size: Traceable::new(Cell::new(None)),
@@ -85,7 +85,7 @@ impl HTMLIFrameElement {
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLIFrameElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLIFrameElement> {
         let element = HTMLIFrameElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLIFrameElementBinding::Wrap)
     }
@@ -102,22 +102,22 @@ pub trait HTMLIFrameElementMethods {
 impl<'a> HTMLIFrameElementMethods for JSRef<'a, HTMLIFrameElement> {
     fn Src(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("src")
+        DOMString::from_string(element.get_string_attribute("src"))
     }
 
     fn SetSrc(&self, src: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_url_attribute("src", src)
+        element.set_url_attribute("src", src.to_string_lossy())
     }
 
     fn Sandbox(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("sandbox")
+        DOMString::from_string(element.get_string_attribute("sandbox"))
     }
 
     fn SetSandbox(&self, sandbox: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("sandbox", sandbox);
+        element.set_string_attribute("sandbox", sandbox.to_string_lossy());
     }
 
     fn GetContentWindow(&self) -> Option<Temporary<Window>> {
@@ -142,7 +142,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLIFrameElement> {
         Some(htmlelement as &VirtualMethods+)
     }
 
-    fn after_set_attr(&self, name: DOMString, value: DOMString) {
+    fn after_set_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.after_set_attr(name.clone(), value.clone()),
             _ => (),
@@ -165,7 +165,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLIFrameElement> {
         }
     }
 
-    fn before_remove_attr(&self, name: DOMString, value: DOMString) {
+    fn before_remove_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.before_remove_attr(name.clone(), value),
             _ => (),
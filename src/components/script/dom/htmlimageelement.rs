@@ -15,10 +15,10 @@ use dom::eventtarget::{EventTarget, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
 use dom::node::{Node, ElementNodeTypeId, NodeHelpers, window_from_node};
 use dom::virtualmethods::VirtualMethods;
+use servo_util::str::DOMString;
 use servo_util::geometry::to_px;
 use servo_net::image_cache_task;
 use servo_util::url::parse_url;
-use servo_util::str::DOMString;
 use std::cell::RefCell;
 use url::Url;
 
@@ -35,13 +35,13 @@ impl HTMLImageElementDerived for EventTarget {
 }
 
 trait PrivateHTMLImageElementHelpers {
-    fn update_image(&self, value: Option<DOMString>, url: Option<Url>);
+    fn update_image(&self, value: Option<String>, url: Option<Url>);
 }
 
 impl<'a> PrivateHTMLImageElementHelpers for JSRef<'a, HTMLImageElement> {
     /// Makes the local `image` member match the status of the `src` attribute and starts
     /// prefetching the image. This method must be called after `src` is changed.
-    fn update_image(&self, value: Option<DOMString>, url: Option<Url>) {
+    fn update_image(&self, value: Option<String>, url: Option<Url>) {
         let node: &JSRef<Node> = NodeCast::from_ref(self);
         let document = node.owner_doc().root();
         let window = document.deref().window.root();
@@ -66,14 +66,14 @@ impl<'a> PrivateHTMLImageElementHelpers for JSRef<'a, HTMLImageElement> {
 }
 
 impl HTMLImageElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLImageElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLImageElement {
         HTMLImageElement {
             htmlelement: HTMLElement::new_inherited(HTMLImageElementTypeId, localName, document),
             image: Untraceable::new(RefCell::new(None)),
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLImageElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLImageElement> {
         let element = HTMLImageElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLImageElementBinding::Wrap)
     }
@@ -119,32 +119,32 @@ pub trait HTMLImageElementMethods {
 impl<'a> HTMLImageElementMethods for JSRef<'a, HTMLImageElement> {
     fn Alt(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("alt")
+        DOMString::from_string(element.get_string_attribute("alt"))
     }
 
     fn SetAlt(&self, alt: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("alt", alt)
+        element.set_string_attribute("alt", alt.to_string_lossy())
     }
 
     fn Src(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("src")
+        DOMString::from_string(element.get_string_attribute("src"))
     }
 
     fn SetSrc(&self, src: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_url_attribute("src", src)
+        element.set_url_attribute("src", src.to_string_lossy())
     }
 
     fn UseMap(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("useMap")
+        DOMString::from_string(element.get_string_attribute("useMap"))
     }
 
     fn SetUseMap(&self, use_map: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("useMap", use_map)
+        element.set_string_attribute("useMap", use_map.to_string_lossy())
     }
 
     fn IsMap(&self) -> bool {
@@ -181,22 +181,22 @@ impl<'a> HTMLImageElementMethods for JSRef<'a, HTMLImageElement> {
 
     fn Name(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("name")
+        DOMString::from_string(element.get_string_attribute("name"))
     }
 
     fn SetName(&self, name: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("name", name)
+        element.set_string_attribute("name", name.to_string_lossy())
     }
 
     fn Align(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("align")
+        DOMString::from_string(element.get_string_attribute("align"))
     }
 
     fn SetAlign(&self, align: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("align", align)
+        element.set_string_attribute("align", align.to_string_lossy())
     }
 
     fn Hspace(&self) -> u32 {
@@ -221,22 +221,22 @@ impl<'a> HTMLImageElementMethods for JSRef<'a, HTMLImageElement> {
 
     fn LongDesc(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("longdesc")
+        DOMString::from_string(element.get_string_attribute("longdesc"))
     }
 
     fn SetLongDesc(&self, longdesc: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("longdesc", longdesc)
+        element.set_string_attribute("longdesc", longdesc.to_string_lossy())
     }
 
     fn Border(&self) -> DOMString {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.get_string_attribute("border")
+        DOMString::from_string(element.get_string_attribute("border"))
     }
 
     fn SetBorder(&self, border: DOMString) {
         let element: &JSRef<Element> = ElementCast::from_ref(self);
-        element.set_string_attribute("border", border)
+        element.set_string_attribute("border", border.to_string_lossy())
     }
 }
 
@@ -246,7 +246,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLImageElement> {
         Some(htmlelement as &VirtualMethods+)
     }
 
-    fn after_set_attr(&self, name: DOMString, value: DOMString) {
+    fn after_set_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.after_set_attr(name.clone(), value.clone()),
             _ => (),
@@ -259,7 +259,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLImageElement> {
         }
     }
 
-    fn before_remove_attr(&self, name: DOMString, value: DOMString) {
+    fn before_remove_attr(&self, name: String, value: String) {
         match self.super_type() {
             Some(ref s) => s.before_remove_attr(name.clone(), value.clone()),
             _ => (),
@@ -270,7 +270,7 @@ impl<'a> VirtualMethods for JSRef<'a, HTMLImageElement> {
         }
     }
 
-    fn parse_plain_attribute(&self, name: &str, value: DOMString) -> AttrValue {
+    fn parse_plain_attribute(&self, name: &str, value: String) -> AttrValue {
         match name {
             "width" | "height" | "hspace" | "vspace" => AttrValue::from_u32(value, 0),
             _ => self.super_type().unwrap().parse_plain_attribute(name, value),
@@ -3,19 +3,26 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::HTMLInputElementBinding;
-use dom::bindings::codegen::InheritTypes::HTMLInputElementDerived;
+use dom::bindings::codegen::InheritTypes::{ElementCast, HTMLInputElementDerived};
 use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector};
 use dom::document::Document;
-use dom::element::HTMLInputElementTypeId;
+use dom::element::{Element, AttributeHandlers, HTMLInputElementTypeId};
 use dom::eventtarget::{EventTarget, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
 use dom::node::{Node, ElementNodeTypeId};
 use servo_util::str::DOMString;
 
+use std::ascii::StrAsciiExt;
+use std::cell::RefCell;
+
 #[deriving(Encodable)]
 pub struct HTMLInputElement {
     pub htmlelement: HTMLElement,
+    /// The value set by typing or by script, or None while the field still shows its default
+    /// value, the `value` attribute.
+    value: Traceable<RefCell<Option<DOMString>>>,
 }
 
 impl HTMLInputElementDerived for EventTarget {
@@ -25,19 +32,71 @@ impl HTMLInputElementDerived for EventTarget {
 }
 
 impl HTMLInputElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLInputElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLInputElement {
         HTMLInputElement {
-            htmlelement: HTMLElement::new_inherited(HTMLInputElementTypeId, localName, document)
+            htmlelement: HTMLElement::new_inherited(HTMLInputElementTypeId, localName, document),
+            value: Traceable::new(RefCell::new(None)),
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLInputElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLInputElement> {
         let element = HTMLInputElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLInputElementBinding::Wrap)
     }
 }
 
+pub trait HTMLInputElementHelpers {
+    fn is_text_field(&self) -> bool;
+    fn reset(&self);
+}
+
+impl<'a> HTMLInputElementHelpers for JSRef<'a, HTMLInputElement> {
+    /// Returns true if typing edits this input's value, as opposed to, say, a checkbox. Unknown
+    /// types are text fields, as the specification requires.
+    fn is_text_field(&self) -> bool {
+        let element: &JSRef<Element> = ElementCast::from_ref(self);
+        match element.get_string_attribute("type").as_slice().to_ascii_lower().as_slice() {
+            "hidden" | "checkbox" | "radio" | "file" | "submit" | "image" | "reset" |
+            "button" | "color" | "range" => false,
+            _ => true,
+        }
+    }
+
+    // http://www.whatwg.org/html/#the-input-element:concept-form-reset-control
+    /// Puts back the default value, as resetting the form owning this input does.
+    fn reset(&self) {
+        *self.value.deref().borrow_mut() = None;
+    }
+}
+
 pub trait HTMLInputElementMethods {
+    fn DefaultValue(&self) -> DOMString;
+    fn SetDefaultValue(&self, value: DOMString);
+    fn Value(&self) -> DOMString;
+    fn SetValue(&self, value: DOMString);
+}
+
+impl<'a> HTMLInputElementMethods for JSRef<'a, HTMLInputElement> {
+    fn DefaultValue(&self) -> DOMString {
+        let element: &JSRef<Element> = ElementCast::from_ref(self);
+        DOMString::from_string(element.get_string_attribute("value"))
+    }
+
+    fn SetDefaultValue(&self, value: DOMString) {
+        let element: &JSRef<Element> = ElementCast::from_ref(self);
+        element.set_string_attribute("value", value.to_string_lossy())
+    }
+
+    fn Value(&self) -> DOMString {
+        match *self.value.deref().borrow() {
+            Some(ref value) => value.clone(),
+            None => self.DefaultValue(),
+        }
+    }
+
+    fn SetValue(&self, value: DOMString) {
+        *self.value.deref().borrow_mut() = Some(value);
+    }
 }
 
 impl Reflectable for HTMLInputElement {
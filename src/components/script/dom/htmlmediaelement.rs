@@ -10,7 +10,6 @@ use dom::element::{ElementTypeId, HTMLAudioElementTypeId, HTMLVideoElementTypeId
 use dom::eventtarget::{EventTarget, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
 use dom::node::ElementNodeTypeId;
-use servo_util::str::DOMString;
 
 #[deriving(Encodable)]
 pub struct HTMLMediaElement {
@@ -28,7 +27,7 @@ impl HTMLMediaElementDerived for EventTarget {
 }
 
 impl HTMLMediaElement {
-    pub fn new_inherited(type_id: ElementTypeId, tag_name: DOMString, document: &JSRef<Document>) -> HTMLMediaElement {
+    pub fn new_inherited(type_id: ElementTypeId, tag_name: String, document: &JSRef<Document>) -> HTMLMediaElement {
         HTMLMediaElement {
             htmlelement: HTMLElement::new_inherited(type_id, tag_name, document)
         }
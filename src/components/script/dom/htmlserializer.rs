@@ -67,11 +67,12 @@ pub fn serialize(iterator: &mut NodeIterator) -> String {
 
 fn serialize_comment(comment: &JSRef<Comment>, html: &mut String) {
     html.push_str("<!--");
-    html.push_str(comment.deref().characterdata.data.deref().borrow().as_slice());
+    html.push_str(comment.deref().characterdata.data.deref().borrow().to_string_lossy().as_slice());
     html.push_str("-->");
 }
 
 fn serialize_text(text: &JSRef<Text>, html: &mut String) {
+    let data = text.deref().characterdata.data.deref().borrow().to_string_lossy();
     let text_node: &JSRef<Node> = NodeCast::from_ref(text);
     match text_node.parent_node().map(|node| node.root()) {
         Some(ref parent) if parent.is_element() => {
@@ -80,11 +81,11 @@ fn serialize_text(text: &JSRef<Text>, html: &mut String) {
                 "style" | "script" | "xmp" | "iframe" |
                 "noembed" | "noframes" | "plaintext" |
                 "noscript" if elem.deref().namespace == namespace::HTML
-                => html.push_str(text.deref().characterdata.data.deref().borrow().as_slice()),
-                _ => escape(text.deref().characterdata.data.deref().borrow().as_slice(), false, html)
+                => html.push_str(data.as_slice()),
+                _ => escape(data.as_slice(), false, html)
             }
         }
-        _ => escape(text.deref().characterdata.data.deref().borrow().as_slice(), false, html)
+        _ => escape(data.as_slice(), false, html)
     }
 }
 
@@ -93,7 +94,8 @@ fn serialize_processing_instruction(processing_instruction: &JSRef<ProcessingIns
     html.push_str("<?");
     html.push_str(processing_instruction.deref().target.as_slice());
     html.push_char(' ');
-    html.push_str(processing_instruction.deref().characterdata.data.deref().borrow().as_slice());
+    let data = processing_instruction.deref().characterdata.data.deref().borrow().to_string_lossy();
+    html.push_str(data.as_slice());
     html.push_str("?>");
 }
 
@@ -118,7 +120,7 @@ fn serialize_elem(elem: &JSRef<Element>, open_elements: &mut Vec<String>, html:
             match node.first_child().map(|child| child.root()) {
                 Some(ref child) if child.is_text() => {
                     let text: &JSRef<CharacterData> = CharacterDataCast::to_ref(&**child).unwrap();
-                    if text.deref().data.deref().borrow().len() > 0 && text.deref().data.deref().borrow().as_slice().char_at(0) == '\n' {
+                    if text.deref().data.deref().borrow().code_unit_at(0) == Some('\n' as u16) {
                         html.push_char('\x0A');
                     }
                 },
@@ -129,7 +131,7 @@ fn serialize_elem(elem: &JSRef<Element>, open_elements: &mut Vec<String>, html:
     }
 
     if !elem.deref().is_void() {
-        open_elements.push(elem.deref().local_name.clone());
+        open_elements.push(elem.deref().local_name.as_slice().to_string());
     }
 }
 
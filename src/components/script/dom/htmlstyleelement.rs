@@ -14,7 +14,6 @@ use dom::node::{Node, NodeMethods, NodeHelpers, ElementNodeTypeId, window_from_n
 use dom::virtualmethods::VirtualMethods;
 use html::cssparse::parse_inline_css;
 use layout_interface::{AddStylesheetMsg, LayoutChan};
-use servo_util::str::DOMString;
 
 #[deriving(Encodable)]
 pub struct HTMLStyleElement {
@@ -28,13 +27,13 @@ impl HTMLStyleElementDerived for EventTarget {
 }
 
 impl HTMLStyleElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLStyleElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLStyleElement {
         HTMLStyleElement {
             htmlelement: HTMLElement::new_inherited(HTMLStyleElementTypeId, localName, document)
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLStyleElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLStyleElement> {
         let element = HTMLStyleElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLStyleElementBinding::Wrap)
     }
@@ -59,7 +58,7 @@ impl<'a> StyleElementHelpers for JSRef<'a, HTMLStyleElement> {
         let url = win.deref().page().get_url();
 
         let data = node.GetTextContent().expect("Element.textContent must be a string");
-        let sheet = parse_inline_css(url, data);
+        let sheet = parse_inline_css(url, data.to_string_lossy());
         let LayoutChan(ref layout_chan) = *win.deref().page().layout_chan;
         layout_chan.send(AddStylesheetMsg(sheet));
     }
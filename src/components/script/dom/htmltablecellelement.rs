@@ -10,7 +10,6 @@ use dom::element::{ElementTypeId, HTMLTableDataCellElementTypeId, HTMLTableHeade
 use dom::eventtarget::{EventTarget, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
 use dom::node::ElementNodeTypeId;
-use servo_util::str::DOMString;
 
 #[deriving(Encodable)]
 pub struct HTMLTableCellElement {
@@ -28,7 +27,7 @@ impl HTMLTableCellElementDerived for EventTarget {
 }
 
 impl HTMLTableCellElement {
-    pub fn new_inherited(type_id: ElementTypeId, tag_name: DOMString, document: &JSRef<Document>) -> HTMLTableCellElement {
+    pub fn new_inherited(type_id: ElementTypeId, tag_name: String, document: &JSRef<Document>) -> HTMLTableCellElement {
         HTMLTableCellElement {
             htmlelement: HTMLElement::new_inherited(type_id, tag_name, document)
         }
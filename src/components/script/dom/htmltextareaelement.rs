@@ -3,19 +3,25 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding;
-use dom::bindings::codegen::InheritTypes::HTMLTextAreaElementDerived;
+use dom::bindings::codegen::InheritTypes::{HTMLTextAreaElementDerived, NodeCast};
 use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector};
 use dom::document::Document;
 use dom::element::HTMLTextAreaElementTypeId;
 use dom::eventtarget::{EventTarget, NodeTargetTypeId};
 use dom::htmlelement::HTMLElement;
-use dom::node::{Node, ElementNodeTypeId};
+use dom::node::{Node, NodeMethods, ElementNodeTypeId};
 use servo_util::str::DOMString;
 
+use std::cell::RefCell;
+
 #[deriving(Encodable)]
 pub struct HTMLTextAreaElement {
     pub htmlelement: HTMLElement,
+    /// The value set by typing or by script, or None while the text area still shows its default
+    /// value, its text content.
+    value: Traceable<RefCell<Option<DOMString>>>,
 }
 
 impl HTMLTextAreaElementDerived for EventTarget {
@@ -25,19 +31,59 @@ impl HTMLTextAreaElementDerived for EventTarget {
 }
 
 impl HTMLTextAreaElement {
-    pub fn new_inherited(localName: DOMString, document: &JSRef<Document>) -> HTMLTextAreaElement {
+    pub fn new_inherited(localName: String, document: &JSRef<Document>) -> HTMLTextAreaElement {
         HTMLTextAreaElement {
-            htmlelement: HTMLElement::new_inherited(HTMLTextAreaElementTypeId, localName, document)
+            htmlelement: HTMLElement::new_inherited(HTMLTextAreaElementTypeId, localName, document),
+            value: Traceable::new(RefCell::new(None)),
         }
     }
 
-    pub fn new(localName: DOMString, document: &JSRef<Document>) -> Temporary<HTMLTextAreaElement> {
+    pub fn new(localName: String, document: &JSRef<Document>) -> Temporary<HTMLTextAreaElement> {
         let element = HTMLTextAreaElement::new_inherited(localName, document);
         Node::reflect_node(box element, document, HTMLTextAreaElementBinding::Wrap)
     }
 }
 
+pub trait HTMLTextAreaElementHelpers {
+    fn reset(&self);
+}
+
+impl<'a> HTMLTextAreaElementHelpers for JSRef<'a, HTMLTextAreaElement> {
+    // http://www.whatwg.org/html/#the-textarea-element:concept-form-reset-control
+    /// Puts back the default value, as resetting the form owning this text area does.
+    fn reset(&self) {
+        *self.value.deref().borrow_mut() = None;
+    }
+}
+
 pub trait HTMLTextAreaElementMethods {
+    fn DefaultValue(&self) -> DOMString;
+    fn SetDefaultValue(&self, value: DOMString);
+    fn Value(&self) -> DOMString;
+    fn SetValue(&self, value: DOMString);
+}
+
+impl<'a> HTMLTextAreaElementMethods for JSRef<'a, HTMLTextAreaElement> {
+    fn DefaultValue(&self) -> DOMString {
+        let node: &JSRef<Node> = NodeCast::from_ref(self);
+        node.GetTextContent().unwrap_or_else(|| DOMString::new())
+    }
+
+    fn SetDefaultValue(&self, value: DOMString) {
+        let node: &JSRef<Node> = NodeCast::from_ref(self);
+        node.SetTextContent(Some(value)).ok();
+    }
+
+    fn Value(&self) -> DOMString {
+        match *self.value.deref().borrow() {
+            Some(ref value) => value.clone(),
+            None => self.DefaultValue(),
+        }
+    }
+
+    fn SetValue(&self, value: DOMString) {
+        *self.value.deref().borrow_mut() = Some(value);
+    }
 }
 
 impl Reflectable for HTMLTextAreaElement {
@@ -0,0 +1,322 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::KeyboardEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, UIEventCast, KeyboardEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, RootedReference, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, KeyboardEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::uievent::{UIEvent, UIEventMethods};
+use dom::window::Window;
+use servo_msg::constellation_msg::{KeyInput, KeyRepeated};
+use servo_util::str::DOMString;
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct KeyboardEvent {
+    pub uievent: UIEvent,
+    pub key: Traceable<RefCell<DOMString>>,
+    pub code: Traceable<RefCell<DOMString>>,
+    pub location: Traceable<Cell<u32>>,
+    pub ctrl_key: Traceable<Cell<bool>>,
+    pub shift_key: Traceable<Cell<bool>>,
+    pub alt_key: Traceable<Cell<bool>>,
+    pub meta_key: Traceable<Cell<bool>>,
+    pub repeat: Traceable<Cell<bool>>,
+    pub is_composing: Traceable<Cell<bool>>,
+    pub char_code: Traceable<Cell<u32>>,
+    pub key_code: Traceable<Cell<u32>>,
+}
+
+impl KeyboardEventDerived for Event {
+    fn is_keyboardevent(&self) -> bool {
+        self.type_id == KeyboardEventTypeId
+    }
+}
+
+/// The legacy `keyCode` value for a key, for pages that still look at it instead of `key`.
+/// Letters map to their upper case ASCII code and digits to theirs, as on a US keyboard.
+/// Returns 0 for keys without a well-established code.
+pub fn key_code_for_key(key: &str) -> u32 {
+    if key.char_len() == 1 {
+        let c = key.char_at(0);
+        return match c {
+            'a'..'z' | 'A'..'Z' => c.to_uppercase() as u32,
+            '0'..'9' | ' ' => c as u32,
+            _ => 0,
+        }
+    }
+
+    match key {
+        "Backspace" => 8,
+        "Tab" => 9,
+        "Enter" => 13,
+        "Shift" => 16,
+        "Control" => 17,
+        "Alt" => 18,
+        "Escape" | "Esc" => 27,
+        "PageUp" => 33,
+        "PageDown" => 34,
+        "End" => 35,
+        "Home" => 36,
+        "ArrowLeft" | "Left" => 37,
+        "ArrowUp" | "Up" => 38,
+        "ArrowRight" | "Right" => 39,
+        "ArrowDown" | "Down" => 40,
+        "Insert" => 45,
+        "Delete" | "Del" => 46,
+        _ if key.starts_with("F") => {
+            match from_str::<u32>(key.slice_from(1)) {
+                Some(n) if n >= 1 && n <= 24 => 111 + n,
+                _ => 0,
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// The legacy `charCode` value: the code point of the character a keypress produces, or 0 for
+/// other event types and for keys that don't produce a single character.
+pub fn char_code_for_key(type_: &str, key: &str) -> u32 {
+    if type_ == "keypress" && key.char_len() == 1 {
+        key.char_at(0) as u32
+    } else {
+        0
+    }
+}
+
+/// Applies what typing `key` does to the value of a text field, with the caret at the end of the
+/// value since fields don't keep a selection yet. Returns true if the value changed.
+pub fn edit_text_value(value: &mut String, key: &str, multiline: bool) -> bool {
+    match key {
+        "Backspace" => value.pop_char().is_some(),
+        "Enter" if multiline => {
+            value.push_char('\n');
+            true
+        }
+        _ if key.char_len() == 1 => {
+            value.push_str(key);
+            true
+        }
+        _ => false,
+    }
+}
+
+impl KeyboardEvent {
+    pub fn new_inherited() -> KeyboardEvent {
+        KeyboardEvent {
+            uievent: UIEvent::new_inherited(KeyboardEventTypeId),
+            key: Traceable::new(RefCell::new(DOMString::new())),
+            code: Traceable::new(RefCell::new(DOMString::new())),
+            location: Traceable::new(Cell::new(0)),
+            ctrl_key: Traceable::new(Cell::new(false)),
+            shift_key: Traceable::new(Cell::new(false)),
+            alt_key: Traceable::new(Cell::new(false)),
+            meta_key: Traceable::new(Cell::new(false)),
+            repeat: Traceable::new(Cell::new(false)),
+            is_composing: Traceable::new(Cell::new(false)),
+            char_code: Traceable::new(Cell::new(0)),
+            key_code: Traceable::new(Cell::new(0)),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<KeyboardEvent> {
+        reflect_dom_object(box KeyboardEvent::new_inherited(),
+                           &global::Window(window),
+                           KeyboardEventBinding::Wrap)
+    }
+
+    /// Creates a keyboard event. A `char_code` or `key_code` of `None` is derived from `key`,
+    /// which is what the script task wants when synthesizing events from compositor input.
+    pub fn new(window: &JSRef<Window>,
+               type_: DOMString,
+               can_bubble: bool,
+               cancelable: bool,
+               view: Option<JSRef<Window>>,
+               detail: i32,
+               key: DOMString,
+               code: DOMString,
+               location: u32,
+               repeat: bool,
+               is_composing: bool,
+               ctrl_key: bool,
+               alt_key: bool,
+               shift_key: bool,
+               meta_key: bool,
+               char_code: Option<u32>,
+               key_code: Option<u32>) -> Temporary<KeyboardEvent> {
+        let ev = KeyboardEvent::new_uninitialized(window).root();
+        let lossy_key = key.to_string_lossy();
+        let char_code = char_code.unwrap_or_else(|| {
+            char_code_for_key(type_.to_string_lossy().as_slice(), lossy_key.as_slice())
+        });
+        let key_code = key_code.unwrap_or_else(|| key_code_for_key(lossy_key.as_slice()));
+        {
+            let uievent: &JSRef<UIEvent> = UIEventCast::from_ref(&*ev);
+            uievent.InitUIEvent(type_, can_bubble, cancelable, view, detail);
+        }
+        *ev.deref().key.deref().borrow_mut() = key;
+        *ev.deref().code.deref().borrow_mut() = code;
+        ev.deref().location.deref().set(location);
+        ev.deref().repeat.deref().set(repeat);
+        ev.deref().is_composing.deref().set(is_composing);
+        ev.deref().ctrl_key.deref().set(ctrl_key);
+        ev.deref().alt_key.deref().set(alt_key);
+        ev.deref().shift_key.deref().set(shift_key);
+        ev.deref().meta_key.deref().set(meta_key);
+        ev.deref().char_code.deref().set(char_code);
+        ev.deref().key_code.deref().set(key_code);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &KeyboardEventBinding::KeyboardEventInit)
+                       -> Fallible<Temporary<KeyboardEvent>> {
+        let char_code = if init.charCode != 0 { Some(init.charCode) } else { None };
+        let key_code = if init.keyCode != 0 { Some(init.keyCode) } else { None };
+        let event = KeyboardEvent::new(global.as_window(), type_,
+                                       init.parent.parent.bubbles,
+                                       init.parent.parent.cancelable,
+                                       init.parent.view.root_ref(),
+                                       init.parent.detail,
+                                       init.key.clone(), init.code.clone(), init.location,
+                                       init.repeat, init.isComposing,
+                                       init.ctrlKey, init.altKey, init.shiftKey, init.metaKey,
+                                       char_code, key_code);
+        Ok(event)
+    }
+
+    /// Fires a trusted keydown, keypress or keyup event for `input` at `target`, and returns
+    /// false if a listener canceled it.
+    pub fn dispatch(target: &JSRef<EventTarget>, window: &JSRef<Window>, type_: &str,
+                    input: &KeyInput) -> bool {
+        let modifiers = &input.modifiers;
+        let keyboardevent = KeyboardEvent::new(window, DOMString::from_slice(type_), true, true,
+                                               Some(window.clone()), 0,
+                                               DOMString::from_string(input.key.clone()),
+                                               DOMString::from_string(input.code.clone()),
+                                               input.location, input.state == KeyRepeated, false,
+                                               modifiers.control, modifiers.alt,
+                                               modifiers.shift, modifiers.meta,
+                                               None, None).root();
+        let event: &JSRef<Event> = EventCast::from_ref(&*keyboardevent);
+        event.deref().trusted.deref().set(true);
+        target.dispatch_event_with_target(None, event).unwrap_or(true)
+    }
+}
+
+pub trait KeyboardEventMethods {
+    fn Key(&self) -> DOMString;
+    fn Code(&self) -> DOMString;
+    fn Location(&self) -> u32;
+    fn CtrlKey(&self) -> bool;
+    fn ShiftKey(&self) -> bool;
+    fn AltKey(&self) -> bool;
+    fn MetaKey(&self) -> bool;
+    fn Repeat(&self) -> bool;
+    fn IsComposing(&self) -> bool;
+    fn GetModifierState(&self, key_arg: DOMString) -> bool;
+    fn CharCode(&self) -> u32;
+    fn KeyCode(&self) -> u32;
+    fn Which(&self) -> u32;
+}
+
+impl<'a> KeyboardEventMethods for JSRef<'a, KeyboardEvent> {
+    fn Key(&self) -> DOMString {
+        self.key.deref().borrow().clone()
+    }
+
+    fn Code(&self) -> DOMString {
+        self.code.deref().borrow().clone()
+    }
+
+    fn Location(&self) -> u32 {
+        self.location.deref().get()
+    }
+
+    fn CtrlKey(&self) -> bool {
+        self.ctrl_key.deref().get()
+    }
+
+    fn ShiftKey(&self) -> bool {
+        self.shift_key.deref().get()
+    }
+
+    fn AltKey(&self) -> bool {
+        self.alt_key.deref().get()
+    }
+
+    fn MetaKey(&self) -> bool {
+        self.meta_key.deref().get()
+    }
+
+    fn Repeat(&self) -> bool {
+        self.repeat.deref().get()
+    }
+
+    fn IsComposing(&self) -> bool {
+        self.is_composing.deref().get()
+    }
+
+    // https://dvcs.w3.org/hg/dom3events/raw-file/tip/html/DOM3-Events.html#widl-KeyboardEvent-getModifierState
+    fn GetModifierState(&self, key_arg: DOMString) -> bool {
+        match key_arg.to_string_lossy().as_slice() {
+            "Control" => self.ctrl_key.deref().get(),
+            "Shift" => self.shift_key.deref().get(),
+            "Alt" => self.alt_key.deref().get(),
+            "Meta" => self.meta_key.deref().get(),
+            _ => false,
+        }
+    }
+
+    fn CharCode(&self) -> u32 {
+        self.char_code.deref().get()
+    }
+
+    fn KeyCode(&self) -> u32 {
+        self.key_code.deref().get()
+    }
+
+    // https://dvcs.w3.org/hg/dom3events/raw-file/tip/html/DOM3-Events.html#widl-KeyboardEvent-which
+    fn Which(&self) -> u32 {
+        if self.char_code.deref().get() != 0 {
+            self.char_code.deref().get()
+        } else {
+            self.key_code.deref().get()
+        }
+    }
+}
+
+impl Reflectable for KeyboardEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.uievent.reflector()
+    }
+}
+
+#[test]
+fn test_edit_text_value() {
+    let mut value = "ab".to_string();
+    assert!(edit_text_value(&mut value, "c", false));
+    assert_eq!(value.as_slice(), "abc");
+    assert!(edit_text_value(&mut value, "Backspace", false));
+    assert_eq!(value.as_slice(), "ab");
+
+    // Enter only adds a line to multi-line fields, and other named keys change nothing.
+    assert!(!edit_text_value(&mut value, "Enter", false));
+    assert!(edit_text_value(&mut value, "Enter", true));
+    assert_eq!(value.as_slice(), "ab\n");
+    assert!(!edit_text_value(&mut value, "ArrowLeft", true));
+    assert_eq!(value.as_slice(), "ab\n");
+
+    let mut empty = String::new();
+    assert!(!edit_text_value(&mut empty, "Backspace", false));
+    assert!(edit_text_value(&mut empty, "é", false));
+    assert_eq!(empty.as_slice(), "é");
+}
@@ -3,9 +3,10 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::LocationBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
-use dom::window::Window;
+use dom::window::{Window, WindowHelpers};
 use servo_util::str::DOMString;
 
 use page::Page;
@@ -30,18 +31,63 @@ impl Location {
 
     pub fn new(window: &JSRef<Window>, page: Rc<Page>) -> Temporary<Location> {
         reflect_dom_object(box Location::new_inherited(page),
-                           window,
+                           &global::Window(window),
                            LocationBinding::Wrap)
     }
 }
 
 pub trait LocationMethods {
+    fn Assign(&self, url: DOMString);
     fn Href(&self) -> DOMString;
+    fn Hash(&self) -> DOMString;
+    fn SetHash(&self, value: DOMString);
 }
 
 impl<'a> LocationMethods for JSRef<'a, Location> {
+    // http://www.whatwg.org/html/#dom-location-assign
+    fn Assign(&self, url: DOMString) {
+        let window = match *self.page.frame() {
+            Some(ref frame) => frame.window.root(),
+            None => return,
+        };
+        let url = url.to_string_lossy();
+        if !window.load_url(url.clone()) {
+            debug!("location.assign({:s}) was blocked by a beforeunload listener", url);
+        }
+    }
+
     fn Href(&self) -> DOMString {
-        self.page.get_url().to_str()
+        DOMString::from_string(self.page.get_url().to_str())
+    }
+
+    fn Hash(&self) -> DOMString {
+        match self.page.get_url().fragment {
+            Some(ref fragment) if !fragment.is_empty() => {
+                let mut hash = DOMString::from_slice("#");
+                hash.push_str(fragment.as_slice());
+                hash
+            }
+            _ => DOMString::new()
+        }
+    }
+
+    // http://url.spec.whatwg.org/#dom-url-hash
+    fn SetHash(&self, value: DOMString) {
+        let value = value.to_string_lossy();
+        let fragment = if value.as_slice().starts_with("#") {
+            value.as_slice().slice_from(1)
+        } else {
+            value.as_slice()
+        };
+        let mut href = "#".to_string();
+        href.push_str(fragment);
+        match *self.page.frame() {
+            // Fragment navigations never fire "beforeunload", so they can't be blocked.
+            Some(ref frame) => {
+                frame.window.root().load_url(href);
+            }
+            None => (),
+        }
     }
 }
 
@@ -0,0 +1,147 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::MessageEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, MessageEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, JSRef, RootedReference, Temporary, OptionalSettable};
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::bindings::trace::{MutHeapJSVal, Traceable};
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, MessageEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::window::Window;
+use js::jsapi::JSContext;
+use js::jsval::JSVal;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct MessageEvent {
+    event: Event,
+    data: MutHeapJSVal,
+    origin: Traceable<RefCell<DOMString>>,
+    last_event_id: Traceable<RefCell<DOMString>>,
+    /// The window that posted the message, if it is one this script task can reach.
+    source: Cell<Option<JS<Window>>>,
+}
+
+impl MessageEventDerived for Event {
+    fn is_messageevent(&self) -> bool {
+        self.type_id == MessageEventTypeId
+    }
+}
+
+impl MessageEvent {
+    pub fn new_inherited() -> MessageEvent {
+        MessageEvent {
+            event: Event::new_inherited(MessageEventTypeId),
+            data: MutHeapJSVal::new(),
+            origin: Traceable::new(RefCell::new(DOMString::new())),
+            last_event_id: Traceable::new(RefCell::new(DOMString::new())),
+            source: Cell::new(None),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<MessageEvent> {
+        reflect_dom_object(box MessageEvent::new_inherited(),
+                           &global::Window(window),
+                           MessageEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               data: JSVal, origin: DOMString, last_event_id: DOMString,
+               source: Option<JSRef<Window>>)
+               -> Temporary<MessageEvent> {
+        let ev = MessageEvent::new_uninitialized(window).root();
+        ev.deref().InitMessageEvent(window.deref().get_cx(), type_, bubbles, cancelable,
+                                    data, origin, last_event_id);
+        ev.deref().source.assign(source);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &MessageEventBinding::MessageEventInit)
+                       -> Fallible<Temporary<MessageEvent>> {
+        Ok(MessageEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                             init.data, init.origin.clone(), init.lastEventId.clone(),
+                             init.source.root_ref()))
+    }
+
+    /// Fires a trusted "message" event at `target`, as `postMessage` does, with a copy of `data`
+    /// read into `window`. `source` is the window that posted it.
+    pub fn dispatch(target: &JSRef<EventTarget>, window: &JSRef<Window>,
+                    data: &StructuredCloneData, origin: String,
+                    source: Option<JSRef<Window>>) {
+        // Create the event before reading the data: creating it can GC, whereas once the data is
+        // stored in the (rooted) event it is traced through it.
+        let messageevent = MessageEvent::new_uninitialized(window).root();
+        let data = data.read(&global::Window(window));
+        messageevent.deref().InitMessageEvent(window.deref().get_cx(),
+                                              DOMString::from_slice("message"), false, false,
+                                              data, DOMString::from_string(origin),
+                                              DOMString::new());
+        messageevent.deref().source.assign(source);
+        let event: &JSRef<Event> = EventCast::from_ref(&*messageevent);
+        event.deref().trusted.deref().set(true);
+        target.dispatch_event_with_target(None, event).ok();
+    }
+}
+
+pub trait MessageEventMethods {
+    fn Data(&self, _cx: *mut JSContext) -> JSVal;
+    fn Origin(&self) -> DOMString;
+    fn LastEventId(&self) -> DOMString;
+    fn GetSource(&self) -> Option<Temporary<Window>>;
+    fn InitMessageEvent(&self, _cx: *mut JSContext,
+                        type_: DOMString, can_bubble: bool, cancelable: bool,
+                        data: JSVal, origin: DOMString, last_event_id: DOMString);
+}
+
+impl<'a> MessageEventMethods for JSRef<'a, MessageEvent> {
+    fn Data(&self, _cx: *mut JSContext) -> JSVal {
+        self.data.get()
+    }
+
+    fn Origin(&self) -> DOMString {
+        self.origin.deref().borrow().clone()
+    }
+
+    fn LastEventId(&self) -> DOMString {
+        self.last_event_id.deref().borrow().clone()
+    }
+
+    fn GetSource(&self) -> Option<Temporary<Window>> {
+        self.source.get().map(|source| Temporary::new(source))
+    }
+
+    fn InitMessageEvent(&self,
+                        cx: *mut JSContext,
+                        type_: DOMString,
+                        can_bubble: bool,
+                        cancelable: bool,
+                        data: JSVal,
+                        origin: DOMString,
+                        last_event_id: DOMString) {
+        let event: &JSRef<Event> = EventCast::from_ref(self);
+        if event.deref().dispatching.deref().get() {
+            return;
+        }
+        self.data.set(cx, data);
+        *self.origin.deref().borrow_mut() = origin;
+        *self.last_event_id.deref().borrow_mut() = last_event_id;
+        event.InitEvent(type_, can_bubble, cancelable);
+    }
+}
+
+impl Reflectable for MessageEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -3,59 +3,72 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::MouseEventBinding;
-use dom::bindings::codegen::InheritTypes::{UIEventCast, MouseEventDerived};
+use dom::bindings::codegen::InheritTypes::{EventCast, UIEventCast, MouseEventDerived};
 use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, RootedReference, Temporary, OptionalSettable};
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
-use dom::event::{Event, MouseEventTypeId};
-use dom::eventtarget::EventTarget;
+use dom::event::{Event, EventTypeId, MouseEventTypeId, WheelEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
 use dom::uievent::{UIEvent, UIEventMethods};
 use dom::window::Window;
+use servo_msg::constellation_msg::MouseInput;
 use servo_util::str::DOMString;
 use std::cell::Cell;
 
 #[deriving(Encodable)]
 pub struct MouseEvent {
-    pub mouseevent: UIEvent,
+    pub uievent: UIEvent,
     pub screen_x: Traceable<Cell<i32>>,
     pub screen_y: Traceable<Cell<i32>>,
     pub client_x: Traceable<Cell<i32>>,
     pub client_y: Traceable<Cell<i32>>,
+    pub page_x: Traceable<Cell<i32>>,
+    pub page_y: Traceable<Cell<i32>>,
     pub ctrl_key: Traceable<Cell<bool>>,
     pub shift_key: Traceable<Cell<bool>>,
     pub alt_key: Traceable<Cell<bool>>,
     pub meta_key: Traceable<Cell<bool>>,
     pub button: Traceable<Cell<i16>>,
+    pub buttons: Traceable<Cell<u16>>,
     pub related_target: Cell<Option<JS<EventTarget>>>
 }
 
 impl MouseEventDerived for Event {
     fn is_mouseevent(&self) -> bool {
-        self.type_id == MouseEventTypeId
+        // Interfaces inheriting from MouseEvent need to be listed here too.
+        match self.type_id {
+            MouseEventTypeId | WheelEventTypeId => true,
+            _ => false
+        }
     }
 }
 
 impl MouseEvent {
-    pub fn new_inherited() -> MouseEvent {
+    pub fn new_inherited(type_id: EventTypeId) -> MouseEvent {
         MouseEvent {
-            mouseevent: UIEvent::new_inherited(MouseEventTypeId),
+            uievent: UIEvent::new_inherited(type_id),
             screen_x: Traceable::new(Cell::new(0)),
             screen_y: Traceable::new(Cell::new(0)),
             client_x: Traceable::new(Cell::new(0)),
             client_y: Traceable::new(Cell::new(0)),
+            page_x: Traceable::new(Cell::new(0)),
+            page_y: Traceable::new(Cell::new(0)),
             ctrl_key: Traceable::new(Cell::new(false)),
             shift_key: Traceable::new(Cell::new(false)),
             alt_key: Traceable::new(Cell::new(false)),
             meta_key: Traceable::new(Cell::new(false)),
             button: Traceable::new(Cell::new(0)),
+            buttons: Traceable::new(Cell::new(0)),
             related_target: Cell::new(None)
         }
     }
 
     pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<MouseEvent> {
-        reflect_dom_object(box MouseEvent::new_inherited(),
-                           window,
+        reflect_dom_object(box MouseEvent::new_inherited(MouseEventTypeId),
+                           &global::Window(window),
                            MouseEventBinding::Wrap)
     }
 
@@ -74,28 +87,53 @@ impl MouseEvent {
                shiftKey: bool,
                metaKey: bool,
                button: i16,
+               buttons: u16,
                relatedTarget: Option<JSRef<EventTarget>>) -> Temporary<MouseEvent> {
         let ev = MouseEvent::new_uninitialized(window).root();
         ev.deref().InitMouseEvent(type_, canBubble, cancelable, view, detail,
                                   screenX, screenY, clientX, clientY,
                                   ctrlKey, altKey, shiftKey, metaKey,
                                   button, relatedTarget);
+        // initMouseEvent predates the buttons attribute, so it has to be set separately.
+        ev.deref().buttons.deref().set(buttons);
         Temporary::from_rooted(&*ev)
     }
 
-    pub fn Constructor(owner: &JSRef<Window>,
+    pub fn Constructor(global: &GlobalRef,
                        type_: DOMString,
                        init: &MouseEventBinding::MouseEventInit) -> Fallible<Temporary<MouseEvent>> {
-        let event = MouseEvent::new(owner, type_, init.parent.parent.bubbles,
+        let event = MouseEvent::new(global.as_window(), type_, init.parent.parent.bubbles,
                                     init.parent.parent.cancelable,
                                     init.parent.view.root_ref(),
                                     init.parent.detail,
                                     init.screenX, init.screenY,
                                     init.clientX, init.clientY, init.ctrlKey,
                                     init.altKey, init.shiftKey, init.metaKey,
-                                    init.button, init.relatedTarget.root_ref());
+                                    init.button, init.buttons,
+                                    init.relatedTarget.root_ref());
         Ok(event)
     }
+
+    /// Fires a trusted mouse event of type `type_` at `target`, describing the user input in
+    /// `input`. `detail` is the click count, and `related_target` the element the pointer moved
+    /// from or to, for `mouseover` and `mouseout`. Returns true if a listener prevented the default
+    /// action.
+    pub fn dispatch(target: &JSRef<EventTarget>, window: &JSRef<Window>, type_: &str,
+                    input: &MouseInput, detail: i32,
+                    related_target: Option<JSRef<EventTarget>>) -> bool {
+        let modifiers = &input.modifiers;
+        let mouseevent = MouseEvent::new(window, DOMString::from_slice(type_), true, true,
+                                         Some(window.clone()), detail,
+                                         input.screen_point.x as i32, input.screen_point.y as i32,
+                                         input.client_point.x as i32, input.client_point.y as i32,
+                                         modifiers.control, modifiers.alt,
+                                         modifiers.shift, modifiers.meta,
+                                         input.button, input.buttons, related_target).root();
+        mouseevent.deref().page_x.deref().set(input.page_point.x as i32);
+        mouseevent.deref().page_y.deref().set(input.page_point.y as i32);
+        let event: &JSRef<Event> = EventCast::from_ref(&*mouseevent);
+        target.fire_trusted_event(None, event)
+    }
 }
 
 pub trait MouseEventMethods {
@@ -108,7 +146,11 @@ pub trait MouseEventMethods {
     fn AltKey(&self) -> bool;
     fn MetaKey(&self) -> bool;
     fn Button(&self) -> i16;
+    fn Buttons(&self) -> u16;
     fn GetRelatedTarget(&self) -> Option<Temporary<EventTarget>>;
+    fn GetModifierState(&self, keyArg: DOMString) -> bool;
+    fn PageX(&self) -> i32;
+    fn PageY(&self) -> i32;
     fn InitMouseEvent(&self,
                       typeArg: DOMString,
                       canBubbleArg: bool,
@@ -164,10 +206,32 @@ impl<'a> MouseEventMethods for JSRef<'a, MouseEvent> {
         self.button.deref().get()
     }
 
+    fn Buttons(&self) -> u16 {
+        self.buttons.deref().get()
+    }
+
     fn GetRelatedTarget(&self) -> Option<Temporary<EventTarget>> {
         self.related_target.get().clone().map(|target| Temporary::new(target))
     }
 
+    fn GetModifierState(&self, keyArg: DOMString) -> bool {
+        match keyArg.to_string_lossy().as_slice() {
+            "Control" => self.ctrl_key.deref().get(),
+            "Shift" => self.shift_key.deref().get(),
+            "Alt" => self.alt_key.deref().get(),
+            "Meta" => self.meta_key.deref().get(),
+            _ => false,
+        }
+    }
+
+    fn PageX(&self) -> i32 {
+        self.page_x.deref().get()
+    }
+
+    fn PageY(&self) -> i32 {
+        self.page_y.deref().get()
+    }
+
     fn InitMouseEvent(&self,
                       typeArg: DOMString,
                       canBubbleArg: bool,
@@ -184,12 +248,19 @@ impl<'a> MouseEventMethods for JSRef<'a, MouseEvent> {
                       metaKeyArg: bool,
                       buttonArg: i16,
                       relatedTargetArg: Option<JSRef<EventTarget>>) {
+        let event: &JSRef<Event> = EventCast::from_ref(self);
+        if event.deref().dispatching.deref().get() {
+            return;
+        }
         let uievent: &JSRef<UIEvent> = UIEventCast::from_ref(self);
         uievent.InitUIEvent(typeArg, canBubbleArg, cancelableArg, viewArg, detailArg);
         self.screen_x.deref().set(screenXArg);
         self.screen_y.deref().set(screenYArg);
         self.client_x.deref().set(clientXArg);
         self.client_y.deref().set(clientYArg);
+        // Synthetic events know nothing of scrolling, so their page and client coordinates agree.
+        self.page_x.deref().set(clientXArg);
+        self.page_y.deref().set(clientYArg);
         self.ctrl_key.deref().set(ctrlKeyArg);
         self.alt_key.deref().set(altKeyArg);
         self.shift_key.deref().set(shiftKeyArg);
@@ -202,6 +273,6 @@ impl<'a> MouseEventMethods for JSRef<'a, MouseEvent> {
 
 impl Reflectable for MouseEvent {
     fn reflector<'a>(&'a self) -> &'a Reflector {
-        self.mouseevent.reflector()
+        self.uievent.reflector()
     }
 }
@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::NavigatorBinding;
+use dom::bindings::global;
 use dom::bindings::js::{JSRef, Temporary};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::window::Window;
@@ -22,7 +23,7 @@ impl Navigator {
 
     pub fn new(window: &JSRef<Window>) -> Temporary<Navigator> {
         reflect_dom_object(box Navigator::new_inherited(),
-                           window,
+                           &global::Window(window),
                            NavigatorBinding::Wrap)
     }
 }
@@ -37,7 +38,7 @@ pub trait NavigatorMethods {
 
 impl<'a> NavigatorMethods for JSRef<'a, Navigator> {
     fn Product(&self) -> DOMString {
-        "Gecko".to_string()
+        DOMString::from_slice("Gecko")
     }
 
     fn TaintEnabled(&self) -> bool {
@@ -45,15 +46,15 @@ impl<'a> NavigatorMethods for JSRef<'a, Navigator> {
     }
 
     fn AppName(&self) -> DOMString {
-        "Netscape".to_string() // Like Gecko/Webkit
+        DOMString::from_slice("Netscape") // Like Gecko/Webkit
     }
 
     fn AppCodeName(&self) -> DOMString {
-        "Mozilla".to_string()
+        DOMString::from_slice("Mozilla")
     }
 
     fn Platform(&self) -> DOMString {
-        "".to_string()
+        DOMString::new()
     }
 }
 
@@ -5,13 +5,15 @@
 //! The core DOM types. Defines the basic DOM hierarchy as well as all the HTML elements.
 
 use cssparser::tokenize;
-use dom::attr::{Attr, AttrMethods};
+use dom::attr::Attr;
 use dom::bindings::codegen::InheritTypes::{CommentCast, DocumentCast, DocumentTypeCast};
 use dom::bindings::codegen::InheritTypes::{ElementCast, TextCast, NodeCast, ElementDerived};
 use dom::bindings::codegen::InheritTypes::{CharacterDataCast, NodeBase, NodeDerived};
 use dom::bindings::codegen::InheritTypes::{ProcessingInstructionCast, EventTargetCast};
 use dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
 use dom::bindings::error::{ErrorResult, Fallible, NotFound, HierarchyRequest, Syntax};
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, RootedReference, Temporary, Root, OptionalUnrootable};
 use dom::bindings::js::{OptionalSettable, TemporaryPushable, OptionalRootedRootable};
 use dom::bindings::js::{ResultRootable, OptionalRootable};
@@ -36,7 +38,7 @@ use html::hubbub_html_parser::build_element_from_tag;
 use layout_interface::{ContentBoxQuery, ContentBoxResponse, ContentBoxesQuery, ContentBoxesResponse,
                        LayoutChan, ReapLayoutDataMsg, TrustedNodeAddress, UntrustedNodeAddress};
 use servo_util::geometry::Au;
-use servo_util::str::{DOMString, null_str_as_empty};
+use servo_util::str::DOMString;
 use style::{parse_selector_list, matches_compound_selector, NamespaceMap};
 
 use js::jsapi::{JSContext, JSObject, JSRuntime};
@@ -392,8 +394,8 @@ pub trait NodeHelpers {
     fn get_bounding_content_box(&self) -> Rect<Au>;
     fn get_content_boxes(&self) -> Vec<Rect<Au>>;
 
-    fn query_selector(&self, selectors: DOMString) -> Fallible<Option<Temporary<Element>>>;
-    fn query_selector_all(&self, selectors: DOMString) -> Fallible<Temporary<NodeList>>;
+    fn query_selector(&self, selectors: String) -> Fallible<Option<Temporary<Element>>>;
+    fn query_selector_all(&self, selectors: String) -> Fallible<Temporary<NodeList>>;
 
     fn remove_self(&self);
 }
@@ -556,7 +558,7 @@ impl<'a> NodeHelpers for JSRef<'a, Node> {
     }
 
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselector
-    fn query_selector(&self, selectors: DOMString) -> Fallible<Option<Temporary<Element>>> {
+    fn query_selector(&self, selectors: String) -> Fallible<Option<Temporary<Element>>> {
         // Step 1.
         let namespace = NamespaceMap::new();
         match parse_selector_list(tokenize(selectors.as_slice()).map(|(token, _)| token).collect(), &namespace) {
@@ -569,7 +571,8 @@ impl<'a> NodeHelpers for JSRef<'a, Node> {
                     assert!(selector.pseudo_element.is_none());
                     for node in root.traverse_preorder().filter(|node| node.is_element()) {
                         let mut _shareable: bool = false;
-                        if matches_compound_selector(selector.compound_selectors.deref(), &node, &mut _shareable) {
+                        if matches_compound_selector(selector.compound_selectors.deref(), &node, None,
+                                                     &mut _shareable) {
                             let elem: &JSRef<Element> = ElementCast::to_ref(&node).unwrap();
                             return Ok(Some(Temporary::from_rooted(elem)));
                         }
@@ -581,7 +584,7 @@ impl<'a> NodeHelpers for JSRef<'a, Node> {
     }
 
     // http://dom.spec.whatwg.org/#dom-parentnode-queryselectorall
-    fn query_selector_all(&self, selectors: DOMString) -> Fallible<Temporary<NodeList>> {
+    fn query_selector_all(&self, selectors: String) -> Fallible<Temporary<NodeList>> {
         // Step 1.
         let mut nodes = vec!();
         let root = self.ancestors().last().unwrap_or(self.clone());
@@ -595,7 +598,8 @@ impl<'a> NodeHelpers for JSRef<'a, Node> {
                     assert!(selector.pseudo_element.is_none());
                     for node in root.traverse_preorder().filter(|node| node.is_element()) {
                         let mut _shareable: bool = false;
-                        if matches_compound_selector(selector.compound_selectors.deref(), &node, &mut _shareable) {
+                        if matches_compound_selector(selector.compound_selectors.deref(), &node, None,
+                                                     &mut _shareable) {
                             nodes.push(node.clone())
                         }
                     }
@@ -907,11 +911,11 @@ impl Node {
     pub fn reflect_node<N: Reflectable+NodeBase>
             (node:      Box<N>,
              document:  &JSRef<Document>,
-             wrap_fn:   extern "Rust" fn(*mut JSContext, &JSRef<Window>, Box<N>) -> JS<N>)
+             wrap_fn:   extern "Rust" fn(*mut JSContext, &GlobalRef, Box<N>) -> JS<N>)
              -> Temporary<N> {
         assert!(node.reflector().get_jsobject().is_null());
         let window = document.deref().window.root();
-        let node = reflect_dom_object(node, &window.root_ref(), wrap_fn).root();
+        let node = reflect_dom_object(node, &global::Window(&window.root_ref()), wrap_fn).root();
         assert!(node.deref().reflector().get_jsobject().is_not_null());
         Temporary::from_rooted(&*node)
     }
@@ -1275,7 +1279,7 @@ impl Node {
             ElementNodeTypeId(..) => {
                 let element: &JSRef<Element> = ElementCast::to_ref(node).unwrap();
                 let element = element.deref();
-                let element = build_element_from_tag(element.local_name.clone(),
+                let element = build_element_from_tag(element.local_name.as_slice().to_string(),
                     element.namespace.clone(), &*document);
                 NodeCast::from_temporary(element)
             },
@@ -1410,19 +1414,19 @@ impl<'a> NodeMethods for JSRef<'a, Node> {
                 let elem: &JSRef<Element> = ElementCast::to_ref(self).unwrap();
                 elem.TagName()
             }
-            TextNodeTypeId => "#text".to_string(),
+            TextNodeTypeId => DOMString::from_slice("#text"),
             ProcessingInstructionNodeTypeId => {
                 let processing_instruction: &JSRef<ProcessingInstruction> =
                     ProcessingInstructionCast::to_ref(self).unwrap();
                 processing_instruction.Target()
             }
-            CommentNodeTypeId => "#comment".to_string(),
+            CommentNodeTypeId => DOMString::from_slice("#comment"),
             DoctypeNodeTypeId => {
                 let doctype: &JSRef<DocumentType> = DocumentTypeCast::to_ref(self).unwrap();
-                doctype.deref().name.clone()
+                DOMString::from_slice(doctype.deref().name.as_slice())
             },
-            DocumentFragmentNodeTypeId => "#document-fragment".to_string(),
-            DocumentNodeTypeId => "#document".to_string()
+            DocumentFragmentNodeTypeId => DOMString::from_slice("#document-fragment"),
+            DocumentNodeTypeId => DOMString::from_slice("#document")
         }
     }
 
@@ -1532,11 +1536,11 @@ impl<'a> NodeMethods for JSRef<'a, Node> {
         match self.type_id {
             DocumentFragmentNodeTypeId |
             ElementNodeTypeId(..) => {
-                let mut content = String::new();
+                let mut content = DOMString::new();
                 for node in self.traverse_preorder() {
                     if node.is_text() {
                         let text: &JSRef<Text> = TextCast::to_ref(&node).unwrap();
-                        content.push_str(text.deref().characterdata.data.deref().borrow().as_slice());
+                        content.push_domstring(&*text.deref().characterdata.data.deref().borrow());
                     }
                 }
                 Some(content)
@@ -1556,7 +1560,7 @@ impl<'a> NodeMethods for JSRef<'a, Node> {
 
     // http://dom.spec.whatwg.org/#dom-node-textcontent
     fn SetTextContent(&self, value: Option<DOMString>) -> ErrorResult {
-        let value = null_str_as_empty(&value);
+        let value = value.unwrap_or(DOMString::new());
         match self.type_id {
             DocumentFragmentNodeTypeId |
             ElementNodeTypeId(..) => {
@@ -1988,7 +1992,7 @@ impl<'a> style::TNode<JSRef<'a, Element>> for JSRef<'a, Node> {
         match attr.namespace {
             style::SpecificNamespace(ref ns) => {
                 self.as_element().get_attribute(ns.clone(), name).root()
-                    .map_or(false, |attr| test(attr.deref().Value().as_slice()))
+                    .map_or(false, |attr| test(attr.deref().value().as_slice()))
             },
             // FIXME: https://github.com/mozilla/servo/issues/1558
             style::AnyNamespace => false,
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PopStateEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, PopStateEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::MutHeapJSVal;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, PopStateEventTypeId};
+use dom::window::Window;
+use js::jsapi::JSContext;
+use js::jsval::JSVal;
+use servo_util::str::DOMString;
+
+#[deriving(Encodable)]
+pub struct PopStateEvent {
+    event: Event,
+    state: MutHeapJSVal,
+}
+
+impl PopStateEventDerived for Event {
+    fn is_popstateevent(&self) -> bool {
+        self.type_id == PopStateEventTypeId
+    }
+}
+
+impl PopStateEvent {
+    pub fn new_inherited() -> PopStateEvent {
+        PopStateEvent {
+            event: Event::new_inherited(PopStateEventTypeId),
+            state: MutHeapJSVal::new(),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<PopStateEvent> {
+        reflect_dom_object(box PopStateEvent::new_inherited(),
+                           &global::Window(window),
+                           PopStateEventBinding::Wrap)
+    }
+
+    /// Creates a PopStateEvent. Session history uses this when traversing to an entry
+    /// that has a state object.
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool, state: JSVal) -> Temporary<PopStateEvent> {
+        let ev = PopStateEvent::new_uninitialized(window).root();
+        ev.deref().state.set(window.get_cx(), state);
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &PopStateEventBinding::PopStateEventInit)
+                       -> Fallible<Temporary<PopStateEvent>> {
+        Ok(PopStateEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                              init.state))
+    }
+}
+
+pub trait PopStateEventMethods {
+    fn State(&self, _cx: *mut JSContext) -> JSVal;
+}
+
+impl<'a> PopStateEventMethods for JSRef<'a, PopStateEvent> {
+    fn State(&self, _cx: *mut JSContext) -> JSVal {
+        self.state.get()
+    }
+}
+
+impl Reflectable for PopStateEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -16,7 +16,7 @@ use servo_util::str::DOMString;
 #[deriving(Encodable)]
 pub struct ProcessingInstruction {
     pub characterdata: CharacterData,
-    pub target: DOMString,
+    pub target: String,
 }
 
 impl ProcessingInstructionDerived for EventTarget {
@@ -26,14 +26,14 @@ impl ProcessingInstructionDerived for EventTarget {
 }
 
 impl ProcessingInstruction {
-    pub fn new_inherited(target: DOMString, data: DOMString, document: &JSRef<Document>) -> ProcessingInstruction {
+    pub fn new_inherited(target: String, data: DOMString, document: &JSRef<Document>) -> ProcessingInstruction {
         ProcessingInstruction {
             characterdata: CharacterData::new_inherited(ProcessingInstructionNodeTypeId, data, document),
             target: target
         }
     }
 
-    pub fn new(target: DOMString, data: DOMString, document: &JSRef<Document>) -> Temporary<ProcessingInstruction> {
+    pub fn new(target: String, data: DOMString, document: &JSRef<Document>) -> Temporary<ProcessingInstruction> {
         let node = ProcessingInstruction::new_inherited(target, data, document);
         Node::reflect_node(box node, document, ProcessingInstructionBinding::Wrap)
     }
@@ -45,7 +45,7 @@ pub trait ProcessingInstructionMethods {
 
 impl<'a> ProcessingInstructionMethods for JSRef<'a, ProcessingInstruction> {
     fn Target(&self) -> DOMString {
-        self.target.clone()
+        DOMString::from_slice(self.target.as_slice())
     }
 }
 
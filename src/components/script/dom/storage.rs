@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::StorageBinding;
+use dom::bindings::global;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::RefCell;
+
+/// A storage area: a list of key/value pairs, in the order the keys were added. Only session
+/// storage exists so far, and it lives as long as its window.
+#[deriving(Encodable)]
+pub struct Storage {
+    pub reflector_: Reflector,
+    items: Traceable<RefCell<Vec<(DOMString, DOMString)>>>,
+}
+
+impl Storage {
+    pub fn new_inherited() -> Storage {
+        Storage {
+            reflector_: Reflector::new(),
+            items: Traceable::new(RefCell::new(vec!())),
+        }
+    }
+
+    pub fn new(window: &JSRef<Window>) -> Temporary<Storage> {
+        reflect_dom_object(box Storage::new_inherited(),
+                           &global::Window(window),
+                           StorageBinding::Wrap)
+    }
+}
+
+pub trait StorageMethods {
+    fn Length(&self) -> u32;
+    fn Key(&self, index: u32) -> Option<DOMString>;
+    fn GetItem(&self, key: DOMString) -> Option<DOMString>;
+    fn SetItem(&self, key: DOMString, value: DOMString);
+    fn RemoveItem(&self, key: DOMString);
+    fn Clear(&self);
+}
+
+impl<'a> StorageMethods for JSRef<'a, Storage> {
+    fn Length(&self) -> u32 {
+        self.items.deref().borrow().len() as u32
+    }
+
+    fn Key(&self, index: u32) -> Option<DOMString> {
+        let items = self.items.deref().borrow();
+        if (index as uint) < items.len() {
+            Some(items.get(index as uint).ref0().clone())
+        } else {
+            None
+        }
+    }
+
+    fn GetItem(&self, key: DOMString) -> Option<DOMString> {
+        let items = self.items.deref().borrow();
+        items.iter().find(|&&(ref k, _)| *k == key).map(|&(_, ref value)| value.clone())
+    }
+
+    fn SetItem(&self, key: DOMString, value: DOMString) {
+        let mut items = self.items.deref().borrow_mut();
+        match items.iter().position(|&(ref k, _)| *k == key) {
+            Some(index) => *items.get_mut(index).mut1() = value,
+            None => items.push((key, value)),
+        }
+    }
+
+    fn RemoveItem(&self, key: DOMString) {
+        let mut items = self.items.deref().borrow_mut();
+        match items.iter().position(|&(ref k, _)| *k == key) {
+            Some(index) => drop(items.remove(index)),
+            None => {}
+        }
+    }
+
+    fn Clear(&self) {
+        self.items.deref().borrow_mut().clear();
+    }
+}
+
+impl Reflectable for Storage {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        &self.reflector_
+    }
+}
@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::StorageEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, StorageEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, JSRef, OptionalSettable, RootedReference, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, StorageEventTypeId};
+use dom::storage::Storage;
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct StorageEvent {
+    event: Event,
+    /// None when the change was a `clear()`, as opposed to a change to the key "".
+    key: Traceable<RefCell<Option<DOMString>>>,
+    /// None when the key was newly added.
+    old_value: Traceable<RefCell<Option<DOMString>>>,
+    /// None when the key was removed.
+    new_value: Traceable<RefCell<Option<DOMString>>>,
+    url: Traceable<RefCell<DOMString>>,
+    /// The storage area that changed.
+    storage_area: Cell<Option<JS<Storage>>>,
+}
+
+impl StorageEventDerived for Event {
+    fn is_storageevent(&self) -> bool {
+        self.type_id == StorageEventTypeId
+    }
+}
+
+impl StorageEvent {
+    pub fn new_inherited() -> StorageEvent {
+        StorageEvent {
+            event: Event::new_inherited(StorageEventTypeId),
+            key: Traceable::new(RefCell::new(None)),
+            old_value: Traceable::new(RefCell::new(None)),
+            new_value: Traceable::new(RefCell::new(None)),
+            url: Traceable::new(RefCell::new(DOMString::new())),
+            storage_area: Cell::new(None),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<StorageEvent> {
+        reflect_dom_object(box StorageEvent::new_inherited(),
+                           &global::Window(window),
+                           StorageEventBinding::Wrap)
+    }
+
+    /// Creates a StorageEvent. Storage uses this to notify the other windows of an
+    /// origin about a change to their shared storage area.
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               key: Option<DOMString>, old_value: Option<DOMString>,
+               new_value: Option<DOMString>, url: DOMString,
+               storage_area: Option<JSRef<Storage>>) -> Temporary<StorageEvent> {
+        let ev = StorageEvent::new_uninitialized(window).root();
+        ev.deref().InitStorageEvent(type_, bubbles, cancelable,
+                                    key, old_value, new_value, url, storage_area);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &StorageEventBinding::StorageEventInit)
+                       -> Fallible<Temporary<StorageEvent>> {
+        Ok(StorageEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                             init.key.clone(), init.oldValue.clone(),
+                             init.newValue.clone(), init.url.clone(),
+                             init.storageArea.root_ref()))
+    }
+}
+
+pub trait StorageEventMethods {
+    fn GetKey(&self) -> Option<DOMString>;
+    fn GetOldValue(&self) -> Option<DOMString>;
+    fn GetNewValue(&self) -> Option<DOMString>;
+    fn Url(&self) -> DOMString;
+    fn GetStorageArea(&self) -> Option<Temporary<Storage>>;
+    fn InitStorageEvent(&self, type_: DOMString, can_bubble: bool, cancelable: bool,
+                        key: Option<DOMString>, old_value: Option<DOMString>,
+                        new_value: Option<DOMString>, url: DOMString,
+                        storage_area: Option<JSRef<Storage>>);
+}
+
+impl<'a> StorageEventMethods for JSRef<'a, StorageEvent> {
+    fn GetKey(&self) -> Option<DOMString> {
+        self.key.deref().borrow().clone()
+    }
+
+    fn GetOldValue(&self) -> Option<DOMString> {
+        self.old_value.deref().borrow().clone()
+    }
+
+    fn GetNewValue(&self) -> Option<DOMString> {
+        self.new_value.deref().borrow().clone()
+    }
+
+    fn Url(&self) -> DOMString {
+        self.url.deref().borrow().clone()
+    }
+
+    fn GetStorageArea(&self) -> Option<Temporary<Storage>> {
+        self.storage_area.get().clone().map(|storage_area| Temporary::new(storage_area))
+    }
+
+    fn InitStorageEvent(&self,
+                        type_: DOMString,
+                        can_bubble: bool,
+                        cancelable: bool,
+                        key: Option<DOMString>,
+                        old_value: Option<DOMString>,
+                        new_value: Option<DOMString>,
+                        url: DOMString,
+                        storage_area: Option<JSRef<Storage>>) {
+        let event: &JSRef<Event> = EventCast::from_ref(self);
+        if event.deref().dispatching.deref().get() {
+            return;
+        }
+        *self.key.deref().borrow_mut() = key;
+        *self.old_value.deref().borrow_mut() = old_value;
+        *self.new_value.deref().borrow_mut() = new_value;
+        *self.url.deref().borrow_mut() = url;
+        self.storage_area.assign(storage_area);
+        event.InitEvent(type_, can_bubble, cancelable);
+    }
+}
+
+impl Reflectable for StorageEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
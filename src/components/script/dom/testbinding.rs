@@ -48,7 +48,7 @@ pub trait TestBindingMethods {
     fn SetFloatAttribute(&self, _: f32) {}
     fn DoubleAttribute(&self) -> f64 { 0. }
     fn SetDoubleAttribute(&self, _: f64) {}
-    fn StringAttribute(&self) -> DOMString { "".to_string() }
+    fn StringAttribute(&self) -> DOMString { DOMString::new() }
     fn SetStringAttribute(&self, _: DOMString) {}
     fn ByteStringAttribute(&self) -> ByteString { ByteString::new(vec!()) }
     fn SetByteStringAttribute(&self, _: ByteString) {}
@@ -58,7 +58,7 @@ pub trait TestBindingMethods {
     fn SetInterfaceAttribute(&self, _: &JSRef<Blob>) {}
     fn UnionAttribute(&self) -> HTMLElementOrLong { eLong(0) }
     fn SetUnionAttribute(&self, _: HTMLElementOrLong) {}
-    fn Union2Attribute(&self) -> EventOrString { eString("".to_string()) }
+    fn Union2Attribute(&self) -> EventOrString { eString(DOMString::new()) }
     fn SetUnion2Attribute(&self, _: EventOrString) {}
     fn AnyAttribute(&self, _: *mut JSContext) -> JSVal { NullValue() }
     fn SetAnyAttribute(&self, _: *mut JSContext, _: JSVal) {}
@@ -87,14 +87,14 @@ pub trait TestBindingMethods {
     fn SetDoubleAttributeNullable(&self, _: Option<f64>) {}
     fn GetByteStringAttributeNullable(&self) -> Option<ByteString> { Some(ByteString::new(vec!())) }
     fn SetByteStringAttributeNullable(&self, _: Option<ByteString>) {}
-    fn GetStringAttributeNullable(&self) -> Option<DOMString> { Some("".to_string()) }
+    fn GetStringAttributeNullable(&self) -> Option<DOMString> { Some(DOMString::new()) }
     fn SetStringAttributeNullable(&self, _: Option<DOMString>) {}
     fn GetEnumAttributeNullable(&self) -> Option<TestEnum> { Some(_empty) }
     fn GetInterfaceAttributeNullable(&self) -> Option<Temporary<Blob>>;
     fn SetInterfaceAttributeNullable(&self, _: Option<JSRef<Blob>>) {}
     fn GetUnionAttributeNullable(&self) -> Option<HTMLElementOrLong> { Some(eLong(0)) }
     fn SetUnionAttributeNullable(&self, _: Option<HTMLElementOrLong>) {}
-    fn GetUnion2AttributeNullable(&self) -> Option<EventOrString> { Some(eString("".to_string())) }
+    fn GetUnion2AttributeNullable(&self) -> Option<EventOrString> { Some(eString(DOMString::new())) }
     fn SetUnion2AttributeNullable(&self, _: Option<EventOrString>) {}
     fn ReceiveVoid(&self) -> () {}
     fn ReceiveBoolean(&self) -> bool { false }
@@ -108,13 +108,13 @@ pub trait TestBindingMethods {
     fn ReceiveUnsignedLongLong(&self) -> u64 { 0 }
     fn ReceiveFloat(&self) -> f32 { 0. }
     fn ReceiveDouble(&self) -> f64 { 0. }
-    fn ReceiveString(&self) -> DOMString { "".to_string() }
+    fn ReceiveString(&self) -> DOMString { DOMString::new() }
     fn ReceiveByteString(&self) -> ByteString { ByteString::new(vec!()) }
     fn ReceiveEnum(&self) -> TestEnum { _empty }
     fn ReceiveInterface(&self) -> Temporary<Blob>;
     fn ReceiveAny(&self, _: *mut JSContext) -> JSVal { NullValue() }
     fn ReceiveUnion(&self) -> HTMLElementOrLong { eLong(0) }
-    fn ReceiveUnion2(&self) -> EventOrString { eString("".to_string()) }
+    fn ReceiveUnion2(&self) -> EventOrString { eString(DOMString::new()) }
 
     fn ReceiveNullableBoolean(&self) -> Option<bool> { Some(false) }
     fn ReceiveNullableByte(&self) -> Option<i8> { Some(0) }
@@ -127,13 +127,13 @@ pub trait TestBindingMethods {
     fn ReceiveNullableUnsignedLongLong(&self) -> Option<u64> { Some(0) }
     fn ReceiveNullableFloat(&self) -> Option<f32> { Some(0.) }
     fn ReceiveNullableDouble(&self) -> Option<f64> { Some(0.) }
-    fn ReceiveNullableString(&self) -> Option<DOMString> { Some("".to_string()) }
+    fn ReceiveNullableString(&self) -> Option<DOMString> { Some(DOMString::new()) }
     fn ReceiveNullableByteString(&self) -> Option<ByteString> { Some(ByteString::new(vec!())) }
     fn ReceiveNullableEnum(&self) -> Option<TestEnum> { Some(_empty) }
     fn ReceiveNullableInterface(&self) -> Option<Temporary<Blob>>;
     fn ReceiveNullableAny(&self, _: *mut JSContext) -> Option<JSVal> { Some(NullValue()) }
     fn ReceiveNullableUnion(&self) -> Option<HTMLElementOrLong> { Some(eLong(0)) }
-    fn ReceiveNullableUnion2(&self) -> Option<EventOrString> { Some(eString("".to_string())) }
+    fn ReceiveNullableUnion2(&self) -> Option<EventOrString> { Some(eString(DOMString::new())) }
 
     fn PassBoolean(&self, _: bool) {}
     fn PassByte(&self, _: i8) {}
@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::TransitionEventBinding;
+use dom::bindings::codegen::InheritTypes::{EventCast, TransitionEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, EventMethods, TransitionEventTypeId};
+use dom::window::Window;
+use servo_util::str::DOMString;
+
+use std::cell::{Cell, RefCell};
+
+#[deriving(Encodable)]
+pub struct TransitionEvent {
+    event: Event,
+    property_name: Traceable<RefCell<DOMString>>,
+    elapsed_time: Traceable<Cell<f32>>,
+    pseudo_element: Traceable<RefCell<DOMString>>,
+}
+
+impl TransitionEventDerived for Event {
+    fn is_transitionevent(&self) -> bool {
+        self.type_id == TransitionEventTypeId
+    }
+}
+
+impl TransitionEvent {
+    pub fn new_inherited() -> TransitionEvent {
+        TransitionEvent {
+            event: Event::new_inherited(TransitionEventTypeId),
+            property_name: Traceable::new(RefCell::new(DOMString::new())),
+            elapsed_time: Traceable::new(Cell::new(0.0)),
+            pseudo_element: Traceable::new(RefCell::new(DOMString::new())),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<TransitionEvent> {
+        reflect_dom_object(box TransitionEvent::new_inherited(),
+                           &global::Window(window),
+                           TransitionEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>, type_: DOMString,
+               bubbles: bool, cancelable: bool,
+               property_name: DOMString, elapsed_time: f32,
+               pseudo_element: DOMString) -> Temporary<TransitionEvent> {
+        let ev = TransitionEvent::new_uninitialized(window).root();
+        *ev.deref().property_name.deref().borrow_mut() = property_name;
+        ev.deref().elapsed_time.deref().set(elapsed_time);
+        *ev.deref().pseudo_element.deref().borrow_mut() = pseudo_element;
+        let event: &JSRef<Event> = EventCast::from_ref(&*ev);
+        event.InitEvent(type_, bubbles, cancelable);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &TransitionEventBinding::TransitionEventInit)
+                       -> Fallible<Temporary<TransitionEvent>> {
+        Ok(TransitionEvent::new(global.as_window(), type_, init.parent.bubbles, init.parent.cancelable,
+                                init.propertyName.clone(), init.elapsedTime,
+                                init.pseudoElement.clone()))
+    }
+}
+
+pub trait TransitionEventMethods {
+    fn PropertyName(&self) -> DOMString;
+    fn ElapsedTime(&self) -> f32;
+    fn PseudoElement(&self) -> DOMString;
+}
+
+impl<'a> TransitionEventMethods for JSRef<'a, TransitionEvent> {
+    fn PropertyName(&self) -> DOMString {
+        self.property_name.deref().borrow().clone()
+    }
+
+    fn ElapsedTime(&self) -> f32 {
+        self.elapsed_time.deref().get()
+    }
+
+    fn PseudoElement(&self) -> DOMString {
+        self.pseudo_element.deref().borrow().clone()
+    }
+}
+
+impl Reflectable for TransitionEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.event.reflector()
+    }
+}
@@ -5,10 +5,14 @@
 use dom::bindings::codegen::Bindings::UIEventBinding;
 use dom::bindings::codegen::InheritTypes::{EventCast, UIEventDerived};
 use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, RootedReference, Temporary, OptionalSettable};
 use dom::bindings::trace::Traceable;
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
-use dom::event::{Event, EventMethods, EventTypeId, UIEventTypeId};
+use dom::event::{Event, EventMethods, EventTypeId, UIEventTypeId, KeyboardEventTypeId};
+use dom::event::CompositionEventTypeId;
+use dom::event::{FocusEventTypeId, MouseEventTypeId, WheelEventTypeId};
 use dom::window::Window;
 use servo_util::str::DOMString;
 
@@ -24,7 +28,12 @@ pub struct UIEvent {
 
 impl UIEventDerived for Event {
     fn is_uievent(&self) -> bool {
-        self.type_id == UIEventTypeId
+        // Interfaces inheriting from UIEvent need to be listed here too.
+        match self.type_id {
+            UIEventTypeId | CompositionEventTypeId | FocusEventTypeId | KeyboardEventTypeId |
+            MouseEventTypeId | WheelEventTypeId => true,
+            _ => false
+        }
     }
 }
 
@@ -39,7 +48,7 @@ impl UIEvent {
 
     pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<UIEvent> {
         reflect_dom_object(box UIEvent::new_inherited(UIEventTypeId),
-                           window,
+                           &global::Window(window),
                            UIEventBinding::Wrap)
     }
 
@@ -54,10 +63,10 @@ impl UIEvent {
         Temporary::from_rooted(&*ev)
     }
 
-    pub fn Constructor(owner: &JSRef<Window>,
+    pub fn Constructor(global: &GlobalRef,
                        type_: DOMString,
                        init: &UIEventBinding::UIEventInit) -> Fallible<Temporary<UIEvent>> {
-        let event = UIEvent::new(owner, type_,
+        let event = UIEvent::new(global.as_window(), type_,
                                  init.parent.bubbles, init.parent.cancelable,
                                  init.view.root_ref(), init.detail);
         Ok(event)
@@ -91,6 +100,9 @@ impl<'a> UIEventMethods for JSRef<'a, UIEvent> {
                    view: Option<JSRef<Window>>,
                    detail: i32) {
         let event: &JSRef<Event> = EventCast::from_ref(self);
+        if event.deref().dispatching.deref().get() {
+            return;
+        }
         event.InitEvent(type_, can_bubble, cancelable);
         self.view.assign(view);
         self.detail.deref().set(detail);
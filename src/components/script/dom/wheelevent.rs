@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::WheelEventBinding;
+use dom::bindings::codegen::Bindings::WheelEventBinding::WheelEventConstants;
+use dom::bindings::codegen::InheritTypes::{EventCast, MouseEventCast, WheelEventDerived};
+use dom::bindings::error::Fallible;
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JSRef, RootedReference, Temporary};
+use dom::bindings::trace::Traceable;
+use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
+use dom::event::{Event, WheelEventTypeId};
+use dom::eventtarget::{EventTarget, EventTargetHelpers};
+use dom::mouseevent::{MouseEvent, MouseEventMethods};
+use dom::window::Window;
+use geom::point::Point2D;
+use servo_msg::constellation_msg::WheelInput;
+use servo_util::str::DOMString;
+use std::cell::Cell;
+
+#[deriving(Encodable)]
+pub struct WheelEvent {
+    pub mouseevent: MouseEvent,
+    pub delta_x: Traceable<Cell<f64>>,
+    pub delta_y: Traceable<Cell<f64>>,
+    pub delta_z: Traceable<Cell<f64>>,
+    pub delta_mode: Traceable<Cell<u32>>,
+}
+
+impl WheelEventDerived for Event {
+    fn is_wheelevent(&self) -> bool {
+        self.type_id == WheelEventTypeId
+    }
+}
+
+impl WheelEvent {
+    pub fn new_inherited() -> WheelEvent {
+        WheelEvent {
+            mouseevent: MouseEvent::new_inherited(WheelEventTypeId),
+            delta_x: Traceable::new(Cell::new(0.0)),
+            delta_y: Traceable::new(Cell::new(0.0)),
+            delta_z: Traceable::new(Cell::new(0.0)),
+            delta_mode: Traceable::new(Cell::new(0)),
+        }
+    }
+
+    pub fn new_uninitialized(window: &JSRef<Window>) -> Temporary<WheelEvent> {
+        reflect_dom_object(box WheelEvent::new_inherited(),
+                           &global::Window(window),
+                           WheelEventBinding::Wrap)
+    }
+
+    pub fn new(window: &JSRef<Window>,
+               type_: DOMString,
+               canBubble: bool,
+               cancelable: bool,
+               view: Option<JSRef<Window>>,
+               detail: i32,
+               screenX: i32,
+               screenY: i32,
+               clientX: i32,
+               clientY: i32,
+               ctrlKey: bool,
+               altKey: bool,
+               shiftKey: bool,
+               metaKey: bool,
+               button: i16,
+               buttons: u16,
+               relatedTarget: Option<JSRef<EventTarget>>,
+               deltaX: f64,
+               deltaY: f64,
+               deltaZ: f64,
+               deltaMode: u32) -> Temporary<WheelEvent> {
+        let ev = WheelEvent::new_uninitialized(window).root();
+        {
+            let mouseevent: &JSRef<MouseEvent> = MouseEventCast::from_ref(&*ev);
+            mouseevent.InitMouseEvent(type_, canBubble, cancelable, view, detail,
+                                      screenX, screenY, clientX, clientY,
+                                      ctrlKey, altKey, shiftKey, metaKey,
+                                      button, relatedTarget);
+            mouseevent.deref().buttons.deref().set(buttons);
+        }
+        ev.deref().delta_x.deref().set(deltaX);
+        ev.deref().delta_y.deref().set(deltaY);
+        ev.deref().delta_z.deref().set(deltaZ);
+        ev.deref().delta_mode.deref().set(deltaMode);
+        Temporary::from_rooted(&*ev)
+    }
+
+    pub fn Constructor(global: &GlobalRef,
+                       type_: DOMString,
+                       init: &WheelEventBinding::WheelEventInit) -> Fallible<Temporary<WheelEvent>> {
+        let mouseinit = &init.parent;
+        let event = WheelEvent::new(global.as_window(), type_, mouseinit.parent.parent.bubbles,
+                                    mouseinit.parent.parent.cancelable,
+                                    mouseinit.parent.view.root_ref(),
+                                    mouseinit.parent.detail,
+                                    mouseinit.screenX, mouseinit.screenY,
+                                    mouseinit.clientX, mouseinit.clientY, mouseinit.ctrlKey,
+                                    mouseinit.altKey, mouseinit.shiftKey, mouseinit.metaKey,
+                                    mouseinit.button, mouseinit.buttons,
+                                    mouseinit.relatedTarget.root_ref(),
+                                    init.deltaX, init.deltaY, init.deltaZ, init.deltaMode);
+        Ok(event)
+    }
+
+    /// Fires a trusted, cancelable "wheel" event at `target` for the turn of the scroll wheel in
+    /// `input`. Returns true if a listener prevented the default action, in which case the
+    /// caller must not scroll.
+    pub fn dispatch(target: &JSRef<EventTarget>, window: &JSRef<Window>,
+                    input: &WheelInput) -> bool {
+        let mouse = &input.mouse;
+        let modifiers = &mouse.modifiers;
+        let (delta_x, delta_y) = wheel_deltas(&input.delta);
+        let wheelevent = WheelEvent::new(window, DOMString::from_slice("wheel"), true, true,
+                                         Some(window.clone()), 0,
+                                         mouse.screen_point.x as i32, mouse.screen_point.y as i32,
+                                         mouse.client_point.x as i32, mouse.client_point.y as i32,
+                                         modifiers.control, modifiers.alt,
+                                         modifiers.shift, modifiers.meta,
+                                         0, mouse.buttons, None,
+                                         delta_x, delta_y, 0.0,
+                                         WheelEventConstants::DOM_DELTA_PIXEL).root();
+        let mouseevent: &JSRef<MouseEvent> = MouseEventCast::from_ref(&*wheelevent);
+        mouseevent.deref().page_x.deref().set(mouse.page_point.x as i32);
+        mouseevent.deref().page_y.deref().set(mouse.page_point.y as i32);
+        let event: &JSRef<Event> = EventCast::from_ref(&*wheelevent);
+        target.fire_trusted_event(None, event)
+    }
+}
+
+/// Converts a compositor scroll delta, which moves the page so that a positive value scrolls up or
+/// left, into `wheel` event deltas, where a positive value scrolls down or right.
+pub fn wheel_deltas(scroll_delta: &Point2D<f32>) -> (f64, f64) {
+    (-scroll_delta.x as f64, -scroll_delta.y as f64)
+}
+
+pub trait WheelEventMethods {
+    fn DeltaX(&self) -> f64;
+    fn DeltaY(&self) -> f64;
+    fn DeltaZ(&self) -> f64;
+    fn DeltaMode(&self) -> u32;
+}
+
+impl<'a> WheelEventMethods for JSRef<'a, WheelEvent> {
+    fn DeltaX(&self) -> f64 {
+        self.delta_x.deref().get()
+    }
+
+    fn DeltaY(&self) -> f64 {
+        self.delta_y.deref().get()
+    }
+
+    fn DeltaZ(&self) -> f64 {
+        self.delta_z.deref().get()
+    }
+
+    fn DeltaMode(&self) -> u32 {
+        self.delta_mode.deref().get()
+    }
+}
+
+impl Reflectable for WheelEvent {
+    fn reflector<'a>(&'a self) -> &'a Reflector {
+        self.mouseevent.reflector()
+    }
+}
+
+#[test]
+fn test_wheel_deltas() {
+    assert_eq!(wheel_deltas(&Point2D(0.0, 0.0)), (0.0, 0.0));
+    // A negative compositor delta scrolls down, which a wheel event gives as a positive deltaY.
+    assert_eq!(wheel_deltas(&Point2D(0.0, -40.0)), (0.0, 40.0));
+    assert_eq!(wheel_deltas(&Point2D(12.5, 3.0)), (-12.5, -3.0));
+}
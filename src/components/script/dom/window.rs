@@ -4,10 +4,13 @@
 
 use dom::bindings::codegen::Bindings::EventHandlerBinding::{OnErrorEventHandlerNonNull, EventHandlerNonNull};
 use dom::bindings::codegen::Bindings::WindowBinding;
-use dom::bindings::codegen::InheritTypes::EventTargetCast;
+use dom::bindings::codegen::InheritTypes::{EventTargetCast, WindowDerived};
+use dom::bindings::error::{ErrorResult, Syntax};
 use dom::bindings::js::{JS, JSRef, Temporary, OptionalSettable};
+use dom::bindings::structuredclone::StructuredCloneData;
 use dom::bindings::trace::{Traceable, Untraceable};
-use dom::bindings::utils::{Reflectable, Reflector};
+use dom::bindings::utils::{Reflectable, Reflector, global_object_for_js_object};
+use dom::beforeunloadevent::BeforeUnloadEvent;
 use dom::browsercontext::BrowserContext;
 use dom::console::Console;
 use dom::document::Document;
@@ -15,10 +18,12 @@ use dom::eventtarget::{EventTarget, WindowTypeId, EventTargetHelpers};
 use dom::location::Location;
 use dom::navigator::Navigator;
 use dom::performance::Performance;
+use dom::storage::Storage;
 
 use layout_interface::{ReflowForDisplay, DocumentDamageLevel};
 use page::Page;
-use script_task::{ExitWindowMsg, FireTimerMsg, ScriptChan, TriggerLoadMsg, TriggerFragmentMsg};
+use script_task::{ExitWindowMsg, FireTimerMsg, PostMessageMsg, ScriptChan};
+use script_task::{TriggerLoadMsg, TriggerFragmentMsg};
 use servo_msg::compositor_msg::ScriptListener;
 use servo_net::image_cache_task::ImageCacheTask;
 use servo_util::str::DOMString;
@@ -26,7 +31,7 @@ use servo_util::task::{spawn_named};
 use servo_util::url::parse_url;
 
 use js::jsapi::JSContext;
-use js::jsapi::{JS_GC, JS_GetRuntime};
+use js::jsapi::{JS_GC, JS_GetRuntime, JS_GetGlobalForScopeChain};
 use js::jsval::JSVal;
 
 use std::collections::hashmap::HashMap;
@@ -80,6 +85,7 @@ pub struct Window {
     pub browser_context: Traceable<RefCell<Option<BrowserContext>>>,
     pub page: Rc<Page>,
     pub performance: Cell<Option<JS<Performance>>>,
+    pub session_storage: Cell<Option<JS<Storage>>>,
     pub navigationStart: u64,
     pub navigationStartPrecise: f64,
 }
@@ -118,6 +124,8 @@ pub struct TimerData {
 
 pub trait WindowMethods {
     fn Alert(&self, s: DOMString);
+    fn PostMessage(&self, cx: *mut JSContext, message: JSVal, target_origin: DOMString)
+                   -> ErrorResult;
     fn Close(&self);
     fn Document(&self) -> Temporary<Document>;
     fn Location(&self) -> Temporary<Location>;
@@ -130,6 +138,7 @@ pub trait WindowMethods {
     fn Window(&self) -> Temporary<Window>;
     fn Self(&self) -> Temporary<Window>;
     fn Performance(&self) -> Temporary<Performance>;
+    fn SessionStorage(&self) -> Temporary<Storage>;
     fn GetOnclick(&self) -> Option<EventHandlerNonNull>;
     fn SetOnclick(&self, listener: Option<EventHandlerNonNull>);
     fn GetOnload(&self) -> Option<EventHandlerNonNull>;
@@ -145,7 +154,32 @@ pub trait WindowMethods {
 impl<'a> WindowMethods for JSRef<'a, Window> {
     fn Alert(&self, s: DOMString) {
         // Right now, just print to the console
-        println!("ALERT: {:s}", s);
+        println!("ALERT: {}", s);
+    }
+
+    // http://www.whatwg.org/html/#dom-window-postmessage
+    fn PostMessage(&self, cx: *mut JSContext, message: JSVal, target_origin: DOMString)
+                   -> ErrorResult {
+        let source = global_object_for_js_object(unsafe { JS_GetGlobalForScopeChain(cx) }).root();
+        let source_origin = serialize_origin(&source.deref().get_url());
+        let target_origin = match target_origin.to_string_lossy().as_slice() {
+            "*" => None,
+            "/" => Some(source_origin.clone()),
+            url => match from_str::<Url>(url) {
+                Some(url) => Some(serialize_origin(&url)),
+                None => return Err(Syntax),
+            },
+        };
+
+        // Clone now, so that later changes the caller makes to the message aren't seen.
+        let data = try!(StructuredCloneData::write(cx, message));
+
+        // The target origin is checked when the message is delivered, since this window may have
+        // navigated elsewhere by then.
+        let ScriptChan(ref chan) = self.script_chan;
+        chan.send(PostMessageMsg(self.page.id, data, source_origin, target_origin,
+                                 source.deref().page.id));
+        Ok(())
     }
 
     fn Close(&self) {
@@ -221,6 +255,14 @@ impl<'a> WindowMethods for JSRef<'a, Window> {
         Temporary::new(self.performance.get().get_ref().clone())
     }
 
+    fn SessionStorage(&self) -> Temporary<Storage> {
+        if self.session_storage.get().is_none() {
+            let session_storage = Storage::new(self);
+            self.session_storage.assign(Some(session_storage));
+        }
+        Temporary::new(self.session_storage.get().get_ref().clone())
+    }
+
     fn GetOnclick(&self) -> Option<EventHandlerNonNull> {
         let eventtarget: &JSRef<EventTarget> = EventTargetCast::from_ref(self);
         eventtarget.get_event_handler_common("click")
@@ -262,7 +304,7 @@ impl<'a> WindowMethods for JSRef<'a, Window> {
     }
 
     fn Debug(&self, message: DOMString) {
-        debug!("{:s}", message);
+        debug!("{}", message);
     }
 
     fn Gc(&self) {
@@ -282,7 +324,7 @@ pub trait WindowHelpers {
     fn damage_and_reflow(&self, damage: DocumentDamageLevel);
     fn wait_until_safe_to_modify_dom(&self);
     fn init_browser_context(&self, doc: &JSRef<Document>);
-    fn load_url(&self, href: DOMString);
+    fn load_url(&self, href: String) -> bool;
 }
 
 trait PrivateWindowHelpers {
@@ -308,8 +350,10 @@ impl<'a> WindowHelpers for JSRef<'a, Window> {
         *self.browser_context.deref().borrow_mut() = Some(BrowserContext::new(doc));
     }
 
-    /// Commence a new URL load which will either replace this window or scroll to a fragment.
-    fn load_url(&self, href: DOMString) {
+    /// Commences a new URL load, which will either replace this window or scroll to a fragment.
+    /// Unless only the fragment changes, the document is first given a chance to block the load
+    /// from a "beforeunload" listener; returns false if it did.
+    fn load_url(&self, href: String) -> bool {
         let base_url = Some(self.page().get_url());
         debug!("current page url is {:?}", base_url);
         let url = parse_url(href.as_slice(), base_url);
@@ -317,8 +361,12 @@ impl<'a> WindowHelpers for JSRef<'a, Window> {
         if href.as_slice().starts_with("#") {
             script_chan.send(TriggerFragmentMsg(self.page.id, url));
         } else {
+            if !BeforeUnloadEvent::dispatch(self) {
+                return false;
+            }
             script_chan.send(TriggerLoadMsg(self.page.id, url));
         }
+        true
     }
 }
 
@@ -382,6 +430,22 @@ impl<'a> PrivateWindowHelpers for JSRef<'a, Window> {
     }
 }
 
+/// Serializes the origin of `url` for a `MessageEvent`'s `origin`, as scheme, host and port. Local
+/// files have no origin that can be shared, so they serialize to "null".
+pub fn serialize_origin(url: &Url) -> String {
+    match (url.scheme.as_slice(), &url.port) {
+        ("file", _) => "null".to_string(),
+        (scheme, &Some(ref port)) => format!("{}://{}:{}", scheme, url.host, port),
+        (scheme, &None) => format!("{}://{}", scheme, url.host),
+    }
+}
+
+impl WindowDerived for EventTarget {
+    fn is_window(&self) -> bool {
+        self.type_id == WindowTypeId
+    }
+}
+
 impl Window {
     pub fn new(cx: *mut JSContext,
                page: Rc<Page>,
@@ -402,6 +466,7 @@ impl Window {
             next_timer_handle: Traceable::new(Cell::new(0)),
             browser_context: Traceable::new(RefCell::new(None)),
             performance: Cell::new(None),
+            session_storage: Cell::new(None),
             navigationStart: time::get_time().sec as u64,
             navigationStartPrecise: time::precise_time_s(),
         };
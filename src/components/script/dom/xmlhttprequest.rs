@@ -10,6 +10,8 @@ use dom::bindings::codegen::InheritTypes::{EventCast, EventTargetCast, XMLHttpRe
 use dom::bindings::conversions::ToJSValConvertible;
 use dom::bindings::error::{Error, ErrorResult, Fallible, InvalidState, InvalidAccess};
 use dom::bindings::error::{Network, Syntax, Security, Abort, Timeout};
+use dom::bindings::global;
+use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, JSRef, Temporary, OptionalRootedRootable};
 use dom::bindings::str::ByteString;
 use dom::bindings::trace::{Traceable, Untraceable};
@@ -136,7 +138,7 @@ impl XMLHttpRequest {
             timeout: Traceable::new(Cell::new(0u32)),
             with_credentials: Traceable::new(Cell::new(false)),
             upload: Cell::new(JS::from_rooted(&XMLHttpRequestUpload::new(owner))),
-            response_url: "".to_string(),
+            response_url: DOMString::new(),
             status: Traceable::new(Cell::new(0)),
             status_text: Traceable::new(RefCell::new(ByteString::new(vec!()))),
             response: Traceable::new(RefCell::new(ByteString::new(vec!()))),
@@ -147,7 +149,7 @@ impl XMLHttpRequest {
             request_method: Untraceable::new(RefCell::new(Get)),
             request_url: Untraceable::new(RefCell::new(parse_url("", None))),
             request_headers: Untraceable::new(RefCell::new(RequestHeaderCollection::new())),
-            request_body: "".to_string(),
+            request_body: DOMString::new(),
             sync: Traceable::new(Cell::new(false)),
             send_flag: Traceable::new(Cell::new(false)),
 
@@ -165,11 +167,11 @@ impl XMLHttpRequest {
     }
     pub fn new(window: &JSRef<Window>) -> Temporary<XMLHttpRequest> {
         reflect_dom_object(box XMLHttpRequest::new_inherited(window),
-                           window,
+                           &global::Window(window),
                            XMLHttpRequestBinding::Wrap)
     }
-    pub fn Constructor(owner: &JSRef<Window>) -> Fallible<Temporary<XMLHttpRequest>> {
-        Ok(XMLHttpRequest::new(owner))
+    pub fn Constructor(global: &GlobalRef) -> Fallible<Temporary<XMLHttpRequest>> {
+        Ok(XMLHttpRequest::new(global.as_window()))
     }
 
     pub fn handle_xhr_progress(addr: TrustedXHRAddress, progress: XHRProgress) {
@@ -302,7 +304,7 @@ impl<'a> XMLHttpRequestMethods<'a> for JSRef<'a, XMLHttpRequest> {
                 *self.request_method.deref().borrow_mut() = maybe_method.unwrap();
 
                 // Step 6
-                let parsed_url = match try_parse_url(url.as_slice(), base) {
+                let parsed_url = match try_parse_url(url.to_string_lossy().as_slice(), base) {
                     Ok(parsed) => parsed,
                     Err(_) => return Err(Syntax) // Step 7
                 };
@@ -442,7 +444,7 @@ impl<'a> XMLHttpRequestMethods<'a> for JSRef<'a, XMLHttpRequest> {
 
         let data = match *self.request_method.deref().borrow() {
             Get | Head => None, // Step 3
-            _ => data
+            _ => data.map(|data| data.to_string_lossy())
         };
 
         // Step 6
@@ -595,9 +597,9 @@ impl<'a> XMLHttpRequestMethods<'a> for JSRef<'a, XMLHttpRequest> {
             _empty | Text => {
                 let ready_state = self.ready_state.deref().get();
                 if ready_state == XHRDone || ready_state == Loading {
-                    self.text_response().to_jsval(cx)
+                    DOMString::from_string(self.text_response()).to_jsval(cx)
                 } else {
-                    "".to_string().to_jsval(cx)
+                    DOMString::new().to_jsval(cx)
                 }
             },
             _ if self.ready_state.deref().get() != XHRDone => NullValue(),
@@ -619,8 +621,8 @@ impl<'a> XMLHttpRequestMethods<'a> for JSRef<'a, XMLHttpRequest> {
         match self.response_type.deref().get() {
             _empty | Text => {
                 match self.ready_state.deref().get() {
-                    Loading | XHRDone => Ok(self.text_response()),
-                    _ => Ok("".to_string())
+                    Loading | XHRDone => Ok(DOMString::from_string(self.text_response())),
+                    _ => Ok(DOMString::new())
                 }
             },
             _ => Err(InvalidState)
@@ -663,10 +665,10 @@ trait PrivateXMLHttpRequestHelpers {
     fn change_ready_state(&self, XMLHttpRequestState);
     fn process_partial_response(&self, progress: XHRProgress);
     fn insert_trusted_header(&self, name: String, value: String);
-    fn dispatch_progress_event(&self, upload: bool, type_: DOMString, loaded: u64, total: Option<u64>);
-    fn dispatch_upload_progress_event(&self, type_: DOMString, partial_load: Option<u64>);
-    fn dispatch_response_progress_event(&self, type_: DOMString);
-    fn text_response(&self) -> DOMString;
+    fn dispatch_progress_event(&self, upload: bool, type_: String, loaded: u64, total: Option<u64>);
+    fn dispatch_upload_progress_event(&self, type_: String, partial_load: Option<u64>);
+    fn dispatch_response_progress_event(&self, type_: String);
+    fn text_response(&self) -> String;
     fn set_timeout(&self, timeout:u32);
     fn cancel_timeout(&self);
 }
@@ -703,10 +705,8 @@ impl<'a> PrivateXMLHttpRequestHelpers for JSRef<'a, XMLHttpRequest> {
         assert!(self.ready_state.deref().get() != rs)
         self.ready_state.deref().set(rs);
         let win = &*self.global.root();
-        let event =
-            Event::new(win, "readystatechange".to_string(), false, true).root();
         let target: &JSRef<EventTarget> = EventTargetCast::from_ref(self);
-        target.dispatch_event_with_target(None, &*event).ok();
+        target.fire_event(None, &global::Window(win), "readystatechange").ok();
     }
 
     fn process_partial_response(&self, progress: XHRProgress) {
@@ -821,7 +821,7 @@ impl<'a> PrivateXMLHttpRequestHelpers for JSRef<'a, XMLHttpRequest> {
         collection.insert(maybe_header.unwrap());
     }
 
-    fn dispatch_progress_event(&self, upload: bool, type_: DOMString, loaded: u64, total: Option<u64>) {
+    fn dispatch_progress_event(&self, upload: bool, type_: String, loaded: u64, total: Option<u64>) {
         let win = &*self.global.root();
         let upload_target = &*self.upload.get().root();
         let progressevent = ProgressEvent::new(win, type_, false, false,
@@ -833,17 +833,18 @@ impl<'a> PrivateXMLHttpRequestHelpers for JSRef<'a, XMLHttpRequest> {
             EventTargetCast::from_ref(self)
         };
         let event: &JSRef<Event> = EventCast::from_ref(&*progressevent);
+        event.deref().trusted.deref().set(true);
         target.dispatch_event_with_target(None, event).ok();
     }
 
-    fn dispatch_upload_progress_event(&self, type_: DOMString, partial_load: Option<u64>) {
+    fn dispatch_upload_progress_event(&self, type_: String, partial_load: Option<u64>) {
         // If partial_load is None, loading has completed and we can just use the value from the request body
 
         let total = self.request_body.len() as u64;
         self.dispatch_progress_event(true, type_, partial_load.unwrap_or(total), Some(total));
     }
 
-    fn dispatch_response_progress_event(&self, type_: DOMString) {
+    fn dispatch_response_progress_event(&self, type_: String) {
         let len = self.response.deref().borrow().len() as u64;
         let total = self.response_headers.deref().borrow().content_length.map(|x| {x as u64});
         self.dispatch_progress_event(false, type_, len, total);
@@ -888,7 +889,7 @@ impl<'a> PrivateXMLHttpRequestHelpers for JSRef<'a, XMLHttpRequest> {
         // oneshot() closes the previous channel, canceling the timeout
         self.timer.deref().borrow_mut().oneshot(0);
     }
-    fn text_response(&self) -> DOMString {
+    fn text_response(&self) -> String {
         let mut encoding = UTF_8 as &Encoding+Send;
         match self.response_headers.deref().borrow().content_type {
             Some(ref x) => {
@@ -4,6 +4,7 @@
 
 use dom::bindings::codegen::InheritTypes::XMLHttpRequestUploadDerived;
 use dom::bindings::codegen::Bindings::XMLHttpRequestUploadBinding;
+use dom::bindings::global;
 use dom::bindings::js::{Temporary, JSRef};
 use dom::bindings::utils::{Reflectable, Reflector, reflect_dom_object};
 use dom::eventtarget::{EventTarget, XMLHttpRequestTargetTypeId};
@@ -24,7 +25,7 @@ impl XMLHttpRequestUpload {
     }
     pub fn new(window: &JSRef<Window>) -> Temporary<XMLHttpRequestUpload> {
         reflect_dom_object(box XMLHttpRequestUpload::new_inherited(),
-                           window,
+                           &global::Window(window),
                            XMLHttpRequestUploadBinding::Wrap)
     }
 }
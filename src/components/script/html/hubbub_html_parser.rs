@@ -2,7 +2,6 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use dom::attr::AttrMethods;
 use dom::bindings::codegen::InheritTypes::{NodeBase, NodeCast, TextCast, ElementCast};
 use dom::bindings::js::{JS, JSRef, Temporary, OptionalRootable, Root};
 use dom::bindings::utils::Reflectable;
@@ -158,7 +157,7 @@ fn js_script_listener(to_parent: Sender<HtmlDiscoveryMessage>,
 // Silly macros to handle constructing      DOM nodes. This produces bad code and should be optimized
 // via atomization (issue #85).
 
-pub fn build_element_from_tag(tag: DOMString, ns: Namespace, document: &JSRef<Document>) -> Temporary<Element> {
+pub fn build_element_from_tag(tag: String, ns: Namespace, document: &JSRef<Document>) -> Temporary<Element> {
     if ns != namespace::HTML {
         return Element::new(tag, ns, None, document);
     }
@@ -348,7 +347,7 @@ pub fn parse_html(page: &Page,
             // NOTE: tmp vars are workaround for lifetime issues. Both required.
             let tmp_borrow = doc_cell.borrow();
             let tmp = &*tmp_borrow;
-            let comment = Comment::new(data, *tmp).root();
+            let comment = Comment::new(DOMString::from_string(data), *tmp).root();
             let comment: &JSRef<Node> = NodeCast::from_ref(&*comment);
             unsafe { comment.to_hubbub_node() }
         },
@@ -400,11 +399,11 @@ pub fn parse_html(page: &Page,
             //       we get unrooting order failures if these are inside the match.
             let rel = {
                 let rel = element.deref().get_attribute(Null, "rel").root();
-                rel.map(|a| a.deref().Value())
+                rel.map(|a| a.deref().value().as_slice().to_string())
             };
             let href = {
                 let href= element.deref().get_attribute(Null, "href").root();
-                href.map(|a| a.deref().Value())
+                href.map(|a| a.deref().value().as_slice().to_string())
             };
 
             // Spawn additional parsing, network loads, etc. from tag and attrs
@@ -437,7 +436,7 @@ pub fn parse_html(page: &Page,
             // NOTE: tmp vars are workaround for lifetime issues. Both required.
             let tmp_borrow = doc_cell.borrow();
             let tmp = &*tmp_borrow;
-            let text = Text::new(data, *tmp).root();
+            let text = Text::new(DOMString::from_string(data), *tmp).root();
             unsafe { text.deref().to_hubbub_node() }
         },
         ref_node: |_| {},
@@ -501,7 +500,7 @@ pub fn parse_html(page: &Page,
                 let script: &JSRef<Element> = &*from_hubbub_node(script).root();
                 match script.get_attribute(Null, "src").root() {
                     Some(src) => {
-                        debug!("found script: {:s}", src.deref().Value());
+                        debug!("found script: {:s}", src.deref().value().as_slice());
                         let new_url = parse_url(src.deref().value().as_slice(), Some(url3.clone()));
                         js_chan2.send(JSTaskNewFile(new_url));
                     }
@@ -512,7 +511,8 @@ pub fn parse_html(page: &Page,
                         for child in scriptnode.children() {
                             debug!("child = {:?}", child);
                             let text: &JSRef<Text> = TextCast::to_ref(&child).unwrap();
-                            data.push_str(text.deref().characterdata.data.deref().borrow().as_slice());
+                            let text_data = text.deref().characterdata.data.deref().borrow();
+                            data.push_str(text_data.to_string_lossy().as_slice());
                         }
 
                         debug!("script data = {:?}", data);
@@ -78,6 +78,10 @@ pub struct Page {
     /// Pending scroll to fragment event, if any
     pub fragment_node: Cell<Option<JS<Element>>>,
 
+    /// The element of this page's document that the mouse was last over, which gets a
+    /// `mouseout` when the mouse leaves it.
+    pub mouse_target: Cell<Option<JS<Element>>>,
+
     /// Associated resource task for use by DOM objects like XMLHttpRequest
     pub resource_task: Untraceable<ResourceTask>,
 
@@ -138,6 +142,7 @@ impl Page {
             next_subpage_id: Untraceable::new(Cell::new(SubpageId(0))),
             resize_event: Untraceable::new(Cell::new(None)),
             fragment_node: Cell::new(None),
+            mouse_target: Cell::new(None),
             last_reflow_id: Traceable::new(Cell::new(0)),
             resource_task: Untraceable::new(resource_task),
             constellation_chan: Untraceable::new(constellation_chan),
@@ -354,9 +359,9 @@ impl Page {
     }
 
     /// Attempt to find a named element in this page's document.
-    pub fn find_fragment_node(&self, fragid: DOMString) -> Option<Temporary<Element>> {
+    pub fn find_fragment_node(&self, fragid: String) -> Option<Temporary<Element>> {
         let document = self.frame().get_ref().document.root();
-        match document.deref().GetElementById(fragid.to_string()) {
+        match document.deref().GetElementById(DOMString::from_string(fragid)) {
             Some(node) => Some(node),
             None => {
                 let doc_node: &JSRef<Node> = NodeCast::from_ref(&*document);
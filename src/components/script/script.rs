@@ -47,9 +47,11 @@ pub mod dom {
         pub mod utils;
         pub mod callback;
         pub mod error;
+        pub mod global;
         pub mod conversions;
         pub mod proxyhandler;
         pub mod str;
+        pub mod structuredclone;
         pub mod trace;
         pub mod codegen {
             pub mod Bindings;
@@ -73,6 +75,10 @@ pub mod dom {
     pub mod clientrectlist;
     pub mod comment;
     pub mod console;
+    pub mod closeevent;
+    pub mod animationevent;
+    pub mod compositionevent;
+    pub mod beforeunloadevent;
     pub mod customevent;
     pub mod document;
     pub mod documentfragment;
@@ -82,11 +88,14 @@ pub mod dom {
     pub mod domparser;
     pub mod domtokenlist;
     pub mod element;
+    pub mod errorevent;
     pub mod event;
     pub mod eventdispatcher;
     pub mod eventtarget;
     pub mod file;
+    pub mod focusevent;
     pub mod formdata;
+    pub mod hashchangeevent;
     pub mod htmlanchorelement;
     pub mod htmlappletelement;
     pub mod htmlareaelement;
@@ -157,19 +166,26 @@ pub mod dom {
     pub mod htmlulistelement;
     pub mod htmlvideoelement;
     pub mod htmlunknownelement;
+    pub mod keyboardevent;
     pub mod location;
+    pub mod messageevent;
     pub mod mouseevent;
     pub mod navigator;
     pub mod node;
     pub mod nodelist;
+    pub mod popstateevent;
     pub mod processinginstruction;
     pub mod performance;
     pub mod performancetiming;
     pub mod progressevent;
+    pub mod storage;
+    pub mod storageevent;
+    pub mod transitionevent;
     pub mod uievent;
     pub mod text;
     pub mod validitystate;
     pub mod virtualmethods;
+    pub mod wheelevent;
     pub mod window;
     pub mod xmlhttprequest;
     pub mod xmlhttprequesteventtarget;
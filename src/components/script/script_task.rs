@@ -6,20 +6,36 @@
 //! and layout tasks.
 
 use dom::bindings::codegen::RegisterBindings;
-use dom::bindings::codegen::InheritTypes::{EventTargetCast, NodeCast, EventCast};
+use dom::bindings::codegen::InheritTypes::{EventTargetCast, NodeCast, EventCast, ElementCast};
+use dom::bindings::codegen::InheritTypes::{HTMLInputElementCast, HTMLTextAreaElementCast};
+use dom::bindings::global;
+use dom::bindings::structuredclone::StructuredCloneData;
 use dom::bindings::js::{JS, JSRef, RootCollection, Temporary, OptionalSettable};
-use dom::bindings::js::OptionalRootable;
+use dom::bindings::js::{OptionalRootable, OptionalRootedRootable, RootedReference};
+use dom::bindings::trace::assert_write_barriers_unneeded;
 use dom::bindings::utils::Reflectable;
 use dom::bindings::utils::{wrap_for_same_compartment, pre_wrap};
-use dom::document::{Document, HTMLDocument, DocumentHelpers};
+use dom::bindings::utils::global_object_for_js_object;
+use dom::document::{Document, HTMLDocument, DocumentHelpers, DocumentMethods};
+use dom::customevent::{CustomEvent, CustomEventMethods};
 use dom::element::{Element};
+use dom::errorevent::ErrorEvent;
 use dom::event::{Event_, ResizeEvent, ReflowEvent, ClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent};
+use dom::compositionevent::CompositionEvent;
+use dom::event::{CompositionInputEvent, KeyEvent, MouseWheelEvent};
 use dom::event::Event;
+use dom::hashchangeevent::HashChangeEvent;
+use dom::htmlinputelement::{HTMLInputElementHelpers, HTMLInputElementMethods};
+use dom::htmltextareaelement::HTMLTextAreaElementMethods;
+use dom::keyboardevent::{KeyboardEvent, edit_text_value};
+use dom::messageevent::MessageEvent;
+use dom::mouseevent::MouseEvent;
 use dom::uievent::UIEvent;
+use dom::wheelevent::WheelEvent;
 use dom::eventtarget::{EventTarget, EventTargetHelpers};
 use dom::node;
 use dom::node::{Node, NodeHelpers};
-use dom::window::{TimerId, Window, WindowHelpers};
+use dom::window::{TimerId, Window, WindowHelpers, serialize_origin};
 use dom::xmlhttprequest::{TrustedXHRAddress, XMLHttpRequest, XHRProgress};
 use html::hubbub_html_parser::HtmlParserResult;
 use html::hubbub_html_parser::{HtmlDiscoveredStyle, HtmlDiscoveredScript};
@@ -28,31 +44,41 @@ use layout_interface::AddStylesheetMsg;
 use layout_interface::{LayoutChan, MatchSelectorsDocumentDamage};
 use layout_interface::{ReflowDocumentDamage, ReflowForDisplay};
 use layout_interface::ContentChangedDocumentDamage;
+use layout_interface::UntrustedNodeAddress;
 use layout_interface;
 use page::{Page, IterablePage, Frame};
 
 use geom::point::Point2D;
-use js::jsapi::JS_CallFunctionValue;
+use js::jsapi::{JS_CallFunctionValue, JS_ClearPendingException, JS_ParseJSON};
 use js::jsapi::{JS_SetWrapObjectCallbacks, JS_SetGCZeal, JS_DEFAULT_ZEAL_FREQ, JS_GC};
-use js::jsapi::{JSContext, JSRuntime};
+use js::jsapi::{JS_GetGCParameter, JSGC_BYTES};
+use js::jsapi::{JSContext, JSRuntime, JSErrorReport};
+use js::jsapi::{JS_GetGlobalForScopeChain, JS_SetErrorReporter};
 use js::jsval::NullValue;
 use js::rust::{Cx, RtUtils};
 use js::rust::with_compartment;
 use js;
+use libc::{c_char, c_uint};
 use servo_msg::compositor_msg::{FinishedLoading, LayerId, Loading};
 use servo_msg::compositor_msg::{ScriptListener};
 use servo_msg::constellation_msg::{ConstellationChan, LoadCompleteMsg, LoadUrlMsg, NavigationDirection};
 use servo_msg::constellation_msg::{PipelineId, SubpageId, Failure, FailureMsg, WindowSizeData};
+use servo_msg::constellation_msg::{CompositionCommitted, CompositionInput};
+use servo_msg::constellation_msg::{CompositionStarted, CompositionUpdated};
+use servo_msg::constellation_msg::{KeyInput, KeyReleased, MouseInput, WheelInput};
 use servo_msg::constellation_msg;
 use servo_net::image_cache_task::ImageCacheTask;
 use servo_net::resource_task::ResourceTask;
 use servo_util::geometry::to_frac_px;
+use servo_util::memory::{MemoryReport, MemoryReporter, MemoryReportsChan};
+use servo_util::str::DOMString;
 use servo_util::task::send_on_failure;
 use std::cell::RefCell;
 use std::comm::{channel, Sender, Receiver};
 use std::mem::replace;
 use std::ptr;
 use std::rc::Rc;
+use std::str;
 use std::task::TaskBuilder;
 use url::Url;
 
@@ -60,6 +86,46 @@ use serialize::{Encoder, Encodable};
 
 local_data_key!(pub StackRoots: *RootCollection)
 
+/// Set while an "error" event is being dispatched, so that errors thrown by the handlers
+/// are logged rather than reported to them again.
+local_data_key!(ReportingError: bool)
+
+/// The value of SpiderMonkey's `JSREPORT_WARNING` flag.
+static JSREPORT_WARNING: c_uint = 0x1;
+
+/// The error reporter for the script task's JS context. Uncaught errors are reported to the
+/// page by firing an ErrorEvent at its window; they are logged only if no handler canceled it.
+extern fn report_error(cx: *mut JSContext, message: *c_char, report: *mut JSErrorReport) {
+    let (message, filename, lineno, is_warning) = unsafe {
+        let message = str::raw::from_c_str(message);
+        let filename = if (*report).filename.is_not_null() {
+            str::raw::from_c_str((*report).filename)
+        } else {
+            "none".to_string()
+        };
+        (message, filename, (*report).lineno as u32,
+         (*report).flags & JSREPORT_WARNING != 0)
+    };
+
+    let reporting = ReportingError.get().map_or(false, |reporting| *reporting);
+    let mut unhandled = true;
+    if !is_warning && !reporting {
+        let global = unsafe { JS_GetGlobalForScopeChain(cx) };
+        if global.is_not_null() {
+            let window = global_object_for_js_object(global).root();
+            ReportingError.replace(Some(true));
+            // FIXME: SpiderMonkey doesn't give us the column or the exception value here.
+            unhandled = ErrorEvent::report(&*window, message.clone(), filename.clone(),
+                                           lineno, 0, NullValue());
+            ReportingError.replace(None);
+        }
+    }
+
+    if unhandled {
+        error!("Error at {:s}:{}: {:s}", filename, lineno, message);
+    }
+}
+
 /// Messages used to control the script task.
 pub enum ScriptMsg {
     /// Loads a new URL on the specified pipeline.
@@ -87,7 +153,20 @@ pub enum ScriptMsg {
     /// Notifies the script that a window associated with a particular pipeline should be closed.
     ExitWindowMsg(PipelineId),
     /// Notifies the script of progress on a fetch
-    XHRProgressMsg(TrustedXHRAddress, XHRProgress)
+    XHRProgressMsg(TrustedXHRAddress, XHRProgress),
+    /// Dispatches a trusted, bubbling, cancelable CustomEvent of the given type at the
+    /// document of the specified pipeline, with its detail parsed from the given JSON (null
+    /// if the JSON is invalid). Replies with true if a listener canceled the event, and with
+    /// false if the pipeline is gone or has no document; the reply may go unread. Meant for
+    /// embedders and test harnesses driving a page from outside, as with `--custom-event`.
+    DispatchCustomEventMsg(PipelineId, String, String, Sender<bool>),
+    /// Fires a "message" event at the window of the first pipeline with the given cloned data,
+    /// as sent by `postMessage` from a document of the given origin in the second pipeline. The
+    /// message is dropped unless the receiving document has the target origin, if one is given.
+    PostMessageMsg(PipelineId, StructuredCloneData, String, Option<String>, PipelineId),
+    /// Sends the size of the JavaScript heap, which holds the reflectors of every DOM object, to
+    /// the memory profiler.
+    CollectMemoryReportsMsg(MemoryReportsChan),
 }
 
 pub struct NewLayoutInfo {
@@ -115,6 +194,13 @@ impl ScriptChan {
     }
 }
 
+impl MemoryReporter for ScriptChan {
+    fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool {
+        let ScriptChan(ref chan) = *self;
+        chan.send_opt(CollectMemoryReportsMsg(reports_chan)).is_ok()
+    }
+}
+
 struct StackRootTLS;
 
 impl StackRootTLS {
@@ -158,7 +244,7 @@ pub struct ScriptTask {
     /// The JSContext.
     js_context: RefCell<Option<Rc<Cx>>>,
 
-    mouse_over_targets: RefCell<Option<Vec<JS<Node>>>>
+    mouse_over_targets: RefCell<Option<Vec<JS<Node>>>>,
 }
 
 /// In the event of task failure, all data on the stack runs its destructor. However, there
@@ -227,7 +313,7 @@ impl ScriptTask {
 
             js_runtime: js_runtime,
             js_context: RefCell::new(Some(js_context)),
-            mouse_over_targets: RefCell::new(None)
+            mouse_over_targets: RefCell::new(None),
         })
     }
 
@@ -237,6 +323,8 @@ impl ScriptTask {
             let ptr: *mut JSRuntime = (*js_runtime).ptr;
             ptr.is_not_null()
         });
+        // DOM objects store JSVals without write barriers.
+        assert_write_barriers_unneeded((*js_runtime).ptr);
         unsafe {
             // JS_SetWrapObjectCallbacks clobbers the existing wrap callback,
             // and JSCompartment::wrap crashes if that happens. The only way
@@ -258,7 +346,9 @@ impl ScriptTask {
             ptr.is_not_null()
         });
         js_context.set_default_options_and_version();
-        js_context.set_logging_error_reporter();
+        unsafe {
+            JS_SetErrorReporter((*js_context).ptr, Some(report_error));
+        }
         unsafe {
             JS_SetGCZeal((*js_context).ptr, 0, JS_DEFAULT_ZEAL_FREQ);
         }
@@ -380,6 +470,13 @@ impl ScriptTask {
                 ExitWindowMsg(id) => self.handle_exit_window_msg(id),
                 ResizeMsg(..) => fail!("should have handled ResizeMsg already"),
                 XHRProgressMsg(addr, progress) => XMLHttpRequest::handle_xhr_progress(addr, progress),
+                DispatchCustomEventMsg(id, type_, detail, reply) =>
+                    self.handle_dispatch_custom_event_msg(id, type_, detail, reply),
+                PostMessageMsg(id, data, origin, target_origin, source_id) => {
+                    self.handle_post_message_msg(id, data, origin, target_origin, source_id)
+                }
+                CollectMemoryReportsMsg(reports_chan) =>
+                    self.handle_collect_memory_reports_msg(reports_chan),
             }
         }
 
@@ -454,6 +551,184 @@ impl ScriptTask {
         self.compositor.set_ready_state(FinishedLoading);
     }
 
+    /// Handles a request from outside content to dispatch a CustomEvent.
+    fn handle_dispatch_custom_event_msg(&self, id: PipelineId, type_: String,
+                                        detail_json: String, reply: Sender<bool>) {
+        let page = match self.page.borrow().find(id) {
+            Some(page) => page,
+            None => {
+                let _ = reply.send_opt(false);
+                return;
+            }
+        };
+        let frame = page.frame();
+        let (window, document) = match *frame {
+            Some(ref frame) => (frame.window.root(), frame.document.root()),
+            None => {
+                let _ = reply.send_opt(false);
+                return;
+            }
+        };
+
+        let cx = self.get_cx();
+        let this_value = window.deref().reflector().get_jsobject();
+        let canceled = with_compartment(cx, this_value, || {
+            // Create the event before parsing the detail: creating it can GC, whereas once
+            // the detail is stored in the (rooted) event it is traced through it.
+            let customevent = CustomEvent::new_uninitialized(&global::Window(&*window)).root();
+            let json = detail_json.as_slice().to_utf16();
+            let mut detail = NullValue();
+            unsafe {
+                if JS_ParseJSON(cx, json.as_ptr(), json.len() as u32, &mut detail) == 0 {
+                    debug!("invalid JSON for {:s} event detail: {:s}", type_, detail_json);
+                    JS_ClearPendingException(cx);
+                    detail = NullValue();
+                }
+            }
+            // The detail belongs to the window's own global, so it isn't cloned and this can't
+            // fail.
+            let _ = customevent.deref().InitCustomEvent(cx, DOMString::from_slice(type_.as_slice()),
+                                                        true, true, detail);
+
+            let event: &JSRef<Event> = EventCast::from_ref(&*customevent);
+            event.deref().trusted.deref().set(true);
+            let doctarget: &JSRef<EventTarget> = EventTargetCast::from_ref(&*document);
+            !doctarget.dispatch_event_with_target(None, event).unwrap_or(true)
+        });
+        // The sender may not wait for the answer.
+        let _ = reply.send_opt(canceled);
+    }
+
+    /// Handles a message posted to a window, which may since have navigated away or closed. A
+    /// message for another origin is dropped without telling the sender.
+    fn handle_post_message_msg(&self, id: PipelineId, data: StructuredCloneData, origin: String,
+                               target_origin: Option<String>, source_id: PipelineId) {
+        let page = match self.page.borrow().find(id) {
+            Some(page) => page,
+            None => return,
+        };
+        match target_origin {
+            Some(ref target_origin) if *target_origin != serialize_origin(&page.get_url()) => {
+                return;
+            }
+            _ => {}
+        }
+        let frame = page.frame();
+        let window = match *frame {
+            Some(ref frame) => frame.window.root(),
+            None => return,
+        };
+        // The source is only reachable if it runs in this script task.
+        let source_page = self.page.borrow().find(source_id);
+        let source_frame = source_page.as_ref().map(|page| page.frame());
+        let source = match source_frame {
+            Some(ref frame) => frame.as_ref().map(|frame| frame.window.root()),
+            None => None,
+        };
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(&*window);
+        MessageEvent::dispatch(target, &*window, &data, origin, source.root_ref());
+    }
+
+    /// Fires the DOM events for a key going down, repeating or coming up at the focused element,
+    /// or at the body if nothing has focus. Then carries out whatever default actions listeners
+    /// didn't cancel: editing a focused text field, or going back or forward on backspace.
+    fn handle_key_event(&self, pipeline_id: PipelineId, input: KeyInput) {
+        let page = get_page(&*self.page.borrow(), pipeline_id);
+        let frame = page.frame();
+        let (window, document) = match *frame {
+            Some(ref frame) => (frame.window.root(), frame.document.root()),
+            None => return,
+        };
+        let focused = document.get_focused_element().root();
+        let body = document.GetBody().root();
+        let target: &JSRef<EventTarget> = match (&focused, &body) {
+            (&Some(ref focused), _) => EventTargetCast::from_ref(&**focused),
+            (&None, &Some(ref body)) => EventTargetCast::from_ref(&**body),
+            (&None, &None) => EventTargetCast::from_ref(&*document),
+        };
+
+        if input.state == KeyReleased {
+            KeyboardEvent::dispatch(target, &*window, "keyup", &input);
+            return;
+        }
+        if !KeyboardEvent::dispatch(target, &*window, "keydown", &input) {
+            return;
+        }
+
+        let key = input.key.as_slice();
+        if key == "Backspace" {
+            let edited = focused.as_ref().map_or(false, |focused| {
+                edit_text_field(&*window, &**focused, |value, multiline| {
+                    edit_text_value(value, key, multiline)
+                })
+            });
+            if !edited {
+                self.handle_navigate_msg(if input.modifiers.shift {
+                    constellation_msg::Forward
+                } else {
+                    constellation_msg::Back
+                });
+            }
+            return;
+        }
+
+        // Only keys that produce a character get a keypress, and not when a modifier makes them
+        // a shortcut.
+        let modifiers = &input.modifiers;
+        if (key.char_len() != 1 && key != "Enter") ||
+                modifiers.control || modifiers.alt || modifiers.meta {
+            return;
+        }
+        if KeyboardEvent::dispatch(target, &*window, "keypress", &input) {
+            for focused in focused.iter() {
+                edit_text_field(&*window, &**focused, |value, multiline| {
+                    edit_text_value(value, key, multiline)
+                });
+            }
+        }
+    }
+
+    /// Fires the composition events for a step of an input method session at the same target
+    /// keys go to. Committed text is then inserted into the focused text field.
+    fn handle_composition_event(&self, pipeline_id: PipelineId, input: CompositionInput) {
+        let page = get_page(&*self.page.borrow(), pipeline_id);
+        let frame = page.frame();
+        let (window, document) = match *frame {
+            Some(ref frame) => (frame.window.root(), frame.document.root()),
+            None => return,
+        };
+        let focused = document.get_focused_element().root();
+        let body = document.GetBody().root();
+        let target: &JSRef<EventTarget> = match (&focused, &body) {
+            (&Some(ref focused), _) => EventTargetCast::from_ref(&**focused),
+            (&None, &Some(ref body)) => EventTargetCast::from_ref(&**body),
+            (&None, &None) => EventTargetCast::from_ref(&*document),
+        };
+
+        match input {
+            CompositionStarted => {
+                CompositionEvent::dispatch(target, &*window, "compositionstart",
+                                           DOMString::new());
+            }
+            CompositionUpdated(data) => {
+                CompositionEvent::dispatch(target, &*window, "compositionupdate",
+                                           DOMString::from_string(data));
+            }
+            CompositionCommitted(data) => {
+                CompositionEvent::dispatch(target, &*window, "compositionupdate",
+                                           DOMString::from_slice(data.as_slice()));
+                CompositionEvent::dispatch(target, &*window, "compositionend",
+                                           DOMString::from_slice(data.as_slice()));
+                for focused in focused.iter() {
+                    edit_text_field(&*window, &**focused, |value, _| {
+                        value.push_str(data.as_slice());
+                        !data.is_empty()
+                    });
+                }
+            }
+        }
+    }
+
     /// Handles a navigate forward or backward message.
     /// TODO(tkuehn): is it ever possible to navigate only on a subframe?
     fn handle_navigate_msg(&self, direction: NavigationDirection) {
@@ -488,6 +763,19 @@ impl ScriptTask {
         self.compositor.close();
     }
 
+    /// Reports the size of the JavaScript heap. The runtime is shared by every page in the task,
+    /// so the report is filed under the root page's pipeline.
+    fn handle_collect_memory_reports_msg(&self, reports_chan: MemoryReportsChan) {
+        let PipelineId(id) = self.page.borrow().id;
+        let size = unsafe {
+            JS_GetGCParameter((*self.js_runtime).ptr, JSGC_BYTES)
+        };
+        reports_chan.send(vec!(MemoryReport {
+            path: format!("pipeline-{}/js-heap", id),
+            size: size as u64,
+        }));
+    }
+
     /// Handles a request to exit the script task and shut down layout.
     /// Returns true if the script task should shut down and false otherwise.
     fn handle_exit_pipeline_msg(&self, id: PipelineId) -> bool {
@@ -568,6 +856,8 @@ impl ScriptTask {
 
         {
             // Create the root frame.
+            // The element the mouse was over belongs to the old document.
+            page.mouse_target.set(None);
             let mut frame = page.mut_frame();
             *frame = Some(Frame {
                 document: JS::from_rooted(document.deref()),
@@ -627,11 +917,9 @@ impl ScriptTask {
         // We have no concept of a document loader right now, so just dispatch the
         // "load" event as soon as we've finished executing all scripts parsed during
         // the initial load.
-        let event = Event::new(&*window, "load".to_string(), false, false).root();
         let doctarget: &JSRef<EventTarget> = EventTargetCast::from_ref(&*document);
         let wintarget: &JSRef<EventTarget> = EventTargetCast::from_ref(&*window);
-        let _ = wintarget.dispatch_event_with_target(Some((*doctarget).clone()),
-                                                     &*event);
+        wintarget.fire_event(Some((*doctarget).clone()), &global::Window(&*window), "load").ok();
 
         page.fragment_node.assign(fragment.map_or(None, |fragid| page.find_fragment_node(fragid)));
 
@@ -683,10 +971,11 @@ impl ScriptTask {
                         // http://dev.w3.org/csswg/cssom-view/#resizing-viewports
                         // https://dvcs.w3.org/hg/dom3events/raw-file/tip/html/DOM3-Events.html#event-type-resize
                         let uievent = UIEvent::new(&window.clone(),
-                                                   "resize".to_string(), false,
+                                                   DOMString::from_slice("resize"), false,
                                                    false, Some(window.clone()),
                                                    0i32).root();
                         let event: &JSRef<Event> = EventCast::from_ref(&*uievent);
+                        event.deref().trusted.deref().set(true);
 
                         let wintarget: &JSRef<EventTarget> = EventTargetCast::from_ref(&*window);
                         let _ = wintarget.dispatch_event_with_target(None, event);
@@ -706,47 +995,19 @@ impl ScriptTask {
                 }
             }
 
-            ClickEvent(_button, point) => {
-                debug!("ClickEvent: clicked at {:?}", point);
-                let page = get_page(&*self.page.borrow(), pipeline_id);
-                match page.hit_test(&point) {
-                    Some(node_address) => {
-                        debug!("node address is {:?}", node_address);
-
-                        let temp_node =
-                                node::from_untrusted_node_address(
-                                    self.js_runtime.deref().ptr, node_address);
-
-                        let maybe_node = temp_node.root().ancestors().find(|node| node.is_element());
-                        match maybe_node {
-                            Some(node) => {
-                                debug!("clicked on {:s}", node.debug_str());
-                                match *page.frame() {
-                                    Some(ref frame) => {
-                                        let window = frame.window.root();
-                                        let event =
-                                            Event::new(&*window,
-                                                       "click".to_string(),
-                                                       true, true).root();
-                                        let eventtarget: &JSRef<EventTarget> = EventTargetCast::from_ref(&node);
-                                        let _ = eventtarget.dispatch_event_with_target(None, &*event);
-                                    }
-                                    None => {}
-                                }
-                            }
-                            None => {}
-                        }
-                    }
-
-                    None => {}
-                }
+            ClickEvent(input) => self.handle_mouse_button_event(pipeline_id, "click", input),
+            KeyEvent(input) => self.handle_key_event(pipeline_id, input),
+            CompositionInputEvent(input) => self.handle_composition_event(pipeline_id, input),
+            MouseDownEvent(input) => {
+                self.handle_mouse_button_event(pipeline_id, "mousedown", input)
             }
-            MouseDownEvent(..) => {}
-            MouseUpEvent(..) => {}
-            MouseMoveEvent(point) => {
+            MouseUpEvent(input) => self.handle_mouse_button_event(pipeline_id, "mouseup", input),
+            MouseWheelEvent(input) => self.handle_wheel_event(pipeline_id, input),
+            MouseMoveEvent(input) => {
                 let page = get_page(&*self.page.borrow(), pipeline_id);
-                match page.get_nodes_under_mouse(&point) {
-                    Some(node_address) => {
+                let node_addresses = page.get_nodes_under_mouse(&input.page_point);
+                match node_addresses {
+                    Some(ref node_address) => {
 
                         let mut target_list = vec!();
                         let mut target_compare = false;
@@ -806,10 +1067,117 @@ impl ScriptTask {
 
                     None => {}
               }
+
+                // The topmost node is the one a hit test would have found.
+                let top_node = node_addresses.as_ref().and_then(|node_addresses| {
+                    node_addresses.as_slice().head().map(|&node_address| node_address)
+                });
+                self.fire_mouse_move_events(&*page, top_node, &input);
             }
         }
     }
 
+    /// Finds the element under `point`, which is in page coordinates.
+    fn element_under_mouse(&self, page: &Page, point: &Point2D<f32>)
+                           -> Option<Temporary<Element>> {
+        page.hit_test(point).and_then(|node_address| self.element_for_node(node_address))
+    }
+
+    /// Finds the element that is, or contains, the node layout gave us the address of.
+    fn element_for_node(&self, node_address: UntrustedNodeAddress)
+                        -> Option<Temporary<Element>> {
+        let node = node::from_untrusted_node_address(self.js_runtime.deref().ptr,
+                                                     node_address).root();
+        let element = node.deref().ancestors().find(|node| node.is_element());
+        element.map(|node| {
+            let element: &JSRef<Element> = ElementCast::to_ref(&node).unwrap();
+            Temporary::from_rooted(element)
+        })
+    }
+
+    /// Fires a `mousedown`, `mouseup` or `click` at the element under the pointer. A `mousedown`
+    /// that isn't canceled focuses the form field that was pressed, or takes focus away from
+    /// whatever had it.
+    fn handle_mouse_button_event(&self, pipeline_id: PipelineId, type_: &str, input: MouseInput) {
+        debug!("{:s} at {:?}", type_, input.page_point);
+        let page = get_page(&*self.page.borrow(), pipeline_id);
+        let frame = page.frame();
+        let (window, document) = match *frame {
+            Some(ref frame) => (frame.window.root(), frame.document.root()),
+            None => return,
+        };
+        let element = match self.element_under_mouse(&*page, &input.page_point).root() {
+            Some(element) => element,
+            None => return,
+        };
+
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(&*element);
+        if MouseEvent::dispatch(target, &*window, type_, &input, 1, None) || type_ != "mousedown" {
+            return;
+        }
+        let focusable = HTMLInputElementCast::to_ref(&*element).is_some() ||
+            HTMLTextAreaElementCast::to_ref(&*element).is_some();
+        document.set_focused_element(if focusable { Some(&*element) } else { None });
+    }
+
+    /// Fires a `wheel` at the element under the pointer, then has the compositor scroll unless a
+    /// listener canceled it.
+    fn handle_wheel_event(&self, pipeline_id: PipelineId, input: WheelInput) {
+        let page = get_page(&*self.page.borrow(), pipeline_id);
+        let canceled = {
+            let frame = page.frame();
+            match *frame {
+                Some(ref frame) => {
+                    let window = frame.window.root();
+                    match self.element_under_mouse(&*page, &input.mouse.page_point).root() {
+                        Some(element) => {
+                            let target: &JSRef<EventTarget> = EventTargetCast::from_ref(&*element);
+                            WheelEvent::dispatch(target, &*window, &input)
+                        }
+                        None => false,
+                    }
+                }
+                None => false,
+            }
+        };
+        if !canceled {
+            self.compositor.scroll(input.delta, input.window_point);
+        }
+    }
+
+    /// Fires a `mousemove` at the element under the pointer, given the topmost node there. If
+    /// the pointer has moved onto it from another element of the same page, that one first gets
+    /// a `mouseout` and this one a `mouseover`, each with the other as its `relatedTarget`.
+    fn fire_mouse_move_events(&self, page: &Page, top_node: Option<UntrustedNodeAddress>,
+                              input: &MouseInput) {
+        let frame = page.frame();
+        let window = match *frame {
+            Some(ref frame) => frame.window.root(),
+            None => return,
+        };
+        let element = top_node.and_then(|node_address| self.element_for_node(node_address)).root();
+        let old_element = page.mouse_target.get().root();
+
+        let target: Option<&JSRef<EventTarget>> =
+            element.as_ref().map(|element| EventTargetCast::from_ref(&**element));
+        let old_target: Option<&JSRef<EventTarget>> =
+            old_element.as_ref().map(|element| EventTargetCast::from_ref(&**element));
+        if target != old_target {
+            for old_target in old_target.iter() {
+                MouseEvent::dispatch(*old_target, &*window, "mouseout", input, 0,
+                                     target.map(|target| target.clone()));
+            }
+            for target in target.iter() {
+                MouseEvent::dispatch(*target, &*window, "mouseover", input, 0,
+                                     old_target.map(|old_target| old_target.clone()));
+            }
+            page.mouse_target.set(element.as_ref().map(|element| JS::from_rooted(&**element)));
+        }
+        for target in target.iter() {
+            MouseEvent::dispatch(*target, &*window, "mousemove", input, 0, None);
+        }
+    }
+
     /// The entry point for content to notify that a new load has been requested
     /// for the given pipeline.
     fn trigger_load(&self, pipeline_id: PipelineId, url: Url) {
@@ -821,13 +1189,25 @@ impl ScriptTask {
     /// for the given pipeline.
     fn trigger_fragment(&self, pipeline_id: PipelineId, url: Url) {
         let page = get_page(&*self.page.borrow(), pipeline_id);
-        match page.find_fragment_node(url.fragment.unwrap()).root() {
+        match page.find_fragment_node(url.fragment.clone().unwrap()).root() {
             Some(node) => {
                 self.scroll_fragment_point(pipeline_id, &*node);
             }
             None => {}
-         }
-     }
+        }
+
+        let old_url = page.get_url();
+        if old_url.fragment == url.fragment {
+            return;
+        }
+        {
+            let mut page_url = page.mut_url();
+            let needs_reflow = *page_url.get_ref().ref1();
+            *page_url = Some((url.clone(), needs_reflow));
+        }
+        let window = page.frame().get_ref().window.root();
+        HashChangeEvent::dispatch(&*window, old_url.to_str(), url.to_str());
+    }
 }
 
 /// Shuts down layout for the given page tree.
@@ -846,6 +1226,7 @@ fn shut_down_layout(page_tree: &Rc<Page>, rt: *mut JSRuntime) {
     // Remove our references to the DOM objects in this page tree.
     for page in page_tree.iter() {
         *page.mut_frame() = None;
+        page.mouse_target.set(None);
     }
 
     // Drop our references to the JSContext, potentially triggering a GC.
@@ -867,6 +1248,42 @@ fn shut_down_layout(page_tree: &Rc<Page>, rt: *mut JSRuntime) {
 }
 
 
+/// Applies `edit` to the value of `element` if it is a text field, firing an input event at it if
+/// `edit` returns true to say the value changed. `edit` is also told whether the field is
+/// multiline. Returns false if `element` isn't a text field.
+fn edit_text_field(window: &JSRef<Window>, element: &JSRef<Element>,
+                   edit: |&mut String, bool| -> bool) -> bool {
+    let changed = match HTMLInputElementCast::to_ref(element) {
+        Some(input) => {
+            if !input.is_text_field() {
+                return false;
+            }
+            let mut value = input.Value().to_string_lossy();
+            let changed = edit(&mut value, false);
+            if changed {
+                input.SetValue(DOMString::from_string(value));
+            }
+            changed
+        }
+        None => match HTMLTextAreaElementCast::to_ref(element) {
+            Some(textarea) => {
+                let mut value = textarea.Value().to_string_lossy();
+                let changed = edit(&mut value, true);
+                if changed {
+                    textarea.SetValue(DOMString::from_string(value));
+                }
+                changed
+            }
+            None => return false,
+        },
+    };
+    if changed {
+        let target: &JSRef<EventTarget> = EventTargetCast::from_ref(element);
+        target.fire_event(None, &global::Window(window), "input").ok();
+    }
+    true
+}
+
 fn get_page(page: &Rc<Page>, pipeline_id: PipelineId) -> Rc<Page> {
     page.find(pipeline_id).expect("ScriptTask: received an event \
         message for a layout channel that is not associated with this script task.\
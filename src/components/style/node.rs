@@ -6,6 +6,7 @@
 //! style.
 
 use selectors::AttrSelector;
+use servo_util::atom::Atom;
 use servo_util::namespace::Namespace;
 
 
@@ -22,7 +23,12 @@ pub trait TNode<E:TElement> : Clone {
 pub trait TElement {
     fn get_attr(&self, namespace: &Namespace, attr: &str) -> Option<&'static str>;
     fn get_link(&self) -> Option<&'static str>;
-    fn get_local_name<'a>(&'a self) -> &'a str;
+    fn get_local_name<'a>(&'a self) -> &'a Atom;
+    /// The `id` attribute, interned.
+    fn get_id(&self) -> Option<Atom>;
+    /// Calls `callback` with each class in the `class` attribute, interned.
+    fn each_class(&self, callback: |&Atom|);
+    fn has_class(&self, name: &Atom) -> bool;
     fn get_namespace<'a>(&'a self) -> &'a Namespace;
     fn get_hover_state(&self) -> bool;
 }
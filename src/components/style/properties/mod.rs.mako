@@ -777,6 +777,7 @@ pub mod longhands {
 
     ${single_keyword("font-style", "normal italic oblique")}
     ${single_keyword("font-variant", "normal")}  // Add small-caps when supported
+    ${single_keyword("font-stretch", "normal ultra-condensed extra-condensed condensed semi-condensed semi-expanded expanded extra-expanded ultra-expanded")}
 
     <%self:single_component_value name="font-weight">
         #[deriving(Clone)]
@@ -2,18 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use collections::hash::Writer;
 use std::collections::hashmap::HashMap;
-use std::ascii::StrAsciiExt;
-use std::hash::Hash;
-use std::hash::sip::SipState;
+use std::hash::hash;
 use std::num::div_rem;
 use sync::Arc;
 
+use servo_util::atom::Atom;
+use servo_util::bloom::BloomFilter;
 use servo_util::namespace;
 use servo_util::smallvec::VecLike;
 use servo_util::sort;
-use servo_util::str::DOMString;
 
 use media_queries::{Device, Screen};
 use node::{TElement, TNode};
@@ -30,35 +28,6 @@ pub enum StylesheetOrigin {
 /// The definition of whitespace per CSS Selectors Level 3 § 4.
 static SELECTOR_WHITESPACE: &'static [char] = &'static [' ', '\t', '\n', '\r', '\x0C'];
 
-/// A newtype struct used to perform lowercase ASCII comparisons without allocating a whole new
-/// string.
-struct LowercaseAsciiString<'a>(&'a str);
-
-impl<'a> Equiv<DOMString> for LowercaseAsciiString<'a> {
-    fn equiv(&self, other: &DOMString) -> bool {
-        let LowercaseAsciiString(this) = *self;
-        this.eq_ignore_ascii_case(other.as_slice())
-    }
-}
-
-impl<'a> Hash for LowercaseAsciiString<'a> {
-    #[inline]
-    fn hash(&self, state: &mut SipState) {
-        let LowercaseAsciiString(this) = *self;
-        for b in this.bytes() {
-            // FIXME(pcwalton): This is a nasty hack for performance. We temporarily violate the
-            // `Ascii` type's invariants by using `to_ascii_nocheck`, but it's OK as we simply
-            // convert to a byte afterward.
-            unsafe {
-                state.write(&[b.to_ascii_nocheck().to_lowercase().to_byte()])
-            };
-        }
-        // Terminate the string with a non-UTF-8 character, to match what the built-in string
-        // `ToBytes` implementation does. (See `libstd/to_bytes.rs`.)
-        state.write(&[0xff]);
-    }
-}
-
 /// Map node attributes to Rules whose last simple selector starts with them.
 ///
 /// e.g.,
@@ -80,10 +49,10 @@ impl<'a> Hash for LowercaseAsciiString<'a> {
 /// node.
 struct SelectorMap {
     // TODO: Tune the initial capacity of the HashMap
-    // FIXME: Use interned strings
-    id_hash: HashMap<DOMString, Vec<Rule>>,
-    class_hash: HashMap<DOMString, Vec<Rule>>,
-    element_hash: HashMap<DOMString, Vec<Rule>>,
+    // FIXME: Intern IDs and class names too
+    id_hash: HashMap<Atom, Vec<Rule>>,
+    class_hash: HashMap<Atom, Vec<Rule>>,
+    element_hash: HashMap<Atom, Vec<Rule>>,
     // For Rules that don't have ID, class, or element selectors.
     universal_rules: Vec<Rule>,
     /// Whether this hash is empty.
@@ -110,6 +79,7 @@ impl SelectorMap {
                               V:VecLike<MatchedProperty>>(
                               &self,
                               node: &N,
+                              parent_bf: Option<&BloomFilter>,
                               matching_rules_list: &mut V,
                               shareable: &mut bool) {
         if self.empty {
@@ -119,39 +89,42 @@ impl SelectorMap {
         // At the end, we're going to sort the rules that we added, so remember where we began.
         let init_len = matching_rules_list.vec_len();
         let element = node.as_element();
-        match element.get_attr(&namespace::Null, "id") {
+        match element.get_id() {
             Some(id) => {
                 SelectorMap::get_matching_rules_from_hash(node,
+                                                          parent_bf,
                                                           &self.id_hash,
-                                                          id,
+                                                          &id,
                                                           matching_rules_list,
                                                           shareable)
             }
             None => {}
         }
 
-        match element.get_attr(&namespace::Null, "class") {
-            Some(ref class_attr) => {
-                for class in class_attr.split(SELECTOR_WHITESPACE) {
-                    SelectorMap::get_matching_rules_from_hash(node,
-                                                                &self.class_hash,
-                                                                class,
-                                                                matching_rules_list,
-                                                                shareable);
-                }
+        element.each_class(|class| {
+            SelectorMap::get_matching_rules_from_hash(node,
+                                                      parent_bf,
+                                                      &self.class_hash,
+                                                      class,
+                                                      matching_rules_list,
+                                                      shareable);
+        });
+
+        // Rules are filed under both spellings of their type selector (see `insert`), so the
+        // element's own name finds them whatever the case-sensitivity.
+        match self.element_hash.find(element.get_local_name()) {
+            Some(rules) => {
+                SelectorMap::get_matching_rules(node,
+                                                parent_bf,
+                                                rules.as_slice(),
+                                                matching_rules_list,
+                                                shareable)
             }
             None => {}
         }
 
-        // HTML elements in HTML documents must be matched case-insensitively.
-        // TODO(pradeep): Case-sensitivity depends on the document type.
-        SelectorMap::get_matching_rules_from_hash_ignoring_case(node,
-                                                                &self.element_hash,
-                                                                element.get_local_name(),
-                                                                matching_rules_list,
-                                                                shareable);
-
         SelectorMap::get_matching_rules(node,
+                                        parent_bf,
                                         self.universal_rules.as_slice(),
                                         matching_rules_list,
                                         shareable);
@@ -164,29 +137,18 @@ impl SelectorMap {
                                     N:TNode<E>,
                                     V:VecLike<MatchedProperty>>(
                                     node: &N,
-                                    hash: &HashMap<DOMString, Vec<Rule>>,
-                                    key: &str,
+                                    parent_bf: Option<&BloomFilter>,
+                                    hash: &HashMap<Atom, Vec<Rule>>,
+                                    key: &Atom,
                                     matching_rules: &mut V,
                                     shareable: &mut bool) {
-        match hash.find_equiv(&key) {
-            Some(rules) => {
-                SelectorMap::get_matching_rules(node, rules.as_slice(), matching_rules, shareable)
-            }
-            None => {}
-        }
-    }
-
-    fn get_matching_rules_from_hash_ignoring_case<E:TElement,
-                                                  N:TNode<E>,
-                                                  V:VecLike<MatchedProperty>>(
-                                                  node: &N,
-                                                  hash: &HashMap<DOMString, Vec<Rule>>,
-                                                  key: &str,
-                                                  matching_rules: &mut V,
-                                                  shareable: &mut bool) {
-        match hash.find_equiv(&LowercaseAsciiString(key)) {
+        match hash.find(key) {
             Some(rules) => {
-                SelectorMap::get_matching_rules(node, rules.as_slice(), matching_rules, shareable)
+                SelectorMap::get_matching_rules(node,
+                                                parent_bf,
+                                                rules.as_slice(),
+                                                matching_rules,
+                                                shareable)
             }
             None => {}
         }
@@ -197,11 +159,12 @@ impl SelectorMap {
                           N:TNode<E>,
                           V:VecLike<MatchedProperty>>(
                           node: &N,
+                          parent_bf: Option<&BloomFilter>,
                           rules: &[Rule],
                           matching_rules: &mut V,
                           shareable: &mut bool) {
         for rule in rules.iter() {
-            if matches_compound_selector(&*rule.selector, node, shareable) {
+            if matches_compound_selector(&*rule.selector, node, parent_bf, shareable) {
                 // TODO(pradeep): Is the cloning inefficient?
                 matching_rules.vec_push(rule.property.clone());
             }
@@ -244,14 +207,12 @@ impl SelectorMap {
 
         match SelectorMap::get_element_name(&rule) {
             Some(element_name) => {
-                match self.element_hash.find_mut(&element_name) {
-                    Some(rules) => {
-                        rules.push(rule);
-                        return;
-                    }
-                    None => {}
+                // HTML elements match the lower-case name, and others match it as written.
+                if element_name.name != element_name.lower_name {
+                    self.element_hash.find_or_insert(element_name.name, vec!())
+                                     .push(rule.clone());
                 }
-                self.element_hash.insert(element_name, vec!(rule));
+                self.element_hash.find_or_insert(element_name.lower_name, vec!()).push(rule);
                 return;
             }
             None => {}
@@ -261,7 +222,7 @@ impl SelectorMap {
     }
 
     /// Retrieve the first ID name in Rule, or None otherwise.
-    fn get_id_name(rule: &Rule) -> Option<String> {
+    fn get_id_name(rule: &Rule) -> Option<Atom> {
         let simple_selector_sequence = &rule.selector.simple_selectors;
         for ss in simple_selector_sequence.iter() {
             match *ss {
@@ -275,7 +236,7 @@ impl SelectorMap {
     }
 
     /// Retrieve the FIRST class name in Rule, or None otherwise.
-    fn get_class_name(rule: &Rule) -> Option<String> {
+    fn get_class_name(rule: &Rule) -> Option<Atom> {
         let simple_selector_sequence = &rule.selector.simple_selectors;
         for ss in simple_selector_sequence.iter() {
             match *ss {
@@ -289,13 +250,11 @@ impl SelectorMap {
     }
 
     /// Retrieve the name if it is a type selector, or None otherwise.
-    fn get_element_name(rule: &Rule) -> Option<String> {
+    fn get_element_name(rule: &Rule) -> Option<LocalName> {
         let simple_selector_sequence = &rule.selector.simple_selectors;
         for ss in simple_selector_sequence.iter() {
             match *ss {
-                // HTML elements in HTML documents must be matched case-insensitively
-                // TODO: case-sensitivity depends on the document type
-                LocalNameSelector(ref name) => return Some(name.as_slice().to_ascii_lower()),
+                LocalNameSelector(ref name) => return Some(name.clone()),
                 _ => {}
             }
         }
@@ -380,11 +339,16 @@ impl Stylist {
     /// The returned boolean indicates whether the style is *shareable*; that is, whether the
     /// matched selectors are simple enough to allow the matching logic to be reduced to the logic
     /// in `css::matching::PrivateMatchMethods::candidate_element_allows_for_style_sharing`.
+    ///
+    /// `parent_bf`, if present, must hold the local names, IDs and classes of all of the element's
+    /// ancestors. It lets most descendant and child selectors be rejected without walking up the
+    /// tree.
     pub fn push_applicable_declarations<E:TElement,
                                         N:TNode<E>,
                                         V:VecLike<MatchedProperty>>(
                                         &self,
                                         element: &N,
+                                        parent_bf: Option<&BloomFilter>,
                                         style_attribute: Option<&PropertyDeclarationBlock>,
                                         pseudo_element: Option<PseudoElement>,
                                         applicable_declarations: &mut V)
@@ -403,10 +367,17 @@ impl Stylist {
 
         // Step 1: Normal rules.
         map.user_agent.normal.get_all_matching_rules(element,
+                                                     parent_bf,
                                                      applicable_declarations,
                                                      &mut shareable);
-        map.user.normal.get_all_matching_rules(element, applicable_declarations, &mut shareable);
-        map.author.normal.get_all_matching_rules(element, applicable_declarations, &mut shareable);
+        map.user.normal.get_all_matching_rules(element,
+                                               parent_bf,
+                                               applicable_declarations,
+                                               &mut shareable);
+        map.author.normal.get_all_matching_rules(element,
+                                                 parent_bf,
+                                                 applicable_declarations,
+                                                 &mut shareable);
 
         // Step 2: Normal style attributes.
         style_attribute.map(|sa| {
@@ -416,6 +387,7 @@ impl Stylist {
 
         // Step 3: Author-supplied `!important` rules.
         map.author.important.get_all_matching_rules(element,
+                                                    parent_bf,
                                                     applicable_declarations,
                                                     &mut shareable);
 
@@ -427,9 +399,11 @@ impl Stylist {
 
         // Step 5: User and UA `!important` rules.
         map.user.important.get_all_matching_rules(element,
+                                                  parent_bf,
                                                   applicable_declarations,
                                                   &mut shareable);
         map.user_agent.important.get_all_matching_rules(element,
+                                                        parent_bf,
                                                         applicable_declarations,
                                                         &mut shareable);
 
@@ -537,9 +511,10 @@ pub fn matches_compound_selector<E:TElement,
                              N:TNode<E>>(
                              selector: &CompoundSelector,
                              element: &N,
+                             parent_bf: Option<&BloomFilter>,
                              shareable: &mut bool)
                              -> bool {
-    match matches_compound_selector_internal(selector, element, shareable) {
+    match matches_compound_selector_internal(selector, element, parent_bf, shareable) {
         Matched => true,
         _ => false
     }
@@ -598,6 +573,7 @@ fn matches_compound_selector_internal<E:TElement,
                                       N:TNode<E>>(
                                       selector: &CompoundSelector,
                                       element: &N,
+                                      parent_bf: Option<&BloomFilter>,
                                       shareable: &mut bool)
                                       -> SelectorMatchingResult {
     if !selector.simple_selectors.iter().all(|simple_selector| {
@@ -614,6 +590,16 @@ fn matches_compound_selector_internal<E:TElement,
                 NextSibling => (true, NotMatchedAndRestartFromClosestDescendant),
                 LaterSibling => (true, NotMatchedAndRestartFromClosestDescendant),
             };
+            // Every ancestor of this element is an ancestor of the element being styled, so if
+            // the filter rules them all out there is nothing to walk up to.
+            if !siblings {
+                match parent_bf {
+                    Some(bf) if !might_match_an_ancestor(&**next_selector, bf) => {
+                        return NotMatchedGlobally
+                    }
+                    _ => {}
+                }
+            }
             let mut node = (*element).clone();
             loop {
                 let next_node = if siblings {
@@ -628,6 +614,7 @@ fn matches_compound_selector_internal<E:TElement,
                 if node.is_element() {
                     let result = matches_compound_selector_internal(&**next_selector,
                                                                     &node,
+                                                                    parent_bf,
                                                                     shareable);
                     match (result, combinator) {
                         // Return the status immediately.
@@ -660,6 +647,45 @@ fn matches_compound_selector_internal<E:TElement,
     }
 }
 
+/// Returns false if the Bloom filter of an element's ancestors shows that none of them can match
+/// `selector`. Only type, ID and class selectors are checked, since those are what go in the
+/// filter.
+fn might_match_an_ancestor(selector: &CompoundSelector, bf: &BloomFilter) -> bool {
+    selector.simple_selectors.iter().all(|simple_selector| {
+        match *simple_selector {
+            LocalNameSelector(ref name) => {
+                bf.might_contain(&name.name) || bf.might_contain(&name.lower_name)
+            }
+            IDSelector(ref id) => bf.might_contain(id),
+            ClassSelector(ref class) => bf.might_contain(class),
+            _ => true,
+        }
+    })
+}
+
+/// Adds an element's local name, ID and classes to a Bloom filter of ancestors, so that they can
+/// be checked when styling the element's descendants.
+pub fn insert_into_bloom_filter<E:TElement>(element: &E, bf: &mut BloomFilter) {
+    each_bloom_filter_hash(element, |hash| bf.insert_hash(hash))
+}
+
+/// Undoes `insert_into_bloom_filter`.
+pub fn remove_from_bloom_filter<E:TElement>(element: &E, bf: &mut BloomFilter) {
+    each_bloom_filter_hash(element, |hash| bf.remove_hash(hash))
+}
+
+/// Calls `f` with the hash of each value that `might_match_an_ancestor` looks for. These are the
+/// same atoms that `matches_simple_selector` compares, so that nothing can match that the filter
+/// rules out.
+fn each_bloom_filter_hash<E:TElement>(element: &E, f: |u64|) {
+    f(hash(element.get_local_name()));
+    match element.get_id() {
+        Some(id) => f(hash(&id)),
+        None => {}
+    }
+    element.each_class(|class| f(hash(class)));
+}
+
 /// Determines whether the given element matches the given single selector.
 ///
 /// NB: If you add support for any new kinds of selectors to this routine, be sure to set
@@ -674,11 +700,17 @@ fn matches_simple_selector<E:TElement,
                            shareable: &mut bool)
                            -> bool {
     match *selector {
-        // TODO: case-sensitivity depends on the document type
-        // TODO: intern element names
+        // HTML elements in HTML documents match type selectors case-insensitively, and everything
+        // else matches them exactly.
+        // TODO: also match exactly in XML documents, which the element can't tell us about yet.
         LocalNameSelector(ref name) => {
             let element = element.as_element();
-            element.get_local_name().eq_ignore_ascii_case(name.as_slice())
+            let name = if *element.get_namespace() == namespace::HTML {
+                &name.lower_name
+            } else {
+                &name.name
+            };
+            *element.get_local_name() == *name
         }
 
         NamespaceSelector(ref namespace) => {
@@ -687,23 +719,15 @@ fn matches_simple_selector<E:TElement,
             element.get_namespace() == namespace
         }
         // TODO: case-sensitivity depends on the document type and quirks mode
-        // TODO: cache and intern IDs on elements.
         IDSelector(ref id) => {
             *shareable = false;
             let element = element.as_element();
-            element.get_attr(&namespace::Null, "id")
-                    .map_or(false, |attr| {
-                attr == id.as_slice()
-            })
+            element.get_id().map_or(false, |attr| attr == *id)
         }
-        // TODO: cache and intern class names on elements.
+        // TODO: case-sensitivity depends on the document type and quirks mode
         ClassSelector(ref class) => {
             let element = element.as_element();
-            element.get_attr(&namespace::Null, "class")
-                    .map_or(false, |attr| {
-                // TODO: case-sensitivity depends on the document type and quirks mode
-                attr.split(SELECTOR_WHITESPACE).any(|c| c == class.as_slice())
-            })
+            element.has_class(class)
         }
 
         AttrExists(ref attr) => {
@@ -954,6 +978,7 @@ fn matches_last_child<E:TElement,N:TNode<E>>(element: &N) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use servo_util::atom::Atom;
     use sync::Arc;
     use super::{MatchedProperty, Rule, SelectorMap};
 
@@ -992,23 +1017,42 @@ mod tests {
     fn test_get_id_name(){
         let rules_list = get_mock_rules([".intro", "#top"]);
         assert_eq!(SelectorMap::get_id_name(rules_list.get(0).get(0)), None);
-        assert_eq!(SelectorMap::get_id_name(rules_list.get(1).get(0)), Some("top".to_string()));
+        assert_eq!(SelectorMap::get_id_name(rules_list.get(1).get(0)),
+                   Some(Atom::from_slice("top")));
     }
 
     #[test]
     fn test_get_class_name(){
         let rules_list = get_mock_rules([".intro.foo", "#top"]);
-        assert_eq!(SelectorMap::get_class_name(rules_list.get(0).get(0)), Some("intro".to_string()));
+        assert_eq!(SelectorMap::get_class_name(rules_list.get(0).get(0)),
+                   Some(Atom::from_slice("intro")));
         assert_eq!(SelectorMap::get_class_name(rules_list.get(1).get(0)), None);
     }
 
     #[test]
     fn test_get_element_name(){
         let rules_list = get_mock_rules(["img.foo", "#top", "IMG", "ImG"]);
-        assert_eq!(SelectorMap::get_element_name(rules_list.get(0).get(0)), Some("img".to_string()));
-        assert_eq!(SelectorMap::get_element_name(rules_list.get(1).get(0)), None);
-        assert_eq!(SelectorMap::get_element_name(rules_list.get(2).get(0)), Some("img".to_string()));
-        assert_eq!(SelectorMap::get_element_name(rules_list.get(3).get(0)), Some("img".to_string()));
+        let lower_name = |rule: &Rule| {
+            SelectorMap::get_element_name(rule).map(|name| name.lower_name)
+        };
+        assert_eq!(lower_name(rules_list.get(0).get(0)), Some(Atom::from_slice("img")));
+        assert_eq!(lower_name(rules_list.get(1).get(0)), None);
+        assert_eq!(lower_name(rules_list.get(2).get(0)), Some(Atom::from_slice("img")));
+        assert_eq!(lower_name(rules_list.get(3).get(0)), Some(Atom::from_slice("img")));
+        let name = SelectorMap::get_element_name(rules_list.get(3).get(0)).unwrap().name;
+        assert_eq!(name.as_slice(), "ImG");
+    }
+
+    #[test]
+    fn test_insert_element_name(){
+        let rules_list = get_mock_rules(["img", "foreignObject"]);
+        let mut selector_map = SelectorMap::new();
+        selector_map.insert(rules_list.get(0).get(0).clone());
+        selector_map.insert(rules_list.get(1).get(0).clone());
+        assert_eq!(selector_map.element_hash.len(), 3);
+        assert!(selector_map.element_hash.find(&Atom::from_slice("img")).is_some());
+        assert!(selector_map.element_hash.find(&Atom::from_slice("foreignObject")).is_some());
+        assert!(selector_map.element_hash.find(&Atom::from_slice("foreignobject")).is_some());
     }
 
     #[test]
@@ -1016,9 +1060,13 @@ mod tests {
         let rules_list = get_mock_rules([".intro.foo", "#top"]);
         let mut selector_map = SelectorMap::new();
         selector_map.insert(rules_list.get(1).get(0).clone());
-        assert_eq!(1, selector_map.id_hash.find_equiv(&("top")).unwrap().get(0).property.source_order);
+        {
+            let top_rules = selector_map.id_hash.find(&Atom::from_slice("top")).unwrap();
+            assert_eq!(1, top_rules.get(0).property.source_order);
+        }
         selector_map.insert(rules_list.get(0).get(0).clone());
-        assert_eq!(0, selector_map.class_hash.find_equiv(&("intro")).unwrap().get(0).property.source_order);
-        assert!(selector_map.class_hash.find_equiv(&("foo")).is_none());
+        let intro_rules = selector_map.class_hash.find(&Atom::from_slice("intro")).unwrap();
+        assert_eq!(0, intro_rules.get(0).property.source_order);
+        assert!(selector_map.class_hash.find(&Atom::from_slice("foo")).is_none());
     }
 }
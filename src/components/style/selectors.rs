@@ -10,6 +10,7 @@ use sync::Arc;
 use cssparser::ast::*;
 use cssparser::parse_nth;
 
+use servo_util::atom::Atom;
 use servo_util::namespace::Namespace;
 use servo_util::namespace;
 
@@ -56,9 +57,9 @@ pub enum Combinator {
 
 #[deriving(PartialEq, Clone)]
 pub enum SimpleSelector {
-    IDSelector(String),
-    ClassSelector(String),
-    LocalNameSelector(String),
+    IDSelector(Atom),
+    ClassSelector(Atom),
+    LocalNameSelector(LocalName),
     NamespaceSelector(Namespace),
 
     // Attribute selectors
@@ -90,10 +91,20 @@ pub enum SimpleSelector {
     // ...
 }
 
+/// The name in a type selector, interned both as written and in lower case, since HTML elements
+/// in HTML documents match it case-insensitively.
+#[deriving(PartialEq, Clone)]
+pub struct LocalName {
+    pub name: Atom,
+    pub lower_name: Atom,
+}
+
+/// The name in an attribute selector, interned both as written and in lower case, since HTML
+/// elements in HTML documents match it case-insensitively.
 #[deriving(PartialEq, Clone)]
 pub struct AttrSelector {
-    pub name: String,
-    pub lower_name: String,
+    pub name: Atom,
+    pub lower_name: Atom,
     pub namespace: NamespaceConstraint,
 }
 
@@ -285,7 +296,12 @@ fn parse_type_selector(iter: &mut Iter, namespaces: &NamespaceMap)
                 AnyNamespace => (),
             }
             match local_name {
-                Some(name) => simple_selectors.push(LocalNameSelector(name)),
+                Some(name) => {
+                    simple_selectors.push(LocalNameSelector(LocalName {
+                        lower_name: Atom::from_string(name.as_slice().to_ascii_lower()),
+                        name: Atom::from_string(name),
+                    }))
+                }
                 None => (),
             }
             TypeSelector(simple_selectors)
@@ -306,13 +322,17 @@ fn parse_one_simple_selector(iter: &mut Iter, namespaces: &NamespaceMap, inside_
                          -> SimpleSelectorParseResult {
     match iter.peek() {
         Some(&IDHash(_)) => match iter.next() {
-            Some(IDHash(id)) => SimpleSelectorResult(IDSelector(id.into_owned())),
+            Some(IDHash(id)) => {
+                SimpleSelectorResult(IDSelector(Atom::from_string(id.into_owned())))
+            }
             _ => fail!("Implementation error, this should not happen."),
         },
         Some(&Delim('.')) => {
             iter.next();
             match iter.next() {
-                Some(Ident(class)) => SimpleSelectorResult(ClassSelector(class.into_owned())),
+                Some(Ident(class)) => {
+                    SimpleSelectorResult(ClassSelector(Atom::from_string(class.into_owned())))
+                }
                 _ => InvalidSimpleSelector,
             }
         }
@@ -440,8 +460,8 @@ fn parse_attribute_selector(content: Vec<ComponentValue>, namespaces: &Namespace
         QualifiedName(_, None) => fail!("Implementation error, this should not happen."),
         QualifiedName(namespace, Some(local_name)) => AttrSelector {
             namespace: namespace,
-            lower_name: local_name.as_slice().to_ascii_lower(),
-            name: local_name,
+            lower_name: Atom::from_string(local_name.as_slice().to_ascii_lower()),
+            name: Atom::from_string(local_name),
         },
     };
     skip_whitespace(iter);
@@ -570,8 +590,10 @@ fn skip_whitespace(iter: &mut Iter) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::ascii::StrAsciiExt;
     use sync::Arc;
     use cssparser;
+    use servo_util::atom::Atom;
     use servo_util::namespace;
     use namespaces::NamespaceMap;
     use super::*;
@@ -586,6 +608,13 @@ mod tests {
             namespaces)
     }
 
+    fn local_name(name: &str) -> SimpleSelector {
+        LocalNameSelector(LocalName {
+            name: Atom::from_slice(name),
+            lower_name: Atom::from_slice(name.to_ascii_lower().as_slice()),
+        })
+    }
+
     fn specificity(a: u32, b: u32, c: u32) -> u32 {
         a << 20 | b << 10 | c
     }
@@ -595,7 +624,7 @@ mod tests {
         assert!(parse("") == None)
         assert!(parse("e") == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
-                simple_selectors: vec!(LocalNameSelector("e".to_string())),
+                simple_selectors: vec!(local_name("e")),
                 next: None,
             }),
             pseudo_element: None,
@@ -603,7 +632,7 @@ mod tests {
         })))
         assert!(parse(".foo") == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
-                simple_selectors: vec!(ClassSelector("foo".to_string())),
+                simple_selectors: vec!(ClassSelector(Atom::from_slice("foo"))),
                 next: None,
             }),
             pseudo_element: None,
@@ -611,7 +640,7 @@ mod tests {
         })))
         assert!(parse("#bar") == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
-                simple_selectors: vec!(IDSelector("bar".to_string())),
+                simple_selectors: vec!(IDSelector(Atom::from_slice("bar"))),
                 next: None,
             }),
             pseudo_element: None,
@@ -619,9 +648,9 @@ mod tests {
         })))
         assert!(parse("e.foo#bar") == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
-                simple_selectors: vec!(LocalNameSelector("e".to_string()),
-                                       ClassSelector("foo".to_string()),
-                                       IDSelector("bar".to_string())),
+                simple_selectors: vec!(local_name("e"),
+                                       ClassSelector(Atom::from_slice("foo")),
+                                       IDSelector(Atom::from_slice("bar"))),
                 next: None,
             }),
             pseudo_element: None,
@@ -629,10 +658,10 @@ mod tests {
         })))
         assert!(parse("e.foo #bar") == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
-                simple_selectors: vec!(IDSelector("bar".to_string())),
+                simple_selectors: vec!(IDSelector(Atom::from_slice("bar"))),
                 next: Some((box CompoundSelector {
-                    simple_selectors: vec!(LocalNameSelector("e".to_string()),
-                                           ClassSelector("foo".to_string())),
+                    simple_selectors: vec!(local_name("e"),
+                                           ClassSelector(Atom::from_slice("foo"))),
                     next: None,
                 }, Descendant)),
             }),
@@ -645,8 +674,8 @@ mod tests {
         assert!(parse_ns("[Foo]", &namespaces) == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
                 simple_selectors: vec!(AttrExists(AttrSelector {
-                    name: "Foo".to_string(),
-                    lower_name: "foo".to_string(),
+                    name: Atom::from_slice("Foo"),
+                    lower_name: Atom::from_slice("foo"),
                     namespace: SpecificNamespace(namespace::Null),
                 })),
                 next: None,
@@ -660,8 +689,8 @@ mod tests {
         assert!(parse_ns("[Foo]", &namespaces) == Some(vec!(Selector{
             compound_selectors: Arc::new(CompoundSelector {
                 simple_selectors: vec!(AttrExists(AttrSelector {
-                    name: "Foo".to_string(),
-                    lower_name: "foo".to_string(),
+                    name: Atom::from_slice("Foo"),
+                    lower_name: Atom::from_slice("foo"),
                     namespace: SpecificNamespace(namespace::Null),
                 })),
                 next: None,
@@ -674,7 +703,7 @@ mod tests {
             compound_selectors: Arc::new(CompoundSelector {
                 simple_selectors: vec!(
                     NamespaceSelector(namespace::MathML),
-                    LocalNameSelector("e".to_string()),
+                    local_name("e"),
                 ),
                 next: None,
             }),
@@ -694,7 +723,7 @@ mod tests {
             compound_selectors: Arc::new(CompoundSelector {
                 simple_selectors: vec!(),
                 next: Some((box CompoundSelector {
-                    simple_selectors: vec!(LocalNameSelector("div".to_string())),
+                    simple_selectors: vec!(local_name("div")),
                     next: None,
                 }, Descendant)),
             }),
@@ -0,0 +1,251 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Interned strings. Every distinct string is stored once in a table shared by the whole
+//! process, so atoms can be sent between tasks and compared, or hashed, by pointer rather than by
+//! contents. Atoms are reference counted, and a string leaves the table once nothing refers to it,
+//! so names from web content (tags, attributes, IDs, classes) don't accumulate forever.
+
+use serialize::{Encodable, Encoder};
+use std::collections::hashmap::HashMap;
+use std::fmt;
+use std::hash::{Hash, Writer};
+use std::mem;
+use std::rt::mutex::{StaticNativeMutex, NATIVE_MUTEX_INIT};
+use std::str::{MaybeOwned, Owned, Slice};
+use std::sync::atomics::{AtomicUint, SeqCst};
+
+struct AtomEntry {
+    string: String,
+    /// The number of atoms pointing here. Once it drops to zero the entry is dead: it is never
+    /// handed out again, and the task that dropped the last atom removes it and frees it.
+    ref_count: AtomicUint,
+}
+
+impl AtomEntry {
+    /// Adds a reference, unless the entry is already dead.
+    fn try_add_ref(&self) -> bool {
+        let mut count = self.ref_count.load(SeqCst);
+        loop {
+            if count == 0 {
+                return false
+            }
+            let old = self.ref_count.compare_and_swap(count, count + 1, SeqCst);
+            if old == count {
+                return true
+            }
+            count = old;
+        }
+    }
+
+    /// The entry's string, as the table's key for it. The key must be removed before the entry
+    /// is freed.
+    unsafe fn key(&self) -> &'static str {
+        mem::transmute(self.string.as_slice())
+    }
+}
+
+/// Guards `ATOM_TABLE`.
+static ATOM_TABLE_LOCK: StaticNativeMutex = NATIVE_MUTEX_INIT;
+
+/// Maps each interned string to its entry, which the key borrows from. Created on first use, and
+/// only touched with `ATOM_TABLE_LOCK` held.
+static mut ATOM_TABLE: *mut HashMap<&'static str, *AtomEntry> =
+    0 as *mut HashMap<&'static str, *AtomEntry>;
+
+/// Returns the table. `ATOM_TABLE_LOCK` must be held.
+unsafe fn atom_table() -> &'static mut HashMap<&'static str, *AtomEntry> {
+    if ATOM_TABLE.is_null() {
+        ATOM_TABLE = mem::transmute(box HashMap::<&'static str, *AtomEntry>::new());
+    }
+    &mut *ATOM_TABLE
+}
+
+pub struct Atom {
+    entry: *AtomEntry,
+}
+
+impl Atom {
+    /// Interns `string`.
+    pub fn from_slice(string: &str) -> Atom {
+        Atom::intern(Slice(string))
+    }
+
+    /// Interns `string`, keeping it as the atom's storage if it's new.
+    pub fn from_string(string: String) -> Atom {
+        Atom::intern(Owned(string))
+    }
+
+    /// Returns the atom for `string` if it has been interned, without interning it. Nothing that
+    /// isn't interned can be equal to an atom, so this is a cheap way to rule out a match.
+    pub fn find(string: &str) -> Option<Atom> {
+        unsafe {
+            let _guard = ATOM_TABLE_LOCK.lock();
+            match atom_table().find_equiv(&string) {
+                Some(&entry) if (*entry).try_add_ref() => Some(Atom { entry: entry }),
+                _ => None,
+            }
+        }
+    }
+
+    fn intern(string: MaybeOwned) -> Atom {
+        unsafe {
+            let _guard = ATOM_TABLE_LOCK.lock();
+            let table = atom_table();
+            let existing = table.find_equiv(&string.as_slice()).map(|&entry| entry);
+            match existing {
+                Some(entry) if (*entry).try_add_ref() => return Atom { entry: entry },
+                // The entry is dead, and will be freed once its last atom's task gets the lock.
+                Some(entry) => {
+                    table.remove(&(*entry).key());
+                }
+                None => {}
+            }
+
+            let entry: *AtomEntry = mem::transmute(box AtomEntry {
+                string: string.into_string(),
+                ref_count: AtomicUint::new(1),
+            });
+            table.insert((*entry).key(), entry);
+            Atom {
+                entry: entry,
+            }
+        }
+    }
+
+    fn as_ptr(&self) -> *AtomEntry {
+        self.entry
+    }
+}
+
+impl Clone for Atom {
+    #[inline]
+    fn clone(&self) -> Atom {
+        unsafe {
+            (*self.entry).ref_count.fetch_add(1, SeqCst);
+        }
+        Atom {
+            entry: self.entry,
+        }
+    }
+}
+
+impl Drop for Atom {
+    fn drop(&mut self) {
+        unsafe {
+            if (*self.entry).ref_count.fetch_sub(1, SeqCst) != 1 {
+                return
+            }
+            let _guard = ATOM_TABLE_LOCK.lock();
+            let table = atom_table();
+            // `intern` may already have replaced the dead entry with a new one.
+            let key = (*self.entry).key();
+            let is_current = table.find(&key).map_or(false, |&entry| entry == self.entry);
+            if is_current {
+                table.remove(&key);
+            }
+            let _: Box<AtomEntry> = mem::transmute(self.entry);
+        }
+    }
+}
+
+impl PartialEq for Atom {
+    #[inline]
+    fn eq(&self, other: &Atom) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+}
+
+impl Eq for Atom {}
+
+impl<S: Writer> Hash<S> for Atom {
+    #[inline]
+    fn hash(&self, state: &mut S) {
+        (self.as_ptr() as uint).hash(state)
+    }
+}
+
+impl Str for Atom {
+    #[inline]
+    fn as_slice<'a>(&'a self) -> &'a str {
+        unsafe {
+            (*self.entry).string.as_slice()
+        }
+    }
+}
+
+impl fmt::Show for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_slice())
+    }
+}
+
+impl<S: Encoder<E>, E> Encodable<S, E> for Atom {
+    fn encode(&self, s: &mut S) -> Result<(), E> {
+        s.emit_str(self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atom::Atom;
+
+    use std::collections::hashmap::HashSet;
+    use std::task::TaskBuilder;
+
+    #[test]
+    fn test_interning() {
+        let div = Atom::from_slice("div");
+        assert!(div == Atom::from_slice("div"));
+        assert!(div == Atom::from_string("div".to_string()));
+        assert!(div != Atom::from_slice("DIV"));
+        assert_eq!(div.as_slice(), "div");
+        assert_eq!(format!("{}", div), "div".to_string());
+    }
+
+    #[test]
+    fn test_find() {
+        assert!(Atom::find("test-find-never-interned").is_none());
+        let atom = Atom::from_string("test-find-interned".to_string());
+        assert!(Atom::find("test-find-interned") == Some(atom));
+    }
+
+    #[test]
+    fn test_freed_when_unused() {
+        let atom = Atom::from_slice("test-freed-when-unused");
+        let clone = atom.clone();
+        drop(atom);
+        assert!(Atom::find("test-freed-when-unused") == Some(clone.clone()));
+        drop(clone);
+        assert!(Atom::find("test-freed-when-unused").is_none());
+
+        // Interning the string again makes a new entry.
+        let atom = Atom::from_string("test-freed-when-unused".to_string());
+        assert_eq!(atom.as_slice(), "test-freed-when-unused");
+        assert!(Atom::find("test-freed-when-unused") == Some(atom));
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut set = HashSet::new();
+        set.insert(Atom::from_slice("span"));
+        assert!(set.contains(&Atom::from_string("span".to_string())));
+        assert!(!set.contains(&Atom::from_slice("p")));
+    }
+
+    #[test]
+    fn test_shared_between_tasks() {
+        let (sender, receiver) = channel();
+        for _ in range(0u, 4) {
+            let sender = sender.clone();
+            TaskBuilder::new().spawn(proc() {
+                sender.send(Atom::from_slice("test-shared-between-tasks"))
+            });
+        }
+        let atom = Atom::from_slice("test-shared-between-tasks");
+        for _ in range(0u, 4) {
+            assert!(receiver.recv() == atom);
+        }
+    }
+}
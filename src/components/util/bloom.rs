@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A counting Bloom filter. Selector matching keeps one holding the names, IDs and classes of the
+//! ancestors of the element being styled, so that most descendant selectors can be rejected
+//! without walking up the tree. Because it counts, entries can be removed again as the traversal
+//! moves back up the DOM, instead of the filter having to be rebuilt.
+
+use std::hash::{Hash, hash};
+
+/// The number of bits of each hash used to pick a counter.
+static KEY_SIZE: uint = 12;
+static ARRAY_SIZE: uint = 1 << KEY_SIZE;
+static KEY_MASK: u64 = (1u64 << KEY_SIZE) - 1;
+
+/// A counter that reaches this value has overflowed, so the true count is unknown. It is never
+/// decremented again (until the filter is cleared), which can only cause false positives.
+static SATURATED: u8 = 255;
+
+/// A Bloom filter with 8-bit counters and two hash functions, both taken from one 64-bit hash.
+///
+/// Like any Bloom filter, `might_contain` can return true for values that were never inserted,
+/// but never returns false for a value that is in the filter. Removing a value that wasn't
+/// inserted breaks that guarantee.
+pub struct BloomFilter {
+    counters: [u8, ..ARRAY_SIZE],
+}
+
+impl BloomFilter {
+    /// Creates an empty filter. At 4KB it is best kept in a box.
+    pub fn new() -> BloomFilter {
+        BloomFilter {
+            counters: [0, ..ARRAY_SIZE],
+        }
+    }
+
+    #[inline]
+    pub fn insert<T:Hash>(&mut self, value: &T) {
+        self.insert_hash(hash(value))
+    }
+
+    #[inline]
+    pub fn remove<T:Hash>(&mut self, value: &T) {
+        self.remove_hash(hash(value))
+    }
+
+    #[inline]
+    pub fn might_contain<T:Hash>(&self, value: &T) -> bool {
+        self.might_contain_hash(hash(value))
+    }
+
+    /// Inserts a value given its hash, for callers that hash the same value many times.
+    pub fn insert_hash(&mut self, hash: u64) {
+        for &index in [first_index(hash), second_index(hash)].iter() {
+            let counter = &mut self.counters[index];
+            if *counter != SATURATED {
+                *counter += 1;
+            }
+        }
+    }
+
+    pub fn remove_hash(&mut self, hash: u64) {
+        for &index in [first_index(hash), second_index(hash)].iter() {
+            let counter = &mut self.counters[index];
+            if *counter != SATURATED && *counter != 0 {
+                *counter -= 1;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn might_contain_hash(&self, hash: u64) -> bool {
+        self.counters[first_index(hash)] != 0 && self.counters[second_index(hash)] != 0
+    }
+
+    /// Removes everything, including saturated counters.
+    pub fn clear(&mut self) {
+        for counter in self.counters.mut_iter() {
+            *counter = 0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.iter().all(|&counter| counter == 0)
+    }
+}
+
+impl Clone for BloomFilter {
+    fn clone(&self) -> BloomFilter {
+        BloomFilter {
+            counters: self.counters,
+        }
+    }
+}
+
+#[inline]
+fn first_index(hash: u64) -> uint {
+    (hash & KEY_MASK) as uint
+}
+
+#[inline]
+fn second_index(hash: u64) -> uint {
+    ((hash >> 32) & KEY_MASK) as uint
+}
+
+#[cfg(test)]
+mod tests {
+    use bloom::{BloomFilter, SATURATED};
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut bf = box BloomFilter::new();
+        assert!(bf.is_empty());
+
+        for i in range(0u, 100) {
+            bf.insert(&i);
+        }
+        for i in range(0u, 100) {
+            assert!(bf.might_contain(&i));
+        }
+
+        // A few false positives are fine, but nearly every value should be rejected.
+        let false_positives = range(1000u, 2000).filter(|i| bf.might_contain(i)).count();
+        assert!(false_positives < 50, "too many false positives: {}", false_positives);
+
+        for i in range(0u, 50) {
+            bf.remove(&i);
+        }
+        for i in range(50u, 100) {
+            assert!(bf.might_contain(&i));
+        }
+        for i in range(50u, 100) {
+            bf.remove(&i);
+        }
+        assert!(bf.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_values() {
+        let mut bf = box BloomFilter::new();
+        bf.insert(&"div");
+        bf.insert(&"div");
+        bf.remove(&"div");
+        assert!(bf.might_contain(&"div"));
+        bf.remove(&"div");
+        assert!(!bf.might_contain(&"div"));
+    }
+
+    #[test]
+    fn test_saturation() {
+        let mut bf = box BloomFilter::new();
+        for _ in range(0, SATURATED as uint + 10) {
+            bf.insert(&"span");
+        }
+        for _ in range(0, SATURATED as uint + 10) {
+            bf.remove(&"span");
+        }
+        // The real count was lost, so the value has to stay in rather than risk a false negative.
+        assert!(bf.might_contain(&"span"));
+        bf.clear();
+        assert!(!bf.might_contain(&"span"));
+        assert!(bf.is_empty());
+    }
+}
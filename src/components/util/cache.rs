@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use std::collections::hashmap::HashMap;
+use std::collections::hashmap::{Entries, HashMap};
 use rand::Rng;
 use std::hash::{Hash, sip};
 use std::rand::task_rng;
@@ -80,6 +80,14 @@ impl<K: Clone + PartialEq + Eq + Hash, V: Clone> HashCache<K,V> {
           entries: HashMap::new(),
         }
     }
+
+    pub fn iter<'a>(&'a self) -> Entries<'a,K,V> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> uint {
+        self.entries.len()
+    }
 }
 
 impl<K: Clone + PartialEq + Eq + Hash, V: Clone> Cache<K,V> for HashCache<K,V> {
@@ -118,9 +126,30 @@ fn test_hashcache() {
     assert!(cache.find(&2).is_some());
 }
 
+/// Told about every entry an `LRUCache` evicts, so that its owner can release whatever the entry
+/// was holding on to.
+pub trait EvictionHandler<K, V> {
+    fn evicted(&mut self, key: K, value: V);
+}
+
+/// How well an `LRUCache` has been doing.
+#[deriving(Clone, PartialEq, Show)]
+pub struct CacheStats {
+    /// Lookups that found their key.
+    pub hits: uint,
+    /// Lookups that didn't.
+    pub misses: uint,
+    /// Entries thrown out, to make room or by `evict_all`.
+    pub evictions: uint,
+}
+
+/// A cache that holds up to a fixed number of entries, evicting the least recently used one to
+/// make room. Entries are kept in a vector in order of use, so it's meant for small caches.
 pub struct LRUCache<K, V> {
     entries: Vec<(K, V)>,
     cache_size: uint,
+    stats: CacheStats,
+    eviction_handler: Option<Box<EvictionHandler<K, V>>>,
 }
 
 impl<K: Clone + PartialEq, V: Clone> LRUCache<K,V> {
@@ -128,9 +157,23 @@ impl<K: Clone + PartialEq, V: Clone> LRUCache<K,V> {
         LRUCache {
           entries: vec!(),
           cache_size: size,
+          stats: CacheStats {
+              hits: 0,
+              misses: 0,
+              evictions: 0,
+          },
+          eviction_handler: None,
         }
     }
 
+    /// Creates a cache that hands every entry it evicts to `handler`.
+    pub fn with_eviction_handler(size: uint, handler: Box<EvictionHandler<K, V>>)
+                                 -> LRUCache<K, V> {
+        let mut cache = LRUCache::new(size);
+        cache.eviction_handler = Some(handler);
+        cache
+    }
+
     #[inline]
     pub fn touch(&mut self, pos: uint) -> V {
         let last_index = self.entries.len() - 1;
@@ -144,25 +187,77 @@ impl<K: Clone + PartialEq, V: Clone> LRUCache<K,V> {
     pub fn iter<'a>(&'a self) -> Items<'a,(K,V)> {
         self.entries.iter()
     }
+
+    pub fn len(&self) -> uint {
+        self.entries.len()
+    }
+
+    /// The most entries the cache holds.
+    pub fn capacity(&self) -> uint {
+        self.cache_size
+    }
+
+    /// Changes how many entries the cache holds, evicting the least recently used ones if there
+    /// are now too many.
+    pub fn set_capacity(&mut self, size: uint) {
+        self.cache_size = size;
+        while self.entries.len() > size {
+            self.evict_oldest()
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.clone()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats {
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let (key, value) = match self.entries.remove(0) {
+            Some(entry) => entry,
+            None => return,
+        };
+        self.stats.evictions += 1;
+        match self.eviction_handler {
+            Some(ref mut handler) => handler.evicted(key, value),
+            None => {}
+        }
+    }
+
+    /// Finds the position of `key`, counting the lookup as a hit or a miss.
+    fn lookup(&mut self, key: &K) -> Option<uint> {
+        let position = self.entries.iter().position(|&(ref k, _)| *k == *key);
+        match position {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        position
+    }
 }
 
 impl<K: Clone + PartialEq, V: Clone> Cache<K,V> for LRUCache<K,V> {
     fn insert(&mut self, key: K, val: V) {
         if self.entries.len() == self.cache_size {
-            self.entries.remove(0);
+            self.evict_oldest();
         }
         self.entries.push((key, val));
     }
 
     fn find(&mut self, key: &K) -> Option<V> {
-        match self.entries.iter().position(|&(ref k, _)| *k == *key) {
+        match self.lookup(key) {
             Some(pos) => Some(self.touch(pos)),
             None      => None,
         }
     }
 
     fn find_or_create(&mut self, key: &K, blk: |&K| -> V) -> V {
-        match self.entries.iter().position(|&(ref k, _)| *k == *key) {
+        match self.lookup(key) {
             Some(pos) => self.touch(pos),
             None => {
                 let val = blk(key);
@@ -173,7 +268,9 @@ impl<K: Clone + PartialEq, V: Clone> Cache<K,V> for LRUCache<K,V> {
     }
 
     fn evict_all(&mut self) {
-        self.entries.clear();
+        while !self.entries.is_empty() {
+            self.evict_oldest()
+        }
     }
 }
 
@@ -277,3 +374,57 @@ fn test_lru_cache() {
     assert!(cache.find(&3).is_none()); // (4, 1) (no change)
     assert!(cache.find(&4).is_some()); // (1, 4)
 }
+
+#[cfg(test)]
+struct RecordEvictions {
+    sender: Sender<(int, &'static str)>,
+}
+
+#[cfg(test)]
+impl EvictionHandler<int, &'static str> for RecordEvictions {
+    fn evicted(&mut self, key: int, value: &'static str) {
+        self.sender.send((key, value))
+    }
+}
+
+#[test]
+fn test_lru_cache_stats() {
+    let mut cache = LRUCache::new(2);
+    cache.insert(1, "one");
+    cache.insert(2, "two");
+    cache.insert(3, "three");
+
+    assert!(cache.find(&1).is_none());
+    assert!(cache.find(&2).is_some());
+    cache.find_or_create(&4, |_| "four");
+    cache.find_or_create(&4, |_| "four");
+    assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 2, evictions: 2 });
+    assert_eq!(cache.len(), 2);
+
+    cache.reset_stats();
+    assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 0, evictions: 0 });
+}
+
+#[test]
+fn test_lru_cache_eviction_handler() {
+    let (sender, receiver) = channel();
+    let mut cache = LRUCache::with_eviction_handler(2, box RecordEvictions {
+        sender: sender,
+    });
+    cache.insert(1, "one");
+    cache.insert(2, "two");
+    assert!(cache.find(&1).is_some());
+    cache.insert(3, "three");
+    assert_eq!(receiver.try_recv(), Ok((2, "two")));
+
+    cache.set_capacity(1);
+    assert_eq!(cache.capacity(), 1);
+    assert_eq!(receiver.try_recv(), Ok((1, "one")));
+    assert!(cache.find(&3).is_some());
+
+    cache.evict_all();
+    assert_eq!(receiver.try_recv(), Ok((3, "three")));
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(cache.len(), 0);
+    assert_eq!(cache.stats().evictions, 3);
+}
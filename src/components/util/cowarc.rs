@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! An atomically reference-counted pointer that copies its value on write. Cloning one is as cheap
+//! as cloning an `Arc`, and the value is only copied if it is mutated while shared, so large
+//! structures such as stylesheets can be handed between tasks and only occasionally changed.
+
+use std::mem;
+use std::sync::atomics::{AtomicUint, Acquire, Release, Relaxed, fence};
+
+struct CowArcAlloc<T> {
+    ref_count: AtomicUint,
+    value: T,
+}
+
+pub struct CowArc<T> {
+    ptr: *mut CowArcAlloc<T>,
+}
+
+impl<T:Send+Share+Clone> CowArc<T> {
+    #[inline]
+    pub fn new(value: T) -> CowArc<T> {
+        unsafe {
+            CowArc {
+                ptr: mem::transmute(box CowArcAlloc {
+                    ref_count: AtomicUint::new(1),
+                    value: value,
+                }),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get<'a>(&'a self) -> &'a T {
+        unsafe {
+            &(*self.ptr).value
+        }
+    }
+
+    /// Returns true if no other `CowArc` shares this value, so that it can be mutated in place.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        // Acquire, so that other tasks' last uses of the value happen before we mutate it.
+        unsafe {
+            (*self.ptr).ref_count.load(Acquire) == 1
+        }
+    }
+
+    /// Returns a mutable reference to the value, first copying it if it is shared. The copy is
+    /// then this `CowArc`'s alone, and the other sharers keep the original.
+    pub fn make_unique<'a>(&'a mut self) -> &'a mut T {
+        if !self.is_unique() {
+            let copy = self.get().clone();
+            *self = CowArc::new(copy)
+        }
+        // Nobody else can reach the value now, and nobody can clone this `CowArc` while we have it
+        // borrowed mutably.
+        unsafe {
+            &mut (*self.ptr).value
+        }
+    }
+
+    /// Returns true if both point to the same value.
+    #[inline]
+    pub fn ptr_eq(&self, other: &CowArc<T>) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<T:Send+Share+Clone> Clone for CowArc<T> {
+    #[inline]
+    fn clone(&self) -> CowArc<T> {
+        unsafe {
+            // A new reference can only be made from an existing one, so nothing needs to be
+            // ordered here.
+            (*self.ptr).ref_count.fetch_add(1, Relaxed);
+        }
+        CowArc {
+            ptr: self.ptr,
+        }
+    }
+}
+
+impl<T:Send+Share+Clone> Deref<T> for CowArc<T> {
+    #[inline]
+    fn deref<'a>(&'a self) -> &'a T {
+        self.get()
+    }
+}
+
+impl<T> Drop for CowArc<T> {
+    fn drop(&mut self) {
+        unsafe {
+            if (*self.ptr).ref_count.fetch_sub(1, Release) != 1 {
+                return
+            }
+            // Make sure every other task's use of the value happens before we free it.
+            fence(Acquire);
+            let _: Box<CowArcAlloc<T>> = mem::transmute(self.ptr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cowarc::CowArc;
+
+    use std::sync::atomics::{AtomicUint, INIT_ATOMIC_UINT, SeqCst};
+
+    #[test]
+    fn test_copy_on_write() {
+        let mut a = CowArc::new(vec!(1u, 2, 3));
+        assert!(a.is_unique());
+        let b = a.clone();
+        assert!(!a.is_unique());
+        assert!(a.ptr_eq(&b));
+
+        a.make_unique().push(4);
+        assert!(!a.ptr_eq(&b));
+        assert!(a.is_unique() && b.is_unique());
+        assert_eq!(a.get(), &vec!(1, 2, 3, 4));
+        assert_eq!(*b, vec!(1, 2, 3));
+    }
+
+    #[test]
+    fn test_unique_mutates_in_place() {
+        let mut a = CowArc::new(vec!(1u));
+        let before: *Vec<uint> = a.get();
+        a.make_unique().push(2);
+        let after: *Vec<uint> = a.get();
+        assert!(before == after);
+        assert_eq!(*a, vec!(1, 2));
+    }
+
+    static mut DROPS: AtomicUint = INIT_ATOMIC_UINT;
+
+    #[deriving(Clone)]
+    struct CountDrops;
+
+    impl Drop for CountDrops {
+        fn drop(&mut self) {
+            unsafe {
+                DROPS.fetch_add(1, SeqCst);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_between_tasks() {
+        let a = CowArc::new(CountDrops);
+        let (chan, port) = channel();
+        for _ in range(0u, 4) {
+            let chan = chan.clone();
+            let a = a.clone();
+            spawn(proc() {
+                let mut a = a;
+                a.make_unique();
+                chan.send(a);
+            });
+        }
+        // The original was shared when each task wrote to it, so each got its own copy.
+        let copies: Vec<CowArc<CountDrops>> = range(0u, 4).map(|_| port.recv()).collect();
+        assert!(a.is_unique());
+        assert!(copies.iter().all(|copy| copy.is_unique() && !copy.ptr_eq(&a)));
+
+        drop(copies);
+        drop(a);
+        assert_eq!(unsafe { DROPS.load(SeqCst) }, 5);
+    }
+}
@@ -299,3 +299,558 @@ pub fn f32_rect_to_au_rect(rect: Rect<f32>) -> Rect<Au> {
          Size2D(Au::from_frac32_px(rect.size.width), Au::from_frac32_px(rect.size.height)))
 }
 
+
+/// A 4×4 matrix for 3D transforms, shared by style, layout and the compositor.
+///
+/// Entries are named as in CSSOM's `DOMMatrix`: `mXY` is column `X`, row `Y`, so the translation
+/// is in `m41`, `m42` and `m43`. Points are column vectors, and `a * b` is the transform that
+/// applies `b` first and then `a`, which is how a CSS transform list composes.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Matrix4 {
+    pub m11: f64, pub m12: f64, pub m13: f64, pub m14: f64,
+    pub m21: f64, pub m22: f64, pub m23: f64, pub m24: f64,
+    pub m31: f64, pub m32: f64, pub m33: f64, pub m34: f64,
+    pub m41: f64, pub m42: f64, pub m43: f64, pub m44: f64,
+}
+
+/// The parts of a 3D transform matrix, as produced by the "unmatrix" algorithm in CSS Transforms:
+/// http://dev.w3.org/csswg/css-transforms/#decomposing-a-3d-matrix
+pub struct DecomposedMatrix4 {
+    pub translate: [f64, ..3],
+    pub scale: [f64, ..3],
+    /// The XY, XZ and YZ shear factors.
+    pub skew: [f64, ..3],
+    pub perspective: [f64, ..4],
+    pub quaternion: [f64, ..4],
+}
+
+impl Matrix4 {
+    /// Creates a matrix from its entries in column-major order, which is the argument order of
+    /// CSS `matrix3d()`.
+    pub fn from_array(m: [f64, ..16]) -> Matrix4 {
+        Matrix4 {
+            m11: m[0], m12: m[1], m13: m[2], m14: m[3],
+            m21: m[4], m22: m[5], m23: m[6], m24: m[7],
+            m31: m[8], m32: m[9], m33: m[10], m34: m[11],
+            m41: m[12], m42: m[13], m43: m[14], m44: m[15],
+        }
+    }
+
+    /// Returns the entries in column-major order.
+    pub fn to_array(&self) -> [f64, ..16] {
+        [
+            self.m11, self.m12, self.m13, self.m14,
+            self.m21, self.m22, self.m23, self.m24,
+            self.m31, self.m32, self.m33, self.m34,
+            self.m41, self.m42, self.m43, self.m44,
+        ]
+    }
+
+    fn from_columns(c: [[f64, ..4], ..4]) -> Matrix4 {
+        Matrix4 {
+            m11: c[0][0], m12: c[0][1], m13: c[0][2], m14: c[0][3],
+            m21: c[1][0], m22: c[1][1], m23: c[1][2], m24: c[1][3],
+            m31: c[2][0], m32: c[2][1], m33: c[2][2], m34: c[2][3],
+            m41: c[3][0], m42: c[3][1], m43: c[3][2], m44: c[3][3],
+        }
+    }
+
+    /// Returns the entries as `columns[X - 1][Y - 1]`.
+    fn to_columns(&self) -> [[f64, ..4], ..4] {
+        [
+            [self.m11, self.m12, self.m13, self.m14],
+            [self.m21, self.m22, self.m23, self.m24],
+            [self.m31, self.m32, self.m33, self.m34],
+            [self.m41, self.m42, self.m43, self.m44],
+        ]
+    }
+
+    pub fn identity() -> Matrix4 {
+        Matrix4::from_array([
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    /// The CSS `translate3d()` transform.
+    pub fn translate(x: f64, y: f64, z: f64) -> Matrix4 {
+        Matrix4::from_array([
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            x, y, z, 1.0,
+        ])
+    }
+
+    /// The CSS `scale3d()` transform.
+    pub fn scale(x: f64, y: f64, z: f64) -> Matrix4 {
+        Matrix4::from_array([
+            x, 0.0, 0.0, 0.0,
+            0.0, y, 0.0, 0.0,
+            0.0, 0.0, z, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    /// The CSS `rotate3d()` transform: a rotation by `angle` radians about the vector
+    /// `(x, y, z)`. Returns the identity if the vector is zero, as CSS does.
+    pub fn rotate(x: f64, y: f64, z: f64, angle: f64) -> Matrix4 {
+        let length = (x * x + y * y + z * z).sqrt();
+        if length == 0.0 {
+            return Matrix4::identity()
+        }
+        let (x, y, z) = (x / length, y / length, z / length);
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        let sc = sin * cos;
+        let sq = sin * sin;
+        Matrix4::from_array([
+            1.0 - 2.0 * (y * y + z * z) * sq,
+            2.0 * (x * y * sq + z * sc),
+            2.0 * (x * z * sq - y * sc),
+            0.0,
+
+            2.0 * (x * y * sq - z * sc),
+            1.0 - 2.0 * (x * x + z * z) * sq,
+            2.0 * (y * z * sq + x * sc),
+            0.0,
+
+            2.0 * (x * z * sq + y * sc),
+            2.0 * (y * z * sq - x * sc),
+            1.0 - 2.0 * (x * x + y * y) * sq,
+            0.0,
+
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    /// The CSS `skew()` transform, with both angles in radians.
+    pub fn skew(x_angle: f64, y_angle: f64) -> Matrix4 {
+        Matrix4::from_array([
+            1.0, y_angle.tan(), 0.0, 0.0,
+            x_angle.tan(), 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    /// The CSS `perspective()` transform. A depth of zero or less has no effect.
+    pub fn perspective(depth: f64) -> Matrix4 {
+        let mut result = Matrix4::identity();
+        if depth > 0.0 {
+            result.m34 = -1.0 / depth;
+        }
+        result
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Matrix4::identity()
+    }
+
+    /// Returns true if this matrix is equivalent to a 2D affine transform.
+    pub fn is_2d(&self) -> bool {
+        self.m13 == 0.0 && self.m14 == 0.0 &&
+            self.m23 == 0.0 && self.m24 == 0.0 &&
+            self.m31 == 0.0 && self.m32 == 0.0 && self.m33 == 1.0 && self.m34 == 0.0 &&
+            self.m43 == 0.0 && self.m44 == 1.0
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let c = self.to_columns();
+        let mut t = [[0.0, ..4], ..4];
+        for i in range(0u, 4) {
+            for j in range(0u, 4) {
+                t[i][j] = c[j][i];
+            }
+        }
+        Matrix4::from_columns(t)
+    }
+
+    pub fn determinant(&self) -> f64 {
+        // Gaussian elimination with partial pivoting. The determinant of the transpose is the
+        // same, so it doesn't matter which way round the entries are.
+        let mut a = self.to_columns();
+        let mut determinant = 1.0;
+        for i in range(0u, 4) {
+            let pivot = pivot_row(&a, i);
+            if a[pivot][i] == 0.0 {
+                return 0.0
+            }
+            if pivot != i {
+                swap_rows(&mut a, pivot, i);
+                determinant = -determinant;
+            }
+            determinant *= a[i][i];
+            for j in range(i + 1, 4) {
+                let factor = a[j][i] / a[i][i];
+                for k in range(i, 4) {
+                    a[j][k] -= factor * a[i][k];
+                }
+            }
+        }
+        determinant
+    }
+
+    /// Returns the inverse of this matrix, or `None` if it is singular.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        // Gauss-Jordan elimination. As with the determinant, transposing commutes with inversion,
+        // so the columns can be treated as rows.
+        let mut a = self.to_columns();
+        let mut inverse = Matrix4::identity().to_columns();
+        for i in range(0u, 4) {
+            let pivot = pivot_row(&a, i);
+            if a[pivot][i] == 0.0 {
+                return None
+            }
+            swap_rows(&mut a, pivot, i);
+            swap_rows(&mut inverse, pivot, i);
+
+            let divisor = a[i][i];
+            for k in range(0u, 4) {
+                a[i][k] /= divisor;
+                inverse[i][k] /= divisor;
+            }
+            for j in range(0u, 4) {
+                if j == i {
+                    continue
+                }
+                let factor = a[j][i];
+                for k in range(0u, 4) {
+                    a[j][k] -= factor * a[i][k];
+                    inverse[j][k] -= factor * inverse[i][k];
+                }
+            }
+        }
+        Some(Matrix4::from_columns(inverse))
+    }
+
+    /// Multiplies the homogeneous point `(x, y, z, w)` by this matrix.
+    fn transform_homogeneous(&self, x: f64, y: f64, z: f64, w: f64) -> [f64, ..4] {
+        [
+            self.m11 * x + self.m21 * y + self.m31 * z + self.m41 * w,
+            self.m12 * x + self.m22 * y + self.m32 * z + self.m42 * w,
+            self.m13 * x + self.m23 * y + self.m33 * z + self.m43 * w,
+            self.m14 * x + self.m24 * y + self.m34 * z + self.m44 * w,
+        ]
+    }
+
+    /// Transforms a 3D point, dividing through by `w`. Returns `None` if the point ends up at or
+    /// behind the viewer, where it can't be drawn.
+    pub fn transform_point3d(&self, x: f64, y: f64, z: f64) -> Option<(f64, f64, f64)> {
+        let p = self.transform_homogeneous(x, y, z, 1.0);
+        if p[3] <= 0.0 {
+            return None
+        }
+        Some((p[0] / p[3], p[1] / p[3], p[2] / p[3]))
+    }
+
+    /// Transforms a point in the plane `z = 0` and projects it onto the screen.
+    pub fn transform_point(&self, point: Point2D<f64>) -> Option<Point2D<f64>> {
+        self.transform_point3d(point.x, point.y, 0.0).map(|(x, y, _)| Point2D(x, y))
+    }
+
+    /// The reverse of `transform_point`: finds the point in the plane `z = 0` that this matrix
+    /// projects to `point` on the screen. This is what hit testing needs. Returns `None` if the
+    /// matrix is singular, or if the plane is edge-on to the viewer or the point is behind it.
+    pub fn project_point(&self, point: Point2D<f64>) -> Option<Point2D<f64>> {
+        let inverse = match self.inverse() {
+            Some(inverse) => inverse,
+            None => return None,
+        };
+        // The screen point is really a line through every z. Pick the z that lands on the plane.
+        if inverse.m33 == 0.0 {
+            return None
+        }
+        let on_screen = inverse.transform_homogeneous(point.x, point.y, 0.0, 1.0);
+        let z = -on_screen[2] / inverse.m33;
+        let local = inverse.transform_homogeneous(point.x, point.y, z, 1.0);
+        if local[3] <= 0.0 {
+            return None
+        }
+        Some(Point2D(local[0] / local[3], local[1] / local[3]))
+    }
+
+    /// Splits this matrix into translation, scale, skew, perspective and rotation, so that
+    /// transforms can be interpolated. Returns `None` if the matrix can't be decomposed.
+    pub fn decompose(&self) -> Option<DecomposedMatrix4> {
+        let mut matrix = self.to_columns();
+
+        // Normalize the matrix.
+        if matrix[3][3] == 0.0 {
+            return None
+        }
+        let w = matrix[3][3];
+        for i in range(0u, 4) {
+            for j in range(0u, 4) {
+                matrix[i][j] /= w;
+            }
+        }
+
+        // The perspective matrix is used to solve for perspective, but it also provides an easy
+        // way to test for singularity of the upper 3×3 component.
+        let mut perspective_matrix = matrix;
+        for i in range(0u, 3) {
+            perspective_matrix[i][3] = 0.0;
+        }
+        perspective_matrix[3][3] = 1.0;
+        let perspective_matrix = Matrix4::from_columns(perspective_matrix);
+        if perspective_matrix.determinant() == 0.0 {
+            return None
+        }
+
+        // First, isolate perspective.
+        let perspective = if matrix[0][3] != 0.0 || matrix[1][3] != 0.0 || matrix[2][3] != 0.0 {
+            // Solve the equation by inverting the perspective matrix and multiplying the right
+            // hand side by the inverse.
+            let inverse = perspective_matrix.inverse().unwrap().transpose();
+            inverse.transform_homogeneous(matrix[0][3], matrix[1][3], matrix[2][3], matrix[3][3])
+        } else {
+            [0.0, 0.0, 0.0, 1.0]
+        };
+
+        // Next take care of translation.
+        let translate = [matrix[3][0], matrix[3][1], matrix[3][2]];
+
+        // Now get scale and shear.
+        let mut row = [[0.0, ..3], ..3];
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                row[i][j] = matrix[i][j];
+            }
+        }
+        let mut scale = [0.0, ..3];
+        let mut skew = [0.0, ..3];
+
+        // Compute the X scale factor and normalize the first row.
+        scale[0] = length3(row[0]);
+        row[0] = combine3(row[0], row[0], 1.0 / scale[0], 0.0);
+
+        // Compute the XY shear factor and make the second row orthogonal to the first.
+        skew[0] = dot3(row[0], row[1]);
+        row[1] = combine3(row[1], row[0], 1.0, -skew[0]);
+
+        // Now, compute the Y scale and normalize the second row.
+        scale[1] = length3(row[1]);
+        row[1] = combine3(row[1], row[1], 1.0 / scale[1], 0.0);
+        skew[0] /= scale[1];
+
+        // Compute the XZ and YZ shears and orthogonalize the third row.
+        skew[1] = dot3(row[0], row[2]);
+        row[2] = combine3(row[2], row[0], 1.0, -skew[1]);
+        skew[2] = dot3(row[1], row[2]);
+        row[2] = combine3(row[2], row[1], 1.0, -skew[2]);
+
+        // Next, get the Z scale and normalize the third row.
+        scale[2] = length3(row[2]);
+        row[2] = combine3(row[2], row[2], 1.0 / scale[2], 0.0);
+        skew[1] /= scale[2];
+        skew[2] /= scale[2];
+
+        // At this point, the rows are orthonormal. Check for a coordinate system flip; if the
+        // determinant is -1, negate the rows and the scaling factors.
+        if dot3(row[0], cross3(row[1], row[2])) < 0.0 {
+            for i in range(0u, 3) {
+                scale[i] = -scale[i];
+                row[i] = combine3(row[i], row[i], -1.0, 0.0);
+            }
+        }
+
+        // Now, get the rotation out.
+        let mut quaternion = [
+            0.5 * (1.0 + row[0][0] - row[1][1] - row[2][2]).max(0.0).sqrt(),
+            0.5 * (1.0 - row[0][0] + row[1][1] - row[2][2]).max(0.0).sqrt(),
+            0.5 * (1.0 - row[0][0] - row[1][1] + row[2][2]).max(0.0).sqrt(),
+            0.5 * (1.0 + row[0][0] + row[1][1] + row[2][2]).max(0.0).sqrt(),
+        ];
+        if row[2][1] > row[1][2] {
+            quaternion[0] = -quaternion[0];
+        }
+        if row[0][2] > row[2][0] {
+            quaternion[1] = -quaternion[1];
+        }
+        if row[1][0] > row[0][1] {
+            quaternion[2] = -quaternion[2];
+        }
+
+        Some(DecomposedMatrix4 {
+            translate: translate,
+            scale: scale,
+            skew: skew,
+            perspective: perspective,
+            quaternion: quaternion,
+        })
+    }
+
+    /// Returns true if every entry is within `epsilon` of the corresponding entry of `other`.
+    pub fn approx_eq(&self, other: &Matrix4, epsilon: f64) -> bool {
+        let a = self.to_array();
+        let b = other.to_array();
+        range(0u, 16).all(|i| (a[i] - b[i]).abs() <= epsilon)
+    }
+}
+
+impl Mul<Matrix4, Matrix4> for Matrix4 {
+    fn mul(&self, other: &Matrix4) -> Matrix4 {
+        let a = self.to_columns();
+        let b = other.to_columns();
+        let mut result = [[0.0, ..4], ..4];
+        for column in range(0u, 4) {
+            for row in range(0u, 4) {
+                let mut sum = 0.0;
+                for k in range(0u, 4) {
+                    sum += a[k][row] * b[column][k];
+                }
+                result[column][row] = sum;
+            }
+        }
+        Matrix4::from_columns(result)
+    }
+}
+
+/// Returns the index of the row at or below `column` with the largest entry in that column.
+fn pivot_row(a: &[[f64, ..4], ..4], column: uint) -> uint {
+    let mut pivot = column;
+    for j in range(column + 1, 4) {
+        if a[j][column].abs() > a[pivot][column].abs() {
+            pivot = j;
+        }
+    }
+    pivot
+}
+
+fn swap_rows(a: &mut [[f64, ..4], ..4], i: uint, j: uint) {
+    let row = a[i];
+    a[i] = a[j];
+    a[j] = row;
+}
+
+fn length3(v: [f64, ..3]) -> f64 {
+    dot3(v, v).sqrt()
+}
+
+fn dot3(a: [f64, ..3], b: [f64, ..3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross3(a: [f64, ..3], b: [f64, ..3]) -> [f64, ..3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Returns `a * a_scale + b * b_scale`.
+fn combine3(a: [f64, ..3], b: [f64, ..3], a_scale: f64, b_scale: f64) -> [f64, ..3] {
+    [
+        a[0] * a_scale + b[0] * b_scale,
+        a[1] * a_scale + b[1] * b_scale,
+        a[2] * a_scale + b[2] * b_scale,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use geometry::Matrix4;
+
+    use geom::point::Point2D;
+    use std::f64::consts::PI;
+
+    static EPSILON: f64 = 1e-9;
+
+    fn assert_point_eq(actual: Option<Point2D<f64>>, x: f64, y: f64) {
+        let actual = actual.unwrap();
+        assert!((actual.x - x).abs() < EPSILON && (actual.y - y).abs() < EPSILON,
+                "expected ({}, {}), got {}", x, y, actual);
+    }
+
+    #[test]
+    fn test_multiply() {
+        let m = Matrix4::translate(10.0, 20.0, 0.0) * Matrix4::scale(2.0, 3.0, 1.0);
+        assert_point_eq(m.transform_point(Point2D(1.0, 1.0)), 12.0, 23.0);
+        assert!((Matrix4::identity() * m) == m);
+        assert!((m * Matrix4::identity()) == m);
+        assert!(m.is_2d());
+        assert!(!m.is_identity());
+    }
+
+    #[test]
+    fn test_rotate() {
+        // CSS rotations are clockwise on screen, where y points down.
+        let m = Matrix4::rotate(0.0, 0.0, 1.0, PI / 2.0);
+        assert_point_eq(m.transform_point(Point2D(1.0, 0.0)), 0.0, 1.0);
+        assert!(Matrix4::rotate(0.0, 0.0, 0.0, 1.0).is_identity());
+        let around_x = Matrix4::rotate(1.0, 0.0, 0.0, PI / 2.0);
+        assert!(!around_x.is_2d());
+        let (x, y, z) = around_x.transform_point3d(0.0, 1.0, 0.0).unwrap();
+        assert!(x.abs() < EPSILON && y.abs() < EPSILON && (z - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = Matrix4::translate(5.0, -3.0, 2.0) *
+            Matrix4::rotate(1.0, 2.0, 3.0, 0.7) *
+            Matrix4::scale(2.0, 0.5, 4.0) *
+            Matrix4::perspective(100.0);
+        let inverse = m.inverse().unwrap();
+        assert!((m * inverse).approx_eq(&Matrix4::identity(), EPSILON));
+        assert!((inverse * m).approx_eq(&Matrix4::identity(), EPSILON));
+        assert!((m.determinant() * inverse.determinant() - 1.0).abs() < EPSILON);
+
+        assert!(Matrix4::scale(1.0, 0.0, 1.0).inverse().is_none());
+        assert_eq!(Matrix4::scale(1.0, 0.0, 1.0).determinant(), 0.0);
+        assert!((Matrix4::scale(2.0, 3.0, 4.0).determinant() - 24.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_perspective() {
+        let m = Matrix4::perspective(100.0) * Matrix4::translate(0.0, 0.0, 50.0);
+        // Halfway to the viewer, things appear twice the size.
+        assert_point_eq(m.transform_point(Point2D(10.0, 10.0)), 20.0, 20.0);
+        // At or behind the viewer, nothing can be drawn.
+        let behind = Matrix4::perspective(100.0) * Matrix4::translate(0.0, 0.0, 150.0);
+        assert!(behind.transform_point(Point2D(10.0, 10.0)).is_none());
+        assert!(Matrix4::perspective(0.0).is_identity());
+    }
+
+    #[test]
+    fn test_project_point() {
+        let m = Matrix4::perspective(500.0) * Matrix4::rotate(0.0, 1.0, 0.0, PI / 4.0);
+        let screen = m.transform_point(Point2D(30.0, 40.0));
+        assert_point_eq(m.project_point(screen.unwrap()), 30.0, 40.0);
+        // A plane seen edge-on has no point under the cursor.
+        let edge_on = Matrix4::rotate(0.0, 1.0, 0.0, PI / 2.0);
+        assert!(edge_on.project_point(Point2D(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_decompose() {
+        let m = Matrix4::translate(10.0, 20.0, 30.0) *
+            Matrix4::rotate(0.0, 0.0, 1.0, PI / 2.0) *
+            Matrix4::scale(2.0, 3.0, 4.0);
+        let decomposed = m.decompose().unwrap();
+        let expected_translate = [10.0, 20.0, 30.0];
+        let expected_scale = [2.0, 3.0, 4.0];
+        for i in range(0u, 3) {
+            assert!((decomposed.translate[i] - expected_translate[i]).abs() < EPSILON);
+            assert!((decomposed.scale[i] - expected_scale[i]).abs() < EPSILON);
+            assert!(decomposed.skew[i].abs() < EPSILON);
+        }
+        let expected_perspective = [0.0, 0.0, 0.0, 1.0];
+        let half = (0.5f64).sqrt();
+        let expected_quaternion = [0.0, 0.0, half, half];
+        for i in range(0u, 4) {
+            assert!((decomposed.perspective[i] - expected_perspective[i]).abs() < EPSILON);
+            assert!((decomposed.quaternion[i] - expected_quaternion[i]).abs() < EPSILON);
+        }
+
+        let skewed = Matrix4::skew(PI / 4.0, 0.0).decompose().unwrap();
+        assert!((skewed.skew[0] - 1.0).abs() < EPSILON);
+
+        let with_perspective = Matrix4::perspective(200.0).decompose().unwrap();
+        assert!((with_perspective.perspective[2] + 1.0 / 200.0).abs() < EPSILON);
+
+        assert!(Matrix4::scale(0.0, 1.0, 1.0).decompose().is_none());
+    }
+}
@@ -4,13 +4,21 @@
 
 //! Memory profiling functions.
 
+use std::collections::treemap::TreeMap;
+use std::io::Timer;
 use std::io::timer::sleep;
 #[cfg(target_os="linux")]
 use std::io::File;
 #[cfg(target_os="linux")]
 use std::os::page_size;
+use std_time::precise_time_ns;
 use task::spawn_named;
 
+/// How long the memory profiler waits for components to report, so that one that is busy, say
+/// running a long script, shows up as missing rather than holding up the rest.
+static REPORTS_TIMEOUT_MS: u64 = 1000;
+
+#[deriving(Clone)]
 pub struct MemoryProfilerChan(pub Sender<MemoryProfilerMsg>);
 
 impl MemoryProfilerChan {
@@ -20,7 +28,37 @@ impl MemoryProfilerChan {
     }
 }
 
+/// An amount of memory held by some part of a component.
+pub struct MemoryReport {
+    /// What the memory is used for, e.g. `image-cache/decoded`.
+    pub path: String,
+    /// The size in bytes.
+    pub size: u64,
+}
+
+/// The channel a component sends its memory reports back to the profiler on.
+pub struct MemoryReportsChan(pub Sender<Vec<MemoryReport>>);
+
+impl MemoryReportsChan {
+    pub fn send(&self, reports: Vec<MemoryReport>) {
+        let MemoryReportsChan(ref c) = *self;
+        // The profiler may have stopped waiting for us.
+        let _ = c.send_opt(reports);
+    }
+}
+
+/// Measures the memory used by a component. This is called on the memory profiler's task, so
+/// reporters generally pass `reports_chan` on to the task that owns the memory and have it reply
+/// from there. Returns false if that task has gone away, in which case the reporter is dropped.
+pub trait MemoryReporter {
+    fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool;
+}
+
 pub enum MemoryProfilerMsg {
+    /// Registers a reporter under a name, replacing any reporter already registered under it.
+    RegisterReporterMsg(String, Box<MemoryReporter + Send>),
+    /// Unregisters the reporter with the given name.
+    UnregisterReporterMsg(String),
     /// Message used to force print the memory profiling metrics.
     PrintMsg,
     /// Tells the memory profiler to shut down.
@@ -29,6 +67,8 @@ pub enum MemoryProfilerMsg {
 
 pub struct MemoryProfiler {
     pub port: Receiver<MemoryProfilerMsg>,
+    /// The registered reporters, by name.
+    reporters: TreeMap<String, Box<MemoryReporter + Send>>,
 }
 
 impl MemoryProfiler {
@@ -48,13 +88,13 @@ impl MemoryProfiler {
                 });
                 // Spawn the memory profiler.
                 spawn_named("Memory profiler", proc() {
-                    let memory_profiler = MemoryProfiler::new(port);
+                    let mut memory_profiler = MemoryProfiler::new(port);
                     memory_profiler.start();
                 });
             }
             None => {
                 // No-op to handle messages when the memory profiler is
-                // inactive.
+                // inactive. Reporters are dropped as soon as they arrive.
                 spawn_named("Memory profiler", proc() {
                     loop {
                         match port.recv_opt() {
@@ -71,11 +111,12 @@ impl MemoryProfiler {
 
     pub fn new(port: Receiver<MemoryProfilerMsg>) -> MemoryProfiler {
         MemoryProfiler {
-            port: port
+            port: port,
+            reporters: TreeMap::new(),
         }
     }
 
-    pub fn start(&self) {
+    pub fn start(&mut self) {
         loop {
             match self.port.recv_opt() {
                Ok(msg) => {
@@ -88,8 +129,16 @@ impl MemoryProfiler {
         }
     }
 
-    fn handle_msg(&self, msg: MemoryProfilerMsg) -> bool {
+    fn handle_msg(&mut self, msg: MemoryProfilerMsg) -> bool {
         match msg {
+            RegisterReporterMsg(name, reporter) => {
+                self.reporters.insert(name, reporter);
+                true
+            },
+            UnregisterReporterMsg(name) => {
+                self.reporters.remove(&name);
+                true
+            },
             PrintMsg => {
                 self.handle_print_msg();
                 true
@@ -98,22 +147,73 @@ impl MemoryProfiler {
         }
     }
 
+    /// Asks every reporter for its reports and waits up to `timeout_ms` for them all, dropping
+    /// reporters whose components have gone away. Returns the reports, sorted by path, and the
+    /// names of the reporters that didn't answer in time.
+    fn collect_reports(&mut self, timeout_ms: u64) -> (Vec<MemoryReport>, Vec<String>) {
+        // Ask everyone before waiting for anyone, so that the components measure themselves in
+        // parallel.
+        let mut pending = vec!();
+        let mut dead_reporters = vec!();
+        for (name, reporter) in self.reporters.iter() {
+            let (reports_chan, reports_port) = channel();
+            if reporter.collect_reports(MemoryReportsChan(reports_chan)) {
+                pending.push((name.clone(), reports_port));
+            } else {
+                dead_reporters.push(name.clone());
+            }
+        }
+        for name in dead_reporters.iter() {
+            self.reporters.remove(name);
+        }
+
+        let mut reports = vec!();
+        let mut missing = vec!();
+        let deadline = precise_time_ns() + timeout_ms * 1000000;
+        let mut timer = Timer::new().unwrap();
+        for (name, reports_port) in pending.move_iter() {
+            let now = precise_time_ns();
+            let remaining_ms = if deadline > now { (deadline - now) / 1000000 } else { 0 };
+            let timeout = timer.oneshot(remaining_ms);
+            select! (
+                // An error means the component shut down before answering.
+                component_reports = reports_port.recv_opt() => {
+                    match component_reports {
+                        Ok(component_reports) => reports.push_all_move(component_reports),
+                        Err(()) => {}
+                    }
+                },
+                () = timeout.recv() => missing.push(name)
+            )
+        }
+
+        reports.sort_by(|a, b| a.path.cmp(&b.path));
+        (reports, missing)
+    }
+
     fn print_measurement(path: &str, nbytes: Option<i64>) {
         match nbytes {
             Some(nbytes) => {
                 let mebi = 1024f64 * 1024f64;
-                println!("{:12s}: {:12.2f}", path, (nbytes as f64) / mebi);
+                println!("{:32s}: {:12.2f}", path, (nbytes as f64) / mebi);
             }
             None => {
-                println!("{:12s}: {:>12s}", path, "???");
+                println!("{:32s}: {:>12s}", path, "???");
             }
         }
     }
 
-    fn handle_print_msg(&self) {
-        println!("{:12s}: {:12s}", "_category_", "_size (MiB)_");
+    fn handle_print_msg(&mut self) {
+        println!("{:32s}: {:12s}", "_category_", "_size (MiB)_");
         MemoryProfiler::print_measurement("vsize",    get_vsize());
         MemoryProfiler::print_measurement("resident", get_resident());
+        let (reports, missing) = self.collect_reports(REPORTS_TIMEOUT_MS);
+        for report in reports.iter() {
+            MemoryProfiler::print_measurement(report.path.as_slice(), Some(report.size as i64));
+        }
+        for name in missing.iter() {
+            MemoryProfiler::print_measurement(name.as_slice(), None);
+        }
         println!("");
     }
 }
@@ -156,3 +256,100 @@ fn get_resident() -> Option<i64> {
     None
 }
 
+
+#[cfg(test)]
+mod tests {
+    use memory::{MemoryProfiler, MemoryReport, MemoryReporter, MemoryReportsChan};
+    use memory::{RegisterReporterMsg, UnregisterReporterMsg};
+
+    /// Reports a fixed size straight away.
+    struct FixedReporter(&'static str, u64);
+
+    impl MemoryReporter for FixedReporter {
+        fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool {
+            let FixedReporter(path, size) = *self;
+            reports_chan.send(vec!(MemoryReport {
+                path: path.to_string(),
+                size: size,
+            }));
+            true
+        }
+    }
+
+    /// Stands in for a component that has shut down.
+    struct DeadReporter;
+
+    impl MemoryReporter for DeadReporter {
+        fn collect_reports(&self, _: MemoryReportsChan) -> bool {
+            false
+        }
+    }
+
+    /// Stands in for a component that shuts down before it answers.
+    struct SilentReporter;
+
+    impl MemoryReporter for SilentReporter {
+        fn collect_reports(&self, _: MemoryReportsChan) -> bool {
+            true
+        }
+    }
+
+    /// Stands in for a component that is too busy to answer, by holding on to the channel.
+    struct HangingReporter(Sender<MemoryReportsChan>);
+
+    impl MemoryReporter for HangingReporter {
+        fn collect_reports(&self, reports_chan: MemoryReportsChan) -> bool {
+            let HangingReporter(ref held_chan) = *self;
+            held_chan.send(reports_chan);
+            true
+        }
+    }
+
+    fn paths_and_sizes(profiler: &mut MemoryProfiler) -> Vec<(String, u64)> {
+        let (reports, missing) = profiler.collect_reports(1000);
+        assert!(missing.is_empty());
+        reports.move_iter().map(|report| (report.path, report.size)).collect()
+    }
+
+    #[test]
+    fn test_collect_reports() {
+        let (_, port) = channel();
+        let mut profiler = MemoryProfiler::new(port);
+        profiler.handle_msg(RegisterReporterMsg("b".to_string(), box FixedReporter("b/x", 2)));
+        profiler.handle_msg(RegisterReporterMsg("a".to_string(), box FixedReporter("a/x", 1)));
+        profiler.handle_msg(RegisterReporterMsg("silent".to_string(), box SilentReporter));
+        assert_eq!(paths_and_sizes(&mut profiler),
+                   vec!(("a/x".to_string(), 1), ("b/x".to_string(), 2)));
+
+        profiler.handle_msg(RegisterReporterMsg("a".to_string(), box FixedReporter("a/y", 3)));
+        profiler.handle_msg(UnregisterReporterMsg("b".to_string()));
+        assert_eq!(paths_and_sizes(&mut profiler), vec!(("a/y".to_string(), 3)));
+    }
+
+    #[test]
+    fn test_dead_reporters_are_dropped() {
+        let (_, port) = channel();
+        let mut profiler = MemoryProfiler::new(port);
+        profiler.handle_msg(RegisterReporterMsg("dead".to_string(), box DeadReporter));
+        profiler.handle_msg(RegisterReporterMsg("live".to_string(), box FixedReporter("x", 1)));
+        assert_eq!(paths_and_sizes(&mut profiler), vec!(("x".to_string(), 1)));
+        assert_eq!(profiler.reporters.len(), 1);
+    }
+
+    #[test]
+    fn test_slow_reporters_are_missing() {
+        let (_, port) = channel();
+        let mut profiler = MemoryProfiler::new(port);
+        let (held_chan, held_port) = channel();
+        profiler.handle_msg(RegisterReporterMsg("hanging".to_string(),
+                                                box HangingReporter(held_chan)));
+        profiler.handle_msg(RegisterReporterMsg("live".to_string(), box FixedReporter("x", 1)));
+        let (reports, missing) = profiler.collect_reports(10);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(missing, vec!("hanging".to_string()));
+
+        // A slow reporter is kept, and an answer that comes too late goes nowhere.
+        assert_eq!(profiler.reporters.len(), 2);
+        held_port.recv().send(vec!());
+    }
+}
@@ -3,20 +3,24 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 //! Configuration options for a single run of the servo application. Created
-//! from command line arguments.
+//! from command line arguments and, optionally, a configuration file.
 
 use geometry::{DevicePixel, ScreenPx};
+use time::{JsonOutput, TextOutput, TimeProfilerOutput};
 
 use azure::azure_hl::{BackendType, CairoBackend, CoreGraphicsBackend};
 use azure::azure_hl::{CoreGraphicsAcceleratedBackend, Direct2DBackend, SkiaBackend};
 use geom::scale_factor::ScaleFactor;
 use getopts;
 use std::cmp;
+use std::collections::hashmap::HashMap;
+use std::from_str::FromStr;
 use std::io;
+use std::io::File;
 use std::os;
 use std::rt;
 
-/// Global flags for Servo, currently set on the command line.
+/// Global flags for Servo, set on the command line or in a configuration file.
 #[deriving(Clone)]
 pub struct Opts {
     /// The initial URLs to load.
@@ -45,6 +49,9 @@ pub struct Opts {
     /// cause it to produce output on that interval (`-p`).
     pub time_profiler_period: Option<f64>,
 
+    /// How the time profiler prints its measurements (`--profile-output`).
+    pub time_profiler_output: TimeProfilerOutput,
+
     /// `None` to disable the memory profiler or `Some` with an interval in seconds to enable it 
     /// and cause it to produce output on that interval (`-m`).
     pub memory_profiler_period: Option<f64>,
@@ -65,6 +72,64 @@ pub struct Opts {
     /// may wish to turn this flag on in order to benchmark style recalculation against other
     /// browser engines.
     pub bubble_widths_separately: bool,
+
+    /// Which components should print debugging output (`--debug`).
+    pub debug: DebugOptions,
+
+    /// Trusted `CustomEvent`s to fire at each top-level page once it has loaded, as an event type
+    /// and the JSON of its detail (`--custom-event type=json`). This lets a test harness drive a
+    /// page from outside.
+    pub custom_events: Vec<(String, String)>,
+
+    /// Points in CSS px to click with the primary button once each top-level page has loaded,
+    /// after the custom events (`--click x,y`). Each click is a `mousedown`, a `mouseup` and a
+    /// `click`, as if the user had clicked there.
+    pub clicks: Vec<(f32, f32)>,
+}
+
+/// Debugging output that can be turned on for individual components with
+/// `--debug=layout.flows,gfx.fonts,net.http`, without rebuilding with logging enabled.
+#[deriving(Clone, PartialEq, Show)]
+pub struct DebugOptions {
+    /// Print the flow tree after each reflow (`layout.flows`).
+    pub layout_flows: bool,
+
+    /// Print font and font group cache misses (`gfx.fonts`).
+    pub gfx_fonts: bool,
+
+    /// Print each HTTP request and the headers of its response (`net.http`).
+    pub net_http: bool,
+}
+
+static DEBUG_OPTION_NAMES: [&'static str, ..3] = ["layout.flows", "gfx.fonts", "net.http"];
+
+impl DebugOptions {
+    /// Returns options with all debugging output turned off.
+    pub fn new() -> DebugOptions {
+        DebugOptions {
+            layout_flows: false,
+            gfx_fonts: false,
+            net_http: false,
+        }
+    }
+
+    /// Parses a comma-separated list of option names, as given to `--debug`.
+    pub fn parse(names: &str) -> Result<DebugOptions, String> {
+        let mut debug = DebugOptions::new();
+        for name in names.split(',').map(|name| name.trim()).filter(|name| !name.is_empty()) {
+            match name {
+                "layout.flows" => debug.layout_flows = true,
+                "gfx.fonts" => debug.gfx_fonts = true,
+                "net.http" => debug.net_http = true,
+                _ => {
+                    return Err(format!("unknown --debug option `{}`; expected one of {}",
+                                       name,
+                                       DEBUG_OPTION_NAMES.connect(", ")))
+                }
+            }
+        }
+        Ok(debug)
+    }
 }
 
 fn print_usage(app: &str, opts: &[getopts::OptGroup]) {
@@ -77,6 +142,171 @@ fn args_fail(msg: &str) {
     os::set_exit_status(1);
 }
 
+/// Where to look for a configuration file if none is given with `--config`, in order.
+fn default_config_paths() -> Vec<Path> {
+    let mut paths = vec!(Path::new("servo.conf"));
+    match os::homedir() {
+        Some(home) => paths.push(home.join(".servo").join("servo.conf")),
+        None => {}
+    }
+    paths
+}
+
+/// Options read from a configuration file.
+struct Config {
+    /// The file they were read from, for error messages.
+    path: Path,
+    /// Each option's value, keyed by long option name, with the number of the line that set it.
+    values: HashMap<String, (String, uint)>,
+}
+
+/// Parses the contents of a configuration file into option values, keyed by long option name,
+/// along with the numbers of the lines that set them.
+///
+/// Each line is blank, a comment starting with `#`, or `name = value`, where `name` is the long
+/// name of a command-line option, e.g. `layout-threads = 4`. Options that are flags on the command
+/// line take `true` or `false`. Values may be quoted.
+fn parse_config(contents: &str, opts: &[getopts::OptGroup])
+                -> Result<HashMap<String, (String, uint)>, String> {
+    let mut values = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue
+        }
+        let (name, value) = match line.find('=') {
+            Some(equals) => (line.slice_to(equals).trim(), line.slice_from(equals + 1).trim()),
+            None => return Err(format!("line {}: expected `name = value`", index + 1)),
+        };
+        let value = if value.len() >= 2 && value.starts_with("\"") && value.ends_with("\"") {
+            value.slice(1, value.len() - 1)
+        } else {
+            value
+        };
+
+        let opt = match opts.iter().find(|opt| opt.long_name.as_slice() == name) {
+            Some(opt) if name != "config" && name != "unset" && name != "help" => opt,
+            _ => return Err(format!("line {}: unknown option `{}`", index + 1, name)),
+        };
+        let is_bool = value == "true" || value == "false";
+        match opt.hasarg {
+            getopts::No if !is_bool => {
+                return Err(format!("line {}: `{}` must be true or false", index + 1, name))
+            }
+            getopts::Yes if is_bool => {
+                return Err(format!("line {}: `{}` needs a value", index + 1, name))
+            }
+            _ => {}
+        }
+        values.insert(name.to_string(), (value.to_string(), index + 1));
+    }
+    Ok(values)
+}
+
+/// Reads the configuration file named with `--config`, or the first one found in the default
+/// places. Having no configuration file is fine unless one was named.
+fn read_config(matches: &getopts::Matches, opts: &[getopts::OptGroup])
+               -> Result<Option<Config>, String> {
+    let path = match matches.opt_str("config") {
+        Some(path) => Path::new(path),
+        None => {
+            match default_config_paths().move_iter().find(|path| path.exists()) {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+    match File::open(&path).read_to_str() {
+        Ok(contents) => {
+            match parse_config(contents.as_slice(), opts) {
+                Ok(values) => Ok(Some(Config { path: path, values: values })),
+                Err(msg) => Err(format!("{}: {}", path.display(), msg)),
+            }
+        }
+        Err(error) => Err(format!("couldn't read {}: {}", path.display(), error)),
+    }
+}
+
+/// Looks options up by long name, first on the command line and then in the configuration file,
+/// so that the command line takes precedence. `--unset name` hides an option set in the file,
+/// which is the only way to turn off a flag that the file turns on.
+struct Settings {
+    matches: getopts::Matches,
+    config: Option<Config>,
+}
+
+impl Settings {
+    /// Returns the value the configuration file gives `name`, and the line it is on.
+    fn config_entry<'a>(&'a self, name: &str) -> Option<(&'a str, uint)> {
+        if self.matches.opt_strs("unset").iter().any(|unset| unset.as_slice() == name) {
+            return None
+        }
+        let config = match self.config {
+            Some(ref config) => config,
+            None => return None,
+        };
+        config.values.find_equiv(&name).map(|&(ref value, line)| (value.as_slice(), line))
+    }
+
+    fn config_value<'a>(&'a self, name: &str) -> Option<&'a str> {
+        self.config_entry(name).map(|(value, _)| value)
+    }
+
+    fn opt_present(&self, name: &str) -> bool {
+        self.matches.opt_present(name) || self.config_value(name) == Some("true")
+    }
+
+    fn opt_str(&self, name: &str) -> Option<String> {
+        self.matches.opt_str(name).or_else(|| {
+            self.config_value(name).map(|value| value.to_string())
+        })
+    }
+
+    /// Like `opt_str`, but for options that can be repeated on the command line. The
+    /// configuration file can only give such an option once.
+    fn opt_strs(&self, name: &str) -> Vec<String> {
+        let strs = self.matches.opt_strs(name);
+        if !strs.is_empty() {
+            return strs
+        }
+        self.config_value(name).map(|value| vec!(value.to_string())).unwrap_or(vec!())
+    }
+
+    /// Like `getopts::Matches::opt_default`. In the configuration file, `true` means `def`.
+    fn opt_default(&self, name: &str, def: &str) -> Option<String> {
+        match self.matches.opt_default(name, def) {
+            Some(value) => Some(value),
+            None => {
+                match self.config_value(name) {
+                    None | Some("false") => None,
+                    Some("true") => Some(def.to_string()),
+                    Some(value) => Some(value.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Parses `value`, the value of the option `name`. If it is invalid, the error says whether it
+    /// came from the command line or from the configuration file, and if so from which line.
+    fn parse<T: FromStr>(&self, name: &str, value: Option<String>) -> Result<Option<T>, String> {
+        let value = match value {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        match from_str(value.as_slice()) {
+            Some(parsed) => return Ok(Some(parsed)),
+            None => {}
+        }
+        match (self.matches.opt_present(name), self.config_entry(name), &self.config) {
+            (false, Some((_, line)), &Some(ref config)) => {
+                Err(format!("{}: line {}: invalid value `{}` for `{}`",
+                            config.path.display(), line, value, name))
+            }
+            _ => Err(format!("invalid value `{}` for --{}", value, name)),
+        }
+    }
+}
+
 pub fn from_cmdline_args(args: &[String]) -> Option<Opts> {
     let app_name = args[0].to_str();
     let args = args.tail();
@@ -89,12 +319,18 @@ pub fn from_cmdline_args(args: &[String]) -> Option<Opts> {
         getopts::optopt("", "device-pixel-ratio", "Device pixels per px", ""),
         getopts::optopt("t", "threads", "Number of render threads", "1"),
         getopts::optflagopt("p", "profile", "Profiler flag and output interval", "10"),
+        getopts::optopt("", "profile-output", "Time profiler output format", "text|json"),
         getopts::optflagopt("m", "memory-profile", "Memory profiler flag and output interval", "10"),
         getopts::optflag("x", "exit", "Exit after load flag"),
         getopts::optopt("y", "layout-threads", "Number of threads to use for layout", "1"),
         getopts::optflag("z", "headless", "Headless mode"),
         getopts::optflag("f", "hard-fail", "Exit on task failure instead of displaying about:failure"),
         getopts::optflag("b", "bubble-widths", "Bubble intrinsic widths separately like other engines"),
+        getopts::optopt("", "debug", "Components to print debugging output for", "layout.flows,gfx.fonts,net.http"),
+        getopts::optopt("", "config", "Configuration file, overridden by other options", "servo.conf"),
+        getopts::optmulti("", "unset", "Ignore an option set in the configuration file", "cpu"),
+        getopts::optmulti("", "custom-event", "Fire a CustomEvent at each page once it has loaded", "type={\"detail\": 1}"),
+        getopts::optmulti("", "click", "Click at a point of each page once it has loaded", "x,y"),
         getopts::optflag("h", "help", "Print this message")
     );
 
@@ -119,7 +355,29 @@ pub fn from_cmdline_args(args: &[String]) -> Option<Opts> {
         opt_match.free.clone()
     };
 
-    let render_backend = match opt_match.opt_str("r") {
+    let config = match read_config(&opt_match, opts.as_slice()) {
+        Ok(config) => config,
+        Err(msg) => {
+            args_fail(msg.as_slice());
+            return None;
+        }
+    };
+    let settings = Settings {
+        matches: opt_match,
+        config: config,
+    };
+
+    match opts_from_settings(urls, &settings) {
+        Ok(opts) => Some(opts),
+        Err(msg) => {
+            args_fail(msg.as_slice());
+            None
+        }
+    }
+}
+
+fn opts_from_settings(urls: Vec<String>, settings: &Settings) -> Result<Opts, String> {
+    let render_backend = match settings.opt_str("rendering") {
         Some(backend_str) => {
             if "direct2d" == backend_str.as_slice() {
                 Direct2DBackend
@@ -132,42 +390,73 @@ pub fn from_cmdline_args(args: &[String]) -> Option<Opts> {
             } else if "skia" == backend_str.as_slice() {
                 SkiaBackend
             } else {
-                fail!("unknown backend type")
+                return Err(format!("unknown rendering backend `{}`", backend_str));
             }
         }
         None => SkiaBackend
     };
 
-    let tile_size: uint = match opt_match.opt_str("s") {
-        Some(tile_size_str) => from_str(tile_size_str.as_slice()).unwrap(),
-        None => 512,
-    };
+    let tile_size: uint = try!(settings.parse("size", settings.opt_str("size"))).unwrap_or(512);
 
-    let device_pixels_per_px = opt_match.opt_str("device-pixel-ratio").map(|dppx_str|
-        ScaleFactor(from_str(dppx_str.as_slice()).unwrap())
-    );
+    let device_pixels_per_px =
+        try!(settings.parse("device-pixel-ratio", settings.opt_str("device-pixel-ratio")))
+            .map(|dppx| ScaleFactor(dppx));
 
-    let n_render_threads: uint = match opt_match.opt_str("t") {
-        Some(n_render_threads_str) => from_str(n_render_threads_str.as_slice()).unwrap(),
-        None => 1,      // FIXME: Number of cores.
-    };
+    // FIXME: Number of cores.
+    let n_render_threads: uint =
+        try!(settings.parse("threads", settings.opt_str("threads"))).unwrap_or(1);
 
     // If only the flag is present, default to a 5 second period for both profilers.
-    let time_profiler_period = opt_match.opt_default("p", "5").map(|period| {
-        from_str(period.as_slice()).unwrap()
-    });
-    let memory_profiler_period = opt_match.opt_default("m", "5").map(|period| {
-        from_str(period.as_slice()).unwrap()
-    });
-
-    let cpu_painting = opt_match.opt_present("c");
-
-    let layout_threads: uint = match opt_match.opt_str("y") {
-        Some(layout_threads_str) => from_str(layout_threads_str.as_slice()).unwrap(),
-        None => cmp::max(rt::default_sched_threads() * 3 / 4, 1),
+    let time_profiler_period =
+        try!(settings.parse("profile", settings.opt_default("profile", "5")));
+    let time_profiler_output = match settings.opt_str("profile-output") {
+        Some(output_str) => {
+            if "text" == output_str.as_slice() {
+                TextOutput
+            } else if "json" == output_str.as_slice() {
+                JsonOutput
+            } else {
+                return Err("--profile-output must be text or json".to_string());
+            }
+        }
+        None => TextOutput
+    };
+    let memory_profiler_period =
+        try!(settings.parse("memory-profile", settings.opt_default("memory-profile", "5")));
+
+    let cpu_painting = settings.opt_present("cpu");
+
+    let layout_threads: uint =
+        match try!(settings.parse("layout-threads", settings.opt_str("layout-threads"))) {
+            Some(layout_threads) => layout_threads,
+            None => cmp::max(rt::default_sched_threads() * 3 / 4, 1),
+        };
+
+    let debug = match settings.opt_str("debug") {
+        Some(debug_str) => try!(DebugOptions::parse(debug_str.as_slice())),
+        None => DebugOptions::new(),
     };
 
-    Some(Opts {
+    // The detail is optional, and defaults to null.
+    let custom_events = settings.opt_strs("custom-event").move_iter().map(|event| {
+        match event.as_slice().find('=') {
+            Some(equals) => {
+                (event.as_slice().slice_to(equals).to_string(),
+                 event.as_slice().slice_from(equals + 1).to_string())
+            }
+            None => (event.clone(), "null".to_string()),
+        }
+    }).collect();
+
+    let mut clicks = vec!();
+    for click in settings.opt_strs("click").iter() {
+        match parse_click(click.as_slice()) {
+            Some(point) => clicks.push(point),
+            None => return Err(format!("invalid value `{}` for --click; expected `x,y`", click)),
+        }
+    }
+
+    Ok(Opts {
         urls: urls,
         render_backend: render_backend,
         n_render_threads: n_render_threads,
@@ -175,12 +464,122 @@ pub fn from_cmdline_args(args: &[String]) -> Option<Opts> {
         tile_size: tile_size,
         device_pixels_per_px: device_pixels_per_px,
         time_profiler_period: time_profiler_period,
+        time_profiler_output: time_profiler_output,
         memory_profiler_period: memory_profiler_period,
         layout_threads: layout_threads,
-        exit_after_load: opt_match.opt_present("x"),
-        output_file: opt_match.opt_str("o"),
-        headless: opt_match.opt_present("z"),
-        hard_fail: opt_match.opt_present("f"),
-        bubble_widths_separately: opt_match.opt_present("b"),
+        exit_after_load: settings.opt_present("exit"),
+        output_file: settings.opt_str("output"),
+        headless: settings.opt_present("headless"),
+        hard_fail: settings.opt_present("hard-fail"),
+        bubble_widths_separately: settings.opt_present("bubble-widths"),
+        debug: debug,
+        custom_events: custom_events,
+        clicks: clicks,
     })
 }
+
+/// Parses the `x,y` of a `--click`.
+fn parse_click(click: &str) -> Option<(f32, f32)> {
+    let mut coords = click.split(',').map(|coord| from_str::<f32>(coord.trim()));
+    match (coords.next(), coords.next(), coords.next()) {
+        (Some(Some(x)), Some(Some(y)), None) => Some((x, y)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opts::{Config, DebugOptions, Settings, parse_click, parse_config};
+
+    use getopts;
+    use std::collections::hashmap::HashMap;
+
+    #[test]
+    fn test_parse_debug_options() {
+        assert_eq!(DebugOptions::parse(""), Ok(DebugOptions::new()));
+
+        let debug = DebugOptions::parse("layout.flows, net.http").unwrap();
+        assert!(debug.layout_flows && debug.net_http);
+        assert!(!debug.gfx_fonts);
+
+        assert!(DebugOptions::parse("layout.flows,layout.everything").is_err());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let opts = [
+            getopts::optflag("c", "cpu", ""),
+            getopts::optopt("y", "layout-threads", "", ""),
+            getopts::optflagopt("p", "profile", "", ""),
+            getopts::optopt("", "config", "", ""),
+            getopts::optmulti("", "unset", "", ""),
+        ];
+        let config = parse_config("# Comment\n\ncpu = true\nlayout-threads = \"4\"\nprofile=10\n",
+                                  opts.as_slice()).unwrap();
+        assert_eq!(config.find_equiv(&"cpu"), Some(&("true".to_string(), 3)));
+        assert_eq!(config.find_equiv(&"layout-threads"), Some(&("4".to_string(), 4)));
+        assert_eq!(config.find_equiv(&"profile"), Some(&("10".to_string(), 5)));
+
+        assert!(parse_config("cpu", opts.as_slice()).is_err());
+        assert!(parse_config("cpu = 4", opts.as_slice()).is_err());
+        assert!(parse_config("layout-threads = true", opts.as_slice()).is_err());
+        assert!(parse_config("config = other.conf", opts.as_slice()).is_err());
+        assert!(parse_config("unset = cpu", opts.as_slice()).is_err());
+        assert!(parse_config("unknown = 1", opts.as_slice()).is_err());
+    }
+
+    /// Settings for `args`, with a configuration file that turns `cpu` on and gives
+    /// `layout-threads` a value that isn't a number.
+    fn settings(args: &[&str]) -> Settings {
+        let opts = [
+            getopts::optflag("c", "cpu", ""),
+            getopts::optopt("y", "layout-threads", "", ""),
+            getopts::optmulti("", "unset", "", ""),
+        ];
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut values = HashMap::new();
+        values.insert("cpu".to_string(), ("true".to_string(), 1));
+        values.insert("layout-threads".to_string(), ("four".to_string(), 2));
+        Settings {
+            matches: getopts::getopts(args.as_slice(), opts.as_slice()).unwrap(),
+            config: Some(Config {
+                path: Path::new("servo.conf"),
+                values: values,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_settings() {
+        let from_config = settings([]);
+        assert!(from_config.opt_present("cpu"));
+        let threads: Result<Option<uint>, String> =
+            from_config.parse("layout-threads", from_config.opt_str("layout-threads"));
+        assert_eq!(threads, Err("servo.conf: line 2: invalid value `four` for `layout-threads`"
+                                .to_string()));
+
+        // The command line overrides the configuration file.
+        let overridden = settings(["--unset", "cpu", "--layout-threads", "3"]);
+        assert!(!overridden.opt_present("cpu"));
+        let threads: Result<Option<uint>, String> =
+            overridden.parse("layout-threads", overridden.opt_str("layout-threads"));
+        assert_eq!(threads, Ok(Some(3)));
+        assert_eq!(settings(["--unset", "cpu", "--unset", "size"]).opt_strs("unset"),
+                   vec!("cpu".to_string(), "size".to_string()));
+        assert_eq!(from_config.opt_strs("layout-threads"), vec!("four".to_string()));
+
+        let bad = settings(["-y", "x"]);
+        let threads: Result<Option<uint>, String> =
+            bad.parse("layout-threads", bad.opt_str("layout-threads"));
+        assert_eq!(threads, Err("invalid value `x` for --layout-threads".to_string()));
+    }
+
+    #[test]
+    fn test_parse_click() {
+        assert_eq!(parse_click("10,20.5"), Some((10.0, 20.5)));
+        assert_eq!(parse_click(" 0, 3 "), Some((0.0, 3.0)));
+        assert_eq!(parse_click("10"), None);
+        assert_eq!(parse_click("10,20,30"), None);
+        assert_eq!(parse_click("x,20"), None);
+    }
+}
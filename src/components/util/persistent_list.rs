@@ -0,0 +1,174 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A persistent, thread-safe singly-linked list. Prepending makes a new list that shares its tail
+//! with the old one, so a chain of ancestors (inherited styles, say) can be extended for each
+//! child without copying what the children have in common.
+
+use sync::Arc;
+
+pub struct PersistentList<T> {
+    head: PersistentListLink<T>,
+    length: uint,
+}
+
+struct PersistentListEntry<T> {
+    value: T,
+    next: PersistentListLink<T>,
+}
+
+type PersistentListLink<T> = Option<Arc<PersistentListEntry<T>>>;
+
+impl<T:Send+Share> PersistentList<T> {
+    #[inline]
+    pub fn new() -> PersistentList<T> {
+        PersistentList {
+            head: None,
+            length: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.length
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns the first element of the list, if there is one.
+    #[inline]
+    pub fn front<'a>(&'a self) -> Option<&'a T> {
+        self.head.as_ref().map(|head| &head.value)
+    }
+
+    /// Returns a new list with `value` on the front and this list as its tail. This list is left
+    /// as it was, and the two share every entry but the new one.
+    #[inline]
+    pub fn prepend_elem(&self, value: T) -> PersistentList<T> {
+        PersistentList {
+            head: Some(Arc::new(PersistentListEntry {
+                value: value,
+                next: self.head.clone(),
+            })),
+            length: self.length + 1,
+        }
+    }
+
+    /// Returns the list without its first element, or an empty list if this one is empty. The
+    /// result shares all of its entries with this list.
+    #[inline]
+    pub fn tail(&self) -> PersistentList<T> {
+        match self.head {
+            None => PersistentList::new(),
+            Some(ref head) => {
+                PersistentList {
+                    head: head.next.clone(),
+                    length: self.length - 1,
+                }
+            }
+        }
+    }
+
+    /// Returns true if both lists start with the same entry, and so are the same list. This
+    /// doesn't compare the elements, so it is O(1).
+    #[inline]
+    pub fn ptr_eq(&self, other: &PersistentList<T>) -> bool {
+        match (&self.head, &other.head) {
+            (&None, &None) => true,
+            (&Some(ref a), &Some(ref b)) => {
+                let a: *PersistentListEntry<T> = &**a;
+                let b: *PersistentListEntry<T> = &**b;
+                a == b
+            }
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn iter<'a>(&'a self) -> PersistentListIterator<'a,T> {
+        // This could clone (and would not need the lifetime if it did), but then it would incur
+        // atomic operations on each call to `.next()`. Bad.
+        PersistentListIterator {
+            entry: self.head.as_ref().map(|head| &**head),
+        }
+    }
+}
+
+impl<T:Send+Share> Clone for PersistentList<T> {
+    fn clone(&self) -> PersistentList<T> {
+        // This establishes the persistent nature of this list: we can clone a list by just cloning
+        // its head.
+        PersistentList {
+            head: self.head.clone(),
+            length: self.length,
+        }
+    }
+}
+
+pub struct PersistentListIterator<'a,T> {
+    entry: Option<&'a PersistentListEntry<T>>,
+}
+
+impl<'a,T:Send+Share> Iterator<&'a T> for PersistentListIterator<'a,T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let entry = match self.entry {
+            None => return None,
+            Some(entry) => entry,
+        };
+        let value = &entry.value;
+        self.entry = match entry.next {
+            None => None,
+            Some(ref entry) => Some(&**entry),
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use persistent_list::PersistentList;
+
+    #[test]
+    fn test_prepend_shares_tail() {
+        let empty = PersistentList::new();
+        assert!(empty.is_empty());
+        assert!(empty.front().is_none());
+
+        let parent = empty.prepend_elem(1u).prepend_elem(2);
+        let first_child = parent.prepend_elem(3);
+        let second_child = parent.prepend_elem(4);
+
+        assert_eq!(parent.len(), 2);
+        assert_eq!(first_child.len(), 3);
+        assert_eq!(first_child.iter().map(|&x| x).collect::<Vec<uint>>(), vec!(3, 2, 1));
+        assert_eq!(second_child.iter().map(|&x| x).collect::<Vec<uint>>(), vec!(4, 2, 1));
+        assert_eq!(parent.iter().map(|&x| x).collect::<Vec<uint>>(), vec!(2, 1));
+
+        assert!(first_child.tail().ptr_eq(&parent));
+        assert!(second_child.tail().ptr_eq(&parent));
+        assert!(!first_child.ptr_eq(&second_child));
+        assert!(parent.clone().ptr_eq(&parent));
+        assert_eq!(*first_child.front().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_tail_of_empty_list() {
+        let empty: PersistentList<uint> = PersistentList::new();
+        assert!(empty.tail().is_empty());
+        assert!(empty.tail().ptr_eq(&empty));
+    }
+
+    #[test]
+    fn test_outlives_longer_list() {
+        let list = PersistentList::new().prepend_elem("root").prepend_elem("body");
+        let shared = list.tail();
+        drop(list);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(*shared.front().unwrap(), "root");
+    }
+}
@@ -88,6 +88,18 @@ macro_rules! int_range_index {
     )
 }
 
+int_range_index! {
+    #[doc = "An index that refers to a character in a string or text run. This could \
+             point to the middle of a glyph."]
+    struct CharIndex(int)
+}
+
+int_range_index! {
+    #[doc = "An index that refers to a byte of a UTF-8 string. Ranges of these should \
+             begin and end on character boundaries."]
+    struct ByteIndex(int)
+}
+
 #[deriving(Show)]
 pub enum RangeRelation<I> {
     OverlapsBegin(/* overlap */ I),
@@ -256,6 +268,17 @@ impl<I: RangeIndex> Range<I> {
         self.length = length;
     }
 
+    /// Returns the indices that are in both ranges, or an empty range if there are none.
+    ///
+    /// ~~~
+    ///              self
+    ///    |-----------------------|
+    /// <- o - - - - +=====+=======+ - - - - - ->
+    ///                    |--------------|
+    ///                         other
+    ///
+    /// <- o - - - - - - - +=======+ - - - - - ->
+    /// ~~~
     #[inline]
     pub fn intersect(&self, other: &Range<I>) -> Range<I> {
         let begin = max(self.begin(), other.begin());
@@ -268,6 +291,36 @@ impl<I: RangeIndex> Range<I> {
         }
     }
 
+    /// Splits the range in two at `index`, which is clamped to lie within the range. The first
+    /// range covers `[begin, index)` and the second `[index, end)`; either may be empty.
+    ///
+    /// ~~~
+    ///                    index
+    ///                      |
+    /// <- o - - - - - +=====+======+ - - - ->
+    ///                      |
+    /// <- o - - - - - +=====+ - - - - - - - ->
+    /// <- o - - - - - - - - +======+ - - - ->
+    /// ~~~
+    #[inline]
+    pub fn split_at(&self, index: I) -> (Range<I>, Range<I>) {
+        let index = min(max(index, self.begin()), self.end());
+        (Range::new(self.begin(), index - self.begin()), Range::new(index, self.end() - index))
+    }
+
+    /// Joins two ranges that meet end to end, in either order. Returns `None` if there is a gap
+    /// between them or they overlap.
+    #[inline]
+    pub fn merge_adjacent(&self, other: &Range<I>) -> Option<Range<I>> {
+        if self.end() == other.begin() {
+            Some(Range::new(self.begin(), self.length() + other.length()))
+        } else if other.end() == self.begin() {
+            Some(Range::new(other.begin(), self.length() + other.length()))
+        } else {
+            None
+        }
+    }
+
     /// Computes the relationship between two ranges (`self` and `other`),
     /// from the point of view of `self`. So, 'EntirelyBefore' means
     /// that the `self` range is entirely before `other` range.
@@ -353,3 +406,94 @@ impl<T: Int, I: IntRangeIndex<T>> Range<I> {
         debug!("repair_after_coalesced_range: new range: ---- {}", *self);
     }
 }
+
+impl Range<CharIndex> {
+    /// Returns the range of bytes of `text` that holds these characters. Fails if the range
+    /// extends past the end of `text`.
+    pub fn to_byte_range(&self, text: &str) -> Range<ByteIndex> {
+        // The byte offset of each character, then of the end of the string.
+        let mut offsets = text.char_indices()
+                              .map(|(offset, _)| offset)
+                              .chain(Some(text.len()).move_iter());
+        let begin = offsets.nth(self.begin().to_uint()).expect("range begins past the string");
+        let end = if self.is_empty() {
+            begin
+        } else {
+            offsets.nth(self.length().to_uint() - 1).expect("range ends past the string")
+        };
+        Range::new(ByteIndex(begin as int), ByteIndex((end - begin) as int))
+    }
+}
+
+impl Range<ByteIndex> {
+    /// Returns the part of `text` that the range covers. Fails if the range is out of bounds or
+    /// doesn't begin and end on character boundaries.
+    #[inline]
+    pub fn slice<'a>(&self, text: &'a str) -> &'a str {
+        text.slice(self.begin().to_uint(), self.end().to_uint())
+    }
+
+    /// Returns the range of characters that these bytes of `text` hold.
+    pub fn to_char_range(&self, text: &str) -> Range<CharIndex> {
+        let begin = text.slice_to(self.begin().to_uint()).char_len();
+        Range::new(CharIndex(begin as int), CharIndex(self.slice(text).char_len() as int))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use range::{ByteIndex, CharIndex, Range};
+
+    fn range(begin: int, length: int) -> Range<int> {
+        Range::new(begin, length)
+    }
+
+    fn same(a: Range<int>, b: Range<int>) -> bool {
+        a.begin() == b.begin() && a.length() == b.length()
+    }
+
+    #[test]
+    fn test_intersect() {
+        assert!(same(range(2, 5).intersect(&range(4, 10)), range(4, 3)));
+        assert!(same(range(4, 10).intersect(&range(2, 5)), range(4, 3)));
+        assert!(same(range(2, 5).intersect(&range(3, 1)), range(3, 1)));
+        assert!(range(2, 2).intersect(&range(6, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_split_at() {
+        let (left, right) = range(2, 5).split_at(4);
+        assert!(same(left, range(2, 2)) && same(right, range(4, 3)));
+
+        let (left, right) = range(2, 5).split_at(0);
+        assert!(same(left, range(2, 0)) && same(right, range(2, 5)));
+
+        let (left, right) = range(2, 5).split_at(10);
+        assert!(same(left, range(2, 5)) && same(right, range(7, 0)));
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        assert!(same(range(2, 3).merge_adjacent(&range(5, 4)).unwrap(), range(2, 7)));
+        assert!(same(range(5, 4).merge_adjacent(&range(2, 3)).unwrap(), range(2, 7)));
+        assert!(same(range(2, 3).merge_adjacent(&range(5, 0)).unwrap(), range(2, 3)));
+        assert!(range(2, 3).merge_adjacent(&range(6, 4)).is_none());
+        assert!(range(2, 3).merge_adjacent(&range(4, 4)).is_none());
+    }
+
+    #[test]
+    fn test_char_and_byte_ranges() {
+        // "é" and "€" take two and three bytes.
+        let text = "aé€b";
+        let chars = Range::new(CharIndex(1), CharIndex(2));
+        let bytes = chars.to_byte_range(text);
+        assert_eq!((bytes.begin(), bytes.length()), (ByteIndex(1), ByteIndex(5)));
+        assert_eq!(bytes.slice(text), "é€");
+
+        let round_trip = bytes.to_char_range(text);
+        assert_eq!((round_trip.begin(), round_trip.length()), (CharIndex(1), CharIndex(2)));
+
+        let end = Range::new(CharIndex(4), CharIndex(0)).to_byte_range(text);
+        assert_eq!((end.begin(), end.length()), (ByteIndex(7), ByteIndex(0)));
+    }
+}
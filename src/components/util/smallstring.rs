@@ -0,0 +1,162 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Small strings in various sizes. These store up to a certain number of bytes of UTF-8 inline
+//! and fall back to the heap for longer strings, which suits atoms, attribute values and class
+//! names, most of which are short.
+
+use smallvec::{SmallVec, SmallVec16, SmallVec32};
+
+use std::fmt;
+use std::str;
+
+macro_rules! def_small_string(
+    ($name:ident, $vec:ident) => (
+        /// A UTF-8 string kept in a small vector of bytes.
+        #[deriving(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name {
+            bytes: $vec<u8>,
+        }
+
+        impl $name {
+            #[inline]
+            pub fn new() -> $name {
+                $name {
+                    bytes: $vec::new(),
+                }
+            }
+
+            pub fn from_slice(string: &str) -> $name {
+                let mut result = $name::new();
+                result.push_str(string);
+                result
+            }
+
+            /// Copies `bytes` into a new string, or returns `None` if they aren't valid UTF-8.
+            pub fn from_utf8(bytes: &[u8]) -> Option<$name> {
+                if !str::is_utf8(bytes) {
+                    return None
+                }
+                let mut result = $name::new();
+                result.bytes.insert_many(0, bytes);
+                Some(result)
+            }
+
+            pub fn from_string(string: String) -> $name {
+                $name::from_slice(string.as_slice())
+            }
+
+            pub fn push_str(&mut self, string: &str) {
+                let len = self.bytes.len();
+                self.bytes.insert_many(len, string.as_bytes())
+            }
+
+            pub fn push_char(&mut self, c: char) {
+                let mut buffer = [0u8, ..4];
+                let size = c.encode_utf8(buffer);
+                let len = self.bytes.len();
+                self.bytes.insert_many(len, buffer.slice_to(size))
+            }
+
+            pub fn as_slice<'a>(&'a self) -> &'a str {
+                // Everything that goes in is checked to be UTF-8.
+                unsafe {
+                    str::raw::from_utf8(self.bytes.as_slice())
+                }
+            }
+
+            /// The length of the string in bytes.
+            #[inline]
+            pub fn len(&self) -> uint {
+                self.bytes.len()
+            }
+
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.bytes.len() == 0
+            }
+
+            /// Returns true if the string has outgrown its inline storage.
+            #[inline]
+            pub fn spilled(&self) -> bool {
+                self.bytes.spilled()
+            }
+
+            pub fn clear(&mut self) {
+                self.bytes.clear()
+            }
+
+            pub fn into_string(self) -> String {
+                String::from_str(self.as_slice())
+            }
+        }
+
+        impl Str for $name {
+            #[inline]
+            fn as_slice<'a>(&'a self) -> &'a str {
+                self.as_slice()
+            }
+        }
+
+        impl fmt::Show for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.pad(self.as_slice())
+            }
+        }
+    )
+)
+
+def_small_string!(SmallString16, SmallVec16)
+def_small_string!(SmallString32, SmallVec32)
+
+#[cfg(test)]
+mod tests {
+    use smallstring::{SmallString16, SmallString32};
+
+    #[test]
+    fn test_push() {
+        let mut s = SmallString16::new();
+        assert!(s.is_empty());
+        s.push_str("class");
+        s.push_char('-');
+        s.push_char('é');
+        assert_eq!(s.as_slice(), "class-é");
+        assert_eq!(s.len(), 8);
+        assert!(!s.spilled());
+
+        s.push_str(" and a good deal more");
+        assert!(s.spilled());
+        assert_eq!(s.as_slice(), "class-é and a good deal more");
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_from_utf8() {
+        let s = SmallString32::from_utf8("héllo".as_bytes()).unwrap();
+        assert_eq!(s.as_slice(), "héllo");
+        assert!(SmallString32::from_utf8([0x68, 0xc3]).is_none());
+        assert!(SmallString32::from_utf8([0xff]).is_none());
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        let long = "a string that doesn't fit inline".to_string();
+        let s = SmallString16::from_string(long.clone());
+        assert!(s.spilled());
+        assert_eq!(s.clone().into_string(), long);
+        assert_eq!(format!("{}", s), long);
+        assert_eq!(s.as_slice().to_string(), long);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let a = SmallString16::from_slice("apple");
+        let b = SmallString16::from_slice("banana");
+        assert!(a == SmallString16::from_slice("apple"));
+        assert!(a != b);
+        assert!(a < b);
+        assert!(SmallString16::from_slice("b") > SmallString16::from_slice("apple"));
+    }
+}
@@ -8,8 +8,12 @@
 use i = std::mem::init;
 use std::cmp;
 use std::intrinsics;
+use std::iter::Rev;
 use std::mem;
+use std::num::{CheckedAdd, CheckedMul};
 use std::ptr;
+use std::slice::Items;
+use std::uint;
 use std::raw::Slice;
 use rustrt::local_heap;
 use alloc::heap;
@@ -20,13 +24,42 @@ pub trait VecLike<T> {
     fn vec_len(&self) -> uint;
     fn vec_push(&mut self, value: T);
 
+    /// Inserts `value` at position `index`, shifting the elements after it up. Fails if `index`
+    /// is greater than the length.
+    fn vec_insert(&mut self, index: uint, value: T);
+
+    /// Removes and returns the element at position `index`, shifting the elements after it down.
+    /// Returns `None` if `index` is out of bounds.
+    fn vec_remove(&mut self, index: uint) -> Option<T>;
+
+    fn vec_slice<'a>(&'a self, start: uint, end: uint) -> &'a [T];
     fn vec_mut_slice<'a>(&'a mut self, start: uint, end: uint) -> &'a mut [T];
 
+    #[inline]
+    fn vec_as_slice<'a>(&'a self) -> &'a [T] {
+        self.vec_slice(0, self.vec_len())
+    }
+
     #[inline]
     fn vec_mut_slice_from<'a>(&'a mut self, start: uint) -> &'a mut [T] {
         let len = self.vec_len();
         self.vec_mut_slice(start, len)
     }
+
+    #[inline]
+    fn vec_get<'a>(&'a self, index: uint) -> &'a T {
+        &self.vec_as_slice()[index]
+    }
+
+    #[inline]
+    fn vec_get_mut<'a>(&'a mut self, index: uint) -> &'a mut T {
+        &mut self.vec_mut_slice_from(0)[index]
+    }
+
+    #[inline]
+    fn vec_iter<'a>(&'a self) -> Items<'a,T> {
+        self.vec_as_slice().iter()
+    }
 }
 
 impl<T> VecLike<T> for Vec<T> {
@@ -40,14 +73,31 @@ impl<T> VecLike<T> for Vec<T> {
         self.push(value);
     }
 
+    #[inline]
+    fn vec_insert(&mut self, index: uint, value: T) {
+        self.insert(index, value)
+    }
+
+    #[inline]
+    fn vec_remove(&mut self, index: uint) -> Option<T> {
+        self.remove(index)
+    }
+
+    #[inline]
+    fn vec_slice<'a>(&'a self, start: uint, end: uint) -> &'a [T] {
+        self.slice(start, end)
+    }
+
     #[inline]
     fn vec_mut_slice<'a>(&'a mut self, start: uint, end: uint) -> &'a mut [T] {
         self.mut_slice(start, end)
     }
 }
 
-trait SmallVecPrivate<T> {
-    unsafe fn set_len(&mut self, new_len: uint);
+/// The storage accessors that `SmallVec` builds on. This is public only so that the exported
+/// macros can implement it; nothing outside this module should call it.
+#[doc(hidden)]
+pub trait SmallVecPrivate<T> {
     unsafe fn set_cap(&mut self, new_cap: uint);
     fn data(&self, index: uint) -> *T;
     fn mut_data(&mut self, index: uint) -> *mut T;
@@ -61,6 +111,12 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
     fn len(&self) -> uint;
     fn cap(&self) -> uint;
 
+    /// Sets the length of the vector without touching its contents. This is intended for FFI code
+    /// that has written elements into reserved capacity through `as_mut_ptr()`; it is up to the
+    /// caller to ensure that the first `new_len` elements are initialized and that `new_len` does
+    /// not exceed `cap()`.
+    unsafe fn set_len(&mut self, new_len: uint);
+
     fn spilled(&self) -> bool {
         self.cap() > self.inline_size()
     }
@@ -81,10 +137,27 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
     }
 
+    /// Returns a pointer to the first element, whether the elements are stored inline or on the
+    /// heap. The pointer is invalidated by any operation that may reallocate or move the storage
+    /// (`push`, `grow`, inserting, shrinking, and so on), and by moving the vector itself if it has
+    /// not spilled.
+    fn as_ptr(&self) -> *T {
+        self.begin()
+    }
+
+    /// Returns a mutable pointer to the first element, suitable for handing to foreign code. The
+    /// same invalidation rules as `as_ptr()` apply. Elements written past the current length are
+    /// not part of the vector until committed with `set_len()`.
+    fn as_mut_ptr(&mut self) -> *mut T {
+        unsafe {
+            mem::transmute(self.begin())
+        }
+    }
+
     fn iter<'a>(&'a self) -> SmallVecIterator<'a,T> {
         SmallVecIterator {
             ptr: self.begin(),
-            end: self.end(),
+            end: iter_end(self),
             lifetime: None,
         }
     }
@@ -93,12 +166,16 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         unsafe {
             SmallVecMutIterator {
                 ptr: mem::transmute(self.begin()),
-                end: mem::transmute(self.end()),
+                end: mem::transmute(iter_end(self)),
                 lifetime: None,
             }
         }
     }
 
+    fn mut_rev_iter<'a>(&'a mut self) -> Rev<SmallVecMutIterator<'a,T>> {
+        self.mut_iter().rev()
+    }
+
     /// NB: For efficiency reasons (avoiding making a second copy of the inline elements), this
     /// actually clears out the original array instead of moving it.
     fn move_iter<'a>(&'a mut self) -> SmallVecMoveIterator<'a,T> {
@@ -121,10 +198,24 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
     }
 
+    /// Removes all the elements, yielding them by value. Unlike `move_iter()`, this leaves the
+    /// storage in place, inline or on the heap, so the vector can be refilled without growing
+    /// again. Elements the iterator doesn't get to are destroyed when it is dropped.
+    fn drain<'a>(&'a mut self) -> SmallVecDrainIterator<'a,T> {
+        unsafe {
+            let iter = mem::transmute(self.iter());
+            self.set_len(0);
+            SmallVecDrainIterator {
+                iter: iter,
+                lifetime: None,
+            }
+        }
+    }
+
     fn push(&mut self, value: T) {
         let cap = self.cap();
         if self.len() == cap {
-            self.grow(cmp::max(cap * 2, 1))
+            self.grow(cmp::max(doubled_cap(cap), 1))
         }
         unsafe {
             let end: &mut T = mem::transmute(self.end());
@@ -134,10 +225,128 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
     }
 
+    /// Moves all the elements of `other` onto the end of this vector. Where possible the elements
+    /// are relocated with a single memory copy, or `other`'s heap buffer is taken over outright if
+    /// this vector is empty.
     fn push_all_move<V:SmallVec<T>>(&mut self, mut other: V) {
-        for value in other.move_iter() {
-            self.push(value)
+        let other_len = other.len();
+        if other_len == 0 {
+            return
+        }
+
+        // Pointer arithmetic is meaningless for zero-sized types, so just push them one by one.
+        if mem::size_of::<T>() == 0 {
+            for value in other.move_iter() {
+                self.push(value)
+            }
+            return
+        }
+
+        unsafe {
+            // If we're empty and `other` has a heap buffer that we can't mistake for inline
+            // storage, steal it.
+            if self.len() == 0 && other.spilled() && other.cap() > self.inline_size() {
+                if self.spilled() {
+                    deallocate(self.mut_ptr(), self.cap())
+                }
+                self.set_ptr(other.mut_ptr());
+                self.set_cap(other.cap());
+                self.set_len(other_len);
+
+                let other_inline_size = other.inline_size();
+                other.set_ptr(ptr::mut_null());
+                other.set_cap(other_inline_size);
+                other.set_len(0);
+                return
+            }
+
+            grow_for_additional(self, other_len);
+            let new_len = self.len() + other_len;
+
+            let dest: *mut T = mem::transmute(self.end());
+            ptr::copy_nonoverlapping_memory(dest, other.begin(), other_len);
+            self.set_len(new_len);
+
+            // The elements now belong to us. Make sure `other` won't destroy them: its heap buffer
+            // is only walked up to its length, but inline elements have to be zeroed out.
+            if !other.spilled() {
+                let other_begin: *mut T = mem::transmute(other.begin());
+                intrinsics::set_memory(other_begin, 0, other_len)
+            }
+            other.set_len(0)
+        }
+    }
+
+    /// Moves all the elements of `other` into this vector at position `index`, shifting the
+    /// elements after it up.
+    fn insert_all_move<V:SmallVec<T>>(&mut self, index: uint, mut other: V) {
+        let len = self.len();
+        if index > len {
+            self.fail_bounds_check(index)
+        }
+        let other_len = other.len();
+        if other_len == 0 {
+            return
+        }
+
+        unsafe {
+            let gap = open_gap(self, index, other_len);
+            ptr::copy_nonoverlapping_memory(gap, other.begin(), other_len);
+            self.set_len(len + other_len);
+
+            if !other.spilled() {
+                let other_begin: *mut T = mem::transmute(other.begin());
+                intrinsics::set_memory(other_begin, 0, other_len)
+            }
+            other.set_len(0)
+        }
+    }
+
+    /// Inserts `value` at position `index`, shifting the elements after it up.
+    fn insert(&mut self, index: uint, value: T) {
+        let len = self.len();
+        if index > len {
+            self.fail_bounds_check(index)
+        }
+
+        unsafe {
+            let gap = open_gap(self, index, 1);
+            mem::overwrite(&mut *gap, value);
+            self.set_len(len + 1)
+        }
+    }
+
+    /// Removes and returns the element at position `index`, shifting the elements after it down.
+    /// Returns `None` if `index` is out of bounds.
+    fn remove(&mut self, index: uint) -> Option<T> {
+        let len = self.len();
+        if index >= len {
+            return None
+        }
+
+        unsafe {
+            let hole = self.as_mut_ptr().offset(index as int);
+            let value = ptr::read(hole as *T);
+            ptr::copy_memory(hole, hole.offset(1) as *T, len - index - 1);
+            // The last slot now holds a stale copy; zero it so nothing destroys it.
+            intrinsics::set_memory(hole.offset((len - index - 1) as int), 0, 1);
+            self.set_len(len - 1);
+            Some(value)
+        }
+    }
+
+    /// Removes and returns the element at position `index`, replacing it with the last element.
+    /// This doesn't preserve ordering, but doesn't shift anything either. Returns `None` if
+    /// `index` is out of bounds.
+    fn swap_remove(&mut self, index: uint) -> Option<T> {
+        let len = self.len();
+        if index >= len {
+            return None
         }
+        if index != len - 1 {
+            self.swap(index, len - 1)
+        }
+        self.pop()
     }
 
     fn pop(&mut self) -> Option<T> {
@@ -146,35 +355,85 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
 
         unsafe {
-            let mut value: T = mem::uninitialized();
             let last_index = self.len() - 1;
+            let end_ptr: *mut T = mem::transmute(self.begin().offset(last_index as int));
+            let value = ptr::read_and_zero(end_ptr);
+            self.set_len(last_index);
+            Some(value)
+        }
+    }
+
+    /// Like `pop()`, but also gives memory back once the vector has become mostly empty: when the
+    /// length falls below a quarter of a spilled capacity, the storage shrinks to twice the length,
+    /// moving back inline if that fits. Because the vector then has to halve again before the next
+    /// shrink, or double before the next grow, a length hovering around the threshold doesn't
+    /// cause repeated reallocation.
+    fn pop_and_compact(&mut self) -> Option<T> {
+        let value = self.pop();
+        let len = self.len();
+        if self.spilled() && len < self.cap() / 4 {
+            let new_cap = cmp::max(len * 2, self.inline_size());
+            unsafe {
+                shrink_storage(self, new_cap)
+            }
+        }
+        value
+    }
+
+    /// Shortens the vector to `new_len` elements, destroying the rest. Does nothing if the vector
+    /// is no longer than that. The capacity is left alone.
+    fn truncate(&mut self, new_len: uint) {
+        let len = self.len();
+        if new_len >= len {
+            return
+        }
+        unsafe {
+            let surplus = self.as_mut_ptr().offset(new_len as int);
+            self.set_len(new_len);
+            destroy_elements(surplus, len - new_len)
+        }
+    }
+
+    /// Destroys all the elements, keeping the capacity.
+    fn clear(&mut self) {
+        self.truncate(0)
+    }
 
-            if (last_index as int) < 0 {
-                fail!("overflow")
+    /// Keeps only the elements for which `f` returns true, preserving their order.
+    fn retain(&mut self, f: |&T| -> bool) {
+        let len = self.len();
+        let mut removed = 0u;
+        for i in range(0, len) {
+            if !f(self.get(i)) {
+                removed += 1
+            } else if removed > 0 {
+                self.swap(i - removed, i)
             }
-            let end_ptr = self.begin().offset(last_index as int);
+        }
+        self.truncate(len - removed)
+    }
 
-            mem::swap(&mut value, mem::transmute::<*T,&mut T>(end_ptr));
-            self.set_len(last_index);
-            Some(value)
+    /// Shrinks the storage to fit the length exactly. If the elements fit inline they are moved
+    /// back there and the heap buffer is freed.
+    fn shrink_to_fit(&mut self) {
+        if !self.spilled() || self.len() == self.cap() {
+            return
+        }
+        let new_cap = cmp::max(self.len(), self.inline_size());
+        unsafe {
+            shrink_storage(self, new_cap)
         }
     }
 
     fn grow(&mut self, new_cap: uint) {
+        let alloc_size = allocation_size::<T>(new_cap);
         unsafe {
-            let new_alloc: *mut T = mem::transmute(heap::allocate(mem::size_of::<T>() *
-                                                                            new_cap,
+            let new_alloc: *mut T = mem::transmute(heap::allocate(alloc_size,
                                                                   mem::min_align_of::<T>()));
             ptr::copy_nonoverlapping_memory(new_alloc, self.begin(), self.len());
 
             if self.spilled() {
-                if intrinsics::owns_managed::<T>() {
-                    local_heap::local_free(self.ptr() as *u8)
-                } else {
-                    heap::deallocate(self.mut_ptr() as *mut u8,
-                                     mem::size_of::<T>() * self.cap(),
-                                     mem::min_align_of::<T>())
-                }
+                deallocate(self.mut_ptr(), self.cap())
             } else {
                 let mut_begin: *mut T = mem::transmute(self.begin());
                 intrinsics::set_memory(mut_begin, 0, self.len())
@@ -185,6 +444,22 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
     }
 
+    /// Makes sure the vector can hold at least `new_cap` elements, growing it to exactly that
+    /// capacity if it can't.
+    fn reserve(&mut self, new_cap: uint) {
+        if new_cap > self.cap() {
+            self.grow(new_cap)
+        }
+    }
+
+    /// Makes sure `additional` more elements can be pushed without growing the vector again.
+    fn reserve_additional(&mut self, additional: uint) {
+        match self.len().checked_add(&additional) {
+            Some(new_cap) => self.reserve(new_cap),
+            None => fail!("capacity overflow"),
+        }
+    }
+
     fn get<'a>(&'a self, index: uint) -> &'a T {
         if index >= self.len() {
             self.fail_bounds_check(index)
@@ -203,6 +478,31 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
         }
     }
 
+    /// Swaps the elements at indices `a` and `b`.
+    fn swap(&mut self, a: uint, b: uint) {
+        if a >= self.len() {
+            self.fail_bounds_check(a)
+        }
+        if b >= self.len() {
+            self.fail_bounds_check(b)
+        }
+        unsafe {
+            let begin = self.as_mut_ptr();
+            ptr::swap(begin.offset(a as int), begin.offset(b as int))
+        }
+    }
+
+    /// Reverses the order of the elements in place.
+    fn reverse(&mut self) {
+        let len = self.len();
+        unsafe {
+            let begin = self.as_mut_ptr();
+            for i in range(0, len / 2) {
+                ptr::swap(begin.offset(i as int), begin.offset((len - i - 1) as int))
+            }
+        }
+    }
+
     fn slice<'a>(&'a self, start: uint, end: uint) -> &'a [T] {
         assert!(start <= end);
         assert!(end <= self.len());
@@ -244,6 +544,120 @@ pub trait SmallVec<T> : SmallVecPrivate<T> {
     }
 }
 
+/// Returns the number of bytes needed to hold `cap` elements of type `T`, failing if that doesn't
+/// fit in a `uint`.
+fn allocation_size<T>(cap: uint) -> uint {
+    match mem::size_of::<T>().checked_mul(&cap) {
+        Some(size) => size,
+        None => fail!("capacity overflow"),
+    }
+}
+
+/// Returns twice the given capacity, saturating at `uint::MAX` instead of wrapping around.
+fn doubled_cap(cap: uint) -> uint {
+    cap.checked_mul(&2).unwrap_or(uint::MAX)
+}
+
+/// Grows `vec`, if needed, so that `additional` more elements fit without growing again. The
+/// capacity at least doubles, so that a string of calls doesn't reallocate every time.
+#[doc(hidden)]
+pub fn grow_for_additional<T,V:SmallVec<T>>(vec: &mut V, additional: uint) {
+    let new_len = match vec.len().checked_add(&additional) {
+        Some(new_len) => new_len,
+        None => fail!("capacity overflow"),
+    };
+    let cap = vec.cap();
+    if new_len > cap {
+        vec.grow(cmp::max(doubled_cap(cap), new_len))
+    }
+}
+
+/// Returns the end pointer for iterators over `vec`. Iterators step zero-sized elements by one
+/// byte, so for those the end is `len` bytes past the beginning rather than `end()`.
+fn iter_end<T,V:SmallVec<T>>(vec: &V) -> *T {
+    if mem::size_of::<T>() == 0 {
+        unsafe {
+            mem::transmute(vec.begin() as uint + vec.len())
+        }
+    } else {
+        vec.end()
+    }
+}
+
+/// Makes room for `count` elements at `index` in `vec`, growing it at most once and shifting the
+/// elements after `index` up with a single copy. The gap is zeroed and the length is left at
+/// `index`, so that if the caller fails while filling the gap nothing is destroyed twice; once the
+/// gap is filled the caller must set the length to the old length plus `count`. Returns a pointer
+/// to the start of the gap.
+#[doc(hidden)]
+pub unsafe fn open_gap<T,V:SmallVec<T>>(vec: &mut V, index: uint, count: uint) -> *mut T {
+    let len = vec.len();
+    grow_for_additional(vec, count);
+
+    let gap: *mut T = mem::transmute(vec.begin().offset(index as int));
+    vec.set_len(index);
+    ptr::copy_memory(gap.offset(count as int), gap as *T, len - index);
+    intrinsics::set_memory(gap, 0, count);
+    gap
+}
+
+/// Shrinks the heap storage of the spilled vector `vec` to `new_cap` elements, which must be at
+/// least its length. If `new_cap` is no more than the inline size, the elements are moved back
+/// inline and the heap buffer is freed.
+unsafe fn shrink_storage<T,V:SmallVec<T>>(vec: &mut V, new_cap: uint) {
+    let len = vec.len();
+    let old_cap = vec.cap();
+    let old_alloc = vec.mut_ptr();
+
+    let inline_size = vec.inline_size();
+    if new_cap <= inline_size {
+        // The inline slots were zeroed when we spilled, so we can just copy over them.
+        ptr::copy_nonoverlapping_memory(vec.mut_data(0), old_alloc as *T, len);
+        vec.set_ptr(ptr::mut_null());
+        vec.set_cap(inline_size);
+    } else {
+        let new_alloc: *mut T = mem::transmute(heap::allocate(allocation_size::<T>(new_cap),
+                                                              mem::min_align_of::<T>()));
+        ptr::copy_nonoverlapping_memory(new_alloc, old_alloc as *T, len);
+        vec.set_ptr(new_alloc);
+        vec.set_cap(new_cap);
+    }
+    deallocate(old_alloc, old_cap)
+}
+
+/// Destroys `len` elements starting at `ptr`. Each slot is zeroed as its element is read out, so
+/// nothing else (in particular the drop glue for the inline storage) will destroy it again. This
+/// is the only place elements are destroyed in bulk; single elements are taken out with
+/// `ptr::read_and_zero` in the same way.
+unsafe fn destroy_elements<T>(ptr: *mut T, len: uint) {
+    for i in range(0, len) {
+        drop(ptr::read_and_zero(ptr.offset(i as int)))
+    }
+}
+
+/// Destroys the elements of a spilled vector and frees its heap buffer, for the drop glue. Inline
+/// elements are left to the drop glue of the inline array.
+#[doc(hidden)]
+pub unsafe fn free_storage<T,V:SmallVec<T>>(vec: &mut V) {
+    if !vec.spilled() {
+        return
+    }
+    destroy_elements(vec.mut_ptr(), vec.len());
+    deallocate(vec.mut_ptr(), vec.cap())
+}
+
+/// Frees a heap buffer of `cap` elements previously allocated by `grow()`. The elements themselves
+/// must already have been destroyed or moved out.
+unsafe fn deallocate<T>(ptr: *mut T, cap: uint) {
+    if intrinsics::owns_managed::<T>() {
+        local_heap::local_free(ptr as *u8)
+    } else {
+        heap::deallocate(ptr as *mut u8,
+                         mem::size_of::<T>() * cap,
+                         mem::min_align_of::<T>())
+    }
+}
+
 pub struct SmallVecIterator<'a,T> {
     ptr: *T,
     end: *T,
@@ -268,6 +682,23 @@ impl<'a,T> Iterator<&'a T> for SmallVecIterator<'a,T> {
     }
 }
 
+impl<'a,T> DoubleEndedIterator<&'a T> for SmallVecIterator<'a,T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        unsafe {
+            if self.ptr == self.end {
+                return None
+            }
+            self.end = if mem::size_of::<T>() == 0 {
+                mem::transmute(self.end as uint - 1)
+            } else {
+                self.end.offset(-1)
+            };
+            Some(mem::transmute(self.end))
+        }
+    }
+}
+
 pub struct SmallVecMutIterator<'a,T> {
     ptr: *mut T,
     end: *mut T,
@@ -292,6 +723,23 @@ impl<'a,T> Iterator<&'a mut T> for SmallVecMutIterator<'a,T> {
     }
 }
 
+impl<'a,T> DoubleEndedIterator<&'a mut T> for SmallVecMutIterator<'a,T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        unsafe {
+            if self.ptr == self.end {
+                return None
+            }
+            self.end = if mem::size_of::<T>() == 0 {
+                mem::transmute(self.end as uint - 1)
+            } else {
+                self.end.offset(-1)
+            };
+            Some(mem::transmute(self.end))
+        }
+    }
+}
+
 pub struct SmallVecMoveIterator<'a,T> {
     allocation: Option<*mut u8>,
     cap: uint,
@@ -307,8 +755,7 @@ impl<'a,T> Iterator<T> for SmallVecMoveIterator<'a,T> {
                 None => None,
                 Some(reference) => {
                     // Zero out the values as we go so they don't get double-freed.
-                    let reference: &mut T = mem::transmute(reference);
-                    Some(mem::replace(reference, mem::zeroed()))
+                    Some(ptr::read_and_zero(mem::transmute::<&T,*mut T>(reference)))
                 }
             }
         }
@@ -325,21 +772,57 @@ impl<'a,T> Drop for SmallVecMoveIterator<'a,T> {
             None => {}
             Some(allocation) => {
                 unsafe {
-                    if intrinsics::owns_managed::<T>() {
-                        local_heap::local_free(allocation as *u8)
-                    } else {
-                        heap::deallocate(allocation as *mut u8,
-                                         mem::size_of::<T>() * self.cap,
-                                         mem::min_align_of::<T>())
-                    }
+                    deallocate(allocation as *mut T, self.cap)
+                }
+            }
+        }
+    }
+}
+
+pub struct SmallVecDrainIterator<'a,T> {
+    iter: SmallVecIterator<'static,T>,
+    lifetime: Option<&'a mut T>,
+}
+
+impl<'a,T> Iterator<T> for SmallVecDrainIterator<'a,T> {
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        unsafe {
+            match self.iter.next() {
+                None => None,
+                Some(reference) => {
+                    // The vector's length is already zero, so the slots just need zeroing out for
+                    // the inline drop glue.
+                    Some(ptr::read_and_zero(mem::transmute::<&T,*mut T>(reference)))
                 }
             }
         }
     }
 }
 
+#[unsafe_destructor]
+impl<'a,T> Drop for SmallVecDrainIterator<'a,T> {
+    fn drop(&mut self) {
+        // Destroy the remaining elements.
+        for _ in *self {}
+    }
+}
+
 // Concrete implementations
+//
+// The macros below are exported so that other crates can define small vectors of their own inline
+// sizes. A new size needs all four of them:
+//
+//     def_small_vector!(SmallVec3, 3)
+//     def_small_vector_drop_impl!(SmallVec3, 3)
+//     def_small_vector_clone_impl!(SmallVec3)
+//     def_small_vector_cmp_impl!(SmallVec3)
+//
+// They refer to this module as `::servo_util::smallvec`, so the crate must be linked under that
+// name, and the `SmallVec` trait must be in scope where they are expanded.
 
+
+#[macro_export]
 macro_rules! def_small_vector(
     ($name:ident, $size:expr) => (
         pub struct $name<T> {
@@ -349,10 +832,7 @@ macro_rules! def_small_vector(
             data: [T, ..$size],
         }
 
-        impl<T> SmallVecPrivate<T> for $name<T> {
-            unsafe fn set_len(&mut self, new_len: uint) {
-                self.len = new_len
-            }
+        impl<T> ::servo_util::smallvec::SmallVecPrivate<T> for $name<T> {
             unsafe fn set_cap(&mut self, new_cap: uint) {
                 self.cap = new_cap
             }
@@ -368,14 +848,14 @@ macro_rules! def_small_vector(
                 self.ptr
             }
             unsafe fn mut_ptr(&mut self) -> *mut T {
-                mem::transmute(self.ptr)
+                ::std::mem::transmute(self.ptr)
             }
             unsafe fn set_ptr(&mut self, new_ptr: *mut T) {
-                self.ptr = mem::transmute(new_ptr)
+                self.ptr = ::std::mem::transmute(new_ptr)
             }
         }
 
-        impl<T> SmallVec<T> for $name<T> {
+        impl<T> ::servo_util::smallvec::SmallVec<T> for $name<T> {
             fn inline_size(&self) -> uint {
                 $size
             }
@@ -385,9 +865,12 @@ macro_rules! def_small_vector(
             fn cap(&self) -> uint {
                 self.cap
             }
+            unsafe fn set_len(&mut self, new_len: uint) {
+                self.len = new_len
+            }
         }
 
-        impl<T> VecLike<T> for $name<T> {
+        impl<T> ::servo_util::smallvec::VecLike<T> for $name<T> {
             #[inline]
             fn vec_len(&self) -> uint {
                 self.len()
@@ -398,6 +881,21 @@ macro_rules! def_small_vector(
                 self.push(value);
             }
 
+            #[inline]
+            fn vec_insert(&mut self, index: uint, value: T) {
+                self.insert(index, value)
+            }
+
+            #[inline]
+            fn vec_remove(&mut self, index: uint) -> Option<T> {
+                self.remove(index)
+            }
+
+            #[inline]
+            fn vec_slice<'a>(&'a self, start: uint, end: uint) -> &'a [T] {
+                self.slice(start, end)
+            }
+
             #[inline]
             fn vec_mut_slice<'a>(&'a mut self, start: uint, end: uint) -> &'a mut [T] {
                 self.mut_slice(start, end)
@@ -411,11 +909,38 @@ macro_rules! def_small_vector(
                     $name {
                         len: 0,
                         cap: $size,
-                        ptr: ptr::null(),
-                        data: mem::zeroed(),
+                        ptr: ::std::ptr::null(),
+                        data: ::std::mem::zeroed(),
                     }
                 }
             }
+
+            /// Creates an empty vector that can hold `cap` elements before growing. Capacities
+            /// over the inline size are allocated on the heap straight away.
+            #[inline]
+            pub fn with_capacity(cap: uint) -> $name<T> {
+                let mut v = $name::new();
+                v.reserve(cap);
+                v
+            }
+        }
+
+        impl<T> ::std::iter::FromIterator<T> for $name<T> {
+            fn from_iter<I:Iterator<T>>(iter: I) -> $name<T> {
+                let mut v = $name::new();
+                v.extend(iter);
+                v
+            }
+        }
+
+        impl<T> ::std::iter::Extendable<T> for $name<T> {
+            fn extend<I:Iterator<T>>(&mut self, mut iter: I) {
+                let (lower_size_bound, _) = iter.size_hint();
+                ::servo_util::smallvec::grow_for_additional(self, lower_size_bound);
+                for value in iter {
+                    self.push(value)
+                }
+            }
         }
     )
 )
@@ -428,28 +953,14 @@ def_small_vector!(SmallVec16, 16)
 def_small_vector!(SmallVec24, 24)
 def_small_vector!(SmallVec32, 32)
 
+#[macro_export]
 macro_rules! def_small_vector_drop_impl(
     ($name:ident, $size:expr) => (
         #[unsafe_destructor]
         impl<T> Drop for $name<T> {
             fn drop(&mut self) {
-                if !self.spilled() {
-                    return
-                }
-
                 unsafe {
-                    let ptr = self.mut_ptr();
-                    for i in range(0, self.len()) {
-                        *ptr.offset(i as int) = mem::uninitialized();
-                    }
-
-                    if intrinsics::owns_managed::<T>() {
-                        local_heap::local_free(self.ptr() as *u8)
-                    } else {
-                        heap::deallocate(self.mut_ptr() as *mut u8,
-                                         mem::size_of::<T>() * self.cap(),
-                                         mem::min_align_of::<T>())
-                    }
+                    ::servo_util::smallvec::free_storage(self)
                 }
             }
         }
@@ -464,6 +975,7 @@ def_small_vector_drop_impl!(SmallVec16, 16)
 def_small_vector_drop_impl!(SmallVec24, 24)
 def_small_vector_drop_impl!(SmallVec32, 32)
 
+#[macro_export]
 macro_rules! def_small_vector_clone_impl(
     ($name:ident) => (
         impl<T:Clone> Clone for $name<T> {
@@ -474,25 +986,143 @@ macro_rules! def_small_vector_clone_impl(
                 }
                 new_vector
             }
-        }
-    )
-)
-
-def_small_vector_clone_impl!(SmallVec1)
-def_small_vector_clone_impl!(SmallVec2)
-def_small_vector_clone_impl!(SmallVec4)
-def_small_vector_clone_impl!(SmallVec8)
-def_small_vector_clone_impl!(SmallVec16)
-def_small_vector_clone_impl!(SmallVec24)
-def_small_vector_clone_impl!(SmallVec32)
 
-#[cfg(test)]
-pub mod tests {
-    use smallvec::{SmallVec, SmallVec2, SmallVec16};
+            /// Overwrites this vector with a copy of `source`, reusing the existing storage and
+            /// only growing it if `source` doesn't fit.
+            fn clone_from(&mut self, source: &$name<T>) {
+                let len = self.len();
+                let source_len = source.len();
+                self.truncate(source_len);
 
-    // We heap allocate all these strings so that double frees will show up under valgrind.
+                for i in range(0, ::std::cmp::min(len, source_len)) {
+                    self.get_mut(i).clone_from(source.get(i))
+                }
 
-    #[test]
+                if source_len > self.cap() {
+                    self.grow(source_len)
+                }
+                for element in source.slice(self.len(), source_len).iter() {
+                    self.push((*element).clone())
+                }
+            }
+        }
+
+        impl<T:Clone> $name<T> {
+            /// Inserts clones of `values` at position `index`, shifting the elements after it up.
+            pub fn insert_many(&mut self, index: uint, values: &[T]) {
+                let len = self.len();
+                if index > len {
+                    self.fail_bounds_check(index)
+                }
+                let count = values.len();
+                if count == 0 {
+                    return
+                }
+
+                unsafe {
+                    let gap = ::servo_util::smallvec::open_gap(self, index, count);
+                    for (i, value) in values.iter().enumerate() {
+                        ::std::mem::overwrite(&mut *gap.offset(i as int), value.clone())
+                    }
+                    self.set_len(len + count)
+                }
+            }
+        }
+    )
+)
+
+def_small_vector_clone_impl!(SmallVec1)
+def_small_vector_clone_impl!(SmallVec2)
+def_small_vector_clone_impl!(SmallVec4)
+def_small_vector_clone_impl!(SmallVec8)
+def_small_vector_clone_impl!(SmallVec16)
+def_small_vector_clone_impl!(SmallVec24)
+def_small_vector_clone_impl!(SmallVec32)
+
+#[macro_export]
+macro_rules! def_small_vector_cmp_impl(
+    ($name:ident) => (
+        impl<T:PartialEq> PartialEq for $name<T> {
+            #[inline]
+            fn eq(&self, other: &$name<T>) -> bool {
+                self.as_slice() == other.as_slice()
+            }
+        }
+
+        impl<T:Eq> Eq for $name<T> {}
+
+        impl<T:PartialOrd> PartialOrd for $name<T> {
+            #[inline]
+            fn lt(&self, other: &$name<T>) -> bool {
+                self.as_slice() < other.as_slice()
+            }
+        }
+
+        impl<T:Ord> Ord for $name<T> {
+            #[inline]
+            fn cmp(&self, other: &$name<T>) -> Ordering {
+                self.as_slice().cmp(&other.as_slice())
+            }
+        }
+
+        impl<S: ::std::hash::Writer,T: ::std::hash::Hash<S>> ::std::hash::Hash<S> for $name<T> {
+            #[inline]
+            fn hash(&self, state: &mut S) {
+                use std::hash::Hash;
+                self.as_slice().hash(state)
+            }
+        }
+
+        impl<T: ::std::fmt::Show> ::std::fmt::Show for $name<T> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                use std::fmt::Show;
+                self.as_slice().fmt(f)
+            }
+        }
+    )
+)
+
+def_small_vector_cmp_impl!(SmallVec1)
+def_small_vector_cmp_impl!(SmallVec2)
+def_small_vector_cmp_impl!(SmallVec4)
+def_small_vector_cmp_impl!(SmallVec8)
+def_small_vector_cmp_impl!(SmallVec16)
+def_small_vector_cmp_impl!(SmallVec24)
+def_small_vector_cmp_impl!(SmallVec32)
+
+#[cfg(test)]
+pub mod tests {
+    use smallvec::{SmallVec, SmallVec2, SmallVec16, VecLike, allocation_size, doubled_cap};
+    use std::cell::Cell;
+    use std::collections::hashmap::HashSet;
+    use std::rc::Rc;
+    use std::uint;
+
+    // We heap allocate all these strings so that double frees will show up under valgrind.
+
+    /// Counts how many times values of this type have been destroyed.
+    #[deriving(Clone)]
+    struct DropCounter {
+        count: Rc<Cell<uint>>,
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.count.set(self.count.get() + 1)
+        }
+    }
+
+    fn drop_counters(count: &Rc<Cell<uint>>, n: uint) -> SmallVec2<DropCounter> {
+        let mut v = SmallVec2::new();
+        for _ in range(0, n) {
+            v.push(DropCounter {
+                count: count.clone(),
+            })
+        }
+        v
+    }
+
+    #[test]
     pub fn test_inline() {
         let mut v = SmallVec16::new();
         v.push("hello".to_string());
@@ -525,5 +1155,749 @@ pub mod tests {
             "hello".to_string(), "there".to_string(), "burma".to_string(), "shave".to_string(), "hello".to_string(), "there".to_string(), "burma".to_string(), "shave".to_string(),
         ]);
     }
-}
 
+    #[test]
+    pub fn test_push_all_move_spilled() {
+        let mut v = SmallVec2::new();
+        let mut w = SmallVec2::new();
+        for i in range(0u, 3000) {
+            v.push(i.to_string());
+            w.push((i + 3000).to_string());
+        }
+        v.push_all_move(w);
+        assert_eq!(v.len(), 6000);
+        for (i, value) in v.iter().enumerate() {
+            assert_eq!(*value, i.to_string());
+        }
+    }
+
+    #[test]
+    pub fn test_push_all_move_inline() {
+        let mut v = SmallVec16::new();
+        v.push("hello".to_string());
+        let mut w = SmallVec2::new();
+        w.push("there".to_string());
+        w.push("burma".to_string());
+        v.push_all_move(w);
+        assert_eq!(v.as_slice(), &["hello".to_string(), "there".to_string(), "burma".to_string()]);
+        assert!(!v.spilled());
+    }
+
+    #[test]
+    pub fn test_push_all_move_steals_buffer() {
+        let mut v: SmallVec2<String> = SmallVec2::new();
+        let mut w = SmallVec2::new();
+        for i in range(0u, 100) {
+            w.push(i.to_string());
+        }
+        let (w_ptr, w_cap) = (w.begin(), w.cap());
+        v.push_all_move(w);
+        assert_eq!(v.begin(), w_ptr);
+        assert_eq!(v.cap(), w_cap);
+        assert_eq!(v.len(), 100);
+        assert_eq!(*v.get(99), "99".to_string());
+    }
+
+    #[test]
+    pub fn test_allocation_size() {
+        assert_eq!(allocation_size::<u32>(0), 0);
+        assert_eq!(allocation_size::<u32>(16), 64);
+        assert_eq!(allocation_size::<u8>(uint::MAX), uint::MAX);
+        assert_eq!(allocation_size::<u32>(uint::MAX / 4), (uint::MAX / 4) * 4);
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_allocation_size_overflow() {
+        allocation_size::<u32>(uint::MAX / 4 + 1);
+    }
+
+    #[test]
+    pub fn test_doubled_cap() {
+        assert_eq!(doubled_cap(0), 0);
+        assert_eq!(doubled_cap(8), 16);
+        assert_eq!(doubled_cap(uint::MAX / 2), uint::MAX - 1);
+        assert_eq!(doubled_cap(uint::MAX / 2 + 1), uint::MAX);
+        assert_eq!(doubled_cap(uint::MAX), uint::MAX);
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_grow_overflow() {
+        // This must fail on the size computation, before trying to allocate anything.
+        let mut v: SmallVec2<String> = SmallVec2::new();
+        v.grow(uint::MAX);
+    }
+
+    #[test]
+    pub fn test_drop_inline() {
+        let count = Rc::new(Cell::new(0));
+        drop(drop_counters(&count, 1));
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    pub fn test_drop_spilled() {
+        let count = Rc::new(Cell::new(0));
+        drop(drop_counters(&count, 5));
+        assert_eq!(count.get(), 5);
+    }
+
+    #[test]
+    pub fn test_drop_after_pop() {
+        let count = Rc::new(Cell::new(0));
+        let mut v = drop_counters(&count, 2);
+        drop(v.pop());
+        assert_eq!(count.get(), 1);
+        drop(v);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    pub fn test_drop_partially_consumed_move_iter() {
+        let count = Rc::new(Cell::new(0));
+        for n in [1u, 5].iter() {
+            count.set(0);
+            let mut v = drop_counters(&count, *n);
+            {
+                let mut iter = v.move_iter();
+                drop(iter.next());
+                assert_eq!(count.get(), 1);
+            }
+            assert_eq!(count.get(), *n);
+            drop(v);
+            assert_eq!(count.get(), *n);
+        }
+    }
+
+    #[test]
+    pub fn test_clone_from_reuses_allocation() {
+        let mut v = SmallVec2::new();
+        for i in range(0u, 8) {
+            v.push(i.to_string());
+        }
+        let (ptr, cap) = (v.begin(), v.cap());
+
+        let mut shorter = SmallVec2::new();
+        for i in range(0u, 3) {
+            shorter.push((i * 10).to_string());
+        }
+        v.clone_from(&shorter);
+        assert_eq!(v.as_slice(), &["0".to_string(), "10".to_string(), "20".to_string()]);
+        assert_eq!(v.begin(), ptr);
+        assert_eq!(v.cap(), cap);
+
+        let mut longer = SmallVec2::new();
+        for i in range(0u, 6) {
+            longer.push((i * 100).to_string());
+        }
+        v.clone_from(&longer);
+        assert_eq!(v.as_slice(), longer.as_slice());
+        assert_eq!(v.begin(), ptr);
+        assert_eq!(v.cap(), cap);
+    }
+
+    #[test]
+    pub fn test_clone_from_grows() {
+        let mut v = SmallVec2::new();
+        v.push("hello".to_string());
+        let mut source = SmallVec2::new();
+        for i in range(0u, 5) {
+            source.push(i.to_string());
+        }
+        v.clone_from(&source);
+        assert!(v.spilled());
+        assert_eq!(v.as_slice(), source.as_slice());
+    }
+
+    #[test]
+    pub fn test_clone_from_drops_surplus() {
+        let count = Rc::new(Cell::new(0));
+        let mut v = drop_counters(&count, 5);
+        let source = drop_counters(&count, 0);
+        v.clone_from(&source);
+        assert_eq!(v.len(), 0);
+        assert_eq!(count.get(), 5);
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    pub fn test_insert_many() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.push("d".to_string());
+        v.insert_many(1, strings(["b", "c"]).as_slice());
+        assert!(v.spilled());
+        assert_eq!(v.as_slice(), strings(["a", "b", "c", "d"]).as_slice());
+
+        v.insert_many(0, strings(["z"]).as_slice());
+        v.insert_many(5, strings(["e", "f"]).as_slice());
+        v.insert_many(3, []);
+        assert_eq!(v.as_slice(), strings(["z", "a", "b", "c", "d", "e", "f"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_insert_many_inline() {
+        let mut v = SmallVec16::new();
+        v.push("a".to_string());
+        v.insert_many(0, strings(["b", "c"]).as_slice());
+        assert!(!v.spilled());
+        assert_eq!(v.as_slice(), strings(["b", "c", "a"]).as_slice());
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_insert_many_out_of_bounds() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.insert_many(2, strings(["b"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_insert_all_move() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.push("d".to_string());
+        let mut w = SmallVec2::new();
+        w.push("b".to_string());
+        w.push("c".to_string());
+        v.insert_all_move(1, w);
+        assert_eq!(v.as_slice(), strings(["a", "b", "c", "d"]).as_slice());
+
+        let mut spilled = SmallVec2::new();
+        for value in ["e", "f", "g"].iter() {
+            spilled.push(value.to_string());
+        }
+        v.insert_all_move(4, spilled);
+        assert_eq!(v.as_slice(), strings(["a", "b", "c", "d", "e", "f", "g"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_insert() {
+        let mut v = SmallVec2::new();
+        v.insert(0, "b".to_string());
+        v.insert(0, "a".to_string());
+        assert!(!v.spilled());
+        v.insert(2, "d".to_string());
+        v.insert(2, "c".to_string());
+        assert!(v.spilled());
+        assert_eq!(v.as_slice(), strings(["a", "b", "c", "d"]).as_slice());
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_insert_out_of_bounds() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.insert(2, "b".to_string());
+    }
+
+    #[test]
+    pub fn test_remove() {
+        for &n in [2u, 5].iter() {
+            let mut v = SmallVec2::new();
+            for i in range(0, n) {
+                v.push(i.to_string());
+            }
+            assert_eq!(v.remove(n), None);
+            assert_eq!(v.remove(0), Some("0".to_string()));
+            assert_eq!(v.remove(v.len() - 1), Some((n - 1).to_string()));
+            let expected: Vec<String> = range(1, n - 1).map(|i| i.to_string()).collect();
+            assert_eq!(v.as_slice(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    pub fn test_swap_remove() {
+        let mut v = SmallVec2::new();
+        for value in ["a", "b", "c", "d"].iter() {
+            v.push(value.to_string());
+        }
+        assert_eq!(v.swap_remove(0), Some("a".to_string()));
+        assert_eq!(v.as_slice(), strings(["d", "b", "c"]).as_slice());
+        assert_eq!(v.swap_remove(2), Some("c".to_string()));
+        assert_eq!(v.as_slice(), strings(["d", "b"]).as_slice());
+        assert_eq!(v.swap_remove(2), None);
+    }
+
+    #[test]
+    pub fn test_remove_drops_once() {
+        let count = Rc::new(Cell::new(0));
+        for &n in [2u, 5].iter() {
+            count.set(0);
+            let mut v = drop_counters(&count, n);
+            drop(v.remove(0));
+            drop(v.swap_remove(0));
+            assert_eq!(count.get(), 2);
+            drop(v);
+            assert_eq!(count.get(), n);
+        }
+    }
+
+    #[test]
+    pub fn test_from_iterator() {
+        let v: SmallVec16<String> = range(0u, 3).map(|i| i.to_string()).collect();
+        assert!(!v.spilled());
+        assert_eq!(v.as_slice(), strings(["0", "1", "2"]).as_slice());
+
+        let w: SmallVec2<uint> = range(0u, 100).collect();
+        assert_eq!(w.len(), 100);
+        assert_eq!(w.cap(), 100);
+        assert_eq!(*w.get(99), 99);
+    }
+
+    #[test]
+    pub fn test_extend() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.extend(strings(["b", "c"]).move_iter());
+        assert_eq!(v.as_slice(), strings(["a", "b", "c"]).as_slice());
+
+        // Iterators that don't know their length still work.
+        v.extend(strings(["d", "e", "f"]).move_iter().filter(|s| s.as_slice() != "e"));
+        assert_eq!(v.as_slice(), strings(["a", "b", "c", "d", "f"]).as_slice());
+        v.extend(range(0u, 0).map(|i| i.to_string()));
+        assert_eq!(v.len(), 5);
+    }
+
+    #[test]
+    pub fn test_with_capacity() {
+        let v: SmallVec16<String> = SmallVec16::with_capacity(10);
+        assert!(!v.spilled());
+        assert_eq!(v.cap(), 16);
+
+        let mut w = SmallVec2::with_capacity(100);
+        assert!(w.spilled());
+        assert_eq!(w.cap(), 100);
+        let ptr = w.begin();
+        for i in range(0u, 100) {
+            w.push(i.to_string());
+        }
+        assert_eq!(w.begin(), ptr);
+        assert_eq!(w.cap(), 100);
+    }
+
+    #[test]
+    pub fn test_reserve() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.reserve(2);
+        assert!(!v.spilled());
+        v.reserve(7);
+        assert_eq!(v.cap(), 7);
+        v.reserve(3);
+        assert_eq!(v.cap(), 7);
+        assert_eq!(v.as_slice(), strings(["a"]).as_slice());
+
+        v.reserve_additional(6);
+        assert_eq!(v.cap(), 7);
+        v.reserve_additional(10);
+        assert_eq!(v.cap(), 11);
+        assert_eq!(v.as_slice(), strings(["a"]).as_slice());
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_reserve_additional_overflow() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.reserve_additional(uint::MAX);
+    }
+
+    #[test]
+    pub fn test_shrink_to_fit() {
+        let mut v = SmallVec2::new();
+        for i in range(0u, 10) {
+            v.push(i.to_string());
+        }
+        assert!(v.cap() > 10);
+        v.shrink_to_fit();
+        assert_eq!(v.cap(), 10);
+        let ptr = v.begin();
+        v.shrink_to_fit();
+        assert_eq!(v.begin(), ptr);
+
+        for _ in range(0u, 7) {
+            v.pop();
+        }
+        v.shrink_to_fit();
+        assert_eq!(v.cap(), 3);
+        v.pop();
+        v.shrink_to_fit();
+        assert!(!v.spilled());
+        assert_eq!(v.cap(), 2);
+        assert_eq!(v.as_slice(), strings(["0", "1"]).as_slice());
+
+        v.push("2".to_string());
+        assert_eq!(v.as_slice(), strings(["0", "1", "2"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_shrink_to_fit_drops_nothing() {
+        let count = Rc::new(Cell::new(0));
+        let mut v = drop_counters(&count, 5);
+        for _ in range(0u, 4) {
+            v.pop();
+        }
+        v.shrink_to_fit();
+        assert!(!v.spilled());
+        assert_eq!(count.get(), 4);
+        drop(v);
+        assert_eq!(count.get(), 5);
+    }
+
+    #[test]
+    pub fn test_truncate_and_clear() {
+        for &n in [2u, 5].iter() {
+            let mut v = SmallVec2::new();
+            for i in range(0, n) {
+                v.push(i.to_string());
+            }
+            let cap = v.cap();
+            v.truncate(n + 1);
+            assert_eq!(v.len(), n);
+            v.truncate(1);
+            assert_eq!(v.as_slice(), strings(["0"]).as_slice());
+            assert_eq!(v.cap(), cap);
+            v.clear();
+            assert_eq!(v.len(), 0);
+            assert_eq!(v.cap(), cap);
+            v.push("a".to_string());
+            assert_eq!(v.as_slice(), strings(["a"]).as_slice());
+        }
+    }
+
+    #[test]
+    pub fn test_retain() {
+        let mut v = SmallVec2::new();
+        for i in range(0u, 10) {
+            v.push(i);
+        }
+        v.retain(|&i| i % 3 != 0);
+        assert_eq!(v.as_slice(), &[1u, 2, 4, 5, 7, 8]);
+        v.retain(|_| true);
+        assert_eq!(v.len(), 6);
+        v.retain(|_| false);
+        assert_eq!(v.len(), 0);
+
+        let mut w = SmallVec16::new();
+        for value in ["a", "bb", "c"].iter() {
+            w.push(value.to_string());
+        }
+        w.retain(|s| s.len() == 1);
+        assert_eq!(w.as_slice(), strings(["a", "c"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_truncate_and_retain_drop_once() {
+        let count = Rc::new(Cell::new(0));
+        for &n in [2u, 5].iter() {
+            count.set(0);
+            let mut v = drop_counters(&count, n);
+            let mut keep = false;
+            v.retain(|_| { keep = !keep; keep });
+            assert_eq!(count.get(), n / 2);
+            v.clear();
+            assert_eq!(count.get(), n);
+            drop(v);
+            assert_eq!(count.get(), n);
+        }
+    }
+
+    #[test]
+    pub fn test_comparisons() {
+        let a: SmallVec2<uint> = range(0u, 3).collect();
+        let b: SmallVec2<uint> = range(0u, 3).collect();
+        let c: SmallVec2<uint> = range(0u, 2).collect();
+        assert!(a == b);
+        assert!(a != c);
+        assert!(c < a);
+        assert!(a >= b);
+        assert_eq!(a.cmp(&c), Greater);
+        assert_eq!(a.cmp(&b), Equal);
+
+        // Inline and spilled vectors holding the same elements are equal.
+        let mut d = SmallVec2::with_capacity(10);
+        d.push(0u);
+        d.push(1);
+        let e: SmallVec2<uint> = range(0u, 2).collect();
+        assert!(d.spilled() && !e.spilled());
+        assert!(d == e);
+    }
+
+    #[test]
+    pub fn test_hash() {
+        let mut set = HashSet::new();
+        let mut v = SmallVec16::new();
+        v.push("a".to_string());
+        set.insert(v.clone());
+        assert!(set.contains(&v));
+        v.push("b".to_string());
+        assert!(!set.contains(&v));
+    }
+
+    #[test]
+    pub fn test_show() {
+        let mut v: SmallVec2<uint> = SmallVec2::new();
+        assert_eq!(format!("{}", v), "[]".to_string());
+        for i in range(0u, 3) {
+            v.push(i);
+        }
+        assert_eq!(format!("{}", v), "[0, 1, 2]".to_string());
+    }
+
+    // A size that isn't predefined, made the way other crates make theirs.
+    def_small_vector!(SmallVec3, 3)
+    def_small_vector_drop_impl!(SmallVec3, 3)
+    def_small_vector_clone_impl!(SmallVec3)
+    def_small_vector_cmp_impl!(SmallVec3)
+
+    #[test]
+    pub fn test_custom_size() {
+        let mut v = SmallVec3::new();
+        for i in range(0u, 3) {
+            v.push(i.to_string());
+        }
+        assert!(!v.spilled());
+        assert_eq!(v.inline_size(), 3);
+        v.push("3".to_string());
+        assert!(v.spilled());
+        assert!(v.clone() == v);
+        assert_eq!(v.as_slice(), strings(["0", "1", "2", "3"]).as_slice());
+
+        let count = Rc::new(Cell::new(0));
+        let mut w = SmallVec3::new();
+        for _ in range(0u, 5) {
+            w.push(DropCounter {
+                count: count.clone(),
+            })
+        }
+        drop(w);
+        assert_eq!(count.get(), 5);
+    }
+
+    #[test]
+    pub fn test_drain() {
+        for &n in [2u, 5].iter() {
+            let mut v = SmallVec2::new();
+            for i in range(0, n) {
+                v.push(i.to_string());
+            }
+            let (ptr, cap) = (v.begin(), v.cap());
+            let drained: Vec<String> = v.drain().collect();
+            let expected: Vec<String> = range(0, n).map(|i| i.to_string()).collect();
+            assert_eq!(drained, expected);
+            assert_eq!(v.len(), 0);
+            assert_eq!(v.cap(), cap);
+            assert_eq!(v.begin(), ptr);
+
+            v.push("a".to_string());
+            assert_eq!(v.as_slice(), strings(["a"]).as_slice());
+            assert_eq!(v.begin(), ptr);
+        }
+    }
+
+    #[test]
+    pub fn test_drop_partially_consumed_drain() {
+        let count = Rc::new(Cell::new(0));
+        for &n in [2u, 5].iter() {
+            count.set(0);
+            let mut v = drop_counters(&count, n);
+            {
+                let mut drain = v.drain();
+                drop(drain.next());
+                assert_eq!(count.get(), 1);
+            }
+            assert_eq!(count.get(), n);
+            assert_eq!(v.len(), 0);
+            drop(v);
+            assert_eq!(count.get(), n);
+        }
+    }
+
+    /// Keeps `vec` sorted while adding `value` and dropping `unwanted`, using only `VecLike`.
+    fn splice_sorted<V:VecLike<uint>>(vec: &mut V, value: uint, unwanted: uint) {
+        let position = vec.vec_iter().position(|&other| other == unwanted);
+        match position {
+            Some(index) => assert_eq!(vec.vec_remove(index), Some(unwanted)),
+            None => {}
+        }
+        let index = vec.vec_iter().take_while(|&&other| other < value).count();
+        vec.vec_insert(index, value)
+    }
+
+    #[test]
+    pub fn test_vec_like() {
+        let mut v: SmallVec2<uint> = SmallVec2::new();
+        let mut w: Vec<uint> = Vec::new();
+        for &(value, unwanted) in [(5u, 0u), (1, 0), (3, 5), (4, 1), (2, 9)].iter() {
+            splice_sorted(&mut v, value, unwanted);
+            splice_sorted(&mut w, value, unwanted);
+            assert_eq!(v.vec_as_slice(), w.as_slice());
+        }
+        assert_eq!(v.as_slice(), &[2u, 3, 4]);
+        assert_eq!(*v.vec_get(1), 3);
+        *v.vec_get_mut(1) = 7;
+        assert_eq!(v.as_slice(), &[2u, 7, 4]);
+        assert_eq!(v.vec_remove(3), None);
+        assert_eq!(w.vec_remove(3), None);
+    }
+
+    #[test]
+    pub fn test_write_through_as_mut_ptr() {
+        for &reserved in [2u, 8].iter() {
+            let mut v: SmallVec2<u32> = SmallVec2::new();
+            v.push(1);
+            if reserved > v.cap() {
+                v.grow(reserved)
+            }
+            unsafe {
+                let ptr = v.as_mut_ptr();
+                for i in range(1, reserved) {
+                    *ptr.offset(i as int) = (i + 1) as u32;
+                }
+                v.set_len(reserved);
+            }
+            assert_eq!(v.as_ptr(), v.as_slice().as_ptr());
+            let expected: Vec<u32> = range(1, reserved + 1).map(|i| i as u32).collect();
+            assert_eq!(v.as_slice(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    pub fn test_swap() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.push("b".to_string());
+        v.swap(0, 1);
+        assert_eq!(v.as_slice(), strings(["b", "a"]).as_slice());
+        v.swap(1, 1);
+        assert_eq!(v.as_slice(), strings(["b", "a"]).as_slice());
+        v.push("c".to_string());
+        v.swap(0, 2);
+        assert_eq!(v.as_slice(), strings(["c", "a", "b"]).as_slice());
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_swap_out_of_bounds() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.swap(0, 1);
+    }
+
+    #[test]
+    pub fn test_reverse() {
+        let mut v = SmallVec16::new();
+        v.reverse();
+        v.push("a".to_string());
+        v.reverse();
+        assert_eq!(v.as_slice(), strings(["a"]).as_slice());
+        v.push("b".to_string());
+        v.reverse();
+        assert_eq!(v.as_slice(), strings(["b", "a"]).as_slice());
+
+        let mut w = SmallVec2::new();
+        for value in ["a", "b", "c", "d", "e"].iter() {
+            w.push(value.to_string());
+        }
+        w.reverse();
+        assert_eq!(w.as_slice(), strings(["e", "d", "c", "b", "a"]).as_slice());
+        w.pop();
+        w.reverse();
+        assert_eq!(w.as_slice(), strings(["b", "c", "d", "e"]).as_slice());
+    }
+
+    #[test]
+    pub fn test_mut_rev_iter() {
+        let mut v = SmallVec2::new();
+        v.push("a".to_string());
+        v.push("b".to_string());
+        for (i, value) in v.mut_rev_iter().enumerate() {
+            value.push_str(i.to_string().as_slice());
+        }
+        assert_eq!(v.as_slice(), strings(["a1", "b0"]).as_slice());
+
+        v.push("c".to_string());
+        for (i, value) in v.mut_iter().rev().enumerate() {
+            value.push_str(i.to_string().as_slice());
+        }
+        assert_eq!(v.as_slice(), strings(["a12", "b01", "c0"]).as_slice());
+
+        let mut empty: SmallVec16<String> = SmallVec16::new();
+        assert!(empty.mut_rev_iter().next().is_none());
+    }
+
+    #[test]
+    pub fn test_rev_iter_zero_sized() {
+        let mut v = SmallVec16::new();
+        for _ in range(0u, 5) {
+            v.push(());
+        }
+        assert_eq!(v.iter().count(), 5);
+        assert_eq!(v.iter().rev().count(), 5);
+        assert_eq!(v.mut_rev_iter().count(), 5);
+    }
+
+    #[test]
+    pub fn test_pop_and_compact() {
+        let mut v = SmallVec2::new();
+        for i in range(0u, 1000) {
+            v.push(i.to_string());
+        }
+        let full_cap = v.cap();
+        assert!(full_cap >= 1000);
+
+        let mut last_cap = full_cap;
+        for i in range(0u, 1000).rev() {
+            assert_eq!(v.pop_and_compact(), Some(i.to_string()));
+            assert!(v.cap() <= last_cap);
+            last_cap = v.cap();
+            if v.spilled() {
+                assert!(v.len() >= v.cap() / 4);
+            }
+            for (j, value) in v.iter().enumerate() {
+                assert_eq!(*value, j.to_string());
+            }
+        }
+        assert!(!v.spilled());
+        assert_eq!(v.cap(), 2);
+        assert_eq!(v.pop_and_compact(), None);
+    }
+
+    #[test]
+    pub fn test_pop_and_compact_hysteresis() {
+        let mut v = SmallVec2::new();
+        for i in range(0u, 64) {
+            v.push(i.to_string());
+        }
+        let full_cap = v.cap();
+        while v.cap() == full_cap {
+            v.pop_and_compact();
+        }
+        // We just crossed the threshold, so the storage shrank to twice the length.
+        let cap = v.cap();
+        assert_eq!(cap, v.len() * 2);
+        for _ in range(0u, 10) {
+            v.pop_and_compact();
+            v.push("x".to_string());
+            assert_eq!(v.cap(), cap);
+        }
+    }
+
+    #[test]
+    pub fn test_pop_and_compact_drops_nothing() {
+        let count = Rc::new(Cell::new(0));
+        let mut v = drop_counters(&count, 20);
+        for _ in range(0u, 19) {
+            v.pop_and_compact();
+        }
+        assert_eq!(count.get(), 19);
+        assert!(!v.spilled());
+        drop(v);
+        assert_eq!(count.get(), 20);
+    }
+}
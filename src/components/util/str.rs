@@ -2,14 +2,126 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use std::fmt;
 use std::iter::Filter;
+use std::iter;
 use std::str::CharSplits;
+use std::str;
 
-pub type DOMString = String;
 pub type StaticCharVec = &'static [char];
 pub type StaticStringVec = &'static [&'static str];
 
-pub fn null_str_as_empty(s: &Option<DOMString>) -> DOMString {
+/// A string as the DOM sees it: a sequence of UTF-16 code units that need not be valid UTF-16.
+/// JavaScript strings can contain unpaired surrogates, which a `String` can't hold, and DOM APIs
+/// measure lengths and offsets in code units rather than bytes or characters.
+#[deriving(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Encodable)]
+pub struct DOMString {
+    units: Vec<u16>,
+}
+
+impl DOMString {
+    pub fn new() -> DOMString {
+        DOMString {
+            units: vec!(),
+        }
+    }
+
+    pub fn from_slice(string: &str) -> DOMString {
+        DOMString {
+            units: string.to_utf16(),
+        }
+    }
+
+    pub fn from_string(string: String) -> DOMString {
+        DOMString::from_slice(string.as_slice())
+    }
+
+    /// Makes a string from UTF-16 code units, keeping any unpaired surrogates.
+    pub fn from_utf16(units: Vec<u16>) -> DOMString {
+        DOMString {
+            units: units,
+        }
+    }
+
+    #[inline]
+    pub fn as_utf16<'a>(&'a self) -> &'a [u16] {
+        self.units.as_slice()
+    }
+
+    /// Returns the length in UTF-16 code units, which is what the DOM calls the length.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.units.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Returns the code unit at `index`, or `None` if it is out of range.
+    #[inline]
+    pub fn code_unit_at(&self, index: uint) -> Option<u16> {
+        self.units.as_slice().get(index).map(|&unit| unit)
+    }
+
+    /// Returns the `count` code units starting at `offset`, or as many as there are, as in
+    /// `CharacterData.substringData()`. Returns `None` if `offset` is past the end.
+    ///
+    /// This can split a surrogate pair, leaving an unpaired surrogate at either end.
+    pub fn substring(&self, offset: uint, count: uint) -> Option<DOMString> {
+        if offset > self.len() {
+            return None
+        }
+        let end = if count > self.len() - offset { self.len() } else { offset + count };
+        Some(DOMString::from_utf16(Vec::from_slice(self.units.slice(offset, end))))
+    }
+
+    pub fn push_str(&mut self, string: &str) {
+        self.units.push_all(string.to_utf16().as_slice())
+    }
+
+    pub fn push_domstring(&mut self, string: &DOMString) {
+        self.units.push_all(string.as_utf16())
+    }
+
+    /// Returns true if this holds the same text as `other`, without converting either string.
+    pub fn eq_slice(&self, other: &str) -> bool {
+        iter::order::eq(self.units.iter().map(|&unit| unit), other.utf16_units())
+    }
+
+    /// Returns true if every code unit is a space, tab, carriage return or line feed, as
+    /// `is_whitespace` checks for a `&str`.
+    pub fn is_whitespace(&self) -> bool {
+        self.units.iter().all(|&unit| match unit {
+            0x20 | 0x09 | 0x0D | 0x0A => true,
+            _ => false
+        })
+    }
+
+    /// Returns true if the string is valid UTF-16, i.e. has no unpaired surrogates.
+    pub fn is_well_formed(&self) -> bool {
+        str::is_utf16(self.as_utf16())
+    }
+
+    /// Converts to UTF-8, replacing each unpaired surrogate with U+FFFD REPLACEMENT CHARACTER.
+    pub fn to_string_lossy(&self) -> String {
+        str::from_utf16_lossy(self.as_utf16())
+    }
+
+    /// Converts to UTF-8, or returns `None` if the string has unpaired surrogates.
+    pub fn to_string_strict(&self) -> Option<String> {
+        str::from_utf16(self.as_utf16())
+    }
+}
+
+impl fmt::Show for DOMString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.to_string_lossy().as_slice())
+    }
+}
+
+pub fn null_str_as_empty(s: &Option<String>) -> String {
     // We don't use map_default because it would allocate "".to_string() even for Some.
     match *s {
         Some(ref s) => s.clone(),
@@ -17,7 +129,7 @@ pub fn null_str_as_empty(s: &Option<DOMString>) -> DOMString {
     }
 }
 
-pub fn null_str_as_empty_ref<'a>(s: &'a Option<DOMString>) -> &'a str {
+pub fn null_str_as_empty_ref<'a>(s: &'a Option<String>) -> &'a str {
     match *s {
         Some(ref s) => s.as_slice(),
         None => ""
@@ -46,3 +158,64 @@ pub static HTML_SPACE_CHARACTERS: StaticCharVec = &[
 pub fn split_html_space_chars<'a>(s: &'a str) -> Filter<'a, &'a str, CharSplits<'a, StaticCharVec>> {
     s.split(HTML_SPACE_CHARACTERS).filter(|&split| !split.is_empty())
 }
+
+#[cfg(test)]
+mod tests {
+    use str::DOMString;
+
+    #[test]
+    fn test_length_in_code_units() {
+        // U+1F600 takes two UTF-16 code units, four UTF-8 bytes, and is one char.
+        let string = DOMString::from_slice("a\U0001F600b");
+        assert_eq!(string.len(), 4);
+        assert_eq!(string.code_unit_at(1), Some(0xD83D));
+        assert_eq!(string.code_unit_at(4), None);
+        assert!(string.is_well_formed());
+        assert_eq!(string.to_string_strict(), Some("a\U0001F600b".to_string()));
+    }
+
+    #[test]
+    fn test_unpaired_surrogates() {
+        let string = DOMString::from_utf16(vec!(0x61, 0xD800, 0x62));
+        assert!(!string.is_well_formed());
+        assert_eq!(string.to_string_strict(), None);
+        assert_eq!(string.to_string_lossy(), "a\uFFFDb".to_string());
+        assert_eq!(string.as_utf16(), [0x61u16, 0xD800, 0x62].as_slice());
+    }
+
+    #[test]
+    fn test_substring() {
+        let string = DOMString::from_slice("a\U0001F600b");
+        assert_eq!(string.substring(0, 1), Some(DOMString::from_slice("a")));
+        assert_eq!(string.substring(1, 100), Some(DOMString::from_slice("\U0001F600b")));
+        assert_eq!(string.substring(4, 1), Some(DOMString::new()));
+        assert_eq!(string.substring(5, 0), None);
+
+        // Splitting a surrogate pair leaves half of it unpaired.
+        let half = string.substring(0, 2).unwrap();
+        assert!(!half.is_well_formed());
+        let mut joined = half;
+        joined.push_domstring(&string.substring(2, 2).unwrap());
+        assert_eq!(joined, string);
+    }
+
+    #[test]
+    fn test_eq_slice() {
+        assert!(DOMString::from_slice("click").eq_slice("click"));
+        assert!(!DOMString::from_slice("click").eq_slice("clicks"));
+        assert!(!DOMString::from_slice("clicks").eq_slice("click"));
+        assert!(DOMString::from_slice("a\U0001F600").eq_slice("a\U0001F600"));
+        assert!(!DOMString::from_utf16(vec!(0xD800)).eq_slice("\uFFFD"));
+        assert!(DOMString::new().eq_slice(""));
+    }
+
+    #[test]
+    fn test_is_whitespace() {
+        assert!(DOMString::new().is_whitespace());
+        assert!(DOMString::from_slice(" \t\r\n").is_whitespace());
+        assert!(!DOMString::from_slice(" a ").is_whitespace());
+        // Form feeds and no-break spaces aren't whitespace here, just as for `is_whitespace`.
+        assert!(!DOMString::from_slice("\x0c").is_whitespace());
+        assert!(!DOMString::from_slice("\u00a0").is_whitespace());
+    }
+}
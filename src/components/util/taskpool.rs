@@ -0,0 +1,222 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A pool of tasks that runs jobs in priority order, so that work the user is waiting for isn't
+//! held up behind background work such as image decoding.
+//!
+//! Jobs run to completion on whichever worker picks them up, so a job that blocks (on a channel,
+//! say) keeps its worker busy for as long as it blocks. Never submit a job that waits for another
+//! job in the same pool.
+
+use task::spawn_named;
+
+use std::collections::{Deque, RingBuf};
+use std::task;
+use sync::{Arc, Mutex};
+
+/// How urgently a job needs to run. Workers always take the oldest job from the most urgent
+/// nonempty queue.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub enum TaskPriority {
+    /// Work that the user is actively waiting on, such as an image a reflow is blocked on.
+    UserBlockingPriority,
+    /// Work whose results will be shown to the user, such as decoding an image on screen.
+    UserVisiblePriority,
+    /// Work that can wait, such as decoding an image that may not be on screen.
+    BackgroundPriority,
+}
+
+static NUM_PRIORITIES: uint = 3;
+
+impl TaskPriority {
+    fn index(&self) -> uint {
+        match *self {
+            UserBlockingPriority => 0,
+            UserVisiblePriority => 1,
+            BackgroundPriority => 2,
+        }
+    }
+}
+
+struct PoolState {
+    /// One queue per priority, most urgent first.
+    queues: [RingBuf<proc():Send>, ..NUM_PRIORITIES],
+    /// Set once the last handle to the pool is dropped.
+    shutting_down: bool,
+}
+
+impl PoolState {
+    fn pop(&mut self) -> Option<proc():Send> {
+        for queue in self.queues.mut_iter() {
+            match queue.pop_front() {
+                Some(job) => return Some(job),
+                None => {}
+            }
+        }
+        None
+    }
+}
+
+/// Shuts the pool down when the last `TaskPool` handle goes away. Workers only hold the state, so
+/// they don't keep the pool alive.
+struct ShutdownOnDrop {
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl Drop for ShutdownOnDrop {
+    fn drop(&mut self) {
+        let mut guard = self.state.lock();
+        guard.shutting_down = true;
+        guard.cond.broadcast();
+    }
+}
+
+/// A handle to a task pool. Clones share the same workers. Once every handle has been dropped the
+/// workers finish the jobs already queued and then exit.
+#[deriving(Clone)]
+pub struct TaskPool {
+    state: Arc<Mutex<PoolState>>,
+    _shutdown: Arc<ShutdownOnDrop>,
+}
+
+impl TaskPool {
+    /// Creates a pool with `thread_count` workers.
+    pub fn new(thread_count: uint) -> TaskPool {
+        assert!(thread_count > 0);
+        let state = Arc::new(Mutex::new(PoolState {
+            queues: [RingBuf::new(), RingBuf::new(), RingBuf::new()],
+            shutting_down: false,
+        }));
+        for index in range(0, thread_count) {
+            spawn_worker(state.clone(), index);
+        }
+        TaskPool {
+            state: state.clone(),
+            _shutdown: Arc::new(ShutdownOnDrop {
+                state: state,
+            }),
+        }
+    }
+
+    /// Queues `job` to run at the given priority.
+    pub fn execute(&self, priority: TaskPriority, job: proc():Send) {
+        let mut guard = self.state.lock();
+        guard.queues[priority.index()].push_back(job);
+        guard.cond.signal();
+    }
+
+    /// Returns the number of jobs waiting to run at the given priority.
+    pub fn queued_jobs(&self, priority: TaskPriority) -> uint {
+        self.state.lock().queues[priority.index()].len()
+    }
+}
+
+/// Replaces a worker whose job failed, so that one bad job doesn't shrink the pool.
+struct WorkerSentinel {
+    state: Option<Arc<Mutex<PoolState>>>,
+    index: uint,
+}
+
+impl Drop for WorkerSentinel {
+    fn drop(&mut self) {
+        if task::failing() {
+            spawn_worker(self.state.take_unwrap(), self.index)
+        }
+    }
+}
+
+fn spawn_worker(state: Arc<Mutex<PoolState>>, index: uint) {
+    spawn_named(format!("TaskPoolWorker{}", index), proc() {
+        let sentinel = WorkerSentinel {
+            state: Some(state),
+            index: index,
+        };
+        loop {
+            let job = {
+                let state = sentinel.state.get_ref();
+                let mut guard = state.lock();
+                let mut job = guard.pop();
+                while job.is_none() && !guard.shutting_down {
+                    guard.cond.wait();
+                    job = guard.pop();
+                }
+                job
+            };
+            match job {
+                Some(job) => job(),
+                None => break,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use taskpool::{TaskPool, UserBlockingPriority, UserVisiblePriority, BackgroundPriority};
+
+    #[test]
+    fn test_runs_jobs() {
+        let pool = TaskPool::new(4);
+        let (chan, port) = channel();
+        for i in range(0u, 16) {
+            let chan = chan.clone();
+            pool.execute(UserVisiblePriority, proc() chan.send(i));
+        }
+        let mut total = 0;
+        for _ in range(0u, 16) {
+            total += port.recv();
+        }
+        assert_eq!(total, 120);
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let pool = TaskPool::new(1);
+        let (chan, port) = channel();
+
+        // Keep the only worker busy until everything else is queued.
+        let (start_chan, start_port) = channel();
+        let (release_chan, release_port) = channel::<()>();
+        pool.execute(UserBlockingPriority, proc() {
+            start_chan.send(());
+            release_port.recv();
+        });
+        start_port.recv();
+
+        let background_chan = chan.clone();
+        pool.execute(BackgroundPriority, proc() background_chan.send("background"));
+        let visible_chan = chan.clone();
+        pool.execute(UserVisiblePriority, proc() visible_chan.send("visible"));
+        let blocking_chan = chan.clone();
+        pool.execute(UserBlockingPriority, proc() blocking_chan.send("blocking"));
+        assert_eq!(pool.queued_jobs(BackgroundPriority), 1);
+
+        release_chan.send(());
+        assert_eq!(port.recv(), "blocking");
+        assert_eq!(port.recv(), "visible");
+        assert_eq!(port.recv(), "background");
+    }
+
+    #[test]
+    fn test_failing_job_replaces_worker() {
+        let pool = TaskPool::new(1);
+        pool.execute(UserVisiblePriority, proc() fail!("expected failure"));
+        let (chan, port) = channel();
+        pool.execute(UserVisiblePriority, proc() chan.send(()));
+        port.recv();
+    }
+
+    #[test]
+    fn test_finishes_queued_jobs_after_drop() {
+        let pool = TaskPool::new(1);
+        let (chan, port) = channel();
+        for i in range(0u, 4) {
+            let chan = chan.clone();
+            pool.execute(BackgroundPriority, proc() chan.send(i));
+        }
+        drop(pool);
+        drop(chan);
+        assert_eq!(port.iter().count(), 4);
+    }
+}
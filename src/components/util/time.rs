@@ -6,11 +6,13 @@
 
 use std_time::precise_time_ns;
 use collections::treemap::TreeMap;
+use serialize::json;
 use std::comm::{Sender, channel, Receiver};
 use std::f64;
 use std::iter::AdditiveIterator;
 use std::io::timer::sleep;
 use task::{spawn_named};
+use workqueue::WorkerStats;
 
 // front-end representation of the profiler used to communicate with the profiler
 #[deriving(Clone)]
@@ -25,7 +27,9 @@ impl TimeProfilerChan {
 
 pub enum TimeProfilerMsg {
     /// Normal message used for reporting time
-    TimeMsg(TimeProfilerCategory, f64),
+    TimeMsg(TimerKey, f64),
+    /// Reports what each worker of a pipeline's parallel layout did since the last report.
+    WorkerStatsMsg(uint, Vec<WorkerStats>),
     /// Message used to force print the profiling metrics
     PrintMsg,
     /// Tells the profiler to shut down.
@@ -50,67 +54,94 @@ pub enum TimeProfilerCategory {
     RenderingDrawingCategory,
     RenderingPrepBuffCategory,
     RenderingCategory,
-    // FIXME(rust#8803): workaround for lack of CTFE function on enum types to return length
-    NumBuckets,
 }
 
-impl TimeProfilerCategory {
-    // convenience function to not have to cast every time
-    pub fn num_buckets() -> uint {
-        NumBuckets as uint
-    }
+/// How the time profiler prints what it has measured (`--profile-output`).
+#[deriving(Clone, PartialEq, Show)]
+pub enum TimeProfilerOutput {
+    /// A table for people to read.
+    TextOutput,
+    /// A JSON array for tools, one per line.
+    JsonOutput,
+}
 
-    // enumeration of all TimeProfilerCategory types
-    fn empty_buckets() -> TimeProfilerBuckets {
-        let mut buckets = TreeMap::new();
-        buckets.insert(CompositingCategory, vec!());
-        buckets.insert(LayoutQueryCategory, vec!());
-        buckets.insert(LayoutPerformCategory, vec!());
-        buckets.insert(LayoutStyleRecalcCategory, vec!());
-        buckets.insert(LayoutSelectorMatchCategory, vec!());
-        buckets.insert(LayoutTreeBuilderCategory, vec!());
-        buckets.insert(LayoutMainCategory, vec!());
-        buckets.insert(LayoutParallelWarmupCategory, vec!());
-        buckets.insert(LayoutShapingCategory, vec!());
-        buckets.insert(LayoutDamagePropagateCategory, vec!());
-        buckets.insert(LayoutDispListBuildCategory, vec!());
-        buckets.insert(GfxRegenAvailableFontsCategory, vec!());
-        buckets.insert(RenderingDrawingCategory, vec!());
-        buckets.insert(RenderingPrepBuffCategory, vec!());
-        buckets.insert(RenderingCategory, vec!());
-
-        buckets
+/// What a measured time was spent on.
+#[deriving(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimerKey {
+    /// The pipeline the time is attributed to, as the number in its `PipelineId`. Times measured
+    /// inside a `profile` call for a pipeline are attributed to it too.
+    pub pipeline: Option<uint>,
+    /// The category the time is for, preceded by the categories of the `profile` calls it was
+    /// measured inside, outermost first.
+    pub categories: Vec<TimeProfilerCategory>,
+}
+
+impl TimerKey {
+    fn nested_in(outer: &Option<TimerKey>, category: TimeProfilerCategory, pipeline: Option<uint>)
+                 -> TimerKey {
+        match *outer {
+            Some(ref outer) => {
+                let mut categories = outer.categories.clone();
+                categories.push(category);
+                TimerKey {
+                    pipeline: pipeline.or(outer.pipeline),
+                    categories: categories,
+                }
+            }
+            None => {
+                TimerKey {
+                    pipeline: pipeline,
+                    categories: vec!(category),
+                }
+            }
+        }
     }
 
-    // some categories are subcategories of LayoutPerformCategory
-    // and should be printed to indicate this
-    pub fn format(self) -> String {
-        let padding = match self {
-            LayoutStyleRecalcCategory |
-            LayoutMainCategory |
-            LayoutDispListBuildCategory |
-            LayoutShapingCategory |
-            LayoutDamagePropagateCategory => "+ ",
-            LayoutParallelWarmupCategory |
-            LayoutSelectorMatchCategory |
-            LayoutTreeBuilderCategory => "| + ",
-            _ => ""
-        };
-        format!("{:s}{:?}", padding, self)
+    /// The category name, indented to show which categories it is nested in.
+    fn format(&self) -> String {
+        let mut result = String::new();
+        for _ in range(1, self.categories.len()) {
+            result.push_str("| ");
+        }
+        if self.categories.len() > 1 {
+            result.push_str("+ ");
+        }
+        result.push_str(format!("{:?}", *self.categories.last().unwrap()).as_slice());
+        result
     }
 }
 
-type TimeProfilerBuckets = TreeMap<TimeProfilerCategory, Vec<f64>>;
+/// The `profile` call currently running on this task, if any.
+local_data_key!(current_timer: TimerKey)
+
+/// Summary statistics of a bucket, as written in JSON output.
+#[deriving(Encodable)]
+struct BucketSummary {
+    pipeline: Option<uint>,
+    categories: Vec<String>,
+    mean: f64,
+    median: f64,
+    min: f64,
+    max: f64,
+    count: uint,
+}
+
+type TimeProfilerBuckets = TreeMap<TimerKey, Vec<f64>>;
+
+/// What each layout worker of each pipeline has done, by pipeline number.
+type WorkerStatsTotals = TreeMap<uint, Vec<WorkerStats>>;
 
 // back end of the profiler that handles data aggregation and performance metrics
 pub struct TimeProfiler {
     pub port: Receiver<TimeProfilerMsg>,
     buckets: TimeProfilerBuckets,
+    worker_stats: WorkerStatsTotals,
+    output: TimeProfilerOutput,
     pub last_msg: Option<TimeProfilerMsg>,
 }
 
 impl TimeProfiler {
-    pub fn create(period: Option<f64>) -> TimeProfilerChan {
+    pub fn create(period: Option<f64>, output: TimeProfilerOutput) -> TimeProfilerChan {
         let (chan, port) = channel();
         match period {
             Some(period) => {
@@ -126,7 +157,7 @@ impl TimeProfiler {
                 });
                 // Spawn the time profiler.
                 spawn_named("Time profiler", proc() {
-                    let mut profiler = TimeProfiler::new(port);
+                    let mut profiler = TimeProfiler::new(port, output);
                     profiler.start();
                 });
             }
@@ -146,10 +177,12 @@ impl TimeProfiler {
         TimeProfilerChan(chan)
     }
 
-    pub fn new(port: Receiver<TimeProfilerMsg>) -> TimeProfiler {
+    pub fn new(port: Receiver<TimeProfilerMsg>, output: TimeProfilerOutput) -> TimeProfiler {
         TimeProfiler {
             port: port,
-            buckets: TimeProfilerCategory::empty_buckets(),
+            buckets: TreeMap::new(),
+            worker_stats: TreeMap::new(),
+            output: output,
             last_msg: None,
         }
     }
@@ -170,10 +203,39 @@ impl TimeProfiler {
 
     fn handle_msg(&mut self, msg: TimeProfilerMsg) -> bool {
         match msg {
-            TimeMsg(category, t) => self.buckets.find_mut(&category).unwrap().push(t),
+            TimeMsg(ref key, t) => {
+                let found = match self.buckets.find_mut(key) {
+                    Some(data) => {
+                        data.push(t);
+                        true
+                    }
+                    None => false,
+                };
+                if !found {
+                    self.buckets.insert(key.clone(), vec!(t));
+                }
+            }
+            WorkerStatsMsg(pipeline, ref stats) => {
+                if !self.worker_stats.contains_key(&pipeline) {
+                    self.worker_stats.insert(pipeline, vec!());
+                }
+                let totals = self.worker_stats.find_mut(&pipeline).unwrap();
+                // The pool may have grown since the last report.
+                while totals.len() < stats.len() {
+                    totals.push(WorkerStats::new())
+                }
+                for (total, stats) in totals.mut_iter().zip(stats.iter()) {
+                    total.add(stats)
+                }
+            }
             PrintMsg => match self.last_msg {
                 // only print if more data has arrived since the last printout
-                Some(TimeMsg(..)) => self.print_buckets(),
+                Some(TimeMsg(..)) | Some(WorkerStatsMsg(..)) => {
+                    match self.output {
+                        TextOutput => self.print_buckets(),
+                        JsonOutput => println!("{:s}", self.buckets_to_json()),
+                    }
+                }
                 _ => ()
             },
             ExitMsg => return false,
@@ -182,11 +244,11 @@ impl TimeProfiler {
         true
     }
 
-    fn print_buckets(&mut self) {
-        println!("{:39s} {:15s} {:15s} {:15s} {:15s} {:15s}",
-                 "_category_", "_mean (ms)_", "_median (ms)_",
-                 "_min (ms)_", "_max (ms)_", "_bucket size_");
-        for (category, data) in self.buckets.mut_iter() {
+    /// Sorts each bucket and summarizes the non-empty ones, in the order they are printed: by
+    /// pipeline, with nested categories following the ones they are nested in.
+    fn summarize_buckets<'a>(&'a mut self) -> Vec<(&'a TimerKey, f64, f64, f64, f64, uint)> {
+        let mut summaries = vec!();
+        for (key, data) in self.buckets.mut_iter() {
             data.sort_by(|a, b| {
                 if a < b {
                     Less
@@ -201,24 +263,78 @@ impl TimeProfiler {
                      *data.get(data_len / 2),
                      data.iter().fold(f64::INFINITY, |a, &b| a.min(b)),
                      data.iter().fold(-f64::INFINITY, |a, &b| a.max(b)));
-                println!("{:-35s}: {:15.4f} {:15.4f} {:15.4f} {:15.4f} {:15u}",
-                         category.format(), mean, median, min, max, data_len);
+                summaries.push((key, mean, median, min, max, data_len));
+            }
+        }
+        summaries
+    }
+
+    fn print_buckets(&mut self) {
+        println!("{:39s} {:10s} {:15s} {:15s} {:15s} {:15s} {:15s}",
+                 "_category_", "_pipeline_", "_mean (ms)_", "_median (ms)_",
+                 "_min (ms)_", "_max (ms)_", "_bucket size_");
+        for &(key, mean, median, min, max, data_len) in self.summarize_buckets().iter() {
+            let pipeline = match key.pipeline {
+                Some(pipeline) => pipeline.to_str(),
+                None => "-".to_string(),
+            };
+            println!("{:-35s}: {:>10s} {:15.4f} {:15.4f} {:15.4f} {:15.4f} {:15u}",
+                     key.format(), pipeline, mean, median, min, max, data_len);
+        }
+        println!("");
+
+        if self.worker_stats.is_empty() {
+            return
+        }
+        println!("{:10s} {:10s} {:15s} {:15s} {:15s}",
+                 "_pipeline_", "_worker_", "_executed_", "_stolen_", "_backoffs_");
+        for (pipeline, totals) in self.worker_stats.iter() {
+            for (worker, stats) in totals.iter().enumerate() {
+                println!("{:>10u} {:>10u} {:15u} {:15u} {:15u}",
+                         *pipeline, worker, stats.executed, stats.stolen, stats.backoffs);
             }
         }
         println!("");
     }
-}
 
+    /// The summaries of the buckets as a JSON array on one line.
+    fn buckets_to_json(&mut self) -> String {
+        let summaries: Vec<BucketSummary> =
+                self.summarize_buckets().move_iter().map(|(key, mean, median, min, max, count)| {
+            BucketSummary {
+                pipeline: key.pipeline,
+                categories: key.categories.iter().map(|category| {
+                    format!("{:?}", *category)
+                }).collect(),
+                mean: mean,
+                median: median,
+                min: min,
+                max: max,
+                count: count,
+            }
+        }).collect();
+        json::Encoder::str_encode(&summaries)
+    }
+}
 
+/// Times `callback` and reports the time under `category` and, if it's given, `pipeline`. Calls
+/// made from inside `callback` on this task are reported as nested in this one.
 pub fn profile<T>(category: TimeProfilerCategory,
+                  pipeline: Option<uint>,
                   time_profiler_chan: TimeProfilerChan,
                   callback: || -> T)
                   -> T {
+    let outer = current_timer.replace(None);
+    let key = TimerKey::nested_in(&outer, category, pipeline);
+    current_timer.replace(Some(key.clone()));
+
     let start_time = precise_time_ns();
     let val = callback();
     let end_time = precise_time_ns();
+
+    current_timer.replace(outer);
     let ms = (end_time - start_time) as f64 / 1000000f64;
-    time_profiler_chan.send(TimeMsg(category, ms));
+    time_profiler_chan.send(TimeMsg(key, ms));
     return val;
 }
 
@@ -233,9 +349,103 @@ pub fn time<T>(msg: &str, callback: || -> T) -> T{
     return val;
 }
 
-// ensure that the order of the buckets matches the order of the enum categories
-#[test]
-fn check_order() {
-    let buckets = TimeProfilerCategory::empty_buckets();
-    assert!(buckets.len() == NumBuckets as uint);
+#[cfg(test)]
+mod tests {
+    use time::{LayoutMainCategory, LayoutParallelWarmupCategory, LayoutPerformCategory};
+    use time::{CompositingCategory, TextOutput, TimeMsg, TimeProfiler, TimeProfilerChan};
+    use time::{TimeProfilerMsg, TimerKey, WorkerStatsMsg, profile};
+    use workqueue::WorkerStats;
+
+    fn received_keys(port: &Receiver<TimeProfilerMsg>) -> Vec<TimerKey> {
+        let mut keys = vec!();
+        loop {
+            match port.try_recv() {
+                Ok(TimeMsg(key, _)) => keys.push(key),
+                Ok(_) => fail!("expected only times"),
+                Err(_) => return keys,
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_profile_calls() {
+        let (chan, port) = channel();
+        let chan = TimeProfilerChan(chan);
+        profile(LayoutPerformCategory, Some(3), chan.clone(), || {
+            profile(LayoutMainCategory, None, chan.clone(), || {
+                profile(LayoutParallelWarmupCategory, None, chan.clone(), || {})
+            })
+        });
+        profile(CompositingCategory, None, chan.clone(), || {});
+
+        // Inner calls finish, and so are reported, first.
+        let keys = received_keys(&port);
+        assert!(keys == vec!(
+            TimerKey {
+                pipeline: Some(3),
+                categories: vec!(LayoutPerformCategory, LayoutMainCategory,
+                                 LayoutParallelWarmupCategory),
+            },
+            TimerKey {
+                pipeline: Some(3),
+                categories: vec!(LayoutPerformCategory, LayoutMainCategory),
+            },
+            TimerKey {
+                pipeline: Some(3),
+                categories: vec!(LayoutPerformCategory),
+            },
+            TimerKey {
+                pipeline: None,
+                categories: vec!(CompositingCategory),
+            }
+        ));
+        assert_eq!(keys.get(0).format(), "| | + LayoutParallelWarmupCategory".to_string());
+        assert_eq!(keys.get(2).format(), "LayoutPerformCategory".to_string());
+    }
+
+    #[test]
+    fn test_json_output() {
+        let (_, port) = channel();
+        let mut profiler = TimeProfiler::new(port, TextOutput);
+        let key = TimerKey {
+            pipeline: Some(1),
+            categories: vec!(LayoutPerformCategory, LayoutMainCategory),
+        };
+        for &t in [1.0f64, 2.0, 6.0].iter() {
+            profiler.handle_msg(TimeMsg(key.clone(), t));
+        }
+        profiler.handle_msg(TimeMsg(TimerKey {
+            pipeline: None,
+            categories: vec!(CompositingCategory),
+        }, 0.5));
+        assert_eq!(profiler.buckets_to_json(), "[\
+            {\"pipeline\":null,\"categories\":[\"CompositingCategory\"],\
+             \"mean\":0.5,\"median\":0.5,\"min\":0.5,\"max\":0.5,\"count\":1},\
+            {\"pipeline\":1,\"categories\":[\"LayoutPerformCategory\",\"LayoutMainCategory\"],\
+             \"mean\":3,\"median\":2,\"min\":1,\"max\":6,\"count\":3}]".to_string());
+    }
+
+    #[test]
+    fn test_worker_stats_totals() {
+        let (_, port) = channel();
+        let mut profiler = TimeProfiler::new(port, TextOutput);
+        let stats = WorkerStats {
+            executed: 10,
+            stolen: 2,
+            backoffs: 1,
+        };
+        profiler.handle_msg(WorkerStatsMsg(1, vec!(stats.clone())));
+        profiler.handle_msg(WorkerStatsMsg(1, vec!(stats.clone(), stats.clone())));
+        profiler.handle_msg(WorkerStatsMsg(2, vec!(stats.clone())));
+
+        let totals = profiler.worker_stats.find(&1).unwrap();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(*totals.get(0), WorkerStats {
+            executed: 20,
+            stolen: 4,
+            backoffs: 2,
+        });
+        assert_eq!(*totals.get(1), stats);
+        assert_eq!(profiler.worker_stats.find(&2).unwrap().len(), 1);
+    }
 }
@@ -19,6 +19,7 @@ extern crate azure;
 extern crate collections;
 extern crate geom;
 extern crate getopts;
+extern crate libc;
 extern crate native;
 extern crate rand;
 extern crate rustrt;
@@ -27,18 +28,31 @@ extern crate sync;
 extern crate std_time = "time";
 extern crate std_url = "url";
 
+pub mod atom;
+pub mod bloom;
 pub mod cache;
+pub mod cowarc;
 pub mod debug_utils;
 pub mod geometry;
 pub mod memory;
 pub mod namespace;
 pub mod opts;
+pub mod persistent_list;
 pub mod range;
+pub mod smallstring;
 pub mod smallvec;
 pub mod sort;
 pub mod str;
 pub mod task;
+pub mod taskpool;
 pub mod time;
 pub mod url;
 pub mod vec;
 pub mod workqueue;
+
+// The exported small vector macros name their support code as `::servo_util::smallvec`, which is
+// how other crates see this one. This makes the same paths work inside this crate.
+#[doc(hidden)]
+mod servo_util {
+    pub use smallvec;
+}
@@ -7,8 +7,11 @@
 //! Data associated with queues is simply a pair of unsigned integers. It is expected that a
 //! higher-level API on top of this could allow safe fork-join parallelism.
 
+use libc::c_uint;
+use libc::funcs::posix88::unistd::usleep;
 use native;
 use rand::{Rng, XorShiftRng};
+use std::cmp;
 use std::mem;
 use std::rand::weak_rng;
 use std::sync::atomics::{AtomicUint, SeqCst};
@@ -28,10 +31,42 @@ pub struct WorkUnit<QueueData, WorkData> {
     pub data: WorkData,
 }
 
+/// Counts of what a worker did, for profiling parallel work.
+#[deriving(Clone, PartialEq, Show)]
+pub struct WorkerStats {
+    /// The number of work units the worker ran.
+    pub executed: uint,
+    /// How many of those it stole from other workers.
+    pub stolen: uint,
+    /// The number of times it slept because there was nothing to steal.
+    pub backoffs: uint,
+}
+
+impl WorkerStats {
+    pub fn new() -> WorkerStats {
+        WorkerStats {
+            executed: 0,
+            stolen: 0,
+            backoffs: 0,
+        }
+    }
+
+    /// Adds what a worker did in another period to these counts.
+    pub fn add(&mut self, other: &WorkerStats) {
+        self.executed += other.executed;
+        self.stolen += other.stolen;
+        self.backoffs += other.backoffs;
+    }
+}
+
 /// Messages from the supervisor to the worker.
 enum WorkerMsg<QueueData, WorkData> {
-    /// Tells the worker to start work.
-    StartMsg(Worker<WorkUnit<QueueData, WorkData>>, *mut AtomicUint, *QueueData),
+    /// Tells the worker to start work, stealing from the other workers' deques when its own is
+    /// empty.
+    StartMsg(Worker<WorkUnit<QueueData, WorkData>>,
+             Vec<Stealer<WorkUnit<QueueData, WorkData>>>,
+             *mut AtomicUint,
+             *QueueData),
     /// Tells the worker to stop. It can be restarted again with a `StartMsg`.
     StopMsg,
     /// Tells the worker thread to terminate.
@@ -41,7 +76,7 @@ enum WorkerMsg<QueueData, WorkData> {
 /// Messages to the supervisor.
 enum SupervisorMsg<QueueData, WorkData> {
     FinishedMsg,
-    ReturnDequeMsg(uint, Worker<WorkUnit<QueueData, WorkData>>),
+    ReturnDequeMsg(uint, Worker<WorkUnit<QueueData, WorkData>>, WorkerStats),
 }
 
 /// Information that the supervisor thread keeps about the worker threads.
@@ -64,27 +99,34 @@ struct WorkerThread<QueueData, WorkData> {
     port: Receiver<WorkerMsg<QueueData, WorkData>>,
     /// The communication channel on which messages are sent to the supervisor.
     chan: Sender<SupervisorMsg<QueueData, WorkData>>,
-    /// The thief end of the work-stealing deque for all other workers.
-    other_deques: Vec<Stealer<WorkUnit<QueueData, WorkData>>>,
     /// The random number generator for this worker.
     rng: XorShiftRng,
 }
 
 static SPIN_COUNT: uint = 1000;
 
+/// How long an idle worker sleeps the first time it backs off, in microseconds. Each time it
+/// backs off again without finding work, it sleeps twice as long, up to `MAX_BACKOFF_IN_US`. A
+/// sleeping worker only notices that a run is over when it wakes up, so the cap is kept small.
+static MIN_BACKOFF_IN_US: uint = 5;
+static MAX_BACKOFF_IN_US: uint = 200;
+
 impl<QueueData: Send, WorkData: Send> WorkerThread<QueueData, WorkData> {
     /// The main logic. This function starts up the worker and listens for
     /// messages.
     fn start(&mut self) {
         loop {
             // Wait for a start message.
-            let (mut deque, ref_count, queue_data) = match self.port.recv() {
-                StartMsg(deque, ref_count, queue_data) => (deque, ref_count, queue_data),
+            let (mut deque, mut other_deques, ref_count, queue_data) = match self.port.recv() {
+                StartMsg(deque, other_deques, ref_count, queue_data) => {
+                    (deque, other_deques, ref_count, queue_data)
+                }
                 StopMsg => fail!("unexpected stop message"),
                 ExitMsg => return,
             };
 
             // We're off!
+            let mut stats = WorkerStats::new();
             //
             // FIXME(pcwalton): Can't use labeled break or continue cross-crate due to a Rust bug.
             loop {
@@ -98,16 +140,21 @@ impl<QueueData: Send, WorkData: Send> WorkerThread<QueueData, WorkData> {
                     None => {
                         // Become a thief.
                         let mut i = 0;
+                        let mut backoff_in_us = 0;
                         let mut should_continue = true;
                         loop {
-                            let victim = (self.rng.next_u32() as uint) % self.other_deques.len();
-                            match self.other_deques.get_mut(victim).steal() {
-                                Empty | Abort => {
-                                    // Continue.
-                                }
-                                Data(work) => {
-                                    work_unit = work;
-                                    break
+                            // A lone worker has nobody to steal from.
+                            if other_deques.len() > 0 {
+                                let victim = (self.rng.next_u32() as uint) % other_deques.len();
+                                match other_deques.get_mut(victim).steal() {
+                                    Empty | Abort => {
+                                        // Continue.
+                                    }
+                                    Data(work) => {
+                                        work_unit = work;
+                                        stats.stolen += 1;
+                                        break
+                                    }
                                 }
                             }
 
@@ -122,6 +169,16 @@ impl<QueueData: Send, WorkData: Send> WorkerThread<QueueData, WorkData> {
                                     _ => {}
                                 }
 
+                                // There has been nothing to steal for a while, so sleep instead
+                                // of spinning, for longer each time.
+                                backoff_in_us = cmp::min(cmp::max(backoff_in_us * 2,
+                                                                  MIN_BACKOFF_IN_US),
+                                                         MAX_BACKOFF_IN_US);
+                                unsafe {
+                                    usleep(backoff_in_us as c_uint);
+                                }
+                                stats.backoffs += 1;
+
                                 i = 0
                             } else {
                                 i += 1
@@ -141,6 +198,7 @@ impl<QueueData: Send, WorkData: Send> WorkerThread<QueueData, WorkData> {
                     queue_data: queue_data,
                 };
                 (work_unit.fun)(work_unit.data, &mut proxy);
+                stats.executed += 1;
 
                 // The work is done. Now decrement the count of outstanding work items. If this was
                 // the last work unit in the queue, then send a message on the channel.
@@ -152,7 +210,7 @@ impl<QueueData: Send, WorkData: Send> WorkerThread<QueueData, WorkData> {
             }
 
             // Give the deque back to the supervisor.
-            self.chan.send(ReturnDequeMsg(self.index, deque))
+            self.chan.send(ReturnDequeMsg(self.index, deque, stats))
         }
     }
 }
@@ -187,8 +245,14 @@ impl<'a, QueueData, WorkData: Send> WorkerProxy<'a, QueueData, WorkData> {
 pub struct WorkQueue<QueueData, WorkData> {
     /// Information about each of the workers.
     workers: Vec<WorkerInfo<QueueData, WorkData>>,
+    /// What each worker has done since the stats were last reset.
+    stats: Vec<WorkerStats>,
     /// A port on which deques can be received from the workers.
     port: Receiver<SupervisorMsg<QueueData, WorkData>>,
+    /// The channel that new workers send to `port` on.
+    supervisor_chan: Sender<SupervisorMsg<QueueData, WorkData>>,
+    /// The name given to the worker threads.
+    task_name: &'static str,
     /// The amount of work that has been enqueued.
     work_count: uint,
     /// Arbitrary user data.
@@ -199,53 +263,74 @@ impl<QueueData: Send, WorkData: Send> WorkQueue<QueueData, WorkData> {
     /// Creates a new work queue and spawns all the threads associated with
     /// it.
     pub fn new(task_name: &'static str, thread_count: uint, user_data: QueueData) -> WorkQueue<QueueData, WorkData> {
-        // Set up data structures.
         let (supervisor_chan, supervisor_port) = channel();
-        let (mut infos, mut threads) = (vec!(), vec!());
-        for i in range(0, thread_count) {
-            let (worker_chan, worker_port) = channel();
-            let pool = BufferPool::new();
-            let (worker, thief) = pool.deque();
-            infos.push(WorkerInfo {
-                chan: worker_chan,
-                pool: pool,
-                deque: Some(worker),
-                thief: thief,
-            });
-            threads.push(WorkerThread {
-                index: i,
-                port: worker_port,
-                chan: supervisor_chan.clone(),
-                other_deques: vec!(),
-                rng: weak_rng(),
-            });
-        }
+        let mut queue = WorkQueue {
+            workers: vec!(),
+            stats: vec!(),
+            port: supervisor_port,
+            supervisor_chan: supervisor_chan,
+            task_name: task_name,
+            work_count: 0,
+            data: user_data,
+        };
+        queue.set_thread_count(thread_count);
+        queue
+    }
 
-        // Connect workers to one another.
-        for i in range(0, thread_count) {
-            for j in range(0, thread_count) {
-                if i != j {
-                    threads.get_mut(i).other_deques.push(infos.get(j).thief.clone())
-                }
-            }
-            assert!(threads.get(i).other_deques.len() == thread_count - 1)
-        }
+    /// The number of worker threads.
+    pub fn thread_count(&self) -> uint {
+        self.workers.len()
+    }
 
-        // Spawn threads.
-        for thread in threads.move_iter() {
-            let mut opts = TaskOpts::new();
-            opts.name = Some(task_name.into_maybe_owned());
-            native::task::spawn_opts(opts, proc() {
-                let mut thread = thread;
-                thread.start()
-            })
+    /// Spawns or shuts down worker threads so that there are `thread_count` of them. Workers
+    /// only sit idle between runs, so this never interrupts work.
+    pub fn set_thread_count(&mut self, thread_count: uint) {
+        assert!(thread_count > 0, "a work queue needs at least one worker");
+        while self.workers.len() < thread_count {
+            self.spawn_worker()
+        }
+        while self.workers.len() > thread_count {
+            // Work is only ever pushed onto the first worker's deque, so the others are empty.
+            let worker = self.workers.pop().unwrap();
+            worker.chan.send(ExitMsg);
+            self.stats.pop();
         }
+    }
 
-        WorkQueue {
-            workers: infos,
-            port: supervisor_port,
-            work_count: 0,
-            data: user_data,
+    fn spawn_worker(&mut self) {
+        let (worker_chan, worker_port) = channel();
+        let pool = BufferPool::new();
+        let (worker, thief) = pool.deque();
+        self.workers.push(WorkerInfo {
+            chan: worker_chan,
+            pool: pool,
+            deque: Some(worker),
+            thief: thief,
+        });
+        self.stats.push(WorkerStats::new());
+
+        let thread = WorkerThread {
+            index: self.workers.len() - 1,
+            port: worker_port,
+            chan: self.supervisor_chan.clone(),
+            rng: weak_rng(),
+        };
+        let mut opts = TaskOpts::new();
+        opts.name = Some(self.task_name.into_maybe_owned());
+        native::task::spawn_opts(opts, proc() {
+            let mut thread = thread;
+            thread.start()
+        })
+    }
+
+    /// What each worker has done since the queue was created or the stats were last reset.
+    pub fn worker_stats<'a>(&'a self) -> &'a [WorkerStats] {
+        self.stats.as_slice()
+    }
+
+    pub fn reset_worker_stats(&mut self) {
+        for stats in self.stats.mut_iter() {
+            *stats = WorkerStats::new()
         }
     }
 
@@ -265,8 +350,18 @@ impl<QueueData: Send, WorkData: Send> WorkQueue<QueueData, WorkData> {
     pub fn run(&mut self) {
         // Tell the workers to start.
         let mut work_count = AtomicUint::new(self.work_count);
-        for worker in self.workers.mut_iter() {
-            worker.chan.send(StartMsg(worker.deque.take_unwrap(), &mut work_count, &self.data))
+        let thieves: Vec<Stealer<WorkUnit<QueueData, WorkData>>> =
+            self.workers.iter().map(|worker| worker.thief.clone()).collect();
+        for (i, worker) in self.workers.mut_iter().enumerate() {
+            let other_deques = thieves.iter()
+                                      .enumerate()
+                                      .filter(|&(j, _)| j != i)
+                                      .map(|(_, thief)| thief.clone())
+                                      .collect();
+            worker.chan.send(StartMsg(worker.deque.take_unwrap(),
+                                      other_deques,
+                                      &mut work_count,
+                                      &self.data))
         }
 
         // Wait for the work to finish.
@@ -281,7 +376,10 @@ impl<QueueData: Send, WorkData: Send> WorkQueue<QueueData, WorkData> {
         // Get our deques back.
         for _ in range(0, self.workers.len()) {
             match self.port.recv() {
-                ReturnDequeMsg(index, deque) => self.workers.get_mut(index).deque = Some(deque),
+                ReturnDequeMsg(index, deque, stats) => {
+                    self.workers.get_mut(index).deque = Some(deque);
+                    self.stats.get_mut(index).add(&stats);
+                }
                 FinishedMsg => fail!("unexpected finished message!"),
             }
         }
@@ -294,3 +392,62 @@ impl<QueueData: Send, WorkData: Send> WorkQueue<QueueData, WorkData> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use workqueue::{WorkQueue, WorkUnit, WorkerProxy, WorkerStats};
+
+    use std::sync::atomics::{AtomicUint, SeqCst};
+
+    /// Counts a node of a complete binary tree `depth` levels deep, then visits its children.
+    fn visit_tree(depth: uint, proxy: &mut WorkerProxy<AtomicUint, uint>) {
+        proxy.user_data().fetch_add(1, SeqCst);
+        if depth > 0 {
+            for _ in range(0u, 2) {
+                proxy.push(WorkUnit {
+                    fun: visit_tree,
+                    data: depth - 1,
+                })
+            }
+        }
+    }
+
+    /// Returns the number of nodes visited.
+    fn run_tree(queue: &mut WorkQueue<AtomicUint, uint>, depth: uint) -> uint {
+        queue.data.store(0, SeqCst);
+        queue.push(WorkUnit {
+            fun: visit_tree,
+            data: depth,
+        });
+        queue.run();
+        queue.data.load(SeqCst)
+    }
+
+    #[test]
+    fn test_run() {
+        let mut queue = WorkQueue::new("TestWorker", 4, AtomicUint::new(0));
+        assert_eq!(run_tree(&mut queue, 10), 2047);
+        assert_eq!(queue.worker_stats().len(), 4);
+        let executed = queue.worker_stats().iter().fold(0, |n, stats| n + stats.executed);
+        assert_eq!(executed, 2047);
+        queue.shutdown();
+    }
+
+    #[test]
+    fn test_set_thread_count() {
+        let mut queue = WorkQueue::new("TestWorker", 2, AtomicUint::new(0));
+        assert_eq!(run_tree(&mut queue, 6), 127);
+
+        queue.set_thread_count(5);
+        assert_eq!(queue.thread_count(), 5);
+        assert_eq!(run_tree(&mut queue, 6), 127);
+
+        queue.set_thread_count(1);
+        assert_eq!(queue.thread_count(), 1);
+        assert_eq!(queue.worker_stats().len(), 1);
+        assert_eq!(run_tree(&mut queue, 6), 127);
+
+        queue.reset_worker_stats();
+        assert_eq!(queue.worker_stats()[0], WorkerStats::new());
+        queue.shutdown();
+    }
+}
@@ -90,14 +90,23 @@ fn make_test(file: String) -> TestDescAndFn {
 }
 
 fn run_test(file: String) {
+    // A click would set off other tests' click handlers, so only the test that waits for one
+    // gets it.
+    let click = file.as_slice().ends_with("test_click_from_harness.html");
     let path = os::make_absolute(&Path::new(file));
     // FIXME (#1094): not the right way to transform a path
     let infile = "file://".to_string().append(path.display().to_str().as_slice());
     let stdout = CreatePipe(false, true);
     let stderr = InheritFd(2);
 
+    // test_custom_event_from_harness.html waits for this event; other tests ignore it.
+    let mut args = vec!("-z", "-f", "--custom-event", "servo-harness={\"from\": \"contenttest\"}");
+    if click {
+        args.push_all(["--click", "30,40"]);
+    }
+    args.push(infile.as_slice());
     let mut prc = match Command::new("./servo")
-        .args(["-z", "-f", infile.as_slice()])
+        .args(args.as_slice())
         .stdin(Ignored)
         .stdout(stdout)
         .stderr(stderr)